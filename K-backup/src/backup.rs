@@ -0,0 +1,109 @@
+use crate::database::{DbPool, get_network, get_schema_version, table_exists};
+use crate::dump_file::DumpWriter;
+use crate::manifest::{
+    BACKUP_FORMAT_VERSION, Compression, K_TABLES, MANIFEST_FILE, Manifest, TableEntry,
+};
+use anyhow::Result;
+use futures::StreamExt;
+use sqlx::PgConnection;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Dump every K table from a single REPEATABLE READ snapshot and write the manifest last,
+/// so a directory with a manifest always holds a complete, consistent backup
+pub async fn execute(pool: &DbPool, output_dir: &Path, compression: Compression) -> Result<()> {
+    if output_dir.join(MANIFEST_FILE).exists() {
+        return Err(anyhow::anyhow!(
+            "{} already contains a backup",
+            output_dir.display()
+        ));
+    }
+    tokio::fs::create_dir_all(output_dir).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let schema_version = get_schema_version(&mut tx).await?;
+    let network = get_network(&mut tx).await?;
+    info!(
+        "Backing up K schema v{} (network: {})",
+        schema_version,
+        network.as_deref().unwrap_or("unknown")
+    );
+
+    let mut tables = Vec::with_capacity(K_TABLES.len());
+    for table in K_TABLES {
+        if !table_exists(&mut tx, table).await? {
+            warn!(
+                "Table {} not present in schema v{}, skipping",
+                table, schema_version
+            );
+            continue;
+        }
+
+        let file = compression.file_name(table);
+        let start = std::time::Instant::now();
+        let bytes = dump_table(&mut tx, table, &output_dir.join(&file), compression).await?;
+        info!(
+            "Dumped {} to {} ({} bytes of COPY data) in {:.2}s",
+            table,
+            file,
+            bytes,
+            start.elapsed().as_secs_f64()
+        );
+
+        tables.push(TableEntry {
+            table: table.to_string(),
+            file,
+            bytes,
+        });
+    }
+
+    // Read-only snapshot: nothing to commit
+    tx.rollback().await?;
+
+    let manifest = Manifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version,
+        network,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64,
+        compression,
+        tables,
+    };
+    manifest.write(output_dir).await?;
+
+    info!(
+        "Backup completed: {} tables written to {}",
+        manifest.tables.len(),
+        output_dir.display()
+    );
+    Ok(())
+}
+
+/// Stream one table as PostgreSQL binary COPY data into its dump file
+async fn dump_table(
+    conn: &mut PgConnection,
+    table: &str,
+    path: &Path,
+    compression: Compression,
+) -> Result<u64> {
+    let mut writer = DumpWriter::create(path, compression).await?;
+    let mut bytes = 0u64;
+
+    let mut stream = conn
+        .copy_out_raw(&format!("COPY {} TO STDOUT (FORMAT binary)", table))
+        .await?;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        bytes += chunk.len() as u64;
+        writer.write(&chunk).await?;
+    }
+    drop(stream);
+
+    writer.finish().await?;
+    Ok(bytes)
+}
@@ -0,0 +1,178 @@
+use clap::{Parser, Subcommand};
+use k_common::ConfigErrors;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "K-backup: consistent export and restore of K tables", long_about = None)]
+pub struct Args {
+    #[arg(
+        short = 'H',
+        long,
+        global = true,
+        default_value = "localhost",
+        help = "Database host"
+    )]
+    pub db_host: String,
+
+    #[arg(
+        short = 'P',
+        long,
+        global = true,
+        default_value = "5432",
+        help = "Database port"
+    )]
+    pub db_port: u16,
+
+    #[arg(
+        short = 'd',
+        long,
+        global = true,
+        default_value = "kaspa",
+        help = "Database name"
+    )]
+    pub db_name: String,
+
+    #[arg(
+        short = 'U',
+        long,
+        global = true,
+        default_value = "postgres",
+        help = "Database username"
+    )]
+    pub db_user: String,
+
+    #[arg(
+        short = 'p',
+        long,
+        global = true,
+        default_value = "postgres",
+        help = "Database password"
+    )]
+    pub db_password: String,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    pub wait_for_db: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Wait for K-transaction-processor to create the K schema instead of failing"
+    )]
+    pub wait_for_schema: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export all K tables from one consistent snapshot into a backup directory
+    Backup {
+        #[arg(
+            short = 'o',
+            long,
+            help = "Backup directory (created if missing, must not contain a backup)"
+        )]
+        output: PathBuf,
+
+        #[arg(
+            short = 'c',
+            long,
+            default_value = "gzip",
+            value_parser = ["gzip", "none"],
+            help = "Dump file compression (gzip requires the gzip binary in PATH)"
+        )]
+        compression: String,
+    },
+    /// Restore a backup directory into a database with the same K schema version
+    Restore {
+        #[arg(short = 'i', long, help = "Backup directory containing manifest.json")]
+        input: PathBuf,
+
+        #[arg(long, help = "Replace K data already present in the target database")]
+        force: bool,
+    },
+    /// Export everything stored about a pubkey into a machine-readable .tar.gz report
+    SubjectReport {
+        #[arg(long, help = "Public key of the subject (66 hex characters)")]
+        pubkey: String,
+
+        #[arg(
+            short = 'o',
+            long,
+            help = "Report archive to create, e.g. subject-report.tar.gz (requires the tar binary in PATH)"
+        )]
+        output: PathBuf,
+    },
+    /// Check database connectivity, extensions, K tables, indexes, schema version, notification
+    /// channel, gzip/tar and disk space, print a report and exit (non-zero exit code if a check
+    /// fails)
+    Doctor {
+        #[arg(
+            short = 'o',
+            long,
+            help = "Backup directory whose free disk space is checked as well"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+pub struct AppConfig {
+    pub database: DatabaseConfig,
+}
+
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub user: String,
+    pub password: String,
+    /// Startup wait for PostgreSQL to become reachable (None = wait indefinitely)
+    pub wait_for_db_secs: Option<u64>,
+}
+
+impl AppConfig {
+    pub fn from_args(args: &Args) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        if let Command::SubjectReport { pubkey, .. } = &args.command {
+            let pubkey = pubkey.to_lowercase();
+            if pubkey.len() != 66
+                || !pubkey.chars().all(|c| c.is_ascii_hexdigit())
+                || !(pubkey.starts_with("02") || pubkey.starts_with("03"))
+            {
+                errors.push(format!(
+                    "Invalid --pubkey '{}'. Expected 66 hex characters starting with 02 or 03",
+                    pubkey
+                ));
+            }
+        }
+        errors.into_result()?;
+
+        Ok(Self {
+            database: DatabaseConfig {
+                host: args.db_host.clone(),
+                port: args.db_port,
+                database: args.db_name.clone(),
+                user: args.db_user.clone(),
+                password: args.db_password.clone(),
+                wait_for_db_secs: args.wait_for_db,
+            },
+        })
+    }
+
+    pub fn connection_string(&self) -> String {
+        format!(
+            "postgres://{}:{}@{}:{}/{}",
+            self.database.user,
+            self.database.password,
+            self.database.host,
+            self.database.port,
+            self.database.database
+        )
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool, Row};
+
+pub type DbPool = PgPool;
+
+/// Read the K schema version written by K-transaction-processor in k_vars
+pub async fn get_schema_version(conn: &mut PgConnection) -> Result<i32> {
+    let version: Option<String> =
+        sqlx::query_scalar("SELECT value FROM k_vars WHERE key = 'schema_version'")
+            .fetch_optional(&mut *conn)
+            .await?;
+
+    version
+        .ok_or_else(|| anyhow::anyhow!("No K schema found (k_vars.schema_version is missing)"))?
+        .parse::<i32>()
+        .map_err(|e| anyhow::anyhow!("Invalid schema version in k_vars: {}", e))
+}
+
+pub async fn get_network(conn: &mut PgConnection) -> Result<Option<String>> {
+    let network = sqlx::query_scalar("SELECT value FROM k_vars WHERE key = 'network'")
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(network)
+}
+
+pub async fn table_exists(conn: &mut PgConnection, table: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT to_regclass($1) IS NOT NULL AS table_exists")
+        .bind(table)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(row.get::<bool, _>("table_exists"))
+}
@@ -0,0 +1,151 @@
+use crate::config::AppConfig;
+use crate::manifest::K_TABLES;
+use anyhow::Result;
+use k_common::doctor::{
+    Report, Status, check_database_disk, check_disk, check_extensions, check_notification_channel,
+    connect,
+};
+use sqlx::PgPool;
+use std::path::Path;
+
+/// External binaries: gzip for --compression gzip dump files, tar for subject reports
+const TOOLS: &[(&str, &str)] = &[
+    ("gzip", "backup/restore --compression gzip"),
+    ("tar", "subject-report"),
+];
+
+/// Check database connectivity, extensions, K tables, indexes, schema version, notification
+/// channel, external tools and disk space (of `output` too), printing one line per check
+pub async fn run(config: &AppConfig, output: Option<&Path>) -> Result<()> {
+    let mut report = Report::new();
+    println!("K-backup v{} doctor", env!("CARGO_PKG_VERSION"));
+
+    let database = &config.database;
+    let Some(pool) = connect(
+        &mut report,
+        "Database",
+        &config.connection_string(),
+        &format!("{}:{}/{}", database.host, database.port, database.database),
+        1,
+    )
+    .await
+    else {
+        return report.finish();
+    };
+
+    check_extensions(&mut report, &pool).await;
+    check_schema(&mut report, &pool).await;
+    check_indexes(&mut report, &pool).await;
+    check_notification_channel(&mut report, &pool).await;
+    check_tools(&mut report);
+    check_database_disk(&mut report, &pool, &config.database.host).await;
+    if let Some(output) = output {
+        check_output_disk(&mut report, output);
+    }
+
+    pool.close().await;
+    report.finish()
+}
+
+/// Schema version created by K-transaction-processor (as checked at startup) and the K tables
+/// a backup exports (tables of newer schemas are skipped)
+async fn check_schema(report: &mut Report, pool: &PgPool) {
+    let version =
+        sqlx::query_scalar::<_, String>("SELECT value FROM k_vars WHERE key = 'schema_version'")
+            .fetch_optional(pool)
+            .await;
+    let version = match version {
+        Ok(Some(version)) => {
+            report.check(Status::Ok, "Schema version", format!("v{}", version));
+            version
+        }
+        Ok(None) | Err(_) => {
+            report.check(
+                Status::Fail,
+                "Schema version",
+                "no K schema found (start K-transaction-processor first)",
+            );
+            return;
+        }
+    };
+
+    let present = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM unnest($1::text[]) AS t(name) WHERE to_regclass(t.name) IS NOT NULL",
+    )
+    .bind(K_TABLES)
+    .fetch_one(pool)
+    .await;
+    match present {
+        Ok(present) if present as usize == K_TABLES.len() => {
+            report.check(Status::Ok, "Tables", format!("{} K tables", present))
+        }
+        Ok(present) => report.check(
+            Status::Ok,
+            "Tables",
+            format!(
+                "{} of {} K tables (the others are not in schema v{} and are skipped)",
+                present,
+                K_TABLES.len(),
+                version
+            ),
+        ),
+        Err(e) => report.check(Status::Fail, "Tables", e.to_string()),
+    }
+}
+
+/// Backups do not need indexes, but a failed CREATE INDEX CONCURRENTLY leaves an invalid
+/// index that is backed up with the schema and never used
+async fn check_indexes(report: &mut Report, pool: &PgPool) {
+    let invalid = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT c.relname::text
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        WHERE c.relname LIKE 'idx_k_%' AND NOT i.indisvalid
+        "#,
+    )
+    .fetch_all(pool)
+    .await;
+
+    match invalid {
+        Ok(invalid) if invalid.is_empty() => {
+            report.check(Status::Ok, "Indexes", "no invalid K indexes")
+        }
+        Ok(invalid) => report.check(
+            Status::Warn,
+            "Indexes",
+            format!("invalid (rebuild them): {}", invalid.join(", ")),
+        ),
+        Err(e) => report.check(Status::Fail, "Indexes", e.to_string()),
+    }
+}
+
+fn check_tools(report: &mut Report) {
+    for (tool, needed_by) in TOOLS {
+        let found = std::process::Command::new(tool)
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if found {
+            report.check(Status::Ok, "Tools", format!("{} found", tool));
+        } else {
+            report.check(
+                Status::Warn,
+                "Tools",
+                format!("{} not found in PATH (needed by {})", tool, needed_by),
+            );
+        }
+    }
+}
+
+/// Free space where the backup will be written (its nearest existing parent when not created yet)
+fn check_output_disk(report: &mut Report, output: &Path) {
+    match output.ancestors().find(|path| path.exists()) {
+        Some(existing) => check_disk(report, "Output disk", &existing.to_string_lossy()),
+        None => report.check(
+            Status::Warn,
+            "Output disk",
+            format!("free space of {} unknown", output.display()),
+        ),
+    }
+}
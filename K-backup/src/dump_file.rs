@@ -0,0 +1,104 @@
+use crate::manifest::Compression;
+use anyhow::Result;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
+
+/// Destination of a table dump: plain file or stdin of a `gzip -c > file` process
+pub struct DumpWriter {
+    writer: Box<dyn AsyncWrite + Unpin + Send>,
+    gzip: Option<Child>,
+}
+
+impl DumpWriter {
+    pub async fn create(path: &Path, compression: Compression) -> Result<Self> {
+        match compression {
+            Compression::None => Ok(Self {
+                writer: Box::new(File::create(path).await?),
+                gzip: None,
+            }),
+            Compression::Gzip => {
+                let file = std::fs::File::create(path)?;
+                let mut child = Command::new("gzip")
+                    .arg("-c")
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::from(file))
+                    .spawn()
+                    .map_err(|e| anyhow::anyhow!("Failed to start gzip: {}", e))?;
+                let stdin = child
+                    .stdin
+                    .take()
+                    .ok_or_else(|| anyhow::anyhow!("gzip stdin not available"))?;
+                Ok(Self {
+                    writer: Box::new(stdin),
+                    gzip: Some(child),
+                })
+            }
+        }
+    }
+
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.writer.write_all(data).await?;
+        Ok(())
+    }
+
+    pub async fn finish(mut self) -> Result<()> {
+        self.writer.shutdown().await?;
+        // Closing stdin lets gzip flush and exit
+        drop(self.writer);
+        if let Some(mut child) = self.gzip.take() {
+            let status = child.wait().await?;
+            if !status.success() {
+                return Err(anyhow::anyhow!("gzip exited with {}", status));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Source of a table dump: plain file or stdout of a `gzip -dc file` process
+pub struct DumpReader {
+    pub reader: Box<dyn AsyncRead + Unpin + Send>,
+    gzip: Option<Child>,
+}
+
+impl DumpReader {
+    pub async fn open(path: &Path, compression: Compression) -> Result<Self> {
+        match compression {
+            Compression::None => Ok(Self {
+                reader: Box::new(File::open(path).await?),
+                gzip: None,
+            }),
+            Compression::Gzip => {
+                let mut child = Command::new("gzip")
+                    .arg("-dc")
+                    .arg(path)
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| anyhow::anyhow!("Failed to start gzip: {}", e))?;
+                let stdout = child
+                    .stdout
+                    .take()
+                    .ok_or_else(|| anyhow::anyhow!("gzip stdout not available"))?;
+                Ok(Self {
+                    reader: Box::new(stdout),
+                    gzip: Some(child),
+                })
+            }
+        }
+    }
+
+    /// Check that decompression completed successfully (a corrupt file must fail the restore)
+    pub async fn finish(mut self) -> Result<()> {
+        drop(self.reader);
+        if let Some(mut child) = self.gzip.take() {
+            let status = child.wait().await?;
+            if !status.success() {
+                return Err(anyhow::anyhow!("gzip exited with {}", status));
+            }
+        }
+        Ok(())
+    }
+}
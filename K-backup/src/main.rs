@@ -0,0 +1,77 @@
+mod backup;
+mod config;
+mod database;
+mod doctor;
+mod dump_file;
+mod manifest;
+mod restore;
+mod subject_report;
+
+use anyhow::Result;
+use clap::Parser;
+use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use config::{AppConfig, Args, Command};
+use k_common::failover::TargetSessionAttrs;
+use k_common::startup::{self, verify_k_schema};
+use manifest::Compression;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing with default INFO level
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    info!("Starting K-backup v{}", env!("CARGO_PKG_VERSION"));
+
+    // Parse CLI arguments
+    let args = Args::parse();
+
+    // Load configuration from CLI arguments
+    let config = match AppConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+    info!(
+        "Database connection: {}:{}/{}",
+        config.database.host, config.database.port, config.database.database
+    );
+
+    // Doctor reports an unreachable database instead of waiting for it
+    if let Command::Doctor { output } = &args.command {
+        return doctor::run(&config, output.as_deref()).await;
+    }
+
+    // Backup and restore each run on a single connection (one snapshot / one transaction)
+    let db_pool = startup::create_pool(
+        &config.connection_string(),
+        &[],
+        TargetSessionAttrs::Any,
+        1,
+        config.database.wait_for_db_secs,
+    )
+    .await?;
+    verify_k_schema(&db_pool, args.wait_for_schema).await?;
+
+    match &args.command {
+        Command::Backup {
+            output,
+            compression,
+        } => backup::execute(&db_pool, output, Compression::from_arg(compression)).await?,
+        Command::Restore { input, force } => restore::execute(&db_pool, input, *force).await?,
+        Command::SubjectReport { pubkey, output } => {
+            subject_report::execute(&db_pool, pubkey, output).await?
+        }
+        Command::Doctor { .. } => unreachable!("handled before connecting"),
+    }
+
+    Ok(())
+}
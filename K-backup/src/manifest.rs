@@ -0,0 +1,145 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version of the backup directory layout (bumped on incompatible manifest/dump changes)
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// K tables in restore order (referenced tables before the tables referencing them)
+pub const K_TABLES: &[&str] = &[
+    "k_vars",
+    "k_meta",
+    "k_broadcasts",
+    "k_profile_image_blobs",
+    "k_contents",
+    "k_votes",
+    "k_vote_totals",
+    "k_mentions",
+    "k_hashtags",
+    "k_blocks",
+    "k_follows",
+    "k_groups",
+    "k_group_members",
+    "k_group_posts",
+    "k_events",
+    "k_event_rsvps",
+    "k_articles",
+    "k_article_chunks",
+    "k_tips",
+    "k_post_views",
+    "k_feature_flags",
+    "k_featured",
+    "k_erasure_requests",
+    "k_tombstones",
+    "k_pubkey_tombstones",
+    "k_content_hashes",
+    "k_render_spans",
+    "k_storage_snapshots",
+    "k_contents_archive",
+    "k_mentions_archive",
+    "k_contents_cold",
+    "k_profile_history",
+    "k_impersonation_flags",
+    "k_dead_letters",
+    "k_write_intents",
+];
+
+/// Tables whose BIGSERIAL id sequence must be moved past the restored rows
+pub const SERIAL_TABLES: &[&str] = &[
+    "k_broadcasts",
+    "k_contents",
+    "k_votes",
+    "k_mentions",
+    "k_hashtags",
+    "k_blocks",
+    "k_follows",
+    "k_groups",
+    "k_group_members",
+    "k_group_posts",
+    "k_events",
+    "k_event_rsvps",
+    "k_articles",
+    "k_article_chunks",
+    "k_tips",
+    "k_erasure_requests",
+    "k_profile_history",
+    "k_impersonation_flags",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    None,
+}
+
+impl Compression {
+    pub fn from_arg(value: &str) -> Self {
+        match value {
+            "gzip" => Compression::Gzip,
+            _ => Compression::None,
+        }
+    }
+
+    pub fn file_name(&self, table: &str) -> String {
+        match self {
+            Compression::Gzip => format!("{}.copy.gz", table),
+            Compression::None => format!("{}.copy", table),
+        }
+    }
+}
+
+/// One dumped table: PostgreSQL binary COPY data, optionally gzip compressed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableEntry {
+    pub table: String,
+    pub file: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub schema_version: i32,
+    pub network: Option<String>,
+    pub created_at: i64,
+    pub compression: Compression,
+    pub tables: Vec<TableEntry>,
+}
+
+impl Manifest {
+    pub async fn read(directory: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(directory.join(MANIFEST_FILE)).await?;
+        let manifest: Manifest = serde_json::from_str(&content)?;
+
+        if manifest.format_version != BACKUP_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported backup format version {} (expected {})",
+                manifest.format_version,
+                BACKUP_FORMAT_VERSION
+            ));
+        }
+
+        // Table names are interpolated into COPY statements: only accept known K tables
+        if let Some(entry) = manifest
+            .tables
+            .iter()
+            .find(|entry| !K_TABLES.contains(&entry.table.as_str()))
+        {
+            return Err(anyhow::anyhow!(
+                "Unknown table '{}' in backup manifest",
+                entry.table
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    pub async fn write(&self, directory: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(directory.join(MANIFEST_FILE), content).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::database::{DbPool, get_schema_version, table_exists};
+use crate::dump_file::DumpReader;
+use crate::manifest::{Manifest, SERIAL_TABLES};
+use anyhow::Result;
+use sqlx::Row;
+use std::path::Path;
+use tracing::info;
+
+/// Tables written by the processor itself at startup: their rows don't count as existing K data
+const SYSTEM_TABLES: &[&str] = &["k_vars", "k_meta"];
+
+/// Replace the K tables of the target database with a backup in one transaction
+/// The target schema must already exist (created by K-transaction-processor) with the same version
+pub async fn execute(pool: &DbPool, input_dir: &Path, force: bool) -> Result<()> {
+    let manifest = Manifest::read(input_dir).await?;
+    info!(
+        "Restoring backup of K schema v{} (network: {}, {} tables)",
+        manifest.schema_version,
+        manifest.network.as_deref().unwrap_or("unknown"),
+        manifest.tables.len()
+    );
+
+    let mut tx = pool.begin().await?;
+
+    let schema_version = get_schema_version(&mut tx).await?;
+    if schema_version != manifest.schema_version {
+        return Err(anyhow::anyhow!(
+            "Schema version mismatch: backup is v{}, target database is v{}. Run 'K-transaction-processor migrate' (or the matching version) first",
+            manifest.schema_version,
+            schema_version
+        ));
+    }
+
+    for entry in &manifest.tables {
+        if !table_exists(&mut tx, &entry.table).await? {
+            return Err(anyhow::anyhow!(
+                "Table {} missing in target database",
+                entry.table
+            ));
+        }
+
+        if !force && !SYSTEM_TABLES.contains(&entry.table.as_str()) {
+            let has_rows = sqlx::query(&format!(
+                "SELECT EXISTS(SELECT 1 FROM {}) AS has_rows",
+                entry.table
+            ))
+            .fetch_one(&mut *tx)
+            .await?
+            .get::<bool, _>("has_rows");
+
+            if has_rows {
+                return Err(anyhow::anyhow!(
+                    "Table {} already contains data. Use --force to replace the K data of the target database",
+                    entry.table
+                ));
+            }
+        }
+    }
+
+    let table_list = manifest
+        .tables
+        .iter()
+        .map(|entry| entry.table.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    sqlx::query(&format!("TRUNCATE {}", table_list))
+        .execute(&mut *tx)
+        .await?;
+
+    for entry in &manifest.tables {
+        let start = std::time::Instant::now();
+        let mut reader =
+            DumpReader::open(&input_dir.join(&entry.file), manifest.compression).await?;
+
+        let mut copy = tx
+            .copy_in_raw(&format!("COPY {} FROM STDIN (FORMAT binary)", entry.table))
+            .await?;
+        copy.read_from(&mut reader.reader).await?;
+        let rows = copy.finish().await?;
+        reader.finish().await?;
+
+        info!(
+            "Restored {} rows into {} in {:.2}s",
+            rows,
+            entry.table,
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    // Move id sequences past the restored rows so new inserts don't collide
+    for table in SERIAL_TABLES {
+        if manifest.tables.iter().any(|entry| entry.table == *table) {
+            sqlx::query(&format!(
+                "SELECT setval(pg_get_serial_sequence('{table}', 'id'), COALESCE((SELECT MAX(id) FROM {table}), 0) + 1, false)"
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+    info!("Restore completed from {}", input_dir.display());
+    Ok(())
+}
@@ -0,0 +1,303 @@
+use crate::database::{DbPool, get_network, get_schema_version, table_exists};
+use crate::dump_file::DumpWriter;
+use crate::manifest::Compression;
+use anyhow::Result;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+pub const REPORT_FILE: &str = "report.json";
+
+/// Version of the subject report layout (bumped on incompatible report changes)
+pub const REPORT_FORMAT_VERSION: u32 = 1;
+
+/// One part of the report: the rows of a K table involving the subject ($1 = subject pubkey)
+struct ReportSection {
+    name: &'static str,
+    table: &'static str,
+    filter: &'static str,
+}
+
+/// Everything stored about a pubkey, in report order
+const REPORT_SECTIONS: &[ReportSection] = &[
+    ReportSection {
+        name: "profile_broadcasts",
+        table: "k_broadcasts",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "profile_history",
+        table: "k_profile_history",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "contents",
+        table: "k_contents",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "votes_cast",
+        table: "k_votes",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "votes_received",
+        table: "k_votes",
+        filter: "post_id IN (SELECT transaction_id FROM k_contents WHERE sender_pubkey = $1)",
+    },
+    ReportSection {
+        name: "mentions_sent",
+        table: "k_mentions",
+        filter: "sender_pubkey = $1",
+    },
+    // Notification rows: K-webserver builds /get-notifications from these
+    ReportSection {
+        name: "mentions_received",
+        table: "k_mentions",
+        filter: "mentioned_pubkey = $1",
+    },
+    ReportSection {
+        name: "hashtags",
+        table: "k_hashtags",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "blocks",
+        table: "k_blocks",
+        filter: "sender_pubkey = $1 OR blocked_user_pubkey = $1",
+    },
+    ReportSection {
+        name: "follows",
+        table: "k_follows",
+        filter: "sender_pubkey = $1 OR followed_user_pubkey = $1",
+    },
+    ReportSection {
+        name: "groups_created",
+        table: "k_groups",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "group_memberships",
+        table: "k_group_members",
+        filter: "member_pubkey = $1",
+    },
+    ReportSection {
+        name: "group_posts",
+        table: "k_group_posts",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "events",
+        table: "k_events",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "event_rsvps",
+        table: "k_event_rsvps",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "articles",
+        table: "k_articles",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "article_chunks",
+        table: "k_article_chunks",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "tips",
+        table: "k_tips",
+        filter: "sender_pubkey = $1 OR recipient_pubkey = $1",
+    },
+    ReportSection {
+        name: "content_hashes",
+        table: "k_content_hashes",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "archived_contents",
+        table: "k_contents_archive",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "archived_mentions",
+        table: "k_mentions_archive",
+        filter: "sender_pubkey = $1 OR mentioned_pubkey = $1",
+    },
+    ReportSection {
+        name: "cold_contents",
+        table: "k_contents_cold",
+        filter: "sender_pubkey = $1",
+    },
+    ReportSection {
+        name: "impersonation_flags",
+        table: "k_impersonation_flags",
+        filter: "suspect_pubkey = $1 OR target_pubkey = $1",
+    },
+    // Hashed viewer sketches of the subject's contents (no viewer identities are stored)
+    ReportSection {
+        name: "content_views",
+        table: "k_post_views",
+        filter: "content_id IN (SELECT transaction_id FROM k_contents WHERE sender_pubkey = $1)",
+    },
+];
+
+/// One written section: JSON Lines file with one row object per line
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SectionEntry {
+    pub name: String,
+    pub table: String,
+    pub file: String,
+    pub rows: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub format_version: u32,
+    pub subject_pubkey: String,
+    pub schema_version: i32,
+    pub network: Option<String>,
+    pub created_at: i64,
+    pub sections: Vec<SectionEntry>,
+}
+
+/// Write every row involving a pubkey from a single REPEATABLE READ snapshot into a
+/// .tar.gz archive: one JSON Lines file per section plus report.json describing them
+pub async fn execute(pool: &DbPool, pubkey: &str, output: &Path) -> Result<()> {
+    // Validated by AppConfig::from_args
+    let pubkey = pubkey.to_lowercase();
+    let pubkey_bytes: Vec<u8> = (0..pubkey.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&pubkey[i..i + 2], 16))
+        .collect::<Result<_, _>>()?;
+    if output.exists() {
+        return Err(anyhow::anyhow!("{} already exists", output.display()));
+    }
+
+    // Sections are staged next to the archive and removed once it is written
+    let staging = output.with_extension("partial");
+    tokio::fs::create_dir_all(&staging).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let schema_version = get_schema_version(&mut tx).await?;
+    let network = get_network(&mut tx).await?;
+    info!(
+        "Building subject report for {} from K schema v{}",
+        pubkey, schema_version
+    );
+
+    let mut sections = Vec::with_capacity(REPORT_SECTIONS.len());
+    for section in REPORT_SECTIONS {
+        if !table_exists(&mut tx, section.table).await? {
+            warn!(
+                "Table {} not present in schema v{}, skipping section {}",
+                section.table, schema_version, section.name
+            );
+            continue;
+        }
+
+        let file = format!("{}.jsonl", section.name);
+        let rows = write_section(&mut tx, section, &pubkey_bytes, &staging.join(&file)).await?;
+        info!("Wrote {} rows of {} to {}", rows, section.name, file);
+
+        sections.push(SectionEntry {
+            name: section.name.to_string(),
+            table: section.table.to_string(),
+            file,
+            rows,
+        });
+    }
+
+    // Read-only snapshot: nothing to commit
+    tx.rollback().await?;
+
+    let report = Report {
+        format_version: REPORT_FORMAT_VERSION,
+        subject_pubkey: pubkey,
+        schema_version,
+        network,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64,
+        sections,
+    };
+    tokio::fs::write(
+        staging.join(REPORT_FILE),
+        serde_json::to_string_pretty(&report)?,
+    )
+    .await?;
+
+    create_archive(&staging, output).await?;
+    tokio::fs::remove_dir_all(&staging).await?;
+
+    info!(
+        "Subject report completed: {} sections, {} rows written to {}",
+        report.sections.len(),
+        report
+            .sections
+            .iter()
+            .map(|section| section.rows)
+            .sum::<u64>(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Stream the rows of one section as JSON objects (bytea columns as "\x..." hex strings)
+async fn write_section(
+    conn: &mut PgConnection,
+    section: &ReportSection,
+    pubkey: &[u8],
+    path: &Path,
+) -> Result<u64> {
+    let mut writer = DumpWriter::create(path, Compression::None).await?;
+    let mut rows = 0u64;
+
+    // Table and filter come from REPORT_SECTIONS, only the pubkey is user input
+    let query = format!(
+        "SELECT row_to_json(t)::text FROM (SELECT * FROM {} WHERE {} ORDER BY 1) t",
+        section.table, section.filter
+    );
+    let mut stream = sqlx::query_scalar::<_, String>(&query)
+        .bind(pubkey)
+        .fetch(&mut *conn);
+    while let Some(row) = stream.next().await {
+        let mut line = row?;
+        line.push('\n');
+        writer.write(line.as_bytes()).await?;
+        rows += 1;
+    }
+    drop(stream);
+
+    writer.finish().await?;
+    Ok(rows)
+}
+
+/// Pack the staged report into a gzip compressed tarball (requires the tar binary in PATH)
+async fn create_archive(staging: &Path, output: &Path) -> Result<()> {
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(output)
+        .arg("-C")
+        .arg(staging)
+        .arg(".")
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start tar: {}", e))?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("tar exited with {}", status));
+    }
+    Ok(())
+}
@@ -0,0 +1,370 @@
+//! `cargo generate-types [OUT_DIR]`: TypeScript types and JSON Schemas of every API response
+//! model, written to OUT_DIR (default k-indexer-types)
+
+use k_client::models::*;
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, SchemaGenerator};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_OUT_DIR: &str = "k-indexer-types";
+const TYPESCRIPT_FILE: &str = "k-indexer-api.ts";
+const SCHEMAS_DIR: &str = "schemas";
+
+/// Rust type aliases of the models, kept as aliases in TypeScript
+const ALIASES: &[(&str, &str)] = &[("ServerReply", "ServerPost")];
+
+/// Response models collected in a single generator, so shared types are emitted once
+struct Models {
+    generator: SchemaGenerator,
+    // Self-contained schema of every response, by name
+    schemas: Vec<(String, Value)>,
+}
+
+impl Models {
+    fn new() -> Self {
+        Self {
+            generator: settings().into_generator(),
+            schemas: Vec::new(),
+        }
+    }
+
+    fn add<T: JsonSchema>(&mut self) {
+        self.generator.subschema_for::<T>();
+        let schema = settings().into_generator().into_root_schema_for::<T>();
+        self.schemas
+            .push((T::schema_name().into_owned(), schema.to_value()));
+    }
+}
+
+/// Schemas of what the webserver serializes (fields skipped when empty are optional)
+fn settings() -> SchemaSettings {
+    SchemaSettings::draft2020_12().for_serialize()
+}
+
+/// Every body returned by the API, nested models are reached through them
+fn response_models() -> Models {
+    let mut models = Models::new();
+    models.add::<ApiError>();
+    models.add::<CountResponse>();
+    models.add::<PostsResponse>();
+    models.add::<PaginatedPostsResponse>();
+    models.add::<PaginatedPostsLightResponse>();
+    models.add::<PostsMetaResponse>();
+    models.add::<PostsByIdsResponse>();
+    models.add::<PostDetailsResponse>();
+    models.add::<RepliesResponse>();
+    models.add::<PaginatedRepliesResponse>();
+    models.add::<ThreadResponse>();
+    models.add::<ServerUserPost>();
+    models.add::<ProfileResponse>();
+    models.add::<UserStatsResponse>();
+    models.add::<UsersResponse>();
+    models.add::<PaginatedUsersResponse>();
+    models.add::<PaginatedNotificationsResponse>();
+    models.add::<TrendingHashtagsResponse>();
+    models.add::<PaginatedGroupsResponse>();
+    models.add::<PaginatedGroupPostsResponse>();
+    models.add::<PaginatedEventsResponse>();
+    models.add::<ArticleDetailsResponse>();
+    models.add::<PostMentionsResponse>();
+    models.add::<PostTipsResponse>();
+    models.add::<TopTippedPostsResponse>();
+    models.add::<FeaturedPostsResponse>();
+    models.add::<FeatureFlagsResponse>();
+    models.add::<ProfileDiffResponse>();
+    models.add::<ErasureRequestResponse>();
+    models.add::<DuplicateContentsResponse>();
+    models.add::<StorageStatsResponse>();
+    models.add::<SlowQueriesResponse>();
+    models.add::<ImpersonationFlag>();
+    models.add::<ImpersonationFlagsResponse>();
+    models.add::<NicknameHistoryResponse>();
+    models.add::<FeaturedEntriesResponse>();
+    models.add::<FeaturedEntry>();
+    models.add::<PayloadSimulationResponse>();
+    // /ws live feed messages
+    models.add::<LiveEvent>();
+    // /v2 envelopes
+    models.add::<PaginatedEnvelope<ServerPost>>();
+    models.add::<PaginatedEnvelope<ServerPostLight>>();
+    models.add::<PaginatedEnvelope<ServerUserPost>>();
+    models.add::<PaginatedEnvelope<NotificationPost>>();
+    models.add::<PaginatedEnvelope<ServerGroup>>();
+    models.add::<PaginatedEnvelope<ServerGroupPost>>();
+    models.add::<PaginatedEnvelope<ServerEvent>>();
+    models
+}
+
+fn main() {
+    let out_dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_OUT_DIR));
+
+    if let Err(err) = generate(&out_dir) {
+        eprintln!("Failed to generate types in {}: {}", out_dir.display(), err);
+        std::process::exit(1);
+    }
+}
+
+fn generate(out_dir: &Path) -> std::io::Result<()> {
+    let mut models = response_models();
+    let definitions = models.generator.take_definitions(true);
+
+    let schemas_dir = out_dir.join(SCHEMAS_DIR);
+    fs::create_dir_all(&schemas_dir)?;
+    for (name, schema) in &models.schemas {
+        let json = serde_json::to_string_pretty(schema).map_err(std::io::Error::other)?;
+        fs::write(
+            schemas_dir.join(format!("{}.schema.json", name)),
+            json + "\n",
+        )?;
+    }
+    fs::write(out_dir.join(TYPESCRIPT_FILE), typescript(&definitions))?;
+
+    println!(
+        "Generated {} and {} JSON Schemas in {}",
+        TYPESCRIPT_FILE,
+        models.schemas.len(),
+        out_dir.display()
+    );
+    Ok(())
+}
+
+/// One TypeScript declaration per schema definition, sorted by name
+fn typescript(definitions: &Map<String, Value>) -> String {
+    let mut ts = format!(
+        "// Generated by `cargo generate-types` from the k-client {} models. Do not edit.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    for (name, schema) in definitions {
+        ts.push('\n');
+        ts.push_str(&doc_comment(schema, ""));
+        match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => {
+                let object = object_type(schema, properties, "");
+                ts.push_str(&format!("export interface {} {}\n", name, object));
+            }
+            None => ts.push_str(&format!("export type {} = {};\n", name, ts_type(schema))),
+        }
+    }
+
+    for (alias, target) in ALIASES {
+        if definitions.contains_key(*target) {
+            ts.push_str(&format!("\nexport type {} = {};\n", alias, target));
+        }
+    }
+    ts
+}
+
+/// `{ ... }` with one line per property, optional when the property is not required
+fn object_type(schema: &Value, properties: &Map<String, Value>, indent: &str) -> String {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut ts = String::from("{\n");
+    for (property, property_schema) in properties {
+        let inner_indent = format!("{}  ", indent);
+        ts.push_str(&doc_comment(property_schema, &inner_indent));
+        ts.push_str(&format!(
+            "{}{}{}: {};\n",
+            inner_indent,
+            property_name(property),
+            if required.contains(&property.as_str()) {
+                ""
+            } else {
+                "?"
+            },
+            ts_type(property_schema)
+        ));
+    }
+    ts.push_str(indent);
+    ts.push('}');
+    ts
+}
+
+/// TypeScript type of a schema (refs point to other definitions)
+fn ts_type(schema: &Value) -> String {
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything
+        return "unknown".to_string();
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(variants) = schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+    {
+        return union(variants.iter().map(ts_type));
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return union(values.iter().map(Value::to_string));
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+
+    match schema.get("type") {
+        Some(Value::String(name)) => primitive_type(name, schema),
+        Some(Value::Array(names)) => union(
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|name| primitive_type(name, schema)),
+        ),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn primitive_type(name: &str, schema: &Map<String, Value>) -> String {
+    match name {
+        "string" => "string".to_string(),
+        // u64 and i64 values above 2^53 lose precision in JavaScript numbers
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => {
+            let item = schema.get("items").map(ts_type).unwrap_or_default();
+            if item.contains(" | ") {
+                format!("({})[]", item)
+            } else if item.is_empty() {
+                "unknown[]".to_string()
+            } else {
+                format!("{}[]", item)
+            }
+        }
+        "object" => match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => object_type(&Value::Object(schema.clone()), properties, ""),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => "Record<string, never>".to_string(),
+                Some(values @ Value::Object(_)) => format!("Record<string, {}>", ts_type(values)),
+                _ => "Record<string, unknown>".to_string(),
+            },
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+    let mut members: Vec<String> = Vec::new();
+    for member in types {
+        if !members.contains(&member) {
+            members.push(member);
+        }
+    }
+    members.join(" | ")
+}
+
+fn property_name(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+/// JSDoc comment from the schema description (doc comments of the Rust model)
+fn doc_comment(schema: &Value, indent: &str) -> String {
+    let Some(description) = schema.get("description").and_then(Value::as_str) else {
+        return String::new();
+    };
+
+    let mut comment = format!("{}/**\n", indent);
+    for line in description.lines() {
+        let line = format!("{} * {}", indent, line.replace("*/", "*\\/"));
+        comment.push_str(line.trim_end());
+        comment.push('\n');
+    }
+    comment.push_str(&format!("{} */\n", indent));
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ts_type() {
+        assert_eq!(ts_type(&json!({"type": "string"})), "string");
+        assert_eq!(
+            ts_type(&json!({"type": ["integer", "null"]})),
+            "number | null"
+        );
+        assert_eq!(
+            ts_type(&json!({"type": "array", "items": {"$ref": "#/$defs/ServerPost"}})),
+            "ServerPost[]"
+        );
+        assert_eq!(
+            ts_type(&json!({"type": ["array", "null"], "items": {"type": ["string", "null"]}})),
+            "(string | null)[] | null"
+        );
+        assert_eq!(
+            ts_type(&json!({"anyOf": [{"$ref": "#/$defs/QuoteData"}, {"type": "null"}]})),
+            "QuoteData | null"
+        );
+        assert_eq!(
+            ts_type(&json!({"type": "object", "additionalProperties": {"type": "boolean"}})),
+            "Record<string, boolean>"
+        );
+        assert_eq!(
+            ts_type(&json!({"enum": ["post", "reply"]})),
+            "\"post\" | \"reply\""
+        );
+    }
+
+    #[test]
+    fn test_typescript_uses_serialized_field_names() {
+        let mut models = Models::new();
+        models.add::<PaginatedPostsResponse>();
+        let ts = typescript(&models.generator.take_definitions(true));
+
+        assert!(ts.contains("export interface ServerPost {\n"));
+        // Renamed by serde, optional when skipped while empty
+        assert!(ts.contains("  userPublicKey: string;\n"));
+        assert!(ts.contains("  viewCount?: number | null;\n"));
+        assert!(ts.contains("  quote?: QuoteData | null;\n"));
+        assert!(ts.contains("  nestedReplies?: ServerPost[] | null;\n"));
+        assert!(ts.contains("export interface PaginationMetadata {\n"));
+        assert!(ts.contains("  nextCursor: string | null;\n"));
+        assert!(ts.ends_with("export type ServerReply = ServerPost;\n"));
+    }
+
+    #[test]
+    fn test_every_response_model_has_a_schema() {
+        let models = response_models();
+        let names: Vec<&str> = models
+            .schemas
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert!(names.contains(&"PaginatedPostsResponse"));
+        assert!(names.contains(&"ServerPostEnvelope"));
+        for (_, schema) in &models.schemas {
+            assert_eq!(
+                schema["$schema"],
+                json!("https://json-schema.org/draft/2020-12/schema")
+            );
+        }
+    }
+}
@@ -0,0 +1,562 @@
+use crate::models::*;
+use crate::requests::*;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent or the response body could not be read or decoded
+    Http(reqwest::Error),
+    /// The webserver answered with an error status and an ApiError body
+    Api { status: u16, error: ApiError },
+    /// Error status without an ApiError body (e.g. returned by a reverse proxy)
+    Status(u16),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(err) => write!(f, "HTTP error: {}", err),
+            ClientError::Api { status, error } => {
+                write!(f, "API error {} ({}): {}", status, error.code, error.error)
+            }
+            ClientError::Status(status) => write!(f, "Unexpected status {}", status),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Http(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
+
+/// Typed client of a K-indexer webserver
+///
+/// Built on reqwest without runtime-specific code, so it runs natively (tokio) and in the
+/// browser (wasm32, futures driven by wasm-bindgen-futures).
+#[derive(Debug, Clone)]
+pub struct KClient {
+    http: reqwest::Client,
+    base_url: String,
+    admin_token: Option<String>,
+}
+
+impl KClient {
+    /// Client of the webserver at base_url (e.g. "https://indexer.example.org")
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Same as new, reusing a configured reqwest client (timeouts, proxies, headers)
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            admin_token: None,
+        }
+    }
+
+    /// Token sent as bearer authorization to the /admin endpoints
+    pub fn with_admin_token(mut self, admin_token: &str) -> Self {
+        self.admin_token = Some(admin_token.to_string());
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// GET /v1/get-posts
+    pub async fn get_posts(&self, query: &GetPostsQuery) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-posts", query).await
+    }
+
+    /// GET /v1/get-post-details
+    pub async fn get_post_details(
+        &self,
+        query: &GetPostDetailsQuery,
+    ) -> Result<PostDetailsResponse> {
+        self.get("/v1/get-post-details", query).await
+    }
+
+    /// GET /v1/get-posts-watching
+    pub async fn get_posts_watching(
+        &self,
+        query: &GetPostsWatchingQuery,
+    ) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-posts-watching", query).await
+    }
+
+    /// GET /v1/get-posts-light
+    pub async fn get_posts_light(
+        &self,
+        query: &GetPostsLightQuery,
+    ) -> Result<PaginatedPostsLightResponse> {
+        self.get("/v1/get-posts-light", query).await
+    }
+
+    /// GET /v1/get-posts-meta
+    pub async fn get_posts_meta(&self, query: &GetPostsMetaQuery) -> Result<PostsMetaResponse> {
+        self.get("/v1/get-posts-meta", query).await
+    }
+
+    /// GET /v1/get-posts-by-ids
+    pub async fn get_posts_by_ids(&self, query: &GetPostsByIdsQuery) -> Result<PostsByIdsResponse> {
+        self.get("/v1/get-posts-by-ids", query).await
+    }
+
+    /// GET /v1/get-contents-following
+    pub async fn get_contents_following(
+        &self,
+        query: &GetContentsFollowingQuery,
+    ) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-contents-following", query).await
+    }
+
+    /// GET /v1/get-posts-following
+    pub async fn get_posts_following(
+        &self,
+        query: &GetPostsFollowingQuery,
+    ) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-posts-following", query).await
+    }
+
+    /// GET /v1/get-replies
+    pub async fn get_replies(&self, query: &GetRepliesQuery) -> Result<PaginatedRepliesResponse> {
+        self.get("/v1/get-replies", query).await
+    }
+
+    /// GET /v1/get-thread
+    pub async fn get_thread(&self, query: &GetThreadQuery) -> Result<ThreadResponse> {
+        self.get("/v1/get-thread", query).await
+    }
+
+    /// GET /v1/get-mentions
+    pub async fn get_mentions(&self, query: &GetMentionsQuery) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-mentions", query).await
+    }
+
+    /// GET /v1/get-users
+    pub async fn get_users(&self, query: &GetUsersQuery) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-users", query).await
+    }
+
+    /// GET /v1/get-most-active-users
+    pub async fn get_most_active_users(
+        &self,
+        query: &GetMostActiveUsersQuery,
+    ) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-most-active-users", query).await
+    }
+
+    /// GET /v1/get-users-count
+    pub async fn get_users_count(&self) -> Result<CountResponse> {
+        self.get("/v1/get-users-count", &GetUsersCountQuery {})
+            .await
+    }
+
+    /// GET /v1/search-users
+    pub async fn search_users(&self, query: &SearchUsersQuery) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/search-users", query).await
+    }
+
+    /// GET /v1/get-user-details
+    pub async fn get_user_details(&self, query: &GetUserDetailsQuery) -> Result<ServerUserPost> {
+        self.get("/v1/get-user-details", query).await
+    }
+
+    /// GET /v1/get-user-stats
+    pub async fn get_user_stats(&self, query: &GetUserStatsQuery) -> Result<UserStatsResponse> {
+        self.get("/v1/get-user-stats", query).await
+    }
+
+    /// GET /v1/get-users-by-keys
+    pub async fn get_users_by_keys(&self, query: &GetUsersByKeysQuery) -> Result<UsersResponse> {
+        self.get("/v1/get-users-by-keys", query).await
+    }
+
+    /// GET /v1/get-profile
+    pub async fn get_profile(&self, query: &GetProfileQuery) -> Result<ProfileResponse> {
+        self.get("/v1/get-profile", query).await
+    }
+
+    /// GET /v1/get-profile-image, the raw image bytes (redirects to blob storage are followed)
+    pub async fn get_profile_image(&self, query: &GetProfileImageQuery) -> Result<Vec<u8>> {
+        let response = self
+            .send(
+                self.request(Method::GET, "/v1/get-profile-image")
+                    .query(query),
+            )
+            .await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// GET /v1/get-profile-diff
+    pub async fn get_profile_diff(
+        &self,
+        query: &GetProfileDiffQuery,
+    ) -> Result<ProfileDiffResponse> {
+        self.get("/v1/get-profile-diff", query).await
+    }
+
+    /// GET /v1/get-followed-users
+    pub async fn get_followed_users(
+        &self,
+        query: &GetFollowedUsersQuery,
+    ) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-followed-users", query).await
+    }
+
+    /// GET /v1/get-users-following
+    pub async fn get_users_following(
+        &self,
+        query: &GetUsersFollowingQuery,
+    ) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-users-following", query).await
+    }
+
+    /// GET /v1/get-users-followers
+    pub async fn get_users_followers(
+        &self,
+        query: &GetUsersFollowersQuery,
+    ) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-users-followers", query).await
+    }
+
+    /// GET /v1/get-blocked-users
+    pub async fn get_blocked_users(
+        &self,
+        query: &GetBlockedUsersQuery,
+    ) -> Result<PaginatedUsersResponse> {
+        self.get("/v1/get-blocked-users", query).await
+    }
+
+    /// GET /v1/get-notifications-count
+    pub async fn get_notifications_count(
+        &self,
+        query: &GetNotificationsCountQuery,
+    ) -> Result<CountResponse> {
+        self.get("/v1/get-notifications-count", query).await
+    }
+
+    /// GET /v1/get-notifications
+    pub async fn get_notifications(
+        &self,
+        query: &GetNotificationsQuery,
+    ) -> Result<PaginatedNotificationsResponse> {
+        self.get("/v1/get-notifications", query).await
+    }
+
+    /// GET /v1/get-hashtag-content
+    pub async fn get_hashtag_content(
+        &self,
+        query: &GetHashtagContentQuery,
+    ) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/get-hashtag-content", query).await
+    }
+
+    /// GET /v1/search-posts
+    pub async fn search_posts(&self, query: &SearchPostsQuery) -> Result<PaginatedPostsResponse> {
+        self.get("/v1/search-posts", query).await
+    }
+
+    /// GET /v1/get-trending-hashtags
+    pub async fn get_trending_hashtags(
+        &self,
+        query: &GetTrendingHashtagsQuery,
+    ) -> Result<TrendingHashtagsResponse> {
+        self.get("/v1/get-trending-hashtags", query).await
+    }
+
+    /// GET /v1/get-groups
+    pub async fn get_groups(&self, query: &GetGroupsQuery) -> Result<PaginatedGroupsResponse> {
+        self.get("/v1/get-groups", query).await
+    }
+
+    /// GET /v1/get-group-posts
+    pub async fn get_group_posts(
+        &self,
+        query: &GetGroupPostsQuery,
+    ) -> Result<PaginatedGroupPostsResponse> {
+        self.get("/v1/get-group-posts", query).await
+    }
+
+    /// GET /v1/get-events
+    pub async fn get_events(&self, query: &GetEventsQuery) -> Result<PaginatedEventsResponse> {
+        self.get("/v1/get-events", query).await
+    }
+
+    /// GET /v1/get-article
+    pub async fn get_article(&self, query: &GetArticleQuery) -> Result<ArticleDetailsResponse> {
+        self.get("/v1/get-article", query).await
+    }
+
+    /// GET /v1/get-post-mentions
+    pub async fn get_post_mentions(
+        &self,
+        query: &GetPostMentionsQuery,
+    ) -> Result<PostMentionsResponse> {
+        self.get("/v1/get-post-mentions", query).await
+    }
+
+    /// GET /v1/get-post-tips
+    pub async fn get_post_tips(&self, query: &GetPostTipsQuery) -> Result<PostTipsResponse> {
+        self.get("/v1/get-post-tips", query).await
+    }
+
+    /// GET /v1/get-top-tipped-posts
+    pub async fn get_top_tipped_posts(
+        &self,
+        query: &GetTopTippedPostsQuery,
+    ) -> Result<TopTippedPostsResponse> {
+        self.get("/v1/get-top-tipped-posts", query).await
+    }
+
+    /// GET /v1/get-featured
+    pub async fn get_featured(&self, query: &GetFeaturedQuery) -> Result<FeaturedPostsResponse> {
+        self.get("/v1/get-featured", query).await
+    }
+
+    /// GET /v1/get-feature-flags
+    pub async fn get_feature_flags(
+        &self,
+        query: &GetFeatureFlagsQuery,
+    ) -> Result<FeatureFlagsResponse> {
+        self.get("/v1/get-feature-flags", query).await
+    }
+
+    /// POST /v1/track-view (only served with view tracking enabled)
+    pub async fn track_view(&self, query: &TrackViewQuery) -> Result<()> {
+        self.send(self.request(Method::POST, "/v1/track-view").query(query))
+            .await?;
+        Ok(())
+    }
+
+    /// Any paginated /v2 list, e.g. `get_v2::<_, ServerPost>("/get-posts", &query)`
+    pub async fn get_v2<Q, T>(&self, path: &str, query: &Q) -> Result<PaginatedEnvelope<T>>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.get(&format!("/v2{}", path), query).await
+    }
+
+    /// POST /admin/erasure-requests
+    pub async fn create_erasure_request(
+        &self,
+        query: &CreateErasureRequestQuery,
+    ) -> Result<ErasureRequestResponse> {
+        self.admin(Method::POST, "/erasure-requests", query).await
+    }
+
+    /// GET /admin/erasure-requests
+    pub async fn get_erasure_request(
+        &self,
+        query: &GetErasureRequestQuery,
+    ) -> Result<ErasureRequestResponse> {
+        self.admin(Method::GET, "/erasure-requests", query).await
+    }
+
+    /// GET /admin/duplicate-contents
+    pub async fn get_duplicate_contents(
+        &self,
+        query: &GetDuplicateContentsQuery,
+    ) -> Result<DuplicateContentsResponse> {
+        self.admin(Method::GET, "/duplicate-contents", query).await
+    }
+
+    /// GET /admin/storage-stats
+    pub async fn get_storage_stats(&self) -> Result<StorageStatsResponse> {
+        self.admin(Method::GET, "/storage-stats", &()).await
+    }
+
+    /// GET /admin/slow-queries
+    pub async fn get_slow_queries(
+        &self,
+        query: &GetSlowQueriesQuery,
+    ) -> Result<SlowQueriesResponse> {
+        self.admin(Method::GET, "/slow-queries", query).await
+    }
+
+    /// GET /admin/request-analytics
+    pub async fn get_request_analytics(
+        &self,
+        query: &GetRequestAnalyticsQuery,
+    ) -> Result<RequestAnalyticsResponse> {
+        self.admin(Method::GET, "/request-analytics", query).await
+    }
+
+    /// GET /admin/impersonation-flags
+    pub async fn get_impersonation_flags(
+        &self,
+        query: &GetImpersonationFlagsQuery,
+    ) -> Result<ImpersonationFlagsResponse> {
+        self.admin(Method::GET, "/impersonation-flags", query).await
+    }
+
+    /// POST /admin/impersonation-flags
+    pub async fn review_impersonation_flag(
+        &self,
+        query: &ReviewImpersonationFlagQuery,
+    ) -> Result<ImpersonationFlag> {
+        self.admin(Method::POST, "/impersonation-flags", query)
+            .await
+    }
+
+    /// GET /admin/nickname-history
+    pub async fn get_nickname_history(
+        &self,
+        query: &GetNicknameHistoryQuery,
+    ) -> Result<NicknameHistoryResponse> {
+        self.admin(Method::GET, "/nickname-history", query).await
+    }
+
+    /// GET /admin/featured
+    pub async fn get_featured_entries(
+        &self,
+        query: &GetFeaturedEntriesQuery,
+    ) -> Result<FeaturedEntriesResponse> {
+        self.admin(Method::GET, "/featured", query).await
+    }
+
+    /// POST /admin/featured
+    pub async fn set_featured(&self, query: &SetFeaturedQuery) -> Result<FeaturedEntry> {
+        self.admin(Method::POST, "/featured", query).await
+    }
+
+    /// DELETE /admin/featured
+    pub async fn delete_featured(&self, query: &DeleteFeaturedQuery) -> Result<FeaturedEntry> {
+        self.admin(Method::DELETE, "/featured", query).await
+    }
+
+    /// POST /admin/simulate-payload (k-indexer started with --simulator only)
+    pub async fn simulate_payload(
+        &self,
+        query: &SimulatePayloadQuery,
+    ) -> Result<PayloadSimulationResponse> {
+        self.admin(Method::POST, "/simulate-payload", query).await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+    }
+
+    async fn get<Q, T>(&self, path: &str, query: &Q) -> Result<T>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let response = self
+            .send(self.request(Method::GET, path).query(query))
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    async fn admin<Q, T>(&self, method: Method, path: &str, query: &Q) -> Result<T>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let mut request = self
+            .request(method, &format!("/admin{}", path))
+            .query(query);
+        if let Some(admin_token) = &self.admin_token {
+            request = request.bearer_auth(admin_token);
+        }
+        let response = self.send(request).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Send the request, turning error statuses into ClientError
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.bytes().await?;
+        Err(status_error(status, &body))
+    }
+}
+
+/// Error of a non-success response, with the ApiError body when the webserver sent one
+fn status_error(status: StatusCode, body: &[u8]) -> ClientError {
+    match serde_json::from_slice::<ApiError>(body) {
+        Ok(error) => ClientError::Api {
+            status: status.as_u16(),
+            error,
+        },
+        Err(_) => ClientError::Status(status.as_u16()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_string_uses_api_parameter_names() {
+        let client = KClient::new("https://indexer.example.org/");
+        let query = GetRepliesQuery {
+            post: Some("ab".to_string()),
+            requester_pubkey: Some("02aa".to_string()),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let request = client
+            .request(Method::GET, "/v1/get-replies")
+            .query(&query)
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://indexer.example.org/v1/get-replies?post=ab&requesterPubkey=02aa&limit=10"
+        );
+    }
+
+    #[test]
+    fn test_status_error() {
+        let body = br#"{"error":"Post not found","code":"NOT_FOUND"}"#;
+        match status_error(StatusCode::NOT_FOUND, body) {
+            ClientError::Api { status, error } => {
+                assert_eq!(status, 404);
+                assert_eq!(error.code, "NOT_FOUND");
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert!(matches!(
+            status_error(StatusCode::BAD_GATEWAY, b"<html>"),
+            ClientError::Status(502)
+        ));
+    }
+
+    #[test]
+    fn test_optional_fields_may_be_absent() {
+        let post: ServerPost = serde_json::from_str(
+            r#"{"id":"ab","userPublicKey":"02aa","postContent":"aGk=","signature":"sig",
+                "timestamp":1,"repliesCount":0,"upVotesCount":0,"downVotesCount":0,
+                "quotesCount":0,"repostsCount":0,"parentPostId":null,"mentionedPubkeys":[],
+                "isQuote":false}"#,
+        )
+        .unwrap();
+        assert_eq!(post.user_nickname, None);
+        assert_eq!(post.view_count, None);
+        assert_eq!(post.up_votes_weight, None);
+    }
+}
@@ -0,0 +1,10 @@
+//! Typed client of the K-indexer webserver API
+//!
+//! The request and response models are the ones K-webserver itself (de)serializes, so the
+//! client cannot drift from the server.
+
+mod client;
+pub mod models;
+pub mod requests;
+
+pub use client::{ClientError, KClient, Result};
@@ -0,0 +1,880 @@
+//! Response models of the K-indexer webserver API
+//!
+//! The webserver serializes these very types, so clients deserialize exactly what is served.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Referenced content data for quotes (only the original content being quoted)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct QuoteData {
+    #[serde(rename = "referencedContentId")]
+    pub referenced_content_id: String,
+    #[serde(rename = "referencedMessage")]
+    pub referenced_message: String,
+    #[serde(rename = "referencedSenderPubkey")]
+    pub referenced_sender_pubkey: String,
+    #[serde(rename = "referencedNickname", skip_serializing_if = "Option::is_none")]
+    pub referenced_nickname: Option<String>,
+    #[serde(
+        rename = "referencedProfileImage",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub referenced_profile_image: Option<String>,
+}
+
+// Mention, hashtag or URL in the decoded message, found by the indexer; start and end are
+// UTF-16 code unit offsets (JavaScript string indexes), end exclusive
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RenderSpan {
+    // "mention", "hashtag" or "url"
+    #[serde(rename = "type")]
+    pub span_type: String,
+    pub start: u32,
+    pub end: u32,
+    // Mentioned name without '@', lowercase hashtag without '#', or the URL
+    pub value: String,
+}
+
+// API Response models
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerPost {
+    pub id: String,
+    #[serde(rename = "userPublicKey")]
+    pub user_public_key: String,
+    #[serde(rename = "postContent")]
+    pub post_content: String,
+    pub signature: String,
+    pub timestamp: u64,
+    #[serde(rename = "repliesCount")]
+    pub replies_count: u64,
+    #[serde(rename = "upVotesCount")]
+    pub up_votes_count: u64,
+    #[serde(rename = "downVotesCount")]
+    pub down_votes_count: u64,
+    #[serde(rename = "quotesCount")]
+    pub quotes_count: u64,
+    #[serde(rename = "repostsCount")]
+    pub reposts_count: u64,
+    #[serde(rename = "parentPostId")]
+    pub parent_post_id: Option<String>,
+    #[serde(rename = "mentionedPubkeys")]
+    pub mentioned_pubkeys: Vec<String>,
+    // Total number of mentions, only when mentionedPubkeys lists the first ones (GET /get-post-mentions)
+    #[serde(rename = "mentionsCount", skip_serializing_if = "Option::is_none")]
+    pub mentions_count: Option<u64>,
+    // Mentions, hashtags and URLs of the decoded message (indexer schema v26+)
+    #[serde(rename = "renderSpans", skip_serializing_if = "Option::is_none")]
+    pub render_spans: Option<Vec<RenderSpan>>,
+    #[serde(rename = "isUpvoted", skip_serializing_if = "Option::is_none")]
+    pub is_upvoted: Option<bool>,
+    #[serde(rename = "isDownvoted", skip_serializing_if = "Option::is_none")]
+    pub is_downvoted: Option<bool>,
+    #[serde(rename = "userNickname", skip_serializing_if = "Option::is_none")]
+    pub user_nickname: Option<String>,
+    #[serde(rename = "userProfileImage", skip_serializing_if = "Option::is_none")]
+    pub user_profile_image: Option<String>,
+    #[serde(rename = "blockedUser", skip_serializing_if = "Option::is_none")]
+    pub blocked_user: Option<bool>,
+    #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(rename = "isQuote")]
+    pub is_quote: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<QuoteData>,
+    #[serde(rename = "nestedReplies", skip_serializing_if = "Option::is_none")]
+    pub nested_replies: Option<Vec<ServerReply>>,
+    // Approximate number of distinct viewers (only with view tracking enabled)
+    #[serde(rename = "viewCount", skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<u64>,
+    // Sum of the fees (sompi) burned by the up/down votes (includeVoteWeights, indexer schema v27+)
+    #[serde(rename = "upVotesWeight", skip_serializing_if = "Option::is_none")]
+    pub up_votes_weight: Option<u64>,
+    #[serde(rename = "downVotesWeight", skip_serializing_if = "Option::is_none")]
+    pub down_votes_weight: Option<u64>,
+    // Block explorer pages of the content's transaction (get-post-details, with --explorer-url)
+    #[serde(rename = "explorerLinks", skip_serializing_if = "Option::is_none")]
+    pub explorer_links: Option<ExplorerLinks>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExplorerLinks {
+    pub transaction: String,
+    // Only when the Kaspa indexer records the blocks of transactions (blocks_transactions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PostsResponse {
+    pub posts: Vec<ServerPost>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginationMetadata {
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    #[serde(rename = "prevCursor")]
+    pub prev_cursor: Option<String>,
+}
+
+/// Unified envelope returned by every paginated /v2 endpoint
+/// `type` tells clients which item shape `items` holds, so one pagination handler fits all lists
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+// Named after the item type in schemas (e.g. ServerPostEnvelope)
+#[cfg_attr(feature = "schema", schemars(rename = "{T}Envelope"))]
+pub struct PaginatedEnvelope<T> {
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub items: Vec<T>,
+    pub pagination: PaginationMetadata,
+}
+
+impl<T> PaginatedEnvelope<T> {
+    pub fn new(item_type: &str, items: Vec<T>, pagination: PaginationMetadata) -> Self {
+        Self {
+            item_type: item_type.to_string(),
+            items,
+            pagination,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedPostsResponse {
+    pub posts: Vec<ServerPost>,
+    pub pagination: PaginationMetadata,
+}
+
+// Core fields of a post or quote (GET /get-posts-light), rendered before the counts, votes,
+// profiles, mentions and quoted content are hydrated with GET /get-posts-meta
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerPostLight {
+    pub id: String,
+    #[serde(rename = "userPublicKey")]
+    pub user_public_key: String,
+    #[serde(rename = "postContent")]
+    pub post_content: String,
+    pub signature: String,
+    pub timestamp: u64,
+    #[serde(rename = "isQuote")]
+    pub is_quote: bool,
+    // Transaction ID of the quoted content, only for quotes
+    #[serde(
+        rename = "referencedContentId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub referenced_content_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedPostsLightResponse {
+    pub posts: Vec<ServerPostLight>,
+    pub pagination: PaginationMetadata,
+}
+
+// Fields of a content left out of ServerPostLight (GET /get-posts-meta), named as in ServerPost
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerPostMeta {
+    pub id: String,
+    #[serde(rename = "repliesCount")]
+    pub replies_count: u64,
+    #[serde(rename = "upVotesCount")]
+    pub up_votes_count: u64,
+    #[serde(rename = "downVotesCount")]
+    pub down_votes_count: u64,
+    #[serde(rename = "quotesCount")]
+    pub quotes_count: u64,
+    #[serde(rename = "mentionedPubkeys")]
+    pub mentioned_pubkeys: Vec<String>,
+    // Total number of mentions, only when mentionedPubkeys lists the first ones
+    #[serde(rename = "mentionsCount", skip_serializing_if = "Option::is_none")]
+    pub mentions_count: Option<u64>,
+    #[serde(rename = "renderSpans", skip_serializing_if = "Option::is_none")]
+    pub render_spans: Option<Vec<RenderSpan>>,
+    #[serde(rename = "isUpvoted", skip_serializing_if = "Option::is_none")]
+    pub is_upvoted: Option<bool>,
+    #[serde(rename = "isDownvoted", skip_serializing_if = "Option::is_none")]
+    pub is_downvoted: Option<bool>,
+    #[serde(rename = "userNickname", skip_serializing_if = "Option::is_none")]
+    pub user_nickname: Option<String>,
+    #[serde(rename = "userProfileImage", skip_serializing_if = "Option::is_none")]
+    pub user_profile_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<QuoteData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PostsMetaResponse {
+    pub posts: Vec<ServerPostMeta>,
+}
+
+// Posts, replies and quotes of GET /get-posts-by-ids, in the order of the requested IDs
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PostsByIdsResponse {
+    pub posts: Vec<ServerPost>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedNotificationsResponse {
+    pub notifications: Vec<NotificationPost>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TrendingHashtag {
+    pub hashtag: String,
+    #[serde(rename = "usageCount")]
+    pub usage_count: u64,
+    pub rank: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TrendingHashtagsResponse {
+    #[serde(rename = "timeWindow")]
+    pub time_window: String,
+    #[serde(rename = "fromTime")]
+    pub from_time: u64,
+    #[serde(rename = "toTime")]
+    pub to_time: u64,
+    pub hashtags: Vec<TrendingHashtag>,
+}
+
+/// Number of users (GET /get-users-count) or of notifications (GET /get-notifications-count)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CountResponse {
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerUserPost {
+    pub id: String,
+    #[serde(rename = "userPublicKey")]
+    pub user_public_key: String,
+    #[serde(rename = "postContent")]
+    pub post_content: String,
+    pub signature: String,
+    pub timestamp: u64,
+    #[serde(rename = "userNickname", skip_serializing_if = "Option::is_none")]
+    pub user_nickname: Option<String>,
+    #[serde(rename = "userProfileImage", skip_serializing_if = "Option::is_none")]
+    pub user_profile_image: Option<String>,
+    #[serde(rename = "blockedUser", skip_serializing_if = "Option::is_none")]
+    pub blocked_user: Option<bool>,
+    #[serde(rename = "followedUser", skip_serializing_if = "Option::is_none")]
+    pub followed_user: Option<bool>,
+    #[serde(rename = "followersCount", skip_serializing_if = "Option::is_none")]
+    pub followers_count: Option<i64>,
+    #[serde(rename = "followingCount", skip_serializing_if = "Option::is_none")]
+    pub following_count: Option<i64>,
+    #[serde(rename = "blockedCount", skip_serializing_if = "Option::is_none")]
+    pub blocked_count: Option<i64>,
+    #[serde(rename = "contentsCount", skip_serializing_if = "Option::is_none")]
+    pub contents_count: Option<i64>,
+}
+
+/// Everything a profile page needs (GET /get-profile): the user details with the follow/block
+/// status of the requester and the first page of the user's posts
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProfileResponse {
+    pub user: ServerUserPost,
+    pub posts: Vec<ServerPost>,
+    pub pagination: PaginationMetadata,
+}
+
+/// Activity totals of a user (GET /get-user-stats)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UserStatsResponse {
+    pub user_public_key: String,
+    pub posts_count: u64,
+    pub replies_count: u64,
+    pub quotes_count: u64,
+    // Votes cast by the user
+    pub votes_given_count: u64,
+    // Votes received by the posts, replies and quotes of the user
+    pub up_votes_count: u64,
+    pub down_votes_count: u64,
+    // block_time of the first and latest indexed action of the user (absent without any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_active: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UsersResponse {
+    pub posts: Vec<ServerUserPost>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedUsersResponse {
+    pub posts: Vec<ServerUserPost>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PostDetailsResponse {
+    pub post: ServerPost,
+    // Set when the content was served from k_contents_archive (includeArchived=true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApiError {
+    pub error: String,
+    pub code: String,
+}
+
+pub type ServerReply = ServerPost;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPost {
+    pub id: String,
+    pub user_public_key: String,
+    pub post_content: String,
+    pub timestamp: u64,
+    pub user_nickname: Option<String>,
+    pub user_profile_image: Option<String>,
+    pub content_type: String, // "post", "reply", "quote", "vote" or "follow" from k_mentions table
+    pub cursor: String,       // Compound cursor combining block_time and k_mentions.id
+    // Vote-specific fields
+    pub vote_type: Option<String>,       // "upvote" or "downvote"
+    pub mention_block_time: Option<u64>, // block_time from k_mentions table
+    pub content_id: Option<String>,      // The ID of the content being voted on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<String>, // The post ID that the vote refers to
+    pub voted_content: Option<String>,   // Content of the post/reply being voted on
+    // Number of notifications collapsed into this one: repeated votes, replies or quotes of the
+    // same sender on the same content within the collapse window (absent when not collapsed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RepliesResponse {
+    pub replies: Vec<ServerReply>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedRepliesResponse {
+    pub replies: Vec<ServerReply>,
+    pub pagination: PaginationMetadata,
+}
+
+// Root content with a page of its direct replies, each reply carrying its own replies in
+// nestedReplies down to maxDepth (GET /get-thread)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ThreadResponse {
+    pub post: ServerPost,
+    pub replies: Vec<ServerReply>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerGroup {
+    pub id: String,
+    pub owner_public_key: String,
+    pub group_name: String,
+    pub group_description: String,
+    pub membership_policy: String, // "open" or "closed"
+    pub signature: String,
+    pub timestamp: u64,
+    pub members_count: u64,
+    pub is_member: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerGroupPost {
+    pub id: String,
+    pub group_id: String,
+    pub user_public_key: String,
+    pub post_content: String,
+    pub signature: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_profile_image: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedGroupsResponse {
+    pub groups: Vec<ServerGroup>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedGroupPostsResponse {
+    pub group: ServerGroup,
+    pub posts: Vec<ServerGroupPost>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEvent {
+    pub id: String,
+    pub organizer_public_key: String,
+    pub title: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub location: String,
+    pub signature: String,
+    pub timestamp: u64,
+    pub going_count: u64,
+    pub interested_count: u64,
+    pub not_going_count: u64,
+    // RSVP of the requester ("going", "interested", "not_going"), absent if none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsvp_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organizer_nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organizer_profile_image: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaginatedEventsResponse {
+    pub events: Vec<ServerEvent>,
+    pub pagination: PaginationMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerArticle {
+    pub id: String,
+    pub author_public_key: String,
+    pub title: String,
+    pub chunk_count: u32,
+    pub received_chunks: u32,
+    // "incomplete", "complete" or "invalid" (chunks do not match the manifest content hash)
+    pub status: String,
+    pub is_complete: bool,
+    // Reassembled base64 encoded article, only when complete and the author is not blocked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub content_hash: String,
+    pub signature: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+    // Null when the server withholds the relations of the requester (--requester-privacy)
+    pub blocked_user: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_profile_image: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ArticleDetailsResponse {
+    pub article: ServerArticle,
+}
+
+/// Every pubkey mentioned by a post, reply or quote (GET /get-post-mentions)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PostMentionsResponse {
+    pub post_id: String,
+    pub mentioned_pubkeys: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PostTipsResponse {
+    pub post_id: String,
+    pub recipient_public_key: String,
+    pub tips_count: u64,
+    pub tippers_count: u64,
+    // Total tipped amount in sompi (1 KAS = 100,000,000 sompi)
+    pub total_amount: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TopTippedPost {
+    pub rank: u32,
+    pub tips_count: u64,
+    // Amount in sompi tipped within the time window
+    pub tips_amount: u64,
+    pub post: ServerPost,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TopTippedPostsResponse {
+    pub time_window: String,
+    pub from_time: u64,
+    pub to_time: u64,
+    pub posts: Vec<TopTippedPost>,
+}
+
+/// Feature flags evaluated for a requester (GET /get-feature-flags)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FeatureFlagsResponse {
+    pub flags: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ErasureRequestResponse {
+    pub id: i64,
+    pub subject_public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    // "pending" until K-content-remover has erased the subject's data, then "completed"
+    pub status: String,
+    pub requested_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed_rows: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tombstoned_transactions: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateContent {
+    pub message_hash: String,
+    pub sample_content_id: String,
+    pub sample_message: String,
+    pub distinct_senders: u64,
+    pub content_count: u64,
+    pub first_block_time: u64,
+    pub last_block_time: u64,
+    // At most 100 pubkeys (MAX_DUPLICATE_SENDERS of the webserver)
+    pub sender_pubkeys: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateContentsResponse {
+    pub time_window: String,
+    pub from_time: u64,
+    pub to_time: u64,
+    pub min_senders: u32,
+    pub duplicates: Vec<DuplicateContent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TableStorageStats {
+    pub table: String,
+    pub row_count: u64,
+    pub total_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compared_to: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_growth: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_growth: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_growth_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSnapshot {
+    pub id: String,
+    pub timestamp: u64,
+    pub user_nickname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_image_hash: Option<String>,
+    pub post_content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDiffResponse {
+    pub user_public_key: String,
+    pub from: u64,
+    pub to: u64,
+    pub from_profile: Option<ProfileSnapshot>,
+    pub to_profile: Option<ProfileSnapshot>,
+    // "nickname", "profileImage" and/or "bio"
+    pub changed_fields: Vec<String>,
+    pub profile_updates: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatsResponse {
+    pub generated_at: u64,
+    pub total_bytes: u64,
+    pub tables: Vec<TableStorageStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStats {
+    // DatabaseInterface method the queries belong to
+    pub method: String,
+    pub calls: u64,
+    pub total_time_ms: f64,
+    pub mean_time_ms: f64,
+    pub max_time_ms: f64,
+    pub rows: u64,
+    // Shared buffer reads served from memory (None before the first read)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit_percent: Option<f64>,
+    // Share of the execution time of every statement recorded in the database
+    pub time_share_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueriesResponse {
+    pub generated_at: u64,
+    pub queries: Vec<QueryStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointRequests {
+    // Endpoint path without leading slash or version, e.g. "get-post-details"
+    pub endpoint: String,
+    pub requests: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ContentRequests {
+    // Transaction ID given as id or post parameter
+    pub id: String,
+    pub requests: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RequestAnalyticsHour {
+    // Unix time (seconds) of the start of the hour
+    pub hour_start: u64,
+    pub requests: u64,
+    // Most requested first
+    pub endpoints: Vec<EndpointRequests>,
+    pub contents: Vec<ContentRequests>,
+    // Requests for contents beyond the distinct contents counted in an hour
+    pub untracked_content_requests: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RequestAnalyticsResponse {
+    pub generated_at: u64,
+    pub retention_hours: u64,
+    // Newest hour first, hours without requests omitted
+    pub hours: Vec<RequestAnalyticsHour>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationFlag {
+    pub id: i64,
+    pub suspect_public_key: String,
+    pub suspect_nickname: String,
+    pub target_public_key: String,
+    pub target_nickname: String,
+    pub target_followers: u64,
+    // Normalized nickname similarity, 0.0 - 1.0
+    pub similarity: f64,
+    pub transaction_id: String,
+    pub flagged_at: u64,
+    // "pending" until reviewed, then "confirmed" or "dismissed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reviewed_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationFlagsResponse {
+    pub status: String,
+    pub flags: Vec<ImpersonationFlag>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NicknameUse {
+    pub user_public_key: String,
+    // Last matching nickname of the user, base64 encoded as broadcast
+    pub nickname: String,
+    // Block times of the first and last broadcasts with a matching nickname
+    pub first_used: u64,
+    pub last_used: u64,
+    pub broadcasts_count: u64,
+    // Nickname of the latest broadcast of the user, None when it is no longer indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_nickname: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NicknameHistoryResponse {
+    pub nickname: String,
+    pub normalized_nickname: String,
+    // Earliest user of the nickname first
+    pub users: Vec<NicknameUse>,
+}
+
+/// Operator-curated featured content, as managed with /admin/featured
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedEntry {
+    pub content_id: String,
+    // Lower positions are listed first, then the most recently featured
+    pub position: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub featured_at: u64,
+    // False once the content is no longer indexed: /get-featured skips the entry
+    pub available: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedEntriesResponse {
+    pub entries: Vec<FeaturedEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedPost {
+    pub position: i32,
+    // Operator note shown with the content, e.g. "Node maintenance on Friday"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub featured_at: u64,
+    pub post: ServerPost,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FeaturedPostsResponse {
+    pub posts: Vec<FeaturedPost>,
+}
+
+/// Rows one table would receive from a payload simulated with /admin/simulate-payload
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTableWrites {
+    pub table: String,
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadSimulationResponse {
+    pub transaction_id: String,
+    pub block_time: i64,
+    // Action as parsed by the transaction processor
+    pub action: serde_json::Value,
+    // Nothing is persisted: the simulation is always rolled back
+    pub writes: Vec<SimulatedTableWrites>,
+}
+
+/// Message pushed by the /ws live feed when a post, reply, quote or vote is indexed, and by
+/// /notifications/stream when a notification is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    // "post", "reply", "quote", "vote" or "notification"
+    #[serde(rename = "type")]
+    pub event_type: String,
+    // Transaction id of the content, vote or follow
+    pub id: String,
+    pub sender_pubkey: String,
+    // Replied, quoted or voted content (None for posts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    // Post of the thread: the content itself unless it is a reply, or a vote on a reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    // "upvote" or "downvote" (votes only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vote: Option<String>,
+    // Notified user (notifications only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentioned_pubkey: Option<String>,
+    // "post", "reply", "quote", "vote" or "follow" (notifications only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub block_time: u64,
+    // Sent faster than the per-sender rate limit of the indexer (never pushed with --hide-rate-limited)
+    #[serde(default)]
+    pub rate_limited: bool,
+}
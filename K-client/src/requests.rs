@@ -0,0 +1,508 @@
+//! Query parameters of the K-indexer webserver API
+//!
+//! The webserver deserializes these very types; unset fields are left out of the query string.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsQuery {
+    pub user: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetRepliesQuery {
+    pub post: Option<String>,
+    pub user: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>, // Changed to String to support compound cursors
+    pub after: Option<String>,  // Changed to String to support compound cursors
+    #[serde(rename = "includeReplies")]
+    pub include_replies: Option<u32>,
+    #[serde(rename = "includeVoteWeights")]
+    pub include_vote_weights: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetThreadQuery {
+    pub id: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    // Levels of replies below the root (default 3)
+    #[serde(rename = "maxDepth")]
+    pub max_depth: Option<u32>,
+    // Replies per parent at every level
+    pub limit: Option<u32>,
+    // Cursors of the direct replies of the root
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsWatchingQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    // latest (default), top or hot
+    pub sort: Option<String>,
+    // Window of the ranked sorts: 1h, 6h, 24h (default), 7d or 30d
+    #[serde(rename = "timeWindow")]
+    pub time_window: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsLightQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsMetaQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    // Comma-separated transaction IDs of the contents
+    pub ids: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsByIdsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    // Comma-separated transaction IDs of the contents
+    pub ids: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetContentsFollowingQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostsFollowingQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUsersQuery {
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetMostActiveUsersQuery {
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "timeWindow")]
+    pub time_window: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SearchUsersQuery {
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "searchedUserPubkey")]
+    pub searched_user_pubkey: Option<String>,
+    #[serde(rename = "searchedUserNickname")]
+    pub searched_user_nickname: Option<String>,
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetMentionsQuery {
+    pub user: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetNotificationsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetHashtagContentQuery {
+    pub hashtag: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SearchPostsQuery {
+    pub query: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetTrendingHashtagsQuery {
+    #[serde(rename = "timeWindow")]
+    pub time_window: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostDetailsQuery {
+    pub id: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "includeReplies")]
+    pub include_replies: Option<u32>,
+    #[serde(rename = "includeVoteWeights")]
+    pub include_vote_weights: Option<bool>,
+    // Admin only: fall back to k_contents_archive when the content is not live
+    #[serde(rename = "includeArchived")]
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetArticleQuery {
+    pub id: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TrackViewQuery {
+    pub id: Option<String>,
+}
+
+/// Subscription of a /ws live feed connection: a user, a post thread, or the watching feed
+/// when neither is given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LiveFeedQuery {
+    pub user: Option<String>,
+    pub post: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationStreamQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CreateErasureRequestQuery {
+    pub pubkey: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetErasureRequestQuery {
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetImpersonationFlagsQuery {
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetNicknameHistoryQuery {
+    pub nickname: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetFeaturedEntriesQuery {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetFeaturedQuery {
+    pub id: Option<String>,
+    pub position: Option<i32>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteFeaturedQuery {
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SimulatePayloadQuery {
+    // Raw K payload, e.g. "k:1:post:..."
+    pub payload: Option<String>,
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    #[serde(rename = "blockTime")]
+    pub block_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReviewImpersonationFlagQuery {
+    pub id: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetDuplicateContentsQuery {
+    #[serde(rename = "timeWindow")]
+    pub time_window: Option<String>,
+    #[serde(rename = "minSenders")]
+    pub min_senders: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetSlowQueriesQuery {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetRequestAnalyticsQuery {
+    pub hours: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetFeatureFlagsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostMentionsQuery {
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetPostTipsQuery {
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetTopTippedPostsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "timeWindow")]
+    pub time_window: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetFeaturedQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetProfileImageQuery {
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUserDetailsQuery {
+    pub user: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUserStatsQuery {
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUsersByKeysQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    // Comma-separated public keys of the users
+    pub pubkeys: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetProfileQuery {
+    pub user: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    // Size of the first page of posts
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetProfileDiffQuery {
+    pub user: Option<String>,
+    // Unix timestamps in milliseconds (to defaults to now)
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetBlockedUsersQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetFollowedUsersQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUsersFollowingQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "userPubkey")]
+    pub user_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUsersFollowersQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    #[serde(rename = "userPubkey")]
+    pub user_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetNotificationsCountQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetUsersCountQuery {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetGroupsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub member: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetEventsQuery {
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub organizer: Option<String>,
+    pub when: Option<String>, // "upcoming" (default) or "past"
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetGroupPostsQuery {
+    #[serde(rename = "groupId")]
+    pub group_id: Option<String>,
+    #[serde(rename = "requesterPubkey")]
+    pub requester_pubkey: Option<String>,
+    pub limit: Option<u32>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
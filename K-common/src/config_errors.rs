@@ -0,0 +1,97 @@
+use std::fmt;
+
+/// Every invalid option found while loading the configuration, reported together at startup
+#[derive(Debug, Default)]
+pub struct ConfigErrors(Vec<String>);
+
+impl ConfigErrors {
+    pub fn push(&mut self, error: String) {
+        self.0.push(error);
+    }
+
+    /// Value of a fallible check, recording its error
+    pub fn check<T>(&mut self, result: Result<T, String>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    /// Value of a numeric option, recording an error when it is below `min`
+    pub fn at_least<T: PartialOrd + fmt::Display>(&mut self, option: &str, value: T, min: T) -> T {
+        if value < min {
+            self.push(format!(
+                "Invalid {} {}. Must be at least {}",
+                option, value, min
+            ));
+        }
+        value
+    }
+
+    /// The recorded errors, in the order they were found
+    pub fn errors(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.0.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid configuration ({} error{}):",
+            self.0.len(),
+            if self.0.len() == 1 { "" } else { "s" }
+        )?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_errors() {
+        let mut errors = ConfigErrors::default();
+        assert_eq!(errors.at_least("--workers", 4, 1), 4);
+        assert_eq!(errors.check(Ok::<_, String>("value")), Some("value"));
+        assert!(errors.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_errors_reported_together() {
+        let mut errors = ConfigErrors::default();
+        assert_eq!(errors.at_least("--workers", 0, 1), 0);
+        assert_eq!(
+            errors.check(Err::<u16, _>("Invalid port 'x'".to_string())),
+            None
+        );
+        errors.push("--image-storage-path is required".to_string());
+
+        let errors = errors.into_result().unwrap_err();
+        assert_eq!(errors.errors().len(), 3);
+        assert_eq!(
+            errors.to_string(),
+            "Invalid configuration (3 errors):\n  \
+             - Invalid --workers 0. Must be at least 1\n  \
+             - Invalid port 'x'\n  \
+             - --image-storage-path is required"
+        );
+    }
+
+    #[test]
+    fn test_single_error() {
+        let mut errors = ConfigErrors::default();
+        errors.at_least("--storage-report-interval", 59, 60);
+        assert_eq!(
+            errors.into_result().unwrap_err().to_string(),
+            "Invalid configuration (1 error):\n  - Invalid --storage-report-interval 59. Must be at least 60"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::fmt;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// How long `doctor` waits for the database (no retries, unlike the binaries' pools)
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Free disk space (percent of the filesystem) below which the disk check fails
+const DISK_FAIL_PERCENT: u64 = 5;
+
+/// Free disk space (percent of the filesystem) below which the disk check warns
+const DISK_WARN_PERCENT: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Check results printed as they complete, colored when stdout is a terminal
+pub struct Report {
+    color: bool,
+    warnings: usize,
+    failures: usize,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            warnings: 0,
+            failures: 0,
+        }
+    }
+
+    pub fn check(&mut self, status: Status, name: &str, detail: impl AsRef<str>) {
+        let (label, color) = match status {
+            Status::Ok => ("  OK  ", "32"),
+            Status::Warn => {
+                self.warnings += 1;
+                (" WARN ", "33")
+            }
+            Status::Fail => {
+                self.failures += 1;
+                (" FAIL ", "31")
+            }
+        };
+        if self.color {
+            println!(
+                "[\x1b[{}m{}\x1b[0m] {:<22} {}",
+                color,
+                label,
+                name,
+                detail.as_ref()
+            );
+        } else {
+            println!("[{}] {:<22} {}", label, name, detail.as_ref());
+        }
+    }
+
+    /// Print the summary; fails when any check failed so scripts can gate on the exit code
+    pub fn finish<E: From<ChecksFailed>>(self) -> Result<(), E> {
+        println!("{} warning(s), {} failure(s)", self.warnings, self.failures);
+        if self.failures > 0 {
+            return Err(ChecksFailed(self.failures).into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of failed checks of a report
+#[derive(Debug)]
+pub struct ChecksFailed(pub usize);
+
+impl fmt::Display for ChecksFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} check(s) failed", self.0)
+    }
+}
+
+impl std::error::Error for ChecksFailed {}
+
+/// Pool of at most `max_connections` to `connection_string`, reporting the server version
+/// under `name` (`target` being the host:port/database shown)
+pub async fn connect(
+    report: &mut Report,
+    name: &str,
+    connection_string: &str,
+    target: &str,
+    max_connections: u32,
+) -> Option<PgPool> {
+    let connected = match PgPoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(CONNECT_TIMEOUT)
+        .connect(connection_string)
+        .await
+    {
+        Ok(pool) => sqlx::query_scalar::<_, String>("SELECT current_setting('server_version')")
+            .fetch_one(&pool)
+            .await
+            .map(|version| (pool, version)),
+        Err(e) => Err(e),
+    };
+
+    match connected {
+        Ok((pool, version)) => {
+            report.check(
+                Status::Ok,
+                name,
+                format!("PostgreSQL {} at {}", version, target),
+            );
+            Some(pool)
+        }
+        Err(e) => {
+            report.check(
+                Status::Fail,
+                name,
+                format!("{} not reachable: {}", target, e),
+            );
+            None
+        }
+    }
+}
+
+pub async fn check_extensions(report: &mut Report, pool: &PgPool) {
+    match sqlx::query_scalar::<_, String>(
+        "SELECT extversion FROM pg_extension WHERE extname = 'pg_stat_statements'",
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(version)) => report.check(
+            Status::Ok,
+            "Extensions",
+            format!("pg_stat_statements {}", version),
+        ),
+        Ok(None) => report.check(
+            Status::Warn,
+            "Extensions",
+            "pg_stat_statements not installed (see QUERY_MONITORING.md)",
+        ),
+        Err(e) => report.check(Status::Fail, "Extensions", e.to_string()),
+    }
+}
+
+/// For the binaries that do not LISTEN themselves: the trigger notifying
+/// K-transaction-processor is reported so that one run checks the whole deployment, without
+/// it nothing new is ever indexed
+pub async fn check_notification_channel(report: &mut Report, pool: &PgPool) {
+    let trigger = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT pg_get_functiondef(p.oid)
+        FROM pg_proc p
+        WHERE p.proname = 'notify_transaction'
+          AND EXISTS(
+              SELECT 1 FROM pg_trigger
+              WHERE tgname = 'transaction_notify_trigger' AND tgenabled <> 'D'
+          )
+        "#,
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match trigger {
+        Ok(Some(definition)) => match notified_channel(&definition) {
+            Some(channel) => report.check(
+                Status::Ok,
+                "LISTEN channel",
+                format!("'{}' notified by transaction_notify_trigger", channel),
+            ),
+            None => report.check(
+                Status::Warn,
+                "LISTEN channel",
+                "notify_transaction() does not call pg_notify",
+            ),
+        },
+        Ok(None) => report.check(
+            Status::Warn,
+            "LISTEN channel",
+            "transaction_notify_trigger missing or disabled, new content is not indexed",
+        ),
+        Err(e) => report.check(Status::Fail, "LISTEN channel", e.to_string()),
+    }
+}
+
+/// Channel name of the first `pg_notify('<channel>', ...)` call in a function definition
+pub fn notified_channel(definition: &str) -> Option<&str> {
+    let start = definition.find("pg_notify('")? + "pg_notify('".len();
+    let len = definition[start..].find('\'')?;
+    Some(&definition[start..start + len])
+}
+
+/// Free space of the PostgreSQL data directory, when the database runs on this host
+pub async fn check_database_disk(report: &mut Report, pool: &PgPool, host: &str) {
+    if !matches!(host, "localhost" | "127.0.0.1" | "::1") && !host.starts_with('/') {
+        report.check(
+            Status::Warn,
+            "Database disk",
+            format!("database runs on {}, check its disk space there", host),
+        );
+        return;
+    }
+
+    // Readable by superusers and members of pg_read_all_settings only
+    match sqlx::query_scalar::<_, String>("SELECT current_setting('data_directory')")
+        .fetch_one(pool)
+        .await
+    {
+        Ok(data_directory) => check_disk(report, "Database disk", &data_directory),
+        Err(e) => report.check(
+            Status::Warn,
+            "Database disk",
+            format!("data directory not readable: {}", e),
+        ),
+    }
+}
+
+/// Free space of the filesystem holding `path`, reported under `name`
+pub fn check_disk(report: &mut Report, name: &str, path: &str) {
+    let output = std::process::Command::new("df")
+        .args(["-Pk", path])
+        .output();
+    let space = match &output {
+        Ok(output) if output.status.success() => {
+            parse_df_output(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => None,
+    };
+
+    match space {
+        Some((available_kib, total_kib)) => {
+            let detail = format!(
+                "{:.1} GiB of {:.1} GiB free on {}",
+                available_kib as f64 / 1048576.0,
+                total_kib as f64 / 1048576.0,
+                path
+            );
+            report.check(disk_status(available_kib, total_kib), name, detail);
+        }
+        None => report.check(
+            Status::Warn,
+            name,
+            format!("free space of {} unknown ('df' failed)", path),
+        ),
+    }
+}
+
+/// (available KiB, total KiB) from POSIX `df -Pk` output
+fn parse_df_output(output: &str) -> Option<(u64, u64)> {
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    let total_kib = fields.get(1)?.parse().ok()?;
+    let available_kib = fields.get(3)?.parse().ok()?;
+    Some((available_kib, total_kib))
+}
+
+fn disk_status(available_kib: u64, total_kib: u64) -> Status {
+    let free_percent = available_kib * 100 / total_kib.max(1);
+    if free_percent < DISK_FAIL_PERCENT {
+        Status::Fail
+    } else if free_percent < DISK_WARN_PERCENT {
+        Status::Warn
+    } else {
+        Status::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/nvme0n1p2   102400000  81920000  20480000      80% /var/lib/postgresql\n";
+        assert_eq!(parse_df_output(output), Some((20480000, 102400000)));
+        assert_eq!(parse_df_output(""), None);
+        assert_eq!(
+            parse_df_output("df: /missing: No such file or directory\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_disk_status() {
+        assert_eq!(disk_status(50, 100), Status::Ok);
+        assert_eq!(disk_status(10, 100), Status::Warn);
+        assert_eq!(disk_status(4, 100), Status::Fail);
+        assert_eq!(disk_status(0, 0), Status::Fail);
+    }
+
+    #[test]
+    fn test_notified_channel() {
+        let definition = "BEGIN\n    PERFORM pg_notify('transaction_channel', encode(NEW.transaction_id, 'hex'));\nEND;";
+        assert_eq!(notified_channel(definition), Some("transaction_channel"));
+        assert_eq!(notified_channel("BEGIN RETURN NEW; END;"), None);
+    }
+
+    #[test]
+    fn test_report_finish() {
+        let mut report = Report::new();
+        report.check(Status::Ok, "Database", "PostgreSQL 17");
+        report.check(Status::Warn, "Extensions", "pg_stat_statements not installed");
+        assert!(report.finish::<ChecksFailed>().is_ok());
+
+        let mut report = Report::new();
+        report.check(Status::Fail, "Indexes", "missing: idx_k_contents_thread");
+        report.check(Status::Fail, "Schema version", "no K schema found");
+        let error = report.finish::<ChecksFailed>().unwrap_err();
+        assert_eq!(error.to_string(), "2 check(s) failed");
+    }
+}
@@ -0,0 +1,269 @@
+use metrics::counter;
+use sqlx::postgres::{PgConnectOptions, PgConnection};
+use sqlx::{Connection, PgPool};
+use std::fmt;
+use std::time::Duration;
+use tracing::warn;
+
+/// How often the host of the pools is checked (see start_failover_watcher)
+const CHECK_INTERVAL_SECS: u64 = 5;
+
+/// How long a host may take to accept a connection and answer the check
+const CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Checks after a failover closing the idle connections of the pools, so connections that
+/// were busy on the old host when it failed over are not reused once returned
+const DRAIN_CHECKS: u32 = 3;
+
+/// A database server of --db-host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbHost {
+    pub host: String,
+    pub port: u16,
+}
+
+impl fmt::Display for DbHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// Which host of --db-host sessions are opened on (--db-target-session-attrs, as in libpq)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSessionAttrs {
+    /// The first host accepting connections
+    Any,
+    /// The first host accepting writes, i.e. the current primary
+    ReadWrite,
+}
+
+impl TargetSessionAttrs {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "any" => Ok(Self::Any),
+            "read-write" => Ok(Self::ReadWrite),
+            other => Err(format!(
+                "Invalid target session attributes '{}'. Must be 'any' or 'read-write'",
+                other
+            )),
+        }
+    }
+}
+
+/// Hosts of a comma-separated --db-host in order: host, host:port or [IPv6]:port each, the
+/// port defaulting to `default_port`
+pub fn parse_hosts(value: &str, default_port: u16) -> Result<Vec<DbHost>, String> {
+    value
+        .split(',')
+        .map(|entry| {
+            let entry = entry.trim();
+            let invalid = || {
+                format!(
+                    "Invalid database host '{}'. Must be host or host:port, comma-separated",
+                    entry
+                )
+            };
+            let (host, port) = if let Some(rest) = entry.strip_prefix('[') {
+                let (host, port) = rest.split_once(']').ok_or_else(invalid)?;
+                match port {
+                    "" => (host, None),
+                    port => (host, Some(port.strip_prefix(':').ok_or_else(invalid)?)),
+                }
+            } else if entry.matches(':').count() == 1 {
+                let (host, port) = entry.split_once(':').ok_or_else(invalid)?;
+                (host, Some(port))
+            } else {
+                // Plain host name, or an IPv6 address without a port
+                (entry, None)
+            };
+            let port = match port {
+                Some(port) => port.parse::<u16>().map_err(|_| invalid())?,
+                None => default_port,
+            };
+            if host.is_empty() {
+                return Err(invalid());
+            }
+            Ok(DbHost {
+                host: host.to_string(),
+                port,
+            })
+        })
+        .collect()
+}
+
+/// Whether the host of `options` is suitable for `attrs`: it accepts a connection and, for
+/// read-write, answers transaction_read_only = off like libpq checks it
+async fn check_host(
+    options: &PgConnectOptions,
+    attrs: TargetSessionAttrs,
+) -> Result<bool, sqlx::Error> {
+    let check = async {
+        let mut conn = PgConnection::connect_with(options).await?;
+        let suitable = match attrs {
+            TargetSessionAttrs::Any => true,
+            TargetSessionAttrs::ReadWrite => {
+                let read_only: String = sqlx::query_scalar("SHOW transaction_read_only")
+                    .fetch_one(&mut conn)
+                    .await?;
+                read_only == "off"
+            }
+        };
+        let _ = conn.close().await;
+        Ok(suitable)
+    };
+
+    tokio::time::timeout(Duration::from_secs(CHECK_TIMEOUT_SECS), check)
+        .await
+        .unwrap_or_else(|_| {
+            Err(sqlx::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "database host check timed out",
+            )))
+        })
+}
+
+/// `options` pointed at the first of `hosts` suitable for `attrs`, as libpq picks the host of a
+/// multi-host connection string
+pub async fn select_host(
+    options: &PgConnectOptions,
+    hosts: &[DbHost],
+    attrs: TargetSessionAttrs,
+) -> Result<PgConnectOptions, sqlx::Error> {
+    // Without hosts the host of `options` is used, a single one is connected to as-is (the
+    // pool reports its errors)
+    match hosts {
+        [] => return Ok(options.clone()),
+        [host] if attrs == TargetSessionAttrs::Any => {
+            return Ok(options.clone().host(&host.host).port(host.port));
+        }
+        _ => {}
+    }
+
+    let mut last_error = None;
+    for host in hosts {
+        let candidate = options.clone().host(&host.host).port(host.port);
+        match check_host(&candidate, attrs).await {
+            Ok(true) => return Ok(candidate),
+            Ok(false) => {
+                last_error = Some(sqlx::Error::Configuration(
+                    format!("database host {} is read-only", host).into(),
+                ))
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.expect("hosts is not empty"))
+}
+
+/// Point `pools` (the main pool first, then the tenant pools sharing its host) at the first
+/// suitable host of `hosts` whenever their host stops accepting connections or, with
+/// read-write, turns read-only after a failover, instead of reconnecting to a dead host forever.
+/// Listeners reconnecting through the main pool follow it to the new host
+pub async fn start_failover_watcher(
+    pools: Vec<PgPool>,
+    hosts: Vec<DbHost>,
+    attrs: TargetSessionAttrs,
+) {
+    let Some(main_pool) = pools.first().cloned() else {
+        return;
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+    let mut drain_checks_left: u32 = 0;
+
+    loop {
+        interval.tick().await;
+
+        if drain_checks_left > 0 {
+            drain_checks_left -= 1;
+            for pool in &pools {
+                close_idle_connections(pool).await;
+            }
+        }
+
+        let current = main_pool.connect_options();
+        let current_host = format!("{}:{}", current.get_host(), current.get_port());
+        match check_host(&current, attrs).await {
+            Ok(true) => continue,
+            Ok(false) => warn!("Database host {} is read-only", current_host),
+            Err(e) => warn!("Database host {} not reachable: {}", current_host, e),
+        }
+
+        let selected = match select_host(&current, &hosts, attrs).await {
+            Ok(selected) => selected,
+            Err(e) => {
+                warn!("No database host to fail over to: {}", e);
+                continue;
+            }
+        };
+        if selected.get_host() == current.get_host() && selected.get_port() == current.get_port() {
+            continue;
+        }
+
+        warn!(
+            "Failing over from database host {} to {}:{}",
+            current_host,
+            selected.get_host(),
+            selected.get_port()
+        );
+        counter!("k_db_failovers_total").increment(1);
+        for pool in &pools {
+            let options = (*pool.connect_options())
+                .clone()
+                .host(selected.get_host())
+                .port(selected.get_port());
+            pool.set_connect_options(options);
+            close_idle_connections(pool).await;
+        }
+        drain_checks_left = DRAIN_CHECKS;
+    }
+}
+
+/// Close the connections idle in `pool`, new ones being opened with its current options
+async fn close_idle_connections(pool: &PgPool) {
+    for _ in 0..pool.num_idle() {
+        match pool.try_acquire() {
+            Some(conn) => {
+                let _ = conn.close().await;
+            }
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(host: &str, port: u16) -> DbHost {
+        DbHost {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn test_parse_hosts() {
+        assert_eq!(parse_hosts("db", 5432), Ok(vec![host("db", 5432)]));
+        assert_eq!(
+            parse_hosts("db-1:5433, db-2,[::1]:5434,::1", 5432),
+            Ok(vec![
+                host("db-1", 5433),
+                host("db-2", 5432),
+                host("::1", 5434),
+                host("::1", 5432)
+            ])
+        );
+        assert!(parse_hosts("db-1,", 5432).is_err());
+        assert!(parse_hosts("db:port", 5432).is_err());
+        assert!(parse_hosts("[::1]5434", 5432).is_err());
+    }
+
+    #[test]
+    fn test_parse_target_session_attrs() {
+        assert_eq!(
+            TargetSessionAttrs::parse("read-write"),
+            Ok(TargetSessionAttrs::ReadWrite)
+        );
+        assert!(TargetSessionAttrs::parse("primary").is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! Helpers shared by the K-indexer binaries
+
+pub mod config_errors;
+pub mod doctor;
+pub mod failover;
+pub mod pool_monitor;
+pub mod startup;
+
+pub use config_errors::ConfigErrors;
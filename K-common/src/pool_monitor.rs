@@ -0,0 +1,55 @@
+use metrics::{counter, gauge};
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Sample the connection pool every `interval_secs` (--pool-monitor-interval), publish its
+/// statistics as Prometheus gauges and warn when acquiring a connection takes longer than
+/// `acquire_warn_ms` (--pool-acquire-warn-ms), so pool exhaustion shows up before timeouts
+pub async fn start_pool_monitor(pool: PgPool, interval_secs: u64, acquire_warn_ms: u64) {
+    let max_connections = pool.options().get_max_connections();
+    let warn_threshold = Duration::from_millis(acquire_warn_ms);
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        let size = pool.size();
+        let idle = pool.num_idle() as u32;
+        gauge!("k_db_pool_max_connections").set(max_connections as f64);
+        gauge!("k_db_pool_size").set(size as f64);
+        gauge!("k_db_pool_idle").set(idle as f64);
+        gauge!("k_db_pool_in_use").set(size.saturating_sub(idle) as f64);
+
+        // Probe acquire: waits behind the same queue as the queries of the binary
+        let start = Instant::now();
+        let acquired = pool.acquire().await;
+        let wait = start.elapsed();
+        gauge!("k_db_pool_acquire_wait_seconds").set(wait.as_secs_f64());
+
+        match acquired {
+            Ok(_conn) => {
+                if wait > warn_threshold {
+                    warn!(
+                        "Database pool acquire took {}ms (pool size {}/{}, idle {})",
+                        wait.as_millis(),
+                        size,
+                        max_connections,
+                        idle
+                    );
+                }
+            }
+            Err(e) => {
+                counter!("k_db_pool_acquire_failures_total").increment(1);
+                warn!(
+                    "Database pool acquire failed after {}ms (pool size {}/{}, idle {}): {}",
+                    wait.as_millis(),
+                    size,
+                    max_connections,
+                    idle,
+                    e
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::failover::{self, DbHost, TargetSessionAttrs};
+use anyhow::Result;
+use sqlx::PgPool;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use tracing::{info, warn};
+
+/// Seconds between database connection / schema checks while waiting at startup
+pub const STARTUP_RETRY_SECS: u64 = 5;
+
+/// Connect a pool of `max_connections` to `connection_string`, retrying until reachable or until
+/// `wait_for_db_secs` (--wait-for-db) elapsed
+/// With several `hosts` (--db-host), each attempt connects to the first suitable for `attrs`
+pub async fn create_pool(
+    connection_string: &str,
+    hosts: &[DbHost],
+    attrs: TargetSessionAttrs,
+    max_connections: u32,
+    wait_for_db_secs: Option<u64>,
+) -> Result<PgPool> {
+    let connect_options: PgConnectOptions = connection_string.parse()?;
+    let target = if hosts.is_empty() {
+        format!(
+            "{}:{}",
+            connect_options.get_host(),
+            connect_options.get_port()
+        )
+    } else {
+        hosts
+            .iter()
+            .map(|host| host.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let started = std::time::Instant::now();
+    let mut attempt: u32 = 1;
+
+    loop {
+        let result = async {
+            let options = failover::select_host(&connect_options, hosts, attrs).await?;
+            let pool = PgPoolOptions::new()
+                .max_connections(max_connections)
+                .connect_with(options)
+                .await?;
+            // Test the pool connection
+            sqlx::query("SELECT 1").fetch_one(&pool).await?;
+            Ok::<_, sqlx::Error>(pool)
+        }
+        .await;
+
+        let e = match result {
+            Ok(pool) => {
+                info!("Database connection pool created and tested successfully");
+                return Ok(pool);
+            }
+            Err(e) => e,
+        };
+
+        let elapsed = started.elapsed().as_secs();
+        let retry_in = match wait_for_db_secs {
+            Some(limit) if elapsed >= limit => {
+                return Err(anyhow::anyhow!(
+                    "Database {} not reachable after {}s ({} attempts): {}",
+                    target,
+                    elapsed,
+                    attempt,
+                    e
+                ));
+            }
+            Some(limit) => {
+                warn!(
+                    "Waiting for database {} (attempt {}, {}s/{}s): {}",
+                    target, attempt, elapsed, limit, e
+                );
+                STARTUP_RETRY_SECS.min(limit - elapsed)
+            }
+            None => {
+                warn!(
+                    "Waiting for database {} (attempt {}, {}s elapsed): {}",
+                    target, attempt, elapsed, e
+                );
+                STARTUP_RETRY_SECS
+            }
+        };
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(retry_in)).await;
+        attempt += 1;
+    }
+}
+
+/// Check that K-transaction-processor has created the K schema (k_vars.schema_version)
+/// With --wait-for-schema, waits until it exists instead of failing
+pub async fn verify_k_schema(pool: &PgPool, wait_for_schema: bool) -> Result<()> {
+    let started = std::time::Instant::now();
+
+    loop {
+        let table_exists: bool = sqlx::query_scalar("SELECT to_regclass('k_vars') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+
+        let version: Option<String> = if table_exists {
+            sqlx::query_scalar("SELECT value FROM k_vars WHERE key = 'schema_version'")
+                .fetch_optional(pool)
+                .await?
+        } else {
+            None
+        };
+
+        if let Some(version) = version {
+            info!("✓ K schema v{} found", version);
+            return Ok(());
+        }
+
+        if !wait_for_schema {
+            return Err(anyhow::anyhow!(
+                "No K schema found - start K-transaction-processor first. Set flag '--wait-for-schema' to wait for it"
+            ));
+        }
+
+        warn!(
+            "Waiting for K-transaction-processor to create the K schema ({}s elapsed)",
+            started.elapsed().as_secs()
+        );
+        tokio::time::sleep(tokio::time::Duration::from_secs(STARTUP_RETRY_SECS)).await;
+    }
+}
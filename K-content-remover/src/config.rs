@@ -1,8 +1,13 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use k_common::ConfigErrors;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "K-content-remover - Remove all content created by a specific user", long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(short = 'H', long, default_value = "localhost", help = "Database host")]
     pub db_host: String,
 
@@ -36,12 +41,32 @@ pub struct Args {
     )]
     pub db_max_connections: usize,
 
+    #[arg(
+        long,
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    pub wait_for_db: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Wait for K-transaction-processor to create the K schema instead of failing"
+    )]
+    pub wait_for_schema: bool,
+
     #[arg(
         short = 't',
         long = "target-user",
+        required_unless_present = "erasure_request",
+        conflicts_with = "erasure_request",
         help = "Public key (hex string) of the user whose content should be removed"
     )]
-    pub target_user_pubkey: String,
+    pub target_user_pubkey: Option<String>,
+
+    #[arg(
+        long = "erasure-request",
+        help = "Carry out a pending erasure request (schema v15+): erase the subject's data, write tombstones and mark it completed"
+    )]
+    pub erasure_request: Option<i64>,
 
     #[arg(
         long = "dry-run",
@@ -57,9 +82,17 @@ pub struct Args {
     pub skip_confirmation: bool,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check database connectivity, extensions, indexes, schema version, notification channel
+    /// and disk space, print a report and exit (non-zero exit code if a check fails)
+    Doctor,
+}
+
 pub struct AppConfig {
     pub database: DatabaseConfig,
-    pub target_user_pubkey: String,
+    pub target_user_pubkey: Option<String>,
+    pub erasure_request_id: Option<i64>,
     pub dry_run: bool,
     pub skip_confirmation: bool,
 }
@@ -71,11 +104,29 @@ pub struct DatabaseConfig {
     pub user: String,
     pub password: String,
     pub max_connections: usize,
+    /// Startup wait for PostgreSQL to become reachable (None = wait indefinitely)
+    pub wait_for_db_secs: Option<u64>,
 }
 
 impl AppConfig {
-    pub fn from_args(args: &Args) -> Self {
-        Self {
+    pub fn from_args(args: &Args) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        if let Some(pubkey) = &args.target_user_pubkey
+            && (pubkey.len() != 66 || hex::decode(pubkey).is_err())
+        {
+            errors.push(format!(
+                "Invalid --target-user '{}'. Must be a public key of 66 hex characters",
+                pubkey
+            ));
+        }
+        if let Some(request_id) = args.erasure_request {
+            errors.at_least("--erasure-request", request_id, 1);
+        }
+        errors.at_least("--db-max-connections", args.db_max_connections, 1);
+        errors.into_result()?;
+
+        Ok(Self {
             database: DatabaseConfig {
                 host: args.db_host.clone(),
                 port: args.db_port,
@@ -83,11 +134,13 @@ impl AppConfig {
                 user: args.db_user.clone(),
                 password: args.db_password.clone(),
                 max_connections: args.db_max_connections,
+                wait_for_db_secs: args.wait_for_db,
             },
             target_user_pubkey: args.target_user_pubkey.clone(),
+            erasure_request_id: args.erasure_request,
             dry_run: args.dry_run,
             skip_confirmation: args.skip_confirmation,
-        }
+        })
     }
 
     pub fn connection_string(&self) -> String {
@@ -0,0 +1,118 @@
+use crate::config::AppConfig;
+use anyhow::Result;
+use k_common::doctor::{
+    Report, Status, check_database_disk, check_extensions, check_notification_channel, connect,
+};
+use sqlx::{PgPool, Row};
+
+/// Indexes the removal looks the target user's rows up by
+const REMOVAL_INDEXES: &[&str] = &[
+    "idx_k_contents_sender_pubkey",
+    "idx_k_votes_sender_pubkey",
+    "idx_k_broadcasts_sender_pubkey",
+    "idx_k_blocks_sender_pubkey",
+    "idx_k_follows_sender_pubkey",
+];
+
+/// Check database connectivity, extensions, indexes, schema version, notification channel and
+/// disk space, printing one line per check
+pub async fn run(config: &AppConfig) -> Result<()> {
+    let mut report = Report::new();
+    println!("K-content-remover v{} doctor", env!("CARGO_PKG_VERSION"));
+
+    let database = &config.database;
+    let Some(pool) = connect(
+        &mut report,
+        "Database",
+        &config.connection_string(),
+        &format!("{}:{}/{}", database.host, database.port, database.database),
+        1,
+    )
+    .await
+    else {
+        return report.finish();
+    };
+
+    check_extensions(&mut report, &pool).await;
+    check_schema(&mut report, &pool).await;
+    check_indexes(&mut report, &pool).await;
+    check_notification_channel(&mut report, &pool).await;
+    check_database_disk(&mut report, &pool, &config.database.host).await;
+
+    pool.close().await;
+    report.finish()
+}
+
+/// Schema version created by K-transaction-processor (as checked at startup)
+async fn check_schema(report: &mut Report, pool: &PgPool) {
+    let version =
+        sqlx::query_scalar::<_, String>("SELECT value FROM k_vars WHERE key = 'schema_version'")
+            .fetch_optional(pool)
+            .await;
+    match version {
+        Ok(Some(version)) => report.check(Status::Ok, "Schema version", format!("v{}", version)),
+        Ok(None) | Err(_) => report.check(
+            Status::Fail,
+            "Schema version",
+            "no K schema found (start K-transaction-processor first)",
+        ),
+    }
+}
+
+/// Indexes of the removal queries must exist and be valid (a failed CREATE INDEX CONCURRENTLY
+/// leaves an invalid index that is never used)
+async fn check_indexes(report: &mut Report, pool: &PgPool) {
+    let expected = REMOVAL_INDEXES;
+
+    let indexes = sqlx::query(
+        r#"
+        SELECT c.relname::text AS name, i.indisvalid AS valid
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        WHERE c.relname::text = ANY($1)
+        "#,
+    )
+    .bind(expected)
+    .fetch_all(pool)
+    .await;
+
+    match indexes {
+        Ok(rows) => {
+            let missing: Vec<&str> = expected
+                .iter()
+                .copied()
+                .filter(|index| {
+                    !rows
+                        .iter()
+                        .any(|row| row.get::<String, _>("name") == *index)
+                })
+                .collect();
+            let invalid: Vec<String> = rows
+                .iter()
+                .filter(|row| !row.get::<bool, _>("valid"))
+                .map(|row| row.get("name"))
+                .collect();
+
+            if !missing.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("missing: {}", missing.join(", ")),
+                );
+            } else if !invalid.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("invalid (rebuild them): {}", invalid.join(", ")),
+                );
+            } else {
+                report.check(
+                    Status::Ok,
+                    "Indexes",
+                    format!("{} removal indexes", expected.len()),
+                );
+            }
+        }
+        Err(e) => report.check(Status::Fail, "Indexes", e.to_string()),
+    }
+}
@@ -0,0 +1,321 @@
+use crate::removal_operation::release_profile_images;
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::info;
+
+/// A K table holding data of the erasure subject ($1 = subject pubkey)
+struct ErasureTable {
+    table: &'static str,
+    filter: &'static str,
+    // Rows come from the subject's own transactions, which get tombstones
+    tombstone: bool,
+}
+
+/// Everything sent by the subject, in deletion order (rows referencing k_contents first)
+/// Groups and events created by the subject are erased with their members, posts and RSVPs
+const ERASURE_TABLES: &[ErasureTable] = &[
+    ErasureTable {
+        table: "k_post_views",
+        filter: "content_id IN (SELECT transaction_id FROM k_contents WHERE sender_pubkey = $1)",
+        tombstone: false,
+    },
+    ErasureTable {
+        table: "k_mentions",
+        filter: "sender_pubkey = $1",
+        tombstone: false,
+    },
+    ErasureTable {
+        table: "k_hashtags",
+        filter: "sender_pubkey = $1",
+        tombstone: false,
+    },
+    ErasureTable {
+        table: "k_tips",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_article_chunks",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_articles",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_event_rsvps",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_events",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_group_posts",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_group_members",
+        filter: "member_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_groups",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_votes",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_blocks",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_follows",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_contents",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_mentions_archive",
+        filter: "sender_pubkey = $1",
+        tombstone: false,
+    },
+    ErasureTable {
+        table: "k_contents_archive",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_contents_cold",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_impersonation_flags",
+        filter: "suspect_pubkey = $1 OR target_pubkey = $1",
+        tombstone: false,
+    },
+    ErasureTable {
+        table: "k_profile_history",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+    ErasureTable {
+        table: "k_broadcasts",
+        filter: "sender_pubkey = $1",
+        tombstone: true,
+    },
+];
+
+#[derive(Debug)]
+pub struct ErasureStats {
+    // Rows per table (matched in preview, deleted in execution)
+    pub tables: Vec<(&'static str, i64)>,
+    pub tombstones: i64,
+}
+
+impl ErasureStats {
+    pub fn total(&self) -> i64 {
+        self.tables.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Load the subject pubkey of a pending erasure request
+pub async fn load_pending_request(pool: &PgPool, request_id: i64) -> Result<Vec<u8>> {
+    let row = sqlx::query("SELECT subject_pubkey, status FROM k_erasure_requests WHERE id = $1")
+        .bind(request_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Erasure request {} not found", request_id))?;
+
+    let status: String = row.get("status");
+    if status != "pending" {
+        return Err(anyhow::anyhow!(
+            "Erasure request {} is already {}",
+            request_id,
+            status
+        ));
+    }
+
+    Ok(row.get("subject_pubkey"))
+}
+
+/// Preview what the erasure would delete without deleting anything
+pub async fn preview_erasure(pool: &PgPool, subject_pubkey: &[u8]) -> Result<ErasureStats> {
+    info!(
+        "Previewing erasure for user: {}",
+        hex::encode(subject_pubkey)
+    );
+
+    let mut tables = Vec::with_capacity(ERASURE_TABLES.len());
+    let mut tombstones = 0;
+    for erasure_table in present_tables(pool).await? {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            erasure_table.table, erasure_table.filter
+        ))
+        .bind(subject_pubkey)
+        .fetch_one(pool)
+        .await?;
+
+        if erasure_table.tombstone {
+            tombstones += count;
+        }
+        tables.push((erasure_table.table, count));
+    }
+
+    let stats = ErasureStats { tables, tombstones };
+
+    info!("Preview results:");
+    for (table, count) in &stats.tables {
+        info!("  - {:<17} {} records", format!("{}:", table), count);
+    }
+    info!("  Total records to be deleted: {}", stats.total());
+    info!(
+        "  Transactions to be tombstoned (at most): {}",
+        stats.tombstones
+    );
+
+    Ok(stats)
+}
+
+/// Execute an erasure request in a single transaction: tombstone the subject's transactions,
+/// delete their rows and mark the request completed with the removal report
+pub async fn execute_erasure(
+    pool: &PgPool,
+    request_id: i64,
+    subject_pubkey: &[u8],
+) -> Result<ErasureStats> {
+    info!(
+        "Starting erasure request {} for user: {}",
+        request_id,
+        hex::encode(subject_pubkey)
+    );
+
+    let erased_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+
+    let erasure_tables = present_tables(pool).await?;
+    let mut tx = pool.begin().await?;
+
+    // Tombstones first: some rows are removed by cascades of later deletions
+    let mut tombstones = 0;
+    for erasure_table in erasure_tables.iter().filter(|table| table.tombstone) {
+        tombstones += write_tombstones(
+            &mut tx,
+            erasure_table,
+            subject_pubkey,
+            request_id,
+            erased_at,
+        )
+        .await?;
+    }
+
+    let mut tables = Vec::with_capacity(erasure_tables.len());
+    for erasure_table in &erasure_tables {
+        if erasure_table.table == "k_broadcasts" {
+            release_profile_images(&mut tx, subject_pubkey, erased_at).await?;
+        }
+        let deleted = sqlx::query(&format!(
+            "DELETE FROM {} WHERE {}",
+            erasure_table.table, erasure_table.filter
+        ))
+        .bind(subject_pubkey)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+
+        tables.push((erasure_table.table, deleted));
+    }
+
+    let stats = ErasureStats { tables, tombstones };
+
+    let completed = sqlx::query(
+        r#"
+        UPDATE k_erasure_requests
+        SET status = 'completed', completed_at = $2, removed_rows = $3, tombstoned_transactions = $4
+        WHERE id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(request_id)
+    .bind(erased_at)
+    .bind(stats.total())
+    .bind(stats.tombstones)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    if completed == 0 {
+        // Dropping the transaction rolls everything back
+        return Err(anyhow::anyhow!(
+            "Erasure request {} was completed concurrently, nothing erased",
+            request_id
+        ));
+    }
+
+    tx.commit().await?;
+
+    info!("✓ Erasure completed successfully:");
+    for (table, count) in &stats.tables {
+        info!("  - Deleted {} records from {}", count, table);
+    }
+    info!("  Total records deleted: {}", stats.total());
+    info!("  Transactions tombstoned: {}", stats.tombstones);
+
+    Ok(stats)
+}
+
+/// ERASURE_TABLES present in the database (archive tables only exist from schema v19,
+/// profile history from v20)
+async fn present_tables(pool: &PgPool) -> Result<Vec<&'static ErasureTable>> {
+    let mut present = Vec::with_capacity(ERASURE_TABLES.len());
+    for erasure_table in ERASURE_TABLES {
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(erasure_table.table)
+            .fetch_one(pool)
+            .await?;
+        if exists {
+            present.push(erasure_table);
+        }
+    }
+    Ok(present)
+}
+
+/// Record the subject's transactions of one table in k_tombstones
+async fn write_tombstones(
+    conn: &mut PgConnection,
+    erasure_table: &ErasureTable,
+    subject_pubkey: &[u8],
+    request_id: i64,
+    erased_at: i64,
+) -> Result<i64> {
+    let written = sqlx::query(&format!(
+        "INSERT INTO k_tombstones (transaction_id, erasure_request_id, erased_at) \
+         SELECT transaction_id, $2, $3 FROM {} WHERE {} \
+         ON CONFLICT (transaction_id) DO NOTHING",
+        erasure_table.table, erasure_table.filter
+    ))
+    .bind(subject_pubkey)
+    .bind(request_id)
+    .bind(erased_at)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    Ok(written as i64)
+}
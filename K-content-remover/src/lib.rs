@@ -0,0 +1,174 @@
+mod config;
+mod doctor;
+mod erasure;
+mod removal_operation;
+
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+pub use config::Args;
+use config::{AppConfig, Command};
+use erasure::{execute_erasure, load_pending_request, preview_erasure};
+use k_common::failover::TargetSessionAttrs;
+use k_common::startup::{self, verify_k_schema};
+use removal_operation::{execute_removal, preview_removal};
+
+/// Run with the options of `args` (tracing is initialized by the caller: K-content-remover or the
+/// k-indexer CLI)
+pub async fn run(args: Args) -> Result<()> {
+    info!("Starting K-content-remover v{}", env!("CARGO_PKG_VERSION"));
+
+    // Load configuration from CLI arguments
+    let config = match AppConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+    if let Some(Command::Doctor) = args.command {
+        return doctor::run(&config).await;
+    }
+
+    // Decode target user pubkey from hex (not set when carrying out an erasure request)
+    let target_user_pubkey = match &config.target_user_pubkey {
+        Some(pubkey_hex) => {
+            let pubkey = hex::decode(pubkey_hex).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid target user public key hex string '{}': {}",
+                    pubkey_hex,
+                    e
+                )
+            })?;
+            info!("Target user pubkey: {}", pubkey_hex);
+            Some(pubkey)
+        }
+        None => None,
+    };
+
+    info!(
+        "Database connection: {}:{}/{}",
+        config.database.host, config.database.port, config.database.database
+    );
+
+    // Create database connection pool
+    let db_pool = startup::create_pool(
+        &config.connection_string(),
+        &[],
+        TargetSessionAttrs::Any,
+        config.database.max_connections as u32,
+        config.database.wait_for_db_secs,
+    )
+    .await?;
+    info!(
+        "Database connection pool created with {} max connections",
+        config.database.max_connections
+    );
+    verify_k_schema(&db_pool, args.wait_for_schema).await?;
+
+    let target_user_pubkey = match (target_user_pubkey, config.erasure_request_id) {
+        (_, Some(request_id)) => return run_erasure(&db_pool, &config, request_id).await,
+        (Some(pubkey), None) => pubkey,
+        (None, None) => return Err(anyhow::anyhow!("--target-user is required")),
+    };
+    let target_user_hex = hex::encode(&target_user_pubkey);
+
+    // Preview what will be deleted
+    info!("========== Analyzing content to remove ==========");
+    let preview_stats = preview_removal(&db_pool, &target_user_pubkey).await?;
+
+    if preview_stats.is_empty() {
+        info!("No content found for user {}", target_user_hex);
+        info!("Nothing to remove. Exiting.");
+        return Ok(());
+    }
+
+    // If dry-run mode, exit after preview
+    if config.dry_run {
+        info!("========== DRY RUN MODE - No changes made ==========");
+        info!("Run without --dry-run flag to actually delete the content.");
+        return Ok(());
+    }
+
+    // Confirmation prompt (unless --yes flag is provided)
+    if !config.skip_confirmation && !confirm_deletion(preview_stats.total(), &target_user_hex)? {
+        info!("Deletion cancelled by user. Exiting.");
+        return Ok(());
+    }
+
+    // Execute the removal
+    info!("========== Executing content removal ==========");
+    let removal_stats = execute_removal(&db_pool, &target_user_pubkey).await?;
+
+    if removal_stats.total() > 0 {
+        info!("========== Content removal completed successfully ==========");
+        info!(
+            "Removed {} total records for user {}",
+            removal_stats.total(),
+            target_user_hex
+        );
+    } else {
+        warn!(
+            "No records were deleted (this is unexpected - preview showed {} records)",
+            preview_stats.total()
+        );
+    }
+
+    Ok(())
+}
+
+/// Carry out a pending erasure request: same preview/confirmation as a removal,
+/// then tombstones, deletion and completion of the request in one transaction
+async fn run_erasure(db_pool: &PgPool, config: &AppConfig, request_id: i64) -> Result<()> {
+    let subject_pubkey = load_pending_request(db_pool, request_id).await?;
+    let subject_hex = hex::encode(&subject_pubkey);
+    info!("Erasure request {} for user {}", request_id, subject_hex);
+
+    info!("========== Analyzing content to erase ==========");
+    let preview_stats = preview_erasure(db_pool, &subject_pubkey).await?;
+
+    if config.dry_run {
+        info!("========== DRY RUN MODE - No changes made ==========");
+        info!("Run without --dry-run flag to actually erase the content.");
+        return Ok(());
+    }
+
+    // The request is completed even when nothing is left to delete
+    if !config.skip_confirmation && !confirm_deletion(preview_stats.total(), &subject_hex)? {
+        info!("Erasure cancelled by user. Exiting.");
+        return Ok(());
+    }
+
+    info!("========== Executing erasure ==========");
+    let erasure_stats = execute_erasure(db_pool, request_id, &subject_pubkey).await?;
+
+    info!("========== Erasure completed successfully ==========");
+    info!(
+        "Erasure request {} completed: {} records removed, {} transactions tombstoned",
+        request_id,
+        erasure_stats.total(),
+        erasure_stats.tombstones
+    );
+
+    Ok(())
+}
+
+/// Ask the operator to type DELETE before deleting anything
+fn confirm_deletion(total: i64, target_user_hex: &str) -> Result<bool> {
+    warn!("========== CONFIRMATION REQUIRED ==========");
+    warn!(
+        "You are about to DELETE {} records from the database!",
+        total
+    );
+    warn!("This operation CANNOT be undone!");
+    warn!("Target user: {}", target_user_hex);
+    warn!("");
+    warn!("Type 'DELETE' (all caps) to confirm, or anything else to cancel:");
+
+    // Read user input from stdin
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim() == "DELETE")
+}
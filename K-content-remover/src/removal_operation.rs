@@ -1,5 +1,5 @@
 use anyhow::Result;
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, PgPool, Row};
 use tracing::info;
 
 #[derive(Debug)]
@@ -145,6 +145,11 @@ pub async fn execute_removal(pool: &PgPool, target_user_pubkey: &[u8]) -> Result
     // Deletion is based ONLY on sender_pubkey matching
     let mut tx = pool.begin().await?;
 
+    let released_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+    release_profile_images(&mut tx, target_user_pubkey, released_at).await?;
+
     let result = sqlx::query(
         r#"
         WITH deleted_mentions AS (
@@ -224,3 +229,40 @@ pub async fn execute_removal(pool: &PgPool, target_user_pubkey: &[u8]) -> Result
 
     Ok(stats)
 }
+
+/// Release the profile image references held by the user's broadcasts, right before they are
+/// deleted (k_profile_image_blobs only exists from schema v25)
+/// K-transaction-processor deletes blobs that stay without references
+pub async fn release_profile_images(
+    conn: &mut PgConnection,
+    target_user_pubkey: &[u8],
+    released_at: i64,
+) -> Result<()> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT to_regclass('k_profile_image_blobs') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await?;
+    if !exists {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE k_profile_image_blobs b
+        SET ref_count = b.ref_count - r.refs,
+            orphaned_at = CASE WHEN b.ref_count - r.refs <= 0 THEN $2 END
+        FROM (
+            SELECT profile_image_ref, COUNT(*) AS refs
+            FROM k_broadcasts
+            WHERE sender_pubkey = $1 AND profile_image_ref IS NOT NULL
+            GROUP BY profile_image_ref
+        ) r
+        WHERE b.blob_key = r.profile_image_ref
+        "#,
+    )
+    .bind(target_user_pubkey)
+    .bind(released_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
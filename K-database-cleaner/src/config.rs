@@ -1,8 +1,15 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use k_common::ConfigErrors;
+
+/// Shortest --data-retention: content of non-followed users is kept at least this many hours
+const MIN_DATA_RETENTION_HOURS: u64 = 1;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "K-database-cleaner", long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(short = 'H', long, default_value = "localhost", help = "Database host")]
     pub db_host: String,
 
@@ -36,6 +43,18 @@ pub struct Args {
     )]
     pub db_max_connections: usize,
 
+    #[arg(
+        long,
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    pub wait_for_db: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Wait for K-transaction-processor to create the K schema instead of failing"
+    )]
+    pub wait_for_schema: bool,
+
     #[arg(
         short = 'u',
         long = "user",
@@ -58,6 +77,35 @@ pub struct Args {
         help = "Data retention time in hours for non-followed users' content"
     )]
     pub data_retention_hours: u64,
+
+    #[arg(
+        long = "retention-action",
+        default_value = "delete",
+        value_parser = ["delete", "archive"],
+        help = "What to do with expired content: 'delete' or 'archive' (move to k_contents_archive, requires schema v34)"
+    )]
+    pub retention_action: String,
+
+    #[arg(
+        long = "cold-tier-after",
+        help = "Move content older than this many days to the k_contents_cold tier (requires schema v33, default: keep all content hot)"
+    )]
+    pub cold_tier_after_days: Option<u64>,
+
+    #[arg(
+        long = "cold-tablespace",
+        requires = "cold_tier_after_days",
+        help = "Existing tablespace (e.g. on cheaper storage) k_contents_cold and its indexes are moved to"
+    )]
+    pub cold_tablespace: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check the configuration, database connectivity, extensions, indexes, schema version,
+    /// notification channel and disk space, print a report and exit (non-zero exit code if a
+    /// check fails)
+    Doctor,
 }
 
 pub struct AppConfig {
@@ -65,6 +113,19 @@ pub struct AppConfig {
     pub user_pubkey: String,
     pub purge_interval: u64,
     pub data_retention_hours: u64,
+    pub retention_action: RetentionAction,
+    /// Age in days after which content moves to k_contents_cold (None = no cold tier)
+    pub cold_tier_after_days: Option<u64>,
+    pub cold_tablespace: Option<String>,
+}
+
+/// What the data retention rule does with expired content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionAction {
+    /// Delete the rows
+    Delete,
+    /// Move the rows to k_contents_archive / k_mentions_archive
+    Archive,
 }
 
 pub struct DatabaseConfig {
@@ -74,11 +135,33 @@ pub struct DatabaseConfig {
     pub user: String,
     pub password: String,
     pub max_connections: usize,
+    /// Startup wait for PostgreSQL to become reachable (None = wait indefinitely)
+    pub wait_for_db_secs: Option<u64>,
 }
 
 impl AppConfig {
-    pub fn from_args(args: &Args) -> Self {
-        Self {
+    pub fn from_args(args: &Args) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        if args.user_pubkey.len() != 66 || hex::decode(&args.user_pubkey).is_err() {
+            errors.push(format!(
+                "Invalid --user '{}'. Must be a public key of 66 hex characters",
+                args.user_pubkey
+            ));
+        }
+        errors.at_least("--db-max-connections", args.db_max_connections, 1);
+        errors.at_least("--purge-interval", args.purge_interval, 1);
+        errors.at_least(
+            "--data-retention",
+            args.data_retention_hours,
+            MIN_DATA_RETENTION_HOURS,
+        );
+        if let Some(cold_tier_after_days) = args.cold_tier_after_days {
+            errors.at_least("--cold-tier-after", cold_tier_after_days, 1);
+        }
+        errors.into_result()?;
+
+        Ok(Self {
             database: DatabaseConfig {
                 host: args.db_host.clone(),
                 port: args.db_port,
@@ -86,11 +169,18 @@ impl AppConfig {
                 user: args.db_user.clone(),
                 password: args.db_password.clone(),
                 max_connections: args.db_max_connections,
+                wait_for_db_secs: args.wait_for_db,
             },
             user_pubkey: args.user_pubkey.clone(),
             purge_interval: args.purge_interval,
             data_retention_hours: args.data_retention_hours,
-        }
+            retention_action: match args.retention_action.as_str() {
+                "archive" => RetentionAction::Archive,
+                _ => RetentionAction::Delete,
+            },
+            cold_tier_after_days: args.cold_tier_after_days,
+            cold_tablespace: args.cold_tablespace.clone(),
+        })
     }
 
     pub fn connection_string(&self) -> String {
@@ -104,3 +194,44 @@ impl AppConfig {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "02e4774cdda0793f86414e8b9140bb6db4b98e6d4405ba9c3d60673e5fc9bc39be";
+
+    fn config(extra_args: &[&str]) -> Result<AppConfig, ConfigErrors> {
+        let args = Args::try_parse_from(
+            ["K-database-cleaner", "--user", USER]
+                .iter()
+                .chain(extra_args),
+        )
+        .unwrap();
+        AppConfig::from_args(&args)
+    }
+
+    #[test]
+    fn test_retention_action() {
+        assert_eq!(
+            config(&[]).unwrap().retention_action,
+            RetentionAction::Delete
+        );
+        assert_eq!(
+            config(&["--retention-action", "archive"])
+                .unwrap()
+                .retention_action,
+            RetentionAction::Archive
+        );
+        assert!(
+            Args::try_parse_from([
+                "K-database-cleaner",
+                "--user",
+                USER,
+                "--retention-action",
+                "move"
+            ])
+            .is_err()
+        );
+    }
+}
@@ -1,41 +1,215 @@
-use crate::config::AppConfig;
 use anyhow::Result;
-use sqlx::{PgPool, postgres::PgPoolOptions};
-use tracing::{info, warn};
+use sqlx::{PgConnection, PgPool};
+use tracing::info;
 
 pub type DbPool = PgPool;
 
-pub async fn create_pool(config: &AppConfig) -> Result<DbPool> {
-    let connection_string = config.connection_string();
-
-    loop {
-        match PgPoolOptions::new()
-            .max_connections(config.database.max_connections as u32)
-            .connect(&connection_string)
-            .await
-        {
-            Ok(pool) => {
-                // Test the pool connection
-                match sqlx::query("SELECT 1").fetch_one(&pool).await {
-                    Ok(_) => {
-                        info!("Database connection pool created and tested successfully");
-                        return Ok(pool);
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Database connection pool created but test query failed: {}",
-                            e
-                        );
-                        warn!("Retrying in 10 seconds...");
-                        tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to create database connection pool: {}", e);
-                warn!("Retrying in 10 seconds...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+/// Advisory lock key coordinating purges with K-transaction-processor worker batches ("K_PURGE")
+/// Must match PURGE_LOCK_KEY in K-transaction-processor
+pub const PURGE_LOCK_KEY: i64 = 0x004b_5f50_5552_4745;
+
+/// Take the purge lock exclusively until the end of the current transaction
+/// Waits for running worker batches (which hold it shared) and holds back new ones,
+/// so a purge never interleaves with a batch reading or referencing the purged rows
+pub async fn lock_out_workers(conn: &mut PgConnection) -> Result<()> {
+    let started = std::time::Instant::now();
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(PURGE_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let waited = started.elapsed();
+    if waited.as_secs() >= 1 {
+        info!(
+            "Waited {:.1}s for running worker batches to finish",
+            waited.as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+/// Check that the archive tables exist with the columns added to the live tables since (schema
+/// v34) before archiving instead of deleting
+pub async fn verify_archive_tables(pool: &DbPool) -> Result<()> {
+    let archive_exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM information_schema.columns
+            WHERE table_schema = current_schema()
+              AND table_name = 'k_contents_archive'
+              AND column_name = 'render_spans'
+        )
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if !archive_exists {
+        return Err(anyhow::anyhow!(
+            "--retention-action archive requires K schema v34 (archive tables with every content column) - upgrade K-transaction-processor first"
+        ));
+    }
+
+    info!("✓ Archive tables found, expired content will be archived");
+    Ok(())
+}
+
+/// Check that the cold tier exists with the thread roots of replies (schema v33) before moving
+/// old content to it, and move k_contents_cold with its indexes to `tablespace` when it is not
+/// there yet
+pub async fn prepare_cold_tier(pool: &DbPool, tablespace: Option<&str>) -> Result<()> {
+    let cold_exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM information_schema.columns
+            WHERE table_schema = current_schema()
+              AND table_name = 'k_contents_cold'
+              AND column_name = 'root_post_id'
+        )
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if !cold_exists {
+        return Err(anyhow::anyhow!(
+            "--cold-tier-after requires K schema v33 (k_contents_cold with thread roots) - upgrade K-transaction-processor first"
+        ));
+    }
+
+    if let Some(tablespace) = tablespace {
+        // NULL = the database default tablespace
+        let current: Option<String> = sqlx::query_scalar(
+            "SELECT tablespace::text FROM pg_tables WHERE tablename = 'k_contents_cold'",
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if current.as_deref() != Some(tablespace) {
+            info!(
+                "Moving k_contents_cold to tablespace '{}' (locks the table until done)",
+                tablespace
+            );
+            let quoted = quote_identifier(tablespace);
+            sqlx::query(&format!(
+                "ALTER TABLE k_contents_cold SET TABLESPACE {}",
+                quoted
+            ))
+            .execute(pool)
+            .await?;
+
+            let indexes: Vec<String> = sqlx::query_scalar(
+                "SELECT indexname::text FROM pg_indexes WHERE tablename = 'k_contents_cold'",
+            )
+            .fetch_all(pool)
+            .await?;
+            for index in &indexes {
+                sqlx::query(&format!(
+                    "ALTER INDEX {} SET TABLESPACE {}",
+                    quote_identifier(index),
+                    quoted
+                ))
+                .execute(pool)
+                .await?;
             }
         }
     }
+
+    info!("✓ Cold tier table found, old content will be moved to k_contents_cold");
+    Ok(())
+}
+
+/// Relation with every indexed content: k_contents_tiered (hot and cold) once the cold tier
+/// exists, so replies and votes on cold content are not taken for orphans
+pub async fn all_contents_relation(conn: &mut PgConnection) -> Result<&'static str> {
+    let tiered_exists: bool =
+        sqlx::query_scalar("SELECT to_regclass('k_contents_tiered') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await?;
+
+    Ok(if tiered_exists {
+        "k_contents_tiered"
+    } else {
+        "k_contents"
+    })
+}
+
+/// CTE deleting the k_contents_cold rows matching `filter` (named deleted_cold_contents) and the
+/// expression counting them, so purges also reach content moved by operation 6
+/// Without a cold tier (`contents` is k_contents) there is no CTE and the count is 0
+pub fn cold_contents_delete(contents: &str, filter: &str) -> (String, &'static str) {
+    if contents != "k_contents_tiered" {
+        return (String::new(), "0");
+    }
+
+    (
+        format!(
+            r#"
+        deleted_cold_contents AS (
+            DELETE FROM k_contents_cold
+            WHERE {}
+            RETURNING id
+        ),"#,
+            filter
+        ),
+        "(SELECT COUNT(*) FROM deleted_cold_contents)",
+    )
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+
+    /// Pool on the K schema database of K_TEST_DATABASE_URL (None: the test is skipped)
+    pub async fn test_pool() -> Option<PgPool> {
+        let Ok(database_url) = std::env::var("K_TEST_DATABASE_URL") else {
+            eprintln!("K_TEST_DATABASE_URL is not set, skipping the database test");
+            return None;
+        };
+        Some(PgPool::connect(&database_url).await.unwrap())
+    }
+
+    /// Pubkey no other test row uses
+    pub fn unique_pubkey() -> Vec<u8> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let mut pubkey = vec![0x02; 33];
+        pubkey[1..17].copy_from_slice(&nanos.to_be_bytes());
+        pubkey
+    }
+
+    /// Insert a post of `sender` into k_contents_cold, returning its transaction_id
+    pub async fn insert_cold_post(pool: &PgPool, sender: &[u8], block_time: i64) -> Vec<u8> {
+        let transaction_id = unique_pubkey()[1..].to_vec();
+        sqlx::query(
+            "INSERT INTO k_contents_cold (id, transaction_id, block_time, sender_pubkey,
+                 sender_signature, base64_encoded_message, content_type)
+             VALUES (nextval(pg_get_serial_sequence('k_contents', 'id')), $1, $2, $3, $1,
+                 'cG9zdA==', 'post')",
+        )
+        .bind(&transaction_id)
+        .bind(block_time)
+        .bind(sender)
+        .execute(pool)
+        .await
+        .unwrap();
+        transaction_id
+    }
+
+    /// Whether k_contents_cold holds `transaction_id`
+    pub async fn cold_post_exists(pool: &PgPool, transaction_id: &[u8]) -> bool {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM k_contents_cold WHERE transaction_id = $1)",
+        )
+        .bind(transaction_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
 }
@@ -0,0 +1,215 @@
+use crate::config::{AppConfig, RetentionAction};
+use anyhow::Result;
+use k_common::doctor::{
+    Report, Status, check_database_disk, check_extensions, check_notification_channel, connect,
+};
+use sqlx::{PgPool, Row};
+
+/// Indexes the purge operations look rows up by
+const PURGE_INDEXES: &[&str] = &[
+    "idx_k_blocks_sender_pubkey",
+    "idx_k_follows_sender_pubkey",
+    "idx_k_contents_transaction_id",
+    "idx_k_contents_sender_pubkey",
+    "idx_k_contents_block_time",
+    "idx_k_mentions_content_id",
+    "idx_k_votes_sender_pubkey",
+    "idx_k_votes_post_id",
+];
+
+/// Indexes of the archive tables written by --retention-action archive (schema v19)
+const ARCHIVE_INDEXES: &[&str] = &[
+    "idx_k_contents_archive_sender_pubkey",
+    "idx_k_mentions_archive_content_id",
+];
+
+/// Indexes of the cold tier written by --cold-tier-after (schema v24, full-text search v31,
+/// thread roots v33)
+const COLD_TIER_INDEXES: &[&str] = &[
+    "idx_k_contents_cold_block_time",
+    "idx_k_contents_cold_referenced",
+    "idx_k_contents_cold_search_vector",
+    "idx_k_contents_cold_thread",
+];
+
+/// Check the configuration, database connectivity, extensions, indexes, schema version,
+/// notification channel and disk space, printing one line per check
+pub async fn run(config: &AppConfig) -> Result<()> {
+    let mut report = Report::new();
+    println!("K-database-cleaner v{} doctor", env!("CARGO_PKG_VERSION"));
+
+    match hex::decode(&config.user_pubkey) {
+        Ok(_) => report.check(Status::Ok, "User", &config.user_pubkey),
+        Err(e) => report.check(Status::Fail, "User", format!("--user is not hex: {}", e)),
+    }
+
+    let database = &config.database;
+    let Some(pool) = connect(
+        &mut report,
+        "Database",
+        &config.connection_string(),
+        &format!("{}:{}/{}", database.host, database.port, database.database),
+        1,
+    )
+    .await
+    else {
+        return report.finish();
+    };
+
+    check_extensions(&mut report, &pool).await;
+    check_schema(&mut report, &pool, config).await;
+    check_indexes(&mut report, &pool, config).await;
+    check_notification_channel(&mut report, &pool).await;
+    check_database_disk(&mut report, &pool, &config.database.host).await;
+
+    pool.close().await;
+    report.finish()
+}
+
+/// Schema version created by K-transaction-processor, and the tables the configured
+/// retention action and cold tier need (as checked at startup)
+async fn check_schema(report: &mut Report, pool: &PgPool, config: &AppConfig) {
+    let version =
+        sqlx::query_scalar::<_, String>("SELECT value FROM k_vars WHERE key = 'schema_version'")
+            .fetch_optional(pool)
+            .await;
+    match version {
+        Ok(Some(version)) => report.check(Status::Ok, "Schema version", format!("v{}", version)),
+        Ok(None) | Err(_) => {
+            report.check(
+                Status::Fail,
+                "Schema version",
+                "no K schema found (start K-transaction-processor first)",
+            );
+            return;
+        }
+    }
+
+    if config.retention_action == RetentionAction::Archive {
+        match column_exists(pool, "k_contents_archive", "render_spans").await {
+            Ok(true) => report.check(Status::Ok, "Archive tables", "k_contents_archive found"),
+            Ok(false) => report.check(
+                Status::Fail,
+                "Archive tables",
+                "--retention-action archive requires K schema v34",
+            ),
+            Err(e) => report.check(Status::Fail, "Archive tables", e.to_string()),
+        }
+    }
+
+    if config.cold_tier_after_days.is_some() {
+        match table_exists(pool, "k_contents_cold").await {
+            Ok(true) => report.check(Status::Ok, "Cold tier", "k_contents_cold found"),
+            Ok(false) => report.check(
+                Status::Fail,
+                "Cold tier",
+                "--cold-tier-after requires K schema v33",
+            ),
+            Err(e) => report.check(Status::Fail, "Cold tier", e.to_string()),
+        }
+    }
+
+    if let Some(tablespace) = &config.cold_tablespace {
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM pg_tablespace WHERE spcname = $1)",
+        )
+        .bind(tablespace)
+        .fetch_one(pool)
+        .await;
+        match exists {
+            Ok(true) => report.check(Status::Ok, "Cold tablespace", tablespace),
+            Ok(false) => report.check(
+                Status::Fail,
+                "Cold tablespace",
+                format!("tablespace '{}' does not exist", tablespace),
+            ),
+            Err(e) => report.check(Status::Fail, "Cold tablespace", e.to_string()),
+        }
+    }
+}
+
+async fn table_exists(pool: &PgPool, table: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(table)
+        .fetch_one(pool)
+        .await
+}
+
+async fn column_exists(pool: &PgPool, table: &str, column: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM information_schema.columns
+            WHERE table_schema = current_schema() AND table_name = $1 AND column_name = $2
+        )
+        "#,
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(pool)
+    .await
+}
+
+/// Indexes of the purge queries must exist and be valid (a failed CREATE INDEX CONCURRENTLY
+/// leaves an invalid index that is never used)
+async fn check_indexes(report: &mut Report, pool: &PgPool, config: &AppConfig) {
+    let mut expected = PURGE_INDEXES.to_vec();
+    if config.retention_action == RetentionAction::Archive {
+        expected.extend_from_slice(ARCHIVE_INDEXES);
+    }
+    if config.cold_tier_after_days.is_some() {
+        expected.extend_from_slice(COLD_TIER_INDEXES);
+    }
+
+    let indexes = sqlx::query(
+        r#"
+        SELECT c.relname::text AS name, i.indisvalid AS valid
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        WHERE c.relname::text = ANY($1)
+        "#,
+    )
+    .bind(&expected)
+    .fetch_all(pool)
+    .await;
+
+    match indexes {
+        Ok(rows) => {
+            let missing: Vec<&str> = expected
+                .iter()
+                .copied()
+                .filter(|index| {
+                    !rows
+                        .iter()
+                        .any(|row| row.get::<String, _>("name") == *index)
+                })
+                .collect();
+            let invalid: Vec<String> = rows
+                .iter()
+                .filter(|row| !row.get::<bool, _>("valid"))
+                .map(|row| row.get("name"))
+                .collect();
+
+            if !missing.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("missing: {}", missing.join(", ")),
+                );
+            } else if !invalid.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("invalid (rebuild them): {}", invalid.join(", ")),
+                );
+            } else {
+                report.check(
+                    Status::Ok,
+                    "Indexes",
+                    format!("{} purge indexes", expected.len()),
+                );
+            }
+        }
+        Err(e) => report.check(Status::Fail, "Indexes", e.to_string()),
+    }
+}
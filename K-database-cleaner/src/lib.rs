@@ -0,0 +1,183 @@
+mod config;
+mod database;
+mod doctor;
+mod purge_operations;
+
+use anyhow::Result;
+use tracing::{error, info};
+
+pub use config::Args;
+use config::{AppConfig, Command, RetentionAction};
+use database::{prepare_cold_tier, verify_archive_tables};
+use k_common::failover::TargetSessionAttrs;
+use k_common::startup::{self, verify_k_schema};
+
+/// Run with the options of `args` (tracing is initialized by the caller: K-database-cleaner or the
+/// k-indexer CLI)
+pub async fn run(args: Args) -> Result<()> {
+    info!("Starting K-database-cleaner v{}", env!("CARGO_PKG_VERSION"));
+
+    // Load configuration from CLI arguments
+    let config = match AppConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+    if let Some(Command::Doctor) = args.command {
+        return doctor::run(&config).await;
+    }
+
+    // Decode user pubkey from hex
+    let user_pubkey = hex::decode(&config.user_pubkey).map_err(|e| {
+        anyhow::anyhow!(
+            "Invalid user public key hex string '{}': {}",
+            config.user_pubkey,
+            e
+        )
+    })?;
+
+    info!(
+        "Configuration: User pubkey: {}, Purge interval: {}s, Data retention: {}h ({:?})",
+        config.user_pubkey,
+        config.purge_interval,
+        config.data_retention_hours,
+        config.retention_action
+    );
+    if let Some(cold_tier_after_days) = config.cold_tier_after_days {
+        info!(
+            "Cold tier: content older than {} days moves to k_contents_cold (tablespace: {})",
+            cold_tier_after_days,
+            config.cold_tablespace.as_deref().unwrap_or("default")
+        );
+    }
+    info!(
+        "Database connection: {}:{}/{}",
+        config.database.host, config.database.port, config.database.database
+    );
+
+    // Create database connection pool
+    let db_pool = startup::create_pool(
+        &config.connection_string(),
+        &[],
+        TargetSessionAttrs::Any,
+        config.database.max_connections as u32,
+        config.database.wait_for_db_secs,
+    )
+    .await?;
+    info!(
+        "Database connection pool created with {} max connections",
+        config.database.max_connections
+    );
+    verify_k_schema(&db_pool, args.wait_for_schema).await?;
+    if config.retention_action == RetentionAction::Archive {
+        verify_archive_tables(&db_pool).await?;
+    }
+    if config.cold_tier_after_days.is_some() {
+        prepare_cold_tier(&db_pool, config.cold_tablespace.as_deref()).await?;
+    }
+
+    info!("K-database-cleaner started successfully");
+    info!(
+        "Running purge operations every {} seconds",
+        config.purge_interval
+    );
+
+    // Main purge loop
+    loop {
+        info!("========== Starting purge cycle ==========");
+        let cycle_start = std::time::Instant::now();
+
+        // Execute purge operations in sequence
+        match purge_operations::operation_1::execute(&db_pool, &user_pubkey).await {
+            Ok(_) => {}
+            Err(e) => {
+                error!("Purge operation 1 failed: {}", e);
+                error!("Skipping remaining operations in this cycle");
+                tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)).await;
+                continue;
+            }
+        }
+
+        match purge_operations::operation_2::execute(&db_pool, &user_pubkey).await {
+            Ok(_) => {}
+            Err(e) => {
+                error!("Purge operation 2 failed: {}", e);
+                error!("Skipping remaining operations in this cycle");
+                tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)).await;
+                continue;
+            }
+        }
+
+        match purge_operations::operation_3::execute(
+            &db_pool,
+            &user_pubkey,
+            config.data_retention_hours,
+            config.retention_action,
+        )
+        .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                error!("Purge operation 3 failed: {}", e);
+                error!("Skipping remaining operations in this cycle");
+                tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)).await;
+                continue;
+            }
+        }
+
+        match purge_operations::operation_4::execute(&db_pool).await {
+            Ok(_) => {}
+            Err(e) => {
+                error!("Purge operation 4 failed: {}", e);
+                error!("Skipping remaining operations in this cycle");
+                tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)).await;
+                continue;
+            }
+        }
+
+        match purge_operations::operation_5::execute(&db_pool).await {
+            Ok(_) => {}
+            Err(e) => {
+                error!("Purge operation 5 failed: {}", e);
+                error!("Skipping remaining operations in this cycle");
+                tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)).await;
+                continue;
+            }
+        }
+
+        if let Some(cold_tier_after_days) = config.cold_tier_after_days {
+            match purge_operations::operation_6::execute(&db_pool, cold_tier_after_days).await {
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Purge operation 6 failed: {}", e);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval))
+                        .await;
+                    continue;
+                }
+            }
+        }
+
+        let cycle_duration = cycle_start.elapsed();
+        info!(
+            "========== Purge cycle completed in {:.2}s ==========",
+            cycle_duration.as_secs_f64()
+        );
+        info!("Next purge cycle in {} seconds", config.purge_interval);
+
+        // Wait for the next purge interval or shutdown signal
+        tokio::select! {
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(config.purge_interval)) => {
+                // Continue to next purge cycle
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received shutdown signal");
+                break;
+            }
+        }
+    }
+
+    info!("K-database-cleaner shutting down");
+    Ok(())
+}
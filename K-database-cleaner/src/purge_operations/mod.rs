@@ -3,3 +3,4 @@ pub mod operation_2;
 pub mod operation_3;
 pub mod operation_4;
 pub mod operation_5;
+pub mod operation_6;
@@ -2,6 +2,8 @@ use anyhow::Result;
 use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::database::lock_out_workers;
+
 /// Purge Operation 1: Remove all records where sender_pubkey is not the user's pubkey
 /// from k_blocks and k_follows tables
 pub async fn execute(pool: &PgPool, user_pubkey: &[u8]) -> Result<()> {
@@ -9,6 +11,7 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8]) -> Result<()> {
 
     // Single transaction with CTE to delete from both tables and count results
     let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
 
     let result = sqlx::query(
         r#"
@@ -2,27 +2,36 @@ use anyhow::Result;
 use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::database::{all_contents_relation, cold_contents_delete, lock_out_workers};
+
 /// Purge Operation 2: Remove all content from blocked users
-/// This includes posts, quotes, replies, and votes from k_contents and k_votes tables,
-/// along with related data from k_mentions table
+/// This includes posts, quotes, replies, and votes from k_contents (and k_contents_cold) and
+/// k_votes tables, along with related data from k_mentions table
 pub async fn execute(pool: &PgPool, user_pubkey: &[u8]) -> Result<()> {
     info!("Starting purge operation 2: Removing blocked users' content");
 
     // Single transaction with CTE to delete all blocked users' content and mentions atomically
     let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
+
+    let contents = all_contents_relation(&mut tx).await?;
+    let (deleted_cold_contents, cold_contents_count) = cold_contents_delete(
+        contents,
+        "sender_pubkey IN (SELECT blocked_user_pubkey FROM blocked_users)",
+    );
 
-    let result = sqlx::query(
+    let query = format!(
         r#"
         WITH blocked_users AS (
             SELECT blocked_user_pubkey
             FROM k_blocks
             WHERE sender_pubkey = $1
-        ),
+        ),{}
         deleted_mentions_contents AS (
             DELETE FROM k_mentions
             WHERE content_id IN (
                 SELECT transaction_id
-                FROM k_contents
+                FROM {}
                 WHERE sender_pubkey IN (SELECT blocked_user_pubkey FROM blocked_users)
             )
             RETURNING id
@@ -48,14 +57,17 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8]) -> Result<()> {
         )
         SELECT
             (SELECT COUNT(*) FROM deleted_mentions_contents) as mentions_contents_count,
-            (SELECT COUNT(*) FROM deleted_contents) as contents_count,
+            (SELECT COUNT(*) FROM deleted_contents) + {} as contents_count,
             (SELECT COUNT(*) FROM deleted_mentions_votes) as mentions_votes_count,
             (SELECT COUNT(*) FROM deleted_votes) as votes_count
         "#,
-    )
-    .bind(user_pubkey)
-    .fetch_one(&mut *tx)
-    .await?;
+        deleted_cold_contents, contents, cold_contents_count
+    );
+
+    let result = sqlx::query(&query)
+        .bind(user_pubkey)
+        .fetch_one(&mut *tx)
+        .await?;
 
     let k_mentions_contents_deleted: i64 = result.get("mentions_contents_count");
     let k_contents_deleted: i64 = result.get("contents_count");
@@ -93,3 +105,41 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8]) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::{
+        cold_post_exists, insert_cold_post, test_pool, unique_pubkey,
+    };
+
+    #[tokio::test]
+    async fn test_blocked_user_cold_content_is_deleted() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let user = unique_pubkey();
+        let blocked = unique_pubkey();
+        sqlx::query(
+            "INSERT INTO k_blocks (transaction_id, block_time, sender_pubkey, sender_signature,
+                 blocking_action, blocked_user_pubkey)
+             VALUES ($1, 0, $2, $1, 'block', $3)",
+        )
+        .bind(&user[1..])
+        .bind(&user)
+        .bind(&blocked)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let cold_post = insert_cold_post(&pool, &blocked, 1_000).await;
+
+        execute(&pool, &user).await.unwrap();
+
+        assert!(!cold_post_exists(&pool, &cold_post).await);
+        sqlx::query("DELETE FROM k_blocks WHERE sender_pubkey = $1")
+            .bind(&user)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
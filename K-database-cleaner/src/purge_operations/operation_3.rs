@@ -2,30 +2,86 @@ use anyhow::Result;
 use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::config::RetentionAction;
+use crate::database::{all_contents_relation, cold_contents_delete, lock_out_workers};
+
+/// Copies the rows about to be deleted from `contents` into the archive tables ($3 = archived_at),
+/// with the rendering hints of k_render_spans (removed with the content by its foreign key)
+/// Data-modifying CTEs share the statement snapshot, so they see the rows the deletes remove
+fn archive_ctes(contents: &str) -> String {
+    format!(
+        r#"
+        archived_mentions AS (
+            INSERT INTO k_mentions_archive (id, content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, archived_at,
+                parent_id, content_snippet, parent_snippet)
+            SELECT id, content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, $3,
+                parent_id, content_snippet, parent_snippet
+            FROM k_mentions
+            WHERE content_id IN (SELECT transaction_id FROM old_content)
+            ON CONFLICT DO NOTHING
+        ),
+        archived_contents AS (
+            INSERT INTO k_contents_archive (id, transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_message, content_type, referenced_content_id, is_spam, is_rate_limited, archived_at,
+                search_vector, root_post_id, render_spans)
+            SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey, c.sender_signature,
+                c.base64_encoded_message, c.content_type, c.referenced_content_id, c.is_spam, c.is_rate_limited, $3,
+                c.search_vector, c.root_post_id, s.spans
+            FROM {} c
+            LEFT JOIN k_render_spans s ON s.content_id = c.transaction_id
+            WHERE c.transaction_id IN (SELECT transaction_id FROM old_content)
+            ON CONFLICT DO NOTHING
+        ),"#,
+        contents
+    )
+}
+
 /// Purge Operation 3: Remove old posts and quotes from non-followed users
 /// This removes posts and quotes older than the specified data retention period
 /// from users who are not followed by the main user, including related data from k_mentions
-pub async fn execute(pool: &PgPool, user_pubkey: &[u8], data_retention_hours: u64) -> Result<()> {
+/// and content already moved to k_contents_cold
+/// With RetentionAction::Archive the rows are moved to the archive tables instead
+pub async fn execute(
+    pool: &PgPool,
+    user_pubkey: &[u8],
+    data_retention_hours: u64,
+    retention_action: RetentionAction,
+) -> Result<()> {
+    let (action, verb) = match retention_action {
+        RetentionAction::Delete => ("Removing", "Deleted"),
+        RetentionAction::Archive => ("Archiving", "Archived"),
+    };
     info!(
-        "Starting purge operation 3: Removing old posts/quotes from non-followed users (retention: {} hours)",
-        data_retention_hours
+        "Starting purge operation 3: {} old posts/quotes from non-followed users (retention: {} hours)",
+        action, data_retention_hours
     );
 
     // Calculate the cutoff timestamp (current time - retention period)
     // block_time is in milliseconds since epoch
-    let cutoff_timestamp_ms = (std::time::SystemTime::now()
+    let now_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)?
-        .as_millis() as i64)
-        - (data_retention_hours as i64 * 3600 * 1000);
+        .as_millis() as i64;
+    let cutoff_timestamp_ms = now_ms - (data_retention_hours as i64 * 3600 * 1000);
 
-    // Single transaction with CTE to delete old posts/quotes and related mentions atomically
+    // Single transaction with CTE to delete (or archive) old posts/quotes and related mentions atomically
     let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
 
-    let result = sqlx::query(
+    let contents = all_contents_relation(&mut tx).await?;
+    let archive_ctes = match retention_action {
+        RetentionAction::Delete => String::new(),
+        RetentionAction::Archive => archive_ctes(contents),
+    };
+    let (deleted_cold_contents, cold_contents_count) = cold_contents_delete(
+        contents,
+        "transaction_id IN (SELECT transaction_id FROM old_content)",
+    );
+
+    let query = format!(
         r#"
         WITH old_content AS (
             SELECT transaction_id
-            FROM k_contents
+            FROM {}
             WHERE content_type IN ('post', 'quote')
               AND block_time < $1
               AND sender_pubkey != $2
@@ -34,7 +90,7 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8], data_retention_hours: u6
                   FROM k_follows
                   WHERE sender_pubkey = $2
               )
-        ),
+        ),{}{}
         deleted_mentions AS (
             DELETE FROM k_mentions
             WHERE content_id IN (SELECT transaction_id FROM old_content)
@@ -47,13 +103,19 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8], data_retention_hours: u6
         )
         SELECT
             (SELECT COUNT(*) FROM deleted_mentions) as mentions_count,
-            (SELECT COUNT(*) FROM deleted_contents) as contents_count
+            (SELECT COUNT(*) FROM deleted_contents) + {} as contents_count
         "#,
-    )
-    .bind(cutoff_timestamp_ms)
-    .bind(user_pubkey)
-    .fetch_one(&mut *tx)
-    .await?;
+        contents, archive_ctes, deleted_cold_contents, cold_contents_count
+    );
+
+    // $3 is only referenced by the archive CTEs
+    let mut query = sqlx::query(&query)
+        .bind(cutoff_timestamp_ms)
+        .bind(user_pubkey);
+    if retention_action == RetentionAction::Archive {
+        query = query.bind(now_ms);
+    }
+    let result = query.fetch_one(&mut *tx).await?;
 
     let k_mentions_deleted: i64 = result.get("mentions_count");
     let k_contents_deleted: i64 = result.get("contents_count");
@@ -61,19 +123,110 @@ pub async fn execute(pool: &PgPool, user_pubkey: &[u8], data_retention_hours: u6
     tx.commit().await?;
 
     info!(
-        "✓ Purge operation 3: Deleted {} mentions related to old posts/quotes",
-        k_mentions_deleted
+        "✓ Purge operation 3: {} {} mentions related to old posts/quotes",
+        verb, k_mentions_deleted
     );
     info!(
-        "✓ Purge operation 3: Deleted {} old posts/quotes from k_contents table",
-        k_contents_deleted
+        "✓ Purge operation 3: {} {} old posts/quotes from k_contents table",
+        verb, k_contents_deleted
     );
     info!(
-        "✓ Purge operation 3 completed: Total {} records deleted ({} from k_contents, {} mentions)",
+        "✓ Purge operation 3 completed: Total {} records {} ({} from k_contents, {} mentions)",
         k_contents_deleted + k_mentions_deleted,
+        verb.to_lowercase(),
         k_contents_deleted,
         k_mentions_deleted
     );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::{
+        cold_post_exists, insert_cold_post, test_pool, unique_pubkey,
+    };
+
+    #[tokio::test]
+    async fn test_old_cold_content_is_archived() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let user = unique_pubkey();
+        let cold_post = insert_cold_post(&pool, &unique_pubkey(), 1_000).await;
+
+        execute(&pool, &user, 1, RetentionAction::Archive)
+            .await
+            .unwrap();
+
+        assert!(!cold_post_exists(&pool, &cold_post).await);
+        let archived: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM k_contents_archive WHERE transaction_id = $1)",
+        )
+        .bind(&cold_post)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(archived);
+    }
+
+    #[tokio::test]
+    async fn test_archive_keeps_enriched_columns() {
+        let Some(pool) = test_pool().await else {
+            return;
+        };
+        let user = unique_pubkey();
+        let sender = unique_pubkey();
+        let post = unique_pubkey()[1..].to_vec();
+        sqlx::query(
+            "INSERT INTO k_contents (transaction_id, block_time, sender_pubkey, sender_signature,
+                 base64_encoded_message, content_type, search_vector)
+             VALUES ($1, 1000, $2, $1, 'cG9zdA==', 'post', to_tsvector('simple', 'post'))",
+        )
+        .bind(&post)
+        .bind(&sender)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO k_render_spans (content_id, spans) VALUES ($1, '[]')")
+            .bind(&post)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time,
+                 sender_pubkey, content_snippet)
+             VALUES ($1, 'post', $2, 1000, $3, 'cG9zdA==')",
+        )
+        .bind(&post)
+        .bind(&user)
+        .bind(&sender)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        execute(&pool, &user, 1, RetentionAction::Archive)
+            .await
+            .unwrap();
+
+        let (has_search_vector, has_render_spans): (bool, bool) = sqlx::query_as(
+            "SELECT search_vector IS NOT NULL, render_spans IS NOT NULL
+             FROM k_contents_archive WHERE transaction_id = $1",
+        )
+        .bind(&post)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(has_search_vector);
+        assert!(has_render_spans);
+        let content_snippet: Option<String> = sqlx::query_scalar(
+            "SELECT content_snippet FROM k_mentions_archive WHERE content_id = $1",
+        )
+        .bind(&post)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(content_snippet.as_deref(), Some("cG9zdA=="));
+    }
+}
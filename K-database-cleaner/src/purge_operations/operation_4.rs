@@ -2,6 +2,8 @@ use anyhow::Result;
 use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::database::{all_contents_relation, lock_out_workers};
+
 /// Purge Operation 4: Remove orphaned replies
 /// This removes all replies that reference content that no longer exists in the database,
 /// including related data from k_mentions
@@ -10,8 +12,10 @@ pub async fn execute(pool: &PgPool) -> Result<()> {
 
     // Single transaction with CTE to delete orphaned replies and related mentions atomically
     let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
+    let contents = all_contents_relation(&mut tx).await?;
 
-    let result = sqlx::query(
+    let query = format!(
         r#"
         WITH orphaned_replies AS (
             SELECT transaction_id
@@ -19,7 +23,7 @@ pub async fn execute(pool: &PgPool) -> Result<()> {
             WHERE content_type = 'reply'
               AND referenced_content_id IS NOT NULL
               AND referenced_content_id NOT IN (
-                  SELECT transaction_id FROM k_contents
+                  SELECT transaction_id FROM {}
               )
         ),
         deleted_mentions AS (
@@ -36,9 +40,9 @@ pub async fn execute(pool: &PgPool) -> Result<()> {
             (SELECT COUNT(*) FROM deleted_mentions) as mentions_count,
             (SELECT COUNT(*) FROM deleted_contents) as contents_count
         "#,
-    )
-    .fetch_one(&mut *tx)
-    .await?;
+        contents
+    );
+    let result = sqlx::query(&query).fetch_one(&mut *tx).await?;
 
     let k_mentions_deleted: i64 = result.get("mentions_count");
     let k_contents_deleted: i64 = result.get("contents_count");
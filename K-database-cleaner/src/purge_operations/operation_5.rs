@@ -2,6 +2,8 @@ use anyhow::Result;
 use sqlx::{PgPool, Row};
 use tracing::info;
 
+use crate::database::{all_contents_relation, lock_out_workers};
+
 /// Purge Operation 5: Remove orphaned votes
 /// This removes all votes that reference posts that no longer exist in the database,
 /// including related data from k_mentions
@@ -10,14 +12,16 @@ pub async fn execute(pool: &PgPool) -> Result<()> {
 
     // Single transaction with CTE to delete orphaned votes and related mentions atomically
     let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
+    let contents = all_contents_relation(&mut tx).await?;
 
-    let result = sqlx::query(
+    let query = format!(
         r#"
         WITH orphaned_votes AS (
             SELECT transaction_id
             FROM k_votes
             WHERE post_id NOT IN (
-                SELECT transaction_id FROM k_contents
+                SELECT transaction_id FROM {}
             )
         ),
         deleted_mentions AS (
@@ -34,9 +38,9 @@ pub async fn execute(pool: &PgPool) -> Result<()> {
             (SELECT COUNT(*) FROM deleted_mentions) as mentions_count,
             (SELECT COUNT(*) FROM deleted_votes) as votes_count
         "#,
-    )
-    .fetch_one(&mut *tx)
-    .await?;
+        contents
+    );
+    let result = sqlx::query(&query).fetch_one(&mut *tx).await?;
 
     let k_mentions_deleted: i64 = result.get("mentions_count");
     let k_votes_deleted: i64 = result.get("votes_count");
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::info;
+
+use crate::database::lock_out_workers;
+
+/// Purge Operation 6: Move old content to the cold tier
+/// This moves posts, replies and quotes older than the cold tier threshold from k_contents
+/// to k_contents_cold and raises the boundary in k_meta, so K-webserver only reads the cold
+/// tier for pages reaching that far back. Mentions stay in k_mentions, while hashtags and
+/// content hashes of moved content are removed with it (ON DELETE CASCADE)
+pub async fn execute(pool: &PgPool, cold_tier_after_days: u64) -> Result<()> {
+    info!(
+        "Starting purge operation 6: Moving content older than {} days to the cold tier",
+        cold_tier_after_days
+    );
+
+    // block_time is in milliseconds since epoch
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+    let cutoff_timestamp_ms = now_ms - (cold_tier_after_days as i64 * 24 * 3600 * 1000);
+
+    // Single transaction with CTE to move the rows and raise the boundary atomically
+    let mut tx = pool.begin().await?;
+    lock_out_workers(&mut tx).await?;
+
+    let result = sqlx::query(
+        r#"
+        WITH moved_contents AS (
+            DELETE FROM k_contents
+            WHERE block_time < $1
+            RETURNING id, transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_message, content_type, referenced_content_id, is_spam, is_rate_limited,
+                search_vector, root_post_id
+        ),
+        cold_contents AS (
+            INSERT INTO k_contents_cold (id, transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_message, content_type, referenced_content_id, is_spam, is_rate_limited,
+                search_vector, root_post_id)
+            SELECT id, transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_message, content_type, referenced_content_id, is_spam, is_rate_limited,
+                search_vector, root_post_id
+            FROM moved_contents
+            ON CONFLICT DO NOTHING
+        ),
+        boundary AS (
+            UPDATE k_meta
+            SET cold_tier_before = GREATEST(COALESCE(cold_tier_before, 0), $1)
+            WHERE id = 1
+        )
+        SELECT COUNT(*) as contents_count FROM moved_contents
+        "#,
+    )
+    .bind(cutoff_timestamp_ms)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let k_contents_moved: i64 = result.get("contents_count");
+
+    tx.commit().await?;
+
+    info!(
+        "✓ Purge operation 6 completed: Moved {} records from k_contents to k_contents_cold",
+        k_contents_moved
+    );
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+mod payload_simulator;
+mod shared_options;
+
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use payload_simulator::ProcessorSimulator;
+use shared_options::SharedOptions;
+use std::sync::Arc;
+use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Parser, Debug)]
+#[command(
+    author,
+    version,
+    about = "K-indexer: transaction processor, webserver and maintenance tools in one binary",
+    long_about = None
+)]
+struct Args {
+    #[command(flatten)]
+    shared: SharedOptions,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Options after a component command are parsed by the component itself, `--help` included
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the K API (K-webserver options)
+    #[command(disable_help_flag = true)]
+    Webserver {
+        /// Serve /admin/simulate-payload, validating payloads like a processor run with these
+        /// options (e.g. --simulator="--network=testnet-10", or "" for the defaults). Given
+        /// before the webserver options
+        #[arg(long, value_name = "PROCESSOR OPTIONS", allow_hyphen_values = true)]
+        simulator: Option<String>,
+
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Index K transactions (K-transaction-processor subcommands and options)
+    #[command(disable_help_flag = true)]
+    Processor {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Purge the content a personal indexer does not need (K-database-cleaner options)
+    #[command(disable_help_flag = true)]
+    Cleaner {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Remove the content of a user or carry out an erasure request (K-content-remover options)
+    #[command(disable_help_flag = true)]
+    Remover {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Create the K schema or upgrade it to the current version, then exit
+    /// (K-transaction-processor migrate)
+    #[command(disable_help_flag = true)]
+    Migrate {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Print the doctor reports of the transaction processor (which takes the options given
+    /// here, e.g. --network) and of the webserver, then exit (non-zero exit code if a check
+    /// fails)
+    #[command(disable_help_flag = true)]
+    Doctor {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+/// Parse the command line of a component, exiting with its usage on error like the component's
+/// own binary
+fn component_args<T: CommandFactory + FromArgMatches>(name: &str, argv: Vec<String>) -> T {
+    let matches = T::command()
+        .bin_name(format!("k-indexer {}", name))
+        .get_matches_from(argv);
+    T::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+}
+
+fn run_processor(argv: Vec<String>) -> Result<()> {
+    let args = component_args::<k_transaction_processor::Args>("processor", argv);
+    tokio::runtime::Runtime::new()?.block_on(k_transaction_processor::run(args))
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    // Initialize tracing with default INFO level, once for whichever component runs. The filter
+    // only applies to the printed log: the statement capture of the webserver's
+    // --slow-request-trace-ms reads sqlx events below it
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+        ))
+        .with(k_webserver::statement_capture_layer())
+        .init();
+
+    let shared = &args.shared;
+    match args.command {
+        Command::Webserver { simulator, args } => {
+            let simulator: Option<Arc<dyn k_webserver::PayloadSimulator>> = match simulator {
+                Some(options) => {
+                    let options = options.split_whitespace().map(String::from).collect();
+                    let args = component_args::<k_transaction_processor::Args>(
+                        "webserver --simulator",
+                        shared.argv("processor", &["run"], options),
+                    );
+                    let simulator = k_transaction_processor::payload_simulator(args)?;
+                    Some(Arc::new(ProcessorSimulator(simulator)))
+                }
+                None => None,
+            };
+            k_webserver::run_with_simulator(
+                component_args("webserver", shared.argv("webserver", &[], args)),
+                simulator,
+            )
+        }
+        Command::Processor { args } => Ok(run_processor(shared.argv("processor", &[], args))?),
+        Command::Cleaner { args } => {
+            let args = component_args("cleaner", shared.argv("cleaner", &[], args));
+            let runtime = tokio::runtime::Runtime::new()?;
+            Ok(runtime.block_on(k_database_cleaner::run(args))?)
+        }
+        Command::Remover { args } => {
+            let args = component_args("remover", shared.argv("remover", &[], args));
+            let runtime = tokio::runtime::Runtime::new()?;
+            Ok(runtime.block_on(k_content_remover::run(args))?)
+        }
+        Command::Migrate { args } => Ok(run_processor(shared.argv("migrate", &["migrate"], args))?),
+        Command::Doctor { args } => {
+            // Both reports are printed before failing on either
+            let processor = run_processor(shared.argv("doctor", &["doctor"], args));
+            let webserver = k_webserver::run(component_args(
+                "doctor",
+                shared.argv("doctor", &["doctor"], Vec::new()),
+            ));
+            processor?;
+            webserver
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use k_webserver::{PayloadSimulationResponse, SimulatedTableWrites, Simulation};
+
+/// Simulations of K-transaction-processor served by the webserver's /admin/simulate-payload
+pub struct ProcessorSimulator(pub k_transaction_processor::PayloadSimulator);
+
+#[async_trait]
+impl k_webserver::PayloadSimulator for ProcessorSimulator {
+    async fn simulate(
+        &self,
+        payload: &str,
+        transaction_id: Option<String>,
+        block_time: Option<i64>,
+    ) -> Result<Simulation> {
+        let report = self.0.simulate(payload, transaction_id, block_time).await?;
+        let error = report.error.unwrap_or_default();
+        let Some(action) = report.action else {
+            return Ok(Simulation::Invalid(error));
+        };
+        if !report.indexed {
+            return Ok(Simulation::Rejected(error));
+        }
+
+        Ok(Simulation::Indexed(PayloadSimulationResponse {
+            transaction_id: report.transaction_id,
+            block_time: report.block_time,
+            action: serde_json::to_value(action)?,
+            writes: report
+                .writes
+                .into_iter()
+                .map(|writes| SimulatedTableWrites {
+                    table: writes.table,
+                    inserted: writes.inserted,
+                    updated: writes.updated,
+                    deleted: writes.deleted,
+                })
+                .collect(),
+        }))
+    }
+}
@@ -0,0 +1,71 @@
+use clap::Args;
+
+/// Database options of every component, given once before the command (or through the
+/// environment of a deployment image) with the same names for all of them
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Shared options")]
+pub struct SharedOptions {
+    #[arg(long, global = true, env = "K_DB_HOST", help = "Database host")]
+    db_host: Option<String>,
+
+    #[arg(long, global = true, env = "K_DB_PORT", help = "Database port")]
+    db_port: Option<u16>,
+
+    #[arg(long, global = true, env = "K_DB_NAME", help = "Database name")]
+    db_name: Option<String>,
+
+    #[arg(long, global = true, env = "K_DB_USER", help = "Database username")]
+    db_user: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "K_DB_PASSWORD",
+        hide_env_values = true,
+        help = "Database password"
+    )]
+    db_password: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "K_WAIT_FOR_DB",
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    wait_for_db: Option<u64>,
+}
+
+impl SharedOptions {
+    /// Command line of a component: the shared options set, then `command` (a subcommand of the
+    /// component, if any) and the options given after the k-indexer command, which win over the
+    /// shared ones (e.g. over K_DB_HOST in the environment of an image)
+    pub fn argv(&self, name: &str, command: &[&str], args: Vec<String>) -> Vec<String> {
+        let mut argv = vec![format!("k-indexer {}", name)];
+        let options = [
+            ("--db-host", self.db_host.clone()),
+            ("--db-port", self.db_port.map(|port| port.to_string())),
+            ("--db-name", self.db_name.clone()),
+            ("--db-user", self.db_user.clone()),
+            ("--db-password", self.db_password.clone()),
+            (
+                "--wait-for-db",
+                self.wait_for_db.map(|secs| secs.to_string()),
+            ),
+        ];
+        let given = |option: &str| {
+            args.iter()
+                .any(|arg| arg == option || arg.starts_with(&format!("{}=", option)))
+        };
+        for (option, value) in options {
+            if let Some(value) = value
+                && !given(option)
+            {
+                argv.push(option.to_string());
+                argv.push(value);
+            }
+        }
+        argv.extend(command.iter().map(|command| command.to_string()));
+        argv.extend(args);
+        argv
+    }
+}
@@ -0,0 +1,92 @@
+use sha2::{Digest, Sha256};
+
+/// Maximum number of chunks a long-form article manifest can announce
+pub const MAX_ARTICLE_CHUNKS: u32 = 256;
+
+/// Result of trying to reassemble an article from the chunks received so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArticleAssembly {
+    /// Some chunks are still missing
+    Incomplete { received: u32 },
+    /// All chunks arrived and their concatenation matches the manifest hash
+    Complete(String),
+    /// All chunks arrived but their concatenation does not match the manifest hash
+    HashMismatch,
+}
+
+/// Concatenate the chunks (index, base64 data) of an article in index order
+/// Chunks are consecutive slices of the base64 encoded article; the manifest commits to the
+/// SHA-256 of the whole base64 string. Indexes outside 0..chunk_count and duplicates are ignored
+pub fn assemble_article(
+    chunks: &[(u32, String)],
+    chunk_count: u32,
+    content_hash: &[u8],
+) -> ArticleAssembly {
+    let mut slots: Vec<Option<&str>> = vec![None; chunk_count as usize];
+    for (index, data) in chunks {
+        if let Some(slot) = slots.get_mut(*index as usize) {
+            slot.get_or_insert(data.as_str());
+        }
+    }
+
+    let received = slots.iter().filter(|slot| slot.is_some()).count() as u32;
+    if received < chunk_count {
+        return ArticleAssembly::Incomplete { received };
+    }
+
+    let content: String = slots.into_iter().flatten().collect();
+    if Sha256::digest(content.as_bytes()).as_slice() == content_hash {
+        ArticleAssembly::Complete(content)
+    } else {
+        ArticleAssembly::HashMismatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(content: &str) -> Vec<u8> {
+        Sha256::digest(content.as_bytes()).to_vec()
+    }
+
+    #[test]
+    fn test_missing_chunks_are_incomplete() {
+        let chunks = vec![(0, "SGVs".to_string()), (2, "IQ==".to_string())];
+        let outcome = assemble_article(&chunks, 3, &hash("SGVsbG8hIQ=="));
+        assert_eq!(outcome, ArticleAssembly::Incomplete { received: 2 });
+    }
+
+    #[test]
+    fn test_chunks_are_joined_in_index_order() {
+        let chunks = vec![
+            (2, "IQ==".to_string()),
+            (0, "SGVs".to_string()),
+            (1, "bG8h".to_string()),
+        ];
+        let outcome = assemble_article(&chunks, 3, &hash("SGVsbG8hIQ=="));
+        assert_eq!(
+            outcome,
+            ArticleAssembly::Complete("SGVsbG8hIQ==".to_string())
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_and_duplicate_chunks_are_ignored() {
+        let chunks = vec![
+            (0, "SGVs".to_string()),
+            (0, "AAAA".to_string()),
+            (5, "AAAA".to_string()),
+            (1, "bG8h".to_string()),
+        ];
+        let outcome = assemble_article(&chunks, 2, &hash("SGVsbG8h"));
+        assert_eq!(outcome, ArticleAssembly::Complete("SGVsbG8h".to_string()));
+    }
+
+    #[test]
+    fn test_wrong_hash_is_rejected() {
+        let chunks = vec![(0, "SGVs".to_string()), (1, "bG8h".to_string())];
+        let outcome = assemble_article(&chunks, 2, &hash("something else"));
+        assert_eq!(outcome, ArticleAssembly::HashMismatch);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::blob_storage::BlobStorage;
+use crate::config::AppConfig;
+use crate::database::{
+    DbPool, PROCESSED_WATERMARK_MARGIN_MS, fetch_k_transaction_page, get_processed_watermark,
+    is_maintenance_mode,
+};
+use crate::maintenance;
+use crate::queue::NotificationQueue;
+use crate::worker::WorkerPool;
+use anyhow::Result;
+use metrics::{counter, gauge};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+// Transaction ids read from the transactions table per query
+const BACKFILL_PAGE_SIZE: i64 = 1000;
+
+/// Index K transactions already stored in the transactions table (e.g. missed notifications
+/// while the processor was down) through the regular queue and worker pool.
+/// K inserts are idempotent, so overlapping an already indexed range is harmless.
+/// With `max_rate` (K transactions per second, 0 = unlimited) transactions are queued no
+/// faster than that, leaving database capacity to a `run` processor following the tip
+pub async fn execute(
+    pool: &DbPool,
+    config: &AppConfig,
+    from_block_time: Option<i64>,
+    to_block_time: Option<i64>,
+    max_rate: u64,
+) -> Result<()> {
+    let from_block_time = match from_block_time {
+        Some(block_time) => block_time,
+        None => get_processed_watermark(pool)
+            .await?
+            .map_or(0, |watermark| watermark - PROCESSED_WATERMARK_MARGIN_MS),
+    };
+    let to_block_time = match to_block_time {
+        Some(block_time) => block_time,
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64,
+    };
+    info!(
+        "Backfilling K transactions with block time {} - {} using {} workers",
+        from_block_time, to_block_time, config.workers.count
+    );
+    if max_rate > 0 {
+        info!("Backfill rate limited to {} K transactions/s", max_rate);
+    }
+
+    if let Some(metrics_bind_address) = config.monitoring.metrics_bind_address {
+        PrometheusBuilder::new()
+            .with_http_listener(metrics_bind_address)
+            .install()?;
+        info!(
+            "Prometheus metrics available on http://{}/metrics",
+            metrics_bind_address
+        );
+    }
+    gauge!("k_backfill_from_block_time").set(from_block_time as f64);
+    gauge!("k_backfill_target_block_time").set(to_block_time as f64);
+    gauge!("k_backfill_block_time").set(from_block_time as f64);
+
+    let (notification_sender, notification_receiver) = mpsc::unbounded_channel();
+    let (mut notification_queue, worker_receivers) =
+        NotificationQueue::new(notification_receiver, config.workers.count);
+
+    // Workers pause during maintenance mode exactly like in `run`
+    let maintenance_enabled = is_maintenance_mode(pool).await?;
+    let maintenance = maintenance::spawn_maintenance_watcher(
+        pool.clone(),
+        config.maintenance_check_interval_secs,
+        maintenance_enabled,
+    );
+
+    let blob_storage = BlobStorage::from_config(&config.image_storage)?.map(Arc::new);
+    let worker_pool = WorkerPool::new(
+        worker_receivers,
+        pool.clone(),
+        // Only the network's own community is backfilled (payloads with its prefix)
+        Vec::new(),
+        config.clone(),
+        blob_storage,
+        maintenance,
+    );
+
+    let queue_handle = tokio::spawn(async move {
+        notification_queue.start().await;
+    });
+    let worker_handle = tokio::spawn(async move {
+        worker_pool.start().await;
+    });
+
+    // A rate limited backfill reads at most one second worth of transactions per page
+    let page_size = match max_rate {
+        0 => BACKFILL_PAGE_SIZE,
+        rate => BACKFILL_PAGE_SIZE.min(rate as i64),
+    };
+
+    let start = std::time::Instant::now();
+    let mut queued: u64 = 0;
+    let mut last: Option<(i64, Vec<u8>)> = None;
+
+    loop {
+        let page = fetch_k_transaction_page(
+            pool,
+            &config.chain,
+            from_block_time,
+            to_block_time,
+            last.as_ref(),
+            page_size,
+        )
+        .await?;
+
+        let Some(page_last) = page.last().cloned() else {
+            break;
+        };
+
+        for (_, transaction_id) in &page {
+            if notification_sender
+                .send(hex::encode(transaction_id))
+                .is_err()
+            {
+                error!("Notification queue stopped, aborting backfill");
+                return Err(anyhow::anyhow!(
+                    "Notification queue stopped during backfill"
+                ));
+            }
+        }
+
+        queued += page.len() as u64;
+        counter!("k_backfill_queued_total").increment(page.len() as u64);
+        gauge!("k_backfill_block_time").set(page_last.0 as f64);
+        info!(
+            "Queued {} K transactions (up to block time {}, {:.1}% of the range)",
+            queued,
+            page_last.0,
+            progress_percent(from_block_time, to_block_time, page_last.0)
+        );
+        last = Some(page_last);
+
+        // Sleep until the average rate since the start is back under the cap
+        if max_rate > 0 {
+            let due = Duration::from_secs_f64(queued as f64 / max_rate as f64);
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+    gauge!("k_backfill_block_time").set(to_block_time as f64);
+
+    // Closing the channel lets queue and workers drain and stop
+    drop(notification_sender);
+    if let Err(e) = queue_handle.await {
+        error!("Notification queue task failed: {}", e);
+    }
+    if let Err(e) = worker_handle.await {
+        error!("Worker pool task failed: {}", e);
+    }
+
+    info!(
+        "Backfill completed: {} K transactions processed in {:.2}s",
+        queued,
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Share of the block time range already queued
+fn progress_percent(from_block_time: i64, to_block_time: i64, block_time: i64) -> f64 {
+    if to_block_time <= from_block_time {
+        return 100.0;
+    }
+    let done = (block_time - from_block_time).clamp(0, to_block_time - from_block_time);
+    done as f64 * 100.0 / (to_block_time - from_block_time) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_percent() {
+        assert_eq!(progress_percent(1000, 2000, 1500), 50.0);
+        assert_eq!(progress_percent(1000, 2000, 500), 0.0);
+        assert_eq!(progress_percent(1000, 2000, 2500), 100.0);
+        assert_eq!(progress_percent(2000, 2000, 2000), 100.0);
+    }
+}
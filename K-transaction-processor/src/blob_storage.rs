@@ -0,0 +1,106 @@
+use crate::config::ImageStorageConfig;
+use anyhow::Result;
+use object_store::{
+    ObjectStore, PutPayload, aws::AmazonS3Builder, local::LocalFileSystem, path::Path,
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::info;
+
+/// Reference to a blob written to the configured storage backend
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    /// Object key relative to the storage root (stored in the database)
+    pub key: String,
+    /// SHA-256 of the blob content
+    pub hash: Vec<u8>,
+}
+
+impl StoredBlob {
+    /// Content-addressed reference of a decoded profile image: identical images share one key,
+    /// so thousands of broadcasts of the same default avatar are stored once
+    pub fn profile_image(image_bytes: &[u8]) -> Self {
+        let hash = Sha256::digest(image_bytes).to_vec();
+        let key = format!("profile-images/sha256/{}", hex::encode(&hash));
+        Self { key, hash }
+    }
+}
+
+/// Blob storage for decoded profile images (local directory or S3 bucket)
+/// Keeps large images out of Postgres rows; only the key and hash are stored in k_broadcasts
+pub struct BlobStorage {
+    store: Arc<dyn ObjectStore>,
+    backend: &'static str,
+}
+
+impl BlobStorage {
+    /// Build the storage backend from configuration
+    /// Returns None when images are kept inline in the database
+    pub fn from_config(config: &ImageStorageConfig) -> Result<Option<Self>> {
+        let (store, backend): (Arc<dyn ObjectStore>, &'static str) = match config {
+            ImageStorageConfig::Database => return Ok(None),
+            ImageStorageConfig::Filesystem { path } => {
+                std::fs::create_dir_all(path)?;
+                (
+                    Arc::new(LocalFileSystem::new_with_prefix(path)?),
+                    "filesystem",
+                )
+            }
+            ImageStorageConfig::S3 {
+                bucket,
+                region,
+                endpoint,
+            } => {
+                // Credentials are taken from the standard AWS_* environment variables
+                let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+                if let Some(region) = region {
+                    builder = builder.with_region(region);
+                }
+                if let Some(endpoint) = endpoint {
+                    builder = builder.with_endpoint(endpoint).with_allow_http(true);
+                }
+                (Arc::new(builder.build()?), "s3")
+            }
+        };
+
+        info!("Profile image blob storage enabled ({})", backend);
+        Ok(Some(Self { store, backend }))
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.backend
+    }
+
+    /// Write a blob (overwriting an existing blob with the same key)
+    pub async fn put(&self, blob: &StoredBlob, bytes: Vec<u8>) -> Result<()> {
+        self.store
+            .put(&Path::from(blob.key.as_str()), PutPayload::from(bytes))
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a blob, a blob that is already gone is not an error
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        match self.store.delete(&Path::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_image_key_is_content_addressed() {
+        let blob = StoredBlob::profile_image(b"avatar");
+        assert_eq!(blob.hash, Sha256::digest(b"avatar").to_vec());
+        assert_eq!(
+            blob.key,
+            format!("profile-images/sha256/{}", hex::encode(&blob.hash))
+        );
+        assert_eq!(StoredBlob::profile_image(b"avatar").key, blob.key);
+        assert_ne!(StoredBlob::profile_image(b"other").key, blob.key);
+    }
+}
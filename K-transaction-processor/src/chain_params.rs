@@ -0,0 +1,99 @@
+/// Network-specific constants of a Kaspa network, selected with --network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainParams {
+    /// Network name accepted by --network and recorded in k_vars
+    pub name: &'static str,
+    /// Prefix of the addresses on this network
+    pub address_prefix: &'static str,
+    /// Prefix of the transaction payloads carrying K protocol messages
+    pub payload_prefix: &'static str,
+    /// Block rate, converting a confirmation depth in blocks into block time
+    pub blocks_per_second: u64,
+}
+
+pub const MAINNET: ChainParams = ChainParams {
+    name: "mainnet",
+    address_prefix: "kaspa",
+    payload_prefix: "k:1:",
+    blocks_per_second: 10,
+};
+
+pub const TESTNET_10: ChainParams = ChainParams {
+    name: "testnet-10",
+    address_prefix: "kaspatest",
+    payload_prefix: "k:1:",
+    blocks_per_second: 10,
+};
+
+/// Networks the processor can index
+pub const NETWORKS: [ChainParams; 2] = [TESTNET_10, MAINNET];
+
+impl ChainParams {
+    /// Look up the parameters of a network by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        NETWORKS.into_iter().find(|params| params.name == name)
+    }
+
+    /// Whether a decoded transaction payload is a K protocol message on this network
+    pub fn is_k_payload(&self, payload: &[u8]) -> bool {
+        payload.starts_with(self.payload_prefix.as_bytes())
+    }
+
+    /// Block time (ms) for `depth` blocks to be added on top of a block
+    pub fn confirmation_delay_ms(&self, depth: u64) -> i64 {
+        (depth * 1000 / self.blocks_per_second) as i64
+    }
+
+    /// Hex encoded payload prefix, as compared by the notification trigger
+    pub fn payload_prefix_hex(&self) -> String {
+        hex::encode(self.payload_prefix)
+    }
+}
+
+/// Network names for error messages, e.g. "'testnet-10' or 'mainnet'"
+pub fn network_names() -> String {
+    NETWORKS
+        .iter()
+        .map(|params| format!("'{}'", params.name))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// Distinct hex encoded payload prefixes of all networks, for the notification trigger that
+/// is created before the network of the database is recorded
+pub fn all_payload_prefixes_hex() -> Vec<String> {
+    let mut prefixes: Vec<String> = NETWORKS
+        .iter()
+        .map(ChainParams::payload_prefix_hex)
+        .collect();
+    prefixes.dedup();
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(ChainParams::from_name("mainnet"), Some(MAINNET));
+        assert_eq!(ChainParams::from_name("testnet-10"), Some(TESTNET_10));
+        assert_eq!(ChainParams::from_name("testnet-11"), None);
+        assert_eq!(network_names(), "'testnet-10' or 'mainnet'");
+    }
+
+    #[test]
+    fn test_payload_prefix() {
+        assert!(MAINNET.is_k_payload(b"k:1:post:abc"));
+        assert!(!MAINNET.is_k_payload(b"k:2:post:abc"));
+        assert_eq!(TESTNET_10.payload_prefix_hex(), "6b3a313a");
+        assert_eq!(all_payload_prefixes_hex(), vec!["6b3a313a".to_string()]);
+    }
+
+    #[test]
+    fn test_confirmation_delay() {
+        assert_eq!(MAINNET.confirmation_delay_ms(0), 0);
+        assert_eq!(MAINNET.confirmation_delay_ms(100), 10_000);
+        assert_eq!(TESTNET_10.confirmation_delay_ms(5), 500);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::database::DbPool;
+use crate::retry_policy::is_outage_error;
+use metrics::{counter, gauge};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Circuit breaker shared by all workers of a pool
+/// Opens after `threshold` consecutive database outage errors (any worker), so workers stop
+/// pulling notifications and retrying instead of spending the retry attempts of every queued
+/// transaction on a database that is down. Notifications keep queueing up in memory while it is
+/// open; the probe task closes it once the database answers again
+pub struct CircuitBreaker {
+    // 0 = never open
+    threshold: u32,
+    consecutive_failures: AtomicU32,
+    // true while the circuit is open
+    state: watch::Sender<bool>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        let (state, _) = watch::channel(false);
+        Self {
+            threshold,
+            consecutive_failures: AtomicU32::new(0),
+            state,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        *self.state.borrow()
+    }
+
+    /// Wait until the probe closes the circuit
+    pub async fn wait_until_closed(&self) {
+        let mut state = self.state.subscribe();
+        // The sender lives in self, the channel cannot close while waiting
+        let _ = state.wait_for(|open| !open).await;
+    }
+
+    /// A database round trip succeeded
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Count a failed database round trip; only outage errors count towards the threshold
+    pub fn record_failure(&self, err: &anyhow::Error) {
+        if self.threshold == 0 || !is_outage_error(err) {
+            return;
+        }
+
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.threshold
+            && self
+                .state
+                .send_if_modified(|open| !std::mem::replace(open, true))
+        {
+            warn!(
+                "Circuit breaker opened after {} consecutive database errors ({}) - pausing transaction processing",
+                failures, err
+            );
+            counter!("k_circuit_breaker_opened_total").increment(1);
+            gauge!("k_circuit_breaker_open").set(1.0);
+        }
+    }
+
+    fn close(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        if self
+            .state
+            .send_if_modified(|open| std::mem::replace(open, false))
+        {
+            info!(
+                "Circuit breaker closed, database reachable again - resuming transaction processing"
+            );
+            gauge!("k_circuit_breaker_open").set(0.0);
+        }
+    }
+}
+
+/// Probe the database every `probe_interval` while the circuit is open and close it on the
+/// first successful query
+pub fn spawn_probe(breaker: Arc<CircuitBreaker>, pool: DbPool, probe_interval: Duration) {
+    let mut state = breaker.state.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            if state.wait_for(|open| *open).await.is_err() {
+                return;
+            }
+
+            loop {
+                tokio::time::sleep(probe_interval).await;
+
+                match sqlx::query("SELECT 1").execute(&pool).await {
+                    Ok(_) => {
+                        breaker.close();
+                        break;
+                    }
+                    Err(e) => warn!(
+                        "Circuit breaker probe failed, database still unreachable: {}",
+                        e
+                    ),
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outage() -> anyhow::Error {
+        anyhow::Error::from(sqlx::Error::PoolTimedOut)
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_outage_errors() {
+        let breaker = CircuitBreaker::new(3);
+        breaker.record_failure(&outage());
+        breaker.record_failure(&outage());
+        assert!(!breaker.is_open());
+        breaker.record_failure(&outage());
+        assert!(breaker.is_open());
+
+        breaker.close();
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_success_and_record_errors_do_not_open() {
+        let breaker = CircuitBreaker::new(2);
+        breaker.record_failure(&outage());
+        breaker.record_success();
+        breaker.record_failure(&outage());
+        assert!(!breaker.is_open());
+
+        // Failures of a single record say nothing about the database
+        let record_err = anyhow::Error::from(sqlx::Error::RowNotFound);
+        breaker.record_failure(&record_err);
+        breaker.record_failure(&record_err);
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_zero_threshold_never_opens() {
+        let breaker = CircuitBreaker::new(0);
+        for _ in 0..10 {
+            breaker.record_failure(&outage());
+        }
+        assert!(!breaker.is_open());
+    }
+}
@@ -1,21 +1,53 @@
-use crate::Args;
+use crate::chain_params::{self, ChainParams};
+use crate::tenants::{self, Tenant};
+use crate::{DatabaseArgs, ProcessingArgs};
+use k_common::ConfigErrors;
+use k_common::failover::{self, DbHost, TargetSessionAttrs};
+use std::net::SocketAddr;
+
+/// Bounds of --retry-delay, the base of the retry backoff and the listener reconnection delay
+const MIN_RETRY_DELAY_MS: u64 = 10;
+const MAX_RETRY_DELAY_MS: u64 = 60_000;
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub workers: WorkerConfig,
     pub processing: ProcessingConfig,
-    pub network: String,
+    pub image_storage: ImageStorageConfig,
+    pub mentions: MentionLimitConfig,
+    pub sender_rate_limit: SenderRateLimitConfig,
+    pub monitoring: MonitoringConfig,
+    pub impersonation: ImpersonationConfig,
+    pub maintenance_check_interval_secs: u64,
+    /// Interval between reconciliations of k_vote_totals with k_votes
+    pub vote_totals_interval_secs: u64,
+    /// Interval between deletions of unreferenced profile images from blob storage
+    pub image_gc_interval_secs: u64,
+    /// Record the fee burned by each vote (k_votes.fee) for fee-weighted vote totals
+    pub vote_fee_weighting: bool,
+    /// Blocks on top of the block of a content before K-webserver lists it (0 = immediately)
+    pub confirmation_depth: u64,
+    pub chain: ChainParams,
+    /// Communities indexed into their own schema next to the network's one (--tenant)
+    pub tenants: Vec<Tenant>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
+    /// First host of --db-host
     pub host: String,
     pub port: u16,
+    /// Every host of --db-host in order, the first included
+    pub hosts: Vec<DbHost>,
+    /// Which of the hosts connections go to, switching on failover (see failover.rs)
+    pub target_session_attrs: TargetSessionAttrs,
     pub database: String,
     pub username: String,
     pub password: String,
     pub max_connections: usize,
+    /// Startup wait for PostgreSQL to become reachable (None = wait indefinitely)
+    pub wait_for_db_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +60,83 @@ pub struct ProcessingConfig {
     pub channel_name: String,
     pub retry_attempts: u32,
     pub retry_delay_ms: u64,
+    pub batch_size: usize,
+    /// Consecutive database outage errors opening the worker circuit breaker (0 = disabled)
+    pub circuit_breaker_threshold: u32,
+    pub circuit_breaker_probe_interval_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImageStorageConfig {
+    /// Keep base64 profile images inline in k_broadcasts (default)
+    Database,
+    Filesystem {
+        path: String,
+    },
+    S3 {
+        bucket: String,
+        region: Option<String>,
+        endpoint: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct MentionLimitConfig {
+    /// Maximum number of unique mentions indexed per post/reply
+    pub max_mentions: usize,
+    pub excess_action: ExcessMentionsAction,
+    /// Do not index mentions of users who have blocked the sender (checked at index time)
+    pub suppress_blocked: bool,
+}
+
+/// What to do with posts/replies mentioning more users than allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExcessMentionsAction {
+    /// Index the first max_mentions mentions and drop the rest
+    Ignore,
+    /// Index no mentions and flag the content as spam
+    Spam,
+}
+
+/// Index-time per-sender rate limits (None = unlimited)
+#[derive(Debug, Clone)]
+pub struct SenderRateLimitConfig {
+    /// Maximum posts/replies/quotes per sender per minute (by block time)
+    pub max_posts_per_minute: Option<u32>,
+    /// Maximum votes per sender per minute (by block time)
+    pub max_votes_per_minute: Option<u32>,
+    pub action: RateLimitAction,
+}
+
+/// Connection pool monitoring and Prometheus export
+#[derive(Debug, Clone)]
+pub struct MonitoringConfig {
+    /// Address of the Prometheus /metrics listener (None = metrics not exported)
+    pub metrics_bind_address: Option<SocketAddr>,
+    pub pool_monitor_interval_secs: u64,
+    /// Log a warning when acquiring a pooled connection takes longer than this
+    pub pool_acquire_warn_ms: u64,
+    /// Interval between K table storage reports (k_storage_snapshots)
+    pub storage_report_interval_secs: u64,
+}
+
+/// Background detection of profiles imitating the nickname of popular users (k_impersonation_flags)
+#[derive(Debug, Clone)]
+pub struct ImpersonationConfig {
+    pub check_interval_secs: u64,
+    /// Only users with at least this many followers are protected
+    pub min_followers: i64,
+    /// Normalized nickname similarity (0.0 - 1.0) at which a profile is flagged
+    pub threshold: f64,
+}
+
+/// What to do with content from senders exceeding the rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitAction {
+    /// Index the content and mark it as rate-limited (is_rate_limited)
+    Mark,
+    /// Do not index the content
+    Skip,
 }
 
 impl AppConfig {
@@ -42,49 +151,351 @@ impl AppConfig {
         )
     }
 
-    pub fn from_args(args: &Args) -> Self {
+    pub fn from_args(database: &DatabaseArgs, args: &ProcessingArgs) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        // Validate database hosts (comma-separated for failover)
+        let hosts = errors.check(failover::parse_hosts(
+            database.db_host.as_deref().unwrap_or("localhost"),
+            database.db_port.unwrap_or(5432),
+        ));
+        let target_session_attrs = errors.check(TargetSessionAttrs::parse(
+            database.db_target_session_attrs.as_deref().unwrap_or("any"),
+        ));
+
         // Validate network parameter
-        let network = args.network.trim().to_string();
-        if network != "testnet-10" && network != "mainnet" {
-            panic!(
-                "Invalid network type '{}'. Must be 'testnet-10' or 'mainnet'",
-                network
-            );
+        let network = database.network.trim();
+        let chain = ChainParams::from_name(network);
+        if chain.is_none() {
+            errors.push(format!(
+                "Invalid network type '{}'. Must be {}",
+                network,
+                chain_params::network_names()
+            ));
+        }
+
+        let tenants: Vec<Tenant> = database
+            .tenants
+            .iter()
+            .filter_map(|definition| errors.check(Tenant::parse(definition)))
+            .collect();
+        if let Some(chain) = &chain {
+            errors.check(tenants::validate_tenants(&tenants, chain));
+        }
+
+        // Validate image storage parameters
+        let image_storage = match args.image_storage.as_deref().unwrap_or("database").trim() {
+            "database" => Some(ImageStorageConfig::Database),
+            "filesystem" => errors
+                .check(args.image_storage_path.clone().ok_or_else(|| {
+                    "--image-storage-path is required for filesystem image storage".to_string()
+                }))
+                .map(|path| ImageStorageConfig::Filesystem { path }),
+            "s3" => errors
+                .check(args.image_storage_bucket.clone().ok_or_else(|| {
+                    "--image-storage-bucket is required for s3 image storage".to_string()
+                }))
+                .map(|bucket| ImageStorageConfig::S3 {
+                    bucket,
+                    region: args.image_storage_region.clone(),
+                    endpoint: args.image_storage_endpoint.clone(),
+                }),
+            other => errors.check(Err(format!(
+                "Invalid image storage '{}'. Must be 'database', 'filesystem' or 's3'",
+                other
+            ))),
+        };
+
+        // Validate mention limit parameters
+        let excess_action = match args.excess_mentions.as_deref().unwrap_or("ignore").trim() {
+            "ignore" => Some(ExcessMentionsAction::Ignore),
+            "spam" => Some(ExcessMentionsAction::Spam),
+            other => errors.check(Err(format!(
+                "Invalid excess mentions action '{}'. Must be 'ignore' or 'spam'",
+                other
+            ))),
+        };
+
+        // Validate sender rate limit parameters
+        let rate_limit_action = match args.rate_limit_action.as_deref().unwrap_or("mark").trim() {
+            "mark" => Some(RateLimitAction::Mark),
+            "skip" => Some(RateLimitAction::Skip),
+            other => errors.check(Err(format!(
+                "Invalid rate limit action '{}'. Must be 'mark' or 'skip'",
+                other
+            ))),
+        };
+
+        // Validate metrics listener address
+        let metrics_bind_address = match args.metrics_bind_address.as_deref() {
+            Some(address) => errors
+                .check(address.trim().parse::<SocketAddr>().map_err(|_| {
+                    format!(
+                        "Invalid metrics bind address '{}'. Expected host:port, e.g. 0.0.0.0:9100",
+                        address
+                    )
+                }))
+                .map(Some),
+            None => Some(None),
+        };
+
+        // Validate impersonation threshold
+        let impersonation_threshold = args.impersonation_threshold.unwrap_or(0.85);
+        if !(0.0..=1.0).contains(&impersonation_threshold) {
+            errors.push(format!(
+                "Invalid impersonation threshold '{}'. Must be between 0.0 and 1.0",
+                impersonation_threshold
+            ));
         }
 
-        Self {
+        // Validate the LISTEN channel (the k_transactions trigger notifies transaction_channel)
+        let channel_name = args
+            .channel
+            .clone()
+            .unwrap_or_else(|| "transaction_channel".to_string());
+        if !is_channel_name(&channel_name) {
+            errors.push(format!(
+                "Invalid channel '{}'. Must be 1-63 lowercase letters, digits or underscores, not starting with a digit",
+                channel_name
+            ));
+        }
+
+        // Validate retry delay (also the reconnection delay of the notification listener)
+        let retry_delay_ms = args.retry_delay.unwrap_or(1000);
+        if !(MIN_RETRY_DELAY_MS..=MAX_RETRY_DELAY_MS).contains(&retry_delay_ms) {
+            errors.push(format!(
+                "Invalid --retry-delay {}. Must be between {} and {} milliseconds",
+                retry_delay_ms, MIN_RETRY_DELAY_MS, MAX_RETRY_DELAY_MS
+            ));
+        }
+
+        // Validate counts and intervals
+        let max_connections = errors.at_least(
+            "--db-max-connections",
+            database.db_max_connections.unwrap_or(10),
+            1,
+        );
+        let workers = errors.at_least("--workers", args.workers.unwrap_or(4), 1);
+        let batch_size = errors.at_least("--batch-size", args.batch_size.unwrap_or(50), 1);
+        let circuit_breaker_probe_interval_secs = errors.at_least(
+            "--circuit-breaker-probe-interval",
+            args.circuit_breaker_probe_interval.unwrap_or(5),
+            1,
+        );
+        let pool_monitor_interval_secs = errors.at_least(
+            "--pool-monitor-interval",
+            args.pool_monitor_interval.unwrap_or(15),
+            1,
+        );
+        let storage_report_interval_secs = errors.at_least(
+            "--storage-report-interval",
+            args.storage_report_interval.unwrap_or(86400),
+            60,
+        );
+        let impersonation_check_interval_secs = errors.at_least(
+            "--impersonation-check-interval",
+            args.impersonation_check_interval.unwrap_or(600),
+            10,
+        );
+        let impersonation_min_followers = errors.at_least(
+            "--impersonation-min-followers",
+            args.impersonation_min_followers.unwrap_or(10),
+            1,
+        );
+        let maintenance_check_interval_secs = errors.at_least(
+            "--maintenance-check-interval",
+            args.maintenance_check_interval.unwrap_or(10),
+            1,
+        );
+        let vote_totals_interval_secs = errors.at_least(
+            "--vote-totals-interval",
+            args.vote_totals_interval.unwrap_or(3600),
+            60,
+        );
+        let image_gc_interval_secs = errors.at_least(
+            "--image-gc-interval",
+            args.image_gc_interval.unwrap_or(3600),
+            60,
+        );
+
+        let (
+            Some(hosts),
+            Some(target_session_attrs),
+            Some(chain),
+            Some(image_storage),
+            Some(excess_action),
+            Some(rate_limit_action),
+            Some(metrics_bind_address),
+        ) = (
+            hosts,
+            target_session_attrs,
+            chain,
+            image_storage,
+            excess_action,
+            rate_limit_action,
+            metrics_bind_address,
+        )
+        else {
+            return Err(errors);
+        };
+        errors.into_result()?;
+
+        Ok(Self {
             database: DatabaseConfig {
-                host: args
-                    .db_host
-                    .clone()
-                    .unwrap_or_else(|| "localhost".to_string()),
-                port: args.db_port.unwrap_or(5432),
-                database: args
+                host: hosts[0].host.clone(),
+                port: hosts[0].port,
+                hosts,
+                target_session_attrs,
+                database: database
                     .db_name
                     .clone()
                     .unwrap_or_else(|| "your_database".to_string()),
-                username: args
+                username: database
                     .db_user
                     .clone()
                     .unwrap_or_else(|| "your_user".to_string()),
-                password: args
+                password: database
                     .db_password
                     .clone()
                     .unwrap_or_else(|| "your_password".to_string()),
-                max_connections: args.db_max_connections.unwrap_or(10),
-            },
-            workers: WorkerConfig {
-                count: args.workers.unwrap_or(4),
+                max_connections,
+                wait_for_db_secs: database.wait_for_db,
             },
+            workers: WorkerConfig { count: workers },
             processing: ProcessingConfig {
-                channel_name: args
-                    .channel
-                    .clone()
-                    .unwrap_or_else(|| "transaction_channel".to_string()),
+                channel_name,
                 retry_attempts: args.retry_attempts.unwrap_or(3),
-                retry_delay_ms: args.retry_delay.unwrap_or(1000),
+                retry_delay_ms,
+                batch_size,
+                circuit_breaker_threshold: args.circuit_breaker_threshold.unwrap_or(5),
+                circuit_breaker_probe_interval_secs,
+            },
+            image_storage,
+            mentions: MentionLimitConfig {
+                max_mentions: args.max_mentions.unwrap_or(20),
+                excess_action,
+                suppress_blocked: args.suppress_blocked_mentions,
+            },
+            sender_rate_limit: SenderRateLimitConfig {
+                max_posts_per_minute: args.max_posts_per_minute,
+                max_votes_per_minute: args.max_votes_per_minute,
+                action: rate_limit_action,
+            },
+            monitoring: MonitoringConfig {
+                metrics_bind_address,
+                pool_monitor_interval_secs,
+                pool_acquire_warn_ms: args.pool_acquire_warn_ms.unwrap_or(500),
+                storage_report_interval_secs,
             },
-            network,
+            impersonation: ImpersonationConfig {
+                check_interval_secs: impersonation_check_interval_secs,
+                min_followers: impersonation_min_followers,
+                threshold: impersonation_threshold,
+            },
+            maintenance_check_interval_secs,
+            vote_totals_interval_secs,
+            image_gc_interval_secs,
+            vote_fee_weighting: args.vote_fee_weighting,
+            confirmation_depth: args.confirmation_depth.unwrap_or(0),
+            chain,
+            tenants,
+        })
+    }
+}
+
+/// PostgreSQL identifier usable unquoted as a LISTEN/NOTIFY channel
+fn is_channel_name(name: &str) -> bool {
+    name.len() <= 63
+        && name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestArgs {
+        #[command(flatten)]
+        database: DatabaseArgs,
+        #[command(flatten)]
+        processing: ProcessingArgs,
+    }
+
+    fn config(extra_args: &[&str]) -> Result<AppConfig, ConfigErrors> {
+        let args =
+            TestArgs::try_parse_from(["K-transaction-processor"].iter().chain(extra_args)).unwrap();
+        AppConfig::from_args(&args.database, &args.processing)
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = config(&[]).unwrap();
+        assert_eq!(config.workers.count, 4);
+        assert_eq!(config.processing.channel_name, "transaction_channel");
+    }
+
+    #[test]
+    fn test_zero_workers() {
+        let errors = config(&["--workers", "0"]).unwrap_err();
+        assert_eq!(errors.errors(), ["Invalid --workers 0. Must be at least 1"]);
+    }
+
+    #[test]
+    fn test_channel_name() {
+        for name in ["k_transactions", "_channel", "channel_2", &"c".repeat(63)] {
+            assert!(config(&["--channel", name]).is_ok(), "{}", name);
         }
+        for name in [
+            "",
+            "Transaction_channel",
+            "2channel",
+            "k-transactions",
+            "k transactions",
+            "k_transactions;",
+            "kanał",
+            &"c".repeat(64),
+        ] {
+            let errors = config(&["--channel", name]).unwrap_err();
+            assert_eq!(
+                errors.errors(),
+                [format!(
+                    "Invalid channel '{}'. Must be 1-63 lowercase letters, digits or underscores, not starting with a digit",
+                    name
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors_reported_together() {
+        let errors = config(&[
+            "--network",
+            "devnet",
+            "--channel",
+            "K-Channel",
+            "--retry-delay",
+            "5",
+            "--workers",
+            "0",
+        ])
+        .unwrap_err();
+        assert_eq!(errors.errors().len(), 4);
+        assert!(errors.errors()[0].starts_with("Invalid network type 'devnet'"));
+        assert!(errors.errors()[1].starts_with("Invalid channel 'K-Channel'"));
+        assert_eq!(
+            errors.errors()[2],
+            "Invalid --retry-delay 5. Must be between 10 and 60000 milliseconds"
+        );
+        assert_eq!(
+            errors.errors()[3],
+            "Invalid --workers 0. Must be at least 1"
+        );
     }
 }
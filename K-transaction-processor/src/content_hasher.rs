@@ -0,0 +1,75 @@
+use base64::{Engine as _, engine::general_purpose};
+use sha2::{Digest, Sha256};
+
+/// Invisible characters inserted to make copies of a message look different
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Normalize message text so trivially varied copies compare equal:
+/// lowercase, zero-width characters removed, whitespace runs collapsed to one space
+pub fn normalize_message(text: &str) -> String {
+    text.chars()
+        .filter(|c| !is_zero_width(*c))
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// SHA-256 of the normalized text of a base64-encoded message
+/// Returns None for undecodable or empty messages (nothing to compare)
+pub fn content_hash_from_base64(base64_message: &str) -> Option<Vec<u8>> {
+    let decoded_bytes = general_purpose::STANDARD.decode(base64_message).ok()?;
+    let decoded_text = String::from_utf8(decoded_bytes).ok()?;
+
+    let normalized = normalize_message(&decoded_text);
+    if normalized.is_empty() {
+        return None;
+    }
+    Some(Sha256::digest(normalized.as_bytes()).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(text: &str) -> Option<Vec<u8>> {
+        content_hash_from_base64(&general_purpose::STANDARD.encode(text))
+    }
+
+    #[test]
+    fn test_normalize_case_and_whitespace() {
+        assert_eq!(
+            normalize_message("  Buy   NOW\n\tat example.com "),
+            "buy now at example.com"
+        );
+    }
+
+    #[test]
+    fn test_normalize_removes_zero_width_characters() {
+        assert_eq!(normalize_message("fr\u{200B}ee K\u{FEFF}AS"), "free kas");
+    }
+
+    #[test]
+    fn test_trivial_variations_hash_the_same() {
+        assert_eq!(hash("Free KAS here"), hash("free  kas\u{200D} here\n"));
+    }
+
+    #[test]
+    fn test_different_messages_hash_differently() {
+        assert_ne!(hash("Free KAS here"), hash("Free KAS there"));
+    }
+
+    #[test]
+    fn test_empty_message_has_no_hash() {
+        assert_eq!(hash(""), None);
+        assert_eq!(hash(" \n\u{200B} "), None);
+    }
+
+    #[test]
+    fn test_invalid_base64_has_no_hash() {
+        assert_eq!(content_hash_from_base64("not base64!"), None);
+    }
+}
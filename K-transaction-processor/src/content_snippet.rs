@@ -0,0 +1,54 @@
+use base64::{Engine as _, engine::general_purpose};
+
+/// Characters of a message kept in the notification snippets of k_mentions
+pub const SNIPPET_MAX_CHARS: usize = 280;
+
+/// Base64 snippet of a base64-encoded message: its first SNIPPET_MAX_CHARS characters,
+/// cut on a character boundary so the snippet always decodes to valid UTF-8
+/// Returns None for undecodable messages
+pub fn snippet_from_base64(base64_message: &str) -> Option<String> {
+    let decoded_bytes = general_purpose::STANDARD.decode(base64_message).ok()?;
+    let decoded_text = String::from_utf8(decoded_bytes).ok()?;
+
+    match decoded_text.char_indices().nth(SNIPPET_MAX_CHARS) {
+        Some((cut, _)) => Some(general_purpose::STANDARD.encode(&decoded_text[..cut])),
+        // Short enough: stored as sent
+        None => Some(base64_message.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(snippet: &str) -> String {
+        String::from_utf8(general_purpose::STANDARD.decode(snippet).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_short_message_is_kept() {
+        let message = general_purpose::STANDARD.encode("Hello K!");
+        assert_eq!(snippet_from_base64(&message), Some(message));
+    }
+
+    #[test]
+    fn test_long_message_is_cut() {
+        let text = "a".repeat(SNIPPET_MAX_CHARS + 20);
+        let snippet = snippet_from_base64(&general_purpose::STANDARD.encode(&text)).unwrap();
+        assert_eq!(decode(&snippet), "a".repeat(SNIPPET_MAX_CHARS));
+    }
+
+    #[test]
+    fn test_cut_on_character_boundary() {
+        let text = "€".repeat(SNIPPET_MAX_CHARS + 1);
+        let snippet = snippet_from_base64(&general_purpose::STANDARD.encode(&text)).unwrap();
+        assert_eq!(decode(&snippet).chars().count(), SNIPPET_MAX_CHARS);
+    }
+
+    #[test]
+    fn test_undecodable_message() {
+        assert_eq!(snippet_from_base64("not base64!"), None);
+        let invalid_utf8 = general_purpose::STANDARD.encode([0xff, 0xfe]);
+        assert_eq!(snippet_from_base64(&invalid_utf8), None);
+    }
+}
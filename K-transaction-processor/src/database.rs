@@ -1,22 +1,73 @@
+use crate::chain_params::{self, ChainParams};
 use crate::config::AppConfig;
+use crate::tenants::Tenant;
 use anyhow::Result;
-use sqlx::{PgPool, Row, postgres::PgPoolOptions};
+use k_common::startup::{self, STARTUP_RETRY_SECS};
+use sqlx::{Executor, PgPool, Row, postgres::PgPoolOptions};
 use tracing::{error, info, warn};
 
 pub type DbPool = PgPool;
 
 // Schema version management
-const SCHEMA_VERSION: i32 = 2;
+pub const SCHEMA_VERSION: i32 = 34;
+
+/// Features supported by the current schema, published in k_meta for K-webserver
+const SCHEMA_FEATURES: &[&str] = &[
+    "contents",
+    "blocks",
+    "follows",
+    "hashtags",
+    "profile_image_blobs",
+    "spam_flags",
+    "rate_limit_flags",
+    "maintenance_mode",
+    "groups",
+    "events",
+    "articles",
+    "tips",
+    "views",
+    "feature_flags",
+    "erasure",
+    "tombstone_registry",
+    "content_hashes",
+    "storage_stats",
+    "archive",
+    "profile_history",
+    "impersonation_flags",
+    "vote_totals",
+    "enriched_mentions",
+    "cold_tier",
+    "profile_image_dedup",
+    "render_spans",
+    "vote_fees",
+    "visibility_watermark",
+    "nickname_history",
+    "featured",
+    "full_text_search",
+    "live_events",
+    "write_intents",
+    "thread_roots",
+];
 
 /// K-transaction-processor Database Client
 /// Similar to KaspaDbClient in Simply Kaspa Indexer
 pub struct KDbClient {
     pool: DbPool,
+    // Community of the schema (None = the network's own one in public)
+    tenant: Option<Tenant>,
 }
 
 impl KDbClient {
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        Self { pool, tenant: None }
+    }
+
+    /// Client of the schema of a tenant, `pool` coming from create_tenant_pool
+    pub fn for_tenant(pool: DbPool, tenant: Tenant) -> Self {
+        Self {
+            pool,
+            tenant: Some(tenant),
+        }
     }
 
     pub fn pool(&self) -> &DbPool {
@@ -24,8 +75,10 @@ impl KDbClient {
     }
 
     /// Verify that transactions table exists (required for trigger)
-    /// Loops with warning and 10-second wait if not found
-    async fn verify_transactions_table_exists(&self) -> Result<()> {
+    /// With --wait-for-schema, waits until the Kaspa indexer has created it instead of failing
+    async fn verify_transactions_table_exists(&self, wait_for_schema: bool) -> Result<()> {
+        let started = std::time::Instant::now();
+
         loop {
             let table_exists = sqlx::query(
                 "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_name = 'transactions')"
@@ -39,24 +92,72 @@ impl KDbClient {
                     "✓ Transactions table found - proceeding with K-transaction-processor schema setup"
                 );
                 return Ok(());
-            } else {
-                warn!(
-                    "⚠️  Transactions table not found - K-transaction-processor requires the main Kaspa indexer to be running first"
-                );
-                warn!("   Waiting 10 seconds before checking again...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
             }
+
+            if !wait_for_schema {
+                return Err(anyhow::anyhow!(
+                    "Transactions table not found - K-transaction-processor requires the main Kaspa indexer to be running first. Set flag '--wait-for-schema' to wait for it"
+                ));
+            }
+
+            warn!(
+                "⚠️  Waiting for the main Kaspa indexer to create the transactions table ({}s elapsed)",
+                started.elapsed().as_secs()
+            );
+            tokio::time::sleep(tokio::time::Duration::from_secs(STARTUP_RETRY_SECS)).await;
         }
     }
 
     /// Drop existing schema (equivalent to KaspaDbClient::drop_schema)
     pub async fn drop_schema(&self) -> Result<()> {
+        // The notification trigger belongs to the network's own schema
+        if let Some(tenant) = &self.tenant {
+            info!(
+                "Dropping schema {} of tenant {}",
+                tenant.schema(),
+                tenant.id
+            );
+            sqlx::query(&format!(
+                "DROP SCHEMA IF EXISTS {} CASCADE",
+                tenant.schema()
+            ))
+            .execute(&self.pool)
+            .await?;
+            return Ok(());
+        }
+
         info!("Dropping existing schema");
         execute_ddl(SCHEMA_DOWN_SQL, &self.pool).await?;
         info!("Schema dropped successfully");
         Ok(())
     }
 
+    /// Verify schema version, K tables, indexes and the notification trigger
+    pub async fn verify_schema(&self) -> Result<()> {
+        verify_schema_setup(&self.pool).await
+    }
+
+    /// Verify the network type stored in k_vars without setting it
+    pub async fn verify_network(&self, network: &str) -> Result<()> {
+        let stored_network: Option<String> =
+            sqlx::query_scalar("SELECT value FROM k_vars WHERE key = 'network'")
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match stored_network {
+            Some(stored_network) if stored_network == network => {
+                info!("✓ Network type verified: {}", network);
+                Ok(())
+            }
+            Some(stored_network) => Err(anyhow::anyhow!(
+                "Network mismatch! Database is configured for '{}' but K-transaction-processor is set to '{}'",
+                stored_network,
+                network
+            )),
+            None => Err(anyhow::anyhow!("Network type not set in k_vars")),
+        }
+    }
+
     /// Set or verify network type in k_vars table
     pub async fn set_and_verify_network(&self, network: &str) -> Result<()> {
         info!("Setting and verifying network type: {}", network);
@@ -95,11 +196,12 @@ impl KDbClient {
     }
 
     /// Create or upgrade schema (equivalent to KaspaDbClient::create_schema)
-    pub async fn create_schema(&self, upgrade_db: bool) -> Result<()> {
+    pub async fn create_schema(&self, upgrade_db: bool, wait_for_schema: bool) -> Result<()> {
         info!("Starting schema creation/upgrade process");
 
         // Verify transactions table exists (required for trigger)
-        self.verify_transactions_table_exists().await?;
+        self.verify_transactions_table_exists(wait_for_schema)
+            .await?;
 
         // Check current schema version
         let current_version = get_schema_version(&self.pool).await?;
@@ -131,13 +233,269 @@ impl KDbClient {
                             info!("Migration v1 -> v2 completed successfully");
                         }
 
+                        // v2 -> v3: Add k_meta table for schema/feature handshake
+                        if current_version == 2 {
+                            info!("Applying migration v2 -> v3 (k_meta handshake table)");
+                            execute_ddl(MIGRATION_V2_TO_V3_SQL, &self.pool).await?;
+                            current_version = 3;
+                            info!("Migration v2 -> v3 completed successfully");
+                        }
+
+                        // v3 -> v4: Add profile image blob reference columns
+                        if current_version == 3 {
+                            info!("Applying migration v3 -> v4 (profile image blob storage)");
+                            execute_ddl(MIGRATION_V3_TO_V4_SQL, &self.pool).await?;
+                            current_version = 4;
+                            info!("Migration v3 -> v4 completed successfully");
+                        }
+
+                        // v4 -> v5: Add dead-letter table for permanently failed transactions
+                        if current_version == 4 {
+                            info!("Applying migration v4 -> v5 (dead-letter table)");
+                            execute_ddl(MIGRATION_V4_TO_V5_SQL, &self.pool).await?;
+                            current_version = 5;
+                            info!("Migration v4 -> v5 completed successfully");
+                        }
+
+                        // v5 -> v6: Add spam flag to k_contents (mention limits)
+                        if current_version == 5 {
+                            info!("Applying migration v5 -> v6 (content spam flag)");
+                            execute_ddl(MIGRATION_V5_TO_V6_SQL, &self.pool).await?;
+                            current_version = 6;
+                            info!("Migration v5 -> v6 completed successfully");
+                        }
+
+                        // v6 -> v7: Add rate-limited flag to k_contents and k_votes
+                        if current_version == 6 {
+                            info!("Applying migration v6 -> v7 (sender rate limit flags)");
+                            execute_ddl(MIGRATION_V6_TO_V7_SQL, &self.pool).await?;
+                            current_version = 7;
+                            info!("Migration v6 -> v7 completed successfully");
+                        }
+
+                        // v7 -> v8: Add maintenance mode flag to k_meta
+                        if current_version == 7 {
+                            info!("Applying migration v7 -> v8 (maintenance mode flag)");
+                            execute_ddl(MIGRATION_V7_TO_V8_SQL, &self.pool).await?;
+                            current_version = 8;
+                            info!("Migration v7 -> v8 completed successfully");
+                        }
+
+                        // v8 -> v9: Add group tables
+                        if current_version == 8 {
+                            info!("Applying migration v8 -> v9 (group posting)");
+                            execute_ddl(MIGRATION_V8_TO_V9_SQL, &self.pool).await?;
+                            current_version = 9;
+                            info!("Migration v8 -> v9 completed successfully");
+                        }
+
+                        // v9 -> v10: Add event calendar tables
+                        if current_version == 9 {
+                            info!("Applying migration v9 -> v10 (event calendar)");
+                            execute_ddl(MIGRATION_V9_TO_V10_SQL, &self.pool).await?;
+                            current_version = 10;
+                            info!("Migration v9 -> v10 completed successfully");
+                        }
+
+                        // v10 -> v11: Add long-form article tables
+                        if current_version == 10 {
+                            info!("Applying migration v10 -> v11 (long-form articles)");
+                            execute_ddl(MIGRATION_V10_TO_V11_SQL, &self.pool).await?;
+                            current_version = 11;
+                            info!("Migration v10 -> v11 completed successfully");
+                        }
+
+                        // v11 -> v12: Add tips table
+                        if current_version == 11 {
+                            info!("Applying migration v11 -> v12 (tips)");
+                            execute_ddl(MIGRATION_V11_TO_V12_SQL, &self.pool).await?;
+                            current_version = 12;
+                            info!("Migration v11 -> v12 completed successfully");
+                        }
+
+                        // v12 -> v13: Add post views table
+                        if current_version == 12 {
+                            info!("Applying migration v12 -> v13 (post views)");
+                            execute_ddl(MIGRATION_V12_TO_V13_SQL, &self.pool).await?;
+                            current_version = 13;
+                            info!("Migration v12 -> v13 completed successfully");
+                        }
+
+                        // v13 -> v14: Add feature flags table
+                        if current_version == 13 {
+                            info!("Applying migration v13 -> v14 (feature flags)");
+                            execute_ddl(MIGRATION_V13_TO_V14_SQL, &self.pool).await?;
+                            current_version = 14;
+                            info!("Migration v13 -> v14 completed successfully");
+                        }
+
+                        // v14 -> v15: Add erasure requests and tombstones tables
+                        if current_version == 14 {
+                            info!("Applying migration v14 -> v15 (erasure)");
+                            execute_ddl(MIGRATION_V14_TO_V15_SQL, &self.pool).await?;
+                            current_version = 15;
+                            info!("Migration v14 -> v15 completed successfully");
+                        }
+
+                        // v15 -> v16: Tombstone registry (takedowns, sender tombstones, import source)
+                        if current_version == 15 {
+                            info!("Applying migration v15 -> v16 (tombstone registry)");
+                            execute_ddl(MIGRATION_V15_TO_V16_SQL, &self.pool).await?;
+                            current_version = 16;
+                            info!("Migration v15 -> v16 completed successfully");
+                        }
+
+                        // v16 -> v17: Add content hashes table
+                        if current_version == 16 {
+                            info!("Applying migration v16 -> v17 (content hashes)");
+                            execute_ddl(MIGRATION_V16_TO_V17_SQL, &self.pool).await?;
+                            current_version = 17;
+                            info!("Migration v16 -> v17 completed successfully");
+                        }
+
+                        // v17 -> v18: Add storage snapshots table
+                        if current_version == 17 {
+                            info!("Applying migration v17 -> v18 (storage snapshots)");
+                            execute_ddl(MIGRATION_V17_TO_V18_SQL, &self.pool).await?;
+                            current_version = 18;
+                            info!("Migration v17 -> v18 completed successfully");
+                        }
+
+                        // v18 -> v19: Add archive tables
+                        if current_version == 18 {
+                            info!("Applying migration v18 -> v19 (archive tables)");
+                            execute_ddl(MIGRATION_V18_TO_V19_SQL, &self.pool).await?;
+                            current_version = 19;
+                            info!("Migration v18 -> v19 completed successfully");
+                        }
+
+                        // v19 -> v20: Add profile history table
+                        if current_version == 19 {
+                            info!("Applying migration v19 -> v20 (profile history)");
+                            execute_ddl(MIGRATION_V19_TO_V20_SQL, &self.pool).await?;
+                            current_version = 20;
+                            info!("Migration v19 -> v20 completed successfully");
+                        }
+
+                        // v20 -> v21: Add impersonation flags table
+                        if current_version == 20 {
+                            info!("Applying migration v20 -> v21 (impersonation flags)");
+                            execute_ddl(MIGRATION_V20_TO_V21_SQL, &self.pool).await?;
+                            current_version = 21;
+                            info!("Migration v20 -> v21 completed successfully");
+                        }
+
+                        // v21 -> v22: Add pre-computed vote totals
+                        if current_version == 21 {
+                            info!("Applying migration v21 -> v22 (vote totals)");
+                            execute_ddl(MIGRATION_V21_TO_V22_SQL, &self.pool).await?;
+                            current_version = 22;
+                            info!("Migration v21 -> v22 completed successfully");
+                        }
+
+                        // v22 -> v23: Denormalize notification data into k_mentions
+                        if current_version == 22 {
+                            info!("Applying migration v22 -> v23 (enriched mentions)");
+                            execute_ddl(MIGRATION_V22_TO_V23_SQL, &self.pool).await?;
+                            current_version = 23;
+                            info!("Migration v22 -> v23 completed successfully");
+                        }
+
+                        // v23 -> v24: Add the cold content tier
+                        if current_version == 23 {
+                            info!("Applying migration v23 -> v24 (cold tier)");
+                            execute_ddl(MIGRATION_V23_TO_V24_SQL, &self.pool).await?;
+                            current_version = 24;
+                            info!("Migration v23 -> v24 completed successfully");
+                        }
+
+                        // v24 -> v25: Reference count profile images in blob storage
+                        if current_version == 24 {
+                            info!("Applying migration v24 -> v25 (profile image dedup)");
+                            execute_ddl(MIGRATION_V24_TO_V25_SQL, &self.pool).await?;
+                            current_version = 25;
+                            info!("Migration v24 -> v25 completed successfully");
+                        }
+
+                        // v25 -> v26: Add rendering hints of contents
+                        if current_version == 25 {
+                            info!("Applying migration v25 -> v26 (render spans)");
+                            execute_ddl(MIGRATION_V25_TO_V26_SQL, &self.pool).await?;
+                            current_version = 26;
+                            info!("Migration v25 -> v26 completed successfully");
+                        }
+
+                        // v26 -> v27: Add vote fees and fee-weighted vote totals
+                        if current_version == 26 {
+                            info!("Applying migration v26 -> v27 (vote fees)");
+                            execute_ddl(MIGRATION_V26_TO_V27_SQL, &self.pool).await?;
+                            current_version = 27;
+                            info!("Migration v26 -> v27 completed successfully");
+                        }
+
+                        // v27 -> v28: Add the visibility watermark of feeds
+                        if current_version == 27 {
+                            info!("Applying migration v27 -> v28 (visibility watermark)");
+                            execute_ddl(MIGRATION_V27_TO_V28_SQL, &self.pool).await?;
+                            current_version = 28;
+                            info!("Migration v27 -> v28 completed successfully");
+                        }
+
+                        // v28 -> v29: Add normalized nicknames to the profile history
+                        if current_version == 28 {
+                            info!("Applying migration v28 -> v29 (nickname history)");
+                            execute_ddl(MIGRATION_V28_TO_V29_SQL, &self.pool).await?;
+                            current_version = 29;
+                            info!("Migration v28 -> v29 completed successfully");
+                        }
+
+                        // v29 -> v30: Add k_featured table for operator-curated contents
+                        if current_version == 29 {
+                            info!("Applying migration v29 -> v30 (featured contents)");
+                            execute_ddl(MIGRATION_V29_TO_V30_SQL, &self.pool).await?;
+                            current_version = 30;
+                            info!("Migration v29 -> v30 completed successfully");
+                        }
+
+                        // v30 -> v31: Add a full-text search vector to k_contents
+                        if current_version == 30 {
+                            info!("Applying migration v30 -> v31 (full-text search)");
+                            execute_ddl(MIGRATION_V30_TO_V31_SQL, &self.pool).await?;
+                            current_version = 31;
+                            info!("Migration v30 -> v31 completed successfully");
+                        }
+
+                        // v31 -> v32: Add k_write_intents, the intent log of the workers
+                        if current_version == 31 {
+                            info!("Applying migration v31 -> v32 (write intents)");
+                            execute_ddl(MIGRATION_V31_TO_V32_SQL, &self.pool).await?;
+                            current_version = 32;
+                            info!("Migration v31 -> v32 completed successfully");
+                        }
+
+                        // v32 -> v33: Add the thread root of replies to k_contents
+                        if current_version == 32 {
+                            info!("Applying migration v32 -> v33 (thread roots)");
+                            execute_ddl(MIGRATION_V32_TO_V33_SQL, &self.pool).await?;
+                            current_version = 33;
+                            info!("Migration v32 -> v33 completed successfully");
+                        }
+
+                        // v33 -> v34: Complete the rows of the archive tables
+                        if current_version == 33 {
+                            info!("Applying migration v33 -> v34 (complete archive rows)");
+                            execute_ddl(MIGRATION_V33_TO_V34_SQL, &self.pool).await?;
+                            current_version = 34;
+                            info!("Migration v33 -> v34 completed successfully");
+                        }
+
                         info!(
                             "Schema upgrade completed successfully (final version: {})",
                             current_version
                         );
                     } else {
                         return Err(anyhow::anyhow!(
-                            "Found outdated schema v{}. Set flag '--upgrade-db' to upgrade",
+                            "Found outdated schema v{}. Run the 'migrate' subcommand (or 'run --migrate') to upgrade",
                             version
                         ));
                     }
@@ -159,7 +517,10 @@ impl KDbClient {
                 execute_ddl(SCHEMA_UP_SQL, &self.pool).await?;
 
                 // Create the notification function and trigger separately to avoid parsing issues
-                self.create_notification_system().await?;
+                // (one trigger on transactions notifies the payloads of every tenant)
+                if self.tenant.is_none() {
+                    self.create_notification_system().await?;
+                }
 
                 info!("Fresh schema creation completed successfully");
             }
@@ -168,30 +529,52 @@ impl KDbClient {
         // Verify schema setup
         verify_schema_setup(&self.pool).await?;
 
+        // Publish schema version and features for K-webserver
+        self.publish_schema_meta().await?;
+
         info!("Schema creation/upgrade process completed");
         Ok(())
     }
 
-    /// Create the notification function and trigger separately to avoid DDL parsing issues
-    async fn create_notification_system(&self) -> Result<()> {
-        info!("Creating notification function and trigger");
+    /// Write the active schema version and supported features to k_meta
+    /// K-webserver reads this row to decide which endpoints to expose
+    async fn publish_schema_meta(&self) -> Result<()> {
+        let features: Vec<String> = SCHEMA_FEATURES.iter().map(|f| f.to_string()).collect();
+        let updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64;
 
-        // Create the function using dollar quoting
         sqlx::query(
             r#"
-            CREATE OR REPLACE FUNCTION notify_transaction() RETURNS TRIGGER AS $$
-            BEGIN
-                IF substr(encode(NEW.payload, 'hex'), 1, 8) = '6b3a313a' THEN
-                    PERFORM pg_notify('transaction_channel', encode(NEW.transaction_id, 'hex'));
-                END IF;
-                RETURN NEW;
-            END;
-            $$ LANGUAGE plpgsql
-        "#,
+            INSERT INTO k_meta (id, schema_version, features, processor_version, updated_at)
+            VALUES (1, $1, $2, $3, $4)
+            ON CONFLICT (id) DO UPDATE SET
+                schema_version = EXCLUDED.schema_version,
+                features = EXCLUDED.features,
+                processor_version = EXCLUDED.processor_version,
+                updated_at = EXCLUDED.updated_at
+            "#,
         )
+        .bind(SCHEMA_VERSION)
+        .bind(&features)
+        .bind(env!("CARGO_PKG_VERSION"))
+        .bind(updated_at)
         .execute(&self.pool)
         .await?;
 
+        info!(
+            "✓ Published schema meta: version {}, features {:?}",
+            SCHEMA_VERSION, features
+        );
+        Ok(())
+    }
+
+    /// Create the notification function and trigger separately to avoid DDL parsing issues
+    async fn create_notification_system(&self) -> Result<()> {
+        info!("Creating notification function and trigger");
+
+        self.create_notification_function(&[]).await?;
+
         // Create the trigger
         sqlx::query(
             r#"
@@ -206,47 +589,232 @@ impl KDbClient {
         info!("Notification system created successfully");
         Ok(())
     }
+
+    /// Create or replace the function of the notification trigger, notifying the payloads of
+    /// the networks and of `tenants`
+    pub async fn create_notification_function(&self, tenants: &[Tenant]) -> Result<()> {
+        // The network is recorded after the schema is created, so notify on the K payload
+        // prefix of every network; workers only index the prefix of the configured one
+        let mut prefixes = chain_params::all_payload_prefixes_hex();
+        prefixes.extend(
+            tenants
+                .iter()
+                .map(|tenant| hex::encode(tenant.payload_prefix)),
+        );
+        let payload_condition = prefixes
+            .iter()
+            .map(|prefix| {
+                format!(
+                    "substr(encode(NEW.payload, 'hex'), 1, {}) = '{}'",
+                    prefix.len(),
+                    prefix
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        // Create the function using dollar quoting
+        sqlx::query(&format!(
+            r#"
+            CREATE OR REPLACE FUNCTION notify_transaction() RETURNS TRIGGER AS $$
+            BEGIN
+                IF {payload_condition} THEN
+                    PERFORM pg_notify('transaction_channel', encode(NEW.transaction_id, 'hex'));
+                END IF;
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql
+        "#
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
 
+/// K protocol tables checked by `verify` and `doctor`
+pub const K_TABLES: &[&str] = &[
+    "k_contents",
+    "k_broadcasts",
+    "k_votes",
+    "k_mentions",
+    "k_blocks",
+    "k_follows",
+    "k_hashtags",
+    "k_meta",
+    "k_dead_letters",
+    "k_groups",
+    "k_group_members",
+    "k_group_posts",
+    "k_events",
+    "k_event_rsvps",
+    "k_articles",
+    "k_article_chunks",
+    "k_tips",
+    "k_post_views",
+    "k_feature_flags",
+    "k_erasure_requests",
+    "k_tombstones",
+    "k_pubkey_tombstones",
+    "k_content_hashes",
+    "k_storage_snapshots",
+    "k_contents_archive",
+    "k_mentions_archive",
+    "k_profile_history",
+    "k_impersonation_flags",
+    "k_vote_totals",
+    "k_contents_cold",
+    "k_profile_image_blobs",
+    "k_render_spans",
+    "k_featured",
+    "k_write_intents",
+];
+
+/// K protocol indexes checked by `verify` and `doctor`
+pub const K_INDEXES: &[&str] = &[
+    // k_broadcasts indexes
+    "idx_k_broadcasts_transaction_id",
+    "idx_k_broadcasts_sender_pubkey",
+    "idx_k_broadcasts_block_time",
+    "idx_k_broadcasts_profile_image_ref",
+    // k_votes indexes
+    "idx_k_votes_transaction_id",
+    "idx_k_votes_sender_pubkey",
+    "idx_k_votes_sender_signature_unique",
+    "idx_k_votes_post_id",
+    "idx_k_votes_vote",
+    "idx_k_votes_block_time",
+    "idx_k_votes_post_id_sender",
+    "idx_k_votes_sender_block_time",
+    // k_mentions indexes
+    "idx_k_mentions_comprehensive",
+    "idx_k_mentions_content_id",
+    "idx_k_mentions_mentioned_pubkey",
+    // k_blocks indexes
+    "idx_k_blocks_sender_signature_unique",
+    "idx_k_blocks_sender_blocked_user_unique",
+    "idx_k_blocks_sender_pubkey",
+    "idx_k_blocks_blocked_user_pubkey",
+    "idx_k_blocks_block_time",
+    // k_contents indexes
+    "idx_k_contents_transaction_id",
+    "idx_k_contents_sender_signature_unique",
+    "idx_k_contents_sender_pubkey",
+    "idx_k_contents_block_time",
+    "idx_k_contents_replies",
+    "idx_k_contents_thread",
+    "idx_k_contents_search_vector",
+    "idx_k_contents_reposts",
+    "idx_k_contents_quotes",
+    "idx_k_contents_feed_optimized",
+    "idx_k_contents_content_type",
+    "idx_k_contents_sender_content_type",
+    // k_follows indexes
+    "idx_k_follows_sender_signature_unique",
+    "idx_k_follows_sender_followed_user_unique",
+    "idx_k_follows_followed_user_pubkey",
+    "idx_k_follows_sender_pubkey",
+    "idx_k_follows_block_time",
+    // k_hashtags indexes
+    "idx_k_hashtags_by_hashtag_time",
+    "idx_k_hashtags_pattern",
+    "idx_k_hashtags_trending",
+    "idx_k_hashtags_by_hashtag_sender",
+    // k_groups indexes
+    "idx_k_groups_sender_signature_unique",
+    "idx_k_groups_sender_pubkey",
+    "idx_k_groups_block_time",
+    // k_group_members indexes
+    "idx_k_group_members_group_member_unique",
+    "idx_k_group_members_member_pubkey",
+    // k_group_posts indexes
+    "idx_k_group_posts_sender_signature_unique",
+    "idx_k_group_posts_group_id",
+    "idx_k_group_posts_sender_pubkey",
+    // k_events indexes
+    "idx_k_events_sender_signature_unique",
+    "idx_k_events_sender_pubkey",
+    "idx_k_events_start_time",
+    "idx_k_events_end_time",
+    // k_event_rsvps indexes
+    "idx_k_event_rsvps_event_sender_unique",
+    "idx_k_event_rsvps_sender_pubkey",
+    // k_articles indexes
+    "idx_k_articles_sender_signature_unique",
+    "idx_k_articles_sender_pubkey",
+    // k_article_chunks indexes
+    "idx_k_article_chunks_manifest_chunk_unique",
+    // k_tips indexes
+    "idx_k_tips_sender_signature_unique",
+    "idx_k_tips_content_id",
+    "idx_k_tips_block_time",
+    // k_erasure_requests / k_tombstones indexes
+    "idx_k_erasure_requests_subject_pubkey",
+    "idx_k_tombstones_erasure_request_id",
+    "idx_k_content_hashes_block_time",
+    // archive table indexes
+    "idx_k_contents_archive_sender_pubkey",
+    "idx_k_mentions_archive_content_id",
+    // k_profile_history indexes
+    "idx_k_profile_history_sender_pubkey",
+    // k_impersonation_flags indexes
+    "idx_k_impersonation_flags_status",
+    // k_profile_image_blobs indexes
+    "idx_k_profile_image_blobs_orphaned",
+    // k_featured indexes
+    "idx_k_featured_position",
+];
+
 // Embedded SQL migration files
 const SCHEMA_UP_SQL: &str = include_str!("migrations/schema/up.sql");
 const SCHEMA_DOWN_SQL: &str = include_str!("migrations/schema/down.sql");
 const MIGRATION_V0_TO_V1_SQL: &str = include_str!("migrations/schema/v0_to_v1.sql");
 const MIGRATION_V1_TO_V2_SQL: &str = include_str!("migrations/schema/v1_to_v2.sql");
+const MIGRATION_V2_TO_V3_SQL: &str = include_str!("migrations/schema/v2_to_v3.sql");
+const MIGRATION_V3_TO_V4_SQL: &str = include_str!("migrations/schema/v3_to_v4.sql");
+const MIGRATION_V4_TO_V5_SQL: &str = include_str!("migrations/schema/v4_to_v5.sql");
+const MIGRATION_V5_TO_V6_SQL: &str = include_str!("migrations/schema/v5_to_v6.sql");
+const MIGRATION_V6_TO_V7_SQL: &str = include_str!("migrations/schema/v6_to_v7.sql");
+const MIGRATION_V7_TO_V8_SQL: &str = include_str!("migrations/schema/v7_to_v8.sql");
+const MIGRATION_V8_TO_V9_SQL: &str = include_str!("migrations/schema/v8_to_v9.sql");
+const MIGRATION_V9_TO_V10_SQL: &str = include_str!("migrations/schema/v9_to_v10.sql");
+const MIGRATION_V10_TO_V11_SQL: &str = include_str!("migrations/schema/v10_to_v11.sql");
+const MIGRATION_V11_TO_V12_SQL: &str = include_str!("migrations/schema/v11_to_v12.sql");
+const MIGRATION_V12_TO_V13_SQL: &str = include_str!("migrations/schema/v12_to_v13.sql");
+const MIGRATION_V13_TO_V14_SQL: &str = include_str!("migrations/schema/v13_to_v14.sql");
+const MIGRATION_V14_TO_V15_SQL: &str = include_str!("migrations/schema/v14_to_v15.sql");
+const MIGRATION_V15_TO_V16_SQL: &str = include_str!("migrations/schema/v15_to_v16.sql");
+const MIGRATION_V16_TO_V17_SQL: &str = include_str!("migrations/schema/v16_to_v17.sql");
+const MIGRATION_V17_TO_V18_SQL: &str = include_str!("migrations/schema/v17_to_v18.sql");
+const MIGRATION_V18_TO_V19_SQL: &str = include_str!("migrations/schema/v18_to_v19.sql");
+const MIGRATION_V19_TO_V20_SQL: &str = include_str!("migrations/schema/v19_to_v20.sql");
+const MIGRATION_V20_TO_V21_SQL: &str = include_str!("migrations/schema/v20_to_v21.sql");
+const MIGRATION_V21_TO_V22_SQL: &str = include_str!("migrations/schema/v21_to_v22.sql");
+const MIGRATION_V22_TO_V23_SQL: &str = include_str!("migrations/schema/v22_to_v23.sql");
+const MIGRATION_V23_TO_V24_SQL: &str = include_str!("migrations/schema/v23_to_v24.sql");
+const MIGRATION_V24_TO_V25_SQL: &str = include_str!("migrations/schema/v24_to_v25.sql");
+const MIGRATION_V25_TO_V26_SQL: &str = include_str!("migrations/schema/v25_to_v26.sql");
+const MIGRATION_V26_TO_V27_SQL: &str = include_str!("migrations/schema/v26_to_v27.sql");
+const MIGRATION_V27_TO_V28_SQL: &str = include_str!("migrations/schema/v27_to_v28.sql");
+const MIGRATION_V28_TO_V29_SQL: &str = include_str!("migrations/schema/v28_to_v29.sql");
+const MIGRATION_V29_TO_V30_SQL: &str = include_str!("migrations/schema/v29_to_v30.sql");
+const MIGRATION_V30_TO_V31_SQL: &str = include_str!("migrations/schema/v30_to_v31.sql");
+const MIGRATION_V31_TO_V32_SQL: &str = include_str!("migrations/schema/v31_to_v32.sql");
+const MIGRATION_V32_TO_V33_SQL: &str = include_str!("migrations/schema/v32_to_v33.sql");
+const MIGRATION_V33_TO_V34_SQL: &str = include_str!("migrations/schema/v33_to_v34.sql");
 
+/// Connect to PostgreSQL (the first suitable host of --db-host), retrying until reachable or until
+/// --wait-for-db seconds elapsed
 pub async fn create_pool(config: &AppConfig) -> Result<DbPool> {
-    let connection_string = config.connection_string();
-
-    loop {
-        match PgPoolOptions::new()
-            .max_connections(config.database.max_connections as u32)
-            .connect(&connection_string)
-            .await
-        {
-            Ok(pool) => {
-                // Test the pool connection
-                match sqlx::query("SELECT 1").fetch_one(&pool).await {
-                    Ok(_) => {
-                        info!("Database connection pool created and tested successfully");
-                        return Ok(pool);
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Database connection pool created but test query failed: {}",
-                            e
-                        );
-                        warn!("Retrying in 10 seconds...");
-                        tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to create database connection pool: {}", e);
-                warn!("Retrying in 10 seconds...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-            }
-        }
-    }
+    startup::create_pool(
+        &config.connection_string(),
+        &config.database.hosts,
+        config.database.target_session_attrs,
+        config.database.max_connections as u32,
+        config.database.wait_for_db_secs,
+    )
+    .await
 }
 
 #[derive(Debug, Clone)]
@@ -291,10 +859,288 @@ pub async fn fetch_transaction(
     }
 }
 
-async fn get_schema_version(pool: &DbPool) -> Result<Option<i32>> {
+/// Pool whose connections find the K tables in the schema of `tenant`, and the transactions
+/// table of the Kaspa indexer in public, on the host `main_pool` (create_pool) is connected to
+pub async fn create_tenant_pool(
+    main_pool: &DbPool,
+    config: &AppConfig,
+    tenant: &Tenant,
+) -> Result<DbPool> {
+    let set_search_path = format!("SET search_path TO {}, public", tenant.schema());
+    let pool = PgPoolOptions::new()
+        .max_connections(config.database.max_connections as u32)
+        .after_connect(move |conn, _meta| {
+            let set_search_path = set_search_path.clone();
+            Box::pin(async move {
+                conn.execute(set_search_path.as_str()).await?;
+                Ok(())
+            })
+        })
+        .connect_with((*main_pool.connect_options()).clone())
+        .await?;
+    Ok(pool)
+}
+
+/// Create the schema of `tenant` if missing (its K tables are created by its KDbClient)
+pub async fn create_tenant_schema(pool: &DbPool, tenant: &Tenant) -> Result<()> {
+    sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", tenant.schema()))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Block time margin before the processed watermark from which it is read back (reconciliation
+/// after a listener reconnection, backfill): the indexer may store transactions of older blocks
+/// than those already processed (e.g. while it catches up after a failover), and notifications
+/// reach the workers in no particular block time order
+pub const PROCESSED_WATERMARK_MARGIN_MS: i64 = 10 * 60 * 1000;
+
+/// Advance the processed watermark (block_time up to which K transactions are committed, see
+/// InFlightBatches) in k_vars
+/// Must only be called after the batch transaction has been committed
+pub async fn advance_processed_watermark(pool: &DbPool, block_time: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO k_vars (key, value) VALUES ('processed_block_time', $1)
+        ON CONFLICT (key) DO UPDATE
+        SET value = GREATEST(k_vars.value::BIGINT, EXCLUDED.value::BIGINT)::TEXT
+        "#,
+    )
+    .bind(block_time.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Read the processed watermark (block_time up to which K transactions are committed) from k_vars
+pub async fn get_processed_watermark(pool: &DbPool) -> Result<Option<i64>> {
+    let value: Option<String> =
+        sqlx::query_scalar("SELECT value FROM k_vars WHERE key = 'processed_block_time'")
+            .fetch_optional(pool)
+            .await?;
+
+    value
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|_| anyhow::anyhow!("Invalid processed watermark format: {}", value))
+        })
+        .transpose()
+}
+
+/// Fetch one page of K transactions (payload starting with the payload prefix of the network,
+/// k:1:) in a block time range, ordered by (block_time, transaction_id) and starting after the
+/// given position
+pub async fn fetch_k_transaction_page(
+    pool: &DbPool,
+    chain: &ChainParams,
+    from_block_time: i64,
+    to_block_time: i64,
+    after: Option<&(i64, Vec<u8>)>,
+    limit: i64,
+) -> Result<Vec<(i64, Vec<u8>)>> {
+    let (after_block_time, after_transaction_id) = match after {
+        Some((block_time, transaction_id)) => (*block_time, transaction_id.clone()),
+        None => (from_block_time - 1, Vec::new()),
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT block_time, transaction_id
+        FROM transactions
+        WHERE block_time >= $1
+          AND block_time <= $2
+          AND (block_time, transaction_id) > ($3, $4)
+          AND substr(payload, 1, length($6)) = $6
+        ORDER BY block_time, transaction_id
+        LIMIT $5
+        "#,
+    )
+    .bind(from_block_time)
+    .bind(to_block_time)
+    .bind(after_block_time)
+    .bind(&after_transaction_id)
+    .bind(limit)
+    .bind(chain.payload_prefix.as_bytes())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("block_time"), row.get("transaction_id")))
+        .collect())
+}
+
+/// Whether the Kaspa indexer writes transaction inputs with the amount of their previous outpoint
+pub async fn has_resolved_input_amounts(pool: &DbPool) -> Result<bool> {
+    let exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM information_schema.columns
+            WHERE table_schema = current_schema()
+              AND table_name = 'transactions_inputs'
+              AND column_name = 'previous_outpoint_amount'
+        )
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+/// Read the read-only maintenance flag from k_meta
+pub async fn is_maintenance_mode(pool: &DbPool) -> Result<bool> {
+    let row = sqlx::query("SELECT maintenance_mode FROM k_meta WHERE id = 1")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some_and(|row| row.get::<bool, _>("maintenance_mode")))
+}
+
+/// Enable or disable read-only maintenance mode in k_meta
+/// K-webserver answers non-read requests with 503 and Retry-After: retry_after_secs while enabled
+pub async fn set_maintenance_mode(
+    pool: &DbPool,
+    enabled: bool,
+    retry_after_secs: u32,
+    reason: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE k_meta
+        SET maintenance_mode = $1,
+            maintenance_retry_after = $2,
+            maintenance_reason = $3
+        WHERE id = 1
+        "#,
+    )
+    .bind(enabled)
+    .bind(retry_after_secs as i32)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Advisory lock key coordinating worker batches with K-database-cleaner purges ("K_PURGE")
+/// Must match PURGE_LOCK_KEY in K-database-cleaner
+pub const PURGE_LOCK_KEY: i64 = 0x004b_5f50_5552_4745;
+
+/// Hold the purge lock in shared mode until the end of the current transaction:
+/// batches run concurrently with each other, never while the cleaner deletes rows
+pub async fn lock_shared_against_purge<'e, E>(executor: E) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("SELECT pg_advisory_xact_lock_shared($1)")
+        .bind(PURGE_LOCK_KEY)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Record a transaction that failed permanently (or exhausted its retries) in k_dead_letters
+pub async fn insert_dead_letter<'e, E>(
+    executor: E,
+    transaction_id_hex: &str,
+    error_class: &str,
+    error_message: &str,
+    attempts: u32,
+) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let transaction_id_bytes = hex::decode(transaction_id_hex)?;
+    let failed_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+
+    sqlx::query(
+        r#"
+        INSERT INTO k_dead_letters (transaction_id, error_class, error_message, attempts, failed_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET error_class = EXCLUDED.error_class,
+            error_message = EXCLUDED.error_message,
+            attempts = EXCLUDED.attempts,
+            failed_at = EXCLUDED.failed_at
+        "#,
+    )
+    .bind(&transaction_id_bytes)
+    .bind(error_class)
+    .bind(error_message)
+    .bind(attempts as i32)
+    .bind(failed_at)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the K transactions of a batch in k_write_intents before writing it
+pub async fn record_write_intents(
+    pool: &DbPool,
+    worker_id: usize,
+    transaction_ids_hex: &[&str],
+) -> Result<()> {
+    let transaction_ids_bytes = transaction_ids_hex
+        .iter()
+        .map(hex::decode)
+        .collect::<Result<Vec<_>, _>>()?;
+    let recorded_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+
+    sqlx::query(
+        r#"
+        INSERT INTO k_write_intents (transaction_id, worker_id, recorded_at)
+        SELECT unnest($1::bytea[]), $2, $3
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET worker_id = EXCLUDED.worker_id,
+            recorded_at = EXCLUDED.recorded_at
+        "#,
+    )
+    .bind(&transaction_ids_bytes)
+    .bind(worker_id as i32)
+    .bind(recorded_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the write intents of K transactions committed, dead-lettered or not found anymore
+pub async fn clear_write_intents(pool: &DbPool, transaction_ids_hex: &[&str]) -> Result<()> {
+    let transaction_ids_bytes = transaction_ids_hex
+        .iter()
+        .map(hex::decode)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    sqlx::query("DELETE FROM k_write_intents WHERE transaction_id = ANY($1)")
+        .bind(&transaction_ids_bytes)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// K transactions (hex) of the write intents left by batches a previous run did not finish
+pub async fn get_write_intents(pool: &DbPool) -> Result<Vec<String>> {
+    let transaction_ids: Vec<Vec<u8>> = sqlx::query_scalar(
+        "SELECT transaction_id FROM k_write_intents ORDER BY recorded_at, transaction_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transaction_ids.iter().map(hex::encode).collect())
+}
+
+pub async fn get_schema_version(pool: &DbPool) -> Result<Option<i32>> {
     // Check if k_vars table exists
     let table_exists = sqlx::query(
-        "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_name = 'k_vars')",
+        "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = 'k_vars')",
     )
     .fetch_one(pool)
     .await?
@@ -353,20 +1199,11 @@ async fn verify_schema_setup(pool: &DbPool) -> Result<()> {
     }
 
     // Check K protocol tables
-    let tables = vec![
-        "k_contents",
-        "k_broadcasts",
-        "k_votes",
-        "k_mentions",
-        "k_blocks",
-        "k_follows",
-        "k_hashtags",
-    ];
     let mut all_verified = true;
 
-    for table in &tables {
+    for table in K_TABLES {
         let table_exists = sqlx::query(
-            "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_name = $1)",
+            "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = $1)",
         )
         .bind(table)
         .fetch_one(pool)
@@ -402,59 +1239,13 @@ async fn verify_schema_setup(pool: &DbPool) -> Result<()> {
         all_verified = false;
     }
 
-    // Explicit verification of all 37 expected K protocol indexes
-    let expected_indexes = vec![
-        // k_broadcasts indexes
-        "idx_k_broadcasts_transaction_id",
-        "idx_k_broadcasts_sender_pubkey",
-        "idx_k_broadcasts_block_time",
-        // k_votes indexes
-        "idx_k_votes_transaction_id",
-        "idx_k_votes_sender_pubkey",
-        "idx_k_votes_sender_signature_unique",
-        "idx_k_votes_post_id",
-        "idx_k_votes_vote",
-        "idx_k_votes_block_time",
-        "idx_k_votes_post_id_sender",
-        // k_mentions indexes
-        "idx_k_mentions_comprehensive",
-        "idx_k_mentions_content_id",
-        "idx_k_mentions_mentioned_pubkey",
-        // k_blocks indexes
-        "idx_k_blocks_sender_signature_unique",
-        "idx_k_blocks_sender_blocked_user_unique",
-        "idx_k_blocks_sender_pubkey",
-        "idx_k_blocks_blocked_user_pubkey",
-        "idx_k_blocks_block_time",
-        // k_contents indexes
-        "idx_k_contents_transaction_id",
-        "idx_k_contents_sender_signature_unique",
-        "idx_k_contents_sender_pubkey",
-        "idx_k_contents_block_time",
-        "idx_k_contents_replies",
-        "idx_k_contents_reposts",
-        "idx_k_contents_quotes",
-        "idx_k_contents_feed_optimized",
-        "idx_k_contents_content_type",
-        "idx_k_contents_sender_content_type",
-        // k_follows indexes
-        "idx_k_follows_sender_signature_unique",
-        "idx_k_follows_sender_followed_user_unique",
-        "idx_k_follows_followed_user_pubkey",
-        "idx_k_follows_sender_pubkey",
-        "idx_k_follows_block_time",
-        // k_hashtags indexes
-        "idx_k_hashtags_by_hashtag_time",
-        "idx_k_hashtags_pattern",
-        "idx_k_hashtags_trending",
-        "idx_k_hashtags_by_hashtag_sender",
-    ];
+    // Explicit verification of all 65 expected K protocol indexes
 
     let mut missing_indexes = Vec::new();
 
-    for index_name in &expected_indexes {
+    for index_name in K_INDEXES {
         let index_exists =
-            sqlx::query("SELECT EXISTS(SELECT 1 FROM pg_indexes WHERE indexname = $1)")
+            sqlx::query("SELECT EXISTS(SELECT 1 FROM pg_indexes WHERE schemaname = current_schema() AND indexname = $1)")
                 .bind(index_name)
                 .fetch_one(pool)
                 .await?
@@ -469,19 +1260,21 @@ async fn verify_schema_setup(pool: &DbPool) -> Result<()> {
         }
     }
 
-    // Verify total count matches expected (37 indexes)
-    let index_count = sqlx::query("SELECT COUNT(*) FROM pg_indexes WHERE indexname LIKE 'idx_k_%'")
+    // Verify total count matches expected (65 indexes)
+    let index_count = sqlx::query(
+        "SELECT COUNT(*) FROM pg_indexes WHERE schemaname = current_schema() AND indexname LIKE 'idx_k_%'",
+    )
         .fetch_one(pool)
         .await?
         .get::<i64, _>(0);
 
-    if index_count == 37 {
+    if index_count == 65 {
         info!(
-            "  ✓ Expected 37 K protocol indexes verified (found {})",
+            "  ✓ Expected 65 K protocol indexes verified (found {})",
             index_count
         );
     } else {
-        error!("  ✗ Expected 37 K protocol indexes, found {}", index_count);
+        error!("  ✗ Expected 65 K protocol indexes, found {}", index_count);
         all_verified = false;
     }
 
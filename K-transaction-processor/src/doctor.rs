@@ -0,0 +1,250 @@
+use crate::config::{AppConfig, ImageStorageConfig};
+use crate::database::{K_INDEXES, K_TABLES, SCHEMA_VERSION, get_schema_version};
+use anyhow::Result;
+use k_common::doctor::{
+    Report, Status, check_database_disk, check_disk, check_extensions, connect, notified_channel,
+};
+use sqlx::postgres::PgListener;
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+
+/// Indexes of the transactions table (created by the main Kaspa indexer) the processor relies on
+const TRANSACTION_INDEXES: &[&str] = &["transactions_pkey", "transactions_block_time_idx"];
+
+/// Check database connectivity, extensions, indexes, schema version, LISTEN channel and disk
+/// space, printing one line per check
+pub async fn run(config: &AppConfig) -> Result<()> {
+    let mut report = Report::new();
+    println!(
+        "K-transaction-processor v{} doctor",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let database = &config.database;
+    let Some(pool) = connect(
+        &mut report,
+        "Database",
+        &config.connection_string(),
+        &format!("{}:{}/{}", database.host, database.port, database.database),
+        // The LISTEN check holds a second connection
+        2,
+    )
+    .await
+    else {
+        return report.finish();
+    };
+
+    check_extensions(&mut report, &pool).await;
+    check_schema(&mut report, &pool, config.chain.name).await;
+    check_tables_and_indexes(&mut report, &pool).await;
+    check_listen_channel(&mut report, &pool, &config.processing.channel_name).await;
+    check_database_disk(&mut report, &pool, &config.database.host).await;
+    if let ImageStorageConfig::Filesystem { path } = &config.image_storage {
+        check_disk(&mut report, "Image storage disk", path);
+    }
+
+    pool.close().await;
+    report.finish()
+}
+
+async fn check_schema(report: &mut Report, pool: &PgPool, network: &str) {
+    match get_schema_version(pool).await {
+        Ok(Some(version)) if version == SCHEMA_VERSION => {
+            report.check(Status::Ok, "Schema version", format!("v{}", version))
+        }
+        Ok(Some(version)) if version < SCHEMA_VERSION => report.check(
+            Status::Fail,
+            "Schema version",
+            format!(
+                "v{}, this binary expects v{} (run 'migrate')",
+                version, SCHEMA_VERSION
+            ),
+        ),
+        Ok(Some(version)) => report.check(
+            Status::Fail,
+            "Schema version",
+            format!(
+                "v{} is newer than v{} expected by this binary (upgrade the processor)",
+                version, SCHEMA_VERSION
+            ),
+        ),
+        Ok(None) => {
+            report.check(
+                Status::Fail,
+                "Schema version",
+                "no K schema found (run 'migrate' or 'run')",
+            );
+            return;
+        }
+        Err(e) => {
+            report.check(Status::Fail, "Schema version", e.to_string());
+            return;
+        }
+    }
+
+    match sqlx::query_scalar::<_, String>("SELECT value FROM k_vars WHERE key = 'network'")
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(stored)) if stored == network => report.check(Status::Ok, "Network", stored),
+        Ok(Some(stored)) => report.check(
+            Status::Fail,
+            "Network",
+            format!("database indexes '{}', --network is '{}'", stored, network),
+        ),
+        Ok(None) => report.check(Status::Warn, "Network", "not set yet (set on first 'run')"),
+        Err(e) => report.check(Status::Fail, "Network", e.to_string()),
+    }
+}
+
+async fn check_tables_and_indexes(report: &mut Report, pool: &PgPool) {
+    match sqlx::query_scalar::<_, String>("SELECT tablename::text FROM pg_tables")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(tables) => {
+            let tables: HashSet<String> = tables.into_iter().collect();
+            let missing: Vec<&str> = std::iter::once("transactions")
+                .chain(K_TABLES.iter().copied())
+                .filter(|table| !tables.contains(*table))
+                .collect();
+            if missing.is_empty() {
+                report.check(
+                    Status::Ok,
+                    "Tables",
+                    format!("transactions and {} K tables", K_TABLES.len()),
+                );
+            } else {
+                report.check(Status::Fail, "Tables", missing_detail(&missing));
+            }
+        }
+        Err(e) => report.check(Status::Fail, "Tables", e.to_string()),
+    }
+
+    match sqlx::query_scalar::<_, String>("SELECT indexname::text FROM pg_indexes")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(indexes) => {
+            let indexes: HashSet<String> = indexes.into_iter().collect();
+            let missing: Vec<&str> = TRANSACTION_INDEXES
+                .iter()
+                .chain(K_INDEXES.iter())
+                .copied()
+                .filter(|index| !indexes.contains(*index))
+                .collect();
+            if missing.is_empty() {
+                report.check(
+                    Status::Ok,
+                    "Indexes",
+                    format!(
+                        "{} transactions and {} K indexes",
+                        TRANSACTION_INDEXES.len(),
+                        K_INDEXES.len()
+                    ),
+                );
+            } else {
+                report.check(Status::Fail, "Indexes", missing_detail(&missing));
+            }
+        }
+        Err(e) => report.check(Status::Fail, "Indexes", e.to_string()),
+    }
+}
+
+/// First few missing objects, so that an empty database does not print every K index
+fn missing_detail(missing: &[&str]) -> String {
+    if missing.len() > 5 {
+        format!(
+            "{} missing: {}, ...",
+            missing.len(),
+            missing[..5].join(", ")
+        )
+    } else {
+        format!("missing: {}", missing.join(", "))
+    }
+}
+
+/// The trigger on transactions must notify the channel the processor listens on
+async fn check_listen_channel(report: &mut Report, pool: &PgPool, channel: &str) {
+    let trigger = sqlx::query(
+        r#"
+        SELECT pg_get_functiondef(p.oid) AS definition,
+               EXISTS(
+                   SELECT 1 FROM pg_trigger
+                   WHERE tgname = 'transaction_notify_trigger' AND tgenabled <> 'D'
+               ) AS trigger_enabled
+        FROM pg_proc p
+        WHERE p.proname = 'notify_transaction'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await;
+
+    let (definition, trigger_enabled) = match trigger {
+        Ok(Some(row)) => (
+            row.get::<String, _>("definition"),
+            row.get::<bool, _>("trigger_enabled"),
+        ),
+        Ok(None) => {
+            report.check(
+                Status::Fail,
+                "LISTEN channel",
+                "notify_transaction() not found (run 'migrate' or 'run')",
+            );
+            return;
+        }
+        Err(e) => {
+            report.check(Status::Fail, "LISTEN channel", e.to_string());
+            return;
+        }
+    };
+
+    if !trigger_enabled {
+        report.check(
+            Status::Fail,
+            "LISTEN channel",
+            "transaction_notify_trigger missing or disabled",
+        );
+        return;
+    }
+    match notified_channel(&definition) {
+        Some(notified) if notified == channel => {}
+        Some(notified) => {
+            report.check(
+                Status::Fail,
+                "LISTEN channel",
+                format!(
+                    "trigger notifies '{}', --channel is '{}'",
+                    notified, channel
+                ),
+            );
+            return;
+        }
+        None => {
+            report.check(
+                Status::Fail,
+                "LISTEN channel",
+                "notify_transaction() does not call pg_notify",
+            );
+            return;
+        }
+    }
+
+    // The channel exists once someone listens on it: make sure this user can
+    let listened = match PgListener::connect_with(pool).await {
+        Ok(mut listener) => listener.listen(channel).await,
+        Err(e) => Err(e),
+    };
+    match listened {
+        Ok(()) => report.check(
+            Status::Ok,
+            "LISTEN channel",
+            format!("'{}' notified by transaction_notify_trigger", channel),
+        ),
+        Err(e) => report.check(
+            Status::Fail,
+            "LISTEN channel",
+            format!("LISTEN {} failed: {}", channel, e),
+        ),
+    }
+}
@@ -2,6 +2,7 @@ use base64::{Engine as _, engine::general_purpose};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashSet;
+use std::ops::Range;
 use tracing::warn;
 
 // Compile regex patterns once at startup
@@ -12,31 +13,12 @@ static VALID_HASHTAG_PATTERN: Lazy<Regex> = Lazy::new(|| {
 
 static ALL_HASH_PATTERNS: Lazy<Regex> = Lazy::new(|| Regex::new(r"#[^\s]+").unwrap());
 
-/// Extract hashtags from a base64-encoded message
-/// Returns a vector of unique hashtags (lowercase, without # prefix)
-pub fn extract_hashtags_from_base64(base64_message: &str) -> Vec<String> {
-    // 1. Decode base64
-    let decoded_bytes = match general_purpose::STANDARD.decode(base64_message) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            warn!("Failed to decode base64 message: {}", e);
-            return vec![];
-        }
-    };
-
-    let decoded_text = match String::from_utf8(decoded_bytes) {
-        Ok(text) => text,
-        Err(e) => {
-            warn!("Failed to convert decoded bytes to UTF-8: {}", e);
-            return vec![];
-        }
-    };
-
-    // 2. Pass 1: Extract valid hashtags (with Unicode support)
-    let mut valid_hashtags = HashSet::new();
+/// Byte ranges of the valid hashtags of a decoded message, '#' prefix included
+pub fn hashtag_ranges(decoded_text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
 
     // Use find_iter to get all matches and manually validate boundaries
-    for mat in VALID_HASHTAG_PATTERN.find_iter(&decoded_text) {
+    for mat in VALID_HASHTAG_PATTERN.find_iter(decoded_text) {
         let start_pos = mat.start();
         let end_pos = mat.end();
 
@@ -66,13 +48,41 @@ pub fn extract_hashtags_from_base64(base64_message: &str) -> Vec<String> {
             }
         };
 
-        // Only add if both boundaries are valid
+        // Only keep if both boundaries are valid
         if valid_before && valid_after {
-            let hashtag = &mat.as_str()[1..]; // Remove the # prefix
-            valid_hashtags.insert(hashtag.to_lowercase());
+            ranges.push(mat.range());
         }
     }
 
+    ranges
+}
+
+/// Extract hashtags from a base64-encoded message
+/// Returns a vector of unique hashtags (lowercase, without # prefix)
+pub fn extract_hashtags_from_base64(base64_message: &str) -> Vec<String> {
+    // 1. Decode base64
+    let decoded_bytes = match general_purpose::STANDARD.decode(base64_message) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to decode base64 message: {}", e);
+            return vec![];
+        }
+    };
+
+    let decoded_text = match String::from_utf8(decoded_bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Failed to convert decoded bytes to UTF-8: {}", e);
+            return vec![];
+        }
+    };
+
+    // 2. Pass 1: Extract valid hashtags (with Unicode support)
+    let valid_hashtags: HashSet<String> = hashtag_ranges(&decoded_text)
+        .into_iter()
+        .map(|range| decoded_text[range.start + 1..range.end].to_lowercase()) // Remove the # prefix
+        .collect();
+
     // 3. Pass 2: Detect and warn about invalid patterns
     for capture in ALL_HASH_PATTERNS.captures_iter(&decoded_text) {
         let full_match = capture.get(0).unwrap().as_str();
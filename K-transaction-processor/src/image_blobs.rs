@@ -0,0 +1,200 @@
+use crate::blob_storage::{BlobStorage, StoredBlob};
+use anyhow::Result;
+use metrics::counter;
+use sqlx::{PgConnection, PgPool, Row};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Unreferenced blobs are kept this long (ms): clients and caches may still hold the URL of a
+/// replaced image, and a broadcast of the same image can take the blob over again
+const ORPHAN_GRACE_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Blobs deleted per transaction by the collector
+const COLLECT_BATCH_SIZE: i64 = 100;
+
+/// Whether a profile image blob is stored and referenced, so the upload can be skipped
+/// Locks the k_profile_image_blobs row until the transaction ends: the collector skips locked
+/// rows, so the blob cannot be deleted before this broadcast takes its reference
+pub async fn is_referenced(conn: &mut PgConnection, blob: &StoredBlob) -> Result<bool> {
+    let ref_count: Option<i64> = sqlx::query_scalar(
+        "SELECT ref_count FROM k_profile_image_blobs WHERE blob_key = $1 FOR UPDATE",
+    )
+    .bind(&blob.key)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(ref_count.is_some_and(|ref_count| ref_count > 0))
+}
+
+/// Take a reference on a profile image blob for a newly indexed broadcast
+pub async fn acquire(conn: &mut PgConnection, blob: &StoredBlob) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO k_profile_image_blobs (blob_key, image_hash, ref_count)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (blob_key) DO UPDATE SET
+            ref_count = k_profile_image_blobs.ref_count + 1,
+            orphaned_at = NULL
+        "#,
+    )
+    .bind(&blob.key)
+    .bind(&blob.hash)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Release the references held by the k_broadcasts rows matching `filter` ($1 = keys)
+/// Called right before those rows are purged, blobs left without references become orphaned
+pub async fn release(
+    conn: &mut PgConnection,
+    filter: &str,
+    keys: &[Vec<u8>],
+    now: i64,
+) -> Result<u64> {
+    let released = sqlx::query(&format!(
+        r#"
+        UPDATE k_profile_image_blobs b
+        SET ref_count = b.ref_count - r.refs,
+            orphaned_at = CASE WHEN b.ref_count - r.refs <= 0 THEN $2 END
+        FROM (
+            SELECT profile_image_ref, COUNT(*) AS refs
+            FROM k_broadcasts
+            WHERE profile_image_ref IS NOT NULL AND ({})
+            GROUP BY profile_image_ref
+        ) r
+        WHERE b.blob_key = r.profile_image_ref
+        "#,
+        filter
+    ))
+    .bind(keys)
+    .bind(now)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    Ok(released)
+}
+
+/// Periodically reconcile reference counts with k_broadcasts and delete profile image blobs
+/// that have been unreferenced for longer than the grace period
+pub async fn start_image_collector(
+    pool: PgPool,
+    blob_storage: Arc<BlobStorage>,
+    interval_secs: u64,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = reconcile_ref_counts(&pool).await {
+            warn!("Profile image reference count reconciliation failed: {}", e);
+            continue;
+        }
+        if let Err(e) = collect_orphaned_images(&pool, &blob_storage).await {
+            warn!("Profile image collection failed: {}", e);
+        }
+    }
+}
+
+/// Correct counts of references removed by other tools or older versions of them
+async fn reconcile_ref_counts(pool: &PgPool) -> Result<()> {
+    let now = now_millis()?;
+
+    let corrected = sqlx::query(
+        r#"
+        WITH actual AS (
+            SELECT b.blob_key, COUNT(k.id) AS refs
+            FROM k_profile_image_blobs b
+            LEFT JOIN k_broadcasts k ON k.profile_image_ref = b.blob_key
+            GROUP BY b.blob_key
+        )
+        UPDATE k_profile_image_blobs b
+        SET ref_count = a.refs,
+            orphaned_at = CASE WHEN a.refs = 0 THEN COALESCE(b.orphaned_at, $1) END
+        FROM actual a
+        WHERE b.blob_key = a.blob_key AND b.ref_count <> a.refs
+        "#,
+    )
+    .bind(now)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    counter!("k_profile_image_refs_corrected_total").increment(corrected);
+    if corrected > 0 {
+        info!(
+            "Profile image reference counts reconciled: {} corrected",
+            corrected
+        );
+    }
+    Ok(())
+}
+
+async fn collect_orphaned_images(pool: &PgPool, blob_storage: &BlobStorage) -> Result<()> {
+    let start = std::time::Instant::now();
+    let cutoff = now_millis()? - ORPHAN_GRACE_MS;
+    let mut deleted_total = 0;
+
+    loop {
+        // Rows stay locked until their blobs are deleted: a broadcast of the same image waits,
+        // then finds no row and uploads the blob again. k_broadcasts is checked as well, so a
+        // count that is briefly off never deletes a referenced blob
+        let mut tx = pool.begin().await?;
+        let orphans = sqlx::query(
+            r#"
+            SELECT blob_key FROM k_profile_image_blobs b
+            WHERE ref_count <= 0 AND orphaned_at < $1
+              AND NOT EXISTS (SELECT 1 FROM k_broadcasts k WHERE k.profile_image_ref = b.blob_key)
+            ORDER BY orphaned_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(cutoff)
+        .bind(COLLECT_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut deleted = Vec::with_capacity(orphans.len());
+        for row in &orphans {
+            let blob_key: String = row.get("blob_key");
+            match blob_storage.delete(&blob_key).await {
+                Ok(()) => deleted.push(blob_key),
+                Err(e) => warn!("Failed to delete profile image {}: {}", blob_key, e),
+            }
+        }
+
+        sqlx::query("DELETE FROM k_profile_image_blobs WHERE blob_key = ANY($1)")
+            .bind(&deleted)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        deleted_total += deleted.len();
+        counter!("k_profile_images_deleted_total").increment(deleted.len() as u64);
+
+        // A short batch is the last one, failed deletions are retried on the next run
+        if (orphans.len() as i64) < COLLECT_BATCH_SIZE || deleted.is_empty() {
+            break;
+        }
+    }
+
+    if deleted_total > 0 {
+        info!(
+            "Deleted {} unreferenced profile images from {} storage in {:.2}s",
+            deleted_total,
+            blob_storage.backend(),
+            start.elapsed().as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+fn now_millis() -> Result<i64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64)
+}
@@ -0,0 +1,216 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::ImpersonationConfig;
+use crate::nickname_similarity::{normalize_base64_nickname, similarity};
+
+// Last k_profile_history id compared against protected profiles
+const WATERMARK_KEY: &str = "impersonation_checked_id";
+
+// New profile broadcasts read per pass
+const BATCH_SIZE: i64 = 1000;
+
+// Most followed users protected against impersonation
+const MAX_TARGETS: i64 = 5000;
+
+/// A user whose nickname is worth imitating
+struct Target {
+    pubkey: Vec<u8>,
+    base64_nickname: String,
+    normalized_nickname: String,
+    followers: i64,
+}
+
+/// Periodically compare the nicknames of new profile broadcasts against the nicknames of users
+/// with many followers and flag likely impersonators in k_impersonation_flags for review
+pub async fn start_impersonation_detector(pool: PgPool, config: ImpersonationConfig) {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.check_interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = check_new_profiles(&pool, &config).await {
+            warn!("Impersonation check failed: {}", e);
+        }
+    }
+}
+
+async fn check_new_profiles(pool: &PgPool, config: &ImpersonationConfig) -> Result<()> {
+    let mut checked_id: i64 =
+        sqlx::query_scalar::<_, String>("SELECT value FROM k_vars WHERE key = $1")
+            .bind(WATERMARK_KEY)
+            .fetch_optional(pool)
+            .await?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+
+    let targets = load_targets(pool, config.min_followers).await?;
+    let mut checked = 0usize;
+    let mut flagged = 0usize;
+
+    loop {
+        let profiles = sqlx::query(
+            r#"
+            SELECT id, transaction_id, sender_pubkey, base64_encoded_nickname
+            FROM k_profile_history
+            WHERE id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(checked_id)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+        let Some(last) = profiles.last() else {
+            break;
+        };
+        let last_id: i64 = last.get("id");
+
+        for profile in &profiles {
+            let suspect_pubkey: Vec<u8> = profile.get("sender_pubkey");
+            let base64_nickname: String = profile.get("base64_encoded_nickname");
+            let Some(normalized) = normalize_base64_nickname(&base64_nickname) else {
+                continue;
+            };
+            checked += 1;
+
+            for target in &targets {
+                if target.pubkey == suspect_pubkey {
+                    continue;
+                }
+                let score = similarity(&normalized, &target.normalized_nickname);
+                if score < config.threshold {
+                    continue;
+                }
+
+                let transaction_id: Vec<u8> = profile.get("transaction_id");
+                flag(
+                    pool,
+                    &suspect_pubkey,
+                    &base64_nickname,
+                    target,
+                    score,
+                    &transaction_id,
+                )
+                .await?;
+                flagged += 1;
+            }
+        }
+
+        checked_id = last_id;
+        sqlx::query(
+            r#"
+            INSERT INTO k_vars (key, value) VALUES ($1, $2)
+            ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value
+            "#,
+        )
+        .bind(WATERMARK_KEY)
+        .bind(checked_id.to_string())
+        .execute(pool)
+        .await?;
+
+        if (profiles.len() as i64) < BATCH_SIZE {
+            break;
+        }
+    }
+
+    if checked > 0 {
+        info!(
+            "Impersonation check: {} new profiles compared against {} protected users, {} flagged",
+            checked,
+            targets.len(),
+            flagged
+        );
+    }
+
+    Ok(())
+}
+
+/// Latest nickname of the most followed users having at least min_followers followers
+async fn load_targets(pool: &PgPool, min_followers: i64) -> Result<Vec<Target>> {
+    let rows = sqlx::query(
+        r#"
+        WITH followers AS (
+            SELECT followed_user_pubkey AS pubkey, COUNT(*) AS followers
+            FROM k_follows
+            GROUP BY followed_user_pubkey
+            HAVING COUNT(*) >= $1
+            ORDER BY followers DESC
+            LIMIT $2
+        )
+        SELECT DISTINCT ON (b.sender_pubkey)
+               b.sender_pubkey, b.base64_encoded_nickname, f.followers
+        FROM k_broadcasts b
+        JOIN followers f ON f.pubkey = b.sender_pubkey
+        ORDER BY b.sender_pubkey, b.block_time DESC
+        "#,
+    )
+    .bind(min_followers)
+    .bind(MAX_TARGETS)
+    .fetch_all(pool)
+    .await?;
+
+    let targets = rows
+        .iter()
+        .filter_map(|row| {
+            let base64_nickname: String = row.get("base64_encoded_nickname");
+            let normalized_nickname = normalize_base64_nickname(&base64_nickname)?;
+            Some(Target {
+                pubkey: row.get("sender_pubkey"),
+                base64_nickname,
+                normalized_nickname,
+                followers: row.get("followers"),
+            })
+        })
+        .collect();
+
+    Ok(targets)
+}
+
+/// Record a suspect/target pair; pending flags are refreshed, reviewed ones are left alone
+async fn flag(
+    pool: &PgPool,
+    suspect_pubkey: &[u8],
+    suspect_nickname: &str,
+    target: &Target,
+    score: f64,
+    transaction_id: &[u8],
+) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+
+    sqlx::query(
+        r#"
+        INSERT INTO k_impersonation_flags (
+            suspect_pubkey, base64_encoded_suspect_nickname, target_pubkey,
+            base64_encoded_target_nickname, target_followers, similarity, transaction_id, flagged_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (suspect_pubkey, target_pubkey) DO UPDATE SET
+            base64_encoded_suspect_nickname = EXCLUDED.base64_encoded_suspect_nickname,
+            base64_encoded_target_nickname = EXCLUDED.base64_encoded_target_nickname,
+            target_followers = EXCLUDED.target_followers,
+            similarity = EXCLUDED.similarity,
+            transaction_id = EXCLUDED.transaction_id,
+            flagged_at = EXCLUDED.flagged_at
+        WHERE k_impersonation_flags.status = 'pending'
+        "#,
+    )
+    .bind(suspect_pubkey)
+    .bind(suspect_nickname)
+    .bind(&target.pubkey)
+    .bind(&target.base64_nickname)
+    .bind(target.followers)
+    .bind(score)
+    .bind(transaction_id)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
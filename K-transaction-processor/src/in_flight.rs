@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Batches being committed by the workers of a pool, by worker
+/// The processed watermark is the newest block time committed by any worker: a worker committing
+/// newer transactions would otherwise move it past older ones another worker has not committed
+/// yet, and a backfill from the watermark after a crash would skip them
+#[derive(Default)]
+pub struct InFlightBatches {
+    // Worker id -> oldest block time of the batch it is committing
+    batches: Mutex<HashMap<usize, i64>>,
+}
+
+impl InFlightBatches {
+    /// `worker` starts committing a batch whose oldest block time is `oldest_block_time`
+    pub fn begin(&self, worker: usize, oldest_block_time: i64) {
+        self.batches
+            .lock()
+            .unwrap()
+            .insert(worker, oldest_block_time);
+    }
+
+    /// `worker` is done with its batch, committed up to `committed_block_time` (None when
+    /// nothing was): the watermark it may advance to, held back to the oldest batch still in
+    /// flight on another worker
+    pub fn finish(&self, worker: usize, committed_block_time: Option<i64>) -> Option<i64> {
+        let mut batches = self.batches.lock().unwrap();
+        batches.remove(&worker);
+        let committed = committed_block_time?;
+        Some(
+            batches
+                .values()
+                .fold(committed, |watermark, &oldest| watermark.min(oldest)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_held_by_other_batches() {
+        let in_flight = InFlightBatches::default();
+        in_flight.begin(0, 1_000);
+        in_flight.begin(1, 5_000);
+
+        // Worker 1 commits newer transactions than worker 0 is still committing
+        assert_eq!(in_flight.finish(1, Some(9_000)), Some(1_000));
+        // Nothing else in flight: worker 0 acknowledges its whole batch
+        assert_eq!(in_flight.finish(0, Some(4_000)), Some(4_000));
+    }
+
+    #[test]
+    fn test_watermark_of_own_batch_only() {
+        let in_flight = InFlightBatches::default();
+        in_flight.begin(0, 1_000);
+        assert_eq!(in_flight.finish(0, Some(2_000)), Some(2_000));
+
+        // A failed batch releases its hold without advancing anything
+        in_flight.begin(0, 3_000);
+        in_flight.begin(1, 6_000);
+        assert_eq!(in_flight.finish(0, None), None);
+        assert_eq!(in_flight.finish(1, Some(7_000)), Some(7_000));
+    }
+
+    #[test]
+    fn test_newer_batches_do_not_hold_back() {
+        let in_flight = InFlightBatches::default();
+        in_flight.begin(0, 1_000);
+        in_flight.begin(1, 8_000);
+        assert_eq!(in_flight.finish(0, Some(2_000)), Some(2_000));
+    }
+}
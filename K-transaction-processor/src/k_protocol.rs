@@ -1,8 +1,28 @@
-use crate::database::{DbPool, Transaction};
+use crate::article_assembler::{ArticleAssembly, MAX_ARTICLE_CHUNKS, assemble_article};
+use crate::blob_storage::{BlobStorage, StoredBlob};
+use crate::chain_params::ChainParams;
+use crate::config::{MentionLimitConfig, RateLimitAction, SenderRateLimitConfig};
+use crate::content_hasher::content_hash_from_base64;
+use crate::content_snippet::snippet_from_base64;
+use crate::database::Transaction;
 use crate::hashtag_extractor::extract_hashtags_from_base64;
+use crate::image_blobs;
+use crate::mention_limiter::{MentionLimitOutcome, apply_mention_limit};
+use crate::nickname_similarity::normalize_base64_nickname;
+use crate::render_spans::render_spans_from_base64;
+use crate::retry_policy::NotYetIndexed;
+use crate::search_text::search_text_from_base64;
+use crate::tenants::Tenant;
+use crate::tip_detector::tip_amount;
+use crate::transaction_fee::transaction_fee;
 use anyhow::Result;
+use base64::{Engine as _, engine::general_purpose};
 use hex;
 use serde_json;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use std::cell::RefCell;
+use std::sync::Arc;
 use tracing::{error, info, warn};
 
 // Kaspa message signature verification imports (from main K-indexer)
@@ -12,6 +32,50 @@ use secp256k1::XOnlyPublicKey;
 // K Protocol Data Models (ported from main K-indexer)
 use serde::{Deserialize, Serialize};
 
+/// Channel announcing the posts, replies, quotes and votes saved by each batch, consumed by
+/// the webserver live feed (/ws); PostgreSQL delivers the notifications when the batch commits
+pub const LIVE_EVENTS_CHANNEL: &str = "k_live_events";
+
+tokio::task_local! {
+    // Why the transaction being explained was not indexed (the first reason logged)
+    static SKIP_REASON: RefCell<Option<String>>;
+}
+
+/// Log why a transaction (or part of it) is not indexed, at the level of the first argument, and
+/// record the message for process_k_transaction_explained
+macro_rules! log_skip {
+    ($level:ident, $($arg:tt)+) => {{
+        let reason = format!($($arg)+);
+        $level!("{}", reason);
+        let _ = SKIP_REASON.try_with(|skipped| {
+            skipped.borrow_mut().get_or_insert(reason);
+        });
+    }};
+}
+
+/// Ancestors followed from a reply to the post of its thread at most
+const MAX_THREAD_DEPTH: i32 = 100;
+
+/// Ancestors of the content $2 (itself at depth 0), up to the post its replies answer
+const THREAD_ANCESTORS_CTE: &str = r#"
+    WITH RECURSIVE ancestors AS (
+        SELECT transaction_id, referenced_content_id, content_type, 0 AS depth
+        FROM k_contents
+        WHERE transaction_id = $2
+        UNION ALL
+        SELECT parent.transaction_id, parent.referenced_content_id, parent.content_type, a.depth + 1
+        FROM ancestors a
+        JOIN k_contents parent ON parent.transaction_id = a.referenced_content_id
+        WHERE a.content_type = 'reply' AND a.depth < $3
+    ),
+    thread AS (
+        SELECT encode(transaction_id, 'hex') AS thread_id
+        FROM ancestors
+        ORDER BY depth DESC
+        LIMIT 1
+    )
+"#;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum KActionType {
     Broadcast(KBroadcast),
@@ -21,9 +85,42 @@ pub enum KActionType {
     Block(KBlock),
     Quote(KQuote),
     Follow(KFollow),
+    Group(KGroup),
+    Membership(KMembership),
+    GroupPost(KGroupPost),
+    Event(KEvent),
+    Rsvp(KRsvp),
+    Article(KArticle),
+    Chunk(KChunk),
+    Tip(KTip),
     Unknown(String),
 }
 
+impl KActionType {
+    /// Sender of the action (None for unknown actions)
+    pub fn sender_pubkey(&self) -> Option<&str> {
+        let sender_pubkey = match self {
+            KActionType::Broadcast(action) => &action.sender_pubkey,
+            KActionType::Post(action) => &action.sender_pubkey,
+            KActionType::Reply(action) => &action.sender_pubkey,
+            KActionType::Vote(action) => &action.sender_pubkey,
+            KActionType::Block(action) => &action.sender_pubkey,
+            KActionType::Quote(action) => &action.sender_pubkey,
+            KActionType::Follow(action) => &action.sender_pubkey,
+            KActionType::Group(action) => &action.sender_pubkey,
+            KActionType::Membership(action) => &action.sender_pubkey,
+            KActionType::GroupPost(action) => &action.sender_pubkey,
+            KActionType::Event(action) => &action.sender_pubkey,
+            KActionType::Rsvp(action) => &action.sender_pubkey,
+            KActionType::Article(action) => &action.sender_pubkey,
+            KActionType::Chunk(action) => &action.sender_pubkey,
+            KActionType::Tip(action) => &action.sender_pubkey,
+            KActionType::Unknown(_) => return None,
+        };
+        Some(sender_pubkey)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KBroadcast {
     pub sender_pubkey: String,
@@ -85,6 +182,75 @@ pub struct KFollow {
     pub followed_user_pubkey: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KGroup {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_name: String,
+    pub base64_encoded_description: String,
+    pub membership_policy: String, // "open" or "closed"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KMembership {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub group_id: String,
+    pub membership_action: String, // "join", "leave", "add" or "remove"
+    pub member_pubkey: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KGroupPost {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub group_id: String,
+    pub base64_encoded_message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KEvent {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_title: String,
+    pub start_time: String, // unix timestamp in milliseconds
+    pub end_time: String,   // unix timestamp in milliseconds
+    pub base64_encoded_location: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KRsvp {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub event_id: String,
+    pub rsvp_status: String, // "going", "interested" or "not_going"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KArticle {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_title: String,
+    pub chunk_count: u32,
+    pub content_hash: String, // hex SHA-256 of the whole base64 encoded article
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KChunk {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub manifest_id: String, // transaction id of the article manifest
+    pub chunk_index: u32,
+    pub base64_encoded_data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KTip {
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub content_id: String, // the tip amount comes from the transaction outputs paying the author
+}
+
 // Database record structures for PostgreSQL
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KPostRecord {
@@ -149,12 +315,39 @@ pub struct KFollowRecord {
 }
 
 pub struct KProtocolProcessor {
-    db_pool: DbPool,
+    chain: ChainParams,
+    blob_storage: Option<Arc<BlobStorage>>,
+    mention_limit: MentionLimitConfig,
+    rate_limit: SenderRateLimitConfig,
+    vote_fee_weighting: bool,
+    // Channel of the live events (LIVE_EVENTS_CHANNEL, or the one of the tenant)
+    live_events_channel: String,
 }
 
 impl KProtocolProcessor {
-    pub fn new(db_pool: DbPool) -> Self {
-        Self { db_pool }
+    pub fn new(
+        chain: ChainParams,
+        blob_storage: Option<Arc<BlobStorage>>,
+        mention_limit: MentionLimitConfig,
+        rate_limit: SenderRateLimitConfig,
+        vote_fee_weighting: bool,
+    ) -> Self {
+        Self {
+            chain,
+            blob_storage,
+            mention_limit,
+            rate_limit,
+            vote_fee_weighting,
+            live_events_channel: LIVE_EVENTS_CHANNEL.to_string(),
+        }
+    }
+
+    /// Index the K payloads of a tenant (--tenant) instead of the network's own ones; the
+    /// database connections must resolve the tables in the schema of the tenant
+    pub fn for_tenant(mut self, tenant: &Tenant) -> Self {
+        self.chain = tenant.chain(self.chain);
+        self.live_events_channel = tenant.live_events_channel();
+        self
     }
 
     /// Verify a Kaspa message signature using the proper kaspa-wallet-core verification
@@ -231,12 +424,10 @@ impl KProtocolProcessor {
 
     /// Parse K protocol payload and extract action type
     pub fn parse_k_protocol_payload(&self, payload: &str) -> Result<KActionType> {
-        // Remove the K protocol prefix "k:1:"
-        if !payload.starts_with("k:1:") {
+        // Remove the K protocol prefix of the network ("k:1:")
+        let Some(k_payload) = payload.strip_prefix(self.chain.payload_prefix) else {
             return Err(anyhow::anyhow!("Invalid K protocol prefix"));
-        }
-
-        let k_payload = &payload[4..]; // Remove "k:1:" prefix
+        };
 
         // Split by colons to get the components
         let parts: Vec<&str> = k_payload.split(':').collect();
@@ -465,124 +656,692 @@ impl KProtocolProcessor {
                     followed_user_pubkey,
                 }))
             }
-            _ => Ok(KActionType::Unknown(action.to_string())),
-        }
-    }
+            "group" => {
+                // Expected format: group:sender_pubkey:sender_signature:base64_encoded_name:base64_encoded_description:membership_policy
+                if parts.len() < 6 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid group format: expected 6 parts, got {}",
+                        parts.len()
+                    ));
+                }
 
-    /// Process K protocol transaction
-    pub async fn process_k_transaction(&self, transaction: &Transaction) -> Result<()> {
-        let transaction_id = &transaction.transaction_id;
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let base64_encoded_name = parts[3].to_string();
+                let base64_encoded_description = parts[4].to_string();
+                let membership_policy = parts[5].to_string();
 
-        // Get payload as hex string
-        let payload_hex = match &transaction.payload {
-            Some(hex_payload) => hex_payload,
-            None => {
-                warn!("Transaction {} has no payload", transaction_id);
-                return Ok(());
+                if base64_encoded_name.is_empty() {
+                    return Err(anyhow::anyhow!("Invalid group format: empty group name"));
+                }
+
+                // Validate membership_policy value
+                if membership_policy != "open" && membership_policy != "closed" {
+                    return Err(anyhow::anyhow!(
+                        "Invalid membership_policy value: expected 'open' or 'closed', got '{}'",
+                        membership_policy
+                    ));
+                }
+
+                Ok(KActionType::Group(KGroup {
+                    sender_pubkey,
+                    sender_signature,
+                    base64_encoded_name,
+                    base64_encoded_description,
+                    membership_policy,
+                }))
             }
-        };
+            "membership" => {
+                // Expected format: membership:sender_pubkey:sender_signature:group_id:membership_action:member_pubkey
+                if parts.len() < 6 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid membership format: expected 6 parts, got {}",
+                        parts.len()
+                    ));
+                }
 
-        // Convert hex payload to UTF-8 string
-        let payload_bytes = match hex::decode(payload_hex) {
-            Ok(bytes) => bytes,
-            Err(err) => {
-                error!(
-                    "Failed to decode hex payload for transaction {}: {}",
-                    transaction_id, err
-                );
-                return Ok(());
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let group_id = parts[3].to_string();
+                let membership_action = parts[4].to_string();
+                let member_pubkey = parts[5].to_string();
+
+                // Validate membership_action value
+                if !matches!(
+                    membership_action.as_str(),
+                    "join" | "leave" | "add" | "remove"
+                ) {
+                    return Err(anyhow::anyhow!(
+                        "Invalid membership_action value: expected 'join', 'leave', 'add' or 'remove', got '{}'",
+                        membership_action
+                    ));
+                }
+
+                Ok(KActionType::Membership(KMembership {
+                    sender_pubkey,
+                    sender_signature,
+                    group_id,
+                    membership_action,
+                    member_pubkey,
+                }))
             }
-        };
+            "grouppost" => {
+                // Expected format: grouppost:sender_pubkey:sender_signature:group_id:base64_encoded_message
+                if parts.len() < 5 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid grouppost format: expected 5 parts, got {}",
+                        parts.len()
+                    ));
+                }
 
-        let payload_str = match std::str::from_utf8(&payload_bytes) {
-            Ok(payload_str) => payload_str,
-            Err(err) => {
-                error!(
-                    "Invalid UTF-8 in transaction payload for ID: {}: {}",
-                    transaction_id, err
-                );
-                return Ok(());
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let group_id = parts[3].to_string();
+                let base64_encoded_message = parts[4].to_string();
+
+                Ok(KActionType::GroupPost(KGroupPost {
+                    sender_pubkey,
+                    sender_signature,
+                    group_id,
+                    base64_encoded_message,
+                }))
             }
-        };
+            "event" => {
+                // Expected format: event:sender_pubkey:sender_signature:base64_encoded_title:start_time:end_time:base64_encoded_location
+                if parts.len() < 7 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid event format: expected 7 parts, got {}",
+                        parts.len()
+                    ));
+                }
 
-        // Clean the payload string by removing null bytes and other control characters
-        let cleaned_payload = payload_str
-            .chars()
-            .filter(|c| !c.is_control() || *c == '\n' || *c == '\r' || *c == '\t')
-            .collect::<String>();
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let base64_encoded_title = parts[3].to_string();
+                let start_time = parts[4].to_string();
+                let end_time = parts[5].to_string();
+                let base64_encoded_location = parts[6].to_string();
 
-        // Parse K protocol payload
-        match self.parse_k_protocol_payload(&cleaned_payload) {
-            Ok(action_type) => match action_type {
-                KActionType::Broadcast(k_broadcast) => {
-                    self.save_k_broadcast_to_database(transaction, k_broadcast)
-                        .await?;
+                if base64_encoded_title.is_empty() {
+                    return Err(anyhow::anyhow!("Invalid event format: empty event title"));
                 }
-                KActionType::Post(k_post) => {
-                    self.save_k_post_to_database(transaction, k_post).await?;
+
+                // Validate event times (unix milliseconds, end not before start)
+                let start = start_time
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid start_time value: '{}'", start_time))?;
+                let end = end_time
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid end_time value: '{}'", end_time))?;
+                if start > i64::MAX as u64 || end > i64::MAX as u64 || end < start {
+                    return Err(anyhow::anyhow!(
+                        "Invalid event times: start_time {} / end_time {}",
+                        start_time,
+                        end_time
+                    ));
                 }
-                KActionType::Reply(k_reply) => {
-                    self.save_k_reply_to_database(transaction, k_reply).await?;
+
+                Ok(KActionType::Event(KEvent {
+                    sender_pubkey,
+                    sender_signature,
+                    base64_encoded_title,
+                    start_time,
+                    end_time,
+                    base64_encoded_location,
+                }))
+            }
+            "rsvp" => {
+                // Expected format: rsvp:sender_pubkey:sender_signature:event_id:rsvp_status
+                if parts.len() < 5 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid rsvp format: expected 5 parts, got {}",
+                        parts.len()
+                    ));
                 }
-                KActionType::Vote(k_vote) => {
-                    self.save_k_vote_to_database(transaction, k_vote).await?;
+
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let event_id = parts[3].to_string();
+                let rsvp_status = parts[4].to_string();
+
+                // Validate rsvp_status value
+                if !matches!(rsvp_status.as_str(), "going" | "interested" | "not_going") {
+                    return Err(anyhow::anyhow!(
+                        "Invalid rsvp_status value: expected 'going', 'interested' or 'not_going', got '{}'",
+                        rsvp_status
+                    ));
                 }
-                KActionType::Block(k_block) => {
-                    self.process_k_block_in_database(transaction, k_block)
-                        .await?;
+
+                Ok(KActionType::Rsvp(KRsvp {
+                    sender_pubkey,
+                    sender_signature,
+                    event_id,
+                    rsvp_status,
+                }))
+            }
+            "article" => {
+                // Expected format: article:sender_pubkey:sender_signature:base64_encoded_title:chunk_count:content_hash
+                if parts.len() < 6 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid article format: expected 6 parts, got {}",
+                        parts.len()
+                    ));
                 }
-                KActionType::Quote(k_quote) => {
-                    self.save_k_quote_to_database(transaction, k_quote).await?;
+
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let base64_encoded_title = parts[3].to_string();
+                let content_hash = parts[5].to_string();
+
+                let chunk_count = parts[4]
+                    .parse::<u32>()
+                    .map_err(|_| anyhow::anyhow!("Invalid chunk_count value: '{}'", parts[4]))?;
+                if chunk_count == 0 || chunk_count > MAX_ARTICLE_CHUNKS {
+                    return Err(anyhow::anyhow!(
+                        "Invalid chunk_count value: expected 1-{}, got {}",
+                        MAX_ARTICLE_CHUNKS,
+                        chunk_count
+                    ));
                 }
-                KActionType::Follow(k_follow) => {
-                    self.process_k_follow_in_database(transaction, k_follow)
-                        .await?;
+
+                // Validate content_hash (hex encoded SHA-256)
+                if content_hash.len() != 64 || hex::decode(&content_hash).is_err() {
+                    return Err(anyhow::anyhow!(
+                        "Invalid content_hash value: expected 64 hex characters, got '{}'",
+                        content_hash
+                    ));
                 }
-                KActionType::Unknown(action) => {
-                    warn!(
-                        "Unknown K protocol action '{}' in transaction {}",
-                        action, transaction_id
-                    );
+
+                Ok(KActionType::Article(KArticle {
+                    sender_pubkey,
+                    sender_signature,
+                    base64_encoded_title,
+                    chunk_count,
+                    content_hash,
+                }))
+            }
+            "chunk" => {
+                // Expected format: chunk:sender_pubkey:sender_signature:manifest_id:chunk_index:base64_encoded_data
+                if parts.len() < 6 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid chunk format: expected 6 parts, got {}",
+                        parts.len()
+                    ));
                 }
-            },
-            Err(err) => {
-                error!(
-                    "Failed to parse K protocol payload for transaction {}: {}",
-                    transaction_id, err
-                );
+
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let manifest_id = parts[3].to_string();
+                let base64_encoded_data = parts[5].to_string();
+
+                let chunk_index = parts[4]
+                    .parse::<u32>()
+                    .map_err(|_| anyhow::anyhow!("Invalid chunk_index value: '{}'", parts[4]))?;
+                if chunk_index >= MAX_ARTICLE_CHUNKS {
+                    return Err(anyhow::anyhow!(
+                        "Invalid chunk_index value: expected 0-{}, got {}",
+                        MAX_ARTICLE_CHUNKS - 1,
+                        chunk_index
+                    ));
+                }
+
+                Ok(KActionType::Chunk(KChunk {
+                    sender_pubkey,
+                    sender_signature,
+                    manifest_id,
+                    chunk_index,
+                    base64_encoded_data,
+                }))
+            }
+            "tip" => {
+                // Expected format: tip:sender_pubkey:sender_signature:content_id
+                if parts.len() < 4 {
+                    return Err(anyhow::anyhow!(
+                        "Invalid tip format: expected 4 parts, got {}",
+                        parts.len()
+                    ));
+                }
+
+                let sender_pubkey = parts[1].to_string();
+                let sender_signature = parts[2].to_string();
+                let content_id = parts[3].to_string();
+
+                Ok(KActionType::Tip(KTip {
+                    sender_pubkey,
+                    sender_signature,
+                    content_id,
+                }))
             }
+            _ => Ok(KActionType::Unknown(action.to_string())),
         }
+    }
 
-        Ok(())
+    /// Decode a hex transaction payload (UTF-8, control characters removed) and parse it
+    pub fn decode_k_payload(&self, payload_hex: &str) -> Result<KActionType> {
+        let payload_bytes = hex::decode(payload_hex)
+            .map_err(|err| anyhow::anyhow!("Invalid hex payload: {}", err))?;
+        let payload_str = std::str::from_utf8(&payload_bytes)
+            .map_err(|err| anyhow::anyhow!("Invalid UTF-8 in payload: {}", err))?;
+
+        // Clean the payload string by removing null bytes and other control characters
+        let cleaned_payload = payload_str
+            .chars()
+            .filter(|c| !c.is_control() || *c == '\n' || *c == '\r' || *c == '\t')
+            .collect::<String>();
+
+        self.parse_k_protocol_payload(&cleaned_payload)
     }
 
-    /// Save K post to database
-    pub async fn save_k_post_to_database(
+    /// process_k_transaction, returning why the transaction was skipped when it was (invalid
+    /// signature, tombstone, rate limit, unknown reference, already indexed...)
+    pub async fn process_k_transaction_explained(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+    ) -> Result<Option<String>> {
+        SKIP_REASON
+            .scope(RefCell::new(None), async {
+                self.process_k_transaction(conn, transaction).await?;
+                Ok(SKIP_REASON.with(|skipped| skipped.take()))
+            })
+            .await
+    }
+
+    /// Process K protocol transaction
+    /// All writes go through the given connection (the worker's batch transaction)
+    pub async fn process_k_transaction(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
-        k_post: KPost,
     ) -> Result<()> {
         let transaction_id = &transaction.transaction_id;
 
-        // Construct the message to verify - it's the base64 message + mentioned_pubkeys JSON
-        let mentioned_pubkeys_json_str =
-            serde_json::to_string(&k_post.mentioned_pubkeys).unwrap_or_else(|_| "[]".to_string());
-        let message_to_verify = format!(
-            "{}:{}",
-            k_post.base64_encoded_message, mentioned_pubkeys_json_str
-        );
-
-        // Verify the signature
-        if !self.verify_kaspa_signature(
-            &message_to_verify,
-            &k_post.sender_signature,
-            &k_post.sender_pubkey,
-        ) {
-            error!("Invalid signature for post {}, skipping", transaction_id);
-            return Ok(()); // Skip posts with invalid signatures
-        }
+        // Get payload as hex string
+        let payload_hex = match &transaction.payload {
+            Some(hex_payload) => hex_payload,
+            None => {
+                log_skip!(warn, "Transaction {} has no payload", transaction_id);
+                return Ok(());
+            }
+        };
 
-        // Extract block time
+        let action_type = match self.decode_k_payload(payload_hex) {
+            Ok(action_type) => action_type,
+            Err(err) => {
+                log_skip!(
+                    error,
+                    "Failed to parse K protocol payload for transaction {}: {}",
+                    transaction_id,
+                    err
+                );
+                return Ok(());
+            }
+        };
+
+        // Erased or taken down (k_tombstones / k_pubkey_tombstones): never index it again
+        if self
+            .is_tombstoned(conn, transaction_id, action_type.sender_pubkey())
+            .await?
+        {
+            log_skip!(
+                info,
+                "Transaction {} or its sender is tombstoned, skipping",
+                transaction_id
+            );
+            return Ok(());
+        }
+
+        match action_type {
+            KActionType::Broadcast(k_broadcast) => {
+                self.save_k_broadcast_to_database(conn, transaction, k_broadcast)
+                    .await?;
+            }
+            KActionType::Post(k_post) => {
+                self.save_k_post_to_database(conn, transaction, k_post)
+                    .await?;
+            }
+            KActionType::Reply(k_reply) => {
+                self.save_k_reply_to_database(conn, transaction, k_reply)
+                    .await?;
+            }
+            KActionType::Vote(k_vote) => {
+                self.save_k_vote_to_database(conn, transaction, k_vote)
+                    .await?;
+            }
+            KActionType::Block(k_block) => {
+                self.process_k_block_in_database(conn, transaction, k_block)
+                    .await?;
+            }
+            KActionType::Quote(k_quote) => {
+                self.save_k_quote_to_database(conn, transaction, k_quote)
+                    .await?;
+            }
+            KActionType::Follow(k_follow) => {
+                self.process_k_follow_in_database(conn, transaction, k_follow)
+                    .await?;
+            }
+            KActionType::Group(k_group) => {
+                self.save_k_group_to_database(conn, transaction, k_group)
+                    .await?;
+            }
+            KActionType::Membership(k_membership) => {
+                self.process_k_membership_in_database(conn, transaction, k_membership)
+                    .await?;
+            }
+            KActionType::GroupPost(k_group_post) => {
+                self.save_k_group_post_to_database(conn, transaction, k_group_post)
+                    .await?;
+            }
+            KActionType::Event(k_event) => {
+                self.save_k_event_to_database(conn, transaction, k_event)
+                    .await?;
+            }
+            KActionType::Rsvp(k_rsvp) => {
+                self.save_k_rsvp_to_database(conn, transaction, k_rsvp)
+                    .await?;
+            }
+            KActionType::Article(k_article) => {
+                self.save_k_article_to_database(conn, transaction, k_article)
+                    .await?;
+            }
+            KActionType::Chunk(k_chunk) => {
+                self.save_k_chunk_to_database(conn, transaction, k_chunk)
+                    .await?;
+            }
+            KActionType::Tip(k_tip) => {
+                self.save_k_tip_to_database(conn, transaction, k_tip)
+                    .await?;
+            }
+            KActionType::Unknown(action) => {
+                warn!(
+                    "Unknown K protocol action '{}' in transaction {}",
+                    action, transaction_id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the transaction or its sender must not be indexed (k_tombstones / k_pubkey_tombstones)
+    async fn is_tombstoned(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id: &str,
+        sender_pubkey: Option<&str>,
+    ) -> Result<bool> {
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        // Malformed sender keys are rejected when saving, they cannot match a tombstone
+        let sender_pubkey_bytes = sender_pubkey.and_then(|pubkey| hex::decode(pubkey).ok());
+
+        let tombstoned: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM k_tombstones WHERE transaction_id = $1)
+                OR EXISTS(SELECT 1 FROM k_pubkey_tombstones WHERE sender_pubkey = $2)
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(&sender_pubkey_bytes)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(tombstoned)
+    }
+
+    /// Record the normalized message hash of a saved content (k_content_hashes)
+    /// Also fills in hashes of contents indexed before v17 when they are backfilled
+    async fn save_content_hash(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+        message_hash: &[u8],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO k_content_hashes (content_id, message_hash, sender_pubkey, block_time)
+            SELECT transaction_id, $2, sender_pubkey, block_time
+            FROM k_contents
+            WHERE transaction_id = $1
+            ON CONFLICT (content_id) DO NOTHING
+            "#,
+        )
+        .bind(transaction_id_bytes)
+        .bind(message_hash)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the rendering hints (JSON spans) of a saved content (k_render_spans)
+    /// Also fills in spans of contents indexed before v26 when they are backfilled
+    async fn save_render_spans(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+        render_spans: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO k_render_spans (content_id, spans)
+            SELECT transaction_id, $2::jsonb
+            FROM k_contents
+            WHERE transaction_id = $1
+            ON CONFLICT (content_id) DO NOTHING
+            "#,
+        )
+        .bind(transaction_id_bytes)
+        .bind(render_spans)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the full-text search vector of a saved post or reply (k_contents.search_vector)
+    /// Also fills in vectors of contents indexed before v31 when they are backfilled
+    async fn save_search_vector(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+        search_text: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE k_contents
+            SET search_vector = to_tsvector('simple', $2)
+            WHERE transaction_id = $1 AND search_vector IS NULL
+            "#,
+        )
+        .bind(transaction_id_bytes)
+        .bind(search_text)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Resolve the thread root (k_contents.root_post_id) of a saved reply from its parent: the
+    /// parent itself for a post or quote, else the root of the parent, which may have moved to
+    /// the cold tier. Replies indexed before the saved content (out of order, or before v33
+    /// when it is backfilled) get their root too
+    async fn save_thread_root(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            WITH RECURSIVE thread AS (
+                SELECT c.transaction_id,
+                       CASE WHEN c.content_type <> 'reply' THEN c.transaction_id
+                            WHEN p.content_type <> 'reply' THEN p.transaction_id
+                            ELSE p.root_post_id
+                       END AS root_post_id
+                FROM k_contents c
+                LEFT JOIN k_contents_tiered p ON p.transaction_id = c.referenced_content_id
+                WHERE c.transaction_id = $1
+                UNION ALL
+                SELECT r.transaction_id, t.root_post_id
+                FROM thread t
+                JOIN k_contents r ON r.referenced_content_id = t.transaction_id
+                WHERE r.content_type = 'reply' AND r.root_post_id IS NULL
+                  AND t.root_post_id IS NOT NULL
+            )
+            UPDATE k_contents c
+            SET root_post_id = t.root_post_id
+            FROM thread t
+            WHERE c.transaction_id = t.transaction_id
+              AND c.content_type = 'reply'
+              AND c.root_post_id IS NULL
+              AND t.root_post_id IS NOT NULL
+            "#,
+        )
+        .bind(transaction_id_bytes)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Announce a saved post, reply or quote on the live events channel, with the post of its
+    /// thread (the content itself unless it is a reply)
+    async fn notify_live_content(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+    ) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            {THREAD_ANCESTORS_CTE}
+            SELECT pg_notify($1, json_build_object(
+                'type', c.content_type,
+                'id', encode(c.transaction_id, 'hex'),
+                'senderPubkey', encode(c.sender_pubkey, 'hex'),
+                'parentId', encode(c.referenced_content_id, 'hex'),
+                'threadId', (SELECT thread_id FROM thread),
+                'blockTime', c.block_time,
+                'rateLimited', c.is_rate_limited
+            )::text)
+            FROM k_contents c
+            WHERE c.transaction_id = $2
+            "#
+        ))
+        .bind(&self.live_events_channel)
+        .bind(transaction_id_bytes)
+        .bind(MAX_THREAD_DEPTH)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Announce a saved vote on the live events channel, with the post of the voted content's
+    /// thread
+    async fn notify_live_vote(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+        post_id_bytes: &[u8],
+    ) -> Result<()> {
+        sqlx::query(&format!(
+            r#"
+            {THREAD_ANCESTORS_CTE}
+            SELECT pg_notify($1, json_build_object(
+                'type', 'vote',
+                'id', encode(v.transaction_id, 'hex'),
+                'senderPubkey', encode(v.sender_pubkey, 'hex'),
+                'parentId', encode(v.post_id, 'hex'),
+                'threadId', (SELECT thread_id FROM thread),
+                'vote', v.vote,
+                'blockTime', v.block_time,
+                'rateLimited', v.is_rate_limited
+            )::text)
+            FROM k_votes v
+            WHERE v.transaction_id = $4
+            "#
+        ))
+        .bind(&self.live_events_channel)
+        .bind(post_id_bytes)
+        .bind(MAX_THREAD_DEPTH)
+        .bind(transaction_id_bytes)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Announce the notifications (k_mentions rows) of a saved content, vote or follow on the
+    /// live events channel, for the notification streams of the notified users
+    async fn notify_live_notifications(
+        &self,
+        conn: &mut PgConnection,
+        content_id_bytes: &[u8],
+        rate_limited: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            SELECT pg_notify($1, json_build_object(
+                'type', 'notification',
+                'id', encode(m.content_id, 'hex'),
+                'senderPubkey', encode(m.sender_pubkey, 'hex'),
+                'parentId', encode(m.parent_id, 'hex'),
+                'mentionedPubkey', encode(m.mentioned_pubkey, 'hex'),
+                'contentType', m.content_type,
+                'blockTime', m.block_time,
+                'rateLimited', $3
+            )::text)
+            FROM k_mentions m
+            WHERE m.content_id = $2
+              AND m.sender_pubkey IS NOT NULL
+              AND m.sender_pubkey != m.mentioned_pubkey
+            "#,
+        )
+        .bind(&self.live_events_channel)
+        .bind(content_id_bytes)
+        .bind(rate_limited)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Save K post to database
+    pub async fn save_k_post_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        mut k_post: KPost,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's the base64 message + mentioned_pubkeys JSON
+        let mentioned_pubkeys_json_str =
+            serde_json::to_string(&k_post.mentioned_pubkeys).unwrap_or_else(|_| "[]".to_string());
+        let message_to_verify = format!(
+            "{}:{}",
+            k_post.base64_encoded_message, mentioned_pubkeys_json_str
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_post.sender_signature,
+            &k_post.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for post {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip posts with invalid signatures
+        }
+
+        // Cap mentions (after signature verification, which covers the full list)
+        let mention_limit = self.limit_mentions(transaction_id, k_post.mentioned_pubkeys);
+        k_post.mentioned_pubkeys = mention_limit.mentioned_pubkeys;
+
+        // Extract block time
         let block_time = transaction.block_time.unwrap_or(0);
 
         // Convert hex strings to bytea for database storage
@@ -592,9 +1351,42 @@ impl KProtocolProcessor {
 
         // Extract hashtags from the message
         let hashtags = extract_hashtags_from_base64(&k_post.base64_encoded_message);
+        let message_hash = content_hash_from_base64(&k_post.base64_encoded_message);
+        let render_spans = render_spans_from_base64(&k_post.base64_encoded_message);
+        let search_text = search_text_from_base64(&k_post.base64_encoded_message);
+
+        // Per-sender rate limit at index time
+        let rate_limited = self
+            .is_rate_limited(
+                conn,
+                "k_contents",
+                self.rate_limit.max_posts_per_minute,
+                &transaction_id_bytes,
+                &sender_pubkey_bytes,
+                block_time,
+            )
+            .await?;
+        if rate_limited && self.rate_limit.action == RateLimitAction::Skip {
+            log_skip!(
+                warn,
+                "Sender of post {} exceeded {} posts/minute, skipping",
+                transaction_id,
+                self.rate_limit.max_posts_per_minute.unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        k_post.mentioned_pubkeys = self
+            .suppress_blocked_mentions(
+                conn,
+                transaction_id,
+                &sender_pubkey_bytes,
+                k_post.mentioned_pubkeys,
+            )
+            .await?;
 
         // Single query to insert post and all mentions/hashtags using CTE
-        if k_post.mentioned_pubkeys.is_empty() {
+        let inserted = if k_post.mentioned_pubkeys.is_empty() {
             // No mentions - check if we have hashtags
             if hashtags.is_empty() {
                 // No mentions, no hashtags - simple insert
@@ -612,17 +1404,19 @@ impl KProtocolProcessor {
                 .bind(&sender_pubkey_bytes)
                 .bind(&sender_signature_bytes)
                 .bind(&k_post.base64_encoded_message)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Post transaction {} already exists, skipping",
                         transaction_id
                     );
                 } else {
                     info!("Saved K post: {}", transaction_id);
                 }
+                result.rows_affected() > 0
             } else {
                 // No mentions but has hashtags - use CTE to insert post + hashtags atomically
                 let result = sqlx::query(
@@ -646,11 +1440,12 @@ impl KProtocolProcessor {
                 .bind(&sender_signature_bytes)
                 .bind(&k_post.base64_encoded_message)
                 .bind(&hashtags)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Post transaction {} already exists, skipping",
                         transaction_id
                     );
@@ -661,6 +1456,7 @@ impl KProtocolProcessor {
                         transaction_id
                     );
                 }
+                result.rows_affected() > 0
             }
         } else {
             // Has mentions - check if we also have hashtags
@@ -684,8 +1480,8 @@ impl KProtocolProcessor {
                         ON CONFLICT (sender_signature) DO NOTHING
                         RETURNING transaction_id, block_time, sender_pubkey
                     )
-                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                    SELECT pi.transaction_id, 'post', unnest($6::bytea[]), pi.block_time, pi.sender_pubkey
+                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, content_snippet)
+                    SELECT pi.transaction_id, 'post', unnest($6::bytea[]), pi.block_time, pi.sender_pubkey, $7
                     FROM post_insert pi
                     "#,
                 )
@@ -695,17 +1491,20 @@ impl KProtocolProcessor {
                 .bind(&sender_signature_bytes)
                 .bind(&k_post.base64_encoded_message)
                 .bind(&mentioned_pubkeys_bytes)
-                .execute(&self.db_pool)
+                .bind(snippet_from_base64(&k_post.base64_encoded_message))
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Post transaction {} already exists, skipping",
                         transaction_id
                     );
                 } else {
                     info!("Saved K post: {}", transaction_id);
                 }
+                result.rows_affected() > 0
             } else {
                 // Has both mentions AND hashtags - extended CTE with post + mentions + hashtags
                 let result = sqlx::query(
@@ -719,8 +1518,8 @@ impl KProtocolProcessor {
                         RETURNING transaction_id, block_time, sender_pubkey
                     ),
                     mentions_insert AS (
-                        INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                        SELECT pi.transaction_id, 'post', unnest($6::bytea[]), pi.block_time, pi.sender_pubkey
+                        INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, content_snippet)
+                        SELECT pi.transaction_id, 'post', unnest($6::bytea[]), pi.block_time, pi.sender_pubkey, $8
                         FROM post_insert pi
                         RETURNING 1
                     )
@@ -736,11 +1535,13 @@ impl KProtocolProcessor {
                 .bind(&k_post.base64_encoded_message)
                 .bind(&mentioned_pubkeys_bytes)
                 .bind(&hashtags)
-                .execute(&self.db_pool)
+                .bind(snippet_from_base64(&k_post.base64_encoded_message))
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Post transaction {} already exists, skipping",
                         transaction_id
                     );
@@ -752,7 +1553,42 @@ impl KProtocolProcessor {
                         transaction_id
                     );
                 }
+                result.rows_affected() > 0
             }
+        };
+
+        if mention_limit.flag_as_spam {
+            self.flag_content_as_spam(conn, &transaction_id_bytes)
+                .await?;
+        }
+
+        if rate_limited {
+            self.flag_rate_limited(conn, "k_contents", &transaction_id_bytes)
+                .await?;
+        }
+
+        if let Some(message_hash) = message_hash {
+            self.save_content_hash(conn, &transaction_id_bytes, &message_hash)
+                .await?;
+        }
+
+        if let Some(render_spans) = render_spans {
+            self.save_render_spans(conn, &transaction_id_bytes, &render_spans)
+                .await?;
+        }
+
+        if let Some(search_text) = search_text {
+            self.save_search_vector(conn, &transaction_id_bytes, &search_text)
+                .await?;
+        }
+
+        self.save_thread_root(conn, &transaction_id_bytes).await?;
+
+        if inserted {
+            self.notify_live_content(conn, &transaction_id_bytes)
+                .await?;
+            self.notify_live_notifications(conn, &transaction_id_bytes, rate_limited)
+                .await?;
         }
         Ok(())
     }
@@ -760,8 +1596,9 @@ impl KProtocolProcessor {
     /// Save K reply to database
     pub async fn save_k_reply_to_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
-        k_reply: KReply,
+        mut k_reply: KReply,
     ) -> Result<()> {
         let transaction_id = &transaction.transaction_id;
 
@@ -779,10 +1616,18 @@ impl KProtocolProcessor {
             &k_reply.sender_signature,
             &k_reply.sender_pubkey,
         ) {
-            error!("Invalid signature for reply {}, skipping", transaction_id);
+            log_skip!(
+                error,
+                "Invalid signature for reply {}, skipping",
+                transaction_id
+            );
             return Ok(()); // Skip replies with invalid signatures
         }
 
+        // Cap mentions (after signature verification, which covers the full list)
+        let mention_limit = self.limit_mentions(transaction_id, k_reply.mentioned_pubkeys);
+        k_reply.mentioned_pubkeys = mention_limit.mentioned_pubkeys;
+
         // Store values we need for logging before they're moved
         let post_id_for_log = k_reply.post_id.clone();
 
@@ -797,9 +1642,42 @@ impl KProtocolProcessor {
 
         // Extract hashtags from the message
         let hashtags = extract_hashtags_from_base64(&k_reply.base64_encoded_message);
+        let message_hash = content_hash_from_base64(&k_reply.base64_encoded_message);
+        let render_spans = render_spans_from_base64(&k_reply.base64_encoded_message);
+        let search_text = search_text_from_base64(&k_reply.base64_encoded_message);
+
+        // Per-sender rate limit at index time
+        let rate_limited = self
+            .is_rate_limited(
+                conn,
+                "k_contents",
+                self.rate_limit.max_posts_per_minute,
+                &transaction_id_bytes,
+                &sender_pubkey_bytes,
+                block_time,
+            )
+            .await?;
+        if rate_limited && self.rate_limit.action == RateLimitAction::Skip {
+            log_skip!(
+                warn,
+                "Sender of reply {} exceeded {} posts/minute, skipping",
+                transaction_id,
+                self.rate_limit.max_posts_per_minute.unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        k_reply.mentioned_pubkeys = self
+            .suppress_blocked_mentions(
+                conn,
+                transaction_id,
+                &sender_pubkey_bytes,
+                k_reply.mentioned_pubkeys,
+            )
+            .await?;
 
         // Single query to insert reply and all mentions/hashtags using CTE
-        if k_reply.mentioned_pubkeys.is_empty() {
+        let inserted = if k_reply.mentioned_pubkeys.is_empty() {
             // No mentions - check if we have hashtags
             if hashtags.is_empty() {
                 // No mentions, no hashtags - simple insert
@@ -818,17 +1696,19 @@ impl KProtocolProcessor {
                 .bind(&sender_signature_bytes)
                 .bind(&k_reply.base64_encoded_message)
                 .bind(&post_id_bytes)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Reply transaction {} already exists, skipping",
                         transaction_id
                     );
                 } else {
                     info!("Saved K reply: {} -> {}", transaction_id, post_id_for_log);
                 }
+                result.rows_affected() > 0
             } else {
                 // No mentions but has hashtags - use CTE to insert reply + hashtags atomically
                 let result = sqlx::query(
@@ -853,11 +1733,12 @@ impl KProtocolProcessor {
                 .bind(&k_reply.base64_encoded_message)
                 .bind(&post_id_bytes)
                 .bind(&hashtags)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Reply transaction {} already exists, skipping",
                         transaction_id
                     );
@@ -869,6 +1750,7 @@ impl KProtocolProcessor {
                         post_id_for_log
                     );
                 }
+                result.rows_affected() > 0
             }
         } else {
             // Has mentions - check if we also have hashtags
@@ -892,8 +1774,8 @@ impl KProtocolProcessor {
                         ON CONFLICT (sender_signature) DO NOTHING
                         RETURNING transaction_id, block_time, sender_pubkey
                     )
-                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                    SELECT ri.transaction_id, 'reply', unnest($7::bytea[]), ri.block_time, ri.sender_pubkey
+                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet)
+                    SELECT ri.transaction_id, 'reply', unnest($7::bytea[]), ri.block_time, ri.sender_pubkey, $6, $8
                     FROM reply_insert ri
                     "#,
                 )
@@ -904,17 +1786,20 @@ impl KProtocolProcessor {
                 .bind(&k_reply.base64_encoded_message)
                 .bind(&post_id_bytes)
                 .bind(&mentioned_pubkeys_bytes)
-                .execute(&self.db_pool)
+                .bind(snippet_from_base64(&k_reply.base64_encoded_message))
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Reply transaction {} already exists, skipping",
                         transaction_id
                     );
                 } else {
                     info!("Saved K reply: {} -> {}", transaction_id, post_id_for_log);
                 }
+                result.rows_affected() > 0
             } else {
                 // Has both mentions AND hashtags - extended CTE with reply + mentions + hashtags
                 let result = sqlx::query(
@@ -928,8 +1813,8 @@ impl KProtocolProcessor {
                         RETURNING transaction_id, block_time, sender_pubkey
                     ),
                     mentions_insert AS (
-                        INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                        SELECT ri.transaction_id, 'reply', unnest($7::bytea[]), ri.block_time, ri.sender_pubkey
+                        INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet)
+                        SELECT ri.transaction_id, 'reply', unnest($7::bytea[]), ri.block_time, ri.sender_pubkey, $6, $9
                         FROM reply_insert ri
                         RETURNING 1
                     )
@@ -946,11 +1831,13 @@ impl KProtocolProcessor {
                 .bind(&post_id_bytes)
                 .bind(&mentioned_pubkeys_bytes)
                 .bind(&hashtags)
-                .execute(&self.db_pool)
+                .bind(snippet_from_base64(&k_reply.base64_encoded_message))
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Reply transaction {} already exists, skipping",
                         transaction_id
                     );
@@ -963,7 +1850,42 @@ impl KProtocolProcessor {
                         post_id_for_log
                     );
                 }
+                result.rows_affected() > 0
             }
+        };
+
+        if mention_limit.flag_as_spam {
+            self.flag_content_as_spam(conn, &transaction_id_bytes)
+                .await?;
+        }
+
+        if rate_limited {
+            self.flag_rate_limited(conn, "k_contents", &transaction_id_bytes)
+                .await?;
+        }
+
+        if let Some(message_hash) = message_hash {
+            self.save_content_hash(conn, &transaction_id_bytes, &message_hash)
+                .await?;
+        }
+
+        if let Some(render_spans) = render_spans {
+            self.save_render_spans(conn, &transaction_id_bytes, &render_spans)
+                .await?;
+        }
+
+        if let Some(search_text) = search_text {
+            self.save_search_vector(conn, &transaction_id_bytes, &search_text)
+                .await?;
+        }
+
+        self.save_thread_root(conn, &transaction_id_bytes).await?;
+
+        if inserted {
+            self.notify_live_content(conn, &transaction_id_bytes)
+                .await?;
+            self.notify_live_notifications(conn, &transaction_id_bytes, rate_limited)
+                .await?;
         }
         Ok(())
     }
@@ -971,6 +1893,7 @@ impl KProtocolProcessor {
     /// Save K quote to database
     pub async fn save_k_quote_to_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
         k_quote: KQuote,
     ) -> Result<()> {
@@ -988,7 +1911,11 @@ impl KProtocolProcessor {
             &k_quote.sender_signature,
             &k_quote.sender_pubkey,
         ) {
-            error!("Invalid signature for quote {}, skipping", transaction_id);
+            log_skip!(
+                error,
+                "Invalid signature for quote {}, skipping",
+                transaction_id
+            );
             return Ok(()); // Skip quotes with invalid signatures
         }
 
@@ -1008,11 +1935,51 @@ impl KProtocolProcessor {
 
         // Extract hashtags from the message
         let hashtags = extract_hashtags_from_base64(&k_quote.base64_encoded_message);
+        let message_hash = content_hash_from_base64(&k_quote.base64_encoded_message);
+        let render_spans = render_spans_from_base64(&k_quote.base64_encoded_message);
+
+        // Per-sender rate limit at index time
+        let rate_limited = self
+            .is_rate_limited(
+                conn,
+                "k_contents",
+                self.rate_limit.max_posts_per_minute,
+                &transaction_id_bytes,
+                &sender_pubkey_bytes,
+                block_time,
+            )
+            .await?;
+        if rate_limited && self.rate_limit.action == RateLimitAction::Skip {
+            log_skip!(
+                warn,
+                "Sender of quote {} exceeded {} posts/minute, skipping",
+                transaction_id,
+                self.rate_limit.max_posts_per_minute.unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        // The quoted author is not mentioned when they blocked the sender
+        let index_mention = !self
+            .suppress_blocked_mentions(
+                conn,
+                transaction_id,
+                &sender_pubkey_bytes,
+                vec![k_quote.mentioned_pubkey.clone()],
+            )
+            .await?
+            .is_empty();
+        let quoted_snippet = if index_mention {
+            self.indexed_content_snippet(conn, &content_id_bytes)
+                .await?
+        } else {
+            None
+        };
 
         // Single query to insert quote, mention, and hashtags using CTE
-        if hashtags.is_empty() {
-            // No hashtags - CTE with quote + mention only
-            let result = sqlx::query(
+        let inserted = if hashtags.is_empty() {
+            // No hashtags - CTE with quote + mention only (counts quotes: the mention may be suppressed)
+            let inserted: i64 = sqlx::query_scalar(
                 r#"
                 WITH quote_insert AS (
                     INSERT INTO k_contents (
@@ -1021,10 +1988,15 @@ impl KProtocolProcessor {
                     ) VALUES ($1, $2, $3, $4, $5, 'quote', $6)
                     ON CONFLICT (sender_signature) DO NOTHING
                     RETURNING transaction_id, block_time, sender_pubkey
+                ),
+                mention_insert AS (
+                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet, parent_snippet)
+                    SELECT qi.transaction_id, 'quote', $7, qi.block_time, qi.sender_pubkey, $6, $9, $10
+                    FROM quote_insert qi
+                    WHERE $8
+                    RETURNING 1
                 )
-                INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                SELECT qi.transaction_id, 'quote', $7, qi.block_time, qi.sender_pubkey
-                FROM quote_insert qi
+                SELECT COUNT(*) FROM quote_insert
                 "#,
             )
             .bind(&transaction_id_bytes)
@@ -1034,11 +2006,15 @@ impl KProtocolProcessor {
             .bind(&k_quote.base64_encoded_message)
             .bind(&content_id_bytes)
             .bind(&mentioned_pubkey_bytes)
-            .execute(&self.db_pool)
+            .bind(index_mention)
+            .bind(snippet_from_base64(&k_quote.base64_encoded_message))
+            .bind(&quoted_snippet)
+            .fetch_one(&mut *conn)
             .await?;
 
-            if result.rows_affected() == 0 {
-                info!(
+            if inserted == 0 {
+                log_skip!(
+                    info,
                     "Quote transaction {} already exists, skipping",
                     transaction_id
                 );
@@ -1048,6 +2024,7 @@ impl KProtocolProcessor {
                     transaction_id, content_id_for_log, mentioned_pubkey_for_log
                 );
             }
+            inserted > 0
         } else {
             // Has hashtags - extended CTE with quote + mention + hashtags
             let result = sqlx::query(
@@ -1061,9 +2038,10 @@ impl KProtocolProcessor {
                     RETURNING transaction_id, block_time, sender_pubkey
                 ),
                 mentions_insert AS (
-                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-                    SELECT qi.transaction_id, 'quote', $7, qi.block_time, qi.sender_pubkey
+                    INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet, parent_snippet)
+                    SELECT qi.transaction_id, 'quote', $7, qi.block_time, qi.sender_pubkey, $6, $10, $11
                     FROM quote_insert qi
+                    WHERE $9
                     RETURNING 1
                 )
                 INSERT INTO k_hashtags (sender_pubkey, content_id, block_time, hashtag)
@@ -1079,11 +2057,15 @@ impl KProtocolProcessor {
             .bind(&content_id_bytes)
             .bind(&mentioned_pubkey_bytes)
             .bind(&hashtags)
-            .execute(&self.db_pool)
+            .bind(index_mention)
+            .bind(snippet_from_base64(&k_quote.base64_encoded_message))
+            .bind(&quoted_snippet)
+            .execute(&mut *conn)
             .await?;
 
             if result.rows_affected() == 0 {
-                info!(
+                log_skip!(
+                    info,
                     "Quote transaction {} already exists, skipping",
                     transaction_id
                 );
@@ -1096,6 +2078,31 @@ impl KProtocolProcessor {
                     mentioned_pubkey_for_log
                 );
             }
+            result.rows_affected() > 0
+        };
+
+        if rate_limited {
+            self.flag_rate_limited(conn, "k_contents", &transaction_id_bytes)
+                .await?;
+        }
+
+        if let Some(message_hash) = message_hash {
+            self.save_content_hash(conn, &transaction_id_bytes, &message_hash)
+                .await?;
+        }
+
+        if let Some(render_spans) = render_spans {
+            self.save_render_spans(conn, &transaction_id_bytes, &render_spans)
+                .await?;
+        }
+
+        self.save_thread_root(conn, &transaction_id_bytes).await?;
+
+        if inserted {
+            self.notify_live_content(conn, &transaction_id_bytes)
+                .await?;
+            self.notify_live_notifications(conn, &transaction_id_bytes, rate_limited)
+                .await?;
         }
         Ok(())
     }
@@ -1103,6 +2110,7 @@ impl KProtocolProcessor {
     /// Save K broadcast to database
     pub async fn save_k_broadcast_to_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
         k_broadcast: KBroadcast,
     ) -> Result<()> {
@@ -1126,7 +2134,8 @@ impl KProtocolProcessor {
             &k_broadcast.sender_signature,
             &k_broadcast.sender_pubkey,
         ) {
-            error!(
+            log_skip!(
+                error,
                 "Invalid signature for broadcast {}, skipping",
                 transaction_id
             );
@@ -1138,18 +2147,66 @@ impl KProtocolProcessor {
         let sender_pubkey_bytes = hex::decode(&k_broadcast.sender_pubkey)?;
         let sender_signature_bytes = hex::decode(&k_broadcast.sender_signature)?;
 
+        // Move the profile image to blob storage if configured (keeps only reference + hash in DB)
+        let mut base64_encoded_profile_image = k_broadcast.base64_encoded_profile_image;
+        let mut stored_image: Option<StoredBlob> = None;
+        if let (Some(blob_storage), Some(image_base64)) =
+            (&self.blob_storage, base64_encoded_profile_image.as_deref())
+        {
+            stored_image = self
+                .store_profile_image(conn, blob_storage, transaction_id, image_base64)
+                .await?;
+            if stored_image.is_some() {
+                base64_encoded_profile_image = None;
+            }
+        }
+        let profile_image_ref = stored_image.as_ref().map(|blob| blob.key.clone());
+        let profile_image_hash = stored_image.as_ref().map(|blob| blob.hash.clone());
+
+        // Profile history compares images by hash: same digest as blob storage (decoded bytes),
+        // images that are not valid base64 are hashed as text
+        let history_image_hash = profile_image_hash.clone().or_else(|| {
+            base64_encoded_profile_image
+                .as_deref()
+                .filter(|image| !image.is_empty())
+                .map(|image| {
+                    let image_bytes = general_purpose::STANDARD
+                        .decode(image)
+                        .unwrap_or_else(|_| image.as_bytes().to_vec());
+                    Sha256::digest(&image_bytes).to_vec()
+                })
+        });
+
+        let released_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64;
+
         // Use a single query to delete existing records and insert the new one atomically (skip if transaction already exists)
+        // Profile images of the deleted broadcasts are released, unreferenced blobs become orphaned
         let result = sqlx::query(
             r#"
             WITH deleted AS (
                 DELETE FROM k_broadcasts
                 WHERE sender_pubkey = $3 AND transaction_id != $1
-                RETURNING transaction_id
+                RETURNING profile_image_ref
+            ),
+            released AS (
+                UPDATE k_profile_image_blobs b
+                SET ref_count = b.ref_count - r.refs,
+                    orphaned_at = CASE WHEN b.ref_count - r.refs <= 0 THEN $10 END
+                FROM (
+                    SELECT profile_image_ref, COUNT(*) AS refs
+                    FROM deleted
+                    WHERE profile_image_ref IS NOT NULL
+                    GROUP BY profile_image_ref
+                ) r
+                WHERE b.blob_key = r.profile_image_ref
             )
             INSERT INTO k_broadcasts (
                 transaction_id, block_time, sender_pubkey, sender_signature,
-                base64_encoded_nickname, base64_encoded_profile_image, base64_encoded_message
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                base64_encoded_nickname, base64_encoded_profile_image, base64_encoded_message,
+                profile_image_ref, profile_image_hash
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ON CONFLICT (transaction_id) DO NOTHING
             "#,
         )
@@ -1157,29 +2214,256 @@ impl KProtocolProcessor {
         .bind(transaction.block_time.unwrap_or(0))
         .bind(&sender_pubkey_bytes)
         .bind(&sender_signature_bytes)
-        .bind(k_broadcast.base64_encoded_nickname)
-        .bind(k_broadcast.base64_encoded_profile_image)
-        .bind(k_broadcast.base64_encoded_message)
-        .execute(&self.db_pool)
+        .bind(&k_broadcast.base64_encoded_nickname)
+        .bind(base64_encoded_profile_image)
+        .bind(&k_broadcast.base64_encoded_message)
+        .bind(profile_image_ref)
+        .bind(profile_image_hash)
+        .bind(released_at)
+        .execute(&mut *conn)
         .await?;
 
-        if result.rows_affected() == 0 {
-            info!(
-                "Broadcast transaction {} already exists, skipping",
-                transaction_id
-            );
-        } else {
-            info!(
-                "Saved K broadcast: {} (replaced any existing broadcasts for sender)",
-                transaction_id
-            );
+        // Only a newly indexed broadcast references its image
+        if let Some(blob) = stored_image.as_ref().filter(|_| result.rows_affected() > 0) {
+            image_blobs::acquire(conn, blob).await?;
         }
-        Ok(())
+
+        // Kept even when the broadcast is superseded, for /get-profile-diff and nickname history
+        // searches; also fills in normalized nicknames of broadcasts recorded before v29 when
+        // they are backfilled
+        sqlx::query(
+            r#"
+            INSERT INTO k_profile_history (
+                transaction_id, block_time, sender_pubkey, base64_encoded_nickname,
+                profile_image_hash, base64_encoded_message, normalized_nickname
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (transaction_id) DO UPDATE SET normalized_nickname = EXCLUDED.normalized_nickname
+            WHERE k_profile_history.normalized_nickname IS NULL
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(transaction.block_time.unwrap_or(0))
+        .bind(&sender_pubkey_bytes)
+        .bind(&k_broadcast.base64_encoded_nickname)
+        .bind(history_image_hash)
+        .bind(&k_broadcast.base64_encoded_message)
+        .bind(normalize_base64_nickname(&k_broadcast.base64_encoded_nickname))
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Broadcast transaction {} already exists, skipping",
+                transaction_id
+            );
+        } else {
+            info!(
+                "Saved K broadcast: {} (replaced any existing broadcasts for sender)",
+                transaction_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Decode a base64 profile image and write it to blob storage, unless the same image is
+    /// already stored for another broadcast
+    /// Returns None (keeping the image inline in the database) if decoding or upload fails
+    async fn store_profile_image(
+        &self,
+        conn: &mut PgConnection,
+        blob_storage: &BlobStorage,
+        transaction_id: &str,
+        image_base64: &str,
+    ) -> Result<Option<StoredBlob>> {
+        let image_bytes = match general_purpose::STANDARD.decode(image_base64) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                warn!(
+                    "Failed to decode profile image for broadcast {}: {} (keeping inline)",
+                    transaction_id, err
+                );
+                return Ok(None);
+            }
+        };
+
+        let blob = StoredBlob::profile_image(&image_bytes);
+        if image_blobs::is_referenced(conn, &blob).await? {
+            info!(
+                "Profile image for broadcast {} already stored: {}",
+                transaction_id, blob.key
+            );
+            return Ok(Some(blob));
+        }
+
+        match blob_storage.put(&blob, image_bytes).await {
+            Ok(()) => {
+                info!(
+                    "Stored profile image for broadcast {} in {} storage: {}",
+                    transaction_id,
+                    blob_storage.backend(),
+                    blob.key
+                );
+                Ok(Some(blob))
+            }
+            Err(err) => {
+                error!(
+                    "Failed to store profile image for broadcast {}: {} (keeping inline)",
+                    transaction_id, err
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Apply the configured mention limit to a post/reply mention list
+    fn limit_mentions(
+        &self,
+        transaction_id: &str,
+        mentioned_pubkeys: Vec<String>,
+    ) -> MentionLimitOutcome {
+        let outcome = apply_mention_limit(mentioned_pubkeys, &self.mention_limit);
+
+        if outcome.excess > 0 {
+            warn!(
+                "Transaction {} exceeds mention limit of {} by {} ({})",
+                transaction_id,
+                self.mention_limit.max_mentions,
+                outcome.excess,
+                if outcome.flag_as_spam {
+                    "flagged as spam, mentions dropped"
+                } else {
+                    "excess mentions ignored"
+                }
+            );
+        }
+
+        outcome
+    }
+
+    /// Drop mentions of users who have blocked the sender (--suppress-blocked-mentions)
+    /// Only blocks indexed before this transaction count: later blocks do not remove mentions
+    async fn suppress_blocked_mentions(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id: &str,
+        sender_pubkey_bytes: &[u8],
+        mentioned_pubkeys: Vec<String>,
+    ) -> Result<Vec<String>> {
+        if !self.mention_limit.suppress_blocked || mentioned_pubkeys.is_empty() {
+            return Ok(mentioned_pubkeys);
+        }
+
+        let mentioned_pubkeys_bytes = mentioned_pubkeys
+            .iter()
+            .map(hex::decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        let blockers: Vec<Vec<u8>> = sqlx::query_scalar(
+            "SELECT sender_pubkey FROM k_blocks WHERE blocked_user_pubkey = $1 AND sender_pubkey = ANY($2)",
+        )
+        .bind(sender_pubkey_bytes)
+        .bind(&mentioned_pubkeys_bytes)
+        .fetch_all(&mut *conn)
+        .await?;
+        if blockers.is_empty() {
+            return Ok(mentioned_pubkeys);
+        }
+
+        info!(
+            "Transaction {}: suppressed {} mentions of users who blocked the sender",
+            transaction_id,
+            blockers.len()
+        );
+        Ok(mentioned_pubkeys
+            .into_iter()
+            .zip(mentioned_pubkeys_bytes)
+            .filter(|(_, bytes)| !blockers.contains(bytes))
+            .map(|(pubkey, _)| pubkey)
+            .collect())
+    }
+
+    /// Flag content as spam (mention-bomb)
+    async fn flag_content_as_spam(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id_bytes: &[u8],
+    ) -> Result<()> {
+        sqlx::query("UPDATE k_contents SET is_spam = TRUE WHERE transaction_id = $1")
+            .bind(transaction_id_bytes)
+            .execute(&mut *conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Snippet of an indexed content's message, stored with the mentions of quotes and votes on it
+    /// (None when the content is not indexed, hot or cold)
+    async fn indexed_content_snippet(
+        &self,
+        conn: &mut PgConnection,
+        content_id_bytes: &[u8],
+    ) -> Result<Option<String>> {
+        let message: Option<String> = sqlx::query_scalar(
+            "SELECT base64_encoded_message FROM k_contents_tiered WHERE transaction_id = $1 LIMIT 1",
+        )
+        .bind(content_id_bytes)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(message.and_then(|message| snippet_from_base64(&message)))
+    }
+
+    /// Check whether the sender exceeds the rate limit in the minute before this block_time
+    /// Counts the sender's rows already indexed in `table` (k_contents or k_votes)
+    async fn is_rate_limited(
+        &self,
+        conn: &mut PgConnection,
+        table: &'static str,
+        max_per_minute: Option<u32>,
+        transaction_id_bytes: &[u8],
+        sender_pubkey_bytes: &[u8],
+        block_time: i64,
+    ) -> Result<bool> {
+        let Some(max_per_minute) = max_per_minute else {
+            return Ok(false);
+        };
+
+        let recent_count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} \
+             WHERE sender_pubkey = $1 AND block_time > $2 AND block_time <= $3 AND transaction_id != $4",
+            table
+        ))
+        .bind(sender_pubkey_bytes)
+        .bind(block_time - 60_000)
+        .bind(block_time)
+        .bind(transaction_id_bytes)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // The current record is not indexed yet
+        Ok(recent_count + 1 > max_per_minute as i64)
+    }
+
+    /// Mark a row as rate-limited so K-webserver can hide it (--hide-rate-limited)
+    async fn flag_rate_limited(
+        &self,
+        conn: &mut PgConnection,
+        table: &'static str,
+        transaction_id_bytes: &[u8],
+    ) -> Result<()> {
+        sqlx::query(&format!(
+            "UPDATE {} SET is_rate_limited = TRUE WHERE transaction_id = $1",
+            table
+        ))
+        .bind(transaction_id_bytes)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
     }
 
     /// Save K vote to database
     pub async fn save_k_vote_to_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
         k_vote: KVote,
     ) -> Result<()> {
@@ -1197,7 +2481,11 @@ impl KProtocolProcessor {
             &k_vote.sender_signature,
             &k_vote.sender_pubkey,
         ) {
-            error!("Invalid signature for vote {}, skipping", transaction_id);
+            log_skip!(
+                error,
+                "Invalid signature for vote {}, skipping",
+                transaction_id
+            );
             return Ok(()); // Skip votes with invalid signatures
         }
 
@@ -1215,20 +2503,85 @@ impl KProtocolProcessor {
         let post_id_bytes = hex::decode(&k_vote.post_id)?;
         let mentioned_pubkey_bytes = hex::decode(&k_vote.mentioned_pubkey)?;
 
-        // Single query to insert vote and mention using CTE (skip if already exists)
-        let result = sqlx::query(
+        // Per-sender rate limit at index time
+        let rate_limited = self
+            .is_rate_limited(
+                conn,
+                "k_votes",
+                self.rate_limit.max_votes_per_minute,
+                &transaction_id_bytes,
+                &sender_pubkey_bytes,
+                block_time,
+            )
+            .await?;
+        if rate_limited && self.rate_limit.action == RateLimitAction::Skip {
+            log_skip!(
+                warn,
+                "Sender of vote {} exceeded {} votes/minute, skipping",
+                transaction_id,
+                self.rate_limit.max_votes_per_minute.unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        // The post author is not mentioned when they blocked the voter
+        let index_mention = !self
+            .suppress_blocked_mentions(
+                conn,
+                transaction_id,
+                &sender_pubkey_bytes,
+                vec![k_vote.mentioned_pubkey.clone()],
+            )
+            .await?
+            .is_empty();
+        let voted_snippet = if index_mention {
+            self.indexed_content_snippet(conn, &post_id_bytes).await?
+        } else {
+            None
+        };
+
+        let fee = if self.vote_fee_weighting {
+            self.vote_fee(conn, transaction_id, &transaction_id_bytes)
+                .await?
+        } else {
+            None
+        };
+
+        // Single query to insert vote, vote totals and mention using CTE (skip if already exists)
+        // Counts votes, not mentions: the mention may be suppressed
+        let inserted: i64 = sqlx::query_scalar(
             r#"
             WITH vote_insert AS (
                 INSERT INTO k_votes (
                     transaction_id, block_time, sender_pubkey, sender_signature,
-                    post_id, vote
-                ) VALUES ($1, $2, $3, $4, $5, $6)
+                    post_id, vote, fee
+                ) VALUES ($1, $2, $3, $4, $5, $6, $10)
                 ON CONFLICT (sender_signature) DO NOTHING
-                RETURNING transaction_id, block_time, sender_pubkey
+                RETURNING transaction_id, block_time, sender_pubkey, post_id, vote, fee
+            ),
+            totals_update AS (
+                INSERT INTO k_vote_totals (post_id, up_votes, down_votes, up_votes_fee, down_votes_fee)
+                SELECT vi.post_id,
+                       CASE WHEN vi.vote = 'upvote' THEN 1 ELSE 0 END,
+                       CASE WHEN vi.vote = 'downvote' THEN 1 ELSE 0 END,
+                       CASE WHEN vi.vote = 'upvote' THEN COALESCE(vi.fee, 0) ELSE 0 END,
+                       CASE WHEN vi.vote = 'downvote' THEN COALESCE(vi.fee, 0) ELSE 0 END
+                FROM vote_insert vi
+                ON CONFLICT (post_id) DO UPDATE SET
+                    up_votes = k_vote_totals.up_votes + EXCLUDED.up_votes,
+                    down_votes = k_vote_totals.down_votes + EXCLUDED.down_votes,
+                    up_votes_fee = k_vote_totals.up_votes_fee + EXCLUDED.up_votes_fee,
+                    down_votes_fee = k_vote_totals.down_votes_fee + EXCLUDED.down_votes_fee
+                RETURNING 1
+            ),
+            mention_insert AS (
+                INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet, parent_snippet)
+                SELECT vi.transaction_id, 'vote', $7, vi.block_time, vi.sender_pubkey, vi.post_id, vi.vote, $9
+                FROM vote_insert vi
+                WHERE $8
+                RETURNING 1
             )
-            INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
-            SELECT vi.transaction_id, 'vote', $7, vi.block_time, vi.sender_pubkey
-            FROM vote_insert vi
+            SELECT COUNT(*) FROM vote_insert
             "#,
         )
         .bind(&transaction_id_bytes)
@@ -1238,11 +2591,15 @@ impl KProtocolProcessor {
         .bind(&post_id_bytes)
         .bind(k_vote.vote)
         .bind(&mentioned_pubkey_bytes)
-        .execute(&self.db_pool)
+        .bind(index_mention)
+        .bind(&voted_snippet)
+        .bind(fee.map(|fee| fee as i64))
+        .fetch_one(&mut *conn)
         .await?;
 
-        if result.rows_affected() == 0 {
-            info!(
+        if inserted == 0 {
+            log_skip!(
+                info,
                 "Vote transaction {} already exists, skipping",
                 transaction_id
             );
@@ -1252,12 +2609,57 @@ impl KProtocolProcessor {
                 transaction_id, post_id_for_log, vote_for_log
             );
         }
+
+        if rate_limited {
+            self.flag_rate_limited(conn, "k_votes", &transaction_id_bytes)
+                .await?;
+        }
+
+        if inserted > 0 {
+            self.notify_live_vote(conn, &transaction_id_bytes, &post_id_bytes)
+                .await?;
+            self.notify_live_notifications(conn, &transaction_id_bytes, rate_limited)
+                .await?;
+        }
         Ok(())
     }
 
+    /// Fee burned by a vote transaction (--vote-fee-weighting), from the inputs and outputs
+    /// written by the Kaspa indexer; None when an input amount was not resolved
+    async fn vote_fee(
+        &self,
+        conn: &mut PgConnection,
+        transaction_id: &str,
+        transaction_id_bytes: &[u8],
+    ) -> Result<Option<u64>> {
+        let input_amounts: Vec<Option<i64>> = sqlx::query_scalar(
+            "SELECT previous_outpoint_amount FROM transactions_inputs WHERE transaction_id = $1",
+        )
+        .bind(transaction_id_bytes)
+        .fetch_all(&mut *conn)
+        .await?;
+        let output_amounts: Vec<i64> =
+            sqlx::query_scalar("SELECT amount FROM transactions_outputs WHERE transaction_id = $1")
+                .bind(transaction_id_bytes)
+                .fetch_all(&mut *conn)
+                .await?;
+
+        // Every non-coinbase transaction has inputs and outputs
+        if input_amounts.is_empty() || output_amounts.is_empty() {
+            return Err(NotYetIndexed(format!(
+                "inputs and outputs of vote transaction {}",
+                transaction_id
+            ))
+            .into());
+        }
+
+        Ok(transaction_fee(&input_amounts, &output_amounts))
+    }
+
     /// Process K block action (block/unblock) in database
     pub async fn process_k_block_in_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
         k_block: KBlock,
     ) -> Result<()> {
@@ -1275,7 +2677,8 @@ impl KProtocolProcessor {
             &k_block.sender_signature,
             &k_block.sender_pubkey,
         ) {
-            error!(
+            log_skip!(
+                error,
                 "Invalid signature for block action {}, skipping",
                 transaction_id
             );
@@ -1311,11 +2714,12 @@ impl KProtocolProcessor {
                 .bind(&sender_signature_bytes)
                 .bind(&k_block.blocking_action)
                 .bind(&blocked_user_pubkey_bytes)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 if result.rows_affected() == 0 {
-                    info!(
+                    log_skip!(
+                        info,
                         "Block already exists: {} already blocked {} (keeping original), skipping",
                         hex::encode(&sender_pubkey_bytes),
                         hex::encode(&blocked_user_pubkey_bytes)
@@ -1340,7 +2744,7 @@ impl KProtocolProcessor {
                 )
                 .bind(&sender_pubkey_bytes)
                 .bind(&blocked_user_pubkey_bytes)
-                .execute(&self.db_pool)
+                .execute(&mut *conn)
                 .await?;
 
                 info!(
@@ -1365,6 +2769,7 @@ impl KProtocolProcessor {
     /// Process K follow action (follow/unfollow) in database
     pub async fn process_k_follow_in_database(
         &self,
+        conn: &mut PgConnection,
         transaction: &Transaction,
         k_follow: KFollow,
     ) -> Result<()> {
@@ -1382,7 +2787,8 @@ impl KProtocolProcessor {
             &k_follow.sender_signature,
             &k_follow.sender_pubkey,
         ) {
-            error!(
+            log_skip!(
+                error,
                 "Invalid signature for follow action {}, skipping",
                 transaction_id
             );
@@ -1401,15 +2807,38 @@ impl KProtocolProcessor {
                 let transaction_id_bytes = hex::decode(transaction_id)?;
                 let sender_signature_bytes = hex::decode(&k_follow.sender_signature)?;
 
-                // Insert follow record (skip if same sender already follows this user)
-                let result = sqlx::query(
+                // The followed user is not notified when they blocked the follower
+                let notify = !self
+                    .suppress_blocked_mentions(
+                        conn,
+                        transaction_id,
+                        &sender_pubkey_bytes,
+                        vec![k_follow.followed_user_pubkey.clone()],
+                    )
+                    .await?
+                    .is_empty();
+
+                // Insert follow record and its "follow" notification in k_mentions
+                // (skip if same sender already follows this user)
+                let inserted: i64 = sqlx::query_scalar(
                     r#"
-                    INSERT INTO k_follows (
-                        transaction_id, block_time, sender_pubkey, sender_signature,
-                        following_action, followed_user_pubkey
-                    ) VALUES ($1, $2, $3, $4, $5, $6)
-                    ON CONFLICT (sender_pubkey, followed_user_pubkey)
-                    DO NOTHING
+                    WITH follow_insert AS (
+                        INSERT INTO k_follows (
+                            transaction_id, block_time, sender_pubkey, sender_signature,
+                            following_action, followed_user_pubkey
+                        ) VALUES ($1, $2, $3, $4, $5, $6)
+                        ON CONFLICT (sender_pubkey, followed_user_pubkey)
+                        DO NOTHING
+                        RETURNING transaction_id, block_time, sender_pubkey, followed_user_pubkey
+                    ),
+                    mention_insert AS (
+                        INSERT INTO k_mentions (content_id, content_type, mentioned_pubkey, block_time, sender_pubkey)
+                        SELECT fi.transaction_id, 'follow', fi.followed_user_pubkey, fi.block_time, fi.sender_pubkey
+                        FROM follow_insert fi
+                        WHERE $7
+                        RETURNING 1
+                    )
+                    SELECT COUNT(*) FROM follow_insert
                     "#,
                 )
                 .bind(&transaction_id_bytes)
@@ -1418,11 +2847,13 @@ impl KProtocolProcessor {
                 .bind(&sender_signature_bytes)
                 .bind(&k_follow.following_action)
                 .bind(&followed_user_pubkey_bytes)
-                .execute(&self.db_pool)
+                .bind(notify)
+                .fetch_one(&mut *conn)
                 .await?;
 
-                if result.rows_affected() == 0 {
-                    info!(
+                if inserted == 0 {
+                    log_skip!(
+                        info,
                         "Follow already exists: {} already follows {} (keeping original), skipping",
                         hex::encode(&sender_pubkey_bytes),
                         hex::encode(&followed_user_pubkey_bytes)
@@ -1433,28 +2864,41 @@ impl KProtocolProcessor {
                         hex::encode(&sender_pubkey_bytes),
                         hex::encode(&followed_user_pubkey_bytes)
                     );
+                    self.notify_live_notifications(conn, &transaction_id_bytes, false)
+                        .await?;
                 }
             }
             "unfollow" => {
-                // Delete any existing "follow" record for the same sender and followed user
-                let delete_result = sqlx::query(
+                // Delete any existing "follow" record for the same sender and followed user,
+                // with its notification
+                let deleted: i64 = sqlx::query_scalar(
                     r#"
-                    DELETE FROM k_follows
-                    WHERE sender_pubkey = $1
-                    AND followed_user_pubkey = $2
-                    AND following_action = 'follow'
+                    WITH follow_delete AS (
+                        DELETE FROM k_follows
+                        WHERE sender_pubkey = $1
+                        AND followed_user_pubkey = $2
+                        AND following_action = 'follow'
+                        RETURNING transaction_id
+                    ),
+                    mention_delete AS (
+                        DELETE FROM k_mentions
+                        WHERE content_id IN (SELECT transaction_id FROM follow_delete)
+                        AND content_type = 'follow'
+                        RETURNING 1
+                    )
+                    SELECT COUNT(*) FROM follow_delete
                     "#,
                 )
                 .bind(&sender_pubkey_bytes)
                 .bind(&followed_user_pubkey_bytes)
-                .execute(&self.db_pool)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 info!(
                     "Processed K unfollow: {} unfollowed {} (deleted {} existing follow records)",
                     hex::encode(&sender_pubkey_bytes),
                     hex::encode(&followed_user_pubkey_bytes),
-                    delete_result.rows_affected()
+                    deleted
                 );
             }
             _ => {
@@ -1468,4 +2912,868 @@ impl KProtocolProcessor {
 
         Ok(())
     }
+
+    /// Save K group creation to database
+    /// The group id is the transaction id of the creation transaction; the creator owns the group
+    pub async fn save_k_group_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_group: KGroup,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's name + description + membership_policy
+        let message_to_verify = format!(
+            "{}:{}:{}",
+            k_group.base64_encoded_name,
+            k_group.base64_encoded_description,
+            k_group.membership_policy
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_group.sender_signature,
+            &k_group.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for group {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip groups with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_group.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_group.sender_signature)?;
+
+        // Insert group and its owner as first member atomically
+        let result = sqlx::query(
+            r#"
+            WITH group_insert AS (
+                INSERT INTO k_groups (
+                    transaction_id, block_time, sender_pubkey, sender_signature,
+                    base64_encoded_name, base64_encoded_description, membership_policy
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (sender_signature) DO NOTHING
+                RETURNING transaction_id, block_time, sender_pubkey
+            )
+            INSERT INTO k_group_members (group_id, member_pubkey, transaction_id, block_time)
+            SELECT gi.transaction_id, gi.sender_pubkey, gi.transaction_id, gi.block_time
+            FROM group_insert gi
+            ON CONFLICT (group_id, member_pubkey) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&k_group.base64_encoded_name)
+        .bind(&k_group.base64_encoded_description)
+        .bind(&k_group.membership_policy)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Group transaction {} already exists, skipping",
+                transaction_id
+            );
+        } else {
+            info!(
+                "Saved K group: {} ({} membership)",
+                transaction_id, k_group.membership_policy
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Process K membership action (join/leave/add/remove) in database
+    /// Members can join open groups and leave any group themselves; only the owner can add
+    /// or remove other members. The owner always stays a member of their own group
+    pub async fn process_k_membership_in_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_membership: KMembership,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's group_id + membership_action + member_pubkey
+        let message_to_verify = format!(
+            "{}:{}:{}",
+            k_membership.group_id, k_membership.membership_action, k_membership.member_pubkey
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_membership.sender_signature,
+            &k_membership.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for membership action {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip membership actions with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let group_id_bytes = hex::decode(&k_membership.group_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_membership.sender_pubkey)?;
+        let member_pubkey_bytes = hex::decode(&k_membership.member_pubkey)?;
+
+        // Look up group owner and membership policy
+        let group: Option<(Vec<u8>, String)> = sqlx::query_as(
+            "SELECT sender_pubkey, membership_policy FROM k_groups WHERE transaction_id = $1",
+        )
+        .bind(&group_id_bytes)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some((owner_pubkey_bytes, membership_policy)) = group else {
+            log_skip!(
+                warn,
+                "Membership action {} references unknown group {}, skipping",
+                transaction_id,
+                k_membership.group_id
+            );
+            return Ok(());
+        };
+
+        let sender_is_owner = sender_pubkey_bytes == owner_pubkey_bytes;
+        let member_is_sender = member_pubkey_bytes == sender_pubkey_bytes;
+        let member_is_owner = member_pubkey_bytes == owner_pubkey_bytes;
+
+        let allowed = match k_membership.membership_action.as_str() {
+            "join" => member_is_sender && membership_policy == "open",
+            "leave" => member_is_sender && !sender_is_owner,
+            "add" => sender_is_owner,
+            "remove" => sender_is_owner && !member_is_owner,
+            _ => {
+                error!(
+                    "Invalid membership_action: {}",
+                    k_membership.membership_action
+                );
+                return Err(anyhow::anyhow!(
+                    "Invalid membership_action: {}",
+                    k_membership.membership_action
+                ));
+            }
+        };
+
+        if !allowed {
+            log_skip!(
+                warn,
+                "Membership action {} ({} {} in {} group {}) not permitted for sender {}, skipping",
+                transaction_id,
+                k_membership.membership_action,
+                k_membership.member_pubkey,
+                membership_policy,
+                k_membership.group_id,
+                k_membership.sender_pubkey
+            );
+            return Ok(());
+        }
+
+        match k_membership.membership_action.as_str() {
+            "join" | "add" => {
+                let transaction_id_bytes = hex::decode(transaction_id)?;
+
+                // Insert member (skip if already a member)
+                let result = sqlx::query(
+                    r#"
+                    INSERT INTO k_group_members (group_id, member_pubkey, transaction_id, block_time)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (group_id, member_pubkey) DO NOTHING
+                    "#,
+                )
+                .bind(&group_id_bytes)
+                .bind(&member_pubkey_bytes)
+                .bind(&transaction_id_bytes)
+                .bind(block_time)
+                .execute(&mut *conn)
+                .await?;
+
+                if result.rows_affected() == 0 {
+                    log_skip!(
+                        info,
+                        "Member {} already in group {}, skipping",
+                        k_membership.member_pubkey,
+                        k_membership.group_id
+                    );
+                } else {
+                    info!(
+                        "Saved K membership: {} {} group {}",
+                        k_membership.member_pubkey,
+                        if k_membership.membership_action == "join" {
+                            "joined"
+                        } else {
+                            "added to"
+                        },
+                        k_membership.group_id
+                    );
+                }
+            }
+            _ => {
+                // "leave" | "remove": delete the membership row
+                let delete_result = sqlx::query(
+                    "DELETE FROM k_group_members WHERE group_id = $1 AND member_pubkey = $2",
+                )
+                .bind(&group_id_bytes)
+                .bind(&member_pubkey_bytes)
+                .execute(&mut *conn)
+                .await?;
+
+                info!(
+                    "Processed K membership {}: {} removed from group {} (deleted {} membership records)",
+                    k_membership.membership_action,
+                    k_membership.member_pubkey,
+                    k_membership.group_id,
+                    delete_result.rows_affected()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save K group post to database
+    /// Posts are only indexed when the sender is a member of the group at processing time
+    pub async fn save_k_group_post_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_group_post: KGroupPost,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's group_id + base64_message
+        let message_to_verify = format!(
+            "{}:{}",
+            k_group_post.group_id, k_group_post.base64_encoded_message
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_group_post.sender_signature,
+            &k_group_post.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for group post {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip group posts with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let group_id_bytes = hex::decode(&k_group_post.group_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_group_post.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_group_post.sender_signature)?;
+
+        // Insert only if the sender is currently a member of the group
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_group_posts (
+                transaction_id, block_time, group_id, sender_pubkey, sender_signature,
+                base64_encoded_message
+            )
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE EXISTS (
+                SELECT 1 FROM k_group_members
+                WHERE group_id = $3 AND member_pubkey = $4
+            )
+            ON CONFLICT (sender_signature) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&group_id_bytes)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&k_group_post.base64_encoded_message)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Group post {} skipped (already exists, unknown group or sender not a member of {})",
+                transaction_id,
+                k_group_post.group_id
+            );
+        } else {
+            info!(
+                "Saved K group post: {} in group {}",
+                transaction_id, k_group_post.group_id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Save K event to database
+    pub async fn save_k_event_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_event: KEvent,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's title + start_time + end_time + location
+        let message_to_verify = format!(
+            "{}:{}:{}:{}",
+            k_event.base64_encoded_title,
+            k_event.start_time,
+            k_event.end_time,
+            k_event.base64_encoded_location
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_event.sender_signature,
+            &k_event.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for event {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip events with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Times were validated while parsing
+        let start_time: i64 = k_event.start_time.parse()?;
+        let end_time: i64 = k_event.end_time.parse()?;
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_event.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_event.sender_signature)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_events (
+                transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_title, start_time, end_time, base64_encoded_location
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (sender_signature) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&k_event.base64_encoded_title)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(&k_event.base64_encoded_location)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Event transaction {} already exists, skipping",
+                transaction_id
+            );
+        } else {
+            info!("Saved K event: {}", transaction_id);
+        }
+
+        Ok(())
+    }
+
+    /// Save K RSVP to database
+    /// Each user keeps one RSVP per event; a newer RSVP replaces the previous one
+    pub async fn save_k_rsvp_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_rsvp: KRsvp,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's event_id + rsvp_status
+        let message_to_verify = format!("{}:{}", k_rsvp.event_id, k_rsvp.rsvp_status);
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_rsvp.sender_signature,
+            &k_rsvp.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for RSVP {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip RSVPs with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let event_id_bytes = hex::decode(&k_rsvp.event_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_rsvp.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_rsvp.sender_signature)?;
+
+        // Insert only for known events; keep the RSVP with the latest block time
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_event_rsvps (
+                transaction_id, block_time, event_id, sender_pubkey, sender_signature, rsvp_status
+            )
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE EXISTS (SELECT 1 FROM k_events WHERE transaction_id = $3)
+            ON CONFLICT (event_id, sender_pubkey) DO UPDATE SET
+                transaction_id = EXCLUDED.transaction_id,
+                block_time = EXCLUDED.block_time,
+                sender_signature = EXCLUDED.sender_signature,
+                rsvp_status = EXCLUDED.rsvp_status
+            WHERE k_event_rsvps.block_time <= EXCLUDED.block_time
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&event_id_bytes)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&k_rsvp.rsvp_status)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "RSVP {} skipped (unknown event {} or newer RSVP already stored)",
+                transaction_id,
+                k_rsvp.event_id
+            );
+        } else {
+            info!(
+                "Saved K RSVP: {} is {} to event {}",
+                k_rsvp.sender_pubkey, k_rsvp.rsvp_status, k_rsvp.event_id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Save K article manifest to database and reassemble it if its chunks already arrived
+    pub async fn save_k_article_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_article: KArticle,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's title + chunk_count + content_hash
+        let message_to_verify = format!(
+            "{}:{}:{}",
+            k_article.base64_encoded_title, k_article.chunk_count, k_article.content_hash
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_article.sender_signature,
+            &k_article.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for article {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip articles with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_article.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_article.sender_signature)?;
+        let content_hash_bytes = hex::decode(&k_article.content_hash)?;
+
+        // Serialize with the chunks of this article processed by other workers
+        lock_article(conn, transaction_id).await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_articles (
+                transaction_id, block_time, sender_pubkey, sender_signature,
+                base64_encoded_title, chunk_count, content_hash
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (sender_signature) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&k_article.base64_encoded_title)
+        .bind(k_article.chunk_count as i32)
+        .bind(&content_hash_bytes)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Article transaction {} already exists, skipping",
+                transaction_id
+            );
+            return Ok(());
+        }
+
+        info!(
+            "Saved K article manifest: {} ({} chunks)",
+            transaction_id, k_article.chunk_count
+        );
+
+        self.try_assemble_article(conn, &transaction_id_bytes, block_time)
+            .await
+    }
+
+    /// Save K article chunk to database and reassemble the article once all chunks arrived
+    /// Chunks may be processed before their manifest; they wait in k_article_chunks
+    pub async fn save_k_chunk_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_chunk: KChunk,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's manifest_id + chunk_index + data
+        let message_to_verify = format!(
+            "{}:{}:{}",
+            k_chunk.manifest_id, k_chunk.chunk_index, k_chunk.base64_encoded_data
+        );
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_chunk.sender_signature,
+            &k_chunk.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for chunk {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip chunks with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let manifest_id_bytes = hex::decode(&k_chunk.manifest_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_chunk.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_chunk.sender_signature)?;
+
+        // Serialize with the manifest and the other chunks of this article
+        lock_article(conn, &k_chunk.manifest_id).await?;
+
+        // Chunks arriving after the article was reassembled are not stored again
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_article_chunks (
+                transaction_id, block_time, manifest_id, sender_pubkey, sender_signature,
+                chunk_index, base64_encoded_data
+            )
+            SELECT $1, $2, $3, $4, $5, $6, $7
+            WHERE NOT EXISTS (
+                SELECT 1 FROM k_articles WHERE transaction_id = $3 AND status <> 'incomplete'
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&manifest_id_bytes)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(k_chunk.chunk_index as i32)
+        .bind(&k_chunk.base64_encoded_data)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Chunk {} of article {} skipped (already stored or article finished)",
+                k_chunk.chunk_index,
+                k_chunk.manifest_id
+            );
+            return Ok(());
+        }
+
+        info!(
+            "Saved K article chunk {} of article {}",
+            k_chunk.chunk_index, k_chunk.manifest_id
+        );
+
+        self.try_assemble_article(conn, &manifest_id_bytes, block_time)
+            .await
+    }
+
+    /// Save K tip to database
+    /// The tip amount is the sum of the transaction outputs paying the author of the tipped content
+    pub async fn save_k_tip_to_database(
+        &self,
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+        k_tip: KTip,
+    ) -> Result<()> {
+        let transaction_id = &transaction.transaction_id;
+
+        // Construct the message to verify - it's the content_id
+        let message_to_verify = k_tip.content_id.clone();
+
+        // Verify the signature
+        if !self.verify_kaspa_signature(
+            &message_to_verify,
+            &k_tip.sender_signature,
+            &k_tip.sender_pubkey,
+        ) {
+            log_skip!(
+                error,
+                "Invalid signature for tip {}, skipping",
+                transaction_id
+            );
+            return Ok(()); // Skip tips with invalid signatures
+        }
+
+        // Extract block time
+        let block_time = transaction.block_time.unwrap_or(0);
+
+        // Convert hex strings to bytea for database storage
+        let transaction_id_bytes = hex::decode(transaction_id)?;
+        let content_id_bytes = hex::decode(&k_tip.content_id)?;
+        let sender_pubkey_bytes = hex::decode(&k_tip.sender_pubkey)?;
+        let sender_signature_bytes = hex::decode(&k_tip.sender_signature)?;
+
+        // Tips go to the author of the tipped post, reply or quote, also when moved to the cold tier
+        let recipient_pubkey: Option<Vec<u8>> = sqlx::query_scalar(
+            r#"
+            SELECT sender_pubkey FROM k_contents_tiered
+            WHERE transaction_id = $1 AND content_type IN ('post', 'reply', 'quote')
+            "#,
+        )
+        .bind(&content_id_bytes)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some(recipient_pubkey) = recipient_pubkey else {
+            log_skip!(
+                info,
+                "Tip {} skipped (unknown content {})",
+                transaction_id,
+                k_tip.content_id
+            );
+            return Ok(());
+        };
+
+        // The change output of a self tip pays the author too: it cannot be told apart
+        if recipient_pubkey == sender_pubkey_bytes {
+            log_skip!(
+                info,
+                "Tip {} skipped (author tipping own content)",
+                transaction_id
+            );
+            return Ok(());
+        }
+
+        // Outputs are written by the Kaspa indexer; every transaction has at least one
+        let outputs: Vec<(Vec<u8>, i64)> = sqlx::query_as(
+            "SELECT script_public_key, amount FROM transactions_outputs WHERE transaction_id = $1",
+        )
+        .bind(&transaction_id_bytes)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if outputs.is_empty() {
+            return Err(
+                NotYetIndexed(format!("outputs of tip transaction {}", transaction_id)).into(),
+            );
+        }
+
+        let amount = tip_amount(&outputs, &recipient_pubkey);
+        if amount == 0 {
+            log_skip!(
+                warn,
+                "Tip {} skipped (no output pays the author of content {})",
+                transaction_id,
+                k_tip.content_id
+            );
+            return Ok(());
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO k_tips (
+                transaction_id, block_time, sender_pubkey, sender_signature,
+                content_id, recipient_pubkey, amount
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (sender_signature) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_id_bytes)
+        .bind(block_time)
+        .bind(&sender_pubkey_bytes)
+        .bind(&sender_signature_bytes)
+        .bind(&content_id_bytes)
+        .bind(&recipient_pubkey)
+        .bind(amount as i64)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            log_skip!(
+                info,
+                "Tip transaction {} already exists, skipping",
+                transaction_id
+            );
+        } else {
+            info!(
+                "Saved K tip: {} sompi to content {}",
+                amount, k_tip.content_id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reassemble an incomplete article when all of its chunks from the author are stored
+    /// The caller must hold the article lock (see `lock_article`)
+    async fn try_assemble_article(
+        &self,
+        conn: &mut PgConnection,
+        manifest_id: &[u8],
+        block_time: i64,
+    ) -> Result<()> {
+        let manifest_id_hex = hex::encode(manifest_id);
+
+        let article: Option<(Vec<u8>, i32, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT sender_pubkey, chunk_count, content_hash
+            FROM k_articles
+            WHERE transaction_id = $1 AND status = 'incomplete'
+            "#,
+        )
+        .bind(manifest_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        // Manifest not processed yet (or article already finished)
+        let Some((sender_pubkey, chunk_count, content_hash)) = article else {
+            return Ok(());
+        };
+
+        // Only chunks signed by the article author count
+        let chunks: Vec<(i32, String)> = sqlx::query_as(
+            r#"
+            SELECT chunk_index, base64_encoded_data
+            FROM k_article_chunks
+            WHERE manifest_id = $1 AND sender_pubkey = $2 AND chunk_index < $3
+            "#,
+        )
+        .bind(manifest_id)
+        .bind(&sender_pubkey)
+        .bind(chunk_count)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let chunks: Vec<(u32, String)> = chunks
+            .into_iter()
+            .map(|(index, data)| (index as u32, data))
+            .collect();
+
+        match assemble_article(&chunks, chunk_count as u32, &content_hash) {
+            ArticleAssembly::Incomplete { received } => {
+                info!(
+                    "Article {} waiting for chunks ({}/{})",
+                    manifest_id_hex, received, chunk_count
+                );
+                return Ok(());
+            }
+            ArticleAssembly::Complete(content) => {
+                sqlx::query(
+                    r#"
+                    UPDATE k_articles
+                    SET base64_encoded_content = $2, status = 'complete', completed_at = $3
+                    WHERE transaction_id = $1
+                    "#,
+                )
+                .bind(manifest_id)
+                .bind(&content)
+                .bind(block_time)
+                .execute(&mut *conn)
+                .await?;
+                info!(
+                    "Article {} reassembled from {} chunks",
+                    manifest_id_hex, chunk_count
+                );
+            }
+            ArticleAssembly::HashMismatch => {
+                sqlx::query("UPDATE k_articles SET status = 'invalid' WHERE transaction_id = $1")
+                    .bind(manifest_id)
+                    .execute(&mut *conn)
+                    .await?;
+                warn!(
+                    "Article {} chunks do not match the manifest content hash, marked invalid",
+                    manifest_id_hex
+                );
+            }
+        }
+
+        // The article is finished: its chunks are no longer needed
+        sqlx::query("DELETE FROM k_article_chunks WHERE manifest_id = $1")
+            .bind(manifest_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Take a transaction-scoped lock on an article (by manifest id) so that its manifest and
+/// chunks processed concurrently by different workers cannot miss each other
+async fn lock_article(conn: &mut PgConnection, manifest_id: &str) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+        .bind(manifest_id.to_lowercase())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
 }
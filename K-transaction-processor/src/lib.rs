@@ -0,0 +1,898 @@
+mod article_assembler;
+mod backfill;
+mod blob_storage;
+mod chain_params;
+mod circuit_breaker;
+mod config;
+mod content_hasher;
+mod content_snippet;
+mod database;
+mod doctor;
+mod hashtag_extractor;
+mod image_blobs;
+mod impersonation_detector;
+mod in_flight;
+mod k_protocol;
+mod listener;
+mod maintenance;
+mod mention_limiter;
+mod nickname_similarity;
+mod queue;
+mod render_spans;
+mod retry_policy;
+mod sample_data;
+mod search_text;
+mod seed;
+mod simulator;
+mod storage_monitor;
+mod tenants;
+mod tip_detector;
+mod tombstones;
+mod transaction_fee;
+mod transaction_reindex_service;
+mod visibility;
+mod vote_totals;
+mod worker;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use k_common::{failover, pool_monitor};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use blob_storage::BlobStorage;
+use config::AppConfig;
+use database::{
+    KDbClient, create_pool, create_tenant_pool, create_tenant_schema, get_write_intents,
+    has_resolved_input_amounts, is_maintenance_mode, set_maintenance_mode,
+};
+use listener::NotificationListener;
+use queue::NotificationQueue;
+use sample_data::SampleDataConfig;
+use worker::WorkerPool;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "K-indexer Transaction Processor", long_about = None)]
+pub struct Args {
+    #[command(flatten)]
+    database: DatabaseArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Database options shared by all subcommands
+#[derive(clap::Args, Debug)]
+struct DatabaseArgs {
+    #[arg(
+        short = 'H',
+        long,
+        global = true,
+        help = "Database host, or comma-separated hosts (host[:port]) tried in order and failed over to"
+    )]
+    db_host: Option<String>,
+
+    #[arg(short = 'P', long, global = true, help = "Database port")]
+    db_port: Option<u16>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Host of --db-host connections go to: 'any' (the first reachable) or 'read-write' (the first accepting writes, the primary) (default: any)"
+    )]
+    db_target_session_attrs: Option<String>,
+
+    #[arg(short = 'd', long, global = true, help = "Database name")]
+    db_name: Option<String>,
+
+    #[arg(short = 'U', long, global = true, help = "Database username")]
+    db_user: Option<String>,
+
+    #[arg(short = 'p', long, global = true, help = "Database password")]
+    db_password: Option<String>,
+
+    #[arg(
+        short = 'm',
+        long,
+        global = true,
+        help = "Maximum database connections"
+    )]
+    db_max_connections: Option<usize>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    wait_for_db: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Wait for the main Kaspa indexer to create the transactions table instead of failing"
+    )]
+    wait_for_schema: bool,
+
+    #[arg(
+        short = 'n',
+        long,
+        global = true,
+        help = "Network type: 'testnet-10' or 'mainnet'",
+        default_value = "testnet-10"
+    )]
+    network: String,
+
+    #[arg(
+        long = "tenant",
+        global = true,
+        value_name = "ID=PREFIX",
+        help = "Also index the K community using this payload prefix into the schema tenant_<ID> (repeatable)"
+    )]
+    tenants: Vec<String>,
+}
+
+/// Transaction processing options shared by `run` and `backfill`
+#[derive(clap::Args, Debug, Default)]
+struct ProcessingArgs {
+    #[arg(short = 'w', long, help = "Number of worker threads")]
+    workers: Option<usize>,
+
+    #[arg(short = 'C', long, help = "PostgreSQL notification channel name")]
+    channel: Option<String>,
+
+    #[arg(short = 'r', long, help = "Number of retry attempts")]
+    retry_attempts: Option<u32>,
+
+    #[arg(short = 'D', long, help = "Retry delay in milliseconds")]
+    retry_delay: Option<u64>,
+
+    #[arg(
+        short = 'b',
+        long,
+        help = "Maximum transactions per worker batch (one DB transaction per batch)"
+    )]
+    batch_size: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Consecutive database connection errors after which workers pause until the database is reachable, 0 to disable (default: 5)"
+    )]
+    circuit_breaker_threshold: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between database probes while workers are paused (default: 5)"
+    )]
+    circuit_breaker_probe_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Profile image storage: 'database', 'filesystem' or 's3' (default: database)"
+    )]
+    image_storage: Option<String>,
+
+    #[arg(
+        long,
+        help = "Directory for profile images (--image-storage filesystem)"
+    )]
+    image_storage_path: Option<String>,
+
+    #[arg(long, help = "S3 bucket for profile images (--image-storage s3)")]
+    image_storage_bucket: Option<String>,
+
+    #[arg(long, help = "S3 region for profile images (defaults to AWS_REGION)")]
+    image_storage_region: Option<String>,
+
+    #[arg(
+        long,
+        help = "Custom S3 endpoint URL, e.g. for MinIO (--image-storage s3)"
+    )]
+    image_storage_endpoint: Option<String>,
+
+    #[arg(long, help = "Maximum mentions indexed per post/reply (default: 20)")]
+    max_mentions: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Action for posts/replies over --max-mentions: 'ignore' (truncate) or 'spam' (flag, no mentions) (default: ignore)"
+    )]
+    excess_mentions: Option<String>,
+
+    #[arg(
+        long,
+        help = "Do not index mentions (and notifications) of users who have blocked the sender; later blocks and unblocks do not change indexed mentions"
+    )]
+    suppress_blocked_mentions: bool,
+
+    #[arg(
+        long,
+        help = "Maximum posts/replies/quotes per sender per minute (default: unlimited)"
+    )]
+    max_posts_per_minute: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Maximum votes per sender per minute (default: unlimited)"
+    )]
+    max_votes_per_minute: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Action for senders over the rate limit: 'mark' (index as rate-limited) or 'skip' (default: mark)"
+    )]
+    rate_limit_action: Option<String>,
+
+    #[arg(
+        long,
+        help = "Record the fee burned by each vote (inputs minus outputs) for fee-weighted vote totals; needs the transactions_inputs table with resolved previous outpoint amounts"
+    )]
+    vote_fee_weighting: bool,
+
+    #[arg(
+        long,
+        help = "Blocks on top of the block of a content before K-webserver lists it in feeds, against reorg flicker (default: 0, immediately)"
+    )]
+    confirmation_depth: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Expose Prometheus metrics on this address, e.g. 0.0.0.0:9100 (default: disabled)"
+    )]
+    metrics_bind_address: Option<String>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between connection pool samples (default: 15)"
+    )]
+    pool_monitor_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Warn when acquiring a DB connection takes longer than this many ms (default: 500)"
+    )]
+    pool_acquire_warn_ms: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between K table storage reports, at least 60 (default: 86400)"
+    )]
+    storage_report_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between impersonation checks of new profiles, at least 10 (default: 600)"
+    )]
+    impersonation_check_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Followers a profile needs to be protected against impersonation (default: 10)"
+    )]
+    impersonation_min_followers: Option<i64>,
+
+    #[arg(
+        long,
+        help = "Nickname similarity from 0.0 to 1.0 at which a profile is flagged as impersonator (default: 0.85)"
+    )]
+    impersonation_threshold: Option<f64>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between maintenance mode checks in k_meta (default: 10)"
+    )]
+    maintenance_check_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between reconciliations of vote totals with k_votes, at least 60 (default: 3600)"
+    )]
+    vote_totals_interval: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Interval in seconds between deletions of unreferenced profile images from blob storage, at least 60 (default: 3600)"
+    )]
+    image_gc_interval: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Listen for K transactions and index them (creates the schema if missing)
+    Run {
+        #[command(flatten)]
+        processing: ProcessingArgs,
+
+        #[arg(long, help = "Apply pending schema migrations before starting")]
+        migrate: bool,
+    },
+    /// Create the schema if missing or upgrade it to the current version, then exit
+    Migrate,
+    /// Index K transactions already stored in the transactions table, then exit
+    Backfill {
+        #[command(flatten)]
+        processing: ProcessingArgs,
+
+        #[arg(
+            long,
+            help = "Start at this block time in ms (default: 10 minutes before the processed watermark in k_vars, or the beginning)"
+        )]
+        from_block_time: Option<i64>,
+
+        #[arg(long, help = "Stop at this block time in ms (default: now)")]
+        to_block_time: Option<i64>,
+
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Maximum K transactions queued per second, leaving capacity to a running processor (0 = unlimited)"
+        )]
+        max_rate: u64,
+    },
+    /// Run REINDEX CONCURRENTLY on the transactions table indexes once, then exit
+    Reindex,
+    /// Verify schema version, tables, indexes and notification trigger, then exit
+    Verify,
+    /// Check database connectivity, extensions, indexes, schema version, LISTEN channel and
+    /// disk space, print a report and exit (non-zero exit code if a check fails)
+    Doctor {
+        #[command(flatten)]
+        processing: ProcessingArgs,
+    },
+    /// Drop all K tables, functions and triggers, then exit
+    DropSchema {
+        #[arg(long, help = "Confirm dropping the K schema and all indexed data")]
+        yes: bool,
+    },
+    /// Enable or disable read-only maintenance mode in k_meta, then exit
+    Maintenance {
+        #[arg(value_parser = ["on", "off"], help = "'on' or 'off'")]
+        mode: String,
+
+        #[arg(
+            long,
+            default_value = "300",
+            help = "Retry-After seconds announced by K-webserver during maintenance"
+        )]
+        retry_after: u32,
+
+        #[arg(long, help = "Reason shown by K-webserver during maintenance")]
+        reason: Option<String>,
+    },
+    /// Run a raw K payload through the parser and validation without persisting it, print the
+    /// result as JSON and exit (development tool for client authors)
+    SimulatePayload {
+        #[command(flatten)]
+        processing: ProcessingArgs,
+
+        #[arg(long, help = "Raw K payload, e.g. 'k:1:post:...'")]
+        payload: String,
+
+        #[arg(long, help = "Transaction id in hex (default: SHA-256 of the payload)")]
+        transaction_id: Option<String>,
+
+        #[arg(long, help = "Block time in ms (default: now)")]
+        block_time: Option<i64>,
+    },
+    /// Index a deterministic sample data set (users, follows, blocks, posts with mentions and
+    /// hashtags, reply threads, votes and quotes) into a fresh database, then exit
+    Seed {
+        #[command(flatten)]
+        processing: ProcessingArgs,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Seed of the generated data (same seed, same data)"
+        )]
+        seed: u64,
+
+        #[arg(long, default_value_t = 10, help = "Number of users")]
+        users: usize,
+
+        #[arg(long, default_value_t = 5, help = "Posts per user")]
+        posts_per_user: usize,
+
+        #[arg(
+            long,
+            default_value_t = 1_700_000_000_000,
+            help = "Block time of the first transaction in ms (one transaction per second after it)"
+        )]
+        start_block_time: i64,
+    },
+    /// Manage the tombstone registry of transactions and senders that are never indexed, then exit
+    Tombstones {
+        #[command(subcommand)]
+        action: TombstoneCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TombstoneCommand {
+    /// Tombstone a transaction or a sender and remove what is already indexed
+    Add {
+        #[arg(
+            long,
+            required_unless_present = "pubkey",
+            conflicts_with = "pubkey",
+            help = "Transaction id (hex) to tombstone"
+        )]
+        transaction_id: Option<String>,
+
+        #[arg(long, help = "Sender public key (hex) to tombstone")]
+        pubkey: Option<String>,
+
+        #[arg(
+            long,
+            help = "Reason recorded with the tombstone (exported to mirrors)"
+        )]
+        reason: Option<String>,
+    },
+    /// Write all tombstones to a JSON file for mirrored instances
+    Export {
+        #[arg(long, help = "Output JSON file")]
+        output: std::path::PathBuf,
+    },
+    /// Apply tombstones exported by an upstream instance and remove what is already indexed
+    Import {
+        #[arg(long, help = "JSON file written by 'tombstones export'")]
+        input: std::path::PathBuf,
+
+        #[arg(
+            long,
+            help = "Name of the upstream instance, recorded as the tombstone source"
+        )]
+        source: String,
+    },
+}
+
+pub use simulator::{PayloadSimulator, SimulationReport, TableWrites};
+
+/// Payload simulator validating like the processor `run` of `args` (network and limits), for a
+/// webserver sharing the binary (k-indexer) to serve /admin/simulate-payload. Simulations only
+/// use the network's own schema
+pub fn payload_simulator(args: Args) -> Result<PayloadSimulator> {
+    let Command::Run { processing, .. } = &args.command else {
+        anyhow::bail!("The payload simulator takes the options of the processor run command");
+    };
+    let config = AppConfig::from_args(&args.database, processing)?;
+    Ok(PayloadSimulator::new(config))
+}
+
+/// Run the subcommand of `args` (tracing is initialized by the caller: K-transaction-processor
+/// or the k-indexer CLI)
+pub async fn run(args: Args) -> Result<()> {
+    info!(
+        "Starting Transaction Processor v{}",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // Load configuration from CLI arguments only (defaults for commands without processing options)
+    let default_processing = ProcessingArgs::default();
+    let processing = match &args.command {
+        Command::Run { processing, .. }
+        | Command::Backfill { processing, .. }
+        | Command::Doctor { processing }
+        | Command::SimulatePayload { processing, .. }
+        | Command::Seed { processing, .. } => processing,
+        _ => &default_processing,
+    };
+    let config = match AppConfig::from_args(&args.database, processing) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+    info!(
+        "Database connection: {}:{}/{}",
+        config.database.host, config.database.port, config.database.database
+    );
+
+    // Doctor reports an unreachable database instead of waiting for it
+    if let Command::Doctor { .. } = args.command {
+        return doctor::run(&config).await;
+    }
+
+    let db_pool = create_pool(&config).await?;
+    info!(
+        "Database connection pool created with {} max connections",
+        config.database.max_connections
+    );
+
+    // Initialize database following Simply Kaspa Indexer pattern
+    let database = KDbClient::new(db_pool);
+    let wait_for_schema = args.database.wait_for_schema;
+
+    // One schema per community of --tenant, next to the network's own one
+    let mut tenants = Vec::with_capacity(config.tenants.len());
+    for tenant in &config.tenants {
+        info!(
+            "Tenant {}: payload prefix {}, schema {}",
+            tenant.id,
+            tenant.payload_prefix,
+            tenant.schema()
+        );
+        let pool = create_tenant_pool(database.pool(), &config, tenant).await?;
+        tenants.push(KDbClient::for_tenant(pool, tenant.clone()));
+    }
+
+    match args.command {
+        Command::Run { migrate, .. } => {
+            prepare_schema(&database, &config, migrate, wait_for_schema).await?;
+            prepare_tenant_schemas(&database, &tenants, &config, migrate, wait_for_schema).await?;
+            run(database, tenants, config).await
+        }
+        Command::Migrate => {
+            prepare_schema(&database, &config, true, wait_for_schema).await?;
+            prepare_tenant_schemas(&database, &tenants, &config, true, wait_for_schema).await
+        }
+        Command::Backfill {
+            from_block_time,
+            to_block_time,
+            max_rate,
+            ..
+        } => {
+            prepare_schema(&database, &config, false, wait_for_schema).await?;
+            backfill::execute(
+                database.pool(),
+                &config,
+                from_block_time,
+                to_block_time,
+                max_rate,
+            )
+            .await
+        }
+        Command::Reindex => {
+            transaction_reindex_service::run_reindex_once(database.pool().clone()).await;
+            Ok(())
+        }
+        Command::Doctor { .. } => unreachable!("handled before connecting"),
+        Command::Verify => {
+            database.verify_schema().await?;
+            database.verify_network(&config.chain.name).await?;
+            for tenant in &tenants {
+                tenant.verify_schema().await?;
+                tenant.verify_network(&config.chain.name).await?;
+            }
+            Ok(())
+        }
+        Command::DropSchema { yes } => {
+            if !yes {
+                return Err(anyhow::anyhow!(
+                    "Dropping the schema deletes all indexed K data. Re-run with '--yes' to confirm"
+                ));
+            }
+            for tenant in &tenants {
+                tenant.drop_schema().await?;
+            }
+            database.drop_schema().await
+        }
+        Command::Maintenance {
+            mode,
+            retry_after,
+            reason,
+        } => {
+            // Toggle maintenance mode for every processor/webserver sharing this database
+            let enabled = mode == "on";
+            set_maintenance_mode(database.pool(), enabled, retry_after, reason.as_deref()).await?;
+            // Workers pause on the flag of the network's own schema, K-webservers of tenants
+            // read the one of their schema
+            for tenant in &tenants {
+                set_maintenance_mode(tenant.pool(), enabled, retry_after, reason.as_deref())
+                    .await?;
+            }
+            info!(
+                "Maintenance mode {}",
+                if enabled { "enabled" } else { "disabled" }
+            );
+            Ok(())
+        }
+        Command::SimulatePayload {
+            payload,
+            transaction_id,
+            block_time,
+            ..
+        } => {
+            prepare_schema(&database, &config, false, wait_for_schema).await?;
+            let report = simulator::simulate_payload(
+                database.pool(),
+                &config,
+                &payload,
+                transaction_id,
+                block_time,
+            )
+            .await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            Ok(())
+        }
+        Command::Seed {
+            seed,
+            users,
+            posts_per_user,
+            start_block_time,
+            ..
+        } => {
+            prepare_schema(&database, &config, false, wait_for_schema).await?;
+            let sample = SampleDataConfig {
+                seed,
+                users,
+                posts_per_user,
+                start_block_time,
+            };
+            seed::execute(database.pool(), &config, &sample).await
+        }
+        Command::Tombstones { action } => {
+            prepare_schema(&database, &config, false, wait_for_schema).await?;
+            match action {
+                TombstoneCommand::Add {
+                    transaction_id,
+                    pubkey,
+                    reason,
+                } => {
+                    tombstones::add(
+                        database.pool(),
+                        transaction_id.as_deref(),
+                        pubkey.as_deref(),
+                        reason,
+                    )
+                    .await
+                }
+                TombstoneCommand::Export { output } => {
+                    tombstones::export(database.pool(), &config.chain.name, &output).await
+                }
+                TombstoneCommand::Import { input, source } => {
+                    tombstones::import(database.pool(), &config.chain.name, &input, &source).await
+                }
+            }
+        }
+    }
+}
+
+/// Create (or with `migrate`, upgrade) the schema and verify the network type
+async fn prepare_schema(
+    database: &KDbClient,
+    config: &AppConfig,
+    migrate: bool,
+    wait_for_schema: bool,
+) -> Result<()> {
+    database.create_schema(migrate, wait_for_schema).await?;
+
+    // Vote fees need the inputs written by the Kaspa indexer, with resolved previous outpoints
+    if config.vote_fee_weighting && !has_resolved_input_amounts(database.pool()).await? {
+        return Err(anyhow::anyhow!(
+            "--vote-fee-weighting needs the transactions_inputs table with previous_outpoint_amount: enable transactions_inputs_table and previous outpoint resolution in the Kaspa indexer"
+        ));
+    }
+
+    // Notify the payloads of the tenants as well (also forgets the prefixes of removed ones)
+    database
+        .create_notification_function(&config.tenants)
+        .await?;
+
+    // Set and verify network type after schema is created/verified
+    database.set_and_verify_network(&config.chain.name).await
+}
+
+/// Create (or with `migrate`, upgrade) the schema of every tenant
+async fn prepare_tenant_schemas(
+    database: &KDbClient,
+    tenants: &[KDbClient],
+    config: &AppConfig,
+    migrate: bool,
+    wait_for_schema: bool,
+) -> Result<()> {
+    for (tenant_db, tenant) in tenants.iter().zip(&config.tenants) {
+        info!(
+            "Preparing schema {} of tenant {}",
+            tenant.schema(),
+            tenant.id
+        );
+        create_tenant_schema(database.pool(), tenant).await?;
+        tenant_db.create_schema(migrate, wait_for_schema).await?;
+        tenant_db.set_and_verify_network(&config.chain.name).await?;
+    }
+    Ok(())
+}
+
+/// Start listener, queue, workers and background services until shutdown
+async fn run(database: KDbClient, tenants: Vec<KDbClient>, config: AppConfig) -> Result<()> {
+    info!(
+        "Configuration loaded: {} workers, channel: {}, network: {} ({}: addresses, {} payloads)",
+        config.workers.count,
+        config.processing.channel_name,
+        config.chain.name,
+        config.chain.address_prefix,
+        config.chain.payload_prefix
+    );
+
+    let maintenance_enabled = is_maintenance_mode(database.pool()).await?;
+    if maintenance_enabled {
+        info!("Maintenance mode is enabled - transaction processing paused until it is disabled");
+    }
+    let maintenance = maintenance::spawn_maintenance_watcher(
+        database.pool().clone(),
+        config.maintenance_check_interval_secs,
+        maintenance_enabled,
+    );
+
+    let (notification_sender, notification_receiver) = mpsc::unbounded_channel();
+
+    // Roll forward the batches a crash or a kill interrupted (k_write_intents): their K
+    // transactions are queued ahead of the notifications and reprocessed
+    let interrupted = get_write_intents(database.pool()).await?;
+    if !interrupted.is_empty() {
+        warn!(
+            "Rolling forward {} K transactions of batches interrupted by the previous run",
+            interrupted.len()
+        );
+        for transaction_id in interrupted {
+            notification_sender.send(transaction_id)?;
+        }
+    }
+
+    let (mut notification_queue, worker_receivers) =
+        NotificationQueue::new(notification_receiver, config.workers.count);
+
+    let notification_listener =
+        NotificationListener::new(config.clone(), database.pool().clone(), notification_sender);
+
+    // Optional blob storage for profile images (None = keep images inline in the database)
+    let blob_storage = BlobStorage::from_config(&config.image_storage)?.map(Arc::new);
+
+    let tenant_pools = config
+        .tenants
+        .iter()
+        .cloned()
+        .zip(tenants.iter().map(|tenant| tenant.pool().clone()))
+        .collect();
+    let worker_pool = WorkerPool::new(
+        worker_receivers,
+        database.pool().clone(),
+        tenant_pools,
+        config.clone(),
+        blob_storage.clone(),
+        maintenance,
+    );
+
+    if let Some(metrics_bind_address) = config.monitoring.metrics_bind_address {
+        PrometheusBuilder::new()
+            .with_http_listener(metrics_bind_address)
+            .install()?;
+        info!(
+            "Prometheus metrics available on http://{}/metrics",
+            metrics_bind_address
+        );
+    }
+
+    info!("Starting all components...");
+
+    // Start connection pool monitor (gauges + slow acquire warnings)
+    tokio::spawn(pool_monitor::start_pool_monitor(
+        database.pool().clone(),
+        config.monitoring.pool_monitor_interval_secs,
+        config.monitoring.pool_acquire_warn_ms,
+    ));
+
+    // Follow the primary across failovers among the hosts of --db-host
+    if config.database.hosts.len() > 1 {
+        let failover_pools = std::iter::once(database.pool())
+            .chain(tenants.iter().map(|tenant| tenant.pool()))
+            .cloned()
+            .collect();
+        tokio::spawn(failover::start_failover_watcher(
+            failover_pools,
+            config.database.hosts.clone(),
+            config.database.target_session_attrs,
+        ));
+    }
+
+    // Start storage monitor (per-table sizes and week-over-week growth)
+    let storage_pool = database.pool().clone();
+    let storage_config = config.monitoring.clone();
+    tokio::spawn(async move {
+        storage_monitor::start_storage_monitor(storage_pool, storage_config).await;
+    });
+
+    // Start impersonation detector (new profiles vs. nicknames of high-follower users)
+    let impersonation_pool = database.pool().clone();
+    let impersonation_config = config.impersonation.clone();
+    tokio::spawn(async move {
+        impersonation_detector::start_impersonation_detector(
+            impersonation_pool,
+            impersonation_config,
+        )
+        .await;
+    });
+
+    // Start vote totals reconciliation (corrects totals of votes deleted by other tools)
+    let vote_totals_pool = database.pool().clone();
+    let vote_totals_interval = config.vote_totals_interval_secs;
+    tokio::spawn(async move {
+        vote_totals::start_vote_totals_job(vote_totals_pool, vote_totals_interval).await;
+    });
+
+    // Start visibility promoter (feeds list content only at --confirmation-depth)
+    let visibility_pool = database.pool().clone();
+    let visibility_delay_ms = config
+        .chain
+        .confirmation_delay_ms(config.confirmation_depth);
+    tokio::spawn(async move {
+        visibility::start_visibility_promoter(visibility_pool, visibility_delay_ms).await;
+    });
+
+    // Jobs keeping the derived data of each tenant schema up to date, as above
+    for tenant in &tenants {
+        tokio::spawn(impersonation_detector::start_impersonation_detector(
+            tenant.pool().clone(),
+            config.impersonation.clone(),
+        ));
+        tokio::spawn(vote_totals::start_vote_totals_job(
+            tenant.pool().clone(),
+            config.vote_totals_interval_secs,
+        ));
+        tokio::spawn(visibility::start_visibility_promoter(
+            tenant.pool().clone(),
+            visibility_delay_ms,
+        ));
+    }
+
+    // Start profile image collector (deletes blobs no broadcast references anymore)
+    if let Some(blob_storage) = blob_storage {
+        let image_gc_pool = database.pool().clone();
+        let image_gc_interval = config.image_gc_interval_secs;
+        tokio::spawn(async move {
+            image_blobs::start_image_collector(image_gc_pool, blob_storage, image_gc_interval)
+                .await;
+        });
+    }
+
+    // Start transaction reindex service
+    let reindex_pool = database.pool().clone();
+    let reindex_config = config.clone();
+    let reindex_handle = tokio::spawn(async move {
+        transaction_reindex_service::start_reindex_service(reindex_config, reindex_pool).await;
+    });
+
+    let listener_handle = tokio::spawn(async move {
+        if let Err(e) = notification_listener.start().await {
+            error!("Notification listener failed: {}", e);
+        }
+    });
+
+    let queue_handle = tokio::spawn(async move {
+        notification_queue.start().await;
+    });
+
+    let worker_handle = tokio::spawn(async move {
+        worker_pool.start().await;
+    });
+
+    info!("Transaction Processor started successfully");
+    info!(
+        "Listening for notifications on channel: {}",
+        config.processing.channel_name
+    );
+
+    tokio::select! {
+        _ = reindex_handle => {
+            error!("Transaction reindex service stopped unexpectedly");
+        }
+        _ = listener_handle => {
+            error!("Notification listener stopped unexpectedly");
+        }
+        _ = queue_handle => {
+            error!("Notification queue stopped unexpectedly");
+        }
+        _ = worker_handle => {
+            error!("Worker pool stopped unexpectedly");
+        }
+        _ = tokio::signal::ctrl_c() => {
+            info!("Received shutdown signal");
+        }
+    }
+
+    info!("Transaction Processor shutting down");
+    Ok(())
+}
@@ -1,25 +1,39 @@
 use crate::config::AppConfig;
+use crate::database::{
+    DbPool, PROCESSED_WATERMARK_MARGIN_MS, fetch_k_transaction_page, get_processed_watermark,
+};
 use anyhow::Result;
+use metrics::counter;
 use sqlx::{Error as SqlxError, postgres::PgListener};
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
+// Transaction ids read from the transactions table per query while reconciling a gap
+const RECONCILE_PAGE_SIZE: i64 = 1000;
+
 pub struct NotificationListener {
     config: AppConfig,
+    pool: DbPool,
     notification_sender: mpsc::UnboundedSender<String>,
 }
 
 impl NotificationListener {
-    pub fn new(config: AppConfig, notification_sender: mpsc::UnboundedSender<String>) -> Self {
+    pub fn new(
+        config: AppConfig,
+        pool: DbPool,
+        notification_sender: mpsc::UnboundedSender<String>,
+    ) -> Self {
         Self {
             config,
+            pool,
             notification_sender,
         }
     }
 
     pub async fn start(&self) -> Result<()> {
+        let mut reconnecting = false;
         loop {
-            match self.connect_and_listen().await {
+            match self.connect_and_listen(reconnecting).await {
                 Ok(_) => {
                     info!("Notification listener stopped gracefully");
                     break;
@@ -30,6 +44,7 @@ impl NotificationListener {
                         "Reconnecting in {} ms",
                         self.config.processing.retry_delay_ms
                     );
+                    reconnecting = true;
                     tokio::time::sleep(tokio::time::Duration::from_millis(
                         self.config.processing.retry_delay_ms,
                     ))
@@ -40,12 +55,13 @@ impl NotificationListener {
         Ok(())
     }
 
-    async fn connect_and_listen(&self) -> Result<(), SqlxError> {
-        // Create connection string
-        let connection_string = self.config.connection_string();
-
+    /// Listen on the notification channel through a connection of the pool, which follows
+    /// the pool to the new host after a failover (see failover.rs). Notifications sent while
+    /// reconnecting are lost, so a `reconnecting` listener queues the K transactions stored
+    /// meanwhile once listening again
+    async fn connect_and_listen(&self, reconnecting: bool) -> Result<(), SqlxError> {
         // Create a PostgreSQL listener
-        let mut listener = PgListener::connect(&connection_string).await?;
+        let mut listener = PgListener::connect_with(&self.pool).await?;
 
         info!("Connected to database for notifications");
 
@@ -60,13 +76,25 @@ impl NotificationListener {
 
         let notification_sender = self.notification_sender.clone();
 
+        if reconnecting {
+            counter!("k_listener_reconnects_total").increment(1);
+            match self.reconcile_gap().await {
+                Ok(queued) => info!(
+                    "Queued {} K transactions possibly missed while reconnecting",
+                    queued
+                ),
+                Err(e) => error!("Failed to reconcile missed notifications: {}", e),
+            }
+        }
+
         info!("Notification listener is now active and waiting for database triggers");
 
         // Process notifications
         loop {
-            // Wait for a notification
-            match listener.recv().await {
-                Ok(notification) => {
+            // Wait for a notification, None once the connection is lost (PgListener would
+            // reconnect on the next call, without reconciling the notifications missed)
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
                     //info!("Listener received notification on channel '{}' with payload: '{}'", notification.channel(), notification.payload());
 
                     // Send the transaction ID to the processing queue
@@ -76,6 +104,12 @@ impl NotificationListener {
                         break;
                     }
                 }
+                Ok(None) => {
+                    return Err(SqlxError::Io(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "notification connection lost",
+                    )));
+                }
                 Err(e) => {
                     error!("Error receiving notification: {}", e);
                     return Err(e);
@@ -85,4 +119,44 @@ impl NotificationListener {
 
         Ok(())
     }
+
+    /// Queue the K transactions stored since shortly before the processed watermark, as
+    /// backfill does; K inserts are idempotent, so those already indexed are harmless
+    async fn reconcile_gap(&self) -> Result<u64> {
+        let Some(watermark) = get_processed_watermark(&self.pool).await? else {
+            return Ok(0);
+        };
+        let from_block_time = watermark.saturating_sub(PROCESSED_WATERMARK_MARGIN_MS);
+        let mut last: Option<(i64, Vec<u8>)> = None;
+        let mut queued: u64 = 0;
+
+        loop {
+            let page = fetch_k_transaction_page(
+                &self.pool,
+                &self.config.chain,
+                from_block_time,
+                i64::MAX,
+                last.as_ref(),
+                RECONCILE_PAGE_SIZE,
+            )
+            .await?;
+
+            let Some(page_last) = page.last().cloned() else {
+                break;
+            };
+            for (_, transaction_id) in &page {
+                self.notification_sender
+                    .send(hex::encode(transaction_id))
+                    .map_err(|_| anyhow::anyhow!("Notification queue stopped"))?;
+            }
+            queued += page.len() as u64;
+            counter!("k_listener_reconciled_total").increment(page.len() as u64);
+
+            if (page.len() as i64) < RECONCILE_PAGE_SIZE {
+                break;
+            }
+            last = Some(page_last);
+        }
+        Ok(queued)
+    }
 }
@@ -0,0 +1,44 @@
+use crate::database::{DbPool, is_maintenance_mode};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Poll the k_meta maintenance flag and publish it to the workers
+/// Workers stop consuming notifications while the published value is true
+pub fn spawn_maintenance_watcher(
+    pool: DbPool,
+    check_interval_secs: u64,
+    initial: bool,
+) -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(initial);
+    let interval = Duration::from_secs(check_interval_secs.max(1));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            match is_maintenance_mode(&pool).await {
+                Ok(enabled) => {
+                    let changed = sender.send_if_modified(|current| {
+                        let changed = *current != enabled;
+                        *current = enabled;
+                        changed
+                    });
+                    if changed {
+                        if enabled {
+                            info!("Maintenance mode enabled - pausing transaction processing");
+                        } else {
+                            info!("Maintenance mode disabled - resuming transaction processing");
+                        }
+                    }
+                }
+                Err(e) => {
+                    // Keep the last known state, a DB outage must not resume a paused processor
+                    warn!("Failed to read maintenance mode flag: {}", e);
+                }
+            }
+        }
+    });
+
+    receiver
+}
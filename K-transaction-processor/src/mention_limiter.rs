@@ -0,0 +1,115 @@
+use crate::config::{ExcessMentionsAction, MentionLimitConfig};
+use std::collections::HashSet;
+
+/// Result of applying the mention limit to a post or reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionLimitOutcome {
+    /// Mentions to index in k_mentions
+    pub mentioned_pubkeys: Vec<String>,
+    /// Number of unique mentions above the configured limit
+    pub excess: usize,
+    /// True when the content must be flagged as spam (k_contents.is_spam)
+    pub flag_as_spam: bool,
+}
+
+/// Deduplicate mentions and cap them to the configured maximum
+/// - ignore: index the first `max_mentions` mentions, drop the rest
+/// - spam: index no mentions at all (no notification flood) and flag the content as spam
+pub fn apply_mention_limit(
+    mentioned_pubkeys: Vec<String>,
+    config: &MentionLimitConfig,
+) -> MentionLimitOutcome {
+    let mut seen = HashSet::new();
+    let mut unique_pubkeys: Vec<String> = mentioned_pubkeys
+        .into_iter()
+        .filter(|pubkey| seen.insert(pubkey.clone()))
+        .collect();
+
+    if unique_pubkeys.len() <= config.max_mentions {
+        return MentionLimitOutcome {
+            mentioned_pubkeys: unique_pubkeys,
+            excess: 0,
+            flag_as_spam: false,
+        };
+    }
+
+    let excess = unique_pubkeys.len() - config.max_mentions;
+
+    match config.excess_action {
+        ExcessMentionsAction::Ignore => {
+            unique_pubkeys.truncate(config.max_mentions);
+            MentionLimitOutcome {
+                mentioned_pubkeys: unique_pubkeys,
+                excess,
+                flag_as_spam: false,
+            }
+        }
+        ExcessMentionsAction::Spam => MentionLimitOutcome {
+            mentioned_pubkeys: vec![],
+            excess,
+            flag_as_spam: true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pubkeys(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("02{:064x}", i)).collect()
+    }
+
+    #[test]
+    fn test_under_limit_is_unchanged() {
+        let config = MentionLimitConfig {
+            max_mentions: 3,
+            excess_action: ExcessMentionsAction::Spam,
+            suppress_blocked: false,
+        };
+        let outcome = apply_mention_limit(pubkeys(3), &config);
+        assert_eq!(outcome.mentioned_pubkeys, pubkeys(3));
+        assert_eq!(outcome.excess, 0);
+        assert!(!outcome.flag_as_spam);
+    }
+
+    #[test]
+    fn test_duplicates_do_not_count() {
+        let config = MentionLimitConfig {
+            max_mentions: 2,
+            excess_action: ExcessMentionsAction::Spam,
+            suppress_blocked: false,
+        };
+        let mut mentions = pubkeys(2);
+        mentions.extend(pubkeys(2));
+        let outcome = apply_mention_limit(mentions, &config);
+        assert_eq!(outcome.mentioned_pubkeys, pubkeys(2));
+        assert!(!outcome.flag_as_spam);
+    }
+
+    #[test]
+    fn test_ignore_truncates() {
+        let config = MentionLimitConfig {
+            max_mentions: 2,
+            excess_action: ExcessMentionsAction::Ignore,
+            suppress_blocked: false,
+        };
+        let outcome = apply_mention_limit(pubkeys(5), &config);
+        assert_eq!(outcome.mentioned_pubkeys, pubkeys(2));
+        assert_eq!(outcome.excess, 3);
+        assert!(!outcome.flag_as_spam);
+    }
+
+    #[test]
+    fn test_spam_drops_all_mentions() {
+        let config = MentionLimitConfig {
+            max_mentions: 2,
+            excess_action: ExcessMentionsAction::Spam,
+            suppress_blocked: false,
+        };
+        let outcome = apply_mention_limit(pubkeys(5), &config);
+        assert!(outcome.mentioned_pubkeys.is_empty());
+        assert_eq!(outcome.excess, 3);
+        assert!(outcome.flag_as_spam);
+    }
+}
@@ -6,6 +6,34 @@ DROP TRIGGER IF EXISTS transaction_notify_trigger ON transactions;
 DROP FUNCTION IF EXISTS notify_transaction();
 
 -- Drop K protocol tables (reverse dependency order)
+DROP TABLE IF EXISTS k_write_intents CASCADE;
+DROP TABLE IF EXISTS k_featured CASCADE;
+DROP TABLE IF EXISTS k_render_spans CASCADE;
+DROP TABLE IF EXISTS k_profile_image_blobs CASCADE;
+DROP VIEW IF EXISTS k_contents_tiered;
+DROP TABLE IF EXISTS k_contents_cold CASCADE;
+DROP TABLE IF EXISTS k_vote_totals CASCADE;
+DROP TABLE IF EXISTS k_impersonation_flags CASCADE;
+DROP TABLE IF EXISTS k_profile_history CASCADE;
+DROP TABLE IF EXISTS k_mentions_archive CASCADE;
+DROP TABLE IF EXISTS k_contents_archive CASCADE;
+DROP TABLE IF EXISTS k_storage_snapshots CASCADE;
+DROP TABLE IF EXISTS k_content_hashes CASCADE;
+DROP TABLE IF EXISTS k_pubkey_tombstones CASCADE;
+DROP TABLE IF EXISTS k_tombstones CASCADE;
+DROP TABLE IF EXISTS k_erasure_requests CASCADE;
+DROP TABLE IF EXISTS k_feature_flags CASCADE;
+DROP TABLE IF EXISTS k_post_views CASCADE;
+DROP TABLE IF EXISTS k_tips CASCADE;
+DROP TABLE IF EXISTS k_article_chunks CASCADE;
+DROP TABLE IF EXISTS k_articles CASCADE;
+DROP TABLE IF EXISTS k_event_rsvps CASCADE;
+DROP TABLE IF EXISTS k_events CASCADE;
+DROP TABLE IF EXISTS k_group_posts CASCADE;
+DROP TABLE IF EXISTS k_group_members CASCADE;
+DROP TABLE IF EXISTS k_groups CASCADE;
+DROP TABLE IF EXISTS k_dead_letters CASCADE;
+DROP TABLE IF EXISTS k_meta CASCADE;
 DROP TABLE IF EXISTS k_hashtags CASCADE;
 DROP TABLE IF EXISTS k_contents CASCADE;
 DROP TABLE IF EXISTS k_follows CASCADE;
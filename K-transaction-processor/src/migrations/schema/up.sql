@@ -10,8 +10,8 @@ CREATE TABLE IF NOT EXISTS k_vars (
     value TEXT NOT NULL
 );
 
--- Insert initial schema version (v2 = complete K protocol schema with hashtags)
-INSERT INTO k_vars (key, value) VALUES ('schema_version', '2') ON CONFLICT (key) DO NOTHING;
+-- Insert initial schema version (v34 = complete K protocol schema with hashtags, k_meta, image blobs, dead letters, spam and rate-limit flags, maintenance mode, groups, events, articles, tips, post views, feature flags, erasure, tombstone registry, content hashes, storage snapshots, archive tables, profile history, impersonation flags, vote totals, enriched mentions, cold tier, profile image dedup, render spans, fee-weighted votes, visibility watermark, nickname history, featured contents, full-text search, write intents, thread roots, complete archive rows)
+INSERT INTO k_vars (key, value) VALUES ('schema_version', '34') ON CONFLICT (key) DO NOTHING;
 
 -- NOTE: k_posts and k_replies tables removed in v6 (replaced by k_contents table in v4)
 -- Create K protocol tables
@@ -24,7 +24,10 @@ CREATE TABLE IF NOT EXISTS k_broadcasts (
     sender_signature BYTEA NOT NULL,
     base64_encoded_nickname TEXT NOT NULL DEFAULT '',
     base64_encoded_profile_image TEXT,
-    base64_encoded_message TEXT NOT NULL
+    base64_encoded_message TEXT NOT NULL,
+    -- v4: blob storage reference + SHA-256 hash (set when profile images are stored outside the DB)
+    profile_image_ref TEXT,
+    profile_image_hash BYTEA
 );
 
 CREATE TABLE IF NOT EXISTS k_votes (
@@ -34,7 +37,12 @@ CREATE TABLE IF NOT EXISTS k_votes (
     sender_pubkey BYTEA NOT NULL,
     sender_signature BYTEA NOT NULL,
     post_id BYTEA NOT NULL,
-    vote VARCHAR(10) NOT NULL CHECK (vote IN ('upvote', 'downvote'))
+    vote VARCHAR(10) NOT NULL CHECK (vote IN ('upvote', 'downvote')),
+    -- Sender exceeded --max-votes-per-minute (--rate-limit-action mark)
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE,
+    -- NEW in v27: fee (sompi) burned by the vote transaction, captured with --vote-fee-weighting
+    -- (NULL when not captured or when the inputs were not resolved by the Kaspa indexer)
+    fee BIGINT
 );
 
 CREATE TABLE IF NOT EXISTS k_mentions (
@@ -42,8 +50,15 @@ CREATE TABLE IF NOT EXISTS k_mentions (
     content_id BYTEA NOT NULL,
     content_type VARCHAR(10) NOT NULL,
     mentioned_pubkey BYTEA NOT NULL,
+    -- block_time and sender_pubkey of the mentioning content
     block_time BIGINT NOT NULL,
-    sender_pubkey BYTEA
+    sender_pubkey BYTEA,
+    -- v23: notification data denormalized at index time, so notifications are read without joins.
+    -- parent_id is the replied, quoted or voted content, content_snippet the first 280 characters
+    -- of the message (base64, the vote value for votes) and parent_snippet the quoted or voted message
+    parent_id BYTEA,
+    content_snippet TEXT,
+    parent_snippet TEXT
 );
 
 -- Create indexes for K protocol tables
@@ -56,6 +71,7 @@ CREATE INDEX IF NOT EXISTS idx_k_votes_post_id ON k_votes(post_id);
 CREATE INDEX IF NOT EXISTS idx_k_votes_vote ON k_votes(vote);
 CREATE INDEX IF NOT EXISTS idx_k_votes_block_time ON k_votes(block_time);
 CREATE INDEX IF NOT EXISTS idx_k_votes_post_id_sender ON k_votes(post_id, sender_pubkey);
+CREATE INDEX IF NOT EXISTS idx_k_votes_sender_block_time ON k_votes(sender_pubkey, block_time DESC);
 
 -- Create k_blocks table for blocking/unblocking users
 CREATE TABLE IF NOT EXISTS k_blocks (
@@ -135,7 +151,16 @@ CREATE TABLE IF NOT EXISTS k_contents (
     -- Content type discriminator: 'post', 'reply', 'repost', 'quote'
     content_type VARCHAR(10) NOT NULL CHECK (content_type IN ('post', 'reply', 'repost', 'quote')),
     -- Optional reference to parent content (NULL for posts, NOT NULL for replies/reposts/quotes)
-    referenced_content_id BYTEA
+    referenced_content_id BYTEA,
+    -- Flagged when the content exceeds the mention limit (--excess-mentions spam)
+    is_spam BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Sender exceeded --max-posts-per-minute (--rate-limit-action mark)
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE,
+    -- NEW in v31: decoded message as a 'simple' text search vector (NULL if undecodable)
+    search_vector TSVECTOR,
+    -- NEW in v33: post or quote the reply chain of a reply answers (NULL for posts and quotes,
+    -- and for replies whose chain reaches a content not indexed yet)
+    root_post_id BYTEA
 );
 
 -- Primary indexes for k_contents
@@ -148,6 +173,12 @@ CREATE INDEX IF NOT EXISTS idx_k_contents_block_time ON k_contents(block_time DE
 CREATE INDEX IF NOT EXISTS idx_k_contents_replies ON k_contents(referenced_content_id, block_time DESC)
     WHERE content_type = 'reply';
 
+-- NEW in v33: whole conversations, the replies of every depth under a post or quote
+CREATE INDEX IF NOT EXISTS idx_k_contents_thread ON k_contents(root_post_id, block_time DESC, id DESC) WHERE content_type = 'reply';
+
+-- NEW in v31: full-text search over posts and replies (K-webserver /search-posts)
+CREATE INDEX IF NOT EXISTS idx_k_contents_search_vector ON k_contents USING GIN (search_vector) WHERE content_type IN ('post', 'reply');
+
 -- Partial index for reposts: optimized for "get reposts of content X"
 CREATE INDEX IF NOT EXISTS idx_k_contents_reposts ON k_contents(referenced_content_id, block_time DESC)
     WHERE content_type = 'repost';
@@ -205,3 +236,506 @@ ADD CONSTRAINT fk_k_hashtags_content
 FOREIGN KEY (content_id)
 REFERENCES k_contents(transaction_id)
 ON DELETE CASCADE;
+
+-- ============================================================================
+-- NEW in v3: k_meta table for schema version / feature handshake
+-- ============================================================================
+
+-- Single-row table written by K-transaction-processor after every schema check.
+-- K-webserver reads it at startup and periodically to enable/disable endpoints
+-- based on what the active schema supports.
+CREATE TABLE IF NOT EXISTS k_meta (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    schema_version INTEGER NOT NULL,
+    features TEXT[] NOT NULL DEFAULT '{}',
+    processor_version VARCHAR(32) NOT NULL,
+    updated_at BIGINT NOT NULL,
+    -- NEW in v8: read-only maintenance mode (processor paused, webserver serves reads only)
+    maintenance_mode BOOLEAN NOT NULL DEFAULT FALSE,
+    maintenance_retry_after INTEGER NOT NULL DEFAULT 300,
+    maintenance_reason TEXT,
+    -- NEW in v24: content with block_time below this boundary (ms) may be in k_contents_cold
+    cold_tier_before BIGINT,
+    -- NEW in v28: content with block_time above this watermark (ms) is not listed in feeds yet
+    -- (--confirmation-depth, NULL = everything visible)
+    visible_before BIGINT
+);
+
+-- ============================================================================
+-- NEW in v5: k_dead_letters table for permanently failed transactions
+-- ============================================================================
+
+-- K transactions that failed with a permanent error (or exhausted their retries)
+CREATE TABLE IF NOT EXISTS k_dead_letters (
+    transaction_id BYTEA PRIMARY KEY,
+    error_class VARCHAR(16) NOT NULL,
+    error_message TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    failed_at BIGINT NOT NULL
+);
+
+-- ============================================================================
+-- NEW in v9: k_groups, k_group_members and k_group_posts tables for group posting
+-- ============================================================================
+
+-- Groups are identified by the transaction id of their creation transaction.
+-- The creator (sender_pubkey) owns the group and is its first member.
+CREATE TABLE IF NOT EXISTS k_groups (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_name TEXT NOT NULL,
+    base64_encoded_description TEXT NOT NULL DEFAULT '',
+    -- 'open': anyone can join, 'closed': only the owner can add members
+    membership_policy VARCHAR(10) NOT NULL CHECK (membership_policy IN ('open', 'closed'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_groups_sender_signature_unique ON k_groups(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_groups_sender_pubkey ON k_groups(sender_pubkey, block_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_groups_block_time ON k_groups(block_time DESC, id DESC);
+
+-- Current members of each group (one row per member, removed on leave/remove).
+-- transaction_id/block_time refer to the membership transaction that added the member.
+CREATE TABLE IF NOT EXISTS k_group_members (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BYTEA NOT NULL REFERENCES k_groups(transaction_id) ON DELETE CASCADE,
+    member_pubkey BYTEA NOT NULL,
+    transaction_id BYTEA NOT NULL,
+    block_time BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_group_members_group_member_unique ON k_group_members(group_id, member_pubkey);
+CREATE INDEX IF NOT EXISTS idx_k_group_members_member_pubkey ON k_group_members(member_pubkey, block_time DESC);
+
+-- Group-scoped posts, kept out of k_contents so they never appear in public feeds
+CREATE TABLE IF NOT EXISTS k_group_posts (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    group_id BYTEA NOT NULL REFERENCES k_groups(transaction_id) ON DELETE CASCADE,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_group_posts_sender_signature_unique ON k_group_posts(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_group_posts_group_id ON k_group_posts(group_id, block_time DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_k_group_posts_sender_pubkey ON k_group_posts(sender_pubkey, block_time DESC);
+
+-- ============================================================================
+-- NEW in v10: k_events and k_event_rsvps tables for the event calendar
+-- ============================================================================
+
+-- Events announced on chain, start_time/end_time are unix timestamps in milliseconds
+-- (same unit as block_time) chosen by the organizer (sender_pubkey).
+CREATE TABLE IF NOT EXISTS k_events (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_title TEXT NOT NULL,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT NOT NULL,
+    base64_encoded_location TEXT NOT NULL DEFAULT '',
+    CHECK (end_time >= start_time)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_events_sender_signature_unique ON k_events(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_events_sender_pubkey ON k_events(sender_pubkey, start_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_events_start_time ON k_events(start_time, id);
+CREATE INDEX IF NOT EXISTS idx_k_events_end_time ON k_events(end_time);
+
+-- Latest RSVP of each user per event (a newer RSVP replaces the previous one)
+CREATE TABLE IF NOT EXISTS k_event_rsvps (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    event_id BYTEA NOT NULL REFERENCES k_events(transaction_id) ON DELETE CASCADE,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    rsvp_status VARCHAR(10) NOT NULL CHECK (rsvp_status IN ('going', 'interested', 'not_going'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_event_rsvps_event_sender_unique ON k_event_rsvps(event_id, sender_pubkey);
+CREATE INDEX IF NOT EXISTS idx_k_event_rsvps_sender_pubkey ON k_event_rsvps(sender_pubkey, block_time DESC);
+
+-- ============================================================================
+-- NEW in v11: k_articles and k_article_chunks tables for long-form articles
+-- ============================================================================
+
+-- Article manifests: the manifest transaction id identifies the article.
+-- base64_encoded_content is filled once all chunk_count chunks arrived and their
+-- concatenation matches content_hash (SHA-256 of the base64 encoded article).
+CREATE TABLE IF NOT EXISTS k_articles (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_title TEXT NOT NULL,
+    chunk_count INTEGER NOT NULL CHECK (chunk_count > 0),
+    content_hash BYTEA NOT NULL,
+    base64_encoded_content TEXT,
+    -- 'incomplete': waiting for chunks, 'complete': reassembled, 'invalid': hash mismatch
+    status VARCHAR(10) NOT NULL DEFAULT 'incomplete' CHECK (status IN ('incomplete', 'complete', 'invalid')),
+    -- Block time of the transaction that completed the article
+    completed_at BIGINT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_articles_sender_signature_unique ON k_articles(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_articles_sender_pubkey ON k_articles(sender_pubkey, block_time DESC);
+
+-- Chunks waiting for reassembly (they can arrive before their manifest, so no foreign key).
+-- Deleted once the article is complete.
+CREATE TABLE IF NOT EXISTS k_article_chunks (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    manifest_id BYTEA NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    chunk_index INTEGER NOT NULL CHECK (chunk_index >= 0),
+    base64_encoded_data TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_article_chunks_manifest_chunk_unique ON k_article_chunks(manifest_id, sender_pubkey, chunk_index);
+
+-- ============================================================================
+-- NEW in v12: k_tips table for KAS tips sent to content authors
+-- ============================================================================
+
+-- Tips: KAS paid to the author of a post/reply/quote by the transaction carrying the tip payload
+-- amount is the sum (in sompi) of the transaction outputs paying the author
+CREATE TABLE IF NOT EXISTS k_tips (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    content_id BYTEA NOT NULL,
+    recipient_pubkey BYTEA NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_tips_sender_signature_unique ON k_tips(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_tips_content_id ON k_tips(content_id);
+CREATE INDEX IF NOT EXISTS idx_k_tips_block_time ON k_tips(block_time);
+
+-- ============================================================================
+-- NEW in v13: k_post_views table for approximate per-post view counts
+-- ============================================================================
+
+-- Approximate distinct viewers per post/reply/quote, written by K-webserver (/track-view)
+-- registers holds a HyperLogLog sketch: 1024 one-byte registers fed with hashed viewer IPs
+CREATE TABLE IF NOT EXISTS k_post_views (
+    content_id BYTEA PRIMARY KEY,
+    registers BYTEA NOT NULL CHECK (length(registers) = 1024),
+    updated_at BIGINT NOT NULL
+);
+
+-- ============================================================================
+-- NEW in v14: k_feature_flags table for experimental K-webserver behavior
+-- ============================================================================
+
+-- Set by an operator with plain SQL, read by K-webserver on every schema refresh.
+-- A flag is on for rollout_percent % of requesters (or requests without a requester),
+-- flags passed with K-webserver --feature-flags take precedence over these rows.
+CREATE TABLE IF NOT EXISTS k_feature_flags (
+    name VARCHAR(64) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    rollout_percent SMALLINT NOT NULL DEFAULT 100 CHECK (rollout_percent BETWEEN 0 AND 100),
+    description TEXT
+);
+
+-- ============================================================================
+-- NEW in v15: k_erasure_requests and k_tombstones tables for right-to-be-forgotten erasures
+-- ============================================================================
+
+-- Erasure requests recorded by K-webserver (POST /admin/erasure-requests) and carried out
+-- by K-content-remover --erasure-request <id>, which marks them completed
+CREATE TABLE IF NOT EXISTS k_erasure_requests (
+    id BIGSERIAL PRIMARY KEY,
+    subject_pubkey BYTEA NOT NULL,
+    reason TEXT,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'completed')),
+    requested_at BIGINT NOT NULL,
+    completed_at BIGINT,
+    removed_rows BIGINT,
+    tombstoned_transactions BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_erasure_requests_subject_pubkey ON k_erasure_requests(subject_pubkey);
+
+-- Transactions erased by an erasure request: K-transaction-processor skips them,
+-- so backfill and reindex never restore erased content
+CREATE TABLE IF NOT EXISTS k_tombstones (
+    transaction_id BYTEA PRIMARY KEY,
+    -- NULL (v16+) for operator takedowns and tombstones imported from an upstream instance
+    erasure_request_id BIGINT REFERENCES k_erasure_requests(id),
+    erased_at BIGINT NOT NULL,
+    -- NEW in v16: 'local' for tombstones created here, otherwise the upstream they were imported from
+    source VARCHAR(255) NOT NULL DEFAULT 'local',
+    reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_tombstones_erasure_request_id ON k_tombstones(erasure_request_id);
+
+-- ============================================================================
+-- NEW in v16: k_pubkey_tombstones table for senders taken down
+-- ============================================================================
+
+-- Senders taken down by an operator or imported from an upstream instance
+-- (K-transaction-processor tombstones add/import): every transaction they send is skipped
+CREATE TABLE IF NOT EXISTS k_pubkey_tombstones (
+    sender_pubkey BYTEA PRIMARY KEY,
+    source VARCHAR(255) NOT NULL DEFAULT 'local',
+    reason TEXT,
+    tombstoned_at BIGINT NOT NULL
+);
+
+-- ============================================================================
+-- NEW in v17: k_content_hashes table for duplicate message detection across users
+-- ============================================================================
+
+-- SHA-256 of the normalized message text (lowercase, whitespace collapsed, zero-width
+-- characters removed) of every post, reply and quote. The same hash sent by many
+-- different pubkeys is a spam campaign signal (K-webserver /admin/duplicate-contents).
+CREATE TABLE IF NOT EXISTS k_content_hashes (
+    content_id BYTEA PRIMARY KEY REFERENCES k_contents(transaction_id) ON DELETE CASCADE,
+    message_hash BYTEA NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    block_time BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_content_hashes_block_time ON k_content_hashes(block_time);
+
+-- ============================================================================
+-- NEW in v18: k_storage_snapshots table for per-table storage growth reporting
+-- ============================================================================
+
+-- Row counts (estimated, pg_stat_user_tables) and on-disk sizes (pg_total_relation_size) of
+-- every K table, written by K-transaction-processor on every storage report. K-webserver
+-- /admin/storage-stats compares the current usage with the latest snapshot a week old.
+CREATE TABLE IF NOT EXISTS k_storage_snapshots (
+    table_name VARCHAR(64) NOT NULL,
+    taken_at BIGINT NOT NULL,
+    row_count BIGINT NOT NULL,
+    total_bytes BIGINT NOT NULL,
+    PRIMARY KEY (table_name, taken_at)
+);
+
+-- ============================================================================
+-- NEW in v19: k_contents_archive and k_mentions_archive tables for archiving retention rules
+-- ============================================================================
+
+-- Rows moved out of k_contents / k_mentions by K-database-cleaner with --retention-action archive
+-- instead of being deleted. Same columns as the live tables plus the time they were archived,
+-- ids are copied from the live rows. K-webserver only reads them for admin requests with
+-- includeArchived=true.
+CREATE TABLE IF NOT EXISTS k_contents_archive (
+    id BIGINT PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    referenced_content_id BYTEA,
+    is_spam BOOLEAN NOT NULL DEFAULT FALSE,
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE,
+    archived_at BIGINT NOT NULL,
+    -- NEW in v34: search vector, thread root and rendering hints (k_render_spans.spans) of the
+    -- archived content (NULL for rows archived before v34)
+    search_vector TSVECTOR,
+    root_post_id BYTEA,
+    render_spans JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_archive_sender_pubkey ON k_contents_archive(sender_pubkey, block_time DESC);
+
+CREATE TABLE IF NOT EXISTS k_mentions_archive (
+    id BIGINT PRIMARY KEY,
+    content_id BYTEA NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    mentioned_pubkey BYTEA NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA,
+    archived_at BIGINT NOT NULL,
+    -- NEW in v34: enriched mention columns of the archived mention
+    parent_id BYTEA,
+    content_snippet TEXT,
+    parent_snippet TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_mentions_archive_content_id ON k_mentions_archive(content_id);
+
+-- ============================================================================
+-- NEW in v20: k_profile_history table keeping every profile broadcast of each user
+-- ============================================================================
+
+-- k_broadcasts only keeps the latest broadcast of each sender. Every broadcast is also
+-- recorded here (nickname, bio and the SHA-256 of the decoded profile image, as stored by
+-- blob storage) so K-webserver /get-profile-diff can compare a profile at two points in time.
+CREATE TABLE IF NOT EXISTS k_profile_history (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    base64_encoded_nickname TEXT NOT NULL DEFAULT '',
+    profile_image_hash BYTEA,
+    base64_encoded_message TEXT NOT NULL,
+    -- NEW in v29: decoded and normalized nickname (NULL if undecodable), for nickname history searches
+    normalized_nickname TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_profile_history_sender_pubkey ON k_profile_history(sender_pubkey, block_time DESC);
+-- NEW in v29: every user of a nickname, across all broadcast versions (K-webserver /admin/nickname-history)
+CREATE INDEX IF NOT EXISTS idx_k_profile_history_normalized_nickname ON k_profile_history(normalized_nickname, sender_pubkey) WHERE normalized_nickname IS NOT NULL;
+
+-- ============================================================================
+-- NEW in v21: k_impersonation_flags review table for nickname impersonation detection
+-- ============================================================================
+
+-- Written by the K-transaction-processor impersonation detector when a new profile broadcast
+-- uses a nickname similar to a user with many followers (normalized, homoglyph-aware).
+-- One row per suspect/target pair, reviewed by operators through K-webserver
+-- /admin/impersonation-flags (pending -> confirmed or dismissed).
+CREATE TABLE IF NOT EXISTS k_impersonation_flags (
+    id BIGSERIAL PRIMARY KEY,
+    suspect_pubkey BYTEA NOT NULL,
+    base64_encoded_suspect_nickname TEXT NOT NULL,
+    target_pubkey BYTEA NOT NULL,
+    base64_encoded_target_nickname TEXT NOT NULL,
+    target_followers BIGINT NOT NULL,
+    similarity DOUBLE PRECISION NOT NULL,
+    -- Broadcast that triggered the flag
+    transaction_id BYTEA NOT NULL,
+    flagged_at BIGINT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'confirmed', 'dismissed')),
+    reviewed_at BIGINT,
+    UNIQUE (suspect_pubkey, target_pubkey)
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_impersonation_flags_status ON k_impersonation_flags(status, flagged_at DESC);
+
+-- ============================================================================
+-- NEW in v22: k_vote_totals table with pre-computed vote counts per content
+-- ============================================================================
+
+-- Up/down vote counts per post/reply/quote, so K-webserver reads them with one primary key
+-- lookup instead of aggregating k_votes on every request. Incremented by K-transaction-processor
+-- together with each vote insert and periodically reconciled with k_votes (--vote-totals-interval),
+-- which corrects the totals of votes removed by K-database-cleaner and K-content-remover.
+CREATE TABLE IF NOT EXISTS k_vote_totals (
+    post_id BYTEA PRIMARY KEY,
+    up_votes BIGINT NOT NULL DEFAULT 0,
+    down_votes BIGINT NOT NULL DEFAULT 0,
+    -- NEW in v27: sum of the captured fees of the up/down votes (k_votes.fee)
+    up_votes_fee BIGINT NOT NULL DEFAULT 0,
+    down_votes_fee BIGINT NOT NULL DEFAULT 0
+);
+
+-- ============================================================================
+-- NEW in v24: k_contents_cold tier for old content and the k_contents_tiered view
+-- ============================================================================
+
+-- Content older than the cold tier boundary (k_meta.cold_tier_before), moved out of k_contents
+-- by K-database-cleaner (--cold-tier-after, optionally into a cheaper --cold-tablespace).
+-- Same columns as k_contents, ids are copied from the hot rows. K-webserver reads it through
+-- k_contents_tiered only when a page reaches that far back.
+CREATE TABLE IF NOT EXISTS k_contents_cold (
+    id BIGINT PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    referenced_content_id BYTEA,
+    is_spam BOOLEAN NOT NULL DEFAULT FALSE,
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE,
+    -- NEW in v31: full-text search vector, as in k_contents
+    search_vector TSVECTOR,
+    -- NEW in v33: thread root of replies, as in k_contents
+    root_post_id BYTEA
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_block_time ON k_contents_cold(block_time DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_sender_pubkey ON k_contents_cold(sender_pubkey, block_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_referenced ON k_contents_cold(referenced_content_id, content_type);
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_search_vector ON k_contents_cold USING GIN (search_vector) WHERE content_type IN ('post', 'reply');
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_thread ON k_contents_cold(root_post_id, block_time DESC, id DESC) WHERE content_type = 'reply';
+
+CREATE OR REPLACE VIEW k_contents_tiered AS
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector, root_post_id
+FROM k_contents
+UNION ALL
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector, root_post_id
+FROM k_contents_cold;
+
+-- ============================================================================
+-- NEW in v25: k_profile_image_blobs reference counts of profile images in blob storage
+-- ============================================================================
+
+-- One row per stored blob. Images are keyed by content hash (profile-images/sha256/<hex>),
+-- so every broadcast of the same image shares one blob. ref_count is the number of
+-- k_broadcasts rows pointing at the blob: taken when a broadcast is indexed, released when it
+-- is superseded or purged. Blobs without references since orphaned_at (ms) are deleted by
+-- K-transaction-processor after a grace period.
+CREATE TABLE IF NOT EXISTS k_profile_image_blobs (
+    blob_key TEXT PRIMARY KEY,
+    image_hash BYTEA NOT NULL,
+    ref_count BIGINT NOT NULL DEFAULT 0,
+    orphaned_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_profile_image_blobs_orphaned ON k_profile_image_blobs(orphaned_at) WHERE ref_count <= 0;
+CREATE INDEX IF NOT EXISTS idx_k_broadcasts_profile_image_ref ON k_broadcasts(profile_image_ref) WHERE profile_image_ref IS NOT NULL;
+
+-- ============================================================================
+-- NEW in v26: k_render_spans rendering hints (mentions, hashtags, URLs) of contents
+-- ============================================================================
+
+-- Positions of the mentions (@name), hashtags and URLs of the decoded message, parsed once at
+-- index time so clients don't re-implement the parsing. spans is a JSON array of
+-- {"type", "start", "end", "value"} objects ordered by start, with offsets in UTF-16 code
+-- units. Messages without any of them have no row.
+CREATE TABLE IF NOT EXISTS k_render_spans (
+    content_id BYTEA PRIMARY KEY REFERENCES k_contents(transaction_id) ON DELETE CASCADE,
+    spans JSONB NOT NULL
+);
+
+-- ============================================================================
+-- NEW in v30: k_featured operator-curated contents (K-webserver /admin/featured, /get-featured)
+-- ============================================================================
+
+-- Entries are listed by position, then most recently featured first. No foreign key: an entry
+-- whose content was removed or moved to the cold tier is skipped until the operator deletes it.
+CREATE TABLE IF NOT EXISTS k_featured (
+    content_id BYTEA PRIMARY KEY,
+    position INTEGER NOT NULL DEFAULT 0,
+    note TEXT,
+    featured_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_featured_position ON k_featured(position, featured_at DESC);
+
+-- ============================================================================
+-- NEW in v32: k_write_intents write-ahead intent log of the processor workers
+-- ============================================================================
+
+-- K transactions of the batches being written by the workers, recorded before each batch
+-- transaction begins and removed once the batch is committed (or the record dead-lettered).
+-- Rows left behind by a crash or a kill are rolled forward at the next startup: their
+-- transactions are queued again and reprocessed, K inserts being idempotent.
+-- Written in the network's own schema for the batches of every tenant.
+CREATE TABLE IF NOT EXISTS k_write_intents (
+    transaction_id BYTEA PRIMARY KEY,
+    worker_id INTEGER NOT NULL,
+    recorded_at BIGINT NOT NULL
+);
@@ -0,0 +1,43 @@
+-- Migration: v10_to_v11
+-- Description: Add k_articles and k_article_chunks tables for long-form articles
+-- Date: 2026-10-17
+
+-- Article manifests: the manifest transaction id identifies the article.
+-- base64_encoded_content is filled once all chunk_count chunks arrived and their
+-- concatenation matches content_hash (SHA-256 of the base64 encoded article).
+CREATE TABLE IF NOT EXISTS k_articles (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_title TEXT NOT NULL,
+    chunk_count INTEGER NOT NULL CHECK (chunk_count > 0),
+    content_hash BYTEA NOT NULL,
+    base64_encoded_content TEXT,
+    -- 'incomplete': waiting for chunks, 'complete': reassembled, 'invalid': hash mismatch
+    status VARCHAR(10) NOT NULL DEFAULT 'incomplete' CHECK (status IN ('incomplete', 'complete', 'invalid')),
+    -- Block time of the transaction that completed the article
+    completed_at BIGINT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_articles_sender_signature_unique ON k_articles(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_articles_sender_pubkey ON k_articles(sender_pubkey, block_time DESC);
+
+-- Chunks waiting for reassembly (they can arrive before their manifest, so no foreign key).
+-- Deleted once the article is complete.
+CREATE TABLE IF NOT EXISTS k_article_chunks (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    manifest_id BYTEA NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    chunk_index INTEGER NOT NULL CHECK (chunk_index >= 0),
+    base64_encoded_data TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_article_chunks_manifest_chunk_unique ON k_article_chunks(manifest_id, sender_pubkey, chunk_index);
+
+-- Update schema version
+UPDATE k_vars SET value = '11' WHERE key = 'schema_version';
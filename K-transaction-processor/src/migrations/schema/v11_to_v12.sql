@@ -0,0 +1,23 @@
+-- Migration: v11_to_v12
+-- Description: Add k_tips table for KAS tips sent to content authors
+-- Date: 2026-10-17
+
+-- Tips: KAS paid to the author of a post/reply/quote by the transaction carrying the tip payload
+-- amount is the sum (in sompi) of the transaction outputs paying the author
+CREATE TABLE IF NOT EXISTS k_tips (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    content_id BYTEA NOT NULL,
+    recipient_pubkey BYTEA NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_tips_sender_signature_unique ON k_tips(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_tips_content_id ON k_tips(content_id);
+CREATE INDEX IF NOT EXISTS idx_k_tips_block_time ON k_tips(block_time);
+
+-- Update schema version
+UPDATE k_vars SET value = '12' WHERE key = 'schema_version';
@@ -0,0 +1,14 @@
+-- Migration: v12_to_v13
+-- Description: Add k_post_views table for approximate per-post view counts
+-- Date: 2026-10-17
+
+-- Approximate distinct viewers per post/reply/quote, written by K-webserver (/track-view)
+-- registers holds a HyperLogLog sketch: 1024 one-byte registers fed with hashed viewer IPs
+CREATE TABLE IF NOT EXISTS k_post_views (
+    content_id BYTEA PRIMARY KEY,
+    registers BYTEA NOT NULL CHECK (length(registers) = 1024),
+    updated_at BIGINT NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '13' WHERE key = 'schema_version';
@@ -0,0 +1,16 @@
+-- Migration: v13_to_v14
+-- Description: Add k_feature_flags table for experimental K-webserver behavior
+-- Date: 2026-10-17
+
+-- Set by an operator with plain SQL, read by K-webserver on every schema refresh.
+-- A flag is on for rollout_percent % of requesters (or requests without a requester),
+-- flags passed with K-webserver --feature-flags take precedence over these rows.
+CREATE TABLE IF NOT EXISTS k_feature_flags (
+    name VARCHAR(64) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    rollout_percent SMALLINT NOT NULL DEFAULT 100 CHECK (rollout_percent BETWEEN 0 AND 100),
+    description TEXT
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '14' WHERE key = 'schema_version';
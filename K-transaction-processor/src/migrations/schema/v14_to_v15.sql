@@ -0,0 +1,31 @@
+-- Migration: v14_to_v15
+-- Description: Add k_erasure_requests and k_tombstones tables for right-to-be-forgotten erasures
+-- Date: 2026-10-17
+
+-- Erasure requests recorded by K-webserver (POST /admin/erasure-requests) and carried out
+-- by K-content-remover --erasure-request <id>, which marks them completed
+CREATE TABLE IF NOT EXISTS k_erasure_requests (
+    id BIGSERIAL PRIMARY KEY,
+    subject_pubkey BYTEA NOT NULL,
+    reason TEXT,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'completed')),
+    requested_at BIGINT NOT NULL,
+    completed_at BIGINT,
+    removed_rows BIGINT,
+    tombstoned_transactions BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_erasure_requests_subject_pubkey ON k_erasure_requests(subject_pubkey);
+
+-- Transactions erased by an erasure request: K-transaction-processor skips them,
+-- so backfill and reindex never restore erased content
+CREATE TABLE IF NOT EXISTS k_tombstones (
+    transaction_id BYTEA PRIMARY KEY,
+    erasure_request_id BIGINT NOT NULL REFERENCES k_erasure_requests(id),
+    erased_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_tombstones_erasure_request_id ON k_tombstones(erasure_request_id);
+
+-- Update schema version
+UPDATE k_vars SET value = '15' WHERE key = 'schema_version';
@@ -0,0 +1,22 @@
+-- Migration: v15_to_v16
+-- Description: Turn k_tombstones into a takedown registry and add k_pubkey_tombstones
+-- Date: 2026-10-17
+
+-- Tombstones no longer come only from erasure requests: operator takedowns and
+-- tombstones imported from an upstream instance have no erasure request
+ALTER TABLE k_tombstones ALTER COLUMN erasure_request_id DROP NOT NULL;
+
+-- 'local' for tombstones created on this instance, otherwise the upstream they were imported from
+ALTER TABLE k_tombstones ADD COLUMN IF NOT EXISTS source VARCHAR(255) NOT NULL DEFAULT 'local';
+ALTER TABLE k_tombstones ADD COLUMN IF NOT EXISTS reason TEXT;
+
+-- Senders taken down: K-transaction-processor skips every transaction they send
+CREATE TABLE IF NOT EXISTS k_pubkey_tombstones (
+    sender_pubkey BYTEA PRIMARY KEY,
+    source VARCHAR(255) NOT NULL DEFAULT 'local',
+    reason TEXT,
+    tombstoned_at BIGINT NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '16' WHERE key = 'schema_version';
@@ -0,0 +1,19 @@
+-- Migration: v16_to_v17
+-- Description: Add k_content_hashes table for duplicate message detection across users
+-- Date: 2026-10-17
+
+-- SHA-256 of the normalized message text (lowercase, whitespace collapsed, zero-width
+-- characters removed) of every post, reply and quote. The same hash sent by many
+-- different pubkeys is a spam campaign signal (K-webserver /admin/duplicate-contents).
+-- Contents indexed before v17 are hashed when they are backfilled.
+CREATE TABLE IF NOT EXISTS k_content_hashes (
+    content_id BYTEA PRIMARY KEY REFERENCES k_contents(transaction_id) ON DELETE CASCADE,
+    message_hash BYTEA NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    block_time BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_content_hashes_block_time ON k_content_hashes(block_time);
+
+-- Update schema version
+UPDATE k_vars SET value = '17' WHERE key = 'schema_version';
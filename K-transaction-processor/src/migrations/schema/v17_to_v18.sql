@@ -0,0 +1,18 @@
+-- Migration: v17_to_v18
+-- Description: Add k_storage_snapshots table for per-table storage growth reporting
+-- Date: 2026-10-17
+
+-- Row counts (estimated, pg_stat_user_tables) and on-disk sizes (pg_total_relation_size) of
+-- every K table, written by K-transaction-processor on every storage report. K-webserver
+-- /admin/storage-stats compares the current usage with the latest snapshot a week old.
+-- Snapshots older than 90 days are removed by the processor.
+CREATE TABLE IF NOT EXISTS k_storage_snapshots (
+    table_name VARCHAR(64) NOT NULL,
+    taken_at BIGINT NOT NULL,
+    row_count BIGINT NOT NULL,
+    total_bytes BIGINT NOT NULL,
+    PRIMARY KEY (table_name, taken_at)
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '18' WHERE key = 'schema_version';
@@ -0,0 +1,38 @@
+-- Migration: v18_to_v19
+-- Description: Add k_contents_archive and k_mentions_archive tables for archiving retention rules
+-- Date: 2026-10-17
+
+-- Rows moved out of k_contents / k_mentions by K-database-cleaner with --retention-action archive
+-- instead of being deleted. Same columns as the live tables plus the time they were archived,
+-- ids are copied from the live rows. K-webserver only reads them for admin requests with
+-- includeArchived=true.
+CREATE TABLE IF NOT EXISTS k_contents_archive (
+    id BIGINT PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    referenced_content_id BYTEA,
+    is_spam BOOLEAN NOT NULL DEFAULT FALSE,
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE,
+    archived_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_archive_sender_pubkey ON k_contents_archive(sender_pubkey, block_time DESC);
+
+CREATE TABLE IF NOT EXISTS k_mentions_archive (
+    id BIGINT PRIMARY KEY,
+    content_id BYTEA NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    mentioned_pubkey BYTEA NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA,
+    archived_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_mentions_archive_content_id ON k_mentions_archive(content_id);
+
+-- Update schema version
+UPDATE k_vars SET value = '19' WHERE key = 'schema_version';
@@ -0,0 +1,42 @@
+-- Migration: v19_to_v20
+-- Description: Add k_profile_history table keeping every profile broadcast of each user
+-- Date: 2026-10-17
+
+-- k_broadcasts only keeps the latest broadcast of each sender. Every broadcast is also
+-- recorded here (nickname, bio and the SHA-256 of the decoded profile image, as stored by
+-- blob storage) so K-webserver /get-profile-diff can compare a profile at two points in time.
+CREATE TABLE IF NOT EXISTS k_profile_history (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    base64_encoded_nickname TEXT NOT NULL DEFAULT '',
+    profile_image_hash BYTEA,
+    base64_encoded_message TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_profile_history_sender_pubkey ON k_profile_history(sender_pubkey, block_time DESC);
+
+-- History starts with the current profiles (images that are not valid base64 are hashed as text)
+INSERT INTO k_profile_history (
+    transaction_id, block_time, sender_pubkey, base64_encoded_nickname, profile_image_hash, base64_encoded_message
+)
+SELECT
+    transaction_id,
+    block_time,
+    sender_pubkey,
+    base64_encoded_nickname,
+    CASE
+        WHEN profile_image_hash IS NOT NULL THEN profile_image_hash
+        WHEN base64_encoded_profile_image IS NULL OR base64_encoded_profile_image = '' THEN NULL
+        WHEN base64_encoded_profile_image ~ '^[A-Za-z0-9+/]*={0,2}$'
+             AND length(base64_encoded_profile_image) % 4 = 0
+            THEN sha256(decode(base64_encoded_profile_image, 'base64'))
+        ELSE sha256(convert_to(base64_encoded_profile_image, 'UTF8'))
+    END,
+    base64_encoded_message
+FROM k_broadcasts
+ON CONFLICT (transaction_id) DO NOTHING;
+
+-- Update schema version
+UPDATE k_vars SET value = '20' WHERE key = 'schema_version';
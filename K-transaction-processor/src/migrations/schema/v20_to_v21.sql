@@ -0,0 +1,28 @@
+-- Migration: v20_to_v21
+-- Description: Add k_impersonation_flags review table for nickname impersonation detection
+-- Date: 2026-10-17
+
+-- Written by the K-transaction-processor impersonation detector when a new profile broadcast
+-- uses a nickname similar to a user with many followers (normalized, homoglyph-aware).
+-- One row per suspect/target pair, reviewed by operators through K-webserver
+-- /admin/impersonation-flags (pending -> confirmed or dismissed).
+CREATE TABLE IF NOT EXISTS k_impersonation_flags (
+    id BIGSERIAL PRIMARY KEY,
+    suspect_pubkey BYTEA NOT NULL,
+    base64_encoded_suspect_nickname TEXT NOT NULL,
+    target_pubkey BYTEA NOT NULL,
+    base64_encoded_target_nickname TEXT NOT NULL,
+    target_followers BIGINT NOT NULL,
+    similarity DOUBLE PRECISION NOT NULL,
+    -- Broadcast that triggered the flag
+    transaction_id BYTEA NOT NULL,
+    flagged_at BIGINT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'confirmed', 'dismissed')),
+    reviewed_at BIGINT,
+    UNIQUE (suspect_pubkey, target_pubkey)
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_impersonation_flags_status ON k_impersonation_flags(status, flagged_at DESC);
+
+-- Update schema version
+UPDATE k_vars SET value = '21' WHERE key = 'schema_version';
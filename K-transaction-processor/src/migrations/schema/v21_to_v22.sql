@@ -0,0 +1,27 @@
+-- Migration: v21_to_v22
+-- Description: Add k_vote_totals table with pre-computed vote counts per content
+-- Date: 2026-10-17
+
+-- Up/down vote counts per post/reply/quote, so K-webserver reads them with one primary key
+-- lookup instead of aggregating k_votes on every request. Incremented by K-transaction-processor
+-- together with each vote insert and periodically reconciled with k_votes (--vote-totals-interval),
+-- which corrects the totals of votes removed by K-database-cleaner and K-content-remover.
+CREATE TABLE IF NOT EXISTS k_vote_totals (
+    post_id BYTEA PRIMARY KEY,
+    up_votes BIGINT NOT NULL DEFAULT 0,
+    down_votes BIGINT NOT NULL DEFAULT 0
+);
+
+-- Initial totals of the votes indexed so far
+INSERT INTO k_vote_totals (post_id, up_votes, down_votes)
+SELECT post_id,
+       COUNT(*) FILTER (WHERE vote = 'upvote'),
+       COUNT(*) FILTER (WHERE vote = 'downvote')
+FROM k_votes
+GROUP BY post_id
+ON CONFLICT (post_id) DO UPDATE SET
+    up_votes = EXCLUDED.up_votes,
+    down_votes = EXCLUDED.down_votes;
+
+-- Update schema version
+UPDATE k_vars SET value = '22' WHERE key = 'schema_version';
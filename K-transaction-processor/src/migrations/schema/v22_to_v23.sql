@@ -0,0 +1,33 @@
+-- Migration: v22_to_v23
+-- Description: Denormalize parent id and message snippets into k_mentions for notifications
+-- Date: 2026-10-17
+
+-- parent_id is the replied, quoted or voted content, content_snippet the first 280 characters
+-- of the message (base64, the vote value for votes) and parent_snippet the quoted or voted message
+ALTER TABLE k_mentions ADD COLUMN IF NOT EXISTS parent_id BYTEA;
+ALTER TABLE k_mentions ADD COLUMN IF NOT EXISTS content_snippet TEXT;
+ALTER TABLE k_mentions ADD COLUMN IF NOT EXISTS parent_snippet TEXT;
+
+-- Mentions indexed so far keep the full messages (K-transaction-processor cuts new ones)
+UPDATE k_mentions m
+SET parent_id = c.referenced_content_id,
+    content_snippet = c.base64_encoded_message,
+    parent_snippet = quoted.base64_encoded_message
+FROM k_contents c
+LEFT JOIN k_contents quoted
+    ON c.content_type = 'quote' AND quoted.transaction_id = c.referenced_content_id
+WHERE m.content_type IN ('post', 'reply', 'quote')
+  AND c.transaction_id = m.content_id
+  AND c.content_type = m.content_type;
+
+UPDATE k_mentions m
+SET parent_id = v.post_id,
+    content_snippet = v.vote,
+    parent_snippet = voted.base64_encoded_message
+FROM k_votes v
+LEFT JOIN k_contents voted ON voted.transaction_id = v.post_id
+WHERE m.content_type = 'vote'
+  AND v.transaction_id = m.content_id;
+
+-- Update schema version
+UPDATE k_vars SET value = '23' WHERE key = 'schema_version';
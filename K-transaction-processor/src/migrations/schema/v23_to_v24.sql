@@ -0,0 +1,38 @@
+-- Migration: v23_to_v24
+-- Description: Add the k_contents_cold tier for old content and the k_contents_tiered view
+-- Date: 2026-10-17
+
+-- Content older than the cold tier boundary, moved out of k_contents by K-database-cleaner
+-- (--cold-tier-after). Same columns as k_contents, ids are copied from the hot rows.
+CREATE TABLE IF NOT EXISTS k_contents_cold (
+    id BIGINT PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL,
+    content_type VARCHAR(10) NOT NULL,
+    referenced_content_id BYTEA,
+    is_spam BOOLEAN NOT NULL DEFAULT FALSE,
+    is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_block_time ON k_contents_cold(block_time DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_sender_pubkey ON k_contents_cold(sender_pubkey, block_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_referenced ON k_contents_cold(referenced_content_id, content_type);
+
+-- Hot and cold content together, read by K-webserver once a page reaches the cold tier
+CREATE OR REPLACE VIEW k_contents_tiered AS
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited
+FROM k_contents
+UNION ALL
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited
+FROM k_contents_cold;
+
+-- Content with block_time below this boundary (ms) may be in k_contents_cold (NULL = no cold tier)
+ALTER TABLE k_meta ADD COLUMN IF NOT EXISTS cold_tier_before BIGINT;
+
+-- Update schema version
+UPDATE k_vars SET value = '24' WHERE key = 'schema_version';
@@ -0,0 +1,29 @@
+-- Migration: v24_to_v25
+-- Description: Add k_profile_image_blobs, reference counts of profile images in blob storage
+-- Date: 2026-10-17
+
+-- One row per stored blob. New images are keyed by content hash (profile-images/sha256/<hex>),
+-- so every broadcast of the same image shares one blob. ref_count is the number of
+-- k_broadcasts rows pointing at the blob: taken when a broadcast is indexed, released when it
+-- is superseded or purged. Blobs without references since orphaned_at (ms) are deleted by
+-- K-transaction-processor after a grace period.
+CREATE TABLE IF NOT EXISTS k_profile_image_blobs (
+    blob_key TEXT PRIMARY KEY,
+    image_hash BYTEA NOT NULL,
+    ref_count BIGINT NOT NULL DEFAULT 0,
+    orphaned_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_profile_image_blobs_orphaned ON k_profile_image_blobs(orphaned_at) WHERE ref_count <= 0;
+CREATE INDEX IF NOT EXISTS idx_k_broadcasts_profile_image_ref ON k_broadcasts(profile_image_ref) WHERE profile_image_ref IS NOT NULL;
+
+-- Blobs stored before v25 (one per broadcast) are counted too, so they are deleted once replaced
+INSERT INTO k_profile_image_blobs (blob_key, image_hash, ref_count)
+SELECT profile_image_ref, (ARRAY_AGG(profile_image_hash))[1], COUNT(*)
+FROM k_broadcasts
+WHERE profile_image_ref IS NOT NULL AND profile_image_hash IS NOT NULL
+GROUP BY profile_image_ref
+ON CONFLICT (blob_key) DO NOTHING;
+
+-- Update schema version
+UPDATE k_vars SET value = '25' WHERE key = 'schema_version';
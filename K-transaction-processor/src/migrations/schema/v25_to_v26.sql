@@ -0,0 +1,16 @@
+-- Migration: v25_to_v26
+-- Description: Add k_render_spans, rendering hints of every post, reply and quote
+-- Date: 2026-10-17
+
+-- Positions of the mentions (@name), hashtags and URLs of the decoded message, parsed once at
+-- index time so clients don't re-implement the parsing. spans is a JSON array of
+-- {"type", "start", "end", "value"} objects ordered by start, with offsets in UTF-16 code
+-- units. Messages without any of them have no row. Contents indexed before v26 get their
+-- spans when they are backfilled.
+CREATE TABLE IF NOT EXISTS k_render_spans (
+    content_id BYTEA PRIMARY KEY REFERENCES k_contents(transaction_id) ON DELETE CASCADE,
+    spans JSONB NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '26' WHERE key = 'schema_version';
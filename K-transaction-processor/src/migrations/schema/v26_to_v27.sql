@@ -0,0 +1,15 @@
+-- Migration: v26_to_v27
+-- Description: Record the fee burned by each vote and fee-weighted vote totals
+-- Date: 2026-10-17
+
+-- Fee (sompi) paid by the vote transaction: inputs minus outputs, captured at index time with
+-- --vote-fee-weighting. NULL when not captured or when the inputs were not resolved by the
+-- Kaspa indexer. Votes indexed before v27 keep NULL.
+ALTER TABLE k_votes ADD COLUMN IF NOT EXISTS fee BIGINT;
+
+-- Sum of the captured fees of the up/down votes, next to the plain counts
+ALTER TABLE k_vote_totals ADD COLUMN IF NOT EXISTS up_votes_fee BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE k_vote_totals ADD COLUMN IF NOT EXISTS down_votes_fee BIGINT NOT NULL DEFAULT 0;
+
+-- Update schema version
+UPDATE k_vars SET value = '27' WHERE key = 'schema_version';
@@ -0,0 +1,11 @@
+-- Migration: v27_to_v28
+-- Description: Add the visibility watermark of K-webserver feeds (confirmation depth delay)
+-- Date: 2026-10-17
+
+-- Content with a block time above this watermark (ms) is not listed in K-webserver feeds yet.
+-- Kept at the newest block time minus the --confirmation-depth delay by K-transaction-processor.
+-- NULL (the default, and with a depth of 0) lists everything.
+ALTER TABLE k_meta ADD COLUMN IF NOT EXISTS visible_before BIGINT;
+
+-- Update schema version
+UPDATE k_vars SET value = '28' WHERE key = 'schema_version';
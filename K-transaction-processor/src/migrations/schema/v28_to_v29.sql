@@ -0,0 +1,13 @@
+-- Migration: v28_to_v29
+-- Description: Add the normalized nickname of every profile broadcast for nickname history searches
+-- Date: 2026-10-17
+
+-- Decoded nickname normalized like the impersonation detector (case, separators, zero-width and
+-- look-alike characters), searched by K-webserver /admin/nickname-history.
+-- NULL for broadcasts recorded before v29 until they are backfilled, and for undecodable nicknames
+ALTER TABLE k_profile_history ADD COLUMN IF NOT EXISTS normalized_nickname TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_k_profile_history_normalized_nickname ON k_profile_history(normalized_nickname, sender_pubkey) WHERE normalized_nickname IS NOT NULL;
+
+-- Update schema version
+UPDATE k_vars SET value = '29' WHERE key = 'schema_version';
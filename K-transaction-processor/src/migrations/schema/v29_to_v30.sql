@@ -0,0 +1,18 @@
+-- Migration: v29_to_v30
+-- Description: Add k_featured table for operator-curated featured contents
+-- Date: 2026-10-17
+
+-- Managed by an operator through K-webserver /admin/featured and served by /get-featured.
+-- Entries are listed by position, then most recently featured first. No foreign key: an entry
+-- whose content was removed or moved to the cold tier is skipped until the operator deletes it.
+CREATE TABLE IF NOT EXISTS k_featured (
+    content_id BYTEA PRIMARY KEY,
+    position INTEGER NOT NULL DEFAULT 0,
+    note TEXT,
+    featured_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_k_featured_position ON k_featured(position, featured_at DESC);
+
+-- Update schema version
+UPDATE k_vars SET value = '30' WHERE key = 'schema_version';
@@ -0,0 +1,17 @@
+-- Migration: v2_to_v3
+-- Description: Add k_meta table for schema version / feature handshake with K-webserver
+-- Date: 2026-10-17
+
+-- Single-row table written by K-transaction-processor after every schema check.
+-- K-webserver reads it at startup and periodically to enable/disable endpoints
+-- based on what the active schema supports.
+CREATE TABLE IF NOT EXISTS k_meta (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    schema_version INTEGER NOT NULL,
+    features TEXT[] NOT NULL DEFAULT '{}',
+    processor_version VARCHAR(32) NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '3' WHERE key = 'schema_version';
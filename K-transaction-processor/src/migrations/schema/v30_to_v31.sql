@@ -0,0 +1,28 @@
+-- Migration: v30_to_v31
+-- Description: Add a full-text search vector to k_contents for post and reply searches
+-- Date: 2026-10-17
+
+-- Decoded message as a 'simple' text search vector (no stemming, K messages are written in
+-- many languages), searched by K-webserver /search-posts.
+-- NULL for contents indexed before v31 until they are backfilled, and for undecodable messages
+ALTER TABLE k_contents ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_search_vector ON k_contents USING GIN (search_vector) WHERE content_type IN ('post', 'reply');
+
+-- Same column in the cold tier, moved along with the content by K-database-cleaner, so
+-- searches through k_contents_tiered reach old content too
+ALTER TABLE k_contents_cold ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_search_vector ON k_contents_cold USING GIN (search_vector) WHERE content_type IN ('post', 'reply');
+
+CREATE OR REPLACE VIEW k_contents_tiered AS
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector
+FROM k_contents
+UNION ALL
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector
+FROM k_contents_cold;
+
+-- Update schema version
+UPDATE k_vars SET value = '31' WHERE key = 'schema_version';
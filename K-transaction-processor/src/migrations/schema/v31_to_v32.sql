@@ -0,0 +1,17 @@
+-- Migration: v31_to_v32
+-- Description: Add k_write_intents, the write-ahead intent log of the processor workers
+-- Date: 2026-10-18
+
+-- K transactions of the batches being written by the workers, recorded before each batch
+-- transaction begins and removed once the batch is committed (or the record dead-lettered).
+-- Rows left behind by a crash or a kill are rolled forward at the next startup: their
+-- transactions are queued again and reprocessed, K inserts being idempotent.
+-- Written in the network's own schema for the batches of every tenant.
+CREATE TABLE IF NOT EXISTS k_write_intents (
+    transaction_id BYTEA PRIMARY KEY,
+    worker_id INTEGER NOT NULL,
+    recorded_at BIGINT NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '32' WHERE key = 'schema_version';
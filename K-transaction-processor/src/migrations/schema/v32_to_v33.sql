@@ -0,0 +1,54 @@
+-- Migration: v32_to_v33
+-- Description: Add the thread root of replies to k_contents
+-- Date: 2026-10-18
+
+-- Post or quote the reply chain of a reply answers, resolved by K-transaction-processor when
+-- the reply is indexed, so a whole conversation is read with one index lookup.
+-- NULL for posts and quotes (roots of their own thread), and for replies whose chain reaches a
+-- content not indexed yet (resolved once it is indexed)
+ALTER TABLE k_contents ADD COLUMN IF NOT EXISTS root_post_id BYTEA;
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_thread ON k_contents(root_post_id, block_time DESC, id DESC) WHERE content_type = 'reply';
+
+-- Same column in the cold tier, moved along with the content by K-database-cleaner
+ALTER TABLE k_contents_cold ADD COLUMN IF NOT EXISTS root_post_id BYTEA;
+
+CREATE INDEX IF NOT EXISTS idx_k_contents_cold_thread ON k_contents_cold(root_post_id, block_time DESC, id DESC) WHERE content_type = 'reply';
+
+CREATE OR REPLACE VIEW k_contents_tiered AS
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector, root_post_id
+FROM k_contents
+UNION ALL
+SELECT id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message,
+       content_type, referenced_content_id, is_spam, is_rate_limited, search_vector, root_post_id
+FROM k_contents_cold;
+
+-- Roots of the replies indexed so far, walking every reply chain down from its post or quote
+-- across both tiers
+WITH RECURSIVE thread AS (
+    SELECT r.transaction_id, p.transaction_id AS root_post_id
+    FROM k_contents_tiered r
+    JOIN k_contents_tiered p ON p.transaction_id = r.referenced_content_id
+    WHERE r.content_type = 'reply' AND p.content_type <> 'reply'
+    UNION ALL
+    SELECT r.transaction_id, t.root_post_id
+    FROM thread t
+    JOIN k_contents_tiered r ON r.referenced_content_id = t.transaction_id
+    WHERE r.content_type = 'reply'
+),
+hot_roots AS (
+    UPDATE k_contents c
+    SET root_post_id = t.root_post_id
+    FROM thread t
+    WHERE c.transaction_id = t.transaction_id
+      AND c.root_post_id IS NULL
+)
+UPDATE k_contents_cold c
+SET root_post_id = t.root_post_id
+FROM thread t
+WHERE c.transaction_id = t.transaction_id
+  AND c.root_post_id IS NULL;
+
+-- Update schema version
+UPDATE k_vars SET value = '33' WHERE key = 'schema_version';
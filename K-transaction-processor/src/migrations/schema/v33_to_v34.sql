@@ -0,0 +1,18 @@
+-- Migration: v33_to_v34
+-- Description: Carry the columns added to k_contents and k_mentions since v19 into the archive tables
+-- Date: 2026-10-18
+
+-- Full-text search vector (v31), thread root (v33) and rendering hints (k_render_spans, v26)
+-- of the contents archived by K-database-cleaner --retention-action archive.
+-- NULL for rows archived before v34
+ALTER TABLE k_contents_archive ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+ALTER TABLE k_contents_archive ADD COLUMN IF NOT EXISTS root_post_id BYTEA;
+ALTER TABLE k_contents_archive ADD COLUMN IF NOT EXISTS render_spans JSONB;
+
+-- Enriched mention columns (v23) of the archived mentions
+ALTER TABLE k_mentions_archive ADD COLUMN IF NOT EXISTS parent_id BYTEA;
+ALTER TABLE k_mentions_archive ADD COLUMN IF NOT EXISTS content_snippet TEXT;
+ALTER TABLE k_mentions_archive ADD COLUMN IF NOT EXISTS parent_snippet TEXT;
+
+-- Update schema version
+UPDATE k_vars SET value = '34' WHERE key = 'schema_version';
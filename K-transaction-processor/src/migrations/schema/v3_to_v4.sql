@@ -0,0 +1,12 @@
+-- Migration: v3_to_v4
+-- Description: Add blob storage reference and hash for profile images
+-- Date: 2026-10-17
+
+-- When K-transaction-processor runs with --image-storage filesystem|s3, decoded profile
+-- images are written to blob storage and only the object key + SHA-256 hash are kept here
+-- (base64_encoded_profile_image is left NULL for those rows)
+ALTER TABLE k_broadcasts ADD COLUMN IF NOT EXISTS profile_image_ref TEXT;
+ALTER TABLE k_broadcasts ADD COLUMN IF NOT EXISTS profile_image_hash BYTEA;
+
+-- Update schema version
+UPDATE k_vars SET value = '4' WHERE key = 'schema_version';
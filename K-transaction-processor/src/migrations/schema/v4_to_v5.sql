@@ -0,0 +1,17 @@
+-- Migration: v4_to_v5
+-- Description: Add k_dead_letters table for transactions that failed permanently
+-- Date: 2026-10-17
+
+-- K transactions are written here when processing fails with a permanent error
+-- (constraint violation, malformed payload) or when all retry attempts are exhausted.
+-- Rows can be inspected and removed manually once the cause is fixed.
+CREATE TABLE IF NOT EXISTS k_dead_letters (
+    transaction_id BYTEA PRIMARY KEY,
+    error_class VARCHAR(16) NOT NULL,
+    error_message TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    failed_at BIGINT NOT NULL
+);
+
+-- Update schema version
+UPDATE k_vars SET value = '5' WHERE key = 'schema_version';
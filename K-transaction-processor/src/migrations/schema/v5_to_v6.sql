@@ -0,0 +1,10 @@
+-- Migration: v5_to_v6
+-- Description: Add spam flag to k_contents for mention-bomb posts/replies
+-- Date: 2026-10-17
+
+-- Set by K-transaction-processor (--excess-mentions spam) when a post or reply
+-- mentions more users than --max-mentions, no k_mentions rows are created for it
+ALTER TABLE k_contents ADD COLUMN IF NOT EXISTS is_spam BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Update schema version
+UPDATE k_vars SET value = '6' WHERE key = 'schema_version';
@@ -0,0 +1,14 @@
+-- Migration: v6_to_v7
+-- Description: Add rate-limited flag to k_contents and k_votes (per-sender index-time throttle)
+-- Date: 2026-10-17
+
+-- Set by K-transaction-processor (--rate-limit-action mark) when the sender exceeded
+-- --max-posts-per-minute / --max-votes-per-minute, K-webserver can hide these rows
+ALTER TABLE k_contents ADD COLUMN IF NOT EXISTS is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE k_votes ADD COLUMN IF NOT EXISTS is_rate_limited BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Per-sender vote rate lookups (k_contents already has idx_k_contents_sender_pubkey)
+CREATE INDEX IF NOT EXISTS idx_k_votes_sender_block_time ON k_votes(sender_pubkey, block_time DESC);
+
+-- Update schema version
+UPDATE k_vars SET value = '7' WHERE key = 'schema_version';
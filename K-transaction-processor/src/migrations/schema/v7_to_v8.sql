@@ -0,0 +1,13 @@
+-- Migration: v7_to_v8
+-- Description: Add read-only maintenance mode flag to k_meta
+-- Date: 2026-10-17
+
+-- Set by an operator (--set-maintenance on|off or plain SQL). While TRUE,
+-- K-transaction-processor pauses consumption and K-webserver only serves reads,
+-- answering anything else with 503 and Retry-After: maintenance_retry_after seconds.
+ALTER TABLE k_meta ADD COLUMN IF NOT EXISTS maintenance_mode BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE k_meta ADD COLUMN IF NOT EXISTS maintenance_retry_after INTEGER NOT NULL DEFAULT 300;
+ALTER TABLE k_meta ADD COLUMN IF NOT EXISTS maintenance_reason TEXT;
+
+-- Update schema version
+UPDATE k_vars SET value = '8' WHERE key = 'schema_version';
@@ -0,0 +1,52 @@
+-- Migration: v8_to_v9
+-- Description: Add k_groups, k_group_members and k_group_posts tables for group-scoped posting
+-- Date: 2026-10-17
+
+-- Groups are identified by the transaction id of their creation transaction.
+-- The creator (sender_pubkey) owns the group and is its first member.
+CREATE TABLE IF NOT EXISTS k_groups (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_name TEXT NOT NULL,
+    base64_encoded_description TEXT NOT NULL DEFAULT '',
+    -- 'open': anyone can join, 'closed': only the owner can add members
+    membership_policy VARCHAR(10) NOT NULL CHECK (membership_policy IN ('open', 'closed'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_groups_sender_signature_unique ON k_groups(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_groups_sender_pubkey ON k_groups(sender_pubkey, block_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_groups_block_time ON k_groups(block_time DESC, id DESC);
+
+-- Current members of each group (one row per member, removed on leave/remove).
+-- transaction_id/block_time refer to the membership transaction that added the member.
+CREATE TABLE IF NOT EXISTS k_group_members (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BYTEA NOT NULL REFERENCES k_groups(transaction_id) ON DELETE CASCADE,
+    member_pubkey BYTEA NOT NULL,
+    transaction_id BYTEA NOT NULL,
+    block_time BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_group_members_group_member_unique ON k_group_members(group_id, member_pubkey);
+CREATE INDEX IF NOT EXISTS idx_k_group_members_member_pubkey ON k_group_members(member_pubkey, block_time DESC);
+
+-- Group-scoped posts, kept out of k_contents so they never appear in public feeds
+CREATE TABLE IF NOT EXISTS k_group_posts (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    group_id BYTEA NOT NULL REFERENCES k_groups(transaction_id) ON DELETE CASCADE,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_message TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_group_posts_sender_signature_unique ON k_group_posts(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_group_posts_group_id ON k_group_posts(group_id, block_time DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_k_group_posts_sender_pubkey ON k_group_posts(sender_pubkey, block_time DESC);
+
+-- Update schema version
+UPDATE k_vars SET value = '9' WHERE key = 'schema_version';
@@ -0,0 +1,40 @@
+-- Migration: v9_to_v10
+-- Description: Add k_events and k_event_rsvps tables for the event calendar
+-- Date: 2026-10-17
+
+-- Events announced on chain, start_time/end_time are unix timestamps in milliseconds
+-- (same unit as block_time) chosen by the organizer (sender_pubkey).
+CREATE TABLE IF NOT EXISTS k_events (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    base64_encoded_title TEXT NOT NULL,
+    start_time BIGINT NOT NULL,
+    end_time BIGINT NOT NULL,
+    base64_encoded_location TEXT NOT NULL DEFAULT '',
+    CHECK (end_time >= start_time)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_events_sender_signature_unique ON k_events(sender_signature);
+CREATE INDEX IF NOT EXISTS idx_k_events_sender_pubkey ON k_events(sender_pubkey, start_time DESC);
+CREATE INDEX IF NOT EXISTS idx_k_events_start_time ON k_events(start_time, id);
+CREATE INDEX IF NOT EXISTS idx_k_events_end_time ON k_events(end_time);
+
+-- Latest RSVP of each user per event (a newer RSVP replaces the previous one)
+CREATE TABLE IF NOT EXISTS k_event_rsvps (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id BYTEA UNIQUE NOT NULL,
+    block_time BIGINT NOT NULL,
+    event_id BYTEA NOT NULL REFERENCES k_events(transaction_id) ON DELETE CASCADE,
+    sender_pubkey BYTEA NOT NULL,
+    sender_signature BYTEA NOT NULL,
+    rsvp_status VARCHAR(10) NOT NULL CHECK (rsvp_status IN ('going', 'interested', 'not_going'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_k_event_rsvps_event_sender_unique ON k_event_rsvps(event_id, sender_pubkey);
+CREATE INDEX IF NOT EXISTS idx_k_event_rsvps_sender_pubkey ON k_event_rsvps(sender_pubkey, block_time DESC);
+
+-- Update schema version
+UPDATE k_vars SET value = '10' WHERE key = 'schema_version';
@@ -0,0 +1,174 @@
+use base64::{Engine as _, engine::general_purpose};
+
+/// Nicknames shorter than this (after normalization) are never compared: too many collisions
+pub const MIN_COMPARED_LENGTH: usize = 3;
+
+/// Invisible characters inserted to make copies of a nickname look different
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Map look-alike characters (Cyrillic, Greek, fullwidth, digits and symbols) to the Latin
+/// letter they imitate; expects lowercase input
+fn fold_homoglyph(c: char) -> char {
+    match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' => 'e',
+        'һ' => 'h',
+        'і' | 'ї' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'н' => 'h',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'ԝ' => 'w',
+        'х' => 'x',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'η' => 'n',
+        'ι' => 'i',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        // Digits and symbols used as letters
+        '0' => 'o',
+        '1' | '!' | '|' => 'l',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        '8' => 'b',
+        // Fullwidth Latin letters
+        'ａ'..='ｚ' => char::from_u32(c as u32 - 'ａ' as u32 + 'a' as u32).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Normalize a nickname so look-alike variants compare equal: lowercase, zero-width characters
+/// removed, homoglyphs folded to Latin letters, separators dropped, "rn" read as "m" and "vv" as "w"
+pub fn normalize_nickname(nickname: &str) -> String {
+    nickname
+        .chars()
+        .filter(|c| !is_zero_width(*c))
+        .collect::<String>()
+        .to_lowercase()
+        .chars()
+        .map(fold_homoglyph)
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+/// Decode and normalize a base64 nickname; None when it is undecodable or empty
+pub fn normalize_base64_nickname(base64_nickname: &str) -> Option<String> {
+    let decoded_bytes = general_purpose::STANDARD.decode(base64_nickname).ok()?;
+    let normalized = normalize_nickname(&String::from_utf8(decoded_bytes).ok()?);
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// Similarity of two normalized nicknames from 0.0 (unrelated) to 1.0 (identical),
+/// one minus the edit distance relative to the longer nickname
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len() < MIN_COMPARED_LENGTH || b.len() < MIN_COMPARED_LENGTH {
+        return 0.0;
+    }
+
+    // Levenshtein distance, single row
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    1.0 - row[b.len()] as f64 / a.len().max(b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nickname_similarity(a: &str, b: &str) -> f64 {
+        similarity(&normalize_nickname(a), &normalize_nickname(b))
+    }
+
+    #[test]
+    fn test_normalize_case_and_separators() {
+        assert_eq!(normalize_nickname("The Sheep_Cat."), "thesheepcat");
+    }
+
+    #[test]
+    fn test_normalize_folds_homoglyphs() {
+        // Cyrillic "е" and "а", digit zero for "o"
+        assert_eq!(normalize_nickname("Shееp Cаt"), "sheepcat");
+        assert_eq!(normalize_nickname("K0SPA"), "kospa");
+    }
+
+    #[test]
+    fn test_normalize_removes_zero_width_characters() {
+        assert_eq!(normalize_nickname("Sheep\u{200B}Cat"), "sheepcat");
+    }
+
+    #[test]
+    fn test_normalize_reads_rn_as_m() {
+        assert_eq!(normalize_nickname("Sarn"), "sam");
+    }
+
+    #[test]
+    fn test_normalize_base64_nickname() {
+        // "Sh\u{0435}ep_Cat" and "..." (no letters left)
+        assert_eq!(
+            normalize_base64_nickname("U2jQtWVwX0NhdA==").as_deref(),
+            Some("sheepcat")
+        );
+        assert_eq!(normalize_base64_nickname("Li4u"), None);
+        assert_eq!(normalize_base64_nickname("not base64!"), None);
+    }
+
+    #[test]
+    fn test_lookalike_nicknames_are_identical() {
+        assert_eq!(
+            nickname_similarity("TheSheepCat", "The_Sh\u{0435}\u{0435}pCat"),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_one_edit_is_similar() {
+        let score = nickname_similarity("TheSheepCat", "TheSheepCats");
+        assert!(score > 0.9 && score < 1.0, "score {}", score);
+    }
+
+    #[test]
+    fn test_unrelated_nicknames_are_dissimilar() {
+        assert!(nickname_similarity("TheSheepCat", "KaspaWhale") < 0.5);
+    }
+
+    #[test]
+    fn test_short_nicknames_are_not_compared() {
+        assert_eq!(nickname_similarity("Al", "Al"), 0.0);
+        assert_eq!(nickname_similarity("", "TheSheepCat"), 0.0);
+    }
+}
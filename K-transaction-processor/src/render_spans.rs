@@ -0,0 +1,166 @@
+use crate::hashtag_extractor::hashtag_ranges;
+use base64::{Engine as _, engine::general_purpose};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+
+static URL_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"https?://[^\s<>"]+"#).unwrap());
+
+// Mentions are written @nickname, the name ending at the first character other than a letter,
+// digit or '_'
+static MENTION_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"@[\p{L}\p{N}_]{1,50}").unwrap());
+
+/// Punctuation ending a sentence rather than a URL
+const URL_TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', '\'', '"'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanType {
+    Mention,
+    Hashtag,
+    Url,
+}
+
+/// Part of a decoded message that clients render as a link, with offsets in UTF-16 code units
+/// (JavaScript string indexes)
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RenderSpan {
+    #[serde(rename = "type")]
+    pub span_type: SpanType,
+    pub start: usize,
+    pub end: usize,
+    // Mentioned name without '@', lowercase hashtag without '#' (as in k_hashtags), or the URL
+    pub value: String,
+}
+
+/// Whether a link can start at `pos`: start of the message, after whitespace or an opening parenthesis
+fn starts_word(text: &str, pos: usize) -> bool {
+    text[..pos]
+        .chars()
+        .next_back()
+        .is_none_or(|c| c.is_whitespace() || c == '(')
+}
+
+/// Mentions, hashtags and URLs of a decoded message, ordered by position
+/// URLs are found first: a '#' or '@' inside a link belongs to the link
+pub fn render_spans(text: &str) -> Vec<RenderSpan> {
+    let mut found: Vec<(SpanType, Range<usize>, String)> = Vec::new();
+
+    for mat in URL_PATTERN.find_iter(text) {
+        if !starts_word(text, mat.start()) {
+            continue;
+        }
+        let url = mat.as_str().trim_end_matches(URL_TRAILING_PUNCTUATION);
+        let range = mat.start()..mat.start() + url.len();
+        found.push((SpanType::Url, range, url.to_string()));
+    }
+
+    for range in hashtag_ranges(text) {
+        let hashtag = text[range.start + 1..range.end].to_lowercase();
+        found.push((SpanType::Hashtag, range, hashtag));
+    }
+
+    for mat in MENTION_PATTERN.find_iter(text) {
+        let ends_word = text[mat.end()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_alphanumeric() && c != '_');
+        if starts_word(text, mat.start()) && ends_word {
+            found.push((
+                SpanType::Mention,
+                mat.range(),
+                mat.as_str()[1..].to_string(),
+            ));
+        }
+    }
+
+    // Keep the first span found where several overlap (URLs, then hashtags)
+    let mut kept: Vec<(SpanType, Range<usize>, String)> = Vec::new();
+    for span in found {
+        if !kept
+            .iter()
+            .any(|(_, range, _)| range.start < span.1.end && span.1.start < range.end)
+        {
+            kept.push(span);
+        }
+    }
+    kept.sort_by_key(|(_, range, _)| range.start);
+
+    kept.into_iter()
+        .map(|(span_type, range, value)| RenderSpan {
+            span_type,
+            start: text[..range.start].encode_utf16().count(),
+            end: text[..range.end].encode_utf16().count(),
+            value,
+        })
+        .collect()
+}
+
+/// JSON array of the render spans of a base64-encoded message (k_render_spans.spans)
+/// Returns None for undecodable messages and messages without any span
+pub fn render_spans_from_base64(base64_message: &str) -> Option<String> {
+    let decoded_bytes = general_purpose::STANDARD.decode(base64_message).ok()?;
+    let decoded_text = String::from_utf8(decoded_bytes).ok()?;
+
+    let spans = render_spans(&decoded_text);
+    if spans.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&spans).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(span_type: SpanType, start: usize, end: usize, value: &str) -> RenderSpan {
+        RenderSpan {
+            span_type,
+            start,
+            end,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_mentions_hashtags_and_urls() {
+        let spans = render_spans("Hi @Alice, see https://kaspa.org/news. #Kaspa");
+        assert_eq!(
+            spans,
+            vec![
+                span(SpanType::Mention, 3, 9, "Alice"),
+                span(SpanType::Url, 15, 37, "https://kaspa.org/news"),
+                span(SpanType::Hashtag, 39, 45, "kaspa"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markup_inside_urls_and_words() {
+        let spans = render_spans("(https://x.com/@bob#top) mail@example.com word#tag");
+        assert_eq!(
+            spans,
+            vec![span(SpanType::Url, 1, 23, "https://x.com/@bob#top")]
+        );
+    }
+
+    #[test]
+    fn test_utf16_offsets() {
+        // '😀' is 4 bytes in UTF-8 and 2 code units in UTF-16
+        let spans = render_spans("😀 #ok");
+        assert_eq!(spans, vec![span(SpanType::Hashtag, 3, 6, "ok")]);
+    }
+
+    #[test]
+    fn test_json_from_base64() {
+        let message = general_purpose::STANDARD.encode("Hello #world");
+        assert_eq!(
+            render_spans_from_base64(&message).as_deref(),
+            Some(r#"[{"type":"hashtag","start":6,"end":12,"value":"world"}]"#)
+        );
+        let plain = general_purpose::STANDARD.encode("Hello world");
+        assert_eq!(render_spans_from_base64(&plain), None);
+        assert_eq!(render_spans_from_base64("not base64!"), None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Classification of processing errors, deciding whether a transaction is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Temporary condition (connection loss, serialization failure, deadlock): retry with backoff
+    Transient,
+    /// Retrying cannot help (constraint violation, malformed data): send to dead-letter
+    Permanent,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Transient => "transient",
+            ErrorClass::Permanent => "permanent",
+        }
+    }
+}
+
+/// Error for data the transaction depends on that is not indexed yet (e.g. outputs written
+/// by the Kaspa indexer after the transaction row); always classified as transient
+#[derive(Debug)]
+pub struct NotYetIndexed(pub String);
+
+impl std::fmt::Display for NotYetIndexed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not indexed yet: {}", self.0)
+    }
+}
+
+impl std::error::Error for NotYetIndexed {}
+
+/// Classify an error returned while fetching or processing a transaction
+pub fn classify_error(err: &anyhow::Error) -> ErrorClass {
+    if err.downcast_ref::<NotYetIndexed>().is_some() {
+        return ErrorClass::Transient;
+    }
+
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx_err) => classify_sqlx_error(sqlx_err),
+        // Non-database errors come from payload decoding/parsing (hex, base64, UTF-8)
+        None => ErrorClass::Permanent,
+    }
+}
+
+fn classify_sqlx_error(err: &sqlx::Error) -> ErrorClass {
+    match err {
+        sqlx::Error::Database(db_err) => match db_err.code() {
+            Some(code) => classify_sqlstate(&code),
+            None => ErrorClass::Transient,
+        },
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// Classify a PostgreSQL SQLSTATE code
+/// Unknown codes are treated as transient so they still get their retry budget
+fn classify_sqlstate(code: &str) -> ErrorClass {
+    match code {
+        // serialization_failure, deadlock_detected, lock_not_available, query_canceled
+        "40001" | "40P01" | "55P03" | "57014" => ErrorClass::Transient,
+        // admin_shutdown, crash_shutdown, cannot_connect_now, too_many_connections
+        "57P01" | "57P02" | "57P03" | "53300" => ErrorClass::Transient,
+        // Class 08: connection exception
+        c if c.starts_with("08") => ErrorClass::Transient,
+        // Class 22: data exception, class 23: integrity constraint violation
+        c if c.starts_with("22") || c.starts_with("23") => ErrorClass::Permanent,
+        // Class 42: syntax error or access rule violation
+        c if c.starts_with("42") => ErrorClass::Permanent,
+        _ => ErrorClass::Transient,
+    }
+}
+
+/// Whether an error means the database itself is unreachable or refusing work (lost
+/// connection, pool exhausted, server shutting down), as opposed to a failure of one record
+pub fn is_outage_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db_err)) => db_err.code().is_some_and(|code| {
+            code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03" | "53300")
+        }),
+        Some(
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed,
+        ) => true,
+        _ => false,
+    }
+}
+
+/// Exponential backoff delay for a retry attempt (1-based): base, 2x base, 4x base, ...
+pub fn backoff_delay(base_delay_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(10);
+    Duration::from_millis(base_delay_ms.saturating_mul(factor))
+}
+
+/// Per-class error counters shared by all workers
+#[derive(Debug, Default)]
+pub struct ErrorStats {
+    transient: AtomicU64,
+    permanent: AtomicU64,
+    retried: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+impl ErrorStats {
+    pub fn record(&self, class: ErrorClass) {
+        match class {
+            ErrorClass::Transient => self.transient.fetch_add(1, Ordering::Relaxed),
+            ErrorClass::Permanent => self.permanent.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    pub fn record_retry(&self) {
+        self.retried.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dead_letter(&self) {
+        self.dead_lettered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Snapshot of the counters: (transient, permanent, retried, dead_lettered)
+    pub fn snapshot(&self) -> (u64, u64, u64, u64) {
+        (
+            self.transient.load(Ordering::Relaxed),
+            self.permanent.load(Ordering::Relaxed),
+            self.retried.load(Ordering::Relaxed),
+            self.dead_lettered.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_sqlstate() {
+        assert_eq!(classify_sqlstate("40001"), ErrorClass::Transient);
+        assert_eq!(classify_sqlstate("40P01"), ErrorClass::Transient);
+        assert_eq!(classify_sqlstate("08006"), ErrorClass::Transient);
+        assert_eq!(classify_sqlstate("23505"), ErrorClass::Permanent);
+        assert_eq!(classify_sqlstate("22P02"), ErrorClass::Permanent);
+        assert_eq!(classify_sqlstate("XX000"), ErrorClass::Transient);
+    }
+
+    #[test]
+    fn test_classify_error() {
+        let io_err = anyhow::Error::from(sqlx::Error::PoolTimedOut);
+        assert_eq!(classify_error(&io_err), ErrorClass::Transient);
+
+        let decode_err = anyhow::Error::from(hex::decode("zz").unwrap_err());
+        assert_eq!(classify_error(&decode_err), ErrorClass::Permanent);
+
+        let row_err = anyhow::Error::from(sqlx::Error::RowNotFound);
+        assert_eq!(classify_error(&row_err), ErrorClass::Permanent);
+
+        let pending_err = anyhow::Error::from(NotYetIndexed("outputs".to_string()));
+        assert_eq!(classify_error(&pending_err), ErrorClass::Transient);
+    }
+
+    #[test]
+    fn test_is_outage_error() {
+        let pool_err = anyhow::Error::from(sqlx::Error::PoolTimedOut);
+        assert!(is_outage_error(&pool_err));
+
+        let io_err = anyhow::Error::from(sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionRefused,
+        )));
+        assert!(is_outage_error(&io_err));
+
+        let row_err = anyhow::Error::from(sqlx::Error::RowNotFound);
+        assert!(!is_outage_error(&row_err));
+
+        let pending_err = anyhow::Error::from(NotYetIndexed("outputs".to_string()));
+        assert!(!is_outage_error(&pending_err));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(1000, 1), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(1000, 2), Duration::from_millis(2000));
+        assert_eq!(backoff_delay(1000, 3), Duration::from_millis(4000));
+        assert_eq!(backoff_delay(1000, 0), Duration::from_millis(1000));
+    }
+}
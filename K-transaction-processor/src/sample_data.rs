@@ -0,0 +1,295 @@
+use crate::chain_params::ChainParams;
+use crate::database::Transaction;
+use anyhow::Result;
+use base64::{Engine as _, engine::general_purpose};
+use kaspa_wallet_core::message::{PersonalMessage, SignMessageOptions, sign_message};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+
+// Block time between two generated transactions (ms)
+const SAMPLE_BLOCK_TIME_STEP_MS: i64 = 1_000;
+
+const SAMPLE_NICKNAMES: &[&str] = &[
+    "alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi", "ivan", "judy",
+];
+
+const SAMPLE_MESSAGES: &[&str] = &[
+    "Just synced a new node",
+    "What is everyone building this week?",
+    "Blocks are flying today",
+    "Reading the protocol specifications again",
+    "Coffee first, then code",
+    "Anyone tried the new wallet release?",
+    "Sharing my notes from the meetup",
+    "Testing a long running indexer setup",
+];
+
+const SAMPLE_HASHTAGS: &[&str] = &["kaspa", "dev", "nodes", "meetup", "wallets"];
+
+const SAMPLE_REPLIES: &[&str] = &[
+    "Agreed!",
+    "Interesting, tell me more",
+    "Same here",
+    "Not sure about that one",
+    "Thanks for sharing",
+];
+
+/// Shape of the generated data set
+#[derive(Debug, Clone)]
+pub struct SampleDataConfig {
+    /// Same seed, same data: keys, signatures, ids and block times are all derived from it
+    pub seed: u64,
+    pub users: usize,
+    pub posts_per_user: usize,
+    /// Block time of the first transaction (ms)
+    pub start_block_time: i64,
+}
+
+/// A generated user able to sign K actions
+pub struct SampleUser {
+    secret_key: [u8; 32],
+    pub pubkey: String,
+    pub nickname: String,
+}
+
+impl SampleUser {
+    fn new(seed: u64, index: usize) -> Result<Self> {
+        let secret_key: [u8; 32] =
+            Sha256::digest(format!("k-sample-user:{}:{}", seed, index)).into();
+        let secp = Secp256k1::new();
+        let public_key = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&secret_key)?);
+        let name = SAMPLE_NICKNAMES[index % SAMPLE_NICKNAMES.len()];
+        let nickname = if index < SAMPLE_NICKNAMES.len() {
+            name.to_string()
+        } else {
+            format!("{}{}", name, index / SAMPLE_NICKNAMES.len())
+        };
+
+        Ok(Self {
+            secret_key,
+            pubkey: hex::encode(public_key.serialize()),
+            nickname,
+        })
+    }
+
+    /// Sign a K message, without auxiliary randomness so signatures are reproducible
+    fn sign(&self, message: &str) -> Result<String> {
+        let signature = sign_message(
+            &PersonalMessage(message),
+            &self.secret_key,
+            &SignMessageOptions { no_aux_rand: true },
+        )?;
+        Ok(hex::encode(signature))
+    }
+}
+
+/// Small deterministic PRNG (SplitMix64), enough to pick sample content reproducibly
+struct SampleRng(u64);
+
+impl SampleRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Index below `len` (len > 0)
+    fn below(&mut self, len: usize) -> usize {
+        (self.next() % len as u64) as usize
+    }
+
+    /// True with a probability of `percent` / 100
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+}
+
+/// Builds signed K transactions in block time order
+struct SampleBuilder<'a> {
+    chain: &'a ChainParams,
+    block_time: i64,
+    transactions: Vec<Transaction>,
+}
+
+impl SampleBuilder<'_> {
+    /// Append `action:sender:signature:fields`, the fields being the signed message of every
+    /// action; returns the transaction id
+    fn push(&mut self, user: &SampleUser, action: &str, fields: &str) -> Result<String> {
+        let payload = format!(
+            "{}{}:{}:{}:{}",
+            self.chain.payload_prefix,
+            action,
+            user.pubkey,
+            user.sign(fields)?,
+            fields
+        );
+        // Same transaction id as simulate-payload would derive for the payload
+        let transaction_id = hex::encode(Sha256::digest(payload.as_bytes()));
+        self.transactions.push(Transaction {
+            transaction_id: transaction_id.clone(),
+            payload: Some(hex::encode(payload)),
+            block_time: Some(self.block_time),
+        });
+        self.block_time += SAMPLE_BLOCK_TIME_STEP_MS;
+        Ok(transaction_id)
+    }
+}
+
+fn encode(text: &str) -> String {
+    general_purpose::STANDARD.encode(text)
+}
+
+/// Generate the users of a data set
+pub fn generate_users(config: &SampleDataConfig) -> Result<Vec<SampleUser>> {
+    (0..config.users)
+        .map(|index| SampleUser::new(config.seed, index))
+        .collect()
+}
+
+/// Generate a deterministic data set of signed K transactions, in the order they must be
+/// indexed: profiles, follows and blocks, then posts with mentions and hashtags, each followed
+/// by its reply thread, votes and occasional quotes
+pub fn generate_transactions(
+    chain: &ChainParams,
+    config: &SampleDataConfig,
+    users: &[SampleUser],
+) -> Result<Vec<Transaction>> {
+    let mut rng = SampleRng(config.seed);
+    let mut builder = SampleBuilder {
+        chain,
+        block_time: config.start_block_time,
+        transactions: Vec::new(),
+    };
+    if users.is_empty() {
+        return Ok(builder.transactions);
+    }
+
+    for user in users {
+        let nickname = encode(&user.nickname);
+        let message = encode(&format!("Hi, I am {}", user.nickname));
+        // Profile image left empty
+        builder.push(user, "broadcast", &format!("{}::{}", nickname, message))?;
+    }
+
+    for (index, user) in users.iter().enumerate() {
+        for other in users.iter().skip(index + 1).chain(users.iter().take(index)) {
+            if rng.chance(40) {
+                let fields = format!("follow:{}", other.pubkey);
+                builder.push(user, "follow", &fields)?;
+            } else if rng.chance(3) {
+                let fields = format!("block:{}", other.pubkey);
+                builder.push(user, "block", &fields)?;
+            }
+        }
+    }
+
+    for _ in 0..config.posts_per_user {
+        for user in users {
+            let author = &user.pubkey;
+            let mut mentioned = Vec::new();
+            if rng.chance(30) {
+                let other = &users[rng.below(users.len())].pubkey;
+                if other != author {
+                    mentioned.push(other.clone());
+                }
+            }
+            let mut text = SAMPLE_MESSAGES[rng.below(SAMPLE_MESSAGES.len())].to_string();
+            if rng.chance(50) {
+                text.push_str(&format!(
+                    " #{}",
+                    SAMPLE_HASHTAGS[rng.below(SAMPLE_HASHTAGS.len())]
+                ));
+            }
+            let fields = format!("{}:{}", encode(&text), serde_json::to_string(&mentioned)?);
+            let post_id = builder.push(user, "post", &fields)?;
+
+            // Thread: each reply answers the post or one of the earlier replies
+            let mut thread = vec![(post_id.clone(), author.clone())];
+            for _ in 0..rng.below(4) {
+                let replier = &users[rng.below(users.len())];
+                let (parent_id, parent_author) = thread[rng.below(thread.len())].clone();
+                let message = encode(SAMPLE_REPLIES[rng.below(SAMPLE_REPLIES.len())]);
+                let mentioned_json = serde_json::to_string(&vec![parent_author])?;
+                let fields = format!("{}:{}:{}", parent_id, message, mentioned_json);
+                let reply_id = builder.push(replier, "reply", &fields)?;
+                thread.push((reply_id, replier.pubkey.clone()));
+            }
+
+            for voter in users {
+                if voter.pubkey != *author && rng.chance(35) {
+                    let vote = if rng.chance(80) { "upvote" } else { "downvote" };
+                    let fields = format!("{}:{}:{}", post_id, vote, author);
+                    builder.push(voter, "vote", &fields)?;
+                }
+            }
+
+            if rng.chance(15) {
+                let quoter = &users[rng.below(users.len())];
+                let fields = format!("{}:{}:{}", post_id, encode("Worth a read"), author);
+                builder.push(quoter, "quote", &fields)?;
+            }
+        }
+    }
+
+    Ok(builder.transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::TESTNET_10;
+
+    fn config(seed: u64) -> SampleDataConfig {
+        SampleDataConfig {
+            seed,
+            users: 4,
+            posts_per_user: 2,
+            start_block_time: 1_700_000_000_000,
+        }
+    }
+
+    fn generate(config: &SampleDataConfig) -> Vec<Transaction> {
+        let users = generate_users(config).unwrap();
+        generate_transactions(&TESTNET_10, config, &users).unwrap()
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let first = generate(&config(7));
+        let second = generate(&config(7));
+        let ids = |transactions: &[Transaction]| {
+            transactions
+                .iter()
+                .map(|tx| (tx.transaction_id.clone(), tx.payload.clone(), tx.block_time))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&first), ids(&second));
+        assert_ne!(ids(&first), ids(&generate(&config(8))));
+    }
+
+    #[test]
+    fn test_generated_transactions() {
+        let config = config(1);
+        let transactions = generate(&config);
+        // One profile per user, then at least one post per user and round
+        let posts = transactions
+            .iter()
+            .filter(|tx| {
+                tx.payload
+                    .as_deref()
+                    .unwrap()
+                    .starts_with(&hex::encode("k:1:post:"))
+            })
+            .count();
+        assert_eq!(posts, config.users * config.posts_per_user);
+        assert_eq!(transactions[0].block_time, Some(config.start_block_time));
+        assert!(
+            transactions
+                .windows(2)
+                .all(|pair| pair[0].block_time < pair[1].block_time)
+        );
+        assert_eq!(generate_users(&config).unwrap()[0].pubkey.len(), 66);
+    }
+}
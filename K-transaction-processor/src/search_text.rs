@@ -0,0 +1,50 @@
+use base64::{Engine as _, engine::general_purpose};
+
+/// Decoded text of a base64-encoded message, as indexed in k_contents.search_vector
+/// NUL characters are dropped since PostgreSQL text cannot hold them
+/// Returns None for undecodable messages and messages without any text
+pub fn search_text_from_base64(base64_message: &str) -> Option<String> {
+    let decoded_bytes = general_purpose::STANDARD.decode(base64_message).ok()?;
+    let decoded_text = String::from_utf8(decoded_bytes).ok()?;
+
+    let text = decoded_text.replace('\0', "");
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_text(text: &str) -> Option<String> {
+        search_text_from_base64(&general_purpose::STANDARD.encode(text))
+    }
+
+    #[test]
+    fn test_message_is_decoded() {
+        assert_eq!(
+            search_text("Hello #kaspa world"),
+            Some("Hello #kaspa world".to_string())
+        );
+    }
+
+    #[test]
+    fn test_nul_characters_are_dropped() {
+        assert_eq!(search_text("a\0b"), Some("ab".to_string()));
+    }
+
+    #[test]
+    fn test_blank_message_has_no_text() {
+        assert_eq!(search_text(""), None);
+        assert_eq!(search_text(" \0 "), None);
+    }
+
+    #[test]
+    fn test_undecodable_message() {
+        assert_eq!(search_text_from_base64("not base64!"), None);
+        let invalid_utf8 = general_purpose::STANDARD.encode([0xff, 0xfe]);
+        assert_eq!(search_text_from_base64(&invalid_utf8), None);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::config::AppConfig;
+use crate::database::DbPool;
+use crate::k_protocol::KProtocolProcessor;
+use crate::sample_data::{SampleDataConfig, generate_transactions, generate_users};
+use anyhow::Result;
+use tracing::{info, warn};
+
+/// Index a deterministic sample data set (see sample_data) into a fresh database through the
+/// same validation as the workers, for local client development and UI tests.
+/// The transactions table is left untouched: only the K tables are populated
+pub async fn execute(pool: &DbPool, config: &AppConfig, sample: &SampleDataConfig) -> Result<()> {
+    let indexed: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM k_contents) OR EXISTS (SELECT 1 FROM k_broadcasts)",
+    )
+    .fetch_one(pool)
+    .await?;
+    if indexed {
+        return Err(anyhow::anyhow!(
+            "The database already contains K data, seed only populates a fresh database"
+        ));
+    }
+
+    // Profile images stay inline and fees unknown: seeded transactions have no inputs
+    let processor = KProtocolProcessor::new(
+        config.chain,
+        None,
+        config.mentions.clone(),
+        config.sender_rate_limit.clone(),
+        false,
+    );
+
+    let users = generate_users(sample)?;
+    let transactions = generate_transactions(&config.chain, sample, &users)?;
+    info!(
+        "Seeding {} transactions of {} users (seed {})",
+        transactions.len(),
+        users.len(),
+        sample.seed
+    );
+
+    let mut failed = 0;
+    for transaction in &transactions {
+        let mut db_tx = pool.begin().await?;
+        match processor
+            .process_k_transaction(&mut db_tx, transaction)
+            .await
+        {
+            Ok(()) => db_tx.commit().await?,
+            Err(e) => {
+                warn!(
+                    "Failed to seed transaction {}: {}",
+                    transaction.transaction_id, e
+                );
+                db_tx.rollback().await?;
+                failed += 1;
+            }
+        }
+    }
+
+    for user in &users {
+        info!("Seeded user {} ({})", user.nickname, user.pubkey);
+    }
+    info!(
+        "Seed completed: {} transactions indexed, {} failed",
+        transactions.len() - failed,
+        failed
+    );
+    Ok(())
+}
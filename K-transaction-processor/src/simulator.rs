@@ -0,0 +1,144 @@
+use crate::config::AppConfig;
+use crate::database::{DbPool, Transaction, create_pool};
+use crate::k_protocol::{KActionType, KProtocolProcessor};
+use anyhow::Result;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use tokio::sync::OnceCell;
+
+/// Rows one table would receive from the simulated transaction
+#[derive(Debug, Serialize)]
+pub struct TableWrites {
+    pub table: String,
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+/// Outcome of running a payload through the indexing pipeline without persisting it
+#[derive(Debug, Serialize)]
+pub struct SimulationReport {
+    pub transaction_id: String,
+    pub block_time: i64,
+    /// Parsed action, None when the payload does not parse
+    pub action: Option<KActionType>,
+    /// Parse error, why validation rejected the action, or the error that would fail the
+    /// transaction
+    pub error: Option<String>,
+    /// Whether the pipeline accepted the action (it wrote at least one row)
+    pub indexed: bool,
+    pub writes: Vec<TableWrites>,
+}
+
+/// Simulations run for a webserver sharing the binary (k-indexer /admin/simulate-payload),
+/// with the configuration of a processor `run` and a pool opened by the first simulation
+pub struct PayloadSimulator {
+    config: AppConfig,
+    pool: OnceCell<DbPool>,
+}
+
+impl PayloadSimulator {
+    pub fn new(config: AppConfig) -> Self {
+        Self {
+            config,
+            pool: OnceCell::new(),
+        }
+    }
+
+    pub async fn simulate(
+        &self,
+        payload: &str,
+        transaction_id: Option<String>,
+        block_time: Option<i64>,
+    ) -> Result<SimulationReport> {
+        let pool = self
+            .pool
+            .get_or_try_init(|| create_pool(&self.config))
+            .await?;
+        simulate_payload(pool, &self.config, payload, transaction_id, block_time).await
+    }
+}
+
+/// Run a raw K payload (e.g. "k:1:post:...") through the exact parser and validation of the
+/// workers (signature, tombstones, mention and rate limits, references to indexed content)
+/// inside a database transaction that is always rolled back. A payload that is not indexed
+/// always comes with its error
+pub async fn simulate_payload(
+    pool: &DbPool,
+    config: &AppConfig,
+    payload: &str,
+    transaction_id: Option<String>,
+    block_time: Option<i64>,
+) -> Result<SimulationReport> {
+    // Profile images stay inline: blob storage writes could not be rolled back
+    let processor = KProtocolProcessor::new(
+        config.chain,
+        None,
+        config.mentions.clone(),
+        config.sender_rate_limit.clone(),
+        config.vote_fee_weighting,
+    );
+
+    let transaction = Transaction {
+        // Default id derived from the payload, so repeated simulations use the same one
+        transaction_id: transaction_id
+            .unwrap_or_else(|| hex::encode(Sha256::digest(payload.as_bytes()))),
+        payload: Some(hex::encode(payload)),
+        block_time: Some(block_time.unwrap_or_else(|| chrono::Utc::now().timestamp_millis())),
+    };
+    let mut report = SimulationReport {
+        transaction_id: transaction.transaction_id.clone(),
+        block_time: transaction.block_time.unwrap_or_default(),
+        action: None,
+        error: None,
+        indexed: false,
+        writes: Vec::new(),
+    };
+
+    match processor.decode_k_payload(transaction.payload.as_deref().unwrap_or_default()) {
+        Ok(action) => report.action = Some(action),
+        Err(err) => {
+            report.error = Some(err.to_string());
+            return Ok(report);
+        }
+    }
+
+    let mut db_tx = pool.begin().await?;
+    match processor
+        .process_k_transaction_explained(&mut db_tx, &transaction)
+        .await
+    {
+        Err(err) => report.error = Some(err.to_string()),
+        Ok(Some(skipped)) => report.error = Some(skipped),
+        Ok(None) => {
+            // Per table row counts of the current (uncommitted) transaction
+            let rows = sqlx::query(
+                r#"
+                SELECT relname::text AS table_name, n_tup_ins, n_tup_upd, n_tup_del
+                FROM pg_stat_xact_user_tables
+                WHERE n_tup_ins + n_tup_upd + n_tup_del > 0
+                ORDER BY relname
+                "#,
+            )
+            .fetch_all(&mut *db_tx)
+            .await?;
+            report.writes = rows
+                .into_iter()
+                .map(|row| TableWrites {
+                    table: row.get("table_name"),
+                    inserted: row.get("n_tup_ins"),
+                    updated: row.get("n_tup_upd"),
+                    deleted: row.get("n_tup_del"),
+                })
+                .collect();
+            report.indexed = !report.writes.is_empty();
+            if !report.indexed {
+                report.error = Some("The transaction would not write anything".to_string());
+            }
+        }
+    }
+    db_tx.rollback().await?;
+
+    Ok(report)
+}
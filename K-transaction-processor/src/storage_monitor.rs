@@ -0,0 +1,133 @@
+use anyhow::Result;
+use metrics::gauge;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::MonitoringConfig;
+
+const WEEK_MILLIS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+// Snapshots kept in k_storage_snapshots
+const SNAPSHOT_RETENTION_MILLIS: i64 = 90 * 24 * 60 * 60 * 1000;
+
+/// Periodically record row counts and on-disk sizes of the K tables in k_storage_snapshots,
+/// log them with their week-over-week growth and publish them as Prometheus gauges
+pub async fn start_storage_monitor(pool: PgPool, config: MonitoringConfig) {
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(config.storage_report_interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = report_storage(&pool).await {
+            warn!("Storage report failed: {}", e);
+        }
+    }
+}
+
+async fn report_storage(pool: &PgPool) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64;
+
+    // n_live_tup is an estimate maintained by autovacuum: COUNT(*) is too slow on large tables
+    let usage = sqlx::query(
+        r#"
+        SELECT relname::text AS table_name, n_live_tup AS row_count,
+               pg_total_relation_size(relid) AS total_bytes
+        FROM pg_stat_user_tables
+        WHERE schemaname = current_schema() AND relname LIKE 'k\_%'
+        ORDER BY total_bytes DESC, table_name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| {
+        (
+            row.get::<String, _>("table_name"),
+            row.get::<i64, _>("row_count"),
+            row.get::<i64, _>("total_bytes"),
+        )
+    })
+    .collect::<Vec<_>>();
+
+    // Latest snapshot of each table taken at least a week ago
+    let week_ago: HashMap<String, i64> = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (table_name) table_name, total_bytes
+        FROM k_storage_snapshots
+        WHERE taken_at <= $1
+        ORDER BY table_name, taken_at DESC
+        "#,
+    )
+    .bind(now - WEEK_MILLIS)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| (row.get("table_name"), row.get("total_bytes")))
+    .collect();
+
+    let table_names: Vec<&str> = usage.iter().map(|(table, _, _)| table.as_str()).collect();
+    let row_counts: Vec<i64> = usage.iter().map(|(_, rows, _)| *rows).collect();
+    let total_bytes: Vec<i64> = usage.iter().map(|(_, _, bytes)| *bytes).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO k_storage_snapshots (table_name, taken_at, row_count, total_bytes)
+        SELECT table_name, $4, row_count, total_bytes
+        FROM UNNEST($1::text[], $2::bigint[], $3::bigint[]) AS t(table_name, row_count, total_bytes)
+        ON CONFLICT (table_name, taken_at) DO NOTHING
+        "#,
+    )
+    .bind(&table_names)
+    .bind(&row_counts)
+    .bind(&total_bytes)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DELETE FROM k_storage_snapshots WHERE taken_at < $1")
+        .bind(now - SNAPSHOT_RETENTION_MILLIS)
+        .execute(pool)
+        .await?;
+
+    info!(
+        "Storage report: {} in {} K tables",
+        format_bytes(total_bytes.iter().sum()),
+        usage.len()
+    );
+    for (table, rows, bytes) in &usage {
+        gauge!("k_table_rows", "table" => table.clone()).set(*rows as f64);
+        gauge!("k_table_total_bytes", "table" => table.clone()).set(*bytes as f64);
+
+        let growth = match week_ago.get(table) {
+            Some(previous) if *previous > 0 => format!(
+                "{:+.1}% week over week",
+                (*bytes - previous) as f64 * 100.0 / *previous as f64
+            ),
+            _ => "no snapshot from a week ago".to_string(),
+        };
+        info!(
+            "  - {:<20} {:>10} ~{} rows ({})",
+            table,
+            format_bytes(*bytes),
+            rows,
+            growth
+        );
+    }
+
+    Ok(())
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,145 @@
+use crate::chain_params::ChainParams;
+use crate::k_protocol::LIVE_EVENTS_CHANNEL;
+
+/// Longest tenant id, keeping schema and channel names well below the PostgreSQL limit
+const MAX_TENANT_ID_LEN: usize = 32;
+
+/// A K community indexed next to the network's own one (--tenant id=prefix): transactions
+/// whose payload starts with its prefix go to the tables of the schema tenant_<id>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant {
+    pub id: String,
+    /// Payload prefix of the community, in place of the network prefix (k:1:)
+    pub payload_prefix: &'static str,
+}
+
+impl Tenant {
+    /// Parse an `id=prefix` tenant definition
+    pub fn parse(definition: &str) -> Result<Self, String> {
+        let (id, prefix) = definition
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid tenant '{}': expected id=prefix", definition))?;
+        let id = id.trim();
+        if id.is_empty()
+            || id.len() > MAX_TENANT_ID_LEN
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!(
+                "Invalid tenant id '{}': 1 to {} lowercase letters, digits or '_'",
+                id, MAX_TENANT_ID_LEN
+            ));
+        }
+        if prefix.is_empty() {
+            return Err(format!("Tenant '{}' has an empty payload prefix", id));
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            // Parsed once at startup, shared like the prefixes of the networks
+            payload_prefix: Box::leak(prefix.to_string().into_boxed_str()),
+        })
+    }
+
+    /// PostgreSQL schema holding the K tables of the tenant
+    pub fn schema(&self) -> String {
+        format!("tenant_{}", self.id)
+    }
+
+    /// Network parameters with the payload prefix of the tenant
+    pub fn chain(&self, network: ChainParams) -> ChainParams {
+        ChainParams {
+            payload_prefix: self.payload_prefix,
+            ..network
+        }
+    }
+
+    /// Channel of the live events of the tenant
+    pub fn live_events_channel(&self) -> String {
+        format!("{}_{}", LIVE_EVENTS_CHANNEL, self.id)
+    }
+}
+
+/// Reject duplicate tenant ids and payload prefixes starting with one another (network prefix
+/// included), so every K payload belongs to exactly one tenant
+pub fn validate_tenants(tenants: &[Tenant], network: &ChainParams) -> Result<(), String> {
+    for (index, tenant) in tenants.iter().enumerate() {
+        let prefixes = std::iter::once(("the network", network.payload_prefix)).chain(
+            tenants[..index]
+                .iter()
+                .map(|other| (other.id.as_str(), other.payload_prefix)),
+        );
+        for (owner, prefix) in prefixes {
+            if tenant.payload_prefix.starts_with(prefix)
+                || prefix.starts_with(tenant.payload_prefix)
+            {
+                return Err(format!(
+                    "Payload prefix '{}' of tenant '{}' overlaps prefix '{}' of {}",
+                    tenant.payload_prefix, tenant.id, prefix, owner
+                ));
+            }
+        }
+        if tenants[..index].iter().any(|other| other.id == tenant.id) {
+            return Err(format!("Tenant '{}' is defined twice", tenant.id));
+        }
+    }
+    Ok(())
+}
+
+/// Tenant of a decoded K payload: Some(None) for the network's own community, None for a
+/// payload of no configured community
+pub fn tenant_of<'a>(
+    tenants: &'a [Tenant],
+    network: &ChainParams,
+    payload: &[u8],
+) -> Option<Option<&'a Tenant>> {
+    if network.is_k_payload(payload) {
+        return Some(None);
+    }
+    tenants
+        .iter()
+        .find(|tenant| payload.starts_with(tenant.payload_prefix.as_bytes()))
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::TESTNET_10;
+
+    #[test]
+    fn test_parse_tenant() {
+        let tenant = Tenant::parse("guild=kg:1:").unwrap();
+        assert_eq!(tenant.id, "guild");
+        assert_eq!(tenant.payload_prefix, "kg:1:");
+        assert_eq!(tenant.schema(), "tenant_guild");
+        assert_eq!(tenant.live_events_channel(), "k_live_events_guild");
+        assert_eq!(tenant.chain(TESTNET_10).payload_prefix, "kg:1:");
+
+        assert!(Tenant::parse("guild").is_err());
+        assert!(Tenant::parse("Guild=kg:1:").is_err());
+        assert!(Tenant::parse("guild; drop=kg:1:").is_err());
+        assert!(Tenant::parse("guild=").is_err());
+    }
+
+    #[test]
+    fn test_validate_and_route_tenants() {
+        let guild = Tenant::parse("guild=kg:1:").unwrap();
+        let club = Tenant::parse("club=kc:1:").unwrap();
+        let tenants = vec![guild.clone(), club.clone()];
+        assert!(validate_tenants(&tenants, &TESTNET_10).is_ok());
+
+        // k:1:x: would also match the network prefix k:1:
+        let nested = Tenant::parse("nested=k:1:x:").unwrap();
+        assert!(validate_tenants(&[nested], &TESTNET_10).is_err());
+        assert!(validate_tenants(&[guild.clone(), guild.clone()], &TESTNET_10).is_err());
+
+        assert_eq!(tenant_of(&tenants, &TESTNET_10, b"k:1:post:a"), Some(None));
+        assert_eq!(
+            tenant_of(&tenants, &TESTNET_10, b"kc:1:post:a"),
+            Some(Some(&club))
+        );
+        assert_eq!(tenant_of(&tenants, &TESTNET_10, b"kx:1:post:a"), None);
+    }
+}
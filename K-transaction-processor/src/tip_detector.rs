@@ -0,0 +1,92 @@
+// Kaspa script opcodes used by pay-to-pubkey output scripts
+const OP_DATA_32: u8 = 0x20;
+const OP_DATA_33: u8 = 0x21;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKSIG_ECDSA: u8 = 0xab;
+
+/// Output scripts paying to the owner of a compressed public key (02/03 prefix):
+/// the Schnorr P2PK script of its x-only key and the ECDSA P2PK script of the full key
+pub fn recipient_scripts(compressed_pubkey: &[u8]) -> Option<[Vec<u8>; 2]> {
+    if compressed_pubkey.len() != 33 || !matches!(compressed_pubkey[0], 0x02 | 0x03) {
+        return None;
+    }
+
+    let mut schnorr = Vec::with_capacity(34);
+    schnorr.push(OP_DATA_32);
+    schnorr.extend_from_slice(&compressed_pubkey[1..]);
+    schnorr.push(OP_CHECKSIG);
+
+    let mut ecdsa = Vec::with_capacity(35);
+    ecdsa.push(OP_DATA_33);
+    ecdsa.extend_from_slice(compressed_pubkey);
+    ecdsa.push(OP_CHECKSIG_ECDSA);
+
+    Some([schnorr, ecdsa])
+}
+
+/// Total amount (sompi) of the transaction outputs (script_public_key, amount) paying the recipient
+pub fn tip_amount(outputs: &[(Vec<u8>, i64)], recipient_pubkey: &[u8]) -> u64 {
+    let Some(scripts) = recipient_scripts(recipient_pubkey) else {
+        return 0;
+    };
+
+    outputs
+        .iter()
+        .filter(|(script, _)| scripts.contains(script))
+        .map(|(_, amount)| (*amount).max(0) as u64)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pubkey() -> Vec<u8> {
+        hex::decode("02218b3732df2353978154ec5323b745bce9520a5ed506a96de4f4e3dad20dc44f").unwrap()
+    }
+
+    #[test]
+    fn test_recipient_scripts() {
+        let [schnorr, ecdsa] = recipient_scripts(&pubkey()).unwrap();
+        assert_eq!(schnorr.len(), 34);
+        assert_eq!(schnorr[0], 0x20);
+        assert_eq!(&schnorr[1..33], &pubkey()[1..]);
+        assert_eq!(schnorr[33], 0xac);
+        assert_eq!(ecdsa.len(), 35);
+        assert_eq!(&ecdsa[1..34], pubkey().as_slice());
+        assert_eq!(ecdsa[34], 0xab);
+    }
+
+    #[test]
+    fn test_invalid_pubkey_has_no_scripts() {
+        assert!(recipient_scripts(&pubkey()[1..]).is_none());
+        let mut uncompressed_prefix = pubkey();
+        uncompressed_prefix[0] = 0x04;
+        assert!(recipient_scripts(&uncompressed_prefix).is_none());
+    }
+
+    #[test]
+    fn test_tip_amount_sums_outputs_to_recipient() {
+        let [schnorr, ecdsa] = recipient_scripts(&pubkey()).unwrap();
+        let other = recipient_scripts(
+            &hex::decode("030f657a3c77eab35c8f3d8d7bcf4ee1ca3aac7f991d0e3abacdb17e3c5de3b2f7")
+                .unwrap(),
+        )
+        .unwrap()[0]
+            .clone();
+
+        let outputs = vec![
+            (schnorr, 100_000_000),
+            (other, 5_000_000_000),
+            (ecdsa, 50_000_000),
+        ];
+        assert_eq!(tip_amount(&outputs, &pubkey()), 150_000_000);
+    }
+
+    #[test]
+    fn test_tip_amount_without_matching_outputs() {
+        let outputs = vec![(vec![0x20; 34], 100_000_000)];
+        assert_eq!(tip_amount(&outputs, &pubkey()), 0);
+        assert_eq!(tip_amount(&[], &pubkey()), 0);
+    }
+}
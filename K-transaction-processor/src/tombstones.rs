@@ -0,0 +1,461 @@
+use crate::database::DbPool;
+use crate::image_blobs;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use std::path::Path;
+use tracing::info;
+
+/// Version of the tombstone export layout (bumped on incompatible changes)
+pub const TOMBSTONE_FORMAT_VERSION: u32 = 1;
+
+/// Source recorded for tombstones created on this instance
+const LOCAL_SOURCE: &str = "local";
+
+/// A K table holding rows of tombstoned transactions or senders ($1 = BYTEA[] of ids or pubkeys)
+struct PurgeTable {
+    table: &'static str,
+    transaction_filter: &'static str,
+    pubkey_filter: &'static str,
+}
+
+/// Indexed rows removed when a tombstone is added, in deletion order (rows referencing k_contents first)
+const PURGE_TABLES: &[PurgeTable] = &[
+    PurgeTable {
+        table: "k_post_views",
+        transaction_filter: "content_id = ANY($1)",
+        pubkey_filter: "content_id IN (SELECT transaction_id FROM k_contents WHERE sender_pubkey = ANY($1))",
+    },
+    PurgeTable {
+        table: "k_mentions",
+        transaction_filter: "content_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_hashtags",
+        transaction_filter: "content_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_tips",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_article_chunks",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_articles",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_event_rsvps",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_events",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_group_posts",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_group_members",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "member_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_groups",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_votes",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_blocks",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_follows",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_contents",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_mentions_archive",
+        transaction_filter: "content_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_contents_archive",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_contents_cold",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_impersonation_flags",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "suspect_pubkey = ANY($1) OR target_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_profile_history",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+    PurgeTable {
+        table: "k_broadcasts",
+        transaction_filter: "transaction_id = ANY($1)",
+        pubkey_filter: "sender_pubkey = ANY($1)",
+    },
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionTombstone {
+    pub transaction_id: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PubkeyTombstone {
+    pub sender_pubkey: String,
+    pub reason: Option<String>,
+}
+
+/// Tombstone registry exchanged between instances (erasure request ids are never exported)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TombstoneExport {
+    pub format_version: u32,
+    pub network: String,
+    pub exported_at: i64,
+    pub transactions: Vec<TransactionTombstone>,
+    pub pubkeys: Vec<PubkeyTombstone>,
+}
+
+#[derive(Debug, Default)]
+struct ApplyStats {
+    transactions: u64,
+    pubkeys: u64,
+    purged_rows: u64,
+}
+
+/// Tombstone a transaction or a sender on this instance and remove what is already indexed
+pub async fn add(
+    pool: &DbPool,
+    transaction_id: Option<&str>,
+    pubkey: Option<&str>,
+    reason: Option<String>,
+) -> Result<()> {
+    let transactions = match transaction_id {
+        Some(transaction_id) => vec![TransactionTombstone {
+            transaction_id: transaction_id.to_string(),
+            reason: reason.clone(),
+        }],
+        None => Vec::new(),
+    };
+    let pubkeys = match pubkey {
+        Some(pubkey) => vec![PubkeyTombstone {
+            sender_pubkey: pubkey.to_string(),
+            reason,
+        }],
+        None => Vec::new(),
+    };
+
+    let mut tx = pool.begin().await?;
+    let stats = apply(&mut tx, &transactions, &pubkeys, LOCAL_SOURCE).await?;
+    tx.commit().await?;
+
+    info!(
+        "✓ Tombstone added ({} new), {} indexed records removed",
+        stats.transactions + stats.pubkeys,
+        stats.purged_rows
+    );
+    Ok(())
+}
+
+/// Write every tombstone (local and imported) to a JSON file for mirrored instances
+pub async fn export(pool: &DbPool, network: &str, output: &Path) -> Result<()> {
+    let transactions = sqlx::query(
+        "SELECT transaction_id, reason FROM k_tombstones ORDER BY erased_at, transaction_id",
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| TransactionTombstone {
+        transaction_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
+        reason: row.get("reason"),
+    })
+    .collect::<Vec<_>>();
+
+    let pubkeys = sqlx::query(
+        "SELECT sender_pubkey, reason FROM k_pubkey_tombstones ORDER BY tombstoned_at, sender_pubkey",
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| PubkeyTombstone {
+        sender_pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
+        reason: row.get("reason"),
+    })
+    .collect::<Vec<_>>();
+
+    let export = TombstoneExport {
+        format_version: TOMBSTONE_FORMAT_VERSION,
+        network: network.to_string(),
+        exported_at: now_millis()?,
+        transactions,
+        pubkeys,
+    };
+    tokio::fs::write(output, serde_json::to_string_pretty(&export)?).await?;
+
+    info!(
+        "✓ Exported {} transaction and {} sender tombstones to {}",
+        export.transactions.len(),
+        export.pubkeys.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Apply the tombstones exported by an upstream instance in a single transaction
+pub async fn import(pool: &DbPool, network: &str, input: &Path, source: &str) -> Result<()> {
+    let export: TombstoneExport = serde_json::from_slice(&tokio::fs::read(input).await?)
+        .map_err(|e| anyhow::anyhow!("Invalid tombstone file {}: {}", input.display(), e))?;
+    check_export(&export, network)?;
+
+    info!(
+        "Importing {} transaction and {} sender tombstones from '{}'",
+        export.transactions.len(),
+        export.pubkeys.len(),
+        source
+    );
+
+    let mut tx = pool.begin().await?;
+    let stats = apply(&mut tx, &export.transactions, &export.pubkeys, source).await?;
+    tx.commit().await?;
+
+    info!("✓ Tombstone import completed:");
+    info!("  - New transaction tombstones: {}", stats.transactions);
+    info!("  - New sender tombstones: {}", stats.pubkeys);
+    info!("  - Indexed records removed: {}", stats.purged_rows);
+    Ok(())
+}
+
+/// Reject an export of another format version or network than this instance's
+fn check_export(export: &TombstoneExport, network: &str) -> Result<()> {
+    if export.format_version != TOMBSTONE_FORMAT_VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported tombstone format version {} (expected {})",
+            export.format_version,
+            TOMBSTONE_FORMAT_VERSION
+        ));
+    }
+    if export.network != network {
+        return Err(anyhow::anyhow!(
+            "Tombstones were exported from '{}' but this database indexes '{}'",
+            export.network,
+            network
+        ));
+    }
+    Ok(())
+}
+
+/// Record tombstones (existing ones are kept with their original source) and delete the
+/// rows already indexed for them, so the registry and the indexed data stay consistent
+async fn apply(
+    conn: &mut PgConnection,
+    transactions: &[TransactionTombstone],
+    pubkeys: &[PubkeyTombstone],
+    source: &str,
+) -> Result<ApplyStats> {
+    let transaction_ids = transactions
+        .iter()
+        .map(|tombstone| decode_key(&tombstone.transaction_id, 32, "transaction id"))
+        .collect::<Result<Vec<_>>>()?;
+    let transaction_reasons: Vec<Option<String>> = transactions
+        .iter()
+        .map(|tombstone| tombstone.reason.clone())
+        .collect();
+    let sender_pubkeys = pubkeys
+        .iter()
+        .map(|tombstone| decode_key(&tombstone.sender_pubkey, 33, "public key"))
+        .collect::<Result<Vec<_>>>()?;
+    let pubkey_reasons: Vec<Option<String>> = pubkeys
+        .iter()
+        .map(|tombstone| tombstone.reason.clone())
+        .collect();
+    let now = now_millis()?;
+
+    let mut stats = ApplyStats::default();
+
+    if !transaction_ids.is_empty() {
+        stats.transactions = sqlx::query(
+            r#"
+            INSERT INTO k_tombstones (transaction_id, erased_at, source, reason)
+            SELECT transaction_id, $3, $4, reason
+            FROM UNNEST($1::bytea[], $2::text[]) AS t(transaction_id, reason)
+            ON CONFLICT (transaction_id) DO NOTHING
+            "#,
+        )
+        .bind(&transaction_ids)
+        .bind(&transaction_reasons)
+        .bind(now)
+        .bind(source)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    if !sender_pubkeys.is_empty() {
+        stats.pubkeys = sqlx::query(
+            r#"
+            INSERT INTO k_pubkey_tombstones (sender_pubkey, tombstoned_at, source, reason)
+            SELECT sender_pubkey, $3, $4, reason
+            FROM UNNEST($1::bytea[], $2::text[]) AS t(sender_pubkey, reason)
+            ON CONFLICT (sender_pubkey) DO NOTHING
+            "#,
+        )
+        .bind(&sender_pubkeys)
+        .bind(&pubkey_reasons)
+        .bind(now)
+        .bind(source)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+
+    for purge_table in PURGE_TABLES {
+        for (filter, keys) in [
+            (purge_table.transaction_filter, &transaction_ids),
+            (purge_table.pubkey_filter, &sender_pubkeys),
+        ] {
+            if keys.is_empty() {
+                continue;
+            }
+            if purge_table.table == "k_broadcasts" {
+                image_blobs::release(&mut *conn, filter, keys, now).await?;
+            }
+            let deleted = sqlx::query(&format!(
+                "DELETE FROM {} WHERE {}",
+                purge_table.table, filter
+            ))
+            .bind(keys)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+            if deleted > 0 {
+                info!("  - Deleted {} records from {}", deleted, purge_table.table);
+            }
+            stats.purged_rows += deleted;
+        }
+    }
+
+    Ok(stats)
+}
+
+fn decode_key(value: &str, expected_len: usize, kind: &str) -> Result<Vec<u8>> {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == expected_len => Ok(bytes),
+        _ => Err(anyhow::anyhow!(
+            "Invalid {} '{}': expected {} hex characters",
+            kind,
+            value,
+            expected_len * 2
+        )),
+    }
+}
+
+fn now_millis() -> Result<i64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format_version: u32, network: &str) -> TombstoneExport {
+        TombstoneExport {
+            format_version,
+            network: network.to_string(),
+            exported_at: 0,
+            transactions: vec![TransactionTombstone {
+                transaction_id: "ab".repeat(32),
+                reason: Some("erasure".to_string()),
+            }],
+            pubkeys: vec![PubkeyTombstone {
+                sender_pubkey: format!("02{}", "cd".repeat(32)),
+                reason: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_decode_key() {
+        assert_eq!(
+            decode_key(&"ab".repeat(32), 32, "transaction id").unwrap(),
+            vec![0xab; 32]
+        );
+        assert_eq!(
+            decode_key(&"AB".repeat(33), 33, "public key").unwrap(),
+            vec![0xab; 33]
+        );
+        assert!(decode_key(&"ab".repeat(33), 32, "transaction id").is_err());
+        assert!(decode_key(&"zz".repeat(32), 32, "transaction id").is_err());
+        assert!(decode_key("", 32, "transaction id").is_err());
+    }
+
+    #[test]
+    fn test_check_export() {
+        assert!(check_export(&export(TOMBSTONE_FORMAT_VERSION, "mainnet"), "mainnet").is_ok());
+        assert!(check_export(&export(TOMBSTONE_FORMAT_VERSION + 1, "mainnet"), "mainnet").is_err());
+        assert!(check_export(&export(TOMBSTONE_FORMAT_VERSION, "testnet-10"), "mainnet").is_err());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let json = serde_json::to_string(&export(TOMBSTONE_FORMAT_VERSION, "mainnet")).unwrap();
+        let imported: TombstoneExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(imported.format_version, TOMBSTONE_FORMAT_VERSION);
+        assert_eq!(imported.network, "mainnet");
+        assert_eq!(imported.transactions[0].transaction_id, "ab".repeat(32));
+        assert_eq!(imported.transactions[0].reason.as_deref(), Some("erasure"));
+        assert_eq!(imported.pubkeys[0].reason, None);
+    }
+
+    #[test]
+    fn test_purge_order() {
+        let position = |table: &str| {
+            PURGE_TABLES
+                .iter()
+                .position(|purge_table| purge_table.table == table)
+                .unwrap()
+        };
+        // Rows referencing contents go before the contents themselves
+        for table in ["k_post_views", "k_mentions", "k_hashtags", "k_votes"] {
+            assert!(position(table) < position("k_contents"), "{}", table);
+        }
+        // Image blobs are released from the broadcasts, which therefore go last
+        assert_eq!(position("k_broadcasts"), PURGE_TABLES.len() - 1);
+    }
+}
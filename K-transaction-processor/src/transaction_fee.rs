@@ -0,0 +1,30 @@
+/// Fee (sompi) burned by a transaction: its input amounts minus its output amounts.
+/// None when an input amount is unknown (previous outpoint not resolved by the Kaspa indexer)
+pub fn transaction_fee(input_amounts: &[Option<i64>], output_amounts: &[i64]) -> Option<u64> {
+    let inputs = input_amounts.iter().copied().sum::<Option<i64>>()?;
+    let outputs: i64 = output_amounts.iter().sum();
+    inputs
+        .checked_sub(outputs)
+        .filter(|fee| *fee >= 0)
+        .map(|fee| fee as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_fee() {
+        assert_eq!(
+            transaction_fee(&[Some(150_000), Some(50_000)], &[120_000, 77_000]),
+            Some(3_000)
+        );
+        assert_eq!(transaction_fee(&[Some(100_000)], &[100_000]), Some(0));
+    }
+
+    #[test]
+    fn test_unresolved_or_invalid_amounts() {
+        assert_eq!(transaction_fee(&[Some(100_000), None], &[50_000]), None);
+        assert_eq!(transaction_fee(&[Some(100_000)], &[150_000]), None);
+    }
+}
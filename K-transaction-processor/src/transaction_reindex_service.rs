@@ -126,3 +126,10 @@ pub async fn start_reindex_service(_config: AppConfig, pool: PgPool) {
     // Run the service (this will loop forever)
     service.run().await;
 }
+
+/// Run a single reindex cycle in the foreground (`reindex` subcommand)
+pub async fn run_reindex_once(pool: PgPool) {
+    // Interval is unused: only one cycle runs
+    let service = TransactionReindexService::new(pool, 0);
+    service.run_reindex_cycle().await;
+}
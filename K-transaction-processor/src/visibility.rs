@@ -0,0 +1,65 @@
+use anyhow::Result;
+use metrics::gauge;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Interval between visibility watermark updates
+const VISIBILITY_PROMOTE_INTERVAL_SECS: u64 = 1;
+
+/// Keep k_meta.visible_before at the newest block time of the transactions table minus
+/// `delay_ms`, so K-webserver lists content only once its block is that deep in the chain.
+/// With a zero delay the watermark is cleared once and everything stays visible
+pub async fn start_visibility_promoter(pool: PgPool, delay_ms: i64) {
+    if delay_ms == 0 {
+        if let Err(e) = clear_visibility_watermark(&pool).await {
+            warn!("Failed to clear the visibility watermark: {}", e);
+        }
+        return;
+    }
+
+    info!(
+        "Content becomes visible {} ms of block time after its block",
+        delay_ms
+    );
+    let mut interval = tokio::time::interval(Duration::from_secs(VISIBILITY_PROMOTE_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        match promote(&pool, delay_ms).await {
+            Ok(Some(visible_before)) => {
+                gauge!("k_visibility_watermark_block_time").set(visible_before as f64);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Visibility watermark update failed: {}", e),
+        }
+    }
+}
+
+/// Move the watermark forward (never back, so a pruned transactions table cannot hide content again)
+async fn promote(pool: &PgPool, delay_ms: i64) -> Result<Option<i64>> {
+    let visible_before: Option<i64> = sqlx::query_scalar(
+        r#"
+        UPDATE k_meta
+        SET visible_before = GREATEST(
+            COALESCE(visible_before, 0),
+            (SELECT MAX(block_time) FROM transactions) - $1
+        )
+        WHERE id = 1 AND EXISTS (SELECT 1 FROM transactions)
+        RETURNING visible_before
+        "#,
+    )
+    .bind(delay_ms)
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+    Ok(visible_before)
+}
+
+async fn clear_visibility_watermark(pool: &PgPool) -> Result<()> {
+    sqlx::query("UPDATE k_meta SET visible_before = NULL WHERE id = 1")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
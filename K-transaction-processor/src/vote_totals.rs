@@ -0,0 +1,75 @@
+use anyhow::Result;
+use metrics::counter;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Periodically reconcile k_vote_totals with k_votes. Vote inserts keep the totals current;
+/// this corrects them after votes are deleted by K-database-cleaner or K-content-remover
+pub async fn start_vote_totals_job(pool: PgPool, interval_secs: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = reconcile_vote_totals(&pool).await {
+            warn!("Vote totals reconciliation failed: {}", e);
+        }
+    }
+}
+
+async fn reconcile_vote_totals(pool: &PgPool) -> Result<()> {
+    let start = std::time::Instant::now();
+
+    // Only totals that differ from k_votes are rewritten, totals of posts without votes removed
+    let row = sqlx::query(
+        r#"
+        WITH actual AS (
+            SELECT post_id,
+                   COUNT(*) FILTER (WHERE vote = 'upvote') AS up_votes,
+                   COUNT(*) FILTER (WHERE vote = 'downvote') AS down_votes,
+                   COALESCE(SUM(fee) FILTER (WHERE vote = 'upvote'), 0) AS up_votes_fee,
+                   COALESCE(SUM(fee) FILTER (WHERE vote = 'downvote'), 0) AS down_votes_fee
+            FROM k_votes
+            GROUP BY post_id
+        ),
+        corrected AS (
+            INSERT INTO k_vote_totals (post_id, up_votes, down_votes, up_votes_fee, down_votes_fee)
+            SELECT post_id, up_votes, down_votes, up_votes_fee, down_votes_fee FROM actual
+            ON CONFLICT (post_id) DO UPDATE SET
+                up_votes = EXCLUDED.up_votes,
+                down_votes = EXCLUDED.down_votes,
+                up_votes_fee = EXCLUDED.up_votes_fee,
+                down_votes_fee = EXCLUDED.down_votes_fee
+            WHERE (k_vote_totals.up_votes, k_vote_totals.down_votes,
+                   k_vote_totals.up_votes_fee, k_vote_totals.down_votes_fee)
+                IS DISTINCT FROM (EXCLUDED.up_votes, EXCLUDED.down_votes,
+                                  EXCLUDED.up_votes_fee, EXCLUDED.down_votes_fee)
+            RETURNING 1
+        ),
+        removed AS (
+            DELETE FROM k_vote_totals vt
+            WHERE NOT EXISTS (SELECT 1 FROM actual a WHERE a.post_id = vt.post_id)
+            RETURNING 1
+        )
+        SELECT (SELECT COUNT(*) FROM corrected) AS corrected,
+               (SELECT COUNT(*) FROM removed) AS removed
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let corrected: i64 = row.get("corrected");
+    let removed: i64 = row.get("removed");
+    counter!("k_vote_totals_corrected_total").increment((corrected + removed) as u64);
+
+    if corrected > 0 || removed > 0 {
+        info!(
+            "Vote totals reconciled in {:.2}s: {} corrected, {} removed",
+            start.elapsed().as_secs_f64(),
+            corrected,
+            removed
+        );
+    }
+    Ok(())
+}
@@ -1,16 +1,43 @@
+use crate::blob_storage::BlobStorage;
+use crate::circuit_breaker::{self, CircuitBreaker};
 use crate::config::AppConfig;
-use crate::database::{DbPool, Transaction, fetch_transaction};
+use crate::database::{
+    DbPool, Transaction, advance_processed_watermark, clear_write_intents, fetch_transaction,
+    insert_dead_letter, lock_shared_against_purge, record_write_intents,
+};
+use crate::in_flight::InFlightBatches;
 use crate::k_protocol::KProtocolProcessor;
+use crate::retry_policy::{ErrorClass, ErrorStats, backoff_delay, classify_error, is_outage_error};
+use crate::tenants::{self, Tenant};
 use anyhow::Result;
-use tokio::sync::mpsc;
+use sqlx::PgConnection;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
 use tracing::{error, info, warn};
 
+/// Where the K transactions of one community are indexed
+struct Route {
+    // None = the network's own community
+    tenant: Option<Tenant>,
+    // Resolves the K tables in the schema of the community
+    db_pool: DbPool,
+    k_processor: KProtocolProcessor,
+}
+
 pub struct Worker {
     id: usize,
     receiver: mpsc::UnboundedReceiver<String>,
     db_pool: DbPool,
     config: AppConfig,
-    k_processor: KProtocolProcessor,
+    // The network's own community first, then one route per --tenant
+    routes: Vec<Route>,
+    error_stats: Arc<ErrorStats>,
+    // k_meta.maintenance_mode, published by the maintenance watcher
+    maintenance: watch::Receiver<bool>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    // Batches being committed by the workers of the pool, holding back the processed watermark
+    in_flight: Arc<InFlightBatches>,
 }
 
 impl Worker {
@@ -18,15 +45,45 @@ impl Worker {
         id: usize,
         receiver: mpsc::UnboundedReceiver<String>,
         db_pool: DbPool,
+        tenant_pools: &[(Tenant, DbPool)],
         config: AppConfig,
+        blob_storage: Option<Arc<BlobStorage>>,
+        error_stats: Arc<ErrorStats>,
+        maintenance: watch::Receiver<bool>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        in_flight: Arc<InFlightBatches>,
     ) -> Self {
-        let k_processor = KProtocolProcessor::new(db_pool.clone());
+        let k_processor = |blob_storage: Option<Arc<BlobStorage>>| {
+            KProtocolProcessor::new(
+                config.chain,
+                blob_storage,
+                config.mentions.clone(),
+                config.sender_rate_limit.clone(),
+                config.vote_fee_weighting,
+            )
+        };
+        let mut routes = vec![Route {
+            tenant: None,
+            db_pool: db_pool.clone(),
+            k_processor: k_processor(blob_storage),
+        }];
+        // Tenants keep profile images in the database: the image collector only knows the
+        // references of the network's own schema
+        routes.extend(tenant_pools.iter().map(|(tenant, tenant_pool)| Route {
+            tenant: Some(tenant.clone()),
+            db_pool: tenant_pool.clone(),
+            k_processor: k_processor(None).for_tenant(tenant),
+        }));
         Self {
             id,
             receiver,
             db_pool,
             config,
-            k_processor,
+            routes,
+            error_stats,
+            maintenance,
+            circuit_breaker,
+            in_flight,
         }
     }
 
@@ -34,72 +91,339 @@ impl Worker {
         info!("Worker {} started", self.id);
 
         while let Some(transaction_id) = self.receiver.recv().await {
-            //info!("Worker {} received notification for transaction: {}", self.id, transaction_id);
+            // Notifications keep queueing up in memory while maintenance mode is enabled
+            self.wait_for_maintenance_end().await;
+            // Likewise while the database is down
+            self.wait_for_circuit_close().await;
 
-            self.process_transaction(transaction_id).await;
+            // Drain already queued notifications into the same batch
+            let mut batch = vec![transaction_id];
+            while batch.len() < self.config.processing.batch_size {
+                match self.receiver.try_recv() {
+                    Ok(transaction_id) => batch.push(transaction_id),
+                    Err(_) => break,
+                }
+            }
+
+            self.process_batch(batch).await;
         }
 
         info!("Worker {} stopped", self.id);
     }
 
-    async fn process_transaction(&self, transaction_id: String) {
-        //info!("Worker {} processing transaction: {}", self.id, transaction_id);
-
-        match self.fetch_and_process_transaction(&transaction_id).await {
-            Ok(Some(transaction)) => {
-                // Process K protocol if payload starts with k:1:
-                if let Some(ref payload_hex) = transaction.payload {
-                    if let Ok(payload_bytes) = hex::decode(payload_hex) {
-                        if let Ok(payload_str) = std::str::from_utf8(&payload_bytes) {
-                            if payload_str.starts_with("k:1:") {
-                                //info!("Worker {} - Processing K protocol transaction: {}", self.id, transaction_id);
-                                if let Err(k_err) =
-                                    self.k_processor.process_k_transaction(&transaction).await
-                                {
-                                    error!(
-                                        "Worker {} - Error processing K protocol transaction {}: {}",
-                                        self.id, transaction_id, k_err
-                                    );
-                                }
-                            } else {
-                                info!(
-                                    "Worker {} - Transaction {} does not contain K protocol data",
-                                    self.id, transaction_id
-                                );
-                            }
-                        }
+    /// Block while read-only maintenance mode is enabled
+    async fn wait_for_maintenance_end(&mut self) {
+        if !*self.maintenance.borrow() {
+            return;
+        }
+
+        info!("Worker {} paused for maintenance mode", self.id);
+        // Err means the watcher is gone: resume rather than stall forever
+        let _ = self.maintenance.wait_for(|enabled| !enabled).await;
+        info!("Worker {} resumed after maintenance mode", self.id);
+    }
+
+    /// Block while the circuit breaker is open
+    async fn wait_for_circuit_close(&self) {
+        if !self.circuit_breaker.is_open() {
+            return;
+        }
+
+        info!("Worker {} paused until the database is reachable", self.id);
+        self.circuit_breaker.wait_until_closed().await;
+        info!("Worker {} resumed", self.id);
+    }
+
+    /// Feed the outcome of a database round trip to the circuit breaker
+    fn record_outcome<T>(&self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.circuit_breaker.record_success(),
+            Err(e) => self.circuit_breaker.record_failure(e),
+        }
+    }
+
+    async fn process_batch(&self, transaction_ids: Vec<String>) {
+        //info!("Worker {} processing batch of {} transactions", self.id, transaction_ids.len());
+
+        // K transactions of each route, committed in one batch per route
+        let mut k_transactions: Vec<Vec<Transaction>> =
+            self.routes.iter().map(|_| Vec::new()).collect();
+
+        for transaction_id in transaction_ids {
+            match self.fetch_and_process_transaction(&transaction_id).await {
+                Ok(Some(transaction)) => {
+                    // Process K protocol if payload starts with k:1: (or a tenant prefix)
+                    if let Some(route) = self.route_of(&transaction) {
+                        k_transactions[route].push(transaction);
+                    } else if transaction.payload.is_some() {
+                        info!(
+                            "Worker {} - Transaction {} does not contain K protocol data",
+                            self.id, transaction_id
+                        );
                     }
                 }
+                Ok(None) => {
+                    warn!(
+                        "Worker {} - Transaction {} not found in database",
+                        self.id, transaction_id
+                    );
+                    // Nothing left to roll forward for an intent recovered at startup
+                    self.clear_intents(&[transaction_id.as_str()]).await;
+                }
+                Err(e) => {
+                    error!(
+                        "Worker {} - Error processing transaction {}: {}",
+                        self.id, transaction_id, e
+                    );
+                    self.handle_failure(&transaction_id, &e).await;
+                }
+            }
+        }
+
+        for (route, k_transactions) in self.routes.iter().zip(k_transactions) {
+            if k_transactions.is_empty() {
+                continue;
             }
-            Ok(None) => {
+
+            let result = self.commit_batch(route, &k_transactions).await;
+            self.record_outcome(&result);
+            match result {
+                Ok(transient_failures) => {
+                    for (transaction_id, _) in transient_failures {
+                        self.retry_or_log(&transaction_id).await;
+                    }
+                }
+                Err(e) => {
+                    let class = classify_error(&e);
+                    self.error_stats.record(class);
+                    error!(
+                        "Worker {} - Failed to commit batch of {} transactions ({}): {}",
+                        self.id,
+                        k_transactions.len(),
+                        class.as_str(),
+                        e
+                    );
+
+                    // Nothing of the batch was committed: retry records one by one
+                    // (isolates the failing record, which gets dead-lettered on its own)
+                    for transaction in &k_transactions {
+                        self.retry_or_log(&transaction.transaction_id).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Route of the community whose payload prefix starts the transaction payload
+    fn route_of(&self, transaction: &Transaction) -> Option<usize> {
+        let payload_bytes = hex::decode(transaction.payload.as_ref()?).ok()?;
+        let tenant = tenants::tenant_of(&self.config.tenants, &self.config.chain, &payload_bytes)?;
+        self.routes
+            .iter()
+            .position(|route| route.tenant.as_ref() == tenant)
+    }
+
+    /// Process K transactions in a single database transaction
+    /// Each record runs inside its own savepoint, so a malformed record only rolls back itself.
+    /// Records failing permanently are dead-lettered in the same transaction;
+    /// records failing with a transient error are returned to be retried.
+    /// The batch is recorded in k_write_intents first and its intents stay there until each
+    /// record is committed or dead-lettered, so a crash meanwhile is rolled forward at startup.
+    /// The processed watermark only advances up to the oldest batch still in flight on the
+    /// other workers
+    async fn commit_batch(
+        &self,
+        route: &Route,
+        transactions: &[Transaction],
+    ) -> Result<Vec<(String, anyhow::Error)>> {
+        let transaction_ids: Vec<&str> = transactions
+            .iter()
+            .map(|transaction| transaction.transaction_id.as_str())
+            .collect();
+        record_write_intents(&self.db_pool, self.id, &transaction_ids).await?;
+
+        // Other workers hold the watermark below this batch until it is committed
+        if let Some(oldest) = transactions.iter().filter_map(|t| t.block_time).min() {
+            self.in_flight.begin(self.id, oldest);
+        }
+        let written = self.write_batch(route, transactions).await;
+        let watermark = self.in_flight.finish(
+            self.id,
+            written.as_ref().ok().and_then(|(_, committed)| *committed),
+        );
+        let (transient_failures, _) = written?;
+
+        // Acknowledge the batch only once the commit is durable
+        if let Some(block_time) = watermark {
+            if let Err(e) = advance_processed_watermark(&route.db_pool, block_time).await {
                 warn!(
-                    "Worker {} - Transaction {} not found in database",
-                    self.id, transaction_id
+                    "Worker {} - Failed to advance processed watermark to {}: {}",
+                    self.id, block_time, e
                 );
             }
-            Err(e) => {
+        }
+
+        // Records failing transiently keep their intent until retried or dead-lettered
+        let resolved: Vec<&str> = transaction_ids
+            .into_iter()
+            .filter(|transaction_id| {
+                !transient_failures
+                    .iter()
+                    .any(|(failed_id, _)| failed_id == transaction_id)
+            })
+            .collect();
+        self.clear_intents(&resolved).await;
+
+        Ok(transient_failures)
+    }
+
+    /// Write the records of commit_batch and commit them, returning the records failing
+    /// transiently and the block time up to which the batch is committed: its newest record,
+    /// but not past a record failing transiently (None when nothing was committed)
+    async fn write_batch(
+        &self,
+        route: &Route,
+        transactions: &[Transaction],
+    ) -> Result<(Vec<(String, anyhow::Error)>, Option<i64>)> {
+        let mut db_tx = route.db_pool.begin().await?;
+        // Counters and references read by the batch stay valid: no purge until commit
+        lock_shared_against_purge(&mut *db_tx).await?;
+        let mut max_block_time: Option<i64> = None;
+        let mut oldest_failure: Option<i64> = None;
+        let mut transient_failures = Vec::new();
+
+        for transaction in transactions {
+            begin_record(&mut db_tx).await?;
+            let result = route
+                .k_processor
+                .process_k_transaction(&mut db_tx, transaction)
+                .await;
+            end_record(&mut db_tx, &result).await?;
+
+            if let Err(k_err) = result {
+                let class = classify_error(&k_err);
+                self.error_stats.record(class);
                 error!(
-                    "Worker {} - Error processing transaction {}: {}",
-                    self.id, transaction_id, e
+                    "Worker {} - Error processing K protocol transaction {} ({}): {}",
+                    self.id,
+                    transaction.transaction_id,
+                    class.as_str(),
+                    k_err
                 );
 
-                if let Err(retry_err) = self.retry_transaction(&transaction_id).await {
+                if class == ErrorClass::Transient {
+                    oldest_failure = oldest_failure
+                        .into_iter()
+                        .chain(transaction.block_time)
+                        .min();
+                    transient_failures.push((transaction.transaction_id.clone(), k_err));
+                    continue;
+                }
+
+                insert_dead_letter(
+                    &mut *db_tx,
+                    &transaction.transaction_id,
+                    class.as_str(),
+                    &k_err.to_string(),
+                    0,
+                )
+                .await?;
+                self.error_stats.record_dead_letter();
+            }
+
+            max_block_time = max_block_time.max(transaction.block_time);
+        }
+
+        db_tx.commit().await?;
+
+        let committed = match (max_block_time, oldest_failure) {
+            (Some(newest), Some(failure)) => Some(newest.min(failure)),
+            (newest, _) => newest,
+        };
+        Ok((transient_failures, committed))
+    }
+
+    /// Remove write intents, which are only rolled forward again at startup if this fails
+    async fn clear_intents(&self, transaction_ids: &[&str]) {
+        if transaction_ids.is_empty() {
+            return;
+        }
+        if let Err(e) = clear_write_intents(&self.db_pool, transaction_ids).await {
+            warn!(
+                "Worker {} - Failed to clear {} write intents: {}",
+                self.id,
+                transaction_ids.len(),
+                e
+            );
+        }
+    }
+
+    /// Route a failed transaction by error class: retry transient errors, dead-letter permanent ones
+    async fn handle_failure(&self, transaction_id: &str, err: &anyhow::Error) {
+        let class = classify_error(err);
+        self.error_stats.record(class);
+
+        match class {
+            ErrorClass::Transient => self.retry_or_log(transaction_id).await,
+            ErrorClass::Permanent => {
+                if let Err(dl_err) = self
+                    .dead_letter(transaction_id, class, &err.to_string(), 0)
+                    .await
+                {
                     error!(
-                        "Worker {} - Failed to retry transaction {}: {}",
-                        self.id, transaction_id, retry_err
+                        "Worker {} - Failed to dead-letter transaction {}: {}",
+                        self.id, transaction_id, dl_err
                     );
                 }
             }
         }
     }
 
+    async fn retry_or_log(&self, transaction_id: &str) {
+        if let Err(retry_err) = self.retry_transaction(transaction_id).await {
+            error!(
+                "Worker {} - Failed to retry transaction {}: {}",
+                self.id, transaction_id, retry_err
+            );
+        }
+    }
+
+    async fn dead_letter(
+        &self,
+        transaction_id: &str,
+        class: ErrorClass,
+        error_message: &str,
+        attempts: u32,
+    ) -> Result<()> {
+        error!(
+            "Worker {} - Sending transaction {} to dead-letter ({}): {}",
+            self.id,
+            transaction_id,
+            class.as_str(),
+            error_message
+        );
+        insert_dead_letter(
+            &self.db_pool,
+            transaction_id,
+            class.as_str(),
+            error_message,
+            attempts,
+        )
+        .await?;
+        self.error_stats.record_dead_letter();
+        self.clear_intents(&[transaction_id]).await;
+        Ok(())
+    }
+
     async fn fetch_and_process_transaction(
         &self,
         transaction_id: &str,
     ) -> Result<Option<Transaction>> {
         //info!("Worker {} received transaction data for processing: {}", self.id, transaction_id);
 
-        fetch_transaction(&self.db_pool, transaction_id).await
+        let result = fetch_transaction(&self.db_pool, transaction_id).await;
+        self.record_outcome(&result);
+        result
     }
 
     /*
@@ -127,55 +451,95 @@ impl Worker {
 
     }*/
 
+    /// Whether a failed attempt is due to the database outage the circuit breaker is open for
+    /// (such attempts are given back)
+    fn opened_circuit(&self, err: &anyhow::Error) -> bool {
+        is_outage_error(err) && self.circuit_breaker.is_open()
+    }
+
     async fn retry_transaction(&self, transaction_id: &str) -> Result<()> {
-        for attempt in 1..=self.config.processing.retry_attempts {
+        let mut last_error = String::new();
+        let mut attempt = 0;
+
+        while attempt < self.config.processing.retry_attempts {
+            // No attempt is spent while the database is down
+            self.wait_for_circuit_close().await;
+            attempt += 1;
+
             warn!(
                 "Worker {} - Retry attempt {} for transaction {}",
                 self.id, attempt, transaction_id
             );
+            self.error_stats.record_retry();
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(
+            tokio::time::sleep(backoff_delay(
                 self.config.processing.retry_delay_ms,
+                attempt,
             ))
             .await;
 
-            match self.fetch_and_process_transaction(transaction_id).await {
+            let result = match self.fetch_and_process_transaction(transaction_id).await {
                 Ok(Some(transaction)) => {
-                    info!(
-                        "Worker {} - Retry successful for transaction {}",
-                        self.id, transaction_id
-                    );
-                    // Process K protocol if payload starts with k:1:
-                    if let Some(ref payload_hex) = transaction.payload {
-                        if let Ok(payload_bytes) = hex::decode(payload_hex) {
-                            if let Ok(payload_str) = std::str::from_utf8(&payload_bytes) {
-                                if payload_str.starts_with("k:1:") {
-                                    //info!("Worker {} - Processing K protocol transaction on retry: {}", self.id, transaction_id);
-                                    if let Err(k_err) =
-                                        self.k_processor.process_k_transaction(&transaction).await
-                                    {
-                                        error!(
-                                            "Worker {} - Error processing K protocol transaction on retry {}: {}",
-                                            self.id, transaction_id, k_err
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    return Ok(());
+                    // Process K protocol if payload starts with k:1: (or a tenant prefix)
+                    let Some(route) = self.route_of(&transaction) else {
+                        return Ok(());
+                    };
+
+                    //info!("Worker {} - Processing K protocol transaction on retry: {}", self.id, transaction_id);
+                    let result = self
+                        .commit_batch(&self.routes[route], std::slice::from_ref(&transaction))
+                        .await;
+                    self.record_outcome(&result);
+                    result.map(|transient_failures| transient_failures.into_iter().next())
                 }
                 Ok(None) => {
                     warn!(
                         "Worker {} - Transaction {} still not found on retry {}",
                         self.id, transaction_id, attempt
                     );
+                    last_error = "transaction not found".to_string();
+                    continue;
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(None) => {
+                    info!(
+                        "Worker {} - Retry completed for transaction {}",
+                        self.id, transaction_id
+                    );
+                    return Ok(());
+                }
+                // Still failing with a transient error (already classified and counted)
+                Ok(Some((_, e))) => {
+                    last_error = e.to_string();
+                    if self.opened_circuit(&e) {
+                        attempt -= 1;
+                    }
                 }
                 Err(e) => {
+                    let class = classify_error(&e);
+                    self.error_stats.record(class);
                     error!(
-                        "Worker {} - Retry {} failed for transaction {}: {}",
-                        self.id, attempt, transaction_id, e
+                        "Worker {} - Retry {} failed for transaction {} ({}): {}",
+                        self.id,
+                        attempt,
+                        transaction_id,
+                        class.as_str(),
+                        e
                     );
+
+                    // No point spending the remaining retry budget on a permanent failure
+                    if class == ErrorClass::Permanent {
+                        return self
+                            .dead_letter(transaction_id, class, &e.to_string(), attempt)
+                            .await;
+                    }
+                    last_error = e.to_string();
+                    if self.opened_circuit(&e) {
+                        attempt -= 1;
+                    }
                 }
             }
         }
@@ -184,32 +548,121 @@ impl Worker {
             "Worker {} - All retry attempts exhausted for transaction {}",
             self.id, transaction_id
         );
-        Ok(())
+        self.dead_letter(
+            transaction_id,
+            ErrorClass::Transient,
+            &format!("retry attempts exhausted: {}", last_error),
+            self.config.processing.retry_attempts,
+        )
+        .await
     }
 }
 
+/// Start the savepoint of the next record of a batch
+async fn begin_record(conn: &mut PgConnection) -> Result<()> {
+    sqlx::query("SAVEPOINT k_record")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Keep the writes of a processed record, or roll back those of a failed one alone (which also
+/// clears the aborted state a failed statement leaves the batch transaction in)
+async fn end_record<T>(conn: &mut PgConnection, result: &Result<T>) -> Result<()> {
+    let statement = match result {
+        Ok(_) => "RELEASE SAVEPOINT k_record",
+        Err(_) => "ROLLBACK TO SAVEPOINT k_record",
+    };
+    sqlx::query(statement).execute(&mut *conn).await?;
+    Ok(())
+}
+
+// Interval between error counter summaries in the log
+const ERROR_STATS_LOG_INTERVAL_SECS: u64 = 300;
+
 pub struct WorkerPool {
     workers: Vec<Worker>,
+    error_stats: Arc<ErrorStats>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    db_pool: DbPool,
+    circuit_breaker_probe_interval: Duration,
 }
 
 impl WorkerPool {
     pub fn new(
         worker_receivers: Vec<mpsc::UnboundedReceiver<String>>,
         db_pool: DbPool,
+        tenant_pools: Vec<(Tenant, DbPool)>,
         config: AppConfig,
+        blob_storage: Option<Arc<BlobStorage>>,
+        maintenance: watch::Receiver<bool>,
     ) -> Self {
+        let error_stats = Arc::new(ErrorStats::default());
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            config.processing.circuit_breaker_threshold,
+        ));
+        let circuit_breaker_probe_interval =
+            Duration::from_secs(config.processing.circuit_breaker_probe_interval_secs);
+        let in_flight = Arc::new(InFlightBatches::default());
         let workers = worker_receivers
             .into_iter()
             .enumerate()
-            .map(|(id, receiver)| Worker::new(id, receiver, db_pool.clone(), config.clone()))
+            .map(|(id, receiver)| {
+                Worker::new(
+                    id,
+                    receiver,
+                    db_pool.clone(),
+                    &tenant_pools,
+                    config.clone(),
+                    blob_storage.clone(),
+                    error_stats.clone(),
+                    maintenance.clone(),
+                    circuit_breaker.clone(),
+                    in_flight.clone(),
+                )
+            })
             .collect();
 
-        Self { workers }
+        Self {
+            workers,
+            error_stats,
+            circuit_breaker,
+            db_pool,
+            circuit_breaker_probe_interval,
+        }
     }
 
     pub async fn start(self) {
         info!("Starting worker pool with {} workers", self.workers.len());
 
+        // Periodically log per-class error counters
+        let error_stats = self.error_stats.clone();
+        tokio::spawn(async move {
+            let mut last_snapshot = (0, 0, 0, 0);
+            loop {
+                tokio::time::sleep(tokio::time::Duration::from_secs(
+                    ERROR_STATS_LOG_INTERVAL_SECS,
+                ))
+                .await;
+
+                let snapshot = error_stats.snapshot();
+                if snapshot != last_snapshot {
+                    let (transient, permanent, retried, dead_lettered) = snapshot;
+                    info!(
+                        "Processing errors - transient: {}, permanent: {}, retries: {}, dead-lettered: {}",
+                        transient, permanent, retried, dead_lettered
+                    );
+                    last_snapshot = snapshot;
+                }
+            }
+        });
+
+        circuit_breaker::spawn_probe(
+            self.circuit_breaker.clone(),
+            self.db_pool.clone(),
+            self.circuit_breaker_probe_interval,
+        );
+
         let mut handles = Vec::new();
 
         for worker in self.workers {
@@ -228,3 +681,74 @@ impl WorkerPool {
         info!("Worker pool stopped");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Connection, Executor};
+
+    /// Connection to the scratch database of K_TEST_DATABASE_URL (None: the test is skipped)
+    async fn test_connection() -> Option<PgConnection> {
+        let Ok(database_url) = std::env::var("K_TEST_DATABASE_URL") else {
+            eprintln!("K_TEST_DATABASE_URL is not set, skipping the database test");
+            return None;
+        };
+        Some(PgConnection::connect(&database_url).await.unwrap())
+    }
+
+    /// Process `ids` as the records of a batch, each inserting its id then failing if listed in
+    /// `failing`, and return the ids committed
+    async fn write_records(conn: &mut PgConnection, ids: &[i32], failing: &[i32]) -> Vec<i32> {
+        conn.execute("CREATE TEMP TABLE IF NOT EXISTS k_test_records (id INT PRIMARY KEY)")
+            .await
+            .unwrap();
+        conn.execute("TRUNCATE k_test_records").await.unwrap();
+
+        let mut db_tx = conn.begin().await.unwrap();
+        for id in ids {
+            begin_record(&mut db_tx).await.unwrap();
+            let result = async {
+                sqlx::query("INSERT INTO k_test_records (id) VALUES ($1)")
+                    .bind(id)
+                    .execute(&mut *db_tx)
+                    .await?;
+                if failing.contains(id) {
+                    anyhow::bail!("record {} is malformed", id);
+                }
+                Ok::<_, anyhow::Error>(())
+            }
+            .await;
+            end_record(&mut db_tx, &result).await.unwrap();
+        }
+        db_tx.commit().await.unwrap();
+
+        sqlx::query_scalar("SELECT id FROM k_test_records ORDER BY id")
+            .fetch_all(&mut *conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failed_record_only_rolls_back_itself() {
+        let Some(mut conn) = test_connection().await else {
+            return;
+        };
+        // Record 2 wrote its row before failing
+        assert_eq!(write_records(&mut conn, &[1, 2, 3], &[2]).await, vec![1, 3]);
+        assert_eq!(
+            write_records(&mut conn, &[1, 2], &[1, 2]).await,
+            Vec::<i32>::new()
+        );
+        assert_eq!(write_records(&mut conn, &[1, 2], &[]).await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_statement_does_not_abort_batch() {
+        let Some(mut conn) = test_connection().await else {
+            return;
+        };
+        // The second insert of 1 violates the primary key, which aborts the transaction until
+        // rolled back to the savepoint: the records after it are still written and committed
+        assert_eq!(write_records(&mut conn, &[1, 1, 2], &[]).await, vec![1, 2]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Synthetic K data sets of fixed sizes for the database benchmarks
+//!
+//! Every scale lives in its own schema (k_bench_<scale>) of the benchmark database, with the
+//! tables, indexes and k_meta row of the public schema created by K-transaction-processor.
+//! Data is generated server side (INSERT ... SELECT generate_series) and deterministic, so two
+//! runs against the same scale measure the same rows. A generated schema is reused by later
+//! runs until its marker comment (DATA_VERSION) changes.
+
+use anyhow::{Result, anyhow};
+use sqlx::{Connection, Executor, PgConnection};
+use std::time::Instant;
+
+// Bump when the generated data changes, to regenerate existing benchmark schemas
+const DATA_VERSION: u32 = 1;
+
+// Block time of the first generated content (ms), one content per second after it
+const FIRST_BLOCK_TIME_MS: i64 = 1_700_000_000_000;
+
+// Follows and blocks of every generated user
+const FOLLOWS_PER_USER: i64 = 20;
+const BLOCKS_PER_USER: i64 = 2;
+
+pub struct Scale {
+    pub name: &'static str,
+    /// Rows of k_contents (and k_votes); users, follows and mentions scale with it
+    pub contents: i64,
+}
+
+pub const SCALES: &[Scale] = &[
+    Scale {
+        name: "10k",
+        contents: 10_000,
+    },
+    Scale {
+        name: "1m",
+        contents: 1_000_000,
+    },
+    Scale {
+        name: "10m",
+        contents: 10_000_000,
+    },
+];
+
+impl Scale {
+    pub fn schema(&self) -> String {
+        format!("k_bench_{}", self.name)
+    }
+
+    pub fn users(&self) -> i64 {
+        (self.contents / 100).max(100)
+    }
+
+    pub fn time_range(&self) -> (u64, u64) {
+        (
+            FIRST_BLOCK_TIME_MS as u64,
+            (FIRST_BLOCK_TIME_MS + self.contents * 1000) as u64,
+        )
+    }
+
+    fn marker(&self) -> String {
+        format!(
+            "K-webserver benchmark data v{}: {} contents, {} users",
+            DATA_VERSION,
+            self.contents,
+            self.users()
+        )
+    }
+}
+
+/// Create the schema of `scale` unless an up-to-date one already exists
+pub async fn prepare(database_url: &str, scale: &Scale) -> Result<()> {
+    let mut conn = PgConnection::connect(database_url).await?;
+    let schema = scale.schema();
+
+    let initialized: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = 'public' AND table_name = 'k_meta')",
+    )
+    .fetch_one(&mut conn)
+    .await?;
+    if !initialized {
+        return Err(anyhow!(
+            "No K tables in the public schema of the benchmark database: run K-transaction-processor against it once to create them"
+        ));
+    }
+
+    let marker: Option<String> = sqlx::query_scalar(
+        "SELECT obj_description(oid, 'pg_namespace') FROM pg_namespace WHERE nspname = $1",
+    )
+    .bind(&schema)
+    .fetch_optional(&mut conn)
+    .await?
+    .flatten();
+    if marker.as_deref() == Some(scale.marker().as_str()) {
+        println!("Reusing benchmark data of {}", schema);
+        return Ok(());
+    }
+
+    println!(
+        "Generating benchmark data of {} ({} contents, {} users)...",
+        schema,
+        scale.contents,
+        scale.users()
+    );
+    let started = Instant::now();
+
+    conn.execute(format!("DROP SCHEMA IF EXISTS {} CASCADE", schema).as_str())
+        .await?;
+    conn.execute(format!("CREATE SCHEMA {}", schema).as_str())
+        .await?;
+
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables WHERE table_schema = 'public' AND table_type = 'BASE TABLE' AND table_name LIKE 'k\\_%' ORDER BY table_name",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+    for table in &tables {
+        conn.execute(
+            format!(
+                "CREATE TABLE {s}.{t} (LIKE public.{t} INCLUDING ALL)",
+                s = schema,
+                t = table
+            )
+            .as_str(),
+        )
+        .await?;
+    }
+
+    for statement in generate_statements(scale) {
+        conn.execute(statement.as_str()).await?;
+    }
+
+    conn.execute(format!("COMMENT ON SCHEMA {} IS '{}'", schema, scale.marker()).as_str())
+        .await?;
+
+    println!(
+        "Generated {} in {:.1}s",
+        schema,
+        started.elapsed().as_secs_f64()
+    );
+    conn.close().await?;
+    Ok(())
+}
+
+// Statements filling the (empty) tables of the benchmark schema, one statement each. Rows get
+// explicit ids: the copied column defaults still draw from the sequences of the public schema
+fn generate_statements(scale: &Scale) -> Vec<String> {
+    let s = scale.schema();
+    let n = scale.contents;
+    let u = scale.users();
+    let t0 = FIRST_BLOCK_TIME_MS;
+
+    vec![
+        // Deterministic pubkeys (33 bytes), transaction ids (32 bytes) and signatures (64 bytes)
+        format!(
+            "CREATE FUNCTION {s}.bench_pubkey(i bigint) RETURNS bytea IMMUTABLE LANGUAGE sql AS
+             $$ SELECT decode('02' || md5('u' || i) || md5(i || 'u'), 'hex') $$"
+        ),
+        format!(
+            "CREATE FUNCTION {s}.bench_id(prefix text, i bigint) RETURNS bytea IMMUTABLE LANGUAGE sql AS
+             $$ SELECT decode(md5(prefix || i) || md5(i || prefix), 'hex') $$"
+        ),
+        format!(
+            "CREATE FUNCTION {s}.bench_signature(prefix text, i bigint) RETURNS bytea IMMUTABLE LANGUAGE sql AS
+             $$ SELECT decode(repeat(md5(prefix || '/' || i), 4), 'hex') $$"
+        ),
+        // Base64 without the line breaks encode() adds every 76 characters
+        format!(
+            "CREATE FUNCTION {s}.bench_base64(message text) RETURNS text IMMUTABLE LANGUAGE sql AS
+             $$ SELECT replace(encode(convert_to(message, 'UTF8'), 'base64'), E'\\n', '') $$"
+        ),
+        // Sender (1..users) and referenced post (an earlier content with i % 10 <= 5) of content i
+        format!(
+            "CREATE FUNCTION {s}.bench_sender(i bigint) RETURNS bigint IMMUTABLE LANGUAGE sql AS
+             $$ SELECT abs(hashint8(-i)::bigint) % {u} + 1 $$"
+        ),
+        format!(
+            "CREATE FUNCTION {s}.bench_parent(i bigint) RETURNS bigint IMMUTABLE LANGUAGE sql AS
+             $$ SELECT 10 * (1 + abs(hashint8(i)::bigint) % greatest(i / 10 - 1, 1)) + i % 6 $$"
+        ),
+        format!("INSERT INTO {s}.k_meta SELECT * FROM public.k_meta"),
+        format!(
+            "UPDATE {s}.k_meta SET maintenance_mode = false, maintenance_reason = NULL, cold_tier_before = NULL, visible_before = NULL"
+        ),
+        format!("INSERT INTO {s}.k_vars SELECT * FROM public.k_vars"),
+        // Users broadcast their profile before the first content
+        format!(
+            "INSERT INTO {s}.k_broadcasts (id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_nickname, base64_encoded_message)
+             SELECT i, {s}.bench_id('b', i), {t0} - ({u} - i + 1) * 1000, {s}.bench_pubkey(i), {s}.bench_signature('b', i),
+                    {s}.bench_base64('user' || i),
+                    {s}.bench_base64('Benchmark user ' || i)
+             FROM generate_series(1::bigint, {u}) AS i"
+        ),
+        // Contents 10..n+9: by i % 10, 0-5 posts, 6-8 replies and 9 quotes, each with one of 100 hashtags
+        format!(
+            "INSERT INTO {s}.k_contents (id, transaction_id, block_time, sender_pubkey, sender_signature, base64_encoded_message, content_type, referenced_content_id, search_vector)
+             SELECT i, {s}.bench_id('c', i), {t0} + i * 1000, {s}.bench_pubkey({s}.bench_sender(i)), {s}.bench_signature('c', i),
+                    {s}.bench_base64(message), content_type,
+                    CASE WHEN content_type = 'post' THEN NULL ELSE {s}.bench_id('c', {s}.bench_parent(i)) END,
+                    CASE WHEN content_type = 'quote' THEN NULL ELSE to_tsvector('simple', message) END
+             FROM generate_series(10::bigint, {n} + 9) AS i,
+                  LATERAL (SELECT CASE WHEN i % 10 <= 5 THEN 'post' WHEN i % 10 <= 8 THEN 'reply' ELSE 'quote' END AS content_type,
+                                  'Benchmark content ' || i || ' about kaspa node ' || (i % 997) || ' #tag' || (i % 100) AS message) AS c"
+        ),
+        format!(
+            "INSERT INTO {s}.k_hashtags (id, sender_pubkey, content_id, block_time, hashtag)
+             SELECT id, sender_pubkey, transaction_id, block_time, 'tag' || (id % 100)
+             FROM {s}.k_contents"
+        ),
+        // Every fifth content mentions a user, replies also mention the author of their parent
+        format!(
+            "INSERT INTO {s}.k_mentions (id, content_id, content_type, mentioned_pubkey, block_time, sender_pubkey, parent_id, content_snippet)
+             SELECT row_number() OVER (ORDER BY block_time, id), transaction_id, content_type, mentioned_pubkey, block_time, sender_pubkey, referenced_content_id, 'Benchmark content ' || id
+             FROM (
+                 SELECT c.*, {s}.bench_pubkey(abs(hashint8(c.id * 7)::bigint) % {u} + 1) AS mentioned_pubkey
+                 FROM {s}.k_contents c WHERE c.id % 5 = 0
+                 UNION ALL
+                 SELECT c.*, p.sender_pubkey
+                 FROM {s}.k_contents c JOIN {s}.k_contents p ON p.id = {s}.bench_parent(c.id)
+                 WHERE c.content_type = 'reply'
+             ) AS m"
+        ),
+        // One vote per content on an earlier post, 4 upvotes out of 5
+        format!(
+            "INSERT INTO {s}.k_votes (id, transaction_id, block_time, sender_pubkey, sender_signature, post_id, vote, fee)
+             SELECT i, {s}.bench_id('v', i), {t0} + i * 1000 + 500, {s}.bench_pubkey(abs(hashint8(i * 3)::bigint) % {u} + 1),
+                    {s}.bench_signature('v', i), {s}.bench_id('c', {s}.bench_parent(i)),
+                    CASE WHEN i % 5 = 0 THEN 'downvote' ELSE 'upvote' END, 1000 + i % 9000
+             FROM generate_series(10::bigint, {n} + 9) AS i"
+        ),
+        format!(
+            "INSERT INTO {s}.k_vote_totals (post_id, up_votes, down_votes, up_votes_fee, down_votes_fee)
+             SELECT post_id, count(*) FILTER (WHERE vote = 'upvote'), count(*) FILTER (WHERE vote = 'downvote'),
+                    coalesce(sum(fee) FILTER (WHERE vote = 'upvote'), 0), coalesce(sum(fee) FILTER (WHERE vote = 'downvote'), 0)
+             FROM {s}.k_votes GROUP BY post_id"
+        ),
+        // User k follows the next FOLLOWS_PER_USER users and blocks two users further away
+        format!(
+            "INSERT INTO {s}.k_follows (id, transaction_id, block_time, sender_pubkey, sender_signature, following_action, followed_user_pubkey)
+             SELECT i, {s}.bench_id('f', i), {t0} - 1000 * {u} - i, {s}.bench_pubkey((i - 1) / {FOLLOWS_PER_USER} + 1),
+                    {s}.bench_signature('f', i), 'follow',
+                    {s}.bench_pubkey(((i - 1) / {FOLLOWS_PER_USER} + (i - 1) % {FOLLOWS_PER_USER} + 1) % {u} + 1)
+             FROM generate_series(1::bigint, {u} * {FOLLOWS_PER_USER}) AS i"
+        ),
+        format!(
+            "INSERT INTO {s}.k_blocks (id, transaction_id, block_time, sender_pubkey, sender_signature, blocking_action, blocked_user_pubkey)
+             SELECT i, {s}.bench_id('k', i), {t0} - 1000 * {u} - i, {s}.bench_pubkey((i - 1) / {BLOCKS_PER_USER} + 1),
+                    {s}.bench_signature('k', i), 'block',
+                    {s}.bench_pubkey(((i - 1) / {BLOCKS_PER_USER} + 50 + (i - 1) % {BLOCKS_PER_USER}) % {u} + 1)
+             FROM generate_series(1::bigint, {u} * {BLOCKS_PER_USER}) AS i"
+        ),
+        format!("ANALYZE {s}.k_broadcasts, {s}.k_contents, {s}.k_hashtags, {s}.k_mentions, {s}.k_votes, {s}.k_vote_totals, {s}.k_follows, {s}.k_blocks"),
+    ]
+}
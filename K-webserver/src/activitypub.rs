@@ -0,0 +1,413 @@
+use base64ct::{Base64, Encoding};
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{Value, json};
+
+/// Media type of every ActivityPub document served under /ap
+pub const ACTIVITY_JSON: &str = "application/activity+json; charset=utf-8";
+
+/// Media type of WebFinger responses
+pub const JRD_JSON: &str = "application/jrd+json; charset=utf-8";
+
+const ACTIVITY_STREAMS_CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+const SECURITY_CONTEXT: &str = "https://w3id.org/security/v1";
+const PUBLIC_COLLECTION: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Latest broadcast of a K user, as published in their actor document
+pub struct ActorProfile<'a> {
+    pub pubkey: &'a str,
+    pub base64_nickname: &'a str,
+    pub base64_message: &'a str,
+    pub block_time: u64,
+    pub has_profile_image: bool,
+}
+
+/// K post (or quote) published as a Note
+pub struct NoteSource<'a> {
+    pub content_id: &'a str,
+    pub sender_pubkey: &'a str,
+    pub base64_message: &'a str,
+    pub block_time: u64,
+    pub mentioned_pubkeys: &'a [String],
+    // Quoted post and its author, for quotes
+    pub quoted: Option<(&'a str, &'a str)>,
+}
+
+pub fn actor_id(base_url: &str, pubkey: &str) -> String {
+    format!("{}/ap/users/{}", base_url, pubkey)
+}
+
+pub fn instance_actor_id(base_url: &str) -> String {
+    format!("{}/ap/actor", base_url)
+}
+
+pub fn note_id(base_url: &str, pubkey: &str, content_id: &str) -> String {
+    format!("{}/posts/{}", actor_id(base_url, pubkey), content_id)
+}
+
+/// Key id published in actor documents and used in the Signature header of outgoing fetches
+pub fn key_id(actor_id: &str) -> String {
+    format!("{}#main-key", actor_id)
+}
+
+/// Person actor of a K user; all users share the instance key, owned by each actor
+pub fn person(base_url: &str, profile: &ActorProfile, public_key_pem: &str) -> Value {
+    let id = actor_id(base_url, profile.pubkey);
+    let nickname = decode_text(profile.base64_nickname);
+    let message = decode_text(profile.base64_message);
+    let name = if nickname.trim().is_empty() {
+        profile.pubkey.to_string()
+    } else {
+        nickname
+    };
+
+    let mut actor = json!({
+        "@context": [ACTIVITY_STREAMS_CONTEXT, SECURITY_CONTEXT],
+        "id": id,
+        "type": "Person",
+        "preferredUsername": profile.pubkey,
+        "name": name,
+        "summary": html_text(&message),
+        "inbox": format!("{}/inbox", id),
+        "outbox": format!("{}/outbox", id),
+        "published": rfc3339(profile.block_time),
+        "manuallyApprovesFollowers": false,
+        "discoverable": true,
+        "publicKey": {
+            "id": key_id(&id),
+            "owner": id,
+            "publicKeyPem": public_key_pem
+        }
+    });
+
+    if profile.has_profile_image {
+        actor["icon"] = json!({
+            "type": "Image",
+            "url": format!("{}/get-profile-image?user={}", base_url, profile.pubkey)
+        });
+    }
+
+    actor
+}
+
+/// Application actor representing the indexer itself, used to sign outgoing key fetches
+pub fn instance_actor(base_url: &str, public_key_pem: &str) -> Value {
+    let id = instance_actor_id(base_url);
+    json!({
+        "@context": [ACTIVITY_STREAMS_CONTEXT, SECURITY_CONTEXT],
+        "id": id,
+        "type": "Application",
+        "preferredUsername": "k-indexer",
+        "name": "K-indexer",
+        "inbox": format!("{}/inbox", id),
+        "outbox": format!("{}/outbox", id),
+        "manuallyApprovesFollowers": true,
+        "publicKey": {
+            "id": key_id(&id),
+            "owner": id,
+            "publicKeyPem": public_key_pem
+        }
+    })
+}
+
+/// Outbox collection of a K user; items are served by its pages, newest first
+pub fn outbox(base_url: &str, pubkey: &str) -> Value {
+    let id = format!("{}/outbox", actor_id(base_url, pubkey));
+    json!({
+        "@context": ACTIVITY_STREAMS_CONTEXT,
+        "id": id,
+        "type": "OrderedCollection",
+        "first": format!("{}?page=true", id)
+    })
+}
+
+/// Outbox page holding Create activities; next_cursor continues with older posts
+pub fn outbox_page(
+    base_url: &str,
+    pubkey: &str,
+    before: Option<&str>,
+    items: Vec<Value>,
+    next_cursor: Option<&str>,
+) -> Value {
+    let outbox_id = format!("{}/outbox", actor_id(base_url, pubkey));
+    let page_id = |cursor: Option<&str>| match cursor {
+        Some(cursor) => format!("{}?page=true&before={}", outbox_id, cursor),
+        None => format!("{}?page=true", outbox_id),
+    };
+
+    let mut page = json!({
+        "@context": ACTIVITY_STREAMS_CONTEXT,
+        "id": page_id(before),
+        "type": "OrderedCollectionPage",
+        "partOf": outbox_id,
+        "orderedItems": items
+    });
+
+    if let Some(cursor) = next_cursor {
+        page["next"] = json!(page_id(Some(cursor)));
+    }
+
+    page
+}
+
+/// Public Note of a K post, with mentions as Mention tags and quotes as quoteUrl
+pub fn note(base_url: &str, source: &NoteSource) -> Value {
+    let author = actor_id(base_url, source.sender_pubkey);
+    let mentions: Vec<String> = source
+        .mentioned_pubkeys
+        .iter()
+        .map(|pubkey| actor_id(base_url, pubkey))
+        .collect();
+    let tags: Vec<Value> = source
+        .mentioned_pubkeys
+        .iter()
+        .zip(&mentions)
+        .map(|(pubkey, href)| {
+            json!({
+                "type": "Mention",
+                "href": href,
+                "name": format!("@{}", pubkey)
+            })
+        })
+        .collect();
+
+    let mut note = json!({
+        "id": note_id(base_url, source.sender_pubkey, source.content_id),
+        "type": "Note",
+        "attributedTo": author,
+        "content": html_text(&decode_text(source.base64_message)),
+        "published": rfc3339(source.block_time),
+        "to": [PUBLIC_COLLECTION],
+        "cc": mentions,
+        "tag": tags
+    });
+
+    if let Some((quoted_id, quoted_pubkey)) = source.quoted {
+        note["quoteUrl"] = json!(note_id(base_url, quoted_pubkey, quoted_id));
+    }
+
+    note
+}
+
+/// Note with its own @context, as served on its id
+pub fn note_document(base_url: &str, source: &NoteSource) -> Value {
+    let mut note = note(base_url, source);
+    note["@context"] = json!(ACTIVITY_STREAMS_CONTEXT);
+    note
+}
+
+/// Create activity wrapping a Note, as listed in outbox pages
+pub fn create_activity(base_url: &str, source: &NoteSource) -> Value {
+    let note = note(base_url, source);
+    json!({
+        "id": format!("{}/activity", note["id"].as_str().unwrap_or_default()),
+        "type": "Create",
+        "actor": note["attributedTo"],
+        "published": note["published"],
+        "to": note["to"],
+        "cc": note["cc"],
+        "object": note
+    })
+}
+
+/// WebFinger (JRD) document pointing acct:{pubkey}@{host} to the actor
+pub fn webfinger(base_url: &str, pubkey: &str) -> Value {
+    let id = actor_id(base_url, pubkey);
+    json!({
+        "subject": format!("acct:{}@{}", pubkey, host(base_url)),
+        "aliases": [id],
+        "links": [{
+            "rel": "self",
+            "type": "application/activity+json",
+            "href": id
+        }]
+    })
+}
+
+/// User public key addressed by a WebFinger resource, either acct:{pubkey}@{host} or the actor id
+pub fn webfinger_pubkey(base_url: &str, resource: &str) -> Option<String> {
+    let pubkey = if let Some(account) = resource.strip_prefix("acct:") {
+        let (user, domain) = account.rsplit_once('@')?;
+        if !domain.eq_ignore_ascii_case(host(base_url)) {
+            return None;
+        }
+        user
+    } else {
+        resource.strip_prefix(&actor_id(base_url, ""))?
+    };
+
+    is_user_pubkey(pubkey).then(|| pubkey.to_ascii_lowercase())
+}
+
+/// Compressed public key of a K user: 66 hex characters starting with 02 or 03
+pub fn is_user_pubkey(value: &str) -> bool {
+    value.len() == 66
+        && value.chars().all(|c| c.is_ascii_hexdigit())
+        && (value.starts_with("02") || value.starts_with("03"))
+}
+
+/// Host (and port) of the public base URL
+fn host(base_url: &str) -> &str {
+    let without_scheme = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest);
+    without_scheme.split('/').next().unwrap_or(without_scheme)
+}
+
+/// Decode a base64 text field; invalid base64 or UTF-8 yields an empty string
+pub fn decode_text(base64: &str) -> String {
+    Base64::decode_vec(base64)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Plain K text as HTML: escaped, one paragraph per blank-line block, line breaks kept
+pub fn html_text(text: &str) -> String {
+    let normalized = text.replace("\r\n", "\n");
+    normalized
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph).replace('\n', "<br>")))
+        .collect()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Block time (milliseconds since the Unix epoch) as an RFC 3339 UTC timestamp
+pub fn rfc3339(block_time_ms: u64) -> String {
+    DateTime::from_timestamp_millis(block_time_ms as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_URL: &str = "https://k.example.org";
+    const ALICE: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const BOB: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400_000), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(1_735_689_599_999), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_html_text_escapes_and_keeps_paragraphs() {
+        assert_eq!(
+            html_text("a <b> & \"c\"\nnext\r\n\r\nsecond"),
+            "<p>a &lt;b&gt; &amp; &quot;c&quot;<br>next</p><p>second</p>"
+        );
+        assert_eq!(html_text("  \n\n "), "");
+    }
+
+    #[test]
+    fn test_person_publishes_shared_key_and_optional_icon() {
+        let profile = ActorProfile {
+            pubkey: ALICE,
+            base64_nickname: "QWxpY2U=",
+            base64_message: "aGk=",
+            block_time: 0,
+            has_profile_image: false,
+        };
+        let actor = person(BASE_URL, &profile, "PEM");
+        let id = format!("{}/ap/users/{}", BASE_URL, ALICE);
+        assert_eq!(actor["id"], json!(id));
+        assert_eq!(actor["name"], json!("Alice"));
+        assert_eq!(actor["summary"], json!("<p>hi</p>"));
+        assert_eq!(actor["publicKey"]["id"], json!(format!("{}#main-key", id)));
+        assert_eq!(actor["publicKey"]["owner"], json!(id));
+        assert!(actor.get("icon").is_none());
+
+        let with_image = ActorProfile {
+            has_profile_image: true,
+            base64_nickname: "",
+            ..profile
+        };
+        let actor = person(BASE_URL, &with_image, "PEM");
+        assert_eq!(actor["name"], json!(ALICE));
+        assert!(actor["icon"]["url"].as_str().unwrap().ends_with(ALICE));
+    }
+
+    #[test]
+    fn test_create_activity_wraps_note_with_mentions_and_quote() {
+        let mentions = vec![BOB.to_string()];
+        let source = NoteSource {
+            content_id: "abcd",
+            sender_pubkey: ALICE,
+            base64_message: "aGk=",
+            block_time: 1000,
+            mentioned_pubkeys: &mentions,
+            quoted: Some(("ef01", BOB)),
+        };
+        let activity = create_activity(BASE_URL, &source);
+        let note_id = format!("{}/ap/users/{}/posts/abcd", BASE_URL, ALICE);
+        assert_eq!(activity["type"], json!("Create"));
+        assert_eq!(activity["id"], json!(format!("{}/activity", note_id)));
+        assert_eq!(activity["object"]["id"], json!(note_id));
+        assert_eq!(
+            activity["object"]["tag"][0]["name"],
+            json!(format!("@{}", BOB))
+        );
+        assert_eq!(activity["cc"], json!([actor_id(BASE_URL, BOB)]));
+        assert_eq!(
+            activity["object"]["quoteUrl"],
+            json!(format!("{}/ap/users/{}/posts/ef01", BASE_URL, BOB))
+        );
+    }
+
+    #[test]
+    fn test_outbox_page_links_next_page() {
+        let page = outbox_page(BASE_URL, ALICE, Some("10_5"), vec![], Some("7_3"));
+        let outbox = format!("{}/ap/users/{}/outbox", BASE_URL, ALICE);
+        assert_eq!(
+            page["id"],
+            json!(format!("{}?page=true&before=10_5", outbox))
+        );
+        assert_eq!(
+            page["next"],
+            json!(format!("{}?page=true&before=7_3", outbox))
+        );
+        assert!(
+            outbox_page(BASE_URL, ALICE, None, vec![], None)
+                .get("next")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_webfinger_pubkey() {
+        let acct = format!("acct:{}@k.example.org", ALICE.to_uppercase());
+        assert_eq!(webfinger_pubkey(BASE_URL, &acct), Some(ALICE.to_string()));
+        assert_eq!(
+            webfinger_pubkey(BASE_URL, &actor_id(BASE_URL, BOB)),
+            Some(BOB.to_string())
+        );
+        assert_eq!(
+            webfinger_pubkey(BASE_URL, &format!("acct:{}@other.example", ALICE)),
+            None
+        );
+        assert_eq!(webfinger_pubkey(BASE_URL, "acct:alice@k.example.org"), None);
+        assert_eq!(
+            webfinger(BASE_URL, BOB)["subject"],
+            json!(format!("acct:{}@k.example.org", BOB))
+        );
+    }
+}
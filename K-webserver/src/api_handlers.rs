@@ -1,20 +1,140 @@
-use crate::database_trait::{DatabaseInterface, QueryOptions};
+use crate::database_trait::{DatabaseInterface, PaginatedResult, PostRanking, QueryOptions};
+use crate::feature_flags::{FlagService, VIEW_COUNTS};
 use crate::models::{
-    ApiError, ContentRecord, NotificationPost, PaginatedNotificationsResponse,
-    PaginatedPostsResponse, PaginatedRepliesResponse, PaginatedUsersResponse, PostDetailsResponse,
-    ServerPost, ServerReply, ServerUserPost,
+    ApiError, ArticleDetailsResponse, ContentRecord, CountResponse, DuplicateContentFromRecord,
+    ErasureRequestResponse, ErasureRequestResponseFromRecord, FeatureFlagsResponse,
+    FeaturedEntryFromRecord, ImpersonationFlagFromRecord, KBroadcastRecord, KReplyRecord,
+    NicknameUseFromRecord, NotificationPost, NotificationPostFromRecord, NotificationSubject,
+    PaginatedEventsResponse, PaginatedGroupPostsResponse, PaginatedGroupsResponse,
+    PaginatedNotificationsResponse, PaginatedPostsLightResponse, PaginatedPostsResponse,
+    PaginatedRepliesResponse, PaginatedUsersResponse, PaginationMetadata, PostDetailsResponse,
+    PostMentionsResponse, PostTipsResponse, PostTipsResponseFromRecord, PostsByIdsResponse,
+    PostsMetaResponse, ProfileDiffResponseFromRecord, ProfileResponse, QueryStatsFromRecord,
+    ServerArticle, ServerArticleFromRecord, ServerEvent, ServerEventFromRecord, ServerGroup,
+    ServerGroupFromRecord, ServerGroupPost, ServerGroupPostFromRecord, ServerPost,
+    ServerPostFromRecord, ServerPostLight, ServerPostLightFromRecord, ServerPostMeta,
+    ServerPostMetaFromRecord, ServerReply, ServerReplyFromRecord, ServerUserPost,
+    ServerUserPostFromRecord, TableStorageStatsFromRecord, ThreadResponse, UserStatsResponse,
+    UserStatsResponseFromRecord, UsersResponse,
 };
+use crate::nickname_normalizer::normalize_nickname;
+use crate::view_counter::{estimate, register_update, viewer_hash};
 use serde_json;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::Arc;
 use tracing::error as log_error;
 
+/// Sender pubkeys listed per message by /admin/duplicate-contents
+pub const MAX_DUPLICATE_SENDERS: u32 = 100;
+
+/// Growth in /admin/storage-stats is measured against a snapshot at least this old
+const STORAGE_GROWTH_PERIOD_MILLIS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Longest operator note of a featured content (characters)
+const MAX_FEATURED_NOTE_CHARS: usize = 280;
+
+/// Contents hydrated per /get-posts-meta request
+pub const MAX_POSTS_META_IDS: usize = 100;
+
+/// Contents returned per /get-posts-by-ids request
+pub const MAX_POSTS_BY_IDS: usize = 100;
+
+/// Users returned per /get-users-by-keys request
+pub const MAX_USERS_BY_KEYS: usize = 100;
+
+/// Levels of replies below the root of a /get-thread response
+pub const MAX_THREAD_DEPTH: u32 = 10;
+
+/// Replies of a /get-thread response: deeper levels are not fetched once reached
+const MAX_THREAD_REPLIES: usize = 500;
+
+/// Replies of a conversation /get-thread reads at once, larger ones are read level by level
+const MAX_THREAD_SCAN: u32 = 2000;
+
+/// Standardized error response body
+fn error_response(message: &str, code: &str) -> String {
+    let error = ApiError {
+        error: message.to_string(),
+        code: code.to_string(),
+    };
+
+    serde_json::to_string(&error).unwrap_or_else(|_| {
+        r#"{"error":"Internal error creating error response","code":"INTERNAL_ERROR"}"#.to_string()
+    })
+}
+
+/// Lowercase, deduplicated content IDs of a comma-separated `ids` parameter, at most `max`
+fn parse_content_ids(ids: &str, max: usize) -> Result<Vec<String>, String> {
+    let mut content_ids: Vec<String> = Vec::new();
+    for content_id in ids.split(',').map(str::trim) {
+        // Validate content ID format (64 hex characters for transaction hash)
+        if content_id.len() != 64 || !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(error_response(
+                "Invalid content ID format in ids. Each must be 64 hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+        let content_id = content_id.to_ascii_lowercase();
+        if !content_ids.contains(&content_id) {
+            content_ids.push(content_id);
+        }
+    }
+    if content_ids.len() > max {
+        return Err(error_response(
+            &format!("At most {} ids can be requested at once", max),
+            "INVALID_PARAMETER",
+        ));
+    }
+    Ok(content_ids)
+}
+
+/// Lowercase, deduplicated compressed public keys of a comma-separated `pubkeys` parameter, at
+/// most `max`
+fn parse_user_keys(pubkeys: &str, max: usize) -> Result<Vec<String>, String> {
+    let mut user_public_keys: Vec<String> = Vec::new();
+    for user_public_key in pubkeys.split(',').map(str::trim) {
+        // Validate compressed public keys (66 hex characters starting with 02 or 03)
+        if user_public_key.len() != 66
+            || !user_public_key.chars().all(|c| c.is_ascii_hexdigit())
+            || !(user_public_key.starts_with("02") || user_public_key.starts_with("03"))
+        {
+            return Err(error_response(
+                "Invalid user public key format in pubkeys. Each must be 66 hex characters starting with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+        let user_public_key = user_public_key.to_ascii_lowercase();
+        if !user_public_keys.contains(&user_public_key) {
+            user_public_keys.push(user_public_key);
+        }
+    }
+    if user_public_keys.len() > max {
+        return Err(error_response(
+            &format!("At most {} pubkeys can be requested at once", max),
+            "INVALID_PARAMETER",
+        ));
+    }
+    Ok(user_public_keys)
+}
+
+/// Optional data embedded into each returned item
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IncludeOptions {
+    /// Nested replies per item (includeReplies, 0 = none)
+    pub replies: u32,
+    /// Fee-weighted vote totals (includeVoteWeights, schema v27+)
+    pub vote_weights: bool,
+}
+
 pub struct ApiHandlers {
     db: Arc<dyn DatabaseInterface>,
+    flags: Arc<FlagService>,
 }
 
 impl ApiHandlers {
-    pub fn new(db: Arc<dyn DatabaseInterface>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<dyn DatabaseInterface>, flags: Arc<FlagService>) -> Self {
+        Self { db, flags }
     }
 
     /// GET /get-posts with pagination
@@ -207,15 +327,18 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-content-following with pagination
-    /// Fetch paginated content (posts, replies, quotes) from followed users
-    pub async fn get_content_following_paginated(
+    /// GET /get-posts-watching?sort={top|hot}&timeWindow={timeWindow}
+    /// Fetch the posts and quotes of a time window ranked by engagement, as a single page
+    /// (ranks move with every vote and, for hot, with time, so there are no cursors)
+    pub async fn get_posts_watching_ranked(
         &self,
         requester_pubkey: &str,
+        ranking: PostRanking,
+        time_window: &str,
         limit: u32,
-        before: Option<String>,
-        after: Option<String>,
     ) -> Result<String, String> {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
         // Validate requester public key format (66 hex characters for compressed public key)
         if requester_pubkey.len() != 66 {
             return Err(self.create_error_response(
@@ -239,25 +362,39 @@ impl ApiHandlers {
             ));
         }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
+        // Calculate time window in milliseconds (block_time is stored in milliseconds)
+        let window_millis = match time_window {
+            "1h" => 3_600_000_u64,
+            "6h" => 21_600_000_u64,
+            "24h" => 86_400_000_u64,
+            "7d" => 604_800_000_u64,
+            "30d" => 2_592_000_000_u64,
+            _ => {
+                return Err(self
+                    .create_error_response("Invalid time window parameter", "INVALID_PARAMETER"));
+            }
         };
 
-        // Get content from followed users
-        let content_result = match self
+        let to_time_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let from_time_millis = to_time_millis.saturating_sub(window_millis);
+
+        let posts = match self
             .db
-            .get_content_following(requester_pubkey, options)
+            .get_ranked_posts(
+                requester_pubkey,
+                ranking,
+                from_time_millis,
+                to_time_millis,
+                limit,
+            )
             .await
         {
-            Ok(result) => result,
+            Ok(posts) => posts,
             Err(err) => {
-                log_error!(
-                    "Database error while querying content from followed users: {}",
-                    err
-                );
+                log_error!("Database error while querying ranked posts: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -265,24 +402,24 @@ impl ApiHandlers {
             }
         };
 
-        // Convert enriched KPostRecords to ServerPosts (blocked users already excluded)
-        let all_posts: Vec<ServerPost> = content_result
-            .items
-            .iter()
-            .map(|post_record| {
-                ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
-            })
-            .collect();
-
         let response = PaginatedPostsResponse {
-            posts: all_posts,
-            pagination: content_result.pagination,
+            posts: posts
+                .iter()
+                .map(|post_record| {
+                    ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
+                })
+                .collect(),
+            pagination: PaginationMetadata {
+                has_more: false,
+                next_cursor: None,
+                prev_cursor: None,
+            },
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize paginated content response: {}", err);
+                log_error!("Failed to serialize ranked posts response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -291,15 +428,39 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-users with pagination and blocked users awareness
-    /// Fetch paginated user introduction posts with cursor-based pagination and blocking status
-    pub async fn get_users_paginated(
+    /// GET /get-posts-light?requesterPubkey={requesterPubkey}&limit={limit}&before={timestamp}&after={timestamp}
+    /// Page of /get-posts-watching with the core fields of each post only, hydrated afterwards
+    /// with /get-posts-meta
+    pub async fn get_posts_light_paginated(
         &self,
-        limit: u32,
         requester_pubkey: &str,
+        limit: u32,
         before: Option<String>,
         after: Option<String>,
     ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
         let options = QueryOptions {
             limit: Some(limit as u64),
             before,
@@ -307,13 +468,10 @@ impl ApiHandlers {
             sort_descending: true,
         };
 
-        let broadcasts_result = match self.db.get_all_users(requester_pubkey, options).await {
+        let posts_result = match self.db.get_all_posts_light(requester_pubkey, options).await {
             Ok(result) => result,
             Err(err) => {
-                log_error!(
-                    "Database error while querying paginated user broadcasts with block status: {}",
-                    err
-                );
+                log_error!("Database error while querying light posts: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -321,34 +479,19 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for (k_broadcast_record, is_blocked, is_followed) in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
-                &k_broadcast_record,
-                is_blocked,
-            );
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
-            server_user_post.followed_user = Some(is_followed);
-
-            all_posts.push(server_user_post);
-        }
-
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
+        let response = PaginatedPostsLightResponse {
+            posts: posts_result
+                .items
+                .iter()
+                .map(ServerPostLight::from_k_post_record)
+                .collect(),
+            pagination: posts_result.pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated users response with block status: {}",
-                    err
-                );
+                log_error!("Failed to serialize light posts response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -357,53 +500,43 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-most-active-users with pagination
-    /// Fetch users ranked by total content count (posts, replies, quotes) in k_contents
-    /// within a specific time window
-    pub async fn get_most_active_users_paginated(
+    /// GET /get-posts-meta?requesterPubkey={requesterPubkey}&ids={id},{id},...
+    /// Counts, requester votes, profiles, mentions and quoted content of the listed contents,
+    /// in the order of `ids`; IDs of unknown contents are left out
+    pub async fn get_posts_meta(
         &self,
-        limit: u32,
         requester_pubkey: &str,
-        time_window: &str,
-        before: Option<String>,
-        after: Option<String>,
+        ids: &str,
     ) -> Result<String, String> {
-        use std::time::{SystemTime, UNIX_EPOCH};
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
 
-        // Calculate time window in milliseconds (block_time is stored in milliseconds)
-        let window_millis = match time_window {
-            "1h" => 3_600_000_u64,
-            "6h" => 21_600_000_u64,
-            "24h" => 86_400_000_u64,
-            "7d" => 604_800_000_u64,
-            "30d" => 2_592_000_000_u64,
-            _ => {
-                return Err(self
-                    .create_error_response("Invalid time window parameter", "INVALID_PARAMETER"));
-            }
-        };
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
 
-        let to_time_millis = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        let from_time_millis = to_time_millis.saturating_sub(window_millis);
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
-        };
+        let content_ids = parse_content_ids(ids, MAX_POSTS_META_IDS)?;
 
-        let result = match self
-            .db
-            .get_most_active_users(requester_pubkey, options, from_time_millis, to_time_millis)
-            .await
-        {
-            Ok(result) => result,
+        let posts = match self.db.get_posts_meta(requester_pubkey, &content_ids).await {
+            Ok(posts) => posts,
             Err(err) => {
-                log_error!("Database error while querying most active users: {}", err);
+                log_error!("Database error while querying posts meta: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -411,31 +544,17 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for (k_broadcast_record, is_blocked, is_followed, content_count) in result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
-                &k_broadcast_record,
-                is_blocked,
-            );
-
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
-            server_user_post.followed_user = Some(is_followed);
-            server_user_post.contents_count = Some(content_count);
-
-            all_posts.push(server_user_post);
-        }
-
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: result.pagination,
+        let response = PostsMetaResponse {
+            posts: posts
+                .iter()
+                .map(ServerPostMeta::from_enriched_k_post_record)
+                .collect(),
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize most active users response: {}", err);
+                log_error!("Failed to serialize posts meta response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -444,56 +563,47 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /search-users with pagination
-    /// Search users with optional filters for pubkey or nickname
-    pub async fn search_users_paginated(
+    /// GET /get-posts-by-ids?requesterPubkey={requesterPubkey}&ids={id},{id},...
+    /// Posts, replies and quotes of the listed contents as returned by /get-post-details, in
+    /// the order of `ids`; IDs of unknown contents are left out
+    pub async fn get_posts_by_ids(
         &self,
-        limit: u32,
         requester_pubkey: &str,
-        before: Option<String>,
-        after: Option<String>,
-        searched_user_pubkey: Option<String>,
-        searched_user_nickname: Option<String>,
+        ids: &str,
     ) -> Result<String, String> {
-        // Validate searched_user_pubkey if provided
-        if let Some(ref pubkey) = searched_user_pubkey {
-            if pubkey.len() != 66 {
-                return Err(self.create_error_response(
-                    "Invalid searched user public key format. Must be 66 hex characters.",
-                    "INVALID_USER_KEY",
-                ));
-            }
-            if !pubkey.starts_with("02") && !pubkey.starts_with("03") {
-                return Err(self.create_error_response(
-                    "Invalid searched user public key prefix. Must start with 02 or 03.",
-                    "INVALID_USER_KEY",
-                ));
-            }
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
         }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
-        };
-
-        // Strip the 02/03 prefix from the searched pubkey to match both variants
-        let searched_pubkey_without_prefix = searched_user_pubkey.map(|pk| pk[2..].to_string());
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
 
-        let broadcasts_result = match self
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let content_ids = parse_content_ids(ids, MAX_POSTS_BY_IDS)?;
+
+        let contents = match self
             .db
-            .search_users(
-                requester_pubkey,
-                options,
-                searched_pubkey_without_prefix,
-                searched_user_nickname,
-            )
+            .get_contents_by_ids(&content_ids, requester_pubkey)
             .await
         {
-            Ok(result) => result,
+            Ok(contents) => contents,
             Err(err) => {
-                log_error!("Database error while searching users: {}", err);
+                log_error!("Database error while querying contents by ids: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -501,31 +611,31 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for (k_broadcast_record, is_blocked, is_followed) in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
-                &k_broadcast_record,
-                is_blocked,
-            );
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
-            server_user_post.followed_user = Some(is_followed);
-
-            all_posts.push(server_user_post);
-        }
-
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
+        let response = PostsByIdsResponse {
+            posts: contents
+                .iter()
+                .filter_map(|(content_record, is_blocked)| match content_record {
+                    ContentRecord::Post(record) => {
+                        Some(ServerPost::from_enriched_k_post_record_with_block_status(
+                            record,
+                            *is_blocked,
+                        ))
+                    }
+                    ContentRecord::Reply(record) => {
+                        Some(ServerReply::from_enriched_k_reply_record_with_block_status(
+                            record,
+                            *is_blocked,
+                        ))
+                    }
+                    ContentRecord::Vote(_) => None,
+                })
+                .collect(),
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize search users response: {}", err);
+                log_error!("Failed to serialize posts by ids response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -534,31 +644,40 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-replies with pagination (Post Replies Mode)
-    /// Fetch paginated replies for a specific post with cursor-based pagination and voting status
-    pub async fn get_replies_paginated(
+    /// GET /get-content-following with pagination
+    /// Fetch paginated content (posts, replies, quotes) from followed users
+    pub async fn get_content_following_paginated(
         &self,
-        post_id: &str,
         requester_pubkey: &str,
         limit: u32,
         before: Option<String>,
         after: Option<String>,
     ) -> Result<String, String> {
-        // Validate post ID format (64 hex characters for transaction hash)
-        if post_id.len() != 64 {
-            return Err(self.create_error_response(
-                "Invalid post ID format. Must be 64 hex characters.",
-                "INVALID_POST_ID",
-            ));
-        }
+        self.followed_contents_paginated(requester_pubkey, limit, before, after, false)
+            .await
+    }
 
-        if !post_id.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid post ID format. Must contain only hex characters.",
-                "INVALID_POST_ID",
-            ));
-        }
+    /// GET /get-posts-following with pagination
+    /// Fetch paginated posts (no replies or quotes) from followed users
+    pub async fn get_posts_following_paginated(
+        &self,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        self.followed_contents_paginated(requester_pubkey, limit, before, after, true)
+            .await
+    }
 
+    async fn followed_contents_paginated(
+        &self,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+        posts_only: bool,
+    ) -> Result<String, String> {
         // Validate requester public key format (66 hex characters for compressed public key)
         if requester_pubkey.len() != 66 {
             return Err(self.create_error_response(
@@ -589,17 +708,19 @@ impl ApiHandlers {
             sort_descending: true,
         };
 
-        // Use the new k_contents table method with blocking awareness
-        let replies_result = match self
-            .db
-            .get_replies_by_post_id(post_id, requester_pubkey, options)
-            .await
-        {
+        // Get content from followed users
+        let content_result = if posts_only {
+            self.db.get_posts_following(requester_pubkey, options).await
+        } else {
+            self.db
+                .get_content_following(requester_pubkey, options)
+                .await
+        };
+        let content_result = match content_result {
             Ok(result) => result,
             Err(err) => {
                 log_error!(
-                    "Database error while querying paginated replies with metadata for post {}: {}",
-                    post_id,
+                    "Database error while querying content from followed users: {}",
                     err
                 );
                 return Err(self.create_error_response(
@@ -609,24 +730,24 @@ impl ApiHandlers {
             }
         };
 
-        // Convert enriched KReplyRecords to ServerReplies (blocked users already excluded)
-        let all_replies: Vec<ServerReply> = replies_result
+        // Convert enriched KPostRecords to ServerPosts (blocked users already excluded)
+        let all_posts: Vec<ServerPost> = content_result
             .items
             .iter()
-            .map(|reply_record| {
-                ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
+            .map(|post_record| {
+                ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
             })
             .collect();
 
-        let response = PaginatedRepliesResponse {
-            replies: all_replies,
-            pagination: replies_result.pagination,
+        let response = PaginatedPostsResponse {
+            posts: all_posts,
+            pagination: content_result.pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize paginated replies response: {}", err);
+                log_error!("Failed to serialize paginated content response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -635,62 +756,15 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-replies with pagination (User Replies Mode)
-    /// Fetch paginated replies made by a specific user with cursor-based pagination and voting status
-    pub async fn get_user_replies_paginated(
+    /// GET /get-users with pagination and blocked users awareness
+    /// Fetch paginated user introduction posts with cursor-based pagination and blocking status
+    pub async fn get_users_paginated(
         &self,
-        user_public_key: &str,
-        requester_pubkey: &str,
         limit: u32,
+        requester_pubkey: &str,
         before: Option<String>,
         after: Option<String>,
     ) -> Result<String, String> {
-        // Validate user public key format (66 hex characters for compressed public key)
-        if user_public_key.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        if !user_public_key.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !user_public_key.starts_with("02") && !user_public_key.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
         let options = QueryOptions {
             limit: Some(limit as u64),
             before,
@@ -698,17 +772,11 @@ impl ApiHandlers {
             sort_descending: true,
         };
 
-        // Use the new k_contents table method with blocking awareness
-        let replies_result = match self
-            .db
-            .get_replies_by_user(user_public_key, requester_pubkey, options)
-            .await
-        {
+        let broadcasts_result = match self.db.get_all_users(requester_pubkey, options).await {
             Ok(result) => result,
             Err(err) => {
                 log_error!(
-                    "Database error while querying paginated user replies with metadata for user {}: {}",
-                    user_public_key,
+                    "Database error while querying paginated user broadcasts with block status: {}",
                     err
                 );
                 return Err(self.create_error_response(
@@ -718,25 +786,32 @@ impl ApiHandlers {
             }
         };
 
-        // Convert enriched KReplyRecords to ServerReplies (blocked users already excluded)
-        let all_replies: Vec<ServerReply> = replies_result
-            .items
-            .iter()
-            .map(|reply_record| {
-                ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
-            })
-            .collect();
+        let mut all_posts = Vec::new();
 
-        let response = PaginatedRepliesResponse {
-            replies: all_replies,
-            pagination: replies_result.pagination,
+        for (k_broadcast_record, is_blocked, is_followed) in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
+                &k_broadcast_record,
+                is_blocked,
+            );
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+            server_user_post.followed_user = Some(is_followed);
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
                 log_error!(
-                    "Failed to serialize paginated user replies response: {}",
+                    "Failed to serialize paginated users response with block status: {}",
                     err
                 );
                 Err(self.create_error_response(
@@ -747,80 +822,53 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-mentions with pagination
-    /// Fetch paginated posts and replies where a specific user has been mentioned with voting status
-    pub async fn get_mentions_paginated(
+    /// GET /get-most-active-users with pagination
+    /// Fetch users ranked by total content count (posts, replies, quotes) in k_contents
+    /// within a specific time window
+    pub async fn get_most_active_users_paginated(
         &self,
-        user_public_key: &str,
-        requester_pubkey: &str,
         limit: u32,
+        requester_pubkey: &str,
+        time_window: &str,
         before: Option<String>,
         after: Option<String>,
     ) -> Result<String, String> {
-        // Validate user public key format (66 hex characters for compressed public key)
-        if user_public_key.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        use std::time::{SystemTime, UNIX_EPOCH};
 
-        if !user_public_key.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        // Calculate time window in milliseconds (block_time is stored in milliseconds)
+        let window_millis = match time_window {
+            "1h" => 3_600_000_u64,
+            "6h" => 21_600_000_u64,
+            "24h" => 86_400_000_u64,
+            "7d" => 604_800_000_u64,
+            "30d" => 2_592_000_000_u64,
+            _ => {
+                return Err(self
+                    .create_error_response("Invalid time window parameter", "INVALID_PARAMETER"));
+            }
+        };
 
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !user_public_key.starts_with("02") && !user_public_key.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        let to_time_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let from_time_millis = to_time_millis.saturating_sub(window_millis);
 
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Fetch limit + 1 to check if there are more results
-        let fetch_limit = limit + 1;
         let options = QueryOptions {
-            limit: Some(fetch_limit as u64),
+            limit: Some(limit as u64),
             before,
             after,
             sort_descending: true,
         };
 
-        // Use the new k_contents table method with blocking awareness
-        let mentions_result = match self
+        let result = match self
             .db
-            .get_contents_mentioning_user(user_public_key, requester_pubkey, options)
+            .get_most_active_users(requester_pubkey, options, from_time_millis, to_time_millis)
             .await
         {
             Ok(result) => result,
             Err(err) => {
-                log_error!("Error getting mentions with metadata for user: {}", err);
+                log_error!("Database error while querying most active users: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -828,57 +876,31 @@ impl ApiHandlers {
             }
         };
 
-        // Convert enriched ContentRecords (posts and replies) to ServerPosts (blocked users already excluded)
-        let all_mentions: Vec<ServerPost> = mentions_result
-            .items
-            .iter()
-            .map(|content_record| match content_record {
-                ContentRecord::Post(post_record) => {
-                    ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
-                }
-                ContentRecord::Reply(reply_record) => {
-                    ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
-                }
-                ContentRecord::Vote(_vote_record) => {
-                    // For get-mentions, votes are returned as ServerReply (same structure as ServerPost)
-                    // but with minimal content since votes don't have message content
-                    ServerPost {
-                        id: _vote_record.transaction_id.clone(),
-                        user_public_key: _vote_record.sender_pubkey.clone(),
-                        post_content: String::new(), // Votes don't have content in mentions
-                        signature: _vote_record.sender_signature.clone(),
-                        timestamp: _vote_record.block_time,
-                        replies_count: 0,
-                        quotes_count: 0,
-                        up_votes_count: 0,
-                        down_votes_count: 0,
-                        reposts_count: 0,
-                        parent_post_id: Some(_vote_record.post_id.clone()),
-                        mentioned_pubkeys: Vec::new(),
-                        is_upvoted: None,
-                        is_downvoted: None,
-                        user_nickname: _vote_record.user_nickname.clone(),
-                        user_profile_image: _vote_record.user_profile_image.clone(),
-                        blocked_user: Some(false),
-                        content_type: Some("vote".to_string()),
-                        is_quote: false,
-                        quote: None,
-                    }
-                }
-            })
-            .collect();
+        let mut all_posts = Vec::new();
 
-        let pagination = mentions_result.pagination;
+        for (k_broadcast_record, is_blocked, is_followed, content_count) in result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
+                &k_broadcast_record,
+                is_blocked,
+            );
 
-        let response = PaginatedPostsResponse {
-            posts: all_mentions,
-            pagination,
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+            server_user_post.followed_user = Some(is_followed);
+            server_user_post.contents_count = Some(content_count);
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: result.pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize paginated mentions response: {}", err);
+                log_error!("Failed to serialize most active users response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -887,53 +909,56 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-notifications?requesterPubkey={requesterPubkey}&limit={limit}&before={before}&after={after}
-    /// Fetch notifications for a user based on mentions in k_mentions table with detailed content information
-    pub async fn get_notifications_paginated(
+    /// GET /search-users with pagination
+    /// Search users with optional filters for pubkey or nickname
+    pub async fn search_users_paginated(
         &self,
-        requester_pubkey: &str,
         limit: u32,
+        requester_pubkey: &str,
         before: Option<String>,
         after: Option<String>,
+        searched_user_pubkey: Option<String>,
+        searched_user_nickname: Option<String>,
     ) -> Result<String, String> {
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
+        // Validate searched_user_pubkey if provided
+        if let Some(ref pubkey) = searched_user_pubkey {
+            if pubkey.len() != 66 {
+                return Err(self.create_error_response(
+                    "Invalid searched user public key format. Must be 66 hex characters.",
+                    "INVALID_USER_KEY",
+                ));
+            }
+            if !pubkey.starts_with("02") && !pubkey.starts_with("03") {
+                return Err(self.create_error_response(
+                    "Invalid searched user public key prefix. Must start with 02 or 03.",
+                    "INVALID_USER_KEY",
+                ));
+            }
         }
 
-        // Fetch limit + 1 to check if there are more results
-        let fetch_limit = limit + 1;
         let options = QueryOptions {
-            limit: Some(fetch_limit as u64),
+            limit: Some(limit as u64),
             before,
             after,
             sort_descending: true,
         };
 
-        // Use the new k_contents table method to get notifications with content details
-        let notifications_result = match self.db.get_notifications(requester_pubkey, options).await
+        // Strip the 02/03 prefix from the searched pubkey to match both variants
+        let searched_pubkey_without_prefix = searched_user_pubkey.map(|pk| pk[2..].to_string());
+
+        let broadcasts_result = match self
+            .db
+            .search_users(
+                requester_pubkey,
+                options,
+                searched_pubkey_without_prefix,
+                searched_user_nickname,
+            )
+            .await
         {
             Ok(result) => result,
             Err(err) => {
-                log_error!("Error getting notifications for user: {}", err);
+                log_error!("Database error while searching users: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -941,53 +966,70 @@ impl ApiHandlers {
             }
         };
 
-        // Convert NotificationContentRecords to NotificationPost with correct mention cursors
-        let all_notifications: Vec<NotificationPost> = notifications_result
-            .items
-            .iter()
-            .map(|notification_record| match &notification_record.content {
-                ContentRecord::Post(post_record) => {
-                    NotificationPost::from_k_post_record_with_mention_cursor(
-                        &post_record,
-                        notification_record.mention_id,
-                        notification_record.mention_block_time,
-                    )
-                }
-                ContentRecord::Reply(reply_record) => {
-                    NotificationPost::from_k_reply_record_with_mention_cursor(
-                        &reply_record,
-                        notification_record.mention_id,
-                        notification_record.mention_block_time,
-                    )
-                }
-                ContentRecord::Vote(vote_record) => {
-                    // For votes, we now have enriched vote record with all necessary data
-                    NotificationPost::from_k_vote_record_with_mention_cursor(
-                        &vote_record,
-                        notification_record.mention_id,
-                        notification_record.mention_block_time,
-                        vote_record.voted_content.clone().unwrap_or_default(),
-                        vote_record.user_nickname.clone(),
-                        vote_record.user_profile_image.clone(),
-                    )
-                }
-            })
-            .collect();
+        self.search_users_response(broadcasts_result)
+    }
 
-        let pagination = notifications_result.pagination;
+    /// GET /search-users?query=...
+    /// Nickname lookup for mention autocomplete: users whose nickname contains the query,
+    /// nicknames starting with it first
+    pub async fn search_users_by_nickname(
+        &self,
+        limit: u32,
+        requester_pubkey: &str,
+        query: &str,
+    ) -> Result<String, String> {
+        let broadcasts_result = match self
+            .db
+            .search_broadcasts_by_nickname(requester_pubkey, query, limit)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while searching users by nickname '{}': {}",
+                    query,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
 
-        let response = PaginatedNotificationsResponse {
-            notifications: all_notifications,
-            pagination,
+        self.search_users_response(broadcasts_result)
+    }
+
+    /// Serialize found users with their block and follow status as a PaginatedUsersResponse
+    fn search_users_response(
+        &self,
+        broadcasts_result: PaginatedResult<(KBroadcastRecord, bool, bool)>,
+    ) -> Result<String, String> {
+        let mut all_posts = Vec::new();
+
+        for (k_broadcast_record, is_blocked, is_followed) in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record_with_block_status(
+                &k_broadcast_record,
+                is_blocked,
+            );
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+            server_user_post.followed_user = Some(is_followed);
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated notifications response: {}",
-                    err
-                );
+                log_error!("Failed to serialize search users response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -996,27 +1038,28 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-post-details?id={postId}&requesterPubkey={requesterPubkey}
-    /// Fetch details for a specific post or reply by its ID with voting information for the requesting user
-
-    /// GET /get-post-details?id={postId}&requesterPubkey={requesterPubkey}
-    /// Fetch details for a specific post or reply by its ID with voting information and blocking status for the requesting user
-    pub async fn get_post_details(
+    /// GET /get-replies with pagination (Post Replies Mode)
+    /// Fetch paginated replies for a specific post with cursor-based pagination and voting status
+    pub async fn get_replies_paginated(
         &self,
-        content_id: &str,
+        post_id: &str,
         requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+        include: IncludeOptions,
     ) -> Result<String, String> {
-        // Validate content ID format (64 hex characters for transaction hash)
-        if content_id.len() != 64 {
+        // Validate post ID format (64 hex characters for transaction hash)
+        if post_id.len() != 64 {
             return Err(self.create_error_response(
-                "Invalid content ID format. Must be 64 hex characters.",
+                "Invalid post ID format. Must be 64 hex characters.",
                 "INVALID_POST_ID",
             ));
         }
 
-        if !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        if !post_id.chars().all(|c| c.is_ascii_hexdigit()) {
             return Err(self.create_error_response(
-                "Invalid content ID format. Must contain only hex characters.",
+                "Invalid post ID format. Must contain only hex characters.",
                 "INVALID_POST_ID",
             ));
         }
@@ -1044,92 +1087,75 @@ impl ApiHandlers {
             ));
         }
 
-        // Use the new k_contents table function to get content with metadata and block status
-        match self
-            .db
-            .get_content_by_id(content_id, requester_pubkey)
-            .await
-        {
-            Ok(Some((content_record, is_blocked))) => {
-                let response = match content_record {
-                    ContentRecord::Post(k_post_record) => {
-                        let server_post = ServerPost::from_enriched_k_post_record_with_block_status(
-                            &k_post_record,
-                            is_blocked,
-                        );
-                        PostDetailsResponse { post: server_post }
-                    }
-                    ContentRecord::Reply(k_reply_record) => {
-                        let server_reply =
-                            ServerReply::from_enriched_k_reply_record_with_block_status(
-                                &k_reply_record,
-                                is_blocked,
-                            );
-                        PostDetailsResponse { post: server_reply }
-                    }
-                    ContentRecord::Vote(k_vote_record) => {
-                        // For get-post-details, votes are returned as ServerPost with vote-specific info
-                        let server_vote = ServerPost {
-                            id: k_vote_record.transaction_id.clone(),
-                            user_public_key: k_vote_record.sender_pubkey.clone(),
-                            post_content: String::new(), // Votes don't have content
-                            signature: k_vote_record.sender_signature.clone(),
-                            timestamp: k_vote_record.block_time,
-                            replies_count: 0,
-                            quotes_count: 0,
-                            up_votes_count: 0,
-                            down_votes_count: 0,
-                            reposts_count: 0,
-                            parent_post_id: Some(k_vote_record.post_id.clone()),
-                            mentioned_pubkeys: Vec::new(),
-                            is_upvoted: None,
-                            is_downvoted: None,
-                            user_nickname: k_vote_record.user_nickname.clone(),
-                            user_profile_image: k_vote_record.user_profile_image.clone(),
-                            blocked_user: Some(is_blocked),
-                            content_type: Some("vote".to_string()),
-                            is_quote: false,
-                            quote: None,
-                        };
-                        PostDetailsResponse { post: server_vote }
-                    }
-                };
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
 
-                match serde_json::to_string(&response) {
-                    Ok(json) => Ok(json),
-                    Err(err) => {
-                        log_error!("Failed to serialize content details response: {}", err);
-                        Err(self.create_error_response(
-                            "Internal server error during serialization",
-                            "SERIALIZATION_ERROR",
-                        ))
-                    }
-                }
-            }
-            Ok(None) => {
-                // Content not found
-                Err(self.create_error_response("Content not found", "NOT_FOUND"))
-            }
+        // Use the new k_contents table method with blocking awareness
+        let replies_result = match self
+            .db
+            .get_replies_by_post_id(post_id, requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
             Err(err) => {
                 log_error!(
-                    "Database error while querying content by ID {}: {}",
-                    content_id,
+                    "Database error while querying paginated replies with metadata for post {}: {}",
+                    post_id,
                     err
                 );
-                Err(self.create_error_response(
+                return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Convert enriched KReplyRecords to ServerReplies (blocked users already excluded)
+        let mut all_replies: Vec<ServerReply> = replies_result
+            .items
+            .iter()
+            .map(|reply_record| {
+                ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
+            })
+            .collect();
+
+        self.attach_nested_replies(&mut all_replies, requester_pubkey, include.replies)
+            .await?;
+        if include.vote_weights {
+            self.attach_vote_weights(&mut all_replies).await?;
+        }
+
+        let response = PaginatedRepliesResponse {
+            replies: all_replies,
+            pagination: replies_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize paginated replies response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
                 ))
             }
         }
     }
 
-    /// GET /get-user-details with user parameter
-    /// Fetch user details from k_broadcast table for a specific user public key
-    pub async fn get_user_details(
+    /// GET /get-replies with pagination (User Replies Mode)
+    /// Fetch paginated replies made by a specific user with cursor-based pagination and voting status
+    pub async fn get_user_replies_paginated(
         &self,
         user_public_key: &str,
         requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+        include: IncludeOptions,
     ) -> Result<String, String> {
         // Validate user public key format (66 hex characters for compressed public key)
         if user_public_key.len() != 66 {
@@ -1177,16 +1203,23 @@ impl ApiHandlers {
             ));
         }
 
-        // Get the user's broadcast record from k_broadcast table with block/follow status
-        let broadcast_result = match self
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        // Use the new k_contents table method with blocking awareness
+        let replies_result = match self
             .db
-            .get_user_details(user_public_key, requester_pubkey)
+            .get_replies_by_user(user_public_key, requester_pubkey, options)
             .await
         {
             Ok(result) => result,
             Err(err) => {
                 log_error!(
-                    "Database error while querying user details for user {}: {}",
+                    "Database error while querying paginated user replies with metadata for user {}: {}",
                     user_public_key,
                     err
                 );
@@ -1197,65 +1230,33 @@ impl ApiHandlers {
             }
         };
 
-        // Handle user data (even if no broadcast exists)
-        let server_user_post = match broadcast_result {
-            Some((record, blocked, followed, followers_count, following_count, blocked_count)) => {
-                // Check if this is a dummy record (no real broadcast data)
-                if record.id == 0 && record.transaction_id.is_empty() {
-                    // User has no broadcast data - create minimal response with empty fields
-                    ServerUserPost {
-                        id: String::new(),
-                        user_public_key: user_public_key.to_string(),
-                        post_content: String::new(),
-                        signature: String::new(),
-                        timestamp: 0,
-                        user_nickname: None,
-                        user_profile_image: None,
-                        blocked_user: Some(blocked), // Use the actual blocking status from database
-                        followed_user: Some(followed), // Use the actual following status from database
-                        followers_count: Some(followers_count),
-                        following_count: Some(following_count),
-                        blocked_count: Some(blocked_count),
-                        contents_count: None,
-                    }
-                } else {
-                    // User has real broadcast data
-                    let mut user_post =
-                        ServerUserPost::from_k_broadcast_record_with_block_and_follow_status(
-                            &record, blocked, followed,
-                        );
-                    user_post.user_nickname = Some(record.base64_encoded_nickname);
-                    user_post.user_profile_image = record.base64_encoded_profile_image;
-                    user_post.followers_count = Some(followers_count);
-                    user_post.following_count = Some(following_count);
-                    user_post.blocked_count = Some(blocked_count);
-                    user_post
-                }
-            }
-            None => {
-                // This case should no longer happen with our new implementation
-                ServerUserPost {
-                    id: String::new(),
-                    user_public_key: user_public_key.to_string(),
-                    post_content: String::new(),
-                    signature: String::new(),
-                    timestamp: 0,
-                    user_nickname: None,
-                    user_profile_image: None,
-                    blocked_user: Some(false),
-                    followed_user: Some(false),
-                    followers_count: Some(0),
-                    following_count: Some(0),
-                    blocked_count: Some(0),
-                    contents_count: None,
-                }
-            }
+        // Convert enriched KReplyRecords to ServerReplies (blocked users already excluded)
+        let mut all_replies: Vec<ServerReply> = replies_result
+            .items
+            .iter()
+            .map(|reply_record| {
+                ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
+            })
+            .collect();
+
+        self.attach_nested_replies(&mut all_replies, requester_pubkey, include.replies)
+            .await?;
+        if include.vote_weights {
+            self.attach_vote_weights(&mut all_replies).await?;
+        }
+
+        let response = PaginatedRepliesResponse {
+            replies: all_replies,
+            pagination: replies_result.pagination,
         };
 
-        match serde_json::to_string(&server_user_post) {
+        match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!("Failed to serialize user details response: {}", err);
+                log_error!(
+                    "Failed to serialize paginated user replies response: {}",
+                    err
+                );
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -1264,15 +1265,39 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-blocked-users with pagination
-    /// Fetch paginated list of users blocked by the requester
-    pub async fn get_blocked_users_paginated(
+    /// GET /get-mentions with pagination
+    /// Fetch paginated posts and replies where a specific user has been mentioned with voting status
+    pub async fn get_mentions_paginated(
         &self,
+        user_public_key: &str,
         requester_pubkey: &str,
         limit: u32,
         before: Option<String>,
         after: Option<String>,
     ) -> Result<String, String> {
+        // Validate user public key format (66 hex characters for compressed public key)
+        if user_public_key.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !user_public_key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !user_public_key.starts_with("02") && !user_public_key.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
         // Validate requester public key format (66 hex characters for compressed public key)
         if requester_pubkey.len() != 66 {
             return Err(self.create_error_response(
@@ -1296,25 +1321,24 @@ impl ApiHandlers {
             ));
         }
 
+        // Fetch limit + 1 to check if there are more results
+        let fetch_limit = limit + 1;
         let options = QueryOptions {
-            limit: Some(limit as u64),
+            limit: Some(fetch_limit as u64),
             before,
             after,
             sort_descending: true,
         };
 
-        let broadcasts_result = match self
+        // Use the new k_contents table method with blocking awareness
+        let mentions_result = match self
             .db
-            .get_blocked_users_by_requester(requester_pubkey, options)
+            .get_contents_mentioning_user(user_public_key, requester_pubkey, options)
             .await
         {
             Ok(result) => result,
             Err(err) => {
-                log_error!(
-                    "Database error while querying blocked users for requester {}: {}",
-                    requester_pubkey,
-                    err
-                );
+                log_error!("Error getting mentions with metadata for user: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -1322,36 +1346,64 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for k_broadcast_record in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
-
-            // Since these are blocked users, set blocked_user to true
-            server_user_post.blocked_user = Some(true);
-
-            // Remove post content for blocked users
-            server_user_post.post_content = String::new();
+        // Convert enriched ContentRecords (posts and replies) to ServerPosts (blocked users already excluded)
+        let all_mentions: Vec<ServerPost> = mentions_result
+            .items
+            .iter()
+            .map(|content_record| match content_record {
+                ContentRecord::Post(post_record) => {
+                    ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
+                }
+                ContentRecord::Reply(reply_record) => {
+                    ServerReply::from_enriched_k_reply_record_with_block_status(reply_record, false)
+                }
+                ContentRecord::Vote(_vote_record) => {
+                    // For get-mentions, votes are returned as ServerReply (same structure as ServerPost)
+                    // but with minimal content since votes don't have message content
+                    ServerPost {
+                        id: _vote_record.transaction_id.clone(),
+                        user_public_key: _vote_record.sender_pubkey.clone(),
+                        post_content: String::new(), // Votes don't have content in mentions
+                        signature: _vote_record.sender_signature.clone(),
+                        timestamp: _vote_record.block_time,
+                        replies_count: 0,
+                        quotes_count: 0,
+                        up_votes_count: 0,
+                        down_votes_count: 0,
+                        reposts_count: 0,
+                        parent_post_id: Some(_vote_record.post_id.clone()),
+                        mentioned_pubkeys: Vec::new(),
+                        mentions_count: None,
+                        render_spans: None,
+                        is_upvoted: None,
+                        is_downvoted: None,
+                        user_nickname: _vote_record.user_nickname.clone(),
+                        user_profile_image: _vote_record.user_profile_image.clone(),
+                        blocked_user: Some(false),
+                        content_type: Some("vote".to_string()),
+                        is_quote: false,
+                        quote: None,
+                        nested_replies: None,
+                        view_count: None,
+                        up_votes_weight: None,
+                        down_votes_weight: None,
+                        explorer_links: None,
+                    }
+                }
+            })
+            .collect();
 
-            all_posts.push(server_user_post);
-        }
+        let pagination = mentions_result.pagination;
 
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
+        let response = PaginatedPostsResponse {
+            posts: all_mentions,
+            pagination,
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated blocked users response: {}",
-                    err
-                );
+                log_error!("Failed to serialize paginated mentions response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -1360,7 +1412,9 @@ impl ApiHandlers {
         }
     }
 
-    pub async fn get_followed_users_paginated(
+    /// GET /get-notifications?requesterPubkey={requesterPubkey}&limit={limit}&before={before}&after={after}
+    /// Fetch notifications for a user based on mentions in k_mentions table with detailed content information
+    pub async fn get_notifications_paginated(
         &self,
         requester_pubkey: &str,
         limit: u32,
@@ -1390,25 +1444,2337 @@ impl ApiHandlers {
             ));
         }
 
+        // Fetch limit + 1 to check if there are more results
+        let fetch_limit = limit + 1;
         let options = QueryOptions {
-            limit: Some(limit as u64),
+            limit: Some(fetch_limit as u64),
             before,
             after,
             sort_descending: true,
         };
 
-        let broadcasts_result = match self
+        // Use the new k_contents table method to get notifications with content details
+        let notifications_result = match self.db.get_notifications(requester_pubkey, options).await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!("Error getting notifications for user: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Convert NotificationContentRecords to NotificationPost with correct mention cursors
+        let all_notifications: Vec<NotificationPost> = notifications_result
+            .items
+            .iter()
+            .map(|notification_record| {
+                let mut notification = match &notification_record.subject {
+                    NotificationSubject::Content(content) => match content.as_ref() {
+                        ContentRecord::Post(post_record) => {
+                            NotificationPost::from_k_post_record_with_mention_cursor(
+                                post_record,
+                                notification_record.mention_id,
+                                notification_record.mention_block_time,
+                            )
+                        }
+                        ContentRecord::Reply(reply_record) => {
+                            NotificationPost::from_k_reply_record_with_mention_cursor(
+                                reply_record,
+                                notification_record.mention_id,
+                                notification_record.mention_block_time,
+                            )
+                        }
+                        ContentRecord::Vote(vote_record) => {
+                            // For votes, we now have enriched vote record with all necessary data
+                            NotificationPost::from_k_vote_record_with_mention_cursor(
+                                vote_record,
+                                notification_record.mention_id,
+                                notification_record.mention_block_time,
+                                vote_record.voted_content.clone().unwrap_or_default(),
+                                vote_record.user_nickname.clone(),
+                                vote_record.user_profile_image.clone(),
+                            )
+                        }
+                    },
+                    NotificationSubject::Follow(follow_record) => {
+                        NotificationPost::from_k_follow_record_with_mention_cursor(
+                            follow_record,
+                            notification_record.mention_id,
+                            notification_record.mention_block_time,
+                        )
+                    }
+                };
+                notification.collapse_count =
+                    Some(notification_record.collapsed_count).filter(|&count| count > 1);
+                notification
+            })
+            .collect();
+
+        let pagination = notifications_result.pagination;
+
+        let response = PaginatedNotificationsResponse {
+            notifications: all_notifications,
+            pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated notifications response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-post-details?id={postId}&requesterPubkey={requesterPubkey}
+    /// Fetch details for a specific post or reply by its ID with voting information for the requesting user
+
+    /// GET /get-post-details?id={postId}&requesterPubkey={requesterPubkey}
+    /// Fetch details for a specific post or reply by its ID with voting information and blocking status for the requesting user
+    pub async fn get_post_details(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+        include: IncludeOptions,
+        include_view_count: bool,
+        include_archived: bool,
+    ) -> Result<String, String> {
+        // Validate content ID format (64 hex characters for transaction hash)
+        if content_id.len() != 64 {
+            return Err(self.create_error_response(
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        if !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid content ID format. Must contain only hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Use the new k_contents table function to get content with metadata and block status
+        match self
+            .db
+            .get_content_by_id(content_id, requester_pubkey)
+            .await
+        {
+            Ok(Some((content_record, is_blocked))) => {
+                let mut response = match content_record {
+                    ContentRecord::Post(k_post_record) => {
+                        let server_post = ServerPost::from_enriched_k_post_record_with_block_status(
+                            &k_post_record,
+                            is_blocked,
+                        );
+                        let mut posts = [server_post];
+                        self.attach_nested_replies(&mut posts, requester_pubkey, include.replies)
+                            .await?;
+                        let [server_post] = posts;
+                        PostDetailsResponse {
+                            post: server_post,
+                            archived: None,
+                        }
+                    }
+                    ContentRecord::Reply(k_reply_record) => {
+                        let server_reply =
+                            ServerReply::from_enriched_k_reply_record_with_block_status(
+                                &k_reply_record,
+                                is_blocked,
+                            );
+                        let mut replies = [server_reply];
+                        self.attach_nested_replies(&mut replies, requester_pubkey, include.replies)
+                            .await?;
+                        let [server_reply] = replies;
+                        PostDetailsResponse {
+                            post: server_reply,
+                            archived: None,
+                        }
+                    }
+                    ContentRecord::Vote(k_vote_record) => {
+                        // For get-post-details, votes are returned as ServerPost with vote-specific info
+                        let server_vote = ServerPost {
+                            id: k_vote_record.transaction_id.clone(),
+                            user_public_key: k_vote_record.sender_pubkey.clone(),
+                            post_content: String::new(), // Votes don't have content
+                            signature: k_vote_record.sender_signature.clone(),
+                            timestamp: k_vote_record.block_time,
+                            replies_count: 0,
+                            quotes_count: 0,
+                            up_votes_count: 0,
+                            down_votes_count: 0,
+                            reposts_count: 0,
+                            parent_post_id: Some(k_vote_record.post_id.clone()),
+                            mentioned_pubkeys: Vec::new(),
+                            mentions_count: None,
+                            render_spans: None,
+                            is_upvoted: None,
+                            is_downvoted: None,
+                            user_nickname: k_vote_record.user_nickname.clone(),
+                            user_profile_image: k_vote_record.user_profile_image.clone(),
+                            blocked_user: Some(is_blocked),
+                            content_type: Some("vote".to_string()),
+                            is_quote: false,
+                            quote: None,
+                            nested_replies: None,
+                            view_count: None,
+                            up_votes_weight: None,
+                            down_votes_weight: None,
+                            explorer_links: None,
+                        };
+                        PostDetailsResponse {
+                            post: server_vote,
+                            archived: None,
+                        }
+                    }
+                };
+
+                // View counts can be rolled out gradually with the view_counts flag
+                if include_view_count
+                    && self
+                        .flags
+                        .check(VIEW_COUNTS, Some(requester_pubkey))
+                        .unwrap_or(true)
+                {
+                    response.post.view_count = Some(self.get_view_count(content_id).await?);
+                }
+
+                if include.vote_weights {
+                    self.attach_vote_weights(std::slice::from_mut(&mut response.post))
+                        .await?;
+                }
+
+                match serde_json::to_string(&response) {
+                    Ok(json) => Ok(json),
+                    Err(err) => {
+                        log_error!("Failed to serialize content details response: {}", err);
+                        Err(self.create_error_response(
+                            "Internal server error during serialization",
+                            "SERIALIZATION_ERROR",
+                        ))
+                    }
+                }
+            }
+            Ok(None) if include_archived => {
+                self.get_archived_post_details(content_id, requester_pubkey)
+                    .await
+            }
+            Ok(None) => {
+                // Content not found
+                Err(self.create_error_response("Content not found", "NOT_FOUND"))
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while querying content by ID {}: {}",
+                    content_id,
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-post-details with includeArchived=true, content no longer in k_contents
+    /// Archived posts/quotes have no replies or votes left: only the content itself is returned
+    async fn get_archived_post_details(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+    ) -> Result<String, String> {
+        match self
+            .db
+            .get_archived_content_by_id(content_id, requester_pubkey)
+            .await
+        {
+            Ok(Some((k_post_record, is_blocked))) => {
+                let response = PostDetailsResponse {
+                    post: ServerPost::from_enriched_k_post_record_with_block_status(
+                        &k_post_record,
+                        is_blocked,
+                    ),
+                    archived: Some(true),
+                };
+
+                match serde_json::to_string(&response) {
+                    Ok(json) => Ok(json),
+                    Err(err) => {
+                        log_error!("Failed to serialize archived content response: {}", err);
+                        Err(self.create_error_response(
+                            "Internal server error during serialization",
+                            "SERIALIZATION_ERROR",
+                        ))
+                    }
+                }
+            }
+            Ok(None) => Err(self.create_error_response("Content not found", "NOT_FOUND")),
+            Err(err) => {
+                log_error!(
+                    "Database error while querying archived content by ID {}: {}",
+                    content_id,
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-thread?id={contentId}&requesterPubkey={requesterPubkey}&maxDepth={maxDepth}
+    /// Fetch a post or reply with a page of its direct replies and, down to `max_depth` levels,
+    /// the first `limit` replies of every reply (read with the whole conversation when it has
+    /// up to MAX_THREAD_SCAN replies, else one query per level)
+    pub async fn get_thread(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+        max_depth: u32,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        if max_depth == 0 || max_depth > MAX_THREAD_DEPTH {
+            return Err(self.create_error_response(
+                &format!("maxDepth must be between 1 and {}", MAX_THREAD_DEPTH),
+                "INVALID_PARAMETER",
+            ));
+        }
+
+        // Validates the content ID and requester, then reads the root
+        let details = self
+            .get_post_details(
+                content_id,
+                requester_pubkey,
+                IncludeOptions::default(),
+                false,
+                false,
+            )
+            .await?;
+        let post = match serde_json::from_str::<PostDetailsResponse>(&details) {
+            Ok(details) => details.post,
+            Err(err) => {
+                log_error!("Failed to parse thread root: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ));
+            }
+        };
+        if post.content_type.as_deref() == Some("vote") {
+            return Err(self.create_error_response(
+                "Votes have no thread, use the ID of a post, reply or quote",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+        let first_level = match self
+            .db
+            .get_replies_by_post_id(content_id, requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying thread replies for {}: {}",
+                    content_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Levels of (parent ID, reply), the direct replies of the root first
+        let mut levels: Vec<Vec<(String, ServerReply)>> = vec![
+            first_level
+                .items
+                .iter()
+                .map(|record| {
+                    (
+                        record.post_id.clone(),
+                        ServerReply::from_enriched_k_reply_record_with_block_status(record, false),
+                    )
+                })
+                .collect(),
+        ];
+        // Replies of every reply in the conversation, newest first, when it could be read at once
+        let mut thread_children: Option<HashMap<String, Vec<KReplyRecord>>> = None;
+        if max_depth > 1 && levels[0].iter().any(|(_, reply)| reply.replies_count > 0) {
+            match self
+                .db
+                .get_thread_replies(content_id, requester_pubkey, MAX_THREAD_SCAN)
+                .await
+            {
+                Ok(Some(records)) => {
+                    let mut children: HashMap<String, Vec<KReplyRecord>> = HashMap::new();
+                    for record in records {
+                        children
+                            .entry(record.post_id.clone())
+                            .or_default()
+                            .push(record);
+                    }
+                    thread_children = Some(children);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    log_error!(
+                        "Database error while querying the conversation of {}: {}",
+                        content_id,
+                        err
+                    );
+                    return Err(self.create_error_response(
+                        "Internal server error during database query",
+                        "DATABASE_ERROR",
+                    ));
+                }
+            }
+        }
+
+        // Replies whose own replies were fetched, as many as the remaining budget allows
+        let mut expanded: HashSet<String> = HashSet::new();
+        let mut total = levels[0].len();
+        while levels.len() < max_depth as usize {
+            let budget = MAX_THREAD_REPLIES.saturating_sub(total) / limit.max(1) as usize;
+            let parent_ids: Vec<String> = levels[levels.len() - 1]
+                .iter()
+                .filter(|(_, reply)| reply.replies_count > 0)
+                .map(|(_, reply)| reply.id.clone())
+                .take(budget)
+                .collect();
+            if parent_ids.is_empty() {
+                break;
+            }
+            expanded.extend(parent_ids.iter().cloned());
+
+            let records = match &mut thread_children {
+                Some(children) => parent_ids
+                    .iter()
+                    .flat_map(|parent_id| {
+                        let mut replies = children.remove(parent_id).unwrap_or_default();
+                        replies.truncate(limit as usize);
+                        replies
+                    })
+                    .collect(),
+                None => match self
+                    .db
+                    .get_nested_replies(&parent_ids, requester_pubkey, limit)
+                    .await
+                {
+                    Ok(records) => records,
+                    Err(err) => {
+                        log_error!("Database error while querying thread replies: {}", err);
+                        return Err(self.create_error_response(
+                            "Internal server error during database query",
+                            "DATABASE_ERROR",
+                        ));
+                    }
+                },
+            };
+            total += records.len();
+            levels.push(
+                records
+                    .iter()
+                    .map(|record| {
+                        (
+                            record.post_id.clone(),
+                            ServerReply::from_enriched_k_reply_record_with_block_status(
+                                record, false,
+                            ),
+                        )
+                    })
+                    .collect(),
+            );
+        }
+
+        // Attach each level to its parents, deepest first; nestedReplies stays unset on the
+        // replies that were not expanded
+        let mut children: HashMap<String, Vec<ServerReply>> = HashMap::new();
+        for level in levels.into_iter().rev() {
+            let mut parents: HashMap<String, Vec<ServerReply>> = HashMap::new();
+            for (parent_id, mut reply) in level {
+                if expanded.contains(&reply.id) {
+                    reply.nested_replies = Some(children.remove(&reply.id).unwrap_or_default());
+                }
+                parents.entry(parent_id).or_default().push(reply);
+            }
+            children = parents;
+        }
+
+        let replies = children.remove(&post.id).unwrap_or_default();
+        let response = ThreadResponse {
+            post,
+            replies,
+            pagination: first_level.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize thread response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-user-details with user parameter
+    /// Fetch user details from k_broadcast table for a specific user public key
+    pub async fn get_user_details(
+        &self,
+        user_public_key: &str,
+        requester_pubkey: &str,
+    ) -> Result<String, String> {
+        // Validate user public key format (66 hex characters for compressed public key)
+        if user_public_key.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !user_public_key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !user_public_key.starts_with("02") && !user_public_key.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Get the user's broadcast record from k_broadcast table with block/follow status
+        let broadcast_result = match self
+            .db
+            .get_user_details(user_public_key, requester_pubkey)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying user details for user {}: {}",
+                    user_public_key,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Handle user data (even if no broadcast exists)
+        let server_user_post = match broadcast_result {
+            Some((record, blocked, followed, followers_count, following_count, blocked_count)) => {
+                // Check if this is a dummy record (no real broadcast data)
+                if record.id == 0 && record.transaction_id.is_empty() {
+                    // User has no broadcast data - create minimal response with empty fields
+                    ServerUserPost {
+                        id: String::new(),
+                        user_public_key: user_public_key.to_string(),
+                        post_content: String::new(),
+                        signature: String::new(),
+                        timestamp: 0,
+                        user_nickname: None,
+                        user_profile_image: None,
+                        blocked_user: Some(blocked), // Use the actual blocking status from database
+                        followed_user: Some(followed), // Use the actual following status from database
+                        followers_count: Some(followers_count),
+                        following_count: Some(following_count),
+                        blocked_count: Some(blocked_count),
+                        contents_count: None,
+                    }
+                } else {
+                    // User has real broadcast data
+                    let mut user_post =
+                        ServerUserPost::from_k_broadcast_record_with_block_and_follow_status(
+                            &record, blocked, followed,
+                        );
+                    user_post.user_nickname = Some(record.base64_encoded_nickname);
+                    user_post.user_profile_image = record.base64_encoded_profile_image;
+                    user_post.followers_count = Some(followers_count);
+                    user_post.following_count = Some(following_count);
+                    user_post.blocked_count = Some(blocked_count);
+                    user_post
+                }
+            }
+            None => {
+                // This case should no longer happen with our new implementation
+                ServerUserPost {
+                    id: String::new(),
+                    user_public_key: user_public_key.to_string(),
+                    post_content: String::new(),
+                    signature: String::new(),
+                    timestamp: 0,
+                    user_nickname: None,
+                    user_profile_image: None,
+                    blocked_user: Some(false),
+                    followed_user: Some(false),
+                    followers_count: Some(0),
+                    following_count: Some(0),
+                    blocked_count: Some(0),
+                    contents_count: None,
+                }
+            }
+        };
+
+        match serde_json::to_string(&server_user_post) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize user details response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-user-stats?user={user}
+    /// Content, vote and activity totals of a user
+    pub async fn get_user_stats(&self, user_public_key: &str) -> Result<String, String> {
+        // Validate user public key format (66 hex characters for compressed public key)
+        if user_public_key.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !user_public_key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !user_public_key.starts_with("02") && !user_public_key.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let stats_record = match self.db.get_user_stats(user_public_key).await {
+            Ok(record) => record,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying stats of user {}: {}",
+                    user_public_key,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = UserStatsResponse::from_k_user_stats_record(&stats_record);
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize user stats response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-users-by-keys?requesterPubkey={requesterPubkey}&pubkeys={pubkey},{pubkey},...
+    /// Latest profile broadcast of each listed user with its block status, in the order of
+    /// `pubkeys`, so a timeline resolves all its authors in one request
+    pub async fn get_users_by_keys(
+        &self,
+        requester_pubkey: &str,
+        pubkeys: &str,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let user_public_keys = parse_user_keys(pubkeys, MAX_USERS_BY_KEYS)?;
+
+        let users = match self
+            .db
+            .get_users_by_keys(&user_public_keys, requester_pubkey)
+            .await
+        {
+            Ok(users) => users,
+            Err(err) => {
+                log_error!("Database error while querying users by keys: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = UsersResponse {
+            posts: users
+                .iter()
+                .map(|(record, blocked)| {
+                    if record.id == 0 && record.transaction_id.is_empty() {
+                        // User without broadcast, as in /get-user-details
+                        ServerUserPost {
+                            id: String::new(),
+                            user_public_key: record.sender_pubkey.clone(),
+                            post_content: String::new(),
+                            signature: String::new(),
+                            timestamp: 0,
+                            user_nickname: None,
+                            user_profile_image: None,
+                            blocked_user: Some(*blocked),
+                            followed_user: None,
+                            followers_count: None,
+                            following_count: None,
+                            blocked_count: None,
+                            contents_count: None,
+                        }
+                    } else {
+                        ServerUserPost::from_k_broadcast_record_with_block_status(record, *blocked)
+                    }
+                })
+                .collect(),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize users by keys response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-profile with user parameter
+    /// Fetch the user details and the first page of the user's posts in parallel, so a profile
+    /// page is rendered from a single request
+    pub async fn get_profile(
+        &self,
+        user_public_key: &str,
+        requester_pubkey: &str,
+        limit: u32,
+    ) -> Result<String, String> {
+        // Both calls validate the keys, a bad one fails the first with the same error
+        let (user_result, posts_result) = tokio::join!(
+            self.get_user_details(user_public_key, requester_pubkey),
+            self.get_posts_paginated(user_public_key, requester_pubkey, limit, None, None)
+        );
+        let user_json = user_result?;
+        let posts_json = posts_result?;
+
+        let (user, posts_page) = match (
+            serde_json::from_str::<ServerUserPost>(&user_json),
+            serde_json::from_str::<PaginatedPostsResponse>(&posts_json),
+        ) {
+            (Ok(user), Ok(posts_page)) => (user, posts_page),
+            (Err(err), _) | (_, Err(err)) => {
+                log_error!("Failed to combine profile response parts: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ));
+            }
+        };
+
+        let response = ProfileResponse {
+            user,
+            posts: posts_page.posts,
+            pagination: posts_page.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize profile response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-blocked-users with pagination
+    /// Fetch paginated list of users blocked by the requester
+    pub async fn get_blocked_users_paginated(
+        &self,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let broadcasts_result = match self
+            .db
+            .get_blocked_users_by_requester(requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying blocked users for requester {}: {}",
+                    requester_pubkey,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let mut all_posts = Vec::new();
+
+        for k_broadcast_record in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+
+            // Since these are blocked users, set blocked_user to true
+            server_user_post.blocked_user = Some(true);
+
+            // Remove post content for blocked users
+            server_user_post.post_content = String::new();
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated blocked users response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    pub async fn get_followed_users_paginated(
+        &self,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let broadcasts_result = match self
+            .db
+            .get_followed_users_by_requester(requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying followed users for requester {}: {}",
+                    requester_pubkey,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let mut all_posts = Vec::new();
+
+        for k_broadcast_record in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+
+            // Since these are followed users, set followed_user to true
+            server_user_post.followed_user = Some(true);
+
+            // Remove post content for followed users
+            server_user_post.post_content = String::new();
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated followed users response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    pub async fn get_users_following_paginated(
+        &self,
+        requester_pubkey: &str,
+        user_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate user public key format (66 hex characters for compressed public key)
+        if user_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !user_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !user_pubkey.starts_with("02") && !user_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let broadcasts_result = match self
+            .db
+            .get_users_following(requester_pubkey, user_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying users following for user {}: {}",
+                    user_pubkey,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let mut all_posts = Vec::new();
+
+        for (k_broadcast_record, is_followed_by_requester) in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+
+            // Set followed_user based on whether requester follows this user
+            server_user_post.followed_user = Some(is_followed_by_requester);
+
+            // Remove post content
+            server_user_post.post_content = String::new();
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated users following response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    pub async fn get_users_followers_paginated(
+        &self,
+        requester_pubkey: &str,
+        user_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate user public key format (66 hex characters for compressed public key)
+        if user_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !user_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !user_pubkey.starts_with("02") && !user_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let broadcasts_result = match self
+            .db
+            .get_users_followers(requester_pubkey, user_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying users followers for user {}: {}",
+                    user_pubkey,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let mut all_posts = Vec::new();
+
+        for (k_broadcast_record, is_followed_by_requester) in broadcasts_result.items {
+            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
+
+            // Enrich with user profile data from broadcasts (self-enrichment)
+            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
+            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+
+            // Set followed_user based on whether requester follows this user
+            server_user_post.followed_user = Some(is_followed_by_requester);
+
+            // Remove post content
+            server_user_post.post_content = String::new();
+
+            all_posts.push(server_user_post);
+        }
+
+        let response = PaginatedUsersResponse {
+            posts: all_posts,
+            pagination: broadcasts_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated users followers response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-notifications-amount
+    /// Get count of notifications for a specific user, optionally with cursor
+    pub async fn get_notification_count(
+        &self,
+        requester_pubkey: &str,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Get notification count from database
+        match self
+            .db
+            .get_notification_count(requester_pubkey, after)
+            .await
+        {
+            Ok(count) => {
+                let response = CountResponse { count };
+                match serde_json::to_string(&response) {
+                    Ok(json_response) => Ok(json_response),
+                    Err(err) => {
+                        log_error!("Failed to serialize notification count response: {}", err);
+                        Err(self.create_error_response(
+                            "Internal server error during serialization",
+                            "SERIALIZATION_ERROR",
+                        ))
+                    }
+                }
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while getting notification count for user {}: {}",
+                    requester_pubkey,
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ))
+            }
+        }
+    }
+
+    pub async fn get_users_count(&self) -> Result<String, String> {
+        // Get users count from database
+        match self.db.get_users_count().await {
+            Ok(count) => {
+                let response = CountResponse { count };
+                match serde_json::to_string(&response) {
+                    Ok(json_response) => Ok(json_response),
+                    Err(err) => {
+                        log_error!("Failed to serialize users count response: {}", err);
+                        Err(self.create_error_response(
+                            "Internal server error during serialization",
+                            "SERIALIZATION_ERROR",
+                        ))
+                    }
+                }
+            }
+            Err(err) => {
+                log_error!("Database error while getting users count: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-hashtag-content with pagination
+    /// Fetch paginated content (posts, replies, quotes) containing a specific hashtag
+    pub async fn get_hashtag_content_paginated(
+        &self,
+        hashtag: &str,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        // Get content with this hashtag
+        let content_result = match self
+            .db
+            .get_hashtag_content(hashtag, requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying hashtag content for #{}: {}",
+                    hashtag,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Convert enriched KPostRecords to ServerPosts (blocked users already excluded)
+        let all_posts: Vec<ServerPost> = content_result
+            .items
+            .iter()
+            .map(|post_record| {
+                ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
+            })
+            .collect();
+
+        let response = PaginatedPostsResponse {
+            posts: all_posts,
+            pagination: content_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated hashtag content response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /search-posts with pagination
+    /// Full-text search over posts and replies, newest first
+    pub async fn search_posts_paginated(
+        &self,
+        search_query: &str,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        // Get posts and replies matching the search query
+        let content_result = match self
+            .db
+            .search_posts(search_query, requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while searching posts for '{}': {}",
+                    search_query,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Convert enriched KPostRecords to ServerPosts (blocked users already excluded)
+        let all_posts: Vec<ServerPost> = content_result
+            .items
+            .iter()
+            .map(|post_record| {
+                ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
+            })
+            .collect();
+
+        let response = PaginatedPostsResponse {
+            posts: all_posts,
+            pagination: content_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated post search response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-groups with pagination
+    /// Fetch groups visible to the requester (open groups and closed groups they are a member of),
+    /// optionally only the groups a specific user is a member of
+    pub async fn get_groups_paginated(
+        &self,
+        requester_pubkey: &str,
+        member_pubkey: Option<&str>,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if let Some(member_pubkey) = member_pubkey
+            && (member_pubkey.len() != 66
+                || !member_pubkey.chars().all(|c| c.is_ascii_hexdigit())
+                || (!member_pubkey.starts_with("02") && !member_pubkey.starts_with("03")))
+        {
+            return Err(self.create_error_response(
+                "Invalid member public key format. Must be a 66 hex characters compressed public key.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let groups_result = match self
+            .db
+            .get_groups(requester_pubkey, member_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!("Database error while querying groups: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = PaginatedGroupsResponse {
+            groups: groups_result
+                .items
+                .iter()
+                .map(ServerGroup::from_k_group_record)
+                .collect(),
+            pagination: groups_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize paginated groups response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-group-posts with pagination
+    /// Fetch posts of a group; posts of closed groups are only returned to members
+    pub async fn get_group_posts_paginated(
+        &self,
+        group_id: &str,
+        requester_pubkey: &str,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        // Validate group ID format (64 hex characters for the creation transaction hash)
+        if group_id.len() != 64 {
+            return Err(self.create_error_response(
+                "Invalid group ID format. Must be 64 hex characters.",
+                "INVALID_GROUP_ID",
+            ));
+        }
+
+        if !group_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid group ID format. Must contain only hex characters.",
+                "INVALID_GROUP_ID",
+            ));
+        }
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let group = match self.db.get_group_by_id(group_id, requester_pubkey).await {
+            Ok(Some(group)) => group,
+            Ok(None) => return Err(self.create_error_response("Group not found", "NOT_FOUND")),
+            Err(err) => {
+                log_error!("Database error while querying group {}: {}", group_id, err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        if group.membership_policy == "closed" && !group.is_member {
+            return Err(self.create_error_response(
+                "Posts of a closed group are only visible to its members",
+                "NOT_GROUP_MEMBER",
+            ));
+        }
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: true,
+        };
+
+        let posts_result = match self
+            .db
+            .get_group_posts(group_id, requester_pubkey, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!(
+                    "Database error while querying posts of group {}: {}",
+                    group_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = PaginatedGroupPostsResponse {
+            group: ServerGroup::from_k_group_record(&group),
+            posts: posts_result
+                .items
+                .iter()
+                .map(ServerGroupPost::from_k_group_post_record)
+                .collect(),
+            pagination: posts_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!(
+                    "Failed to serialize paginated group posts response: {}",
+                    err
+                );
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-events with pagination
+    /// Fetch upcoming (soonest first) or past (most recent first) events with RSVP counts
+    pub async fn get_events_paginated(
+        &self,
+        requester_pubkey: &str,
+        organizer_pubkey: Option<&str>,
+        upcoming: bool,
+        limit: u32,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<String, String> {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if let Some(organizer_pubkey) = organizer_pubkey
+            && (organizer_pubkey.len() != 66
+                || !organizer_pubkey.chars().all(|c| c.is_ascii_hexdigit())
+                || (!organizer_pubkey.starts_with("02") && !organizer_pubkey.starts_with("03")))
+        {
+            return Err(self.create_error_response(
+                "Invalid organizer public key format. Must be a 66 hex characters compressed public key.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Event times are unix timestamps in milliseconds, like block times
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let options = QueryOptions {
+            limit: Some(limit as u64),
+            before,
+            after,
+            sort_descending: !upcoming,
+        };
+
+        let events_result = match self
+            .db
+            .get_events(requester_pubkey, organizer_pubkey, upcoming, now, options)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                log_error!("Database error while querying events: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = PaginatedEventsResponse {
+            events: events_result
+                .items
+                .iter()
+                .map(ServerEvent::from_k_event_record)
+                .collect(),
+            pagination: events_result.pagination,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize paginated events response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-article?id={articleId}&requesterPubkey={requesterPubkey}
+    /// Fetch a long-form article with its reassembly status; content is included once complete
+    pub async fn get_article(
+        &self,
+        article_id: &str,
+        requester_pubkey: &str,
+    ) -> Result<String, String> {
+        // Validate article ID format (64 hex characters for the manifest transaction hash)
+        if article_id.len() != 64 || !article_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid article ID format. Must be 64 hex characters.",
+                "INVALID_ARTICLE_ID",
+            ));
+        }
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let article_record = match self
+            .db
+            .get_article_by_id(article_id, requester_pubkey)
+            .await
+        {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Article not found", "NOT_FOUND"));
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while querying article by ID {}: {}",
+                    article_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = ArticleDetailsResponse {
+            article: ServerArticle::from_k_article_record(&article_record),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize article response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-post-mentions?id={postId}
+    /// Fetch every pubkey mentioned by a post, reply or quote (feeds list only the first ones)
+    pub async fn get_post_mentions(&self, post_id: &str) -> Result<String, String> {
+        // Validate content ID format (64 hex characters for transaction hash)
+        if post_id.len() != 64 || !post_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        let mentioned_pubkeys = match self.db.get_content_mentions(post_id).await {
+            Ok(Some(mentioned_pubkeys)) => mentioned_pubkeys,
+            Ok(None) => {
+                return Err(self.create_error_response("Content not found", "NOT_FOUND"));
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while querying mentions of content {}: {}",
+                    post_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = PostMentionsResponse {
+            post_id: post_id.to_string(),
+            mentioned_pubkeys,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize post mentions response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-post-tips?id={postId}
+    /// Fetch the tip totals (count, distinct tippers, amount in sompi) of a post, reply or quote
+    pub async fn get_post_tips(&self, post_id: &str) -> Result<String, String> {
+        // Validate content ID format (64 hex characters for transaction hash)
+        if post_id.len() != 64 || !post_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        let tips_record = match self.db.get_content_tips(post_id).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Content not found", "NOT_FOUND"));
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while querying tips of content {}: {}",
+                    post_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = PostTipsResponse::from_k_content_tips_record(&tips_record);
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize post tips response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-top-tipped-posts?requesterPubkey={requesterPubkey}&timeWindow={timeWindow}&limit={limit}
+    /// Fetch the contents that received the highest tip amount within a time window
+    pub async fn get_top_tipped_posts(
+        &self,
+        requester_pubkey: &str,
+        time_window: &str,
+        limit: u32,
+    ) -> Result<String, String> {
+        use crate::models::{TopTippedPost, TopTippedPostsResponse};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        // Validate requester public key format (66 hex characters for compressed public key)
+        if requester_pubkey.len() != 66 {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must contain only hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Validate compressed public key prefix (should start with 02 or 03)
+        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        // Calculate time window in milliseconds (block_time is stored in milliseconds)
+        let window_millis = match time_window {
+            "1h" => 3_600_000_u64,
+            "6h" => 21_600_000_u64,
+            "24h" => 86_400_000_u64,
+            "7d" => 604_800_000_u64,
+            "30d" => 2_592_000_000_u64,
+            _ => {
+                return Err(self
+                    .create_error_response("Invalid time window parameter", "INVALID_PARAMETER"));
+            }
+        };
+
+        let to_time_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let from_time_millis = to_time_millis.saturating_sub(window_millis);
+
+        let tipped_posts = match self
+            .db
+            .get_top_tipped_posts(requester_pubkey, from_time_millis, to_time_millis, limit)
+            .await
+        {
+            Ok(records) => records,
+            Err(err) => {
+                log_error!("Database error while querying top tipped posts: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Same time units as get-trending-hashtags (seconds in fromTime/toTime)
+        let response = TopTippedPostsResponse {
+            time_window: time_window.to_string(),
+            from_time: from_time_millis / 1000,
+            to_time: to_time_millis / 1000,
+            posts: tipped_posts
+                .iter()
+                .enumerate()
+                .map(|(index, record)| TopTippedPost {
+                    rank: (index + 1) as u32,
+                    tips_count: record.tips_count,
+                    tips_amount: record.tips_amount,
+                    post: ServerPost::from_enriched_k_post_record_with_block_status(
+                        &record.post,
+                        false,
+                    ),
+                })
+                .collect(),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize top tipped posts response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /get-feature-flags?requesterPubkey={requesterPubkey}
+    /// Evaluate every configured feature flag for the requester (or for this request without one)
+    pub fn get_feature_flags(&self, requester_pubkey: Option<&str>) -> Result<String, String> {
+        if let Some(requester_pubkey) = requester_pubkey
+            && (requester_pubkey.len() != 66
+                || !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(self.create_error_response(
+                "Invalid requester public key format. Must be 66 hex characters.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let response = FeatureFlagsResponse {
+            flags: self.flags.evaluate_all(requester_pubkey),
+        };
+
+        serde_json::to_string(&response).map_err(|err| {
+            log_error!("Failed to serialize feature flags response: {}", err);
+            self.create_error_response(
+                "Internal server error during serialization",
+                "SERIALIZATION_ERROR",
+            )
+        })
+    }
+
+    /// POST /admin/erasure-requests?pubkey={pubkey}&reason={reason}
+    /// Record a right-to-be-forgotten request, carried out by K-content-remover --erasure-request
+    pub async fn create_erasure_request(
+        &self,
+        subject_pubkey: &str,
+        reason: Option<&str>,
+    ) -> Result<String, String> {
+        // Validate subject public key format (66 hex characters for compressed public key)
+        if subject_pubkey.len() != 66
+            || !subject_pubkey.chars().all(|c| c.is_ascii_hexdigit())
+            || !(subject_pubkey.starts_with("02") || subject_pubkey.starts_with("03"))
+        {
+            return Err(self.create_error_response(
+                "Invalid public key format. Must be 66 hex characters starting with 02 or 03.",
+                "INVALID_USER_KEY",
+            ));
+        }
+
+        let record = match self.db.create_erasure_request(subject_pubkey, reason).await {
+            Ok(record) => record,
+            Err(err) => {
+                log_error!(
+                    "Database error while recording erasure request for {}: {}",
+                    subject_pubkey,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = ErasureRequestResponse::from_k_erasure_request_record(&record);
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize erasure request response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /admin/erasure-requests?id={requestId}
+    /// Fetch an erasure request with its completion report (removed rows, tombstones)
+    pub async fn get_erasure_request(&self, request_id: &str) -> Result<String, String> {
+        let request_id = match request_id.parse::<i64>() {
+            Ok(id) if id > 0 => id,
+            _ => {
+                return Err(self.create_error_response(
+                    "Invalid erasure request id. Must be a positive integer.",
+                    "INVALID_REQUEST_ID",
+                ));
+            }
+        };
+
+        let record = match self.db.get_erasure_request(request_id).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Erasure request not found", "NOT_FOUND"));
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while querying erasure request {}: {}",
+                    request_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = ErasureRequestResponse::from_k_erasure_request_record(&record);
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize erasure request response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /admin/duplicate-contents?timeWindow={timeWindow}&minSenders={minSenders}&limit={limit}
+    /// List messages posted verbatim (after normalization) by many different pubkeys, a spam campaign signal
+    pub async fn get_duplicate_contents(
+        &self,
+        time_window: &str,
+        min_senders: u32,
+        limit: u32,
+    ) -> Result<String, String> {
+        use crate::models::{DuplicateContent, DuplicateContentsResponse};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        // The same message from a single sender is not a campaign
+        if min_senders < 2 {
+            return Err(self.create_error_response(
+                "Invalid minSenders parameter. Must be at least 2.",
+                "INVALID_PARAMETER",
+            ));
+        }
+
+        // Calculate time window in milliseconds (block_time is stored in milliseconds)
+        let window_millis = match time_window {
+            "1h" => 3_600_000_u64,
+            "6h" => 21_600_000_u64,
+            "24h" => 86_400_000_u64,
+            "7d" => 604_800_000_u64,
+            "30d" => 2_592_000_000_u64,
+            _ => {
+                return Err(self
+                    .create_error_response("Invalid time window parameter", "INVALID_PARAMETER"));
+            }
+        };
+
+        let to_time_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let from_time_millis = to_time_millis.saturating_sub(window_millis);
+
+        let duplicates = match self
+            .db
+            .get_duplicate_contents(
+                from_time_millis,
+                to_time_millis,
+                min_senders,
+                MAX_DUPLICATE_SENDERS,
+                limit,
+            )
+            .await
+        {
+            Ok(records) => records,
+            Err(err) => {
+                log_error!("Database error while querying duplicate contents: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        // Same time units as get-top-tipped-posts (seconds in fromTime/toTime)
+        let response = DuplicateContentsResponse {
+            time_window: time_window.to_string(),
+            from_time: from_time_millis / 1000,
+            to_time: to_time_millis / 1000,
+            min_senders,
+            duplicates: duplicates
+                .iter()
+                .map(DuplicateContent::from_k_duplicate_content_record)
+                .collect(),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize duplicate contents response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// GET /admin/storage-stats
+    /// Report row counts and on-disk sizes of the K tables with their week-over-week growth
+    pub async fn get_storage_stats(&self) -> Result<String, String> {
+        use crate::models::{StorageStatsResponse, TableStorageStats};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let now_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let tables = match self
             .db
-            .get_followed_users_by_requester(requester_pubkey, options)
+            .get_table_storage(now_millis.saturating_sub(STORAGE_GROWTH_PERIOD_MILLIS))
             .await
         {
-            Ok(result) => result,
+            Ok(records) => records,
             Err(err) => {
-                log_error!(
-                    "Database error while querying followed users for requester {}: {}",
-                    requester_pubkey,
-                    err
-                );
+                log_error!("Database error while querying table storage: {}", err);
                 return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
@@ -1416,36 +3782,65 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for k_broadcast_record in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+        let response = StorageStatsResponse {
+            generated_at: now_millis,
+            total_bytes: tables.iter().map(|table| table.total_bytes).sum(),
+            tables: tables
+                .iter()
+                .map(TableStorageStats::from_k_table_storage_record)
+                .collect(),
+        };
 
-            // Since these are followed users, set followed_user to true
-            server_user_post.followed_user = Some(true);
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize storage stats response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
 
-            // Remove post content for followed users
-            server_user_post.post_content = String::new();
+    /// GET /admin/slow-queries?limit={limit}
+    /// Report the DatabaseInterface methods with the most database time from pg_stat_statements
+    pub async fn get_slow_queries(&self, limit: u32) -> Result<String, String> {
+        use crate::models::{QueryStats, SlowQueriesResponse};
+        use std::time::{SystemTime, UNIX_EPOCH};
 
-            all_posts.push(server_user_post);
-        }
+        let records = match self.db.get_query_stats(limit).await {
+            Ok(Some(records)) => records,
+            Ok(None) => {
+                return Err(self.create_error_response(
+                    "pg_stat_statements is not available: add it to shared_preload_libraries and create the extension",
+                    "FEATURE_UNAVAILABLE",
+                ));
+            }
+            Err(err) => {
+                log_error!("Database error while querying query stats: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
 
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
+        let response = SlowQueriesResponse {
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            queries: records
+                .iter()
+                .map(QueryStats::from_k_query_stats_record)
+                .collect(),
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated followed users response: {}",
-                    err
-                );
+                log_error!("Failed to serialize slow queries response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -1454,76 +3849,43 @@ impl ApiHandlers {
         }
     }
 
-    pub async fn get_users_following_paginated(
+    /// GET /get-profile-diff?user={userPubkey}&from={from}&to={to}
+    /// Compare the profile of a user at two times (milliseconds) using its broadcast history
+    pub async fn get_profile_diff(
         &self,
-        requester_pubkey: &str,
         user_pubkey: &str,
-        limit: u32,
-        before: Option<String>,
-        after: Option<String>,
+        from: u64,
+        to: u64,
     ) -> Result<String, String> {
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
-
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        use crate::models::ProfileDiffResponse;
 
         // Validate user public key format (66 hex characters for compressed public key)
-        if user_pubkey.len() != 66 {
+        if user_pubkey.len() != 66 || !user_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
             return Err(self.create_error_response(
                 "Invalid user public key format. Must be 66 hex characters.",
                 "INVALID_USER_KEY",
             ));
         }
 
-        if !user_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        if !user_pubkey.starts_with("02") && !user_pubkey.starts_with("03") {
             return Err(self.create_error_response(
-                "Invalid user public key format. Must contain only hex characters.",
+                "Invalid user public key format. Compressed public key must start with 02 or 03.",
                 "INVALID_USER_KEY",
             ));
         }
 
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !user_pubkey.starts_with("02") && !user_pubkey.starts_with("03") {
+        if from >= to {
             return Err(self.create_error_response(
-                "Invalid user public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
+                "Invalid time range. 'from' must be earlier than 'to'.",
+                "INVALID_PARAMETER",
             ));
         }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
-        };
-
-        let broadcasts_result = match self
-            .db
-            .get_users_following(requester_pubkey, user_pubkey, options)
-            .await
-        {
-            Ok(result) => result,
+        let record = match self.db.get_profile_diff(user_pubkey, from, to).await {
+            Ok(record) => record,
             Err(err) => {
                 log_error!(
-                    "Database error while querying users following for user {}: {}",
+                    "Database error while querying profile history of {}: {}",
                     user_pubkey,
                     err
                 );
@@ -1534,36 +3896,13 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for (k_broadcast_record, is_followed_by_requester) in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
-
-            // Set followed_user based on whether requester follows this user
-            server_user_post.followed_user = Some(is_followed_by_requester);
-
-            // Remove post content
-            server_user_post.post_content = String::new();
-
-            all_posts.push(server_user_post);
-        }
-
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
-        };
+        let response =
+            ProfileDiffResponse::from_k_profile_diff_record(user_pubkey, from, to, &record);
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated users following response: {}",
-                    err
-                );
+                log_error!("Failed to serialize profile diff response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -1572,77 +3911,88 @@ impl ApiHandlers {
         }
     }
 
-    pub async fn get_users_followers_paginated(
+    /// GET /admin/impersonation-flags?status={status}&limit={limit}
+    /// List profiles flagged as likely impersonators of high-follower users
+    pub async fn get_impersonation_flags(
         &self,
-        requester_pubkey: &str,
-        user_pubkey: &str,
+        status: &str,
         limit: u32,
-        before: Option<String>,
-        after: Option<String>,
     ) -> Result<String, String> {
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        use crate::models::{ImpersonationFlag, ImpersonationFlagsResponse};
 
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        if !["pending", "confirmed", "dismissed"].contains(&status) {
             return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
+                "Invalid status parameter. Must be one of: pending, confirmed, dismissed",
+                "INVALID_PARAMETER",
             ));
         }
 
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
-            return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        let flags = match self.db.get_impersonation_flags(status, limit).await {
+            Ok(records) => records,
+            Err(err) => {
+                log_error!("Database error while querying impersonation flags: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
 
-        // Validate user public key format (66 hex characters for compressed public key)
-        if user_pubkey.len() != 66 {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
-            ));
-        }
+        let response = ImpersonationFlagsResponse {
+            status: status.to_string(),
+            flags: flags
+                .iter()
+                .map(ImpersonationFlag::from_k_impersonation_flag_record)
+                .collect(),
+        };
 
-        if !user_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(self.create_error_response(
-                "Invalid user public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
-            ));
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize impersonation flags response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
         }
+    }
 
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !user_pubkey.starts_with("02") && !user_pubkey.starts_with("03") {
+    /// POST /admin/impersonation-flags?id={flagId}&status={status}
+    /// Review an impersonation flag: confirm the impersonation or dismiss the flag
+    pub async fn review_impersonation_flag(
+        &self,
+        flag_id: &str,
+        status: &str,
+    ) -> Result<String, String> {
+        use crate::models::ImpersonationFlag;
+
+        let flag_id = match flag_id.parse::<i64>() {
+            Ok(id) if id > 0 => id,
+            _ => {
+                return Err(self.create_error_response(
+                    "Invalid impersonation flag id. Must be a positive integer.",
+                    "INVALID_REQUEST_ID",
+                ));
+            }
+        };
+
+        if !["confirmed", "dismissed"].contains(&status) {
             return Err(self.create_error_response(
-                "Invalid user public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
+                "Invalid status parameter. Must be one of: confirmed, dismissed",
+                "INVALID_PARAMETER",
             ));
         }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
-        };
-
-        let broadcasts_result = match self
-            .db
-            .get_users_followers(requester_pubkey, user_pubkey, options)
-            .await
-        {
-            Ok(result) => result,
+        let record = match self.db.review_impersonation_flag(flag_id, status).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Impersonation flag not found", "NOT_FOUND"));
+            }
             Err(err) => {
                 log_error!(
-                    "Database error while querying users followers for user {}: {}",
-                    user_pubkey,
+                    "Database error while reviewing impersonation flag {}: {}",
+                    flag_id,
                     err
                 );
                 return Err(self.create_error_response(
@@ -1652,36 +4002,62 @@ impl ApiHandlers {
             }
         };
 
-        let mut all_posts = Vec::new();
-
-        for (k_broadcast_record, is_followed_by_requester) in broadcasts_result.items {
-            let mut server_user_post = ServerUserPost::from_k_broadcast_record(&k_broadcast_record);
-
-            // Enrich with user profile data from broadcasts (self-enrichment)
-            server_user_post.user_nickname = Some(k_broadcast_record.base64_encoded_nickname);
-            server_user_post.user_profile_image = k_broadcast_record.base64_encoded_profile_image;
+        let response = ImpersonationFlag::from_k_impersonation_flag_record(&record);
 
-            // Set followed_user based on whether requester follows this user
-            server_user_post.followed_user = Some(is_followed_by_requester);
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize impersonation flag response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
 
-            // Remove post content
-            server_user_post.post_content = String::new();
+    /// GET /admin/nickname-history?nickname={nickname}&limit={limit}
+    /// List every user that has broadcast the nickname (normalized like the impersonation
+    /// detector), for impersonation and ban evasion investigations
+    pub async fn get_nickname_history(&self, nickname: &str, limit: u32) -> Result<String, String> {
+        use crate::models::{NicknameHistoryResponse, NicknameUse};
 
-            all_posts.push(server_user_post);
+        let normalized_nickname = normalize_nickname(nickname);
+        if normalized_nickname.is_empty() {
+            return Err(self.create_error_response(
+                "Invalid nickname parameter. Must contain at least one letter or digit.",
+                "INVALID_PARAMETER",
+            ));
         }
 
-        let response = PaginatedUsersResponse {
-            posts: all_posts,
-            pagination: broadcasts_result.pagination,
+        let users = match self
+            .db
+            .get_nickname_history(&normalized_nickname, limit)
+            .await
+        {
+            Ok(records) => records,
+            Err(err) => {
+                log_error!("Database error while querying nickname history: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = NicknameHistoryResponse {
+            nickname: nickname.to_string(),
+            normalized_nickname,
+            users: users
+                .iter()
+                .map(NicknameUse::from_k_nickname_use_record)
+                .collect(),
         };
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
             Err(err) => {
-                log_error!(
-                    "Failed to serialize paginated users followers response: {}",
-                    err
-                );
+                log_error!("Failed to serialize nickname history response: {}", err);
                 Err(self.create_error_response(
                     "Internal server error during serialization",
                     "SERIALIZATION_ERROR",
@@ -1690,13 +4066,11 @@ impl ApiHandlers {
         }
     }
 
-    /// GET /get-notifications-amount
-    /// Get count of notifications for a specific user, optionally with cursor
-    pub async fn get_notification_count(
-        &self,
-        requester_pubkey: &str,
-        after: Option<String>,
-    ) -> Result<String, String> {
+    /// GET /get-featured?requesterPubkey={requesterPubkey}&limit={limit}
+    /// Fetch the contents featured by the operator, in the order the operator chose
+    pub async fn get_featured(&self, requester_pubkey: &str, limit: u32) -> Result<String, String> {
+        use crate::models::{FeaturedPost, FeaturedPostsResponse};
+
         // Validate requester public key format (66 hex characters for compressed public key)
         if requester_pubkey.len() != 66 {
             return Err(self.create_error_response(
@@ -1720,120 +4094,161 @@ impl ApiHandlers {
             ));
         }
 
-        // Get notification count from database
-        match self
-            .db
-            .get_notification_count(requester_pubkey, after)
-            .await
-        {
-            Ok(count) => {
-                let response = serde_json::json!({
-                    "count": count
-                });
-                match serde_json::to_string(&response) {
-                    Ok(json_response) => Ok(json_response),
-                    Err(err) => {
-                        log_error!("Failed to serialize notification count response: {}", err);
-                        Err(self.create_error_response(
-                            "Internal server error during serialization",
-                            "SERIALIZATION_ERROR",
-                        ))
-                    }
-                }
-            }
+        let featured_posts = match self.db.get_featured_posts(requester_pubkey, limit).await {
+            Ok(records) => records,
             Err(err) => {
-                log_error!(
-                    "Database error while getting notification count for user {}: {}",
-                    requester_pubkey,
-                    err
-                );
-                Err(self.create_error_response(
+                log_error!("Database error while querying featured posts: {}", err);
+                return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = FeaturedPostsResponse {
+            posts: featured_posts
+                .iter()
+                .map(|record| FeaturedPost {
+                    position: record.featured.position,
+                    note: record.featured.note.clone(),
+                    featured_at: record.featured.featured_at,
+                    post: ServerPost::from_enriched_k_post_record_with_block_status(
+                        &record.post,
+                        false,
+                    ),
+                })
+                .collect(),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize featured posts response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
                 ))
             }
         }
     }
 
-    pub async fn get_users_count(&self) -> Result<String, String> {
-        // Get users count from database
-        match self.db.get_users_count().await {
-            Ok(count) => {
-                let response = serde_json::json!({
-                    "count": count
-                });
-                match serde_json::to_string(&response) {
-                    Ok(json_response) => Ok(json_response),
-                    Err(err) => {
-                        log_error!("Failed to serialize users count response: {}", err);
-                        Err(self.create_error_response(
-                            "Internal server error during serialization",
-                            "SERIALIZATION_ERROR",
-                        ))
-                    }
-                }
-            }
+    /// GET /admin/featured?limit={limit}
+    /// List the featured entries, including the ones whose content is no longer indexed
+    pub async fn get_featured_entries(&self, limit: u32) -> Result<String, String> {
+        use crate::models::{FeaturedEntriesResponse, FeaturedEntry};
+
+        let entries = match self.db.get_featured_entries(limit).await {
+            Ok(records) => records,
             Err(err) => {
-                log_error!("Database error while getting users count: {}", err);
-                Err(self.create_error_response(
+                log_error!("Database error while querying featured entries: {}", err);
+                return Err(self.create_error_response(
                     "Internal server error during database query",
                     "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = FeaturedEntriesResponse {
+            entries: entries
+                .iter()
+                .map(FeaturedEntry::from_k_featured_record)
+                .collect(),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize featured entries response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
                 ))
             }
         }
     }
 
-    /// GET /get-hashtag-content with pagination
-    /// Fetch paginated content (posts, replies, quotes) containing a specific hashtag
-    pub async fn get_hashtag_content_paginated(
+    /// POST /admin/featured?id={contentId}&position={position}&note={note}
+    /// Feature a post, reply or quote, or update the position or note of its entry
+    pub async fn set_featured(
         &self,
-        hashtag: &str,
-        requester_pubkey: &str,
-        limit: u32,
-        before: Option<String>,
-        after: Option<String>,
+        content_id: &str,
+        position: Option<i32>,
+        note: Option<&str>,
     ) -> Result<String, String> {
-        // Validate requester public key format (66 hex characters for compressed public key)
-        if requester_pubkey.len() != 66 {
+        use crate::models::FeaturedEntry;
+
+        // Validate content ID format (64 hex characters for transaction hash)
+        if content_id.len() != 64 || !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
             return Err(self.create_error_response(
-                "Invalid requester public key format. Must be 66 hex characters.",
-                "INVALID_USER_KEY",
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
             ));
         }
 
-        if !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        if note.is_some_and(|note| note.chars().count() > MAX_FEATURED_NOTE_CHARS) {
             return Err(self.create_error_response(
-                "Invalid requester public key format. Must contain only hex characters.",
-                "INVALID_USER_KEY",
+                &format!(
+                    "Invalid note parameter. Must be at most {} characters.",
+                    MAX_FEATURED_NOTE_CHARS
+                ),
+                "INVALID_PARAMETER",
             ));
         }
 
-        // Validate compressed public key prefix (should start with 02 or 03)
-        if !requester_pubkey.starts_with("02") && !requester_pubkey.starts_with("03") {
+        let record = match self.db.set_featured(content_id, position, note).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Post not found", "NOT_FOUND"));
+            }
+            Err(err) => {
+                log_error!(
+                    "Database error while featuring content {}: {}",
+                    content_id,
+                    err
+                );
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let response = FeaturedEntry::from_k_featured_record(&record);
+
+        match serde_json::to_string(&response) {
+            Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize featured entry response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// DELETE /admin/featured?id={contentId}
+    /// Remove a featured entry; the content itself is left untouched
+    pub async fn delete_featured(&self, content_id: &str) -> Result<String, String> {
+        use crate::models::FeaturedEntry;
+
+        // Validate content ID format (64 hex characters for transaction hash)
+        if content_id.len() != 64 || !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
             return Err(self.create_error_response(
-                "Invalid requester public key format. Compressed public key must start with 02 or 03.",
-                "INVALID_USER_KEY",
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
             ));
         }
 
-        let options = QueryOptions {
-            limit: Some(limit as u64),
-            before,
-            after,
-            sort_descending: true,
-        };
-
-        // Get content with this hashtag
-        let content_result = match self
-            .db
-            .get_hashtag_content(hashtag, requester_pubkey, options)
-            .await
-        {
-            Ok(result) => result,
+        let record = match self.db.delete_featured(content_id).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(self.create_error_response("Featured content not found", "NOT_FOUND"));
+            }
             Err(err) => {
                 log_error!(
-                    "Database error while querying hashtag content for #{}: {}",
-                    hashtag,
+                    "Database error while removing featured content {}: {}",
+                    content_id,
                     err
                 );
                 return Err(self.create_error_response(
@@ -1843,46 +4258,149 @@ impl ApiHandlers {
             }
         };
 
-        // Convert enriched KPostRecords to ServerPosts (blocked users already excluded)
-        let all_posts: Vec<ServerPost> = content_result
-            .items
-            .iter()
-            .map(|post_record| {
-                ServerPost::from_enriched_k_post_record_with_block_status(post_record, false)
-            })
-            .collect();
-
-        let response = PaginatedPostsResponse {
-            posts: all_posts,
-            pagination: content_result.pagination,
-        };
+        let response = FeaturedEntry::from_k_featured_record(&record);
 
         match serde_json::to_string(&response) {
             Ok(json) => Ok(json),
+            Err(err) => {
+                log_error!("Failed to serialize featured entry response: {}", err);
+                Err(self.create_error_response(
+                    "Internal server error during serialization",
+                    "SERIALIZATION_ERROR",
+                ))
+            }
+        }
+    }
+
+    /// POST /track-view?id={postId}
+    /// Record a view of a post, reply or quote; the viewer is identified by a hash of its IP
+    pub async fn track_view(&self, post_id: &str, viewer_ip: IpAddr) -> Result<(), String> {
+        // Validate content ID format (64 hex characters for transaction hash)
+        if post_id.len() != 64 || !post_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.create_error_response(
+                "Invalid content ID format. Must be 64 hex characters.",
+                "INVALID_POST_ID",
+            ));
+        }
+
+        let (register_index, rank) = register_update(viewer_hash(viewer_ip));
+
+        if let Err(err) = self
+            .db
+            .record_post_view(post_id, register_index, rank)
+            .await
+        {
+            log_error!(
+                "Database error while recording view of {}: {}",
+                post_id,
+                err
+            );
+            return Err(self.create_error_response(
+                "Internal server error during database query",
+                "DATABASE_ERROR",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Approximate number of distinct viewers of a content (0 if never viewed)
+    async fn get_view_count(&self, content_id: &str) -> Result<u64, String> {
+        match self.db.get_post_view_registers(content_id).await {
+            Ok(registers) => Ok(registers.map_or(0, |registers| estimate(&registers))),
             Err(err) => {
                 log_error!(
-                    "Failed to serialize paginated hashtag content response: {}",
+                    "Database error while querying views of {}: {}",
+                    content_id,
                     err
                 );
                 Err(self.create_error_response(
-                    "Internal server error during serialization",
-                    "SERIALIZATION_ERROR",
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
                 ))
             }
         }
     }
 
-    /// Create a standardized error response
-    fn create_error_response(&self, message: &str, code: &str) -> String {
-        let error = ApiError {
-            error: message.to_string(),
-            code: code.to_string(),
+    /// Embed the first `include_replies` nested replies into each item, fetched with one query for all items
+    async fn attach_nested_replies(
+        &self,
+        items: &mut [ServerPost],
+        requester_pubkey: &str,
+        include_replies: u32,
+    ) -> Result<(), String> {
+        if include_replies == 0 || items.is_empty() {
+            return Ok(());
+        }
+
+        let parent_ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+        let nested_records = match self
+            .db
+            .get_nested_replies(&parent_ids, requester_pubkey, include_replies)
+            .await
+        {
+            Ok(records) => records,
+            Err(err) => {
+                log_error!("Database error while querying nested replies: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
         };
 
-        serde_json::to_string(&error).unwrap_or_else(|_| {
-            r#"{"error":"Internal error creating error response","code":"INTERNAL_ERROR"}"#
-                .to_string()
-        })
+        let mut replies_by_parent: HashMap<String, Vec<ServerReply>> = HashMap::new();
+        for record in &nested_records {
+            replies_by_parent
+                .entry(record.post_id.clone())
+                .or_default()
+                .push(ServerReply::from_enriched_k_reply_record_with_block_status(
+                    record, false,
+                ));
+        }
+
+        for item in items.iter_mut() {
+            item.nested_replies = Some(replies_by_parent.remove(&item.id).unwrap_or_default());
+        }
+
+        Ok(())
+    }
+
+    /// Set the fee-weighted vote totals of each item (0 for items without votes), fetched with
+    /// one query for all items
+    async fn attach_vote_weights(&self, items: &mut [ServerPost]) -> Result<(), String> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let content_ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+        let weights = match self.db.get_vote_weights(&content_ids).await {
+            Ok(weights) => weights,
+            Err(err) => {
+                log_error!("Database error while querying vote weights: {}", err);
+                return Err(self.create_error_response(
+                    "Internal server error during database query",
+                    "DATABASE_ERROR",
+                ));
+            }
+        };
+
+        let weights_by_id: HashMap<String, (u64, u64)> = weights
+            .into_iter()
+            .map(|(content_id, up, down)| (content_id, (up, down)))
+            .collect();
+        for item in items.iter_mut() {
+            let (up, down) = weights_by_id.get(&item.id).copied().unwrap_or((0, 0));
+            item.up_votes_weight = Some(up);
+            item.down_votes_weight = Some(down);
+        }
+
+        Ok(())
+    }
+
+    /// Create a standardized error response
+    fn create_error_response(&self, message: &str, code: &str) -> String {
+        error_response(message, code)
     }
 
     /// GET /get-trending-hashtags
@@ -1965,3 +4483,82 @@ impl ApiHandlers {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(body: &str) -> String {
+        serde_json::from_str::<ApiError>(body).unwrap().code
+    }
+
+    #[test]
+    fn test_parse_content_ids_lowercases_and_deduplicates() {
+        let a = "a".repeat(64);
+        let b = "B".repeat(64);
+        let ids = format!("{}, {},{}", a, b, a.to_uppercase());
+        assert_eq!(
+            parse_content_ids(&ids, 100).unwrap(),
+            vec![a, b.to_lowercase()]
+        );
+    }
+
+    #[test]
+    fn test_parse_content_ids_rejects_invalid_ids() {
+        let valid = "0".repeat(64);
+        for ids in [
+            String::new(),
+            "0".repeat(63),
+            "g".repeat(64),
+            format!("{},", valid),
+        ] {
+            let err = parse_content_ids(&ids, 100).unwrap_err();
+            assert_eq!(error_code(&err), "INVALID_POST_ID", "ids: {:?}", ids);
+        }
+    }
+
+    #[test]
+    fn test_parse_content_ids_limit_counts_distinct_ids() {
+        let ids: Vec<String> = (0..3).map(|i| format!("{:064x}", i)).collect();
+        let err = parse_content_ids(&ids.join(","), 2).unwrap_err();
+        assert_eq!(error_code(&err), "INVALID_PARAMETER");
+
+        // Duplicates do not count against the limit
+        let repeated = format!("{},{},{}", ids[0], ids[1], ids[0]);
+        assert_eq!(parse_content_ids(&repeated, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_user_keys_lowercases_and_deduplicates() {
+        let a = format!("02{}", "ab".repeat(32));
+        let b = format!("03{}", "CD".repeat(32));
+        let keys = format!("{}, {},{}", a, b, a.to_uppercase());
+        assert_eq!(
+            parse_user_keys(&keys, 100).unwrap(),
+            vec![a, b.to_lowercase()]
+        );
+    }
+
+    #[test]
+    fn test_parse_user_keys_rejects_invalid_keys() {
+        for keys in [
+            String::new(),
+            format!("04{}", "ab".repeat(32)),
+            format!("02{}", "ab".repeat(31)),
+            format!("02{}", "zz".repeat(32)),
+        ] {
+            let err = parse_user_keys(&keys, 100).unwrap_err();
+            assert_eq!(error_code(&err), "INVALID_USER_KEY", "pubkeys: {:?}", keys);
+        }
+    }
+
+    #[test]
+    fn test_parse_user_keys_limit_counts_distinct_keys() {
+        let keys: Vec<String> = (0..3).map(|i| format!("02{:064x}", i)).collect();
+        let err = parse_user_keys(&keys.join(","), 2).unwrap_err();
+        assert_eq!(error_code(&err), "INVALID_PARAMETER");
+
+        let repeated = format!("{},{},{}", keys[0], keys[1], keys[1]);
+        assert_eq!(parse_user_keys(&repeated, 2).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,110 @@
+use axum::http::HeaderName;
+
+/// Versions of the public endpoints, oldest first. Every endpoint is served under /v1; a
+/// version only serves the endpoints whose responses it changed (/v2: pagination envelopes)
+pub const API_VERSIONS: [&str; 2] = ["v1", "v2"];
+
+/// Version answered by the unversioned paths when the client does not ask for one
+pub const DEFAULT_API_VERSION: &str = "v1";
+
+/// Version requested for an unversioned path, and version that served a response
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("k-api-version");
+
+/// Version prefix of a path ("/v2/get-posts" -> v2), None for unversioned paths
+pub fn version_of(path: &str) -> Option<&'static str> {
+    let path = path.trim_start_matches('/');
+    API_VERSIONS.into_iter().find(|version| {
+        path.strip_prefix(version)
+            .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Endpoint of a path, without its leading slash and version prefix
+/// ("/v2/get-posts" and "/get-posts" -> "get-posts")
+pub fn endpoint_of(path: &str) -> &str {
+    let path = path.trim_start_matches('/');
+    match version_of(path) {
+        Some(version) => &path[version.len() + 1..],
+        None => path,
+    }
+}
+
+/// Version named by a K-API-Version header ("v2" or "2"), None if not a version of this server
+pub fn parse_version(value: &str) -> Option<&'static str> {
+    let value = value.trim().to_ascii_lowercase();
+    let value = value.strip_prefix('v').unwrap_or(&value);
+    API_VERSIONS
+        .into_iter()
+        .find(|version| version[1..] == *value)
+}
+
+/// Version answering a request for `requested`: the latest version up to it that serves the
+/// endpoint, as an endpoint unchanged by a version keeps the response of the previous one
+pub fn negotiate(requested: &str, serves: impl Fn(&str) -> bool) -> &'static str {
+    let requested = API_VERSIONS
+        .iter()
+        .position(|version| *version == requested)
+        .unwrap_or(0);
+    API_VERSIONS[..=requested]
+        .iter()
+        .rev()
+        .copied()
+        .find(|version| *version == DEFAULT_API_VERSION || serves(version))
+        .unwrap_or(DEFAULT_API_VERSION)
+}
+
+/// Link from an unversioned endpoint to its versioned path, relative so that it resolves under
+/// the path prefix of a tenant ("notifications/stream" -> "../v1/notifications/stream")
+pub fn successor_reference(version: &str, endpoint: &str) -> String {
+    format!(
+        "{}{}/{}",
+        "../".repeat(endpoint.matches('/').count()),
+        version,
+        endpoint
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(version_of("/v2/get-posts"), Some("v2"));
+        assert_eq!(version_of("/v1/notifications/stream"), Some("v1"));
+        assert_eq!(version_of("/get-posts"), None);
+        assert_eq!(version_of("/v2"), None);
+        assert_eq!(version_of("/v10/get-posts"), None);
+
+        assert_eq!(endpoint_of("/v2/get-posts"), "get-posts");
+        assert_eq!(
+            endpoint_of("/v1/notifications/stream"),
+            "notifications/stream"
+        );
+        assert_eq!(endpoint_of("/get-posts"), "get-posts");
+        assert_eq!(endpoint_of("/"), "");
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(parse_version("v2"), Some("v2"));
+        assert_eq!(parse_version(" V1 "), Some("v1"));
+        assert_eq!(parse_version("2"), Some("v2"));
+        assert_eq!(parse_version("v3"), None);
+        assert_eq!(parse_version(""), None);
+
+        // Endpoints without a v2 response keep answering v1
+        assert_eq!(negotiate("v2", |version| version == "v2"), "v2");
+        assert_eq!(negotiate("v2", |_| false), "v1");
+        assert_eq!(negotiate("v1", |_| true), "v1");
+    }
+
+    #[test]
+    fn test_successor_reference() {
+        assert_eq!(successor_reference("v1", "get-posts"), "v1/get-posts");
+        assert_eq!(
+            successor_reference("v1", "notifications/stream"),
+            "../v1/notifications/stream"
+        );
+    }
+}
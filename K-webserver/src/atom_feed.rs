@@ -0,0 +1,170 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Media type of the feeds served under /feeds
+pub const ATOM_XML: &str = "application/atom+xml; charset=utf-8";
+
+/// Entry titles are the first line of the post, cut to this many characters
+const TITLE_MAX_CHARS: usize = 80;
+
+/// Feed-level metadata
+pub struct Feed<'a> {
+    // Client page the feed mirrors, also used as the feed id
+    pub link: &'a str,
+    // Path of the feed on this webserver (rel="self")
+    pub self_path: &'a str,
+    pub title: &'a str,
+}
+
+/// A post rendered as an Atom entry (text already decoded)
+pub struct FeedEntry {
+    pub link: String,
+    pub author_name: String,
+    pub author_link: String,
+    pub text: String,
+    pub block_time: u64,
+}
+
+/// Render an Atom 1.0 document; `updated` is the newest entry (or now for an empty feed)
+pub fn render(feed: &Feed, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.block_time)
+        .max()
+        .map(timestamp)
+        .unwrap_or_else(|| Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(feed.link)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(feed.title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated));
+    xml.push_str(&format!(
+        "  <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n",
+        escape_xml(feed.link)
+    ));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>\n",
+        escape_xml(feed.self_path)
+    ));
+    xml.push_str(&format!(
+        "  <generator version=\"{}\">K-indexer</generator>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    for entry in entries {
+        let published = timestamp(entry.block_time);
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry.link)));
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&entry_title(&entry.text))
+        ));
+        xml.push_str(&format!(
+            "    <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n",
+            escape_xml(&entry.link)
+        ));
+        xml.push_str(&format!("    <published>{}</published>\n", published));
+        xml.push_str(&format!("    <updated>{}</updated>\n", published));
+        xml.push_str(&format!(
+            "    <author><name>{}</name><uri>{}</uri></author>\n",
+            escape_xml(&entry.author_name),
+            escape_xml(&entry.author_link)
+        ));
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}</content>\n",
+            escape_xml(&entry.text)
+        ));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// First non-empty line of the text, shortened to TITLE_MAX_CHARS
+fn entry_title(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("(no text)");
+
+    if line.chars().count() > TITLE_MAX_CHARS {
+        let cut: String = line.chars().take(TITLE_MAX_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Escape markup and drop the characters XML 1.0 cannot carry (control characters)
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn timestamp(block_time_ms: u64) -> String {
+    DateTime::from_timestamp_millis(block_time_ms as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, block_time: u64) -> FeedEntry {
+        FeedEntry {
+            link: "https://client.example/post/ab".to_string(),
+            author_name: "Alice".to_string(),
+            author_link: "https://client.example/user/02aa".to_string(),
+            text: text.to_string(),
+            block_time,
+        }
+    }
+
+    #[test]
+    fn test_entry_title() {
+        assert_eq!(entry_title("\n  first line \nsecond"), "first line");
+        assert_eq!(entry_title("   "), "(no text)");
+        let long = "x".repeat(100);
+        let title = entry_title(&long);
+        assert_eq!(title.chars().count(), TITLE_MAX_CHARS);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn test_escape_xml_drops_control_characters() {
+        assert_eq!(
+            escape_xml("a<b> & \"c\" 'd'\u{0}\u{1b}\n"),
+            "a&lt;b&gt; &amp; &quot;c&quot; &apos;d&apos;\n"
+        );
+    }
+
+    #[test]
+    fn test_render_uses_newest_entry_as_updated() {
+        let feed = Feed {
+            link: "https://client.example/user/02aa",
+            self_path: "/feeds/user/02aa.xml",
+            title: "Alice on K",
+        };
+        let xml = render(&feed, &[entry("hi <there>", 2000), entry("older", 1000)]);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed"));
+        assert!(xml.contains("  <updated>1970-01-01T00:00:02Z</updated>\n"));
+        assert!(xml.contains("<content type=\"text\">hi &lt;there&gt;</content>"));
+        assert_eq!(xml.matches("<entry>").count(), 2);
+        assert!(xml.ends_with("</feed>\n"));
+    }
+}
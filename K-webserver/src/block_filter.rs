@@ -0,0 +1,197 @@
+use axum_prometheus::metrics::gauge;
+use sqlx::PgPool;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::replica_lag::now_ms;
+
+/// Filter bits per k_blocks row, which sets two keys (its blocker and its pair): about 1% false
+/// positives per lookup
+const BITS_PER_ROW: usize = 20;
+
+/// Bit positions of a key
+const HASHES: u64 = 7;
+
+/// Missed refreshes after which the filter is stale and every feed joins k_blocks again
+const STALE_AFTER_REFRESHES: i64 = 3;
+
+/// Bloom filter keys: a blocker blocking anyone, and a blocker blocking a given user
+#[derive(Hash)]
+enum Key<'a> {
+    Blocker(&'a [u8]),
+    Pair(&'a [u8], &'a [u8]),
+}
+
+struct Bloom {
+    bits: Vec<u64>,
+}
+
+impl Bloom {
+    fn with_rows(rows: usize) -> Self {
+        Self {
+            bits: vec![0; (rows * BITS_PER_ROW).div_ceil(64).max(1)],
+        }
+    }
+
+    /// Positions of `key` among `len` bits (double hashing of one 64-bit hash)
+    fn positions(key: &Key, len: usize) -> impl Iterator<Item = usize> + use<> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+        let (first, step) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        (0..HASHES).map(move |i| (first.wrapping_add(i.wrapping_mul(step)) % len as u64) as usize)
+    }
+
+    fn insert(&mut self, key: &Key) {
+        for position in Self::positions(key, self.bits.len() * 64) {
+            self.bits[position / 64] |= 1 << (position % 64);
+        }
+    }
+
+    fn contains(&self, key: &Key) -> bool {
+        Self::positions(key, self.bits.len() * 64)
+            .all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+}
+
+/// Snapshot of the (blocker, blocked) pairs of k_blocks, reloaded every `interval`. A lookup
+/// can only rule a block out: while the filter is not loaded or stale every block may exist
+pub struct BlockFilter {
+    interval: Duration,
+    bloom: RwLock<Option<Bloom>>,
+    // Start of the read of the loaded snapshot (ms), blocks committed later are not in it
+    loaded_at_ms: AtomicI64,
+}
+
+impl BlockFilter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            bloom: RwLock::new(None),
+            loaded_at_ms: AtomicI64::new(0),
+        }
+    }
+
+    /// Replace the filter with the k_blocks `pairs` read at `loaded_at_ms`
+    pub fn load(&self, pairs: &[(Vec<u8>, Vec<u8>)], loaded_at_ms: i64) {
+        let mut bloom = Bloom::with_rows(pairs.len());
+        for (blocker, blocked) in pairs {
+            bloom.insert(&Key::Blocker(blocker));
+            bloom.insert(&Key::Pair(blocker, blocked));
+        }
+        *self.bloom.write().unwrap() = Some(bloom);
+        self.loaded_at_ms.store(loaded_at_ms, Ordering::Relaxed);
+    }
+
+    /// Whether `blocker` may block `blocked` (None = anyone) at `now_ms`: false only when a
+    /// snapshot at most STALE_AFTER_REFRESHES intervals old rules it out
+    pub fn may_block(&self, blocker: &[u8], blocked: Option<&[u8]>, now_ms: i64) -> bool {
+        let max_age_ms = self.interval.as_millis() as i64 * STALE_AFTER_REFRESHES;
+        if now_ms - self.loaded_at_ms.load(Ordering::Relaxed) > max_age_ms {
+            return true;
+        }
+        let key = match blocked {
+            Some(blocked) => Key::Pair(blocker, blocked),
+            None => Key::Blocker(blocker),
+        };
+        self.bloom
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|bloom| bloom.contains(&key))
+    }
+}
+
+/// Reload the filter from k_blocks every interval, sized to the current row count (unblocks
+/// delete rows, so the filter is rebuilt rather than extended)
+pub async fn start_block_filter_refresh(pool: PgPool, filter: Arc<BlockFilter>) {
+    let mut interval = tokio::time::interval(filter.interval);
+    let mut failing = false;
+
+    loop {
+        interval.tick().await;
+
+        let started_at = now_ms();
+        match sqlx::query_as::<_, (Vec<u8>, Vec<u8>)>(
+            "SELECT sender_pubkey, blocked_user_pubkey FROM k_blocks",
+        )
+        .fetch_all(&pool)
+        .await
+        {
+            Ok(pairs) => {
+                filter.load(&pairs, started_at);
+                gauge!("k_block_filter_pairs").set(pairs.len() as f64);
+                if failing {
+                    info!("Block filter refreshed again ({} blocks)", pairs.len());
+                }
+                failing = false;
+            }
+            Err(e) => {
+                warn!("Failed to refresh the block filter: {}", e);
+                failing = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pubkey(byte: u8) -> Vec<u8> {
+        vec![byte; 33]
+    }
+
+    fn loaded_filter() -> BlockFilter {
+        let filter = BlockFilter::new(Duration::from_secs(5));
+        filter.load(&[(pubkey(1), pubkey(2)), (pubkey(1), pubkey(3))], 1_000);
+        filter
+    }
+
+    #[test]
+    fn test_unloaded_filter_rules_nothing_out() {
+        let filter = BlockFilter::new(Duration::from_secs(5));
+        assert!(filter.may_block(&pubkey(1), None, 1_000));
+        assert!(filter.may_block(&pubkey(1), Some(&pubkey(2)), 1_000));
+    }
+
+    #[test]
+    fn test_loaded_blocks_are_kept() {
+        let filter = loaded_filter();
+        assert!(filter.may_block(&pubkey(1), None, 1_000));
+        assert!(filter.may_block(&pubkey(1), Some(&pubkey(2)), 1_000));
+        assert!(filter.may_block(&pubkey(1), Some(&pubkey(3)), 1_000));
+    }
+
+    #[test]
+    fn test_requester_blocking_nobody_is_ruled_out() {
+        let filter = loaded_filter();
+        assert!(!filter.may_block(&pubkey(2), None, 1_000));
+        assert!(!filter.may_block(&pubkey(2), Some(&pubkey(1)), 1_000));
+        assert!(!filter.may_block(&pubkey(1), Some(&pubkey(4)), 1_000));
+    }
+
+    #[test]
+    fn test_stale_filter_rules_nothing_out() {
+        let filter = loaded_filter();
+        assert!(!filter.may_block(&pubkey(2), None, 16_000));
+        assert!(filter.may_block(&pubkey(2), None, 16_001));
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let pairs: Vec<_> = (0..1_000u32)
+            .map(|i| (i.to_be_bytes().to_vec(), pubkey(0)))
+            .collect();
+        let filter = BlockFilter::new(Duration::from_secs(5));
+        filter.load(&pairs, 1_000);
+
+        let false_positives = (1_000..11_000u32)
+            .filter(|i| filter.may_block(&i.to_be_bytes(), None, 1_000))
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+    }
+}
@@ -1,3 +1,10 @@
+use crate::feature_flags::FlagRule;
+use crate::tenants::{self, TenantConfig};
+use k_common::ConfigErrors;
+use k_common::failover::{self, DbHost, TargetSessionAttrs};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
@@ -6,12 +13,40 @@ pub struct AppConfig {
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
+    // First host of --db-host
     pub host: String,
     pub port: u16,
+    // Every host of --db-host in order, the first included
+    pub hosts: Vec<DbHost>,
+    // Which of the hosts connections go to, switching on failover (see failover.rs)
+    pub target_session_attrs: TargetSessionAttrs,
     pub database: String,
     pub username: String,
     pub password: String,
     pub max_connections: usize,
+    // Startup wait for PostgreSQL to become reachable (None = wait indefinitely)
+    pub wait_for_db_secs: Option<u64>,
+    // Wait for the processor to publish k_meta before serving requests
+    pub wait_for_schema: bool,
+    // Statement timeout of every connection, per-method budgets are capped by it (None = no timeout)
+    pub statement_timeout_ms: Option<u64>,
+    // Read replica serving paginated reads (None = all reads on the primary)
+    pub replica: Option<ReplicaConfig>,
+    // Tag every connection checkout with the request ID, not only budgeted transactions
+    pub tag_request_id: bool,
+    // Percentage of the calls also running the candidate query rewrites (0 = off)
+    pub shadow_query_percent: u8,
+    // Reload interval of the in-memory block filter of the feeds (0 = off, always join k_blocks)
+    pub block_filter_refresh_ms: u64,
+}
+
+/// Read replica with the database name and credentials of the primary (--replica-db-host)
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    pub host: String,
+    pub port: u16,
+    // Paginated reads go to the primary while the replica lags more than this
+    pub max_lag_ms: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -19,29 +54,554 @@ pub struct ServerConfig {
     pub bind_address: String,
     pub request_timeout: u64,
     pub rate_limit: u32,
+    // Client addresses tracked by the rate limiter at most (least recently seen evicted first)
+    pub rate_limit_max_clients: usize,
+    pub schema_refresh_interval: u64,
+    pub image_storage: ImageStorageConfig,
+    pub hide_rate_limited: bool,
+    // Window of the notification collapse in seconds (0 = notifications are never collapsed)
+    pub notification_collapse_window_secs: u64,
+    pub page_sizes: PageSizeConfig,
+    pub pool_monitor_interval_secs: u64,
+    // Log a warning when acquiring a pooled connection takes longer than this
+    pub pool_acquire_warn_ms: u64,
+    // Register /track-view and add approximate view counts to post details
+    pub view_tracking: bool,
+    // Public vote and view counts rounded down to a multiple of this size (0 = exact counts)
+    pub count_bucket_size: u64,
+    // Relations of other users' contents and profiles read for an anonymous requester, and
+    // requester fields withheld (--requester-privacy)
+    pub requester_privacy: bool,
+    // Hours of hourly request counts kept for /admin/request-analytics (None = not counted)
+    pub request_analytics_hours: Option<u64>,
+    // Requests at least this slow are logged with their statements (None = not traced)
+    pub slow_request_trace_ms: Option<u64>,
+    // Deployment feature flags (--feature-flags), override rows of k_feature_flags
+    pub feature_flags: HashMap<String, FlagRule>,
+    // Bearer token of the /admin endpoints (None = admin endpoints not registered)
+    pub admin_token: Option<String>,
+    // Read-only ActivityPub actors under /ap (None = not registered)
+    pub activitypub: Option<ActivityPubConfig>,
+    // Atom feeds under /feeds (None = not registered)
+    pub feeds: Option<FeedConfig>,
+    // Public URL of the webserver for /p preview pages and /sitemap.xml (None = not registered)
+    pub preview_base_url: Option<String>,
+    // Block explorer linked from post details and preview pages (None = no explorer links)
+    pub explorer: Option<ExplorerConfig>,
+    // Reject expensive endpoints while the database is under pressure (None = disabled)
+    pub load_shedding: Option<LoadSheddingConfig>,
+    // Serve hot first pages from a short-lived in-process cache (None = disabled)
+    pub response_cache: Option<ResponseCacheConfig>,
+    // Push newly indexed contents and votes over /ws (None = not registered)
+    pub live_feed: Option<LiveFeedConfig>,
+    // Serve the read-only GraphQL schema on /graphql
+    pub graphql: bool,
+    // Endpoints answered with 501 on this node (--disabled-endpoints), without leading slash
+    pub disabled_endpoints: Vec<String>,
+    // Communities served from their own schema next to the network's one (--tenants)
+    pub tenants: Vec<TenantConfig>,
+}
+
+/// Lifetimes of the response cache entries (--response-cache-ttl)
+#[derive(Debug, Clone)]
+pub struct ResponseCacheConfig {
+    // Entries younger than this are served without querying the database
+    pub ttl_secs: u64,
+    // Older entries are still served this long while a background refresh replaces them
+    pub stale_secs: u64,
+    // Refresh the most requested first pages this often, whether requested or not (0 = never)
+    pub prewarm_interval_secs: u64,
+}
+
+/// Thresholds of the adaptive load shedding (--load-shedding)
+#[derive(Debug, Clone)]
+pub struct LoadSheddingConfig {
+    // Shed when acquiring a pooled connection takes at least this long
+    pub acquire_threshold_ms: u64,
+    // Shed when more requests than this are being served at once
+    pub max_in_flight: usize,
+    // Minimum shedding duration once started, also sent as Retry-After
+    pub hold_secs: u64,
+    // Endpoints rejected while shedding, without leading slash (v1 and /v2)
+    pub endpoints: Vec<String>,
+}
+
+/// WebSocket live feed (--enable-live-feed)
+#[derive(Debug, Clone)]
+pub struct LiveFeedConfig {
+    // Connections accepted at once, further ones answer 503
+    pub max_clients: usize,
+}
+
+/// Atom feeds linking to a K client (--feed-client-url)
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    // Client URL without trailing slash
+    pub client_url: String,
+    // Client paths of a post ({id}) and of a user ({pubkey})
+    pub post_path: String,
+    pub user_path: String,
+}
+
+impl FeedConfig {
+    pub fn post_link(&self, post_id: &str) -> String {
+        format!(
+            "{}{}",
+            self.client_url,
+            self.post_path.replace("{id}", post_id)
+        )
+    }
+
+    pub fn user_link(&self, pubkey: &str) -> String {
+        format!(
+            "{}{}",
+            self.client_url,
+            self.user_path.replace("{pubkey}", pubkey)
+        )
+    }
+}
+
+/// Kaspa block explorer of each network (--explorer-url)
+#[derive(Debug, Clone)]
+pub struct ExplorerConfig {
+    // Base URLs without trailing slash by network name, as recorded by the processor in k_vars
+    pub network_urls: HashMap<String, String>,
+    // Base URL of networks without their own entry
+    pub default_url: Option<String>,
+    // Explorer paths of a transaction ({id}) and of a block ({hash})
+    pub transaction_path: String,
+    pub block_path: String,
+}
+
+impl ExplorerConfig {
+    /// Base URL of the explorer of a network (None = no explorer configured for it)
+    pub fn base_url(&self, network: &str) -> Option<&str> {
+        self.network_urls
+            .get(network)
+            .or(self.default_url.as_ref())
+            .map(String::as_str)
+    }
+
+    pub fn transaction_link(&self, network: &str, transaction_id: &str) -> Option<String> {
+        self.base_url(network).map(|base_url| {
+            format!(
+                "{}{}",
+                base_url,
+                self.transaction_path.replace("{id}", transaction_id)
+            )
+        })
+    }
+
+    pub fn block_link(&self, network: &str, block_hash: &str) -> Option<String> {
+        self.base_url(network).map(|base_url| {
+            format!(
+                "{}{}",
+                base_url,
+                self.block_path.replace("{hash}", block_hash)
+            )
+        })
+    }
+}
+
+/// Read-only ActivityPub exposure of K users (--activitypub-base-url)
+#[derive(Debug, Clone)]
+pub struct ActivityPubConfig {
+    // Public URL of the webserver, without trailing slash; actor ids are built from it
+    pub base_url: String,
+    // PKCS#8 PEM RSA private key shared by all actors
+    pub private_key_pem: String,
+    // Serve actors, outboxes and notes to HTTP-signed fetches only (authorized fetch)
+    pub require_signatures: bool,
+}
+
+/// Default and maximum `limit` of a paginated endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageSizeLimits {
+    pub default: u32,
+    pub max: u32,
+}
+
+/// Page sizes enforced by all paginated endpoints, with per-endpoint overrides
+#[derive(Debug, Clone)]
+pub struct PageSizeConfig {
+    pub global: PageSizeLimits,
+    // Keyed by endpoint path without leading slash, e.g. "get-notifications"
+    pub overrides: HashMap<String, PageSizeLimits>,
+}
+
+impl PageSizeConfig {
+    pub fn limits_for(&self, endpoint: &str) -> PageSizeLimits {
+        self.overrides.get(endpoint).copied().unwrap_or(self.global)
+    }
+
+    /// Parse "endpoint=default:max" entries separated by commas
+    fn parse_overrides(value: &str) -> Result<HashMap<String, PageSizeLimits>, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let invalid = || {
+                    format!(
+                        "Invalid page size override '{}'. Expected endpoint=default:max",
+                        entry
+                    )
+                };
+                let (endpoint, sizes) = entry.split_once('=').ok_or_else(invalid)?;
+                let (default, max) = sizes
+                    .split_once(':')
+                    .and_then(|(default, max)| {
+                        Some((default.trim().parse().ok()?, max.trim().parse().ok()?))
+                    })
+                    .ok_or_else(invalid)?;
+                let endpoint = endpoint.trim().trim_start_matches('/').to_string();
+                let limits = PageSizeLimits { default, max };
+                Self::validate(&endpoint, limits)?;
+                Ok((endpoint, limits))
+            })
+            .collect()
+    }
+
+    fn validate(name: &str, limits: PageSizeLimits) -> Result<(), String> {
+        if limits.default < 1 || limits.default > limits.max {
+            return Err(format!(
+                "Invalid page sizes for {}: default {} must be between 1 and max {}",
+                name, limits.default, limits.max
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse endpoint names separated by commas, with or without leading slash
+fn parse_endpoints(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|endpoint| endpoint.trim().trim_start_matches('/'))
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse "flag=on|off|percent" entries separated by commas
+fn parse_feature_flags(value: &str) -> Result<HashMap<String, FlagRule>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, setting) = entry.split_once('=').ok_or_else(|| {
+                format!(
+                    "Invalid feature flag '{}'. Expected flag=on, flag=off or flag=percent",
+                    entry
+                )
+            })?;
+            let rule = match setting.trim() {
+                "on" => FlagRule {
+                    enabled: true,
+                    rollout_percent: 100,
+                },
+                "off" => FlagRule {
+                    enabled: false,
+                    rollout_percent: 0,
+                },
+                percent => match percent.parse::<u8>() {
+                    Ok(percent) if percent <= 100 => FlagRule {
+                        enabled: true,
+                        rollout_percent: percent,
+                    },
+                    _ => {
+                        return Err(format!(
+                            "Invalid feature flag '{}'. Expected flag=on, flag=off or flag=percent (0-100)",
+                            entry
+                        ));
+                    }
+                },
+            };
+            Ok((name.trim().to_string(), rule))
+        })
+        .collect()
+}
+
+/// Where K-transaction-processor stores profile images (must match the processor setting)
+#[derive(Debug, Clone)]
+pub enum ImageStorageConfig {
+    // Images are stored inline as base64 in k_broadcasts
+    Database,
+    // Images are read from the local directory shared with the processor
+    Filesystem { path: String },
+    // Clients are redirected to the public URL of the bucket/CDN
+    S3 { base_url: String },
 }
 
 impl AppConfig {
-    pub fn from_args(args: &crate::Args, worker_threads: usize) -> Self {
+    pub fn from_args(args: &crate::Args, worker_threads: usize) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        // Comma-separated database hosts for failover
+        let hosts = errors.check(failover::parse_hosts(&args.db_host, args.db_port));
+        let target_session_attrs =
+            errors.check(TargetSessionAttrs::parse(&args.db_target_session_attrs));
+
+        errors.at_least("--worker-threads", worker_threads, 1);
         // Calculate default db connections as worker_threads * 3, with a minimum of 10
         let default_db_connections = std::cmp::max(worker_threads * 3, 10);
-        let max_connections = args.db_max_connections.unwrap_or(default_db_connections);
+        let max_connections = errors.at_least(
+            "--db-max-connections",
+            args.db_max_connections.unwrap_or(default_db_connections),
+            1,
+        );
+        errors.at_least("--request-timeout", args.request_timeout, 1);
+        errors.at_least("--rate-limit", args.rate_limit, 1);
+        let pool_monitor_interval_secs =
+            errors.at_least("--pool-monitor-interval", args.pool_monitor_interval, 1);
+
+        if args.bind_address.trim().parse::<SocketAddr>().is_err() {
+            errors.push(format!(
+                "Invalid bind address '{}'. Expected host:port, e.g. 0.0.0.0:8080",
+                args.bind_address
+            ));
+        }
+
+        if let Some(hours) = args.request_analytics_hours {
+            errors.at_least("--request-analytics-hours", hours, 1);
+            if args.admin_token.as_deref().is_none_or(str::is_empty) {
+                errors.push("--request-analytics-hours requires --admin-token".to_string());
+            }
+        }
+
+        if let Some(threshold_ms) = args.slow_request_trace_ms {
+            errors.at_least("--slow-request-trace-ms", threshold_ms, 1);
+        }
+
+        if args.shadow_query_percent > 100 {
+            errors.push(format!(
+                "--shadow-query-percent must be between 0 and 100, got {}",
+                args.shadow_query_percent
+            ));
+        }
 
-        Self {
+        let image_storage =
+            match args.image_storage.trim() {
+                "database" => Some(ImageStorageConfig::Database),
+                "filesystem" => errors
+                    .check(args.image_storage_path.clone().ok_or_else(|| {
+                        "--image-storage-path is required for filesystem image storage".to_string()
+                    }))
+                    .map(|path| ImageStorageConfig::Filesystem { path }),
+                "s3" => errors
+                    .check(args.image_base_url.as_deref().ok_or_else(|| {
+                        "--image-base-url is required for s3 image storage".to_string()
+                    }))
+                    .map(|base_url| ImageStorageConfig::S3 {
+                        base_url: base_url.trim_end_matches('/').to_string(),
+                    }),
+                other => errors.check(Err(format!(
+                    "Invalid image storage '{}'. Must be 'database', 'filesystem' or 's3'",
+                    other
+                ))),
+            };
+
+        let global_page_sizes = PageSizeLimits {
+            default: args.default_page_size,
+            max: args.max_page_size,
+        };
+        errors.check(PageSizeConfig::validate("all endpoints", global_page_sizes));
+        let page_size_overrides =
+            errors.check(PageSizeConfig::parse_overrides(&args.page_size_overrides));
+        let feature_flags = errors.check(parse_feature_flags(&args.feature_flags));
+        let tenants = errors.check(
+            tenants::parse_tenants(&args.tenants).map_err(|e| format!("Invalid --tenants: {}", e)),
+        );
+
+        let activitypub = args
+            .activitypub_base_url
+            .as_deref()
+            .map(|base_url| {
+                let base_url = base_url.trim().trim_end_matches('/').to_string();
+                if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+                    return Err(format!(
+                        "Invalid --activitypub-base-url '{}'. Must be an http(s) URL",
+                        base_url
+                    ));
+                }
+                let key_file = args.activitypub_key_file.as_deref().ok_or_else(|| {
+                    "--activitypub-key-file is required with --activitypub-base-url".to_string()
+                })?;
+                let private_key_pem = std::fs::read_to_string(key_file).map_err(|e| {
+                    format!(
+                        "Failed to read --activitypub-key-file '{}': {}",
+                        key_file, e
+                    )
+                })?;
+                Ok(ActivityPubConfig {
+                    base_url,
+                    private_key_pem,
+                    require_signatures: args.activitypub_require_signatures,
+                })
+            })
+            .transpose();
+        let activitypub = errors.check(activitypub);
+
+        if args.feed_client_url.is_some() {
+            if !args.feed_post_path.contains("{id}") {
+                errors.push("--feed-post-path must contain {id}".to_string());
+            }
+            if !args.feed_user_path.contains("{pubkey}") {
+                errors.push("--feed-user-path must contain {pubkey}".to_string());
+            }
+        }
+        let feeds = args
+            .feed_client_url
+            .as_deref()
+            .map(|client_url| FeedConfig {
+                client_url: client_url.trim().trim_end_matches('/').to_string(),
+                post_path: args.feed_post_path.clone(),
+                user_path: args.feed_user_path.clone(),
+            });
+
+        if args.explorer_url.is_some() {
+            if !args.explorer_transaction_path.contains("{id}") {
+                errors.push("--explorer-transaction-path must contain {id}".to_string());
+            }
+            if !args.explorer_block_path.contains("{hash}") {
+                errors.push("--explorer-block-path must contain {hash}".to_string());
+            }
+        }
+        let explorer = args.explorer_url.as_deref().map(|explorer_url| {
+            let mut network_urls = HashMap::new();
+            let mut default_url = None;
+            for entry in explorer_url
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+            {
+                let url = |url: &str| url.trim().trim_end_matches('/').to_string();
+                match entry.split_once('=') {
+                    Some((network, base_url)) => {
+                        network_urls.insert(network.trim().to_string(), url(base_url));
+                    }
+                    None => default_url = Some(url(entry)),
+                }
+            }
+            ExplorerConfig {
+                network_urls,
+                default_url,
+                transaction_path: args.explorer_transaction_path.clone(),
+                block_path: args.explorer_block_path.clone(),
+            }
+        });
+
+        let shed_acquire_ms = errors.at_least("--shed-acquire-ms", args.shed_acquire_ms, 1);
+        let shed_hold_secs = errors.at_least("--shed-hold-secs", args.shed_hold_secs, 1);
+        let load_shedding = args.load_shedding.then(|| LoadSheddingConfig {
+            acquire_threshold_ms: shed_acquire_ms,
+            max_in_flight: args.shed_max_in_flight,
+            hold_secs: shed_hold_secs,
+            endpoints: parse_endpoints(&args.shed_endpoints),
+        });
+
+        let live_feed = args.enable_live_feed.then_some(LiveFeedConfig {
+            max_clients: args.live_feed_max_clients,
+        });
+
+        let response_cache = args
+            .response_cache_ttl
+            .filter(|&ttl_secs| ttl_secs > 0)
+            .map(|ttl_secs| ResponseCacheConfig {
+                ttl_secs,
+                stale_secs: args.response_cache_stale,
+                prewarm_interval_secs: args.response_cache_prewarm_interval,
+            });
+
+        let (
+            Some(hosts),
+            Some(target_session_attrs),
+            Some(image_storage),
+            Some(page_size_overrides),
+            Some(feature_flags),
+            Some(tenants),
+            Some(activitypub),
+        ) = (
+            hosts,
+            target_session_attrs,
+            image_storage,
+            page_size_overrides,
+            feature_flags,
+            tenants,
+            activitypub,
+        )
+        else {
+            return Err(errors);
+        };
+        errors.into_result()?;
+
+        let page_sizes = PageSizeConfig {
+            global: global_page_sizes,
+            overrides: page_size_overrides,
+        };
+
+        Ok(Self {
             database: DatabaseConfig {
-                host: args.db_host.clone(),
-                port: args.db_port,
+                host: hosts[0].host.clone(),
+                port: hosts[0].port,
+                hosts,
+                target_session_attrs,
                 database: args.db_name.clone(),
                 username: args.db_user.clone(),
                 password: args.db_password.clone(),
                 max_connections,
+                wait_for_db_secs: args.wait_for_db,
+                wait_for_schema: args.wait_for_schema,
+                // Queries outliving the HTTP request timeout only waste the database by default
+                statement_timeout_ms: Some(
+                    args.statement_timeout_ms
+                        .unwrap_or(args.request_timeout * 1000),
+                )
+                .filter(|&timeout_ms| timeout_ms > 0),
+                replica: args.replica_db_host.clone().map(|host| ReplicaConfig {
+                    host,
+                    port: args.replica_db_port.unwrap_or(args.db_port),
+                    max_lag_ms: args.replica_max_lag_ms,
+                }),
+                tag_request_id: args.db_request_id,
+                shadow_query_percent: args.shadow_query_percent,
+                block_filter_refresh_ms: args.block_filter_refresh_ms,
             },
             server: ServerConfig {
                 bind_address: args.bind_address.clone(),
                 request_timeout: args.request_timeout,
                 rate_limit: args.rate_limit,
+                rate_limit_max_clients: args.rate_limit_max_clients,
+                schema_refresh_interval: args.schema_refresh_interval,
+                image_storage,
+                hide_rate_limited: args.hide_rate_limited,
+                notification_collapse_window_secs: args.notification_collapse_window,
+                page_sizes,
+                pool_monitor_interval_secs,
+                pool_acquire_warn_ms: args.pool_acquire_warn_ms,
+                view_tracking: args.enable_view_tracking,
+                count_bucket_size: args.count_bucket_size,
+                requester_privacy: args.requester_privacy,
+                request_analytics_hours: args.request_analytics_hours,
+                slow_request_trace_ms: args.slow_request_trace_ms,
+                feature_flags,
+                admin_token: args.admin_token.clone().filter(|token| !token.is_empty()),
+                activitypub,
+                feeds,
+                preview_base_url: args
+                    .preview_base_url
+                    .as_deref()
+                    .map(|url| url.trim().trim_end_matches('/').to_string()),
+                explorer,
+                load_shedding,
+                response_cache,
+                live_feed,
+                graphql: args.enable_graphql,
+                disabled_endpoints: parse_endpoints(&args.disabled_endpoints),
+                tenants,
             },
-        }
+        })
     }
 
     pub fn connection_string(&self) -> String {
@@ -54,4 +614,161 @@ impl AppConfig {
             self.database.database
         )
     }
+
+    /// Connection string whose sessions find the K tables in the schema of `tenant`
+    pub fn tenant_connection_string(&self, tenant: &TenantConfig) -> String {
+        format!(
+            "{}?options=-c%20search_path%3D{}%2Cpublic",
+            self.connection_string(),
+            tenant.schema()
+        )
+    }
+
+    pub fn replica_connection_string(&self) -> Option<String> {
+        self.database.replica.as_ref().map(|replica| {
+            format!(
+                "postgresql://{}:{}@{}:{}/{}",
+                self.database.username,
+                self.database.password,
+                replica.host,
+                replica.port,
+                self.database.database
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn config(worker_threads: usize, extra_args: &[&str]) -> Result<AppConfig, ConfigErrors> {
+        let args = crate::Args::try_parse_from(
+            [
+                "K-webserver",
+                "--db-host",
+                "localhost",
+                "--db-name",
+                "k",
+                "--db-user",
+                "k",
+                "--db-password",
+                "secret",
+            ]
+            .iter()
+            .chain(extra_args),
+        )
+        .unwrap();
+        AppConfig::from_args(&args, worker_threads)
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = config(4, &[]).unwrap();
+        assert_eq!(config.server.bind_address, "127.0.0.1:8080");
+        // worker_threads * 3, at least 10
+        assert_eq!(config.database.max_connections, 12);
+    }
+
+    #[test]
+    fn test_zero_worker_threads() {
+        let errors = config(0, &[]).unwrap_err();
+        assert_eq!(
+            errors.errors(),
+            ["Invalid --worker-threads 0. Must be at least 1"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_bind_address() {
+        for address in ["localhost", "0.0.0.0", "0.0.0.0:http", "300.0.0.1:8080"] {
+            let errors = config(4, &["--bind-address", address]).unwrap_err();
+            assert_eq!(
+                errors.errors(),
+                [format!(
+                    "Invalid bind address '{}'. Expected host:port, e.g. 0.0.0.0:8080",
+                    address
+                )]
+            );
+        }
+        assert!(config(4, &["--bind-address", "127.0.0.1:3000"]).is_ok());
+    }
+
+    #[test]
+    fn test_errors_reported_together() {
+        let errors = config(
+            0,
+            &[
+                "--bind-address",
+                "nowhere",
+                "--rate-limit",
+                "0",
+                "--image-storage",
+                "s3",
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors.errors(),
+            [
+                "Invalid --worker-threads 0. Must be at least 1",
+                "Invalid --rate-limit 0. Must be at least 1",
+                "Invalid bind address 'nowhere'. Expected host:port, e.g. 0.0.0.0:8080",
+                "--image-base-url is required for s3 image storage",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_size_overrides() {
+        let page_sizes = PageSizeConfig {
+            global: PageSizeLimits {
+                default: 10,
+                max: 100,
+            },
+            overrides: PageSizeConfig::parse_overrides(
+                " /get-notifications=5:20 , get-replies=50:500,",
+            )
+            .unwrap(),
+        };
+
+        assert_eq!(
+            page_sizes.limits_for("get-notifications"),
+            PageSizeLimits {
+                default: 5,
+                max: 20
+            }
+        );
+        assert_eq!(
+            page_sizes.limits_for("get-replies"),
+            PageSizeLimits {
+                default: 50,
+                max: 500
+            }
+        );
+        assert_eq!(page_sizes.limits_for("get-posts"), page_sizes.global);
+        assert!(PageSizeConfig::parse_overrides("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_page_sizes() {
+        for value in [
+            "get-posts",
+            "get-posts=10",
+            "get-posts=ten:100",
+            "get-posts=0:100",
+            "get-posts=101:100",
+        ] {
+            assert!(
+                PageSizeConfig::parse_overrides(value).is_err(),
+                "value: {:?}",
+                value
+            );
+        }
+        assert!(
+            PageSizeConfig::validate("all endpoints", PageSizeLimits { default: 1, max: 1 })
+                .is_ok()
+        );
+    }
 }
@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+/// Public counters of posts and replies rounded down to their bucket (--count-bucket-size)
+const BUCKETED_COUNTERS: [&str; 3] = ["upVotesCount", "downVotesCount", "viewCount"];
+
+/// Fee-weighted vote totals, withheld while counts are bucketed: a sum of fees changes with
+/// every single vote
+const WITHHELD_COUNTERS: [&str; 2] = ["upVotesWeight", "downVotesWeight"];
+
+/// Round `count` down to a multiple of `bucket_size` (0 or 1 = exact count)
+pub fn bucket_count(count: u64, bucket_size: u64) -> u64 {
+    if bucket_size <= 1 {
+        count
+    } else {
+        count - count % bucket_size
+    }
+}
+
+/// Bucket the public counters of every object of a JSON response, nested replies and
+/// envelopes included
+pub fn bucket_public_counts(value: &mut Value, bucket_size: u64) {
+    match value {
+        Value::Object(object) => {
+            for key in WITHHELD_COUNTERS {
+                object.remove(key);
+            }
+            for (key, field) in object.iter_mut() {
+                match field.as_u64() {
+                    Some(count) if BUCKETED_COUNTERS.contains(&key.as_str()) => {
+                        *field = Value::from(bucket_count(count, bucket_size));
+                    }
+                    _ => bucket_public_counts(field, bucket_size),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                bucket_public_counts(item, bucket_size);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bucket_count() {
+        assert_eq!(bucket_count(7, 0), 7);
+        assert_eq!(bucket_count(7, 1), 7);
+        assert_eq!(bucket_count(4, 5), 0);
+        assert_eq!(bucket_count(5, 5), 5);
+        assert_eq!(bucket_count(123, 10), 120);
+    }
+
+    #[test]
+    fn test_bucket_public_counts() {
+        let mut response = json!({
+            "posts": [{
+                "id": "a",
+                "repliesCount": 3,
+                "upVotesCount": 7,
+                "downVotesCount": 2,
+                "upVotesWeight": 700,
+                "downVotesWeight": 200,
+                "nestedReplies": [{ "id": "b", "upVotesCount": 12, "viewCount": 9 }]
+            }],
+            "pagination": { "hasMore": false }
+        });
+        bucket_public_counts(&mut response, 5);
+
+        assert_eq!(
+            response,
+            json!({
+                "posts": [{
+                    "id": "a",
+                    "repliesCount": 3,
+                    "upVotesCount": 5,
+                    "downVotesCount": 0,
+                    "nestedReplies": [{ "id": "b", "upVotesCount": 10, "viewCount": 5 }]
+                }],
+                "pagination": { "hasMore": false }
+            })
+        );
+    }
+}
@@ -1,60 +1,577 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::{PgPool, Row, postgres::PgPoolOptions};
+use axum_prometheus::metrics::counter;
+use sqlx::{
+    Executor, PgConnection, PgPool, Postgres, Row, Transaction,
+    postgres::{PgConnectOptions, PgPoolOptions, PgRow},
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use tracing::{info, warn};
 
+use crate::block_filter::BlockFilter;
 use crate::database_trait::{
-    DatabaseError, DatabaseInterface, DatabaseResult, PaginatedResult, QueryOptions,
+    DatabaseError, DatabaseInterface, DatabaseResult, FeatureFlagRecord, PaginatedResult,
+    PostRanking, ProfileImageRecord, QueryOptions, SchemaMeta,
 };
 use crate::models::{
-    ContentRecord, KBroadcastRecord, KPostRecord, KReplyRecord, KVoteRecord,
-    NotificationContentRecord, PaginationMetadata,
+    ContentRecord, KArticleRecord, KBroadcastRecord, KContentTipsRecord, KDuplicateContentRecord,
+    KErasureRequestRecord, KEventRecord, KFeaturedPostRecord, KFeaturedRecord, KFollowRecord,
+    KGroupPostRecord, KGroupRecord, KImpersonationFlagRecord, KNicknameUseRecord, KPostRecord,
+    KProfileDiffRecord, KProfileSnapshotRecord, KQueryStatsRecord, KReplyRecord,
+    KTableStorageRecord, KTippedPostRecord, KUserStatsRecord, KVoteRecord,
+    NotificationContentRecord, NotificationSubject, PaginationMetadata,
 };
+use crate::named_query::NamedQuery;
+use crate::query_builder::QueryParams;
+use crate::replica_lag::{ReplicaLag, now_ms};
+use crate::request_id;
+use crate::shadow_query::ShadowQueries;
+use k_common::failover::{self, DbHost, TargetSessionAttrs};
+
+/// Seconds between database connection checks while waiting at startup
+pub const STARTUP_RETRY_SECS: u64 = 5;
+
+/// Mentioned pubkeys listed per content by the feed queries, the full count is in
+/// `mentions_count` and the full list in /get-post-mentions
+const MAX_LISTED_MENTIONS: u32 = 20;
+
+/// Statement timeouts of the costliest queries in milliseconds (scan budget per method),
+/// capped by the connection default; other queries run under the default alone
+const QUERY_BUDGETS_MS: &[(&str, u64)] = &[
+    ("get_content_by_id", 2_000),
+    ("get_contents_by_ids", 5_000),
+    ("get_user_stats", 5_000),
+    ("search_users", 3_000),
+    ("search_broadcasts_by_nickname", 3_000),
+    ("get_contents_mentioning_user", 5_000),
+    ("get_notifications", 5_000),
+    ("get_content_following", 5_000),
+    ("get_posts_following", 5_000),
+    ("get_hashtag_content", 5_000),
+    ("search_posts", 5_000),
+    ("get_most_active_users", 8_000),
+    ("get_trending_hashtags", 8_000),
+    ("get_top_tipped_posts", 8_000),
+    ("get_ranked_posts", 8_000),
+    ("get_posts_meta", 5_000),
+    ("get_featured_posts", 5_000),
+    ("get_stats", 10_000),
+    ("get_duplicate_contents", 10_000),
+];
 
 pub struct PostgresDbManager {
     pub pool: PgPool,
+    // Hide content flagged by the processor's per-sender rate limit (--hide-rate-limited)
+    hide_rate_limited: bool,
+    // Repeated notifications of one sender on the same content within a window of this many
+    // milliseconds are collapsed into the latest one (--notification-collapse-window, 0 = never)
+    notification_collapse_window_ms: i64,
+    // Default statement timeout of every connection (None = no timeout)
+    statement_timeout_ms: Option<u64>,
+    // Vote counts read from k_vote_totals (schema v22+), updated with every schema meta read
+    vote_totals: AtomicBool,
+    // Notifications read from the denormalized k_mentions columns (schema v23+), same as above
+    enriched_mentions: AtomicBool,
+    // Render spans read from k_render_spans (schema v26+), same as above
+    render_spans: AtomicBool,
+    // Feeds hide content above k_meta.visible_before (schema v28+, --confirmation-depth of the
+    // processor), same as above
+    visibility_watermark: AtomicBool,
+    // Conversations read through k_contents.root_post_id (schema v33+), same as above
+    thread_roots: AtomicBool,
+    // k_meta.cold_tier_before: content older than this may be in k_contents_cold (0 = no cold tier)
+    cold_tier_before: AtomicI64,
+    // Read replica serving paginated reads while its lag allows (--replica-db-host)
+    replica: Option<(PgPool, Arc<ReplicaLag>)>,
+    // Candidate query rewrites compared with the current queries (--shadow-query-percent)
+    shadow_queries: Option<ShadowQueries>,
+    // In-memory bloom filter of k_blocks sparing feeds the block join (--block-filter-refresh-ms)
+    block_filter: Option<Arc<BlockFilter>>,
+}
+
+/// Extra WHERE condition hiding notifications (k_mentions alias) followed by a newer one of the
+/// same sender, kind and parent content in the same collapse window of `window` milliseconds
+/// (none when 0)
+fn notification_collapse_filter(window: i64, alias: &str) -> String {
+    if window == 0 {
+        return String::new();
+    }
+    format!(
+        r#" AND NOT EXISTS (
+                SELECT 1 FROM k_mentions newer
+                WHERE newer.mentioned_pubkey = {alias}.mentioned_pubkey
+                  AND newer.sender_pubkey = {alias}.sender_pubkey
+                  AND newer.content_type = {alias}.content_type
+                  AND newer.parent_id = {alias}.parent_id
+                  AND {alias}.content_type IN ('vote', 'reply', 'quote')
+                  AND newer.block_time / {window} = {alias}.block_time / {window}
+                  AND (newer.block_time, newer.id) > ({alias}.block_time, {alias}.id)
+              )"#
+    )
+}
+
+/// Column collapse_count: number of notifications of `mentioned` collapsed into the
+/// notification `notification` (see notification_collapse_filter), 1 when not collapsed
+fn notification_collapse_count(window: i64, notification: &str, mentioned: &str) -> String {
+    if window == 0 {
+        return "1::bigint as collapse_count".to_string();
+    }
+    format!(
+        r#"CASE WHEN {notification}.content_type IN ('vote', 'reply', 'quote') AND {notification}.parent_id IS NOT NULL THEN (
+                    SELECT COUNT(*) FROM k_mentions older
+                    WHERE older.mentioned_pubkey = {mentioned}
+                      AND older.sender_pubkey = {notification}.sender_pubkey
+                      AND older.content_type = {notification}.content_type
+                      AND older.parent_id = {notification}.parent_id
+                      AND older.block_time / {window} = {notification}.block_time / {window}
+                ) ELSE 1 END as collapse_count"#
+    )
 }
 
 impl PostgresDbManager {
-    pub async fn new(connection_string: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+    /// Connect to PostgreSQL on the first of `hosts` suitable for `target_session_attrs` (none =
+    /// the host of `connection_string`), retrying until reachable or until `wait_for_db_secs` elapsed (None = wait indefinitely).
+    /// Statements of every connection are cancelled after `statement_timeout_ms` (None = no
+    /// timeout). With `tag_request_id`, every connection checkout is tagged with the ID of the
+    /// request it serves (see tag_session)
+    pub async fn new(
+        connection_string: &str,
+        hosts: &[DbHost],
+        target_session_attrs: TargetSessionAttrs,
+        max_connections: u32,
+        wait_for_db_secs: Option<u64>,
+        statement_timeout_ms: Option<u64>,
+        tag_request_id: bool,
+    ) -> Result<Self, sqlx::Error> {
+        let connect_options: PgConnectOptions = connection_string.parse()?;
+        let started = std::time::Instant::now();
+        let mut attempt: u32 = 1;
+
         loop {
-            match PgPoolOptions::new()
+            let mut pool_options = PgPoolOptions::new()
                 .max_connections(max_connections)
                 .acquire_timeout(std::time::Duration::from_secs(30))
-                .connect(connection_string)
-                .await
-            {
-                Ok(pool) => {
-                    // Test the pool connection
-                    match sqlx::query("SELECT 1").fetch_one(&pool).await {
-                        Ok(_) => {
-                            info!("Database connection pool created and tested successfully");
-                            return Ok(Self { pool });
+                .after_connect(move |conn, _meta| {
+                    Box::pin(async move {
+                        if let Some(timeout_ms) = statement_timeout_ms {
+                            conn.execute(
+                                format!("SET statement_timeout = {}", timeout_ms).as_str(),
+                            )
+                            .await?;
                         }
-                        Err(e) => {
-                            warn!(
-                                "Database connection pool created but test query failed: {}",
-                                e
-                            );
-                            warn!("Retrying in 10 seconds...");
-                            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                        if tag_request_id {
+                            Self::tag_session(conn).await?;
                         }
-                    }
+                        Ok(())
+                    })
+                });
+            if tag_request_id {
+                // after_connect covers new connections, before_acquire the idle ones
+                pool_options = pool_options.before_acquire(|conn, _meta| {
+                    Box::pin(async move { Self::tag_session(conn).await.map(|_| true) })
+                });
+            }
+
+            let result = async {
+                let options =
+                    failover::select_host(&connect_options, hosts, target_session_attrs).await?;
+                let pool = pool_options.connect_with(options).await?;
+                // Test the pool connection
+                NamedQuery::new("connect", "SELECT 1")
+                    .query()
+                    .fetch_one(&pool)
+                    .await?;
+                Ok::<_, sqlx::Error>(pool)
+            }
+            .await;
+
+            let e = match result {
+                Ok(pool) => {
+                    info!("Database connection pool created and tested successfully");
+                    return Ok(Self {
+                        pool,
+                        hide_rate_limited: false,
+                        notification_collapse_window_ms: 0,
+                        statement_timeout_ms,
+                        vote_totals: AtomicBool::new(false),
+                        enriched_mentions: AtomicBool::new(false),
+                        render_spans: AtomicBool::new(false),
+                        visibility_watermark: AtomicBool::new(false),
+                        thread_roots: AtomicBool::new(false),
+                        cold_tier_before: AtomicI64::new(0),
+                        replica: None,
+                        shadow_queries: None,
+                        block_filter: None,
+                    });
+                }
+                Err(e) => e,
+            };
+
+            let elapsed = started.elapsed().as_secs();
+            let retry_in = match wait_for_db_secs {
+                Some(limit) if elapsed >= limit => {
+                    warn!(
+                        "Database not reachable after {}s ({} attempts), giving up",
+                        elapsed, attempt
+                    );
+                    return Err(e);
                 }
-                Err(e) => {
-                    warn!("Failed to create database connection pool: {}", e);
-                    warn!("Retrying in 10 seconds...");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                Some(limit) => {
+                    warn!(
+                        "Waiting for database (attempt {}, {}s/{}s): {}",
+                        attempt, elapsed, limit, e
+                    );
+                    STARTUP_RETRY_SECS.min(limit - elapsed)
                 }
-            }
+                None => {
+                    warn!(
+                        "Waiting for database (attempt {}, {}s elapsed): {}",
+                        attempt, elapsed, e
+                    );
+                    STARTUP_RETRY_SECS
+                }
+            };
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(retry_in)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Hide rate-limited content from content lists (requires schema v7+)
+    pub fn with_hide_rate_limited(mut self, hide_rate_limited: bool) -> Self {
+        self.hide_rate_limited = hide_rate_limited;
+        self
+    }
+
+    /// Collapse repeated vote, reply and quote notifications of one sender on the same content
+    /// within windows of `window_secs` (requires schema v23+, 0 = disabled)
+    pub fn with_notification_collapse_window(mut self, window_secs: u64) -> Self {
+        self.notification_collapse_window_ms = window_secs.saturating_mul(1000) as i64;
+        self
+    }
+
+    /// Serve paginated reads from a read replica whose lag is tracked by `lag`
+    pub fn with_replica(mut self, replica: PgPool, lag: Arc<ReplicaLag>) -> Self {
+        self.replica = Some((replica, lag));
+        self
+    }
+
+    /// Also run the candidate rewrites of the queries that have one for `percent` % of their
+    /// calls, comparing rows and durations (0 = never)
+    pub fn with_shadow_queries(mut self, percent: u8) -> Self {
+        self.shadow_queries = (percent > 0).then(|| ShadowQueries::new(percent));
+        self
+    }
+
+    /// Leave the k_blocks join out of the feed queries of requesters `filter` rules out any
+    /// block for
+    pub fn with_block_filter(mut self, filter: Arc<BlockFilter>) -> Self {
+        self.block_filter = Some(filter);
+        self
+    }
+
+    /// Shadow executions of this call, when sampled (see with_shadow_queries)
+    fn shadow_sample(&self) -> Option<&ShadowQueries> {
+        self.shadow_queries
+            .as_ref()
+            .filter(|shadow| shadow.sample())
+    }
+
+    /// Pool of a paginated read: the read replica when its lag is within --replica-max-lag-ms
+    /// and it has indexed content up to the block time of the `after` cursor, else the primary
+    fn read_pool(&self, options: &QueryOptions) -> &PgPool {
+        let Some((replica, lag)) = &self.replica else {
+            return &self.pool;
+        };
+
+        let after_ms = options
+            .after
+            .as_deref()
+            .and_then(|cursor| Self::parse_compound_cursor(cursor).ok())
+            .map(|(timestamp, _)| timestamp as i64);
+        if lag.serves(after_ms, now_ms()) {
+            replica
+        } else {
+            counter!("k_replica_reads_on_primary_total").increment(1);
+            &self.pool
+        }
+    }
+
+    /// LEFT JOIN of the blocks of `requester` (a query parameter) on the authors in `author_column`,
+    /// with the WHERE condition hiding blocked authors. Both are left out (the condition is TRUE)
+    /// when the block filter rules out that the requester blocks anyone, or `author` when the
+    /// query lists a single author
+    fn blocked_authors_join(
+        &self,
+        requester_pubkey: &[u8],
+        requester: &str,
+        author_column: &str,
+        author: Option<&[u8]>,
+    ) -> (String, &'static str) {
+        let ruled_out = self
+            .block_filter
+            .as_ref()
+            .is_some_and(|filter| !filter.may_block(requester_pubkey, author, now_ms()));
+        if ruled_out {
+            counter!("k_block_filter_skipped_joins_total").increment(1);
+            return (String::new(), "TRUE");
+        }
+        (
+            format!(
+                "LEFT JOIN k_blocks kb ON kb.sender_pubkey = {requester} AND kb.blocked_user_pubkey = {author_column}"
+            ),
+            "kb.blocked_user_pubkey IS NULL",
+        )
+    }
+
+    /// Extra WHERE condition excluding rate-limited content for the given k_contents alias
+    fn rate_limited_filter(&self, alias: &str) -> String {
+        if self.hide_rate_limited {
+            format!(" AND {}.is_rate_limited = FALSE", alias)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Extra WHERE condition hiding content (k_contents alias) whose block has not reached the
+    /// processor's confirmation depth yet; the watermark is read per query as it moves every second
+    fn visibility_filter(&self, alias: &str) -> String {
+        if self.visibility_watermark.load(Ordering::Relaxed) {
+            format!(
+                " AND {}.block_time <= COALESCE((SELECT visible_before FROM k_meta WHERE id = 1), {})",
+                alias,
+                i64::MAX
+            )
+        } else {
+            String::new()
+        }
+    }
+
+    /// Collapse window of notifications in milliseconds, 0 when they are not collapsed (no
+    /// --notification-collapse-window, or a schema before v23 without the parent_id column)
+    fn notification_collapse_window_ms(&self) -> i64 {
+        if self.enriched_mentions.load(Ordering::Relaxed) {
+            self.notification_collapse_window_ms
+        } else {
+            0
+        }
+    }
+
+    fn notification_collapse_filter(&self, alias: &str) -> String {
+        notification_collapse_filter(self.notification_collapse_window_ms(), alias)
+    }
+
+    fn notification_collapse_count(&self, notification: &str, mentioned: &str) -> String {
+        notification_collapse_count(
+            self.notification_collapse_window_ms(),
+            notification,
+            mentioned,
+        )
+    }
+
+    /// LEFT JOIN LATERAL adding up_votes_count, down_votes_count, user_upvoted and user_downvoted
+    /// (alias v) of the content `post_id`: a lookup in k_vote_totals when the schema has it, else
+    /// aggregated from k_votes
+    fn vote_counts_join(&self, post_id: &str, requester: &str) -> String {
+        if self.vote_totals.load(Ordering::Relaxed) {
+            format!(
+                r#"LEFT JOIN LATERAL (
+                    SELECT vt.up_votes as up_votes_count,
+                           vt.down_votes as down_votes_count,
+                           uv.user_upvoted,
+                           uv.user_downvoted
+                    FROM (
+                        SELECT bool_or(vote = 'upvote') as user_upvoted,
+                               bool_or(vote = 'downvote') as user_downvoted
+                        FROM k_votes
+                        WHERE post_id = {post_id} AND sender_pubkey = {requester}
+                    ) uv
+                    LEFT JOIN k_vote_totals vt ON vt.post_id = {post_id}
+                ) v ON true"#
+            )
+        } else {
+            format!(
+                r#"LEFT JOIN LATERAL (
+                    SELECT COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
+                           COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
+                           bool_or(vote = 'upvote' AND sender_pubkey = {requester}) as user_upvoted,
+                           bool_or(vote = 'downvote' AND sender_pubkey = {requester}) as user_downvoted
+                    FROM k_votes
+                    WHERE post_id = {post_id}
+                ) v ON true"#
+            )
+        }
+    }
+
+    /// LEFT JOINs adding replies_count (alias r) and quotes_count (alias q) of the feed page
+    /// `page` (alias lp) from `contents`. The candidate rewrite counts per content with index
+    /// lookups instead of grouping the replies and quotes of the whole page (see shadow_query)
+    fn page_counts_joins(contents: &str, page: &str, candidate: bool) -> String {
+        if candidate {
+            format!(
+                r#"LEFT JOIN LATERAL (
+                        SELECT COUNT(*) as replies_count
+                        FROM {contents} r
+                        WHERE r.referenced_content_id = lp.transaction_id
+                          AND r.content_type = 'reply'
+                    ) r ON true
+                    LEFT JOIN LATERAL (
+                        SELECT COUNT(*) as quotes_count
+                        FROM {contents} qt
+                        WHERE qt.referenced_content_id = lp.transaction_id
+                          AND qt.content_type = 'quote'
+                    ) q ON true"#
+            )
+        } else {
+            format!(
+                r#"LEFT JOIN (
+                        SELECT referenced_content_id, COUNT(*) as replies_count
+                        FROM {contents} r
+                        WHERE r.content_type = 'reply'
+                          AND EXISTS (SELECT 1 FROM {page} lp WHERE lp.transaction_id = r.referenced_content_id)
+                        GROUP BY referenced_content_id
+                    ) r ON lp.transaction_id = r.referenced_content_id
+                    LEFT JOIN (
+                        SELECT referenced_content_id, COUNT(*) as quotes_count
+                        FROM {contents} qt
+                        WHERE qt.content_type = 'quote'
+                          AND EXISTS (SELECT 1 FROM {page} lp WHERE lp.transaction_id = qt.referenced_content_id)
+                        GROUP BY referenced_content_id
+                    ) q ON lp.transaction_id = q.referenced_content_id"#
+            )
+        }
+    }
+
+    /// Column render_spans (JSON text, NULL for content without spans) of the content `content_id`,
+    /// always NULL before schema v26
+    fn render_spans_column(&self, content_id: &str) -> String {
+        if self.render_spans.load(Ordering::Relaxed) {
+            format!(
+                "(SELECT rsp.spans::text FROM k_render_spans rsp WHERE rsp.content_id = {content_id}) as render_spans"
+            )
+        } else {
+            "NULL::text as render_spans".to_string()
+        }
+    }
+
+    /// Content relations a feed page is read from, in order: k_contents alone, unless content was
+    /// moved to the cold tier (schema v24+) and the page may reach it, then k_contents_tiered
+    fn feed_relations(&self, options: &QueryOptions) -> &'static [&'static str] {
+        let boundary = self.cold_tier_before.load(Ordering::Relaxed);
+        if boundary == 0 {
+            return &["k_contents"];
+        }
+        if options.sort_descending {
+            // Newest first: the cold tier only once a k_contents page runs past the boundary
+            return &["k_contents", "k_contents_tiered"];
+        }
+
+        let after_timestamp = options
+            .after
+            .as_deref()
+            .and_then(|cursor| Self::parse_compound_cursor(cursor).ok())
+            .map(|(timestamp, _)| timestamp as i64);
+        match after_timestamp {
+            // Oldest first from a cursor past the boundary: nothing left in the cold tier
+            Some(timestamp) if timestamp >= boundary => &["k_contents"],
+            _ => &["k_contents_tiered"],
+        }
+    }
+
+    /// Whether a newest-first page read from k_contents may be missing cold content: the page
+    /// is not full, or its extra row is already older than the cold tier boundary
+    fn page_reaches_cold_tier(&self, rows: &[PgRow], limit: i64) -> bool {
+        let boundary = self.cold_tier_before.load(Ordering::Relaxed);
+        boundary != 0
+            && rows
+                .get(limit as usize)
+                .is_none_or(|row| row.get::<i64, _>("block_time") < boundary)
+    }
+
+    /// Statement timeout of `method`: its budget capped by the connection default
+    fn query_budget_ms(&self, method: &str) -> Option<u64> {
+        let budget = QUERY_BUDGETS_MS
+            .iter()
+            .find(|(name, _)| *name == method)
+            .map(|(_, budget_ms)| *budget_ms);
+
+        match (budget, self.statement_timeout_ms) {
+            (Some(budget), Some(default)) => Some(budget.min(default)),
+            (budget, default) => budget.or(default),
         }
     }
 
+    /// Session-level application_name and k.request_id of a connection checked out by the
+    /// current request, so Postgres logs and pg_stat_activity can be joined with its log lines.
+    /// Reset outside of requests: pooled connections must not keep the ID of a previous one
+    async fn tag_session(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        let request_id = request_id::current();
+        let application_name = request_id
+            .as_deref()
+            .map(request_id::application_name)
+            .unwrap_or_else(|| "k-webserver".to_string());
+        NamedQuery::new(
+            "tag_session",
+            "SELECT set_config('application_name', $1, false), set_config('k.request_id', $2, false)",
+        )
+        .query()
+        .bind(application_name)
+        .bind(request_id.unwrap_or_default())
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Begin a transaction whose statements run under the budget of `method`, tagged (SET LOCAL)
+    /// with the request ID, or else the method name, so slow queries can be told apart in
+    /// pg_stat_activity
+    async fn begin_budgeted(&self, method: &str) -> DatabaseResult<Transaction<'static, Postgres>> {
+        self.begin_budgeted_on(&self.pool, method).await
+    }
+
+    /// Same as begin_budgeted, on `pool` (primary or read replica)
+    async fn begin_budgeted_on(
+        &self,
+        pool: &PgPool,
+        method: &str,
+    ) -> DatabaseResult<Transaction<'static, Postgres>> {
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+
+        // 0 disables the timeout
+        let timeout_ms = self.query_budget_ms(method).unwrap_or(0);
+        let request_id = request_id::current();
+        let application_name = match &request_id {
+            Some(id) => request_id::application_name(id),
+            None => format!("k-webserver:{}", method),
+        };
+        NamedQuery::new(
+            method,
+            "SELECT set_config('statement_timeout', $1, true), set_config('application_name', $2, true), set_config('k.request_id', $3, true)",
+        )
+        .query()
+        .bind(timeout_ms.to_string())
+        .bind(application_name)
+        .bind(request_id.unwrap_or_default())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(tx)
+    }
+
     /// Get network type from k_vars table (internal implementation)
     async fn get_network_from_db(&self) -> Result<String, sqlx::Error> {
-        let result = sqlx::query("SELECT value FROM k_vars WHERE key = 'network'")
-            .fetch_optional(&self.pool)
-            .await?;
+        let result = NamedQuery::new(
+            "get_network",
+            "SELECT value FROM k_vars WHERE key = 'network'",
+        )
+        .query()
+        .fetch_optional(&self.pool)
+        .await?;
 
         Ok(result
             .map(|row| row.get("value"))
@@ -106,6 +623,36 @@ impl PostgresDbManager {
         hex::encode(bytes)
     }
 
+    /// Reply of get_nested_replies and get_thread_replies, which select the same columns
+    fn nested_reply_record(row: &PgRow) -> KReplyRecord {
+        let transaction_id: Vec<u8> = row.get("transaction_id");
+        let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+        let sender_signature: Vec<u8> = row.get("sender_signature");
+        let referenced_content_id: Vec<u8> = row.get("referenced_content_id");
+
+        KReplyRecord {
+            id: row.get::<i64, _>("id"),
+            transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+            block_time: row.get::<i64, _>("block_time") as u64,
+            sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+            sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+            post_id: Self::encode_bytes_to_hex(&referenced_content_id),
+            base64_encoded_message: row.get("base64_encoded_message"),
+            mentioned_pubkeys: row.get("mentioned_pubkeys"),
+            mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+            render_spans: row.get("render_spans"),
+            content_type: None,
+            replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+            quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+            up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+            down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+            is_upvoted: Some(row.get("is_upvoted")),
+            is_downvoted: Some(row.get("is_downvoted")),
+            user_nickname: Some(row.get("user_nickname")),
+            user_profile_image: row.get("user_profile_image"),
+        }
+    }
+
     fn parse_compound_cursor(cursor: &str) -> DatabaseResult<(u64, i64)> {
         if cursor.contains('_') {
             let parts: Vec<&str> = cursor.split('_').collect();
@@ -126,298 +673,684 @@ impl PostgresDbManager {
         Ok((timestamp, i64::MAX)) // Use MAX to include all records with same timestamp
     }
 
+    /// Keyset conditions of the page cursors of `options` (see QueryParams::keyset_conditions),
+    /// malformed cursors are ignored
+    fn cursor_conditions(
+        params: &mut QueryParams,
+        sort_column: &str,
+        id_column: &str,
+        options: &QueryOptions,
+    ) -> String {
+        let before = options
+            .before
+            .as_deref()
+            .and_then(|cursor| Self::parse_compound_cursor(cursor).ok());
+        let after = options
+            .after
+            .as_deref()
+            .and_then(|cursor| Self::parse_compound_cursor(cursor).ok());
+        params.keyset_conditions(sort_column, id_column, before, after)
+    }
+
     fn create_compound_cursor(timestamp: u64, id: i64) -> String {
         format!("{}_{}", timestamp, id)
     }
-}
 
-trait HasCompoundCursor {
-    fn get_timestamp(&self) -> u64;
-    fn get_id(&self) -> i64;
-}
+    /// Post or reply of a get_content_by_id row (content type left unset)
+    fn content_record_from_row(row: &PgRow) -> DatabaseResult<ContentRecord> {
+        let content_type: &str = row.get("content_type");
 
-impl HasCompoundCursor for KPostRecord {
-    fn get_timestamp(&self) -> u64 {
-        self.block_time
-    }
+        let content_record = match content_type {
+            "post" | "quote" => {
+                let mentioned_pubkeys_bytes: Vec<Vec<u8>> = row.get("mentioned_pubkeys");
+                let mentioned_pubkeys: Vec<String> = mentioned_pubkeys_bytes
+                    .into_iter()
+                    .map(hex::encode)
+                    .collect();
 
-    fn get_id(&self) -> i64 {
-        self.id
-    }
-}
+                let post_record = KPostRecord {
+                    id: row.get("id"),
+                    transaction_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
+                    block_time: row.get::<i64, _>("block_time") as u64,
+                    sender_pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
+                    sender_signature: hex::encode(row.get::<Vec<u8>, _>("sender_signature")),
+                    base64_encoded_message: row.get("base64_encoded_message"),
+                    mentioned_pubkeys,
+                    mentions_count: None,
+                    render_spans: row.get("render_spans"),
+                    content_type: None,
+                    replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                    quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                    up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                    down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                    is_upvoted: Some(row.get("is_upvoted")),
+                    is_downvoted: Some(row.get("is_downvoted")),
+                    user_nickname: row.get("user_nickname"),
+                    user_profile_image: row.get("user_profile_image"),
+                    referenced_content_id: row.get("ref_content_id"),
+                    referenced_message: row.get("referenced_message"),
+                    referenced_sender_pubkey: row.get("referenced_sender_pubkey"),
+                    referenced_nickname: row.get("referenced_nickname"),
+                    referenced_profile_image: row.get("referenced_profile_image"),
+                };
 
-impl HasCompoundCursor for KReplyRecord {
-    fn get_timestamp(&self) -> u64 {
-        self.block_time
-    }
+                ContentRecord::Post(post_record)
+            }
+            "reply" => {
+                let mentioned_pubkeys_bytes: Vec<Vec<u8>> = row.get("mentioned_pubkeys");
+                let mentioned_pubkeys: Vec<String> = mentioned_pubkeys_bytes
+                    .into_iter()
+                    .map(hex::encode)
+                    .collect();
 
-    fn get_id(&self) -> i64 {
-        self.id
-    }
-}
+                let referenced_content_id: Option<Vec<u8>> = row.get("referenced_content_id");
+                let post_id = match referenced_content_id {
+                    Some(bytes) => hex::encode(bytes),
+                    None => {
+                        return Err(DatabaseError::QueryError(
+                            "Missing referenced_content_id for reply".to_string(),
+                        ));
+                    }
+                };
 
-impl HasCompoundCursor for KBroadcastRecord {
-    fn get_timestamp(&self) -> u64 {
-        self.block_time
-    }
+                let reply_record = KReplyRecord {
+                    id: row.get("id"),
+                    transaction_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
+                    block_time: row.get::<i64, _>("block_time") as u64,
+                    sender_pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
+                    sender_signature: hex::encode(row.get::<Vec<u8>, _>("sender_signature")),
+                    post_id,
+                    base64_encoded_message: row.get("base64_encoded_message"),
+                    mentioned_pubkeys,
+                    mentions_count: None,
+                    render_spans: row.get("render_spans"),
+                    content_type: None,
+                    replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                    quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                    up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                    down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                    is_upvoted: Some(row.get("is_upvoted")),
+                    is_downvoted: Some(row.get("is_downvoted")),
+                    user_nickname: row.get("user_nickname"),
+                    user_profile_image: row.get("user_profile_image"),
+                };
 
-    fn get_id(&self) -> i64 {
-        self.id
-    }
-}
+                ContentRecord::Reply(reply_record)
+            }
+            _ => {
+                return Err(DatabaseError::QueryError(format!(
+                    "Unknown content type: {}",
+                    content_type
+                )));
+            }
+        };
 
-impl HasCompoundCursor for KVoteRecord {
-    fn get_timestamp(&self) -> u64 {
-        self.block_time
+        Ok(content_record)
     }
 
-    fn get_id(&self) -> i64 {
-        self.id
+    fn group_record_from_row(row: &PgRow) -> KGroupRecord {
+        let transaction_id: Vec<u8> = row.get("transaction_id");
+        let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+        let sender_signature: Vec<u8> = row.get("sender_signature");
+
+        KGroupRecord {
+            id: row.get::<i64, _>("id"),
+            transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+            block_time: row.get::<i64, _>("block_time") as u64,
+            sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+            sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+            base64_encoded_name: row.get("base64_encoded_name"),
+            base64_encoded_description: row.get("base64_encoded_description"),
+            membership_policy: row.get("membership_policy"),
+            members_count: row.get::<i64, _>("members_count") as u64,
+            is_member: row.get("is_member"),
+        }
     }
-}
 
-impl HasCompoundCursor for ContentRecord {
-    fn get_timestamp(&self) -> u64 {
-        match self {
-            ContentRecord::Post(post) => post.block_time,
-            ContentRecord::Reply(reply) => reply.block_time,
-            ContentRecord::Vote(vote) => vote.block_time,
+    fn erasure_request_from_row(row: &PgRow) -> KErasureRequestRecord {
+        let subject_pubkey: Vec<u8> = row.get("subject_pubkey");
+
+        KErasureRequestRecord {
+            id: row.get::<i64, _>("id"),
+            subject_pubkey: Self::encode_bytes_to_hex(&subject_pubkey),
+            reason: row.get("reason"),
+            status: row.get("status"),
+            requested_at: row.get::<i64, _>("requested_at") as u64,
+            completed_at: row
+                .get::<Option<i64>, _>("completed_at")
+                .map(|time| time as u64),
+            removed_rows: row
+                .get::<Option<i64>, _>("removed_rows")
+                .map(|count| count as u64),
+            tombstoned_transactions: row
+                .get::<Option<i64>, _>("tombstoned_transactions")
+                .map(|count| count as u64),
         }
     }
 
-    fn get_id(&self) -> i64 {
-        match self {
-            ContentRecord::Post(post) => post.id,
-            ContentRecord::Reply(reply) => reply.id,
-            ContentRecord::Vote(vote) => vote.id,
+    fn impersonation_flag_from_row(row: &PgRow) -> KImpersonationFlagRecord {
+        let suspect_pubkey: Vec<u8> = row.get("suspect_pubkey");
+        let target_pubkey: Vec<u8> = row.get("target_pubkey");
+        let transaction_id: Vec<u8> = row.get("transaction_id");
+
+        KImpersonationFlagRecord {
+            id: row.get::<i64, _>("id"),
+            suspect_pubkey: Self::encode_bytes_to_hex(&suspect_pubkey),
+            base64_encoded_suspect_nickname: row.get("base64_encoded_suspect_nickname"),
+            target_pubkey: Self::encode_bytes_to_hex(&target_pubkey),
+            base64_encoded_target_nickname: row.get("base64_encoded_target_nickname"),
+            target_followers: row.get::<i64, _>("target_followers") as u64,
+            similarity: row.get("similarity"),
+            transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+            flagged_at: row.get::<i64, _>("flagged_at") as u64,
+            status: row.get("status"),
+            reviewed_at: row
+                .get::<Option<i64>, _>("reviewed_at")
+                .map(|time| time as u64),
         }
     }
-}
 
-#[async_trait]
-#[allow(unused_variables)]
-impl DatabaseInterface for PostgresDbManager {
-    // Broadcast operations
+    fn featured_from_row(row: &PgRow) -> KFeaturedRecord {
+        let content_id: Vec<u8> = row.get("content_id");
 
-    async fn get_all_users(
+        KFeaturedRecord {
+            content_id: Self::encode_bytes_to_hex(&content_id),
+            position: row.get("position"),
+            note: row.get("note"),
+            featured_at: row.get::<i64, _>("featured_at") as u64,
+            available: row.get("available"),
+        }
+    }
+
+    /// Contents of `content_types` (SQL list) sent by the users followed by the requester,
+    /// blocked users excluded, for get_content_following and get_posts_following
+    async fn get_followed_contents(
         &self,
+        method: &str,
+        content_types: &str,
         requester_pubkey: &str,
         options: QueryOptions,
-    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>> {
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
-        let offset_limit = limit + 1;
-
-        let mut query = String::from(
-            r#"
-            SELECT
-                b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
-                b.base64_encoded_nickname, b.base64_encoded_profile_image, b.base64_encoded_message,
-                CASE
-                    WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
-                    ELSE false
-                END as is_blocked,
-                CASE
-                    WHEN kf.followed_user_pubkey IS NOT NULL THEN true
-                    ELSE false
-                END as is_followed
-            FROM k_broadcasts b
-            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = b.sender_pubkey
-            LEFT JOIN k_follows kf ON kf.sender_pubkey = $1 AND kf.followed_user_pubkey = b.sender_pubkey
-            WHERE 1=1
-            "#,
-        );
-
-        let mut bind_count = 1; // Start with 1 since we already have requester_pubkey
+        let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (b.block_time < ${} OR (b.block_time = ${} AND b.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (b.block_time > ${} OR (b.block_time = ${} AND b.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        // Add cursor logic
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
 
-        if options.sort_descending {
-            query.push_str(" ORDER BY b.block_time DESC, b.id DESC");
+        let order_clause = if options.sort_descending {
+            " ORDER BY c.block_time DESC, c.id DESC"
         } else {
-            query.push_str(" ORDER BY b.block_time ASC, b.id ASC");
-        }
+            " ORDER BY c.block_time ASC, c.id ASC"
+        };
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
+        let final_order_clause = if options.sort_descending {
+            " ORDER BY ps.block_time DESC, ps.id DESC"
+        } else {
+            " ORDER BY ps.block_time ASC, ps.id ASC"
+        };
 
-        let mut query_builder = sqlx::query(&query).bind(&requester_pubkey_bytes);
+        let vote_counts = self.vote_counts_join("fc.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            method,
+            &format!(
+                r#"
+            WITH followed_content AS (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message, c.content_type,
+                       c.referenced_content_id
+                FROM k_contents c
+                INNER JOIN k_follows kf ON kf.followed_user_pubkey = c.sender_pubkey
+                {blocks_join}
+                WHERE kf.sender_pubkey = $1
+                  AND c.content_type IN ({content_types})
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            ), content_stats AS (
+                SELECT fc.id, fc.transaction_id, fc.block_time, fc.sender_pubkey,
+                       fc.sender_signature, fc.base64_encoded_message, fc.content_type,
+                       fc.referenced_content_id,
+                       COALESCE(r.replies_count, 0) as replies_count,
+                       COALESCE(q.quotes_count, 0) as quotes_count,
+                       COALESCE(v.up_votes_count, 0) as up_votes_count,
+                       COALESCE(v.down_votes_count, 0) as down_votes_count,
+                       COALESCE(v.user_upvoted, false) as is_upvoted,
+                       COALESCE(v.user_downvoted, false) as is_downvoted
+                FROM followed_content fc
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as replies_count
+                    FROM k_contents r
+                    WHERE r.content_type = 'reply'
+                      AND EXISTS (SELECT 1 FROM followed_content fc WHERE fc.transaction_id = r.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) r ON fc.transaction_id = r.referenced_content_id
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as quotes_count
+                    FROM k_contents qt
+                    WHERE qt.content_type = 'quote'
+                      AND EXISTS (SELECT 1 FROM followed_content fc WHERE fc.transaction_id = qt.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) q ON fc.transaction_id = q.referenced_content_id
+                {vote_counts}
+            )
+            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
+                   ps.sender_signature, ps.base64_encoded_message, ps.content_type,
+                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type) as mentions_count,
+                   {render_spans_column},
+                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
+                   ps.is_upvoted, ps.is_downvoted,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image,
+                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                   ref_c.base64_encoded_message as referenced_message,
+                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                   ref_b.base64_encoded_profile_image as referenced_profile_image
+            FROM content_stats ps
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = ps.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM k_contents
+                WHERE transaction_id = ps.referenced_content_id
+                  AND ps.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            WHERE 1=1
+            {final_order_clause}
+            "#,
+                content_types = content_types,
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
+        );
 
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
+        // Build query with parameter binding
+        let query_builder = params.bind(query.query());
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
+        let mut tx = self.begin_budgeted_on(pool, method).await?;
+        let rows = query_builder.fetch_all(&mut *tx).await.map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch followed content: {}", e))
+        })?;
 
-        query_builder = query_builder.bind(offset_limit);
+        // Process results and build pagination
+        let mut items = Vec::new();
+        let mut has_more = false;
 
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
-            DatabaseError::QueryError(format!(
-                "Failed to fetch all broadcasts with block status: {}",
-                e
-            ))
-        })?;
+        for (index, row) in rows.iter().enumerate() {
+            if index >= limit as usize {
+                has_more = true;
+                break;
+            }
 
-        let mut broadcasts_with_block_status = Vec::new();
-        for row in &rows {
             let transaction_id: Vec<u8> = row.get("transaction_id");
             let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
             let sender_signature: Vec<u8> = row.get("sender_signature");
-            let is_blocked: bool = row.get("is_blocked");
-            let is_followed: bool = row.get("is_followed");
+            let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
 
-            let broadcast_record = KBroadcastRecord {
+            let referenced_content_id: Option<String> = row.try_get("referenced_content_id").ok();
+            let referenced_message: Option<String> = row.try_get("referenced_message").ok();
+            let referenced_sender_pubkey: Option<String> =
+                row.try_get("referenced_sender_pubkey").ok();
+            let referenced_nickname: Option<String> = row.try_get("referenced_nickname").ok();
+            let referenced_profile_image: Option<String> =
+                row.try_get("referenced_profile_image").ok();
+
+            let record = KPostRecord {
                 id: row.get::<i64, _>("id"),
                 transaction_id: Self::encode_bytes_to_hex(&transaction_id),
                 block_time: row.get::<i64, _>("block_time") as u64,
                 sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
                 sender_signature: Self::encode_bytes_to_hex(&sender_signature),
-                base64_encoded_nickname: row.get("base64_encoded_nickname"),
-                base64_encoded_profile_image: row.get("base64_encoded_profile_image"),
                 base64_encoded_message: row.get("base64_encoded_message"),
+                mentioned_pubkeys: mentioned_pubkeys_raw,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
+                content_type: row.try_get("content_type").ok(),
+                replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                is_upvoted: Some(row.get("is_upvoted")),
+                is_downvoted: Some(row.get("is_downvoted")),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+                referenced_content_id,
+                referenced_message,
+                referenced_sender_pubkey,
+                referenced_nickname,
+                referenced_profile_image,
             };
 
-            broadcasts_with_block_status.push((broadcast_record, is_blocked, is_followed));
-        }
-
-        let has_more = broadcasts_with_block_status.len() > limit as usize;
-        if has_more {
-            broadcasts_with_block_status.pop();
+            items.push(record);
         }
 
-        // Extract just the broadcast records for pagination metadata calculation
-        let broadcast_records: Vec<KBroadcastRecord> = broadcasts_with_block_status
-            .iter()
-            .map(|(record, _, _)| record.clone())
-            .collect();
-        let pagination =
-            self.create_compound_pagination_metadata(&broadcast_records, limit as u32, has_more);
-
-        Ok(PaginatedResult {
-            items: broadcasts_with_block_status,
-            pagination,
-        })
-    }
-
-    async fn get_most_active_users(
-        &self,
-        requester_pubkey: &str,
-        options: QueryOptions,
-        from_time_millis: u64,
-        to_time_millis: u64,
-    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool, i64)>> {
-        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
-        let limit = options.limit.unwrap_or(20) as i64;
-        let offset_limit = limit + 1;
+        // Build pagination metadata
+        let pagination = if items.is_empty() {
+            PaginationMetadata {
+                has_more: false,
+                next_cursor: None,
+                prev_cursor: None,
+            }
+        } else {
+            let first_item = items.first().unwrap();
+            let last_item = items.last().unwrap();
 
-        let mut query = String::from(
-            r#"
-            WITH user_content_counts AS (
-                SELECT sender_pubkey, COUNT(*) as content_count
-                FROM k_contents
-                WHERE block_time >= $2 AND block_time <= $3
-                GROUP BY sender_pubkey
-            )
-            SELECT
-                b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
-                b.base64_encoded_nickname, b.base64_encoded_profile_image, b.base64_encoded_message,
-                ucc.content_count,
-                CASE
-                    WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
-                    ELSE false
-                END as is_blocked,
-                CASE
-                    WHEN kf.followed_user_pubkey IS NOT NULL THEN true
-                    ELSE false
-                END as is_followed
-            FROM k_broadcasts b
-            INNER JOIN user_content_counts ucc ON ucc.sender_pubkey = b.sender_pubkey
-            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = b.sender_pubkey
-            LEFT JOIN k_follows kf ON kf.sender_pubkey = $1 AND kf.followed_user_pubkey = b.sender_pubkey
-            WHERE 1=1
-            "#,
-        );
+            let next_cursor = if has_more {
+                Some(Self::create_compound_cursor(
+                    last_item.block_time,
+                    last_item.id,
+                ))
+            } else {
+                None
+            };
 
-        // $1 = requester_pubkey, $2 = from_time_millis, $3 = to_time_millis
-        let mut bind_count = 3;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_count, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (ucc.content_count < ${} OR (ucc.content_count = ${} AND b.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+            let prev_cursor = Some(Self::create_compound_cursor(
+                first_item.block_time,
+                first_item.id,
+            ));
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_count, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (ucc.content_count > ${} OR (ucc.content_count = ${} AND b.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
+            PaginationMetadata {
+                has_more,
+                next_cursor,
+                prev_cursor,
             }
-        }
+        };
 
-        query.push_str(" ORDER BY ucc.content_count DESC, b.id DESC");
+        Ok(PaginatedResult { items, pagination })
+    }
+}
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
+trait HasCompoundCursor {
+    fn get_timestamp(&self) -> u64;
+    fn get_id(&self) -> i64;
+}
 
-        let mut query_builder = sqlx::query(&query)
-            .bind(&requester_pubkey_bytes)
-            .bind(from_time_millis as i64)
-            .bind(to_time_millis as i64);
+impl HasCompoundCursor for KPostRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
 
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_count, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_count as i64).bind(before_id);
-            }
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for KReplyRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for KBroadcastRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for KVoteRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for KGroupRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for KGroupPostRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.block_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+// Events are paginated by start time instead of block time
+impl HasCompoundCursor for KEventRecord {
+    fn get_timestamp(&self) -> u64 {
+        self.start_time
+    }
+
+    fn get_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl HasCompoundCursor for ContentRecord {
+    fn get_timestamp(&self) -> u64 {
+        match self {
+            ContentRecord::Post(post) => post.block_time,
+            ContentRecord::Reply(reply) => reply.block_time,
+            ContentRecord::Vote(vote) => vote.block_time,
         }
+    }
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_count, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_count as i64).bind(after_id);
-            }
+    fn get_id(&self) -> i64 {
+        match self {
+            ContentRecord::Post(post) => post.id,
+            ContentRecord::Reply(reply) => reply.id,
+            ContentRecord::Vote(vote) => vote.id,
+        }
+    }
+}
+
+#[async_trait]
+#[allow(unused_variables)]
+impl DatabaseInterface for PostgresDbManager {
+    // Broadcast operations
+
+    async fn get_all_users(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1;
+
+        let mut query = NamedQuery::new(
+            "get_all_users",
+            r#"
+            SELECT
+                b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
+                b.base64_encoded_nickname, b.base64_encoded_profile_image, b.base64_encoded_message,
+                CASE
+                    WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
+                    ELSE false
+                END as is_blocked,
+                CASE
+                    WHEN kf.followed_user_pubkey IS NOT NULL THEN true
+                    ELSE false
+                END as is_followed
+            FROM k_broadcasts b
+            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = b.sender_pubkey
+            LEFT JOIN k_follows kf ON kf.sender_pubkey = $1 AND kf.followed_user_pubkey = b.sender_pubkey
+            WHERE 1=1
+            "#,
+        );
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "b.block_time",
+            "b.id",
+            &options,
+        ));
+
+        if options.sort_descending {
+            query.push_str(" ORDER BY b.block_time DESC, b.id DESC");
+        } else {
+            query.push_str(" ORDER BY b.block_time ASC, b.id ASC");
+        }
+
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
+
+        let query_builder = params.bind(query.query());
+
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
+            DatabaseError::QueryError(format!(
+                "Failed to fetch all broadcasts with block status: {}",
+                e
+            ))
+        })?;
+
+        let mut broadcasts_with_block_status = Vec::new();
+        for row in &rows {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+            let is_blocked: bool = row.get("is_blocked");
+            let is_followed: bool = row.get("is_followed");
+
+            let broadcast_record = KBroadcastRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_nickname: row.get("base64_encoded_nickname"),
+                base64_encoded_profile_image: row.get("base64_encoded_profile_image"),
+                base64_encoded_message: row.get("base64_encoded_message"),
+            };
+
+            broadcasts_with_block_status.push((broadcast_record, is_blocked, is_followed));
+        }
+
+        let has_more = broadcasts_with_block_status.len() > limit as usize;
+        if has_more {
+            broadcasts_with_block_status.pop();
         }
 
-        query_builder = query_builder.bind(offset_limit);
+        // Extract just the broadcast records for pagination metadata calculation
+        let broadcast_records: Vec<KBroadcastRecord> = broadcasts_with_block_status
+            .iter()
+            .map(|(record, _, _)| record.clone())
+            .collect();
+        let pagination =
+            self.create_compound_pagination_metadata(&broadcast_records, limit as u32, has_more);
+
+        Ok(PaginatedResult {
+            items: broadcasts_with_block_status,
+            pagination,
+        })
+    }
+
+    async fn get_most_active_users(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+        from_time_millis: u64,
+        to_time_millis: u64,
+    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool, i64)>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1;
+
+        let mut query = NamedQuery::new(
+            "get_most_active_users",
+            r#"
+            WITH user_content_counts AS (
+                SELECT sender_pubkey, COUNT(*) as content_count
+                FROM k_contents
+                WHERE block_time >= $2 AND block_time <= $3
+                GROUP BY sender_pubkey
+            )
+            SELECT
+                b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
+                b.base64_encoded_nickname, b.base64_encoded_profile_image, b.base64_encoded_message,
+                ucc.content_count,
+                CASE
+                    WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
+                    ELSE false
+                END as is_blocked,
+                CASE
+                    WHEN kf.followed_user_pubkey IS NOT NULL THEN true
+                    ELSE false
+                END as is_followed
+            FROM k_broadcasts b
+            INNER JOIN user_content_counts ucc ON ucc.sender_pubkey = b.sender_pubkey
+            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = b.sender_pubkey
+            LEFT JOIN k_follows kf ON kf.sender_pubkey = $1 AND kf.followed_user_pubkey = b.sender_pubkey
+            WHERE 1=1
+            "#,
+        );
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+        params.push(from_time_millis as i64); // $2
+        params.push(to_time_millis as i64); // $3
+
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "ucc.content_count",
+            "b.id",
+            &options,
+        ));
+
+        query.push_str(" ORDER BY ucc.content_count DESC, b.id DESC");
+
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
+        let query_builder = params.bind(query.query());
+
+        let mut tx = self
+            .begin_budgeted_on(pool, "get_most_active_users")
+            .await?;
+        let rows = query_builder.fetch_all(&mut *tx).await.map_err(|e| {
             DatabaseError::QueryError(format!("Failed to fetch most active users: {}", e))
         })?;
 
@@ -488,11 +1421,13 @@ impl DatabaseInterface for PostgresDbManager {
         searched_user_pubkey: Option<String>,
         searched_user_nickname: Option<String>,
     ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1;
 
-        let mut query = String::from(
+        let mut query = NamedQuery::new(
+            "search_users",
             r#"
             SELECT
                 b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
@@ -512,51 +1447,32 @@ impl DatabaseInterface for PostgresDbManager {
             "#,
         );
 
-        let mut bind_count = 1; // Start with 1 since we already have requester_pubkey
-        let mut search_user_pubkey_bytes: Option<Vec<u8>> = None;
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
         // Add search filter for user pubkey (matches both 02 and 03 prefix variants)
         if let Some(ref pubkey) = searched_user_pubkey {
-            search_user_pubkey_bytes = Some(Self::decode_hex_to_bytes(pubkey)?);
-            bind_count += 1;
+            let pubkey_bytes = Self::decode_hex_to_bytes(pubkey)?;
+            let hex_pattern = params.push(format!("%{}", hex::encode(pubkey_bytes)));
             query.push_str(&format!(
-                " AND encode(b.sender_pubkey, 'hex') LIKE ${}",
-                bind_count
+                " AND encode(b.sender_pubkey, 'hex') LIKE {hex_pattern}"
             ));
         }
 
         // Add search filter for nickname (decode Base64 and search plain text)
-        if let Some(_) = searched_user_nickname.as_ref() {
-            bind_count += 1;
+        if let Some(ref nickname) = searched_user_nickname {
+            let search_pattern = params.push(format!("%{}%", nickname));
             query.push_str(&format!(
-                " AND convert_from(decode(b.base64_encoded_nickname, 'base64'), 'UTF8') ILIKE ${}",
-                bind_count
+                " AND convert_from(decode(b.base64_encoded_nickname, 'base64'), 'UTF8') ILIKE {search_pattern}"
             ));
         }
 
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (b.block_time < ${} OR (b.block_time = ${} AND b.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (b.block_time > ${} OR (b.block_time = ${} AND b.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "b.block_time",
+            "b.id",
+            &options,
+        ));
 
         if options.sort_descending {
             query.push_str(" ORDER BY b.block_time DESC, b.id DESC");
@@ -564,39 +1480,14 @@ impl DatabaseInterface for PostgresDbManager {
             query.push_str(" ORDER BY b.block_time ASC, b.id ASC");
         }
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
-
-        let mut query_builder = sqlx::query(&query).bind(&requester_pubkey_bytes);
-
-        // Bind search user pubkey pattern if provided (matches both 02 and 03 prefix)
-        if let Some(ref pubkey_bytes) = search_user_pubkey_bytes {
-            let hex_pattern = format!("%{}", hex::encode(pubkey_bytes));
-            query_builder = query_builder.bind(hex_pattern);
-        }
-
-        // Bind nickname search pattern if provided
-        if let Some(ref nickname) = searched_user_nickname {
-            let search_pattern = format!("%{}%", nickname);
-            query_builder = query_builder.bind(search_pattern);
-        }
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        query_builder = query_builder.bind(offset_limit);
+        let query_builder = params.bind(query.query());
 
+        let mut tx = self.begin_budgeted_on(pool, "search_users").await?;
         let rows = query_builder
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *tx)
             .await
             .map_err(|e| DatabaseError::QueryError(format!("Failed to search users: {}", e)))?;
 
@@ -641,21 +1532,108 @@ impl DatabaseInterface for PostgresDbManager {
         })
     }
 
-    async fn get_user_details(
+    async fn search_broadcasts_by_nickname(
         &self,
-        user_public_key: &str,
         requester_pubkey: &str,
-    ) -> DatabaseResult<Option<(KBroadcastRecord, bool, bool, i64, i64, i64)>> {
-        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+        query: &str,
+        limit: u32,
+    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>> {
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
-
-        // Single query to get broadcast data + block/follow status + follower counts
-        let query = r#"
+        // LIKE wildcards typed by the user are matched literally
+        let escaped_query = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        let sql = NamedQuery::new(
+            "search_broadcasts_by_nickname",
+            r#"
+            WITH decoded AS (
+                SELECT b.*, convert_from(decode(b.base64_encoded_nickname, 'base64'), 'UTF8') as nickname
+                FROM k_broadcasts b
+                WHERE b.base64_encoded_nickname <> ''
+            )
             SELECT
-                b.id,
-                b.transaction_id,
-                b.block_time,
-                b.sender_pubkey,
+                b.id, b.transaction_id, b.block_time, b.sender_pubkey, b.sender_signature,
+                b.base64_encoded_nickname, b.base64_encoded_profile_image, b.base64_encoded_message,
+                kb.blocked_user_pubkey IS NOT NULL as is_blocked,
+                kf.followed_user_pubkey IS NOT NULL as is_followed
+            FROM decoded b
+            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = b.sender_pubkey
+            LEFT JOIN k_follows kf ON kf.sender_pubkey = $1 AND kf.followed_user_pubkey = b.sender_pubkey
+            WHERE b.nickname ILIKE '%' || $2 || '%'
+            ORDER BY b.nickname ILIKE $2 || '%' DESC, length(b.nickname), b.block_time DESC, b.id DESC
+            LIMIT $3
+            "#,
+        );
+
+        let mut tx = self.begin_budgeted("search_broadcasts_by_nickname").await?;
+        let rows = sql
+            .query()
+            .bind(&requester_pubkey_bytes)
+            .bind(&escaped_query)
+            .bind(limit as i64 + 1)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to search users by nickname: {}", e))
+            })?;
+
+        let mut items = Vec::new();
+        for row in &rows {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+
+            let broadcast_record = KBroadcastRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_nickname: row.get("base64_encoded_nickname"),
+                base64_encoded_profile_image: row.get("base64_encoded_profile_image"),
+                base64_encoded_message: row.get("base64_encoded_message"),
+            };
+
+            items.push((
+                broadcast_record,
+                row.get("is_blocked"),
+                row.get("is_followed"),
+            ));
+        }
+
+        // Ranked results: has_more only tells that a longer query would narrow them down
+        let has_more = items.len() > limit as usize;
+        items.truncate(limit as usize);
+
+        Ok(PaginatedResult {
+            items,
+            pagination: PaginationMetadata {
+                has_more,
+                next_cursor: None,
+                prev_cursor: None,
+            },
+        })
+    }
+
+    async fn get_user_details(
+        &self,
+        user_public_key: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<(KBroadcastRecord, bool, bool, i64, i64, i64)>> {
+        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // Single query to get broadcast data + block/follow status + follower counts
+        let query = NamedQuery::new(
+            "get_user_details",
+            r#"
+            SELECT
+                b.id,
+                b.transaction_id,
+                b.block_time,
+                b.sender_pubkey,
                 b.sender_signature,
                 b.base64_encoded_nickname,
                 b.base64_encoded_profile_image,
@@ -674,9 +1652,11 @@ impl DatabaseInterface for PostgresDbManager {
             FROM k_broadcasts b
             WHERE b.sender_pubkey = $1
             LIMIT 1
-        "#;
+        "#,
+        );
 
-        let row_opt = sqlx::query(query)
+        let row_opt = query
+            .query()
             .bind(&user_pubkey_bytes)
             .bind(&requester_pubkey_bytes)
             .fetch_optional(&self.pool)
@@ -717,7 +1697,9 @@ impl DatabaseInterface for PostgresDbManager {
             )))
         } else {
             // No broadcast data found, need separate query for block/follow status and counts
-            let status_query = r#"
+            let status_query = NamedQuery::new(
+                "get_user_details",
+                r#"
                 SELECT
                     EXISTS (
                         SELECT 1 FROM k_blocks kb
@@ -730,9 +1712,11 @@ impl DatabaseInterface for PostgresDbManager {
                     (SELECT COUNT(*) FROM k_follows WHERE followed_user_pubkey = $1) as followers_count,
                     (SELECT COUNT(*) FROM k_follows WHERE sender_pubkey = $1) as following_count,
                     (SELECT COUNT(*) FROM k_blocks WHERE sender_pubkey = $1) as blocked_count
-            "#;
+            "#,
+            );
 
-            let status_row = sqlx::query(status_query)
+            let status_row = status_query
+                .query()
                 .bind(&user_pubkey_bytes)
                 .bind(&requester_pubkey_bytes)
                 .fetch_one(&self.pool)
@@ -770,16 +1754,178 @@ impl DatabaseInterface for PostgresDbManager {
         }
     }
 
+    async fn get_user_stats(&self, user_public_key: &str) -> DatabaseResult<KUserStatsRecord> {
+        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+
+        // Contents of the user may be anywhere in time, cold tier included
+        let contents = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            "k_contents"
+        } else {
+            "k_contents_tiered"
+        };
+        let votes_received = if self.vote_totals.load(Ordering::Relaxed) {
+            r#"SELECT COALESCE(SUM(vt.up_votes), 0)::BIGINT as up_votes_count,
+                      COALESCE(SUM(vt.down_votes), 0)::BIGINT as down_votes_count
+               FROM user_contents uc
+               JOIN k_vote_totals vt ON vt.post_id = uc.transaction_id"#
+        } else {
+            r#"SELECT COUNT(*) FILTER (WHERE v.vote = 'upvote') as up_votes_count,
+                      COUNT(*) FILTER (WHERE v.vote = 'downvote') as down_votes_count
+               FROM user_contents uc
+               JOIN k_votes v ON v.post_id = uc.transaction_id"#
+        };
+        let query = NamedQuery::new(
+            "get_user_stats",
+            &format!(
+                r#"
+            WITH user_contents AS (
+                SELECT transaction_id, content_type, block_time
+                FROM {contents}
+                WHERE sender_pubkey = $1
+                  AND content_type IN ('post', 'reply', 'quote')
+            ),
+            content_totals AS (
+                SELECT COUNT(*) FILTER (WHERE content_type = 'post') as posts_count,
+                       COUNT(*) FILTER (WHERE content_type = 'reply') as replies_count,
+                       COUNT(*) FILTER (WHERE content_type = 'quote') as quotes_count,
+                       MIN(block_time) as first_content,
+                       MAX(block_time) as last_content
+                FROM user_contents
+            ),
+            votes_given AS (
+                SELECT COUNT(*) as votes_given_count,
+                       MIN(block_time) as first_vote,
+                       MAX(block_time) as last_vote
+                FROM k_votes
+                WHERE sender_pubkey = $1
+            ),
+            votes_received AS (
+                {votes_received}
+            ),
+            broadcasts AS (
+                SELECT MIN(block_time) as first_broadcast, MAX(block_time) as last_broadcast
+                FROM k_broadcasts
+                WHERE sender_pubkey = $1
+            )
+            SELECT ct.posts_count, ct.replies_count, ct.quotes_count,
+                   vg.votes_given_count,
+                   vr.up_votes_count, vr.down_votes_count,
+                   LEAST(ct.first_content, vg.first_vote, b.first_broadcast) as first_seen,
+                   GREATEST(ct.last_content, vg.last_vote, b.last_broadcast) as last_active
+            FROM content_totals ct, votes_given vg, votes_received vr, broadcasts b
+            "#
+            ),
+        );
+
+        let mut tx = self.begin_budgeted("get_user_stats").await?;
+        let row = query
+            .query()
+            .bind(&user_pubkey_bytes)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch user stats: {}", e)))?;
+
+        Ok(KUserStatsRecord {
+            user_pubkey: user_public_key.to_string(),
+            posts_count: row.get::<i64, _>("posts_count") as u64,
+            replies_count: row.get::<i64, _>("replies_count") as u64,
+            quotes_count: row.get::<i64, _>("quotes_count") as u64,
+            votes_given_count: row.get::<i64, _>("votes_given_count") as u64,
+            up_votes_received: row.get::<i64, _>("up_votes_count") as u64,
+            down_votes_received: row.get::<i64, _>("down_votes_count") as u64,
+            first_seen: row
+                .get::<Option<i64>, _>("first_seen")
+                .map(|block_time| block_time as u64),
+            last_active: row
+                .get::<Option<i64>, _>("last_active")
+                .map(|block_time| block_time as u64),
+        })
+    }
+
+    async fn get_users_by_keys(
+        &self,
+        user_public_keys: &[String],
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Vec<(KBroadcastRecord, bool)>> {
+        let user_pubkeys_bytes = user_public_keys
+            .iter()
+            .map(|user_public_key| Self::decode_hex_to_bytes(user_public_key))
+            .collect::<DatabaseResult<Vec<_>>>()?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        let rows = NamedQuery::new(
+            "get_users_by_keys",
+            r#"
+            SELECT
+                COALESCE(b.id, 0) as id,
+                b.transaction_id,
+                COALESCE(b.block_time, 0) as block_time,
+                k.pubkey as sender_pubkey,
+                b.sender_signature,
+                COALESCE(b.base64_encoded_nickname, '') as base64_encoded_nickname,
+                b.base64_encoded_profile_image,
+                COALESCE(b.base64_encoded_message, '') as base64_encoded_message,
+                EXISTS (
+                    SELECT 1 FROM k_blocks kb
+                    WHERE kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = k.pubkey
+                ) as is_blocked
+            FROM unnest($1::bytea[]) WITH ORDINALITY AS k(pubkey, position)
+            LEFT JOIN LATERAL (
+                SELECT id, transaction_id, block_time, sender_signature, base64_encoded_nickname,
+                       base64_encoded_profile_image, base64_encoded_message
+                FROM k_broadcasts
+                WHERE sender_pubkey = k.pubkey
+                LIMIT 1
+            ) b ON true
+            ORDER BY k.position
+            "#,
+        )
+        .query()
+        .bind(&user_pubkeys_bytes)
+        .bind(&requester_pubkey_bytes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch users by keys: {}", e)))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                // Users without broadcast have no transaction and signature
+                let transaction_id: Option<Vec<u8>> = row.get("transaction_id");
+                let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+                let sender_signature: Option<Vec<u8>> = row.get("sender_signature");
+
+                let broadcast_record = KBroadcastRecord {
+                    id: row.get::<i64, _>("id"),
+                    transaction_id: transaction_id
+                        .map(|bytes| Self::encode_bytes_to_hex(&bytes))
+                        .unwrap_or_default(),
+                    block_time: row.get::<i64, _>("block_time") as u64,
+                    sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                    sender_signature: sender_signature
+                        .map(|bytes| Self::encode_bytes_to_hex(&bytes))
+                        .unwrap_or_default(),
+                    base64_encoded_nickname: row.get("base64_encoded_nickname"),
+                    base64_encoded_profile_image: row.get("base64_encoded_profile_image"),
+                    base64_encoded_message: row.get("base64_encoded_message"),
+                };
+                (broadcast_record, row.get("is_blocked"))
+            })
+            .collect())
+    }
+
     async fn get_blocked_users_by_requester(
         &self,
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KBroadcastRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1;
 
-        let mut query = String::from(
+        let mut query = NamedQuery::new(
+            "get_blocked_users_by_requester",
             r#"
             SELECT kb.id, kb.transaction_id, kb.block_time, kb.blocked_user_pubkey as sender_pubkey, kb.sender_signature,
                    COALESCE(b.base64_encoded_nickname, '') as base64_encoded_nickname,
@@ -791,31 +1937,15 @@ impl DatabaseInterface for PostgresDbManager {
             "#,
         );
 
-        let mut bind_count = 1;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kb.block_time < ${} OR (kb.block_time = ${} AND kb.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kb.block_time > ${} OR (kb.block_time = ${} AND kb.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "kb.block_time",
+            "kb.id",
+            &options,
+        ));
 
         if options.sort_descending {
             query.push_str(" ORDER BY kb.block_time DESC, kb.id DESC");
@@ -823,27 +1953,12 @@ impl DatabaseInterface for PostgresDbManager {
             query.push_str(" ORDER BY kb.block_time ASC, kb.id ASC");
         }
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
-
-        let mut query_builder = sqlx::query(&query);
-        query_builder = query_builder.bind(&requester_pubkey_bytes);
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        query_builder = query_builder.bind(offset_limit);
+        let query_builder = params.bind(query.query());
 
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
             DatabaseError::QueryError(format!("Failed to fetch blocked users by requester: {}", e))
         })?;
 
@@ -884,11 +1999,13 @@ impl DatabaseInterface for PostgresDbManager {
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KBroadcastRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1;
 
-        let mut query = String::from(
+        let mut query = NamedQuery::new(
+            "get_followed_users_by_requester",
             r#"
             SELECT kf.id, kf.transaction_id, kf.block_time, kf.followed_user_pubkey as sender_pubkey, kf.sender_signature,
                    COALESCE(b.base64_encoded_nickname, '') as base64_encoded_nickname,
@@ -900,31 +2017,15 @@ impl DatabaseInterface for PostgresDbManager {
             "#,
         );
 
-        let mut bind_count = 1;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time < ${} OR (kf.block_time = ${} AND kf.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time > ${} OR (kf.block_time = ${} AND kf.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "kf.block_time",
+            "kf.id",
+            &options,
+        ));
 
         if options.sort_descending {
             query.push_str(" ORDER BY kf.block_time DESC, kf.id DESC");
@@ -932,27 +2033,12 @@ impl DatabaseInterface for PostgresDbManager {
             query.push_str(" ORDER BY kf.block_time ASC, kf.id ASC");
         }
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        let mut query_builder = sqlx::query(&query);
-        query_builder = query_builder.bind(&requester_pubkey_bytes);
+        let query_builder = params.bind(query.query());
 
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder.bind(offset_limit);
-
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
             DatabaseError::QueryError(format!(
                 "Failed to fetch followed users by requester: {}",
                 e
@@ -997,12 +2083,14 @@ impl DatabaseInterface for PostgresDbManager {
         user_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool)>> {
+        let pool = self.read_pool(&options);
         let user_pubkey_bytes = Self::decode_hex_to_bytes(user_pubkey)?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1;
 
-        let mut query = String::from(
+        let mut query = NamedQuery::new(
+            "get_users_following",
             r#"
             SELECT kf.id, kf.transaction_id, kf.block_time, kf.followed_user_pubkey as sender_pubkey, kf.sender_signature,
                    COALESCE(b.base64_encoded_nickname, '') as base64_encoded_nickname,
@@ -1016,31 +2104,16 @@ impl DatabaseInterface for PostgresDbManager {
             "#,
         );
 
-        let mut bind_count = 2;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time < ${} OR (kf.block_time = ${} AND kf.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&user_pubkey_bytes); // $1
+        params.push(&requester_pubkey_bytes); // $2
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time > ${} OR (kf.block_time = ${} AND kf.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "kf.block_time",
+            "kf.id",
+            &options,
+        ));
 
         if options.sort_descending {
             query.push_str(" ORDER BY kf.block_time DESC, kf.id DESC");
@@ -1048,29 +2121,12 @@ impl DatabaseInterface for PostgresDbManager {
             query.push_str(" ORDER BY kf.block_time ASC, kf.id ASC");
         }
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
-
-        let mut query_builder = sqlx::query(&query);
-        query_builder = query_builder
-            .bind(&user_pubkey_bytes)
-            .bind(&requester_pubkey_bytes);
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        query_builder = query_builder.bind(offset_limit);
+        let query_builder = params.bind(query.query());
 
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
             DatabaseError::QueryError(format!("Failed to fetch users following: {}", e))
         })?;
 
@@ -1120,12 +2176,14 @@ impl DatabaseInterface for PostgresDbManager {
         user_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool)>> {
+        let pool = self.read_pool(&options);
         let user_pubkey_bytes = Self::decode_hex_to_bytes(user_pubkey)?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1;
 
-        let mut query = String::from(
+        let mut query = NamedQuery::new(
+            "get_users_followers",
             r#"
             SELECT kf.id, kf.transaction_id, kf.block_time, kf.sender_pubkey, kf.sender_signature,
                    COALESCE(b.base64_encoded_nickname, '') as base64_encoded_nickname,
@@ -1139,31 +2197,16 @@ impl DatabaseInterface for PostgresDbManager {
             "#,
         );
 
-        let mut bind_count = 2;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time < ${} OR (kf.block_time = ${} AND kf.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&user_pubkey_bytes); // $1
+        params.push(&requester_pubkey_bytes); // $2
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                query.push_str(&format!(
-                    " AND (kf.block_time > ${} OR (kf.block_time = ${} AND kf.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "kf.block_time",
+            "kf.id",
+            &options,
+        ));
 
         if options.sort_descending {
             query.push_str(" ORDER BY kf.block_time DESC, kf.id DESC");
@@ -1171,29 +2214,12 @@ impl DatabaseInterface for PostgresDbManager {
             query.push_str(" ORDER BY kf.block_time ASC, kf.id ASC");
         }
 
-        bind_count += 1;
-        query.push_str(&format!(" LIMIT ${}", bind_count));
-
-        let mut query_builder = sqlx::query(&query);
-        query_builder = query_builder
-            .bind(&user_pubkey_bytes)
-            .bind(&requester_pubkey_bytes);
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
 
-        query_builder = query_builder.bind(offset_limit);
+        let query_builder = params.bind(query.query());
 
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
             DatabaseError::QueryError(format!("Failed to fetch users followers: {}", e))
         })?;
 
@@ -1244,37 +2270,18 @@ impl DatabaseInterface for PostgresDbManager {
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
         // Add cursor logic to the all_posts CTE
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
 
         let order_clause = if options.sort_descending {
             " ORDER BY c.block_time DESC, c.id DESC"
@@ -1288,118 +2295,119 @@ impl DatabaseInterface for PostgresDbManager {
             " ORDER BY ps.block_time ASC, ps.id ASC"
         };
 
-        let query = format!(
-            r#"
-            WITH all_posts AS (
-                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
-                       c.sender_signature, c.base64_encoded_message, c.content_type,
-                       c.referenced_content_id
-                FROM k_contents c
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = c.sender_pubkey
-                WHERE c.content_type IN ('post', 'quote')
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
-                {order_clause}
-                LIMIT ${limit_param}
-            ), post_stats AS (
-                SELECT lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
-                       lp.sender_signature, lp.base64_encoded_message, lp.content_type,
-                       lp.referenced_content_id,
-                       COALESCE(r.replies_count, 0) as replies_count,
-                       COALESCE(q.quotes_count, 0) as quotes_count,
-                       COALESCE(v.up_votes_count, 0) as up_votes_count,
-                       COALESCE(v.down_votes_count, 0) as down_votes_count,
-                       COALESCE(v.user_upvoted, false) as is_upvoted,
-                       COALESCE(v.user_downvoted, false) as is_downvoted
-                FROM all_posts lp
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as replies_count
-                    FROM k_contents r
-                    WHERE r.content_type = 'reply'
-                      AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = r.referenced_content_id)
-                    GROUP BY referenced_content_id
-                ) r ON lp.transaction_id = r.referenced_content_id
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as quotes_count
-                    FROM k_contents qt
-                    WHERE qt.content_type = 'quote'
-                      AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = qt.referenced_content_id)
-                    GROUP BY referenced_content_id
-                ) q ON lp.transaction_id = q.referenced_content_id
-                LEFT JOIN (
-                    SELECT post_id,
-                           COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                           COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                           bool_or(vote = 'upvote' AND sender_pubkey = $1) as user_upvoted,
-                           bool_or(vote = 'downvote' AND sender_pubkey = $1) as user_downvoted
-                    FROM k_votes v
-                    WHERE EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = v.post_id)
-                    GROUP BY post_id
-                ) v ON lp.transaction_id = v.post_id
-            )
-            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
-                   ps.sender_signature, ps.base64_encoded_message,
-                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
-                                  WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')), '{{}}') as mentioned_pubkeys,
-                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
-                   ps.is_upvoted, ps.is_downvoted,
-                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
-                   b.base64_encoded_profile_image as user_profile_image,
-                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
-                   ref_c.base64_encoded_message as referenced_message,
-                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
-                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
-                   ref_b.base64_encoded_profile_image as referenced_profile_image
-            FROM post_stats ps
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_nickname, base64_encoded_profile_image
-                FROM k_broadcasts b
-                WHERE b.sender_pubkey = ps.sender_pubkey
-                LIMIT 1
-            ) b ON true
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_message, sender_pubkey
-                FROM k_contents
-                WHERE transaction_id = ps.referenced_content_id
-                  AND ps.content_type IN ('reply', 'quote')
-                LIMIT 1
-            ) ref_c ON true
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_nickname, base64_encoded_profile_image
-                FROM k_broadcasts
-                WHERE sender_pubkey = ref_c.sender_pubkey
-                LIMIT 1
-            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
-            WHERE 1=1
-            {final_order_clause}
-            "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1
-        );
-
-        // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&requester_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder.bind(offset_limit);
-
-        let rows = query_builder
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        let vote_counts = self.vote_counts_join("lp.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        // Read the cold tier only when the page reaches that far back
+        let mut rows = Vec::new();
+        for contents in self.feed_relations(&options) {
+            let all_posts_sql = |counts_joins: &str| {
+                format!(
+                    r#"
+                WITH all_posts AS (
+                    SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                           c.sender_signature, c.base64_encoded_message, c.content_type,
+                           c.referenced_content_id
+                    FROM {contents} c
+                    {blocks_join}
+                    WHERE c.content_type IN ('post', 'quote')
+                      AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                    {order_clause}
+                    LIMIT {limit_param}
+                ), post_stats AS (
+                    SELECT lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
+                           lp.sender_signature, lp.base64_encoded_message, lp.content_type,
+                           lp.referenced_content_id,
+                           COALESCE(r.replies_count, 0) as replies_count,
+                           COALESCE(q.quotes_count, 0) as quotes_count,
+                           COALESCE(v.up_votes_count, 0) as up_votes_count,
+                           COALESCE(v.down_votes_count, 0) as down_votes_count,
+                           COALESCE(v.user_upvoted, false) as is_upvoted,
+                           COALESCE(v.user_downvoted, false) as is_downvoted
+                    FROM all_posts lp
+                    {counts_joins}
+                    {vote_counts}
+                )
+                SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
+                       ps.sender_signature, ps.base64_encoded_message,
+                       COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                      WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')
+                                      ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                       (SELECT COUNT(*) FROM k_mentions m
+                        WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')) as mentions_count,
+                       {render_spans_column},
+                       ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
+                       ps.is_upvoted, ps.is_downvoted,
+                       COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                       b.base64_encoded_profile_image as user_profile_image,
+                       encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                       ref_c.base64_encoded_message as referenced_message,
+                       encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                       COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                       ref_b.base64_encoded_profile_image as referenced_profile_image
+                FROM post_stats ps
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts b
+                    WHERE b.sender_pubkey = ps.sender_pubkey
+                    LIMIT 1
+                ) b ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_message, sender_pubkey
+                    FROM {contents}
+                    WHERE transaction_id = ps.referenced_content_id
+                      AND ps.content_type IN ('reply', 'quote')
+                    LIMIT 1
+                ) ref_c ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts
+                    WHERE sender_pubkey = ref_c.sender_pubkey
+                    LIMIT 1
+                ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+                WHERE 1=1
+                {final_order_clause}
+                "#,
+                    contents = contents,
+                    cursor_conditions = cursor_conditions,
+                    rate_limited_filter = self.rate_limited_filter("c"),
+                    visibility_filter = self.visibility_filter("c"),
+                    order_clause = order_clause,
+                    final_order_clause = final_order_clause
+                )
+            };
+            let query = NamedQuery::new(
+                "get_all_posts",
+                &all_posts_sql(&Self::page_counts_joins(contents, "all_posts", false)),
+            );
+
+            // Build query with parameter binding
+            let query_builder = params.bind(query.query());
+
+            let started = std::time::Instant::now();
+            rows = query_builder
+                .fetch_all(pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            if let Some(shadow) = self.shadow_sample() {
+                let candidate = NamedQuery::new(
+                    "get_all_posts:candidate",
+                    &all_posts_sql(&Self::page_counts_joins(contents, "all_posts", true)),
+                );
+                shadow.spawn(
+                    "get_all_posts",
+                    pool,
+                    candidate,
+                    params.clone(),
+                    &rows,
+                    started.elapsed(),
+                );
+            }
+            if !self.page_reaches_cold_tier(&rows, limit) {
+                break;
+            }
+        }
 
         let has_more = rows.len() > limit as usize;
         let actual_items = if has_more {
@@ -1423,6 +2431,8 @@ impl DatabaseInterface for PostgresDbManager {
                 sender_signature: Self::encode_bytes_to_hex(&sender_signature),
                 base64_encoded_message: row.get("base64_encoded_message"),
                 mentioned_pubkeys: mentioned_pubkeys_array,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
                 content_type: None,
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                 quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
@@ -1450,106 +2460,75 @@ impl DatabaseInterface for PostgresDbManager {
         })
     }
 
-    async fn get_content_following(
+    async fn get_ranked_posts(
         &self,
         requester_pubkey: &str,
-        options: QueryOptions,
-    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        ranking: PostRanking,
+        from_time: u64,
+        to_time: u64,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KPostRecord>> {
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
-        let limit = options.limit.unwrap_or(20) as i64;
-        let offset_limit = limit + 1; // Get one extra to check if there are more
-
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
-
-        // Add cursor logic
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        let order_clause = if options.sort_descending {
-            " ORDER BY c.block_time DESC, c.id DESC"
+        // Windows reaching the cold tier rank its content too
+        let contents = if (from_time as i64) < self.cold_tier_before.load(Ordering::Relaxed) {
+            "k_contents_tiered"
         } else {
-            " ORDER BY c.block_time ASC, c.id ASC"
+            "k_contents"
         };
-
-        let final_order_clause = if options.sort_descending {
-            " ORDER BY ps.block_time DESC, ps.id DESC"
-        } else {
-            " ORDER BY ps.block_time ASC, ps.id ASC"
+        let engagement = "(COALESCE(v.up_votes_count, 0) - COALESCE(v.down_votes_count, 0) \
+                          + COALESCE(r.replies_count, 0) + COALESCE(q.quotes_count, 0))::float8";
+        let score = match ranking {
+            PostRanking::Top => engagement.to_string(),
+            // Age in hours at the end of the window, see PostRanking::Hot
+            PostRanking::Hot => format!(
+                "{} / power(GREATEST($3 - lp.block_time, 0) / 3600000.0 + 2, 1.5)",
+                engagement
+            ),
         };
 
-        let query = format!(
-            r#"
-            WITH followed_content AS (
+        let vote_counts = self.vote_counts_join("lp.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "get_ranked_posts",
+            &format!(
+                r#"
+            WITH window_posts AS (
                 SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
                        c.sender_signature, c.base64_encoded_message, c.content_type,
                        c.referenced_content_id
-                FROM k_contents c
-                INNER JOIN k_follows kf ON kf.followed_user_pubkey = c.sender_pubkey
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = c.sender_pubkey
-                WHERE kf.sender_pubkey = $1
-                  AND c.content_type IN ('post', 'reply', 'quote')
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
-                {order_clause}
-                LIMIT ${limit_param}
-            ), content_stats AS (
-                SELECT fc.id, fc.transaction_id, fc.block_time, fc.sender_pubkey,
-                       fc.sender_signature, fc.base64_encoded_message, fc.content_type,
-                       fc.referenced_content_id,
+                FROM {contents} c
+                {blocks_join}
+                WHERE c.content_type IN ('post', 'quote')
+                  AND c.block_time >= $2 AND c.block_time <= $3
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}
+            ), post_stats AS (
+                SELECT lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
+                       lp.sender_signature, lp.base64_encoded_message, lp.content_type,
+                       lp.referenced_content_id,
                        COALESCE(r.replies_count, 0) as replies_count,
                        COALESCE(q.quotes_count, 0) as quotes_count,
                        COALESCE(v.up_votes_count, 0) as up_votes_count,
                        COALESCE(v.down_votes_count, 0) as down_votes_count,
                        COALESCE(v.user_upvoted, false) as is_upvoted,
-                       COALESCE(v.user_downvoted, false) as is_downvoted
-                FROM followed_content fc
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as replies_count
-                    FROM k_contents r
-                    WHERE r.content_type = 'reply'
-                    GROUP BY referenced_content_id
-                ) r ON fc.transaction_id = r.referenced_content_id
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as quotes_count
-                    FROM k_contents qt
-                    WHERE qt.content_type = 'quote'
-                    GROUP BY referenced_content_id
-                ) q ON fc.transaction_id = q.referenced_content_id
-                LEFT JOIN (
-                    SELECT post_id,
-                           COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                           COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                           bool_or(vote = 'upvote' AND sender_pubkey = $1) as user_upvoted,
-                           bool_or(vote = 'downvote' AND sender_pubkey = $1) as user_downvoted
-                    FROM k_votes v
-                    GROUP BY post_id
-                ) v ON fc.transaction_id = v.post_id
+                       COALESCE(v.user_downvoted, false) as is_downvoted,
+                       {score} as score
+                FROM window_posts lp
+                {counts_joins}
+                {vote_counts}
+                ORDER BY score DESC, lp.block_time DESC, lp.id DESC
+                LIMIT $4
             )
             SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
-                   ps.sender_signature, ps.base64_encoded_message, ps.content_type,
+                   ps.sender_signature, ps.base64_encoded_message,
                    COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
-                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type), '{{}}') as mentioned_pubkeys,
+                                  WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')) as mentions_count,
+                   {render_spans_column},
                    ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
                    ps.is_upvoted, ps.is_downvoted,
                    COALESCE(b.base64_encoded_nickname, '') as user_nickname,
@@ -1559,7 +2538,7 @@ impl DatabaseInterface for PostgresDbManager {
                    encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
                    COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
                    ref_b.base64_encoded_profile_image as referenced_profile_image
-            FROM content_stats ps
+            FROM post_stats ps
             LEFT JOIN LATERAL (
                 SELECT base64_encoded_nickname, base64_encoded_profile_image
                 FROM k_broadcasts b
@@ -1568,7 +2547,7 @@ impl DatabaseInterface for PostgresDbManager {
             ) b ON true
             LEFT JOIN LATERAL (
                 SELECT base64_encoded_message, sender_pubkey
-                FROM k_contents
+                FROM {contents}
                 WHERE transaction_id = ps.referenced_content_id
                   AND ps.content_type = 'quote'
                 LIMIT 1
@@ -1579,160 +2558,79 @@ impl DatabaseInterface for PostgresDbManager {
                 WHERE sender_pubkey = ref_c.sender_pubkey
                 LIMIT 1
             ) ref_b ON ref_c.sender_pubkey IS NOT NULL
-            WHERE 1=1
-            {final_order_clause}
+            ORDER BY ps.score DESC, ps.block_time DESC, ps.id DESC
             "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1
+                counts_joins = Self::page_counts_joins(contents, "window_posts", false),
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+            ),
         );
 
-        // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&requester_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder.bind(offset_limit);
-
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
-            DatabaseError::QueryError(format!("Failed to fetch followed content: {}", e))
-        })?;
-
-        // Process results and build pagination
-        let mut items = Vec::new();
-        let mut has_more = false;
-
-        for (index, row) in rows.iter().enumerate() {
-            if index >= limit as usize {
-                has_more = true;
-                break;
-            }
+        let mut tx = self.begin_budgeted("get_ranked_posts").await?;
+        let rows = query
+            .query()
+            .bind(&requester_pubkey_bytes)
+            .bind(from_time as i64)
+            .bind(to_time as i64)
+            .bind(limit as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to fetch ranked posts: {}", e))
+            })?;
 
+        let mut posts = Vec::new();
+        for row in &rows {
             let transaction_id: Vec<u8> = row.get("transaction_id");
             let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
             let sender_signature: Vec<u8> = row.get("sender_signature");
-            let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
-
-            let referenced_content_id: Option<String> = row.try_get("referenced_content_id").ok();
-            let referenced_message: Option<String> = row.try_get("referenced_message").ok();
-            let referenced_sender_pubkey: Option<String> =
-                row.try_get("referenced_sender_pubkey").ok();
-            let referenced_nickname: Option<String> = row.try_get("referenced_nickname").ok();
-            let referenced_profile_image: Option<String> =
-                row.try_get("referenced_profile_image").ok();
 
-            let record = KPostRecord {
+            posts.push(KPostRecord {
                 id: row.get::<i64, _>("id"),
                 transaction_id: Self::encode_bytes_to_hex(&transaction_id),
                 block_time: row.get::<i64, _>("block_time") as u64,
                 sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
                 sender_signature: Self::encode_bytes_to_hex(&sender_signature),
                 base64_encoded_message: row.get("base64_encoded_message"),
-                mentioned_pubkeys: mentioned_pubkeys_raw,
-                content_type: row.try_get("content_type").ok(),
+                mentioned_pubkeys: row.get("mentioned_pubkeys"),
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
+                content_type: None,
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
                 up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
                 down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
-                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
                 is_upvoted: Some(row.get("is_upvoted")),
                 is_downvoted: Some(row.get("is_downvoted")),
                 user_nickname: Some(row.get("user_nickname")),
                 user_profile_image: row.get("user_profile_image"),
-                referenced_content_id,
-                referenced_message,
-                referenced_sender_pubkey,
-                referenced_nickname,
-                referenced_profile_image,
-            };
-
-            items.push(record);
+                referenced_content_id: row.get("referenced_content_id"),
+                referenced_message: row.get("referenced_message"),
+                referenced_sender_pubkey: row.get("referenced_sender_pubkey"),
+                referenced_nickname: row.get("referenced_nickname"),
+                referenced_profile_image: row.get("referenced_profile_image"),
+            });
         }
 
-        // Build pagination metadata
-        let pagination = if items.is_empty() {
-            PaginationMetadata {
-                has_more: false,
-                next_cursor: None,
-                prev_cursor: None,
-            }
-        } else {
-            let first_item = items.first().unwrap();
-            let last_item = items.last().unwrap();
-
-            let next_cursor = if has_more {
-                Some(Self::create_compound_cursor(
-                    last_item.block_time,
-                    last_item.id,
-                ))
-            } else {
-                None
-            };
-
-            let prev_cursor = Some(Self::create_compound_cursor(
-                first_item.block_time,
-                first_item.id,
-            ));
-
-            PaginationMetadata {
-                has_more,
-                next_cursor,
-                prev_cursor,
-            }
-        };
-
-        Ok(PaginatedResult { items, pagination })
+        Ok(posts)
     }
 
-    async fn get_contents_mentioning_user(
+    async fn get_all_posts_light(
         &self,
-        user_public_key: &str,
         requester_pubkey: &str,
         options: QueryOptions,
-    ) -> DatabaseResult<PaginatedResult<ContentRecord>> {
-        let mentioned_user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
-
-        // Add cursor logic for unified content table
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
 
         let order_clause = if options.sort_descending {
             " ORDER BY c.block_time DESC, c.id DESC"
@@ -1740,61 +2638,403 @@ impl DatabaseInterface for PostgresDbManager {
             " ORDER BY c.block_time ASC, c.id ASC"
         };
 
-        let cs_final_order_clause = if options.sort_descending {
-            " ORDER BY cs.block_time DESC, cs.id DESC"
-        } else {
-            " ORDER BY cs.block_time ASC, cs.id ASC"
-        };
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
 
-        let query = format!(
-            r#"
-            WITH mentioned_content AS (
-                -- Get content (posts, quotes, and replies) that mention the specific user
-                SELECT c.content_type, c.id, c.transaction_id, c.block_time, c.sender_pubkey,
-                       c.sender_signature, c.base64_encoded_message, c.referenced_content_id
-                FROM k_contents c
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = ${requester_param} AND kb.blocked_user_pubkey = c.sender_pubkey
-                WHERE EXISTS (
-                    SELECT 1
-                    FROM k_mentions m
-                    WHERE m.mentioned_pubkey = $1
-                      AND m.content_id = c.transaction_id
-                      AND m.content_type = c.content_type
-                )
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
+        // The all_posts page of get_all_posts alone
+        let mut rows = Vec::new();
+        for contents in self.feed_relations(&options) {
+            let query = NamedQuery::new(
+                "get_all_posts_light",
+                &format!(
+                    r#"
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message,
+                       encode(c.referenced_content_id, 'hex') as referenced_content_id
+                FROM {contents} c
+                {blocks_join}
+                WHERE c.content_type IN ('post', 'quote')
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
                 {order_clause}
-                LIMIT ${limit_param}
-            ),
-            content_stats AS (
-                -- Pre-aggregate all metadata in one pass
-                SELECT
-                    mc.content_type, mc.id, mc.transaction_id, mc.block_time, mc.sender_pubkey,
-                    mc.sender_signature, mc.base64_encoded_message, mc.referenced_content_id,
-
-                    -- Replies count (only applicable for posts and quotes, not replies)
-                    CASE WHEN mc.content_type IN ('post', 'quote') THEN COALESCE(r.replies_count, 0) ELSE 0 END as replies_count,
-
-                    -- Quotes count (only applicable for posts and quotes, not replies)
-                    CASE WHEN mc.content_type IN ('post', 'quote') THEN COALESCE(q.quotes_count, 0) ELSE 0 END as quotes_count,
-
-                    -- Vote statistics
-                    COALESCE(v.up_votes_count, 0) as up_votes_count,
-                    COALESCE(v.down_votes_count, 0) as down_votes_count,
-                    COALESCE(v.user_upvoted, false) as is_upvoted,
-                    COALESCE(v.user_downvoted, false) as is_downvoted
-
-                FROM mentioned_content mc
+                LIMIT {limit_param}
+                "#,
+                    rate_limited_filter = self.rate_limited_filter("c"),
+                    visibility_filter = self.visibility_filter("c"),
+                ),
+            );
+
+            rows = params
+                .bind(query.query())
+                .fetch_all(pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            if !self.page_reaches_cold_tier(&rows, limit) {
+                break;
+            }
+        }
 
-                -- Optimized replies aggregation (only for posts and quotes)
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as replies_count
-                    FROM k_contents r
-                    WHERE r.content_type = 'reply'
-                      AND EXISTS (SELECT 1 FROM mentioned_content mc WHERE mc.content_type IN ('post', 'quote') AND mc.transaction_id = r.referenced_content_id)
-                    GROUP BY referenced_content_id
-                ) r ON mc.content_type IN ('post', 'quote') AND mc.transaction_id = r.referenced_content_id
+        let has_more = rows.len() > limit as usize;
+        let posts: Vec<KPostRecord> = rows
+            .into_iter()
+            .take(limit as usize)
+            .map(|row| {
+                let transaction_id: Vec<u8> = row.get("transaction_id");
+                let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+                let sender_signature: Vec<u8> = row.get("sender_signature");
 
-                -- Optimized quotes aggregation (only for posts and quotes)
+                KPostRecord {
+                    id: row.get::<i64, _>("id"),
+                    transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                    block_time: row.get::<i64, _>("block_time") as u64,
+                    sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                    sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                    base64_encoded_message: row.get("base64_encoded_message"),
+                    mentioned_pubkeys: Vec::new(),
+                    mentions_count: None,
+                    render_spans: None,
+                    content_type: None,
+                    replies_count: None,
+                    up_votes_count: None,
+                    down_votes_count: None,
+                    quotes_count: None,
+                    is_upvoted: None,
+                    is_downvoted: None,
+                    user_nickname: None,
+                    user_profile_image: None,
+                    referenced_content_id: row.get("referenced_content_id"),
+                    referenced_message: None,
+                    referenced_sender_pubkey: None,
+                    referenced_nickname: None,
+                    referenced_profile_image: None,
+                }
+            })
+            .collect();
+
+        let pagination = self.create_compound_pagination_metadata(&posts, limit as u32, has_more);
+
+        Ok(PaginatedResult {
+            items: posts,
+            pagination,
+        })
+    }
+
+    async fn get_posts_meta(
+        &self,
+        requester_pubkey: &str,
+        content_ids: &[String],
+    ) -> DatabaseResult<Vec<KPostRecord>> {
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let content_ids_bytes = content_ids
+            .iter()
+            .map(|content_id| Self::decode_hex_to_bytes(content_id))
+            .collect::<DatabaseResult<Vec<_>>>()?;
+
+        // Hydrated contents may be anywhere in a feed, cold tier included
+        let contents = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            "k_contents"
+        } else {
+            "k_contents_tiered"
+        };
+        let vote_counts = self.vote_counts_join("lp.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let query = NamedQuery::new(
+            "get_posts_meta",
+            &format!(
+                r#"
+            WITH requested AS (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.content_type, c.referenced_content_id
+                FROM {contents} c
+                WHERE c.transaction_id = ANY($2)
+                  AND c.content_type IN ('post', 'reply', 'quote')
+            ), post_stats AS (
+                SELECT lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
+                       lp.content_type, lp.referenced_content_id,
+                       COALESCE(r.replies_count, 0) as replies_count,
+                       COALESCE(q.quotes_count, 0) as quotes_count,
+                       COALESCE(v.up_votes_count, 0) as up_votes_count,
+                       COALESCE(v.down_votes_count, 0) as down_votes_count,
+                       COALESCE(v.user_upvoted, false) as is_upvoted,
+                       COALESCE(v.user_downvoted, false) as is_downvoted
+                FROM requested lp
+                {counts_joins}
+                {vote_counts}
+            )
+            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey, ps.content_type,
+                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type) as mentions_count,
+                   {render_spans_column},
+                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
+                   ps.is_upvoted, ps.is_downvoted,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image,
+                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                   ref_c.base64_encoded_message as referenced_message,
+                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                   ref_b.base64_encoded_profile_image as referenced_profile_image
+            FROM post_stats ps
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = ps.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM {contents}
+                WHERE transaction_id = ps.referenced_content_id
+                  AND ps.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            ORDER BY array_position($2, ps.transaction_id)
+            "#,
+                counts_joins = Self::page_counts_joins(contents, "requested", false),
+            ),
+        );
+
+        let mut tx = self.begin_budgeted("get_posts_meta").await?;
+        let rows = query
+            .query()
+            .bind(&requester_pubkey_bytes)
+            .bind(&content_ids_bytes)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch posts meta: {}", e)))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let transaction_id: Vec<u8> = row.get("transaction_id");
+                let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+
+                // The message and signature are those of the light page
+                KPostRecord {
+                    id: row.get::<i64, _>("id"),
+                    transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                    block_time: row.get::<i64, _>("block_time") as u64,
+                    sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                    sender_signature: String::new(),
+                    base64_encoded_message: String::new(),
+                    mentioned_pubkeys: row.get("mentioned_pubkeys"),
+                    mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                    render_spans: row.get("render_spans"),
+                    content_type: row.get("content_type"),
+                    replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                    up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                    down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                    quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                    is_upvoted: Some(row.get("is_upvoted")),
+                    is_downvoted: Some(row.get("is_downvoted")),
+                    user_nickname: Some(row.get("user_nickname")),
+                    user_profile_image: row.get("user_profile_image"),
+                    referenced_content_id: row.get("referenced_content_id"),
+                    referenced_message: row.get("referenced_message"),
+                    referenced_sender_pubkey: row.get("referenced_sender_pubkey"),
+                    referenced_nickname: row.get("referenced_nickname"),
+                    referenced_profile_image: row.get("referenced_profile_image"),
+                }
+            })
+            .collect())
+    }
+
+    async fn get_block_set_hash(&self, requester_pubkey: &str) -> DatabaseResult<Option<String>> {
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // Public keys have a fixed length, so the ordered concatenation identifies the set
+        NamedQuery::new(
+            "get_block_set_hash",
+            r#"
+            SELECT encode(sha256(string_agg(blocked_user_pubkey, ''::bytea ORDER BY blocked_user_pubkey)), 'hex')
+            FROM k_blocks
+            WHERE sender_pubkey = $1
+            "#,
+        )
+        .query_scalar::<Option<String>>()
+        .bind(&requester_pubkey_bytes)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to hash block set: {}", e)))
+    }
+
+    async fn get_followed_among(
+        &self,
+        requester_pubkey: &str,
+        user_pubkeys: &[String],
+    ) -> DatabaseResult<Vec<String>> {
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let user_pubkeys_bytes = user_pubkeys
+            .iter()
+            .map(|pubkey| Self::decode_hex_to_bytes(pubkey))
+            .collect::<DatabaseResult<Vec<_>>>()?;
+
+        NamedQuery::new(
+            "get_followed_among",
+            r#"
+            SELECT encode(followed_user_pubkey, 'hex')
+            FROM k_follows
+            WHERE sender_pubkey = $1 AND followed_user_pubkey = ANY($2)
+            "#,
+        )
+        .query_scalar::<String>()
+        .bind(&requester_pubkey_bytes)
+        .bind(&user_pubkeys_bytes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to query followed users: {}", e)))
+    }
+
+    async fn get_requester_votes(
+        &self,
+        requester_pubkey: &str,
+        post_ids: &[String],
+    ) -> DatabaseResult<Vec<(String, String)>> {
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let post_ids_bytes = post_ids
+            .iter()
+            .map(|post_id| Self::decode_hex_to_bytes(post_id))
+            .collect::<DatabaseResult<Vec<_>>>()?;
+
+        NamedQuery::new(
+            "get_requester_votes",
+            r#"
+            SELECT DISTINCT encode(post_id, 'hex'), vote
+            FROM k_votes
+            WHERE sender_pubkey = $1 AND post_id = ANY($2)
+            "#,
+        )
+        .query_as::<(String, String)>()
+        .bind(&requester_pubkey_bytes)
+        .bind(&post_ids_bytes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to query requester votes: {}", e)))
+    }
+
+    async fn get_content_following(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        self.get_followed_contents(
+            "get_content_following",
+            "'post', 'reply', 'quote'",
+            requester_pubkey,
+            options,
+        )
+        .await
+    }
+
+    async fn get_posts_following(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        self.get_followed_contents("get_posts_following", "'post'", requester_pubkey, options)
+            .await
+    }
+
+    async fn get_contents_mentioning_user(
+        &self,
+        user_public_key: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<ContentRecord>> {
+        let pool = self.read_pool(&options);
+        let mentioned_user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1; // Get one extra to check if there are more
+
+        let mut params = QueryParams::new();
+        params.push(&mentioned_user_pubkey_bytes); // $1
+
+        // Add cursor logic for unified content table
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+        let requester_param = params.push(&requester_pubkey_bytes);
+
+        let order_clause = if options.sort_descending {
+            " ORDER BY c.block_time DESC, c.id DESC"
+        } else {
+            " ORDER BY c.block_time ASC, c.id ASC"
+        };
+
+        let cs_final_order_clause = if options.sort_descending {
+            " ORDER BY cs.block_time DESC, cs.id DESC"
+        } else {
+            " ORDER BY cs.block_time ASC, cs.id ASC"
+        };
+
+        let vote_counts = self.vote_counts_join("mc.transaction_id", &requester_param);
+        let render_spans_column = self.render_spans_column("cs.transaction_id");
+        let (blocks_join, not_blocked) = self.blocked_authors_join(
+            &requester_pubkey_bytes,
+            &requester_param,
+            "c.sender_pubkey",
+            None,
+        );
+        let query = NamedQuery::new(
+            "get_contents_mentioning_user",
+            &format!(
+                r#"
+            WITH mentioned_content AS (
+                -- Get content (posts, quotes, and replies) that mention the specific user
+                SELECT c.content_type, c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message, c.referenced_content_id
+                FROM k_contents c
+                {blocks_join}
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM k_mentions m
+                    WHERE m.mentioned_pubkey = $1
+                      AND m.content_id = c.transaction_id
+                      AND m.content_type = c.content_type
+                )
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            ),
+            content_stats AS (
+                -- Pre-aggregate all metadata in one pass
+                SELECT
+                    mc.content_type, mc.id, mc.transaction_id, mc.block_time, mc.sender_pubkey,
+                    mc.sender_signature, mc.base64_encoded_message, mc.referenced_content_id,
+
+                    -- Replies count (only applicable for posts and quotes, not replies)
+                    CASE WHEN mc.content_type IN ('post', 'quote') THEN COALESCE(r.replies_count, 0) ELSE 0 END as replies_count,
+
+                    -- Quotes count (only applicable for posts and quotes, not replies)
+                    CASE WHEN mc.content_type IN ('post', 'quote') THEN COALESCE(q.quotes_count, 0) ELSE 0 END as quotes_count,
+
+                    -- Vote statistics
+                    COALESCE(v.up_votes_count, 0) as up_votes_count,
+                    COALESCE(v.down_votes_count, 0) as down_votes_count,
+                    COALESCE(v.user_upvoted, false) as is_upvoted,
+                    COALESCE(v.user_downvoted, false) as is_downvoted
+
+                FROM mentioned_content mc
+
+                -- Optimized replies aggregation (only for posts and quotes)
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as replies_count
+                    FROM k_contents r
+                    WHERE r.content_type = 'reply'
+                      AND EXISTS (SELECT 1 FROM mentioned_content mc WHERE mc.content_type IN ('post', 'quote') AND mc.transaction_id = r.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) r ON mc.content_type IN ('post', 'quote') AND mc.transaction_id = r.referenced_content_id
+
+                -- Optimized quotes aggregation (only for posts and quotes)
                 LEFT JOIN (
                     SELECT referenced_content_id, COUNT(*) as quotes_count
                     FROM k_contents qt
@@ -1803,18 +3043,7 @@ impl DatabaseInterface for PostgresDbManager {
                     GROUP BY referenced_content_id
                 ) q ON mc.content_type IN ('post', 'quote') AND mc.transaction_id = q.referenced_content_id
 
-                -- Optimized vote aggregation with user vote in single query
-                LEFT JOIN (
-                    SELECT
-                        post_id,
-                        COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                        COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                        bool_or(vote = 'upvote' AND sender_pubkey = ${requester_param}) as user_upvoted,
-                        bool_or(vote = 'downvote' AND sender_pubkey = ${requester_param}) as user_downvoted
-                    FROM k_votes v
-                    WHERE EXISTS (SELECT 1 FROM mentioned_content mc WHERE mc.transaction_id = v.post_id)
-                    GROUP BY post_id
-                ) v ON mc.transaction_id = v.post_id
+                {vote_counts}
             )
             SELECT
                 cs.content_type, cs.id, cs.transaction_id, cs.block_time, cs.sender_pubkey,
@@ -1826,9 +3055,14 @@ impl DatabaseInterface for PostgresDbManager {
                         SELECT encode(m.mentioned_pubkey, 'hex')
                         FROM k_mentions m
                         WHERE m.content_id = cs.transaction_id AND m.content_type = cs.content_type
+                        ORDER BY m.id
+                        LIMIT {MAX_LISTED_MENTIONS}
                     ),
                     '{{}}'::text[]
                 ) as mentioned_pubkeys,
+                (SELECT COUNT(*) FROM k_mentions m
+                 WHERE m.content_id = cs.transaction_id AND m.content_type = cs.content_type) as mentions_count,
+                {render_spans_column},
 
                 cs.replies_count,
                 cs.quotes_count,
@@ -1871,35 +3105,22 @@ impl DatabaseInterface for PostgresDbManager {
             WHERE 1=1
             {cs_final_order_clause}
             "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            cs_final_order_clause = cs_final_order_clause,
-            limit_param = bind_count + 1,
-            requester_param = bind_count + 2
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                cs_final_order_clause = cs_final_order_clause
+            ),
         );
 
         // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&mentioned_user_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder
-            .bind(offset_limit)
-            .bind(&requester_pubkey_bytes);
+        let query_builder = params.bind(query.query());
 
+        let mut tx = self
+            .begin_budgeted_on(pool, "get_contents_mentioning_user")
+            .await?;
         let rows = query_builder
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *tx)
             .await
             .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
@@ -1928,6 +3149,8 @@ impl DatabaseInterface for PostgresDbManager {
                         sender_signature: Self::encode_bytes_to_hex(&sender_signature),
                         base64_encoded_message: row.get("base64_encoded_message"),
                         mentioned_pubkeys: mentioned_pubkeys_array,
+                        mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                        render_spans: row.get("render_spans"),
                         content_type: None,
                         replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                         quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
@@ -1965,6 +3188,8 @@ impl DatabaseInterface for PostgresDbManager {
                         post_id: post_id_hex,
                         base64_encoded_message: row.get("base64_encoded_message"),
                         mentioned_pubkeys: mentioned_pubkeys_array,
+                        mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                        render_spans: row.get("render_spans"),
                         content_type: None,
                         replies_count: Some(0), // Replies don't have replies
                         quotes_count: None,
@@ -2005,30 +3230,167 @@ impl DatabaseInterface for PostgresDbManager {
         let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
 
-        let query = r#"
-            SELECT
-                c.content_type,
-                c.id,
-                c.transaction_id,
-                c.block_time,
-                c.sender_pubkey,
-                c.sender_signature,
-                c.referenced_content_id,
-                c.base64_encoded_message,
-                COALESCE(
-                    ARRAY(
-                        SELECT m.mentioned_pubkey
-                        FROM k_mentions m
-                        WHERE m.content_id = c.transaction_id AND m.content_type = c.content_type
-                    ),
-                    ARRAY[]::bytea[]
-                ) as mentioned_pubkeys,
+        let vote_counts = self.vote_counts_join("c.transaction_id", "$2");
+        let render_spans_column = self.render_spans_column("c.transaction_id");
+        // Content missing from k_contents may have been moved to the cold tier
+        let relations: &[&str] = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            &["k_contents"]
+        } else {
+            &["k_contents", "k_contents_tiered"]
+        };
+
+        let mut tx = self.begin_budgeted("get_content_by_id").await?;
+        let mut found = None;
+        for contents in relations {
+            let query = NamedQuery::new(
+                "get_content_by_id",
+                &format!(
+                    r#"
+                SELECT
+                    c.content_type,
+                    c.id,
+                    c.transaction_id,
+                    c.block_time,
+                    c.sender_pubkey,
+                    c.sender_signature,
+                    c.referenced_content_id,
+                    c.base64_encoded_message,
+                    COALESCE(
+                        ARRAY(
+                            SELECT m.mentioned_pubkey
+                            FROM k_mentions m
+                            WHERE m.content_id = c.transaction_id AND m.content_type = c.content_type
+                        ),
+                        ARRAY[]::bytea[]
+                    ) as mentioned_pubkeys,
+                    {render_spans_column},
+                    COALESCE(reply_counts.replies_count, 0) as replies_count,
+                    COALESCE(quote_counts.quotes_count, 0) as quotes_count,
+                    COALESCE(v.up_votes_count, 0) as up_votes_count,
+                    COALESCE(v.down_votes_count, 0) as down_votes_count,
+                    COALESCE(v.user_upvoted, false) as is_upvoted,
+                    COALESCE(v.user_downvoted, false) as is_downvoted,
+                    user_profile.base64_encoded_nickname as user_nickname,
+                    user_profile.base64_encoded_profile_image as user_profile_image,
+                    encode(c.referenced_content_id, 'hex') as ref_content_id,
+                    ref_c.base64_encoded_message as referenced_message,
+                    encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                    ref_b.base64_encoded_nickname as referenced_nickname,
+                    ref_b.base64_encoded_profile_image as referenced_profile_image,
+                    CASE
+                        WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
+                        ELSE false
+                    END as is_blocked
+                FROM {contents} c
+                LEFT JOIN LATERAL (
+                    SELECT COUNT(*) as replies_count
+                    FROM {contents}
+                    WHERE referenced_content_id = c.transaction_id AND content_type = 'reply'
+                ) reply_counts ON true
+                LEFT JOIN LATERAL (
+                    SELECT COUNT(*) as quotes_count
+                    FROM {contents}
+                    WHERE referenced_content_id = c.transaction_id AND content_type = 'quote'
+                ) quote_counts ON true
+                {vote_counts}
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts
+                    WHERE sender_pubkey = c.sender_pubkey
+                    LIMIT 1
+                ) user_profile ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_message, sender_pubkey
+                    FROM {contents}
+                    WHERE transaction_id = c.referenced_content_id
+                      AND c.content_type = 'quote'
+                    LIMIT 1
+                ) ref_c ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts
+                    WHERE sender_pubkey = ref_c.sender_pubkey
+                    LIMIT 1
+                ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+                LEFT JOIN k_blocks kb ON kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = c.sender_pubkey
+                WHERE c.transaction_id = $1
+                LIMIT 1
+            "#
+                ),
+            );
+
+            match query
+                .query()
+                .bind(&content_id_bytes)
+                .bind(&requester_pubkey_bytes)
+                .fetch_optional(&mut *tx)
+                .await
+            {
+                Ok(Some(row)) => {
+                    found = Some(row);
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) => return Err(DatabaseError::QueryError(e.to_string())),
+            }
+        }
+        let Some(row) = found else {
+            return Ok(None);
+        };
+
+        let is_blocked: bool = row.get("is_blocked");
+        let content_record = Self::content_record_from_row(&row)?;
+
+        Ok(Some((content_record, is_blocked)))
+    }
+
+    async fn get_contents_by_ids(
+        &self,
+        content_ids: &[String],
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Vec<(ContentRecord, bool)>> {
+        let content_ids_bytes = content_ids
+            .iter()
+            .map(|content_id| Self::decode_hex_to_bytes(content_id))
+            .collect::<DatabaseResult<Vec<_>>>()?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // Restored contents may be anywhere in a timeline, cold tier included
+        let contents = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            "k_contents"
+        } else {
+            "k_contents_tiered"
+        };
+        let vote_counts = self.vote_counts_join("c.transaction_id", "$2");
+        let render_spans_column = self.render_spans_column("c.transaction_id");
+        let query = NamedQuery::new(
+            "get_contents_by_ids",
+            &format!(
+                r#"
+            SELECT
+                c.content_type,
+                c.id,
+                c.transaction_id,
+                c.block_time,
+                c.sender_pubkey,
+                c.sender_signature,
+                c.referenced_content_id,
+                c.base64_encoded_message,
+                COALESCE(
+                    ARRAY(
+                        SELECT m.mentioned_pubkey
+                        FROM k_mentions m
+                        WHERE m.content_id = c.transaction_id AND m.content_type = c.content_type
+                    ),
+                    ARRAY[]::bytea[]
+                ) as mentioned_pubkeys,
+                {render_spans_column},
                 COALESCE(reply_counts.replies_count, 0) as replies_count,
                 COALESCE(quote_counts.quotes_count, 0) as quotes_count,
-                COALESCE(vote_counts.up_votes_count, 0) as up_votes_count,
-                COALESCE(vote_counts.down_votes_count, 0) as down_votes_count,
-                COALESCE(user_vote.is_upvoted, false) as is_upvoted,
-                COALESCE(user_vote.is_downvoted, false) as is_downvoted,
+                COALESCE(v.up_votes_count, 0) as up_votes_count,
+                COALESCE(v.down_votes_count, 0) as down_votes_count,
+                COALESCE(v.user_upvoted, false) as is_upvoted,
+                COALESCE(v.user_downvoted, false) as is_downvoted,
                 user_profile.base64_encoded_nickname as user_nickname,
                 user_profile.base64_encoded_profile_image as user_profile_image,
                 encode(c.referenced_content_id, 'hex') as ref_content_id,
@@ -2036,52 +3398,28 @@ impl DatabaseInterface for PostgresDbManager {
                 encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
                 ref_b.base64_encoded_nickname as referenced_nickname,
                 ref_b.base64_encoded_profile_image as referenced_profile_image,
-                CASE
-                    WHEN kb.blocked_user_pubkey IS NOT NULL THEN true
-                    ELSE false
-                END as is_blocked
-            FROM k_contents c
-            LEFT JOIN (
-                SELECT referenced_content_id, COUNT(*) as replies_count
-                FROM k_contents
-                WHERE content_type = 'reply'
-                GROUP BY referenced_content_id
-            ) reply_counts ON c.transaction_id = reply_counts.referenced_content_id
-            LEFT JOIN (
-                SELECT referenced_content_id, COUNT(*) as quotes_count
-                FROM k_contents
-                WHERE content_type = 'quote'
-                GROUP BY referenced_content_id
-            ) quote_counts ON c.transaction_id = quote_counts.referenced_content_id
-            LEFT JOIN (
-                SELECT
-                    post_id,
-                    COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                    COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count
-                FROM k_votes
-                GROUP BY post_id
-            ) vote_counts ON c.transaction_id = vote_counts.post_id
-            LEFT JOIN (
-                SELECT
-                    post_id,
-                    sender_pubkey,
-                    bool_or(vote = 'upvote') as is_upvoted,
-                    bool_or(vote = 'downvote') as is_downvoted
-                FROM k_votes
-                WHERE sender_pubkey = $2
-                GROUP BY post_id, sender_pubkey
-            ) user_vote ON c.transaction_id = user_vote.post_id
-            LEFT JOIN (
-                SELECT DISTINCT ON (sender_pubkey)
-                    sender_pubkey,
-                    base64_encoded_nickname,
-                    base64_encoded_profile_image
+                kb.blocked_user_pubkey IS NOT NULL as is_blocked
+            FROM {contents} c
+            LEFT JOIN LATERAL (
+                SELECT COUNT(*) as replies_count
+                FROM {contents}
+                WHERE referenced_content_id = c.transaction_id AND content_type = 'reply'
+            ) reply_counts ON true
+            LEFT JOIN LATERAL (
+                SELECT COUNT(*) as quotes_count
+                FROM {contents}
+                WHERE referenced_content_id = c.transaction_id AND content_type = 'quote'
+            ) quote_counts ON true
+            {vote_counts}
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
                 FROM k_broadcasts
-                ORDER BY sender_pubkey
-            ) user_profile ON c.sender_pubkey = user_profile.sender_pubkey
+                WHERE sender_pubkey = c.sender_pubkey
+                LIMIT 1
+            ) user_profile ON true
             LEFT JOIN LATERAL (
                 SELECT base64_encoded_message, sender_pubkey
-                FROM k_contents
+                FROM {contents}
                 WHERE transaction_id = c.referenced_content_id
                   AND c.content_type = 'quote'
                 LIMIT 1
@@ -2093,106 +3431,39 @@ impl DatabaseInterface for PostgresDbManager {
                 LIMIT 1
             ) ref_b ON ref_c.sender_pubkey IS NOT NULL
             LEFT JOIN k_blocks kb ON kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = c.sender_pubkey
-            WHERE c.transaction_id = $1
-            LIMIT 1
-        "#;
+            WHERE c.transaction_id = ANY($1)
+              AND c.content_type IN ('post', 'reply', 'quote')
+            ORDER BY array_position($1, c.transaction_id)
+            "#
+            ),
+        );
 
-        let row = match sqlx::query(query)
-            .bind(&content_id_bytes)
+        let mut tx = self.begin_budgeted("get_contents_by_ids").await?;
+        let rows = query
+            .query()
+            .bind(&content_ids_bytes)
             .bind(&requester_pubkey_bytes)
-            .fetch_optional(&self.pool)
+            .fetch_all(&mut *tx)
             .await
-        {
-            Ok(Some(row)) => row,
-            Ok(None) => return Ok(None),
-            Err(e) => return Err(DatabaseError::QueryError(e.to_string())),
-        };
-
-        let content_type: &str = row.get("content_type");
-        let is_blocked: bool = row.get("is_blocked");
-
-        let content_record = match content_type {
-            "post" | "quote" => {
-                let mentioned_pubkeys_bytes: Vec<Vec<u8>> = row.get("mentioned_pubkeys");
-                let mentioned_pubkeys: Vec<String> = mentioned_pubkeys_bytes
-                    .into_iter()
-                    .map(|bytes| hex::encode(bytes))
-                    .collect();
-
-                let post_record = KPostRecord {
-                    id: row.get("id"),
-                    transaction_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
-                    block_time: row.get::<i64, _>("block_time") as u64,
-                    sender_pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
-                    sender_signature: hex::encode(row.get::<Vec<u8>, _>("sender_signature")),
-                    base64_encoded_message: row.get("base64_encoded_message"),
-                    mentioned_pubkeys,
-                    content_type: None,
-                    replies_count: Some(row.get::<i64, _>("replies_count") as u64),
-                    quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
-                    up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
-                    down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
-                    is_upvoted: Some(row.get("is_upvoted")),
-                    is_downvoted: Some(row.get("is_downvoted")),
-                    user_nickname: row.get("user_nickname"),
-                    user_profile_image: row.get("user_profile_image"),
-                    referenced_content_id: row.get("ref_content_id"),
-                    referenced_message: row.get("referenced_message"),
-                    referenced_sender_pubkey: row.get("referenced_sender_pubkey"),
-                    referenced_nickname: row.get("referenced_nickname"),
-                    referenced_profile_image: row.get("referenced_profile_image"),
-                };
-
-                ContentRecord::Post(post_record)
-            }
-            "reply" => {
-                let mentioned_pubkeys_bytes: Vec<Vec<u8>> = row.get("mentioned_pubkeys");
-                let mentioned_pubkeys: Vec<String> = mentioned_pubkeys_bytes
-                    .into_iter()
-                    .map(|bytes| hex::encode(bytes))
-                    .collect();
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch contents: {}", e)))?;
 
-                let referenced_content_id: Option<Vec<u8>> = row.get("referenced_content_id");
-                let post_id = match referenced_content_id {
-                    Some(bytes) => hex::encode(bytes),
-                    None => {
-                        return Err(DatabaseError::QueryError(
-                            "Missing referenced_content_id for reply".to_string(),
-                        ));
+        rows.iter()
+            .map(|row| {
+                let content_type: String = row.get("content_type");
+                let content_record = match Self::content_record_from_row(row)? {
+                    ContentRecord::Post(mut post) => {
+                        post.content_type = Some(content_type);
+                        ContentRecord::Post(post)
                     }
+                    ContentRecord::Reply(mut reply) => {
+                        reply.content_type = Some(content_type);
+                        ContentRecord::Reply(reply)
+                    }
+                    other => other,
                 };
-
-                let reply_record = KReplyRecord {
-                    id: row.get("id"),
-                    transaction_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
-                    block_time: row.get::<i64, _>("block_time") as u64,
-                    sender_pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
-                    sender_signature: hex::encode(row.get::<Vec<u8>, _>("sender_signature")),
-                    post_id,
-                    base64_encoded_message: row.get("base64_encoded_message"),
-                    mentioned_pubkeys,
-                    content_type: None,
-                    replies_count: Some(row.get::<i64, _>("replies_count") as u64),
-                    quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
-                    up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
-                    down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
-                    is_upvoted: Some(row.get("is_upvoted")),
-                    is_downvoted: Some(row.get("is_downvoted")),
-                    user_nickname: row.get("user_nickname"),
-                    user_profile_image: row.get("user_profile_image"),
-                };
-
-                ContentRecord::Reply(reply_record)
-            }
-            _ => {
-                return Err(DatabaseError::QueryError(format!(
-                    "Unknown content type: {}",
-                    content_type
-                )));
-            }
-        };
-
-        Ok(Some((content_record, is_blocked)))
+                Ok((content_record, row.get("is_blocked")))
+            })
+            .collect()
     }
 
     async fn get_replies_by_post_id(
@@ -2201,38 +3472,20 @@ impl DatabaseInterface for PostgresDbManager {
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KReplyRecord>> {
+        let pool = self.read_pool(&options);
         let post_id_bytes = Self::decode_hex_to_bytes(post_id)?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
+        let mut params = QueryParams::new();
+        params.push(&post_id_bytes); // $1
 
         // Add cursor logic to the limited_replies CTE
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+        let requester_param = params.push(&requester_pubkey_bytes);
 
         let order_clause = if options.sort_descending {
             " ORDER BY c.block_time DESC, c.id DESC"
@@ -2246,19 +3499,29 @@ impl DatabaseInterface for PostgresDbManager {
             " ORDER BY rs.block_time ASC, rs.id ASC"
         };
 
-        let query = format!(
-            r#"
+        let vote_counts = self.vote_counts_join("lr.transaction_id", &requester_param);
+        let render_spans_column = self.render_spans_column("rs.transaction_id");
+        let (blocks_join, not_blocked) = self.blocked_authors_join(
+            &requester_pubkey_bytes,
+            &requester_param,
+            "c.sender_pubkey",
+            None,
+        );
+        let query = NamedQuery::new(
+            "get_replies_by_post_id",
+            &format!(
+                r#"
             WITH limited_replies AS (
                 -- Get limited replies for specific post first to reduce data volume
                 SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
                        c.sender_signature, c.referenced_content_id, c.base64_encoded_message
                 FROM k_contents c
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = ${requester_param} AND kb.blocked_user_pubkey = c.sender_pubkey
+                {blocks_join}
                 WHERE c.content_type = 'reply'
                   AND c.referenced_content_id = $1
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
                 {order_clause}
-                LIMIT ${limit_param}
+                LIMIT {limit_param}
             ),
             reply_stats AS (
                 -- Pre-aggregate metadata only for limited replies
@@ -2298,18 +3561,7 @@ impl DatabaseInterface for PostgresDbManager {
                     GROUP BY referenced_content_id
                 ) q ON lr.transaction_id = q.referenced_content_id
 
-                -- Optimized vote aggregation with EXISTS filter and combined user vote
-                LEFT JOIN (
-                    SELECT
-                        post_id,
-                        COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                        COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                        bool_or(vote = 'upvote' AND sender_pubkey = ${requester_param}) as user_upvoted,
-                        bool_or(vote = 'downvote' AND sender_pubkey = ${requester_param}) as user_downvoted
-                    FROM k_votes v
-                    WHERE EXISTS (SELECT 1 FROM limited_replies lr WHERE lr.transaction_id = v.post_id)
-                    GROUP BY post_id
-                ) v ON lr.transaction_id = v.post_id
+                {vote_counts}
             )
             SELECT
                 rs.id, rs.transaction_id, rs.block_time, rs.sender_pubkey,
@@ -2321,9 +3573,14 @@ impl DatabaseInterface for PostgresDbManager {
                         SELECT encode(m.mentioned_pubkey, 'hex')
                         FROM k_mentions m
                         WHERE m.content_id = rs.transaction_id AND m.content_type = 'reply'
+                        ORDER BY m.id
+                        LIMIT {MAX_LISTED_MENTIONS}
                     ),
                     '{{}}'::text[]
                 ) as mentioned_pubkeys,
+                (SELECT COUNT(*) FROM k_mentions m
+                 WHERE m.content_id = rs.transaction_id AND m.content_type = 'reply') as mentions_count,
+                {render_spans_column},
 
                 rs.replies_count,
                 rs.quotes_count,
@@ -2346,35 +3603,19 @@ impl DatabaseInterface for PostgresDbManager {
             WHERE 1=1
             {final_order_clause}
             "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1,
-            requester_param = bind_count + 2
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
         );
 
         // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&post_id_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder
-            .bind(offset_limit)
-            .bind(&requester_pubkey_bytes);
+        let query_builder = params.bind(query.query());
 
         let rows = query_builder
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await
             .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
@@ -2402,6 +3643,8 @@ impl DatabaseInterface for PostgresDbManager {
                 post_id: Self::encode_bytes_to_hex(&referenced_content_id),
                 base64_encoded_message: row.get("base64_encoded_message"),
                 mentioned_pubkeys: mentioned_pubkeys_array,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
                 content_type: None,
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                 quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
@@ -2424,85 +3667,313 @@ impl DatabaseInterface for PostgresDbManager {
         })
     }
 
-    async fn get_replies_by_user(
+    async fn get_nested_replies(
         &self,
-        user_public_key: &str,
+        parent_ids: &[String],
         requester_pubkey: &str,
-        options: QueryOptions,
-    ) -> DatabaseResult<PaginatedResult<KReplyRecord>> {
-        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+        per_parent_limit: u32,
+    ) -> DatabaseResult<Vec<KReplyRecord>> {
+        if parent_ids.is_empty() || per_parent_limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let parent_ids_bytes = parent_ids
+            .iter()
+            .map(|id| Self::decode_hex_to_bytes(id))
+            .collect::<DatabaseResult<Vec<Vec<u8>>>>()?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
-        let limit = options.limit.unwrap_or(20) as i64;
-        let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
+        // LATERAL subquery picks the newest replies of each parent, same order as get_replies_by_post_id,
+        // so clients can continue a nested thread with /get-replies?post=<parent>&before=<cursor>
+        let vote_counts = self.vote_counts_join("nr.transaction_id", "$2");
+        let render_spans_column = self.render_spans_column("nr.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$2", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "get_nested_replies",
+            &format!(
+                r#"
+            SELECT
+                nr.id, nr.transaction_id, nr.block_time, nr.sender_pubkey,
+                nr.sender_signature, nr.referenced_content_id, nr.base64_encoded_message,
 
-        // Add cursor logic to the limited_replies CTE
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+                COALESCE(
+                    ARRAY(
+                        SELECT encode(m.mentioned_pubkey, 'hex')
+                        FROM k_mentions m
+                        WHERE m.content_id = nr.transaction_id AND m.content_type = 'reply'
+                        ORDER BY m.id
+                        LIMIT {MAX_LISTED_MENTIONS}
+                    ),
+                    '{{}}'::text[]
+                ) as mentioned_pubkeys,
+                (SELECT COUNT(*) FROM k_mentions m
+                 WHERE m.content_id = nr.transaction_id AND m.content_type = 'reply') as mentions_count,
+                {render_spans_column},
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+                (SELECT COUNT(*) FROM k_contents r
+                 WHERE r.content_type = 'reply' AND r.referenced_content_id = nr.transaction_id) as replies_count,
+                (SELECT COUNT(*) FROM k_contents qt
+                 WHERE qt.content_type = 'quote' AND qt.referenced_content_id = nr.transaction_id) as quotes_count,
 
-        let order_clause = if options.sort_descending {
-            " ORDER BY c.block_time DESC, c.id DESC"
-        } else {
-            " ORDER BY c.block_time ASC, c.id ASC"
-        };
+                COALESCE(v.up_votes_count, 0) as up_votes_count,
+                COALESCE(v.down_votes_count, 0) as down_votes_count,
+                COALESCE(v.user_upvoted, false) as is_upvoted,
+                COALESCE(v.user_downvoted, false) as is_downvoted,
 
-        let final_order_clause = if options.sort_descending {
-            " ORDER BY rs.block_time DESC, rs.id DESC"
-        } else {
-            " ORDER BY rs.block_time ASC, rs.id ASC"
-        };
+                COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                b.base64_encoded_profile_image as user_profile_image
 
-        let query = format!(
-            r#"
-            WITH limited_replies AS (
-                -- Get limited replies for specific user first to reduce data volume
+            FROM unnest($1::bytea[]) AS p(parent_id)
+            CROSS JOIN LATERAL (
                 SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
                        c.sender_signature, c.referenced_content_id, c.base64_encoded_message
                 FROM k_contents c
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = ${requester_param} AND kb.blocked_user_pubkey = c.sender_pubkey
+                {blocks_join}
                 WHERE c.content_type = 'reply'
-                  AND c.sender_pubkey = $1
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
-                {order_clause}
-                LIMIT ${limit_param}
+                  AND c.referenced_content_id = p.parent_id
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}
+                ORDER BY c.block_time DESC, c.id DESC
+                LIMIT $3
+            ) nr
+            {vote_counts}
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = nr.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            ORDER BY nr.block_time DESC, nr.id DESC
+            "#,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
             ),
-            reply_stats AS (
-                -- Pre-aggregate metadata only for limited replies
-                SELECT
-                    lr.id, lr.transaction_id, lr.block_time, lr.sender_pubkey,
-                    lr.sender_signature, lr.referenced_content_id, lr.base64_encoded_message,
+        );
 
-                    -- Replies count (nested replies to this reply)
-                    COALESCE(r.replies_count, 0) as replies_count,
+        let rows = query
+            .query()
+            .bind(&parent_ids_bytes)
+            .bind(&requester_pubkey_bytes)
+            .bind(per_parent_limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
-                    -- Quotes count
-                    COALESCE(q.quotes_count, 0) as quotes_count,
+        Ok(rows.iter().map(Self::nested_reply_record).collect())
+    }
 
-                    -- Vote statistics
-                    COALESCE(v.up_votes_count, 0) as up_votes_count,
+    async fn get_thread_replies(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+        limit: u32,
+    ) -> DatabaseResult<Option<Vec<KReplyRecord>>> {
+        if !self.thread_roots.load(Ordering::Relaxed) || limit == 0 {
+            return Ok(None);
+        }
+
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // A conversation may be anywhere in time, cold tier included
+        let contents = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            "k_contents"
+        } else {
+            "k_contents_tiered"
+        };
+
+        // Posts and quotes are the roots of their thread, replies carry theirs; the replies
+        // under it are counted up to one past `limit` on idx_k_contents_thread
+        let thread = NamedQuery::new(
+            "get_thread_replies",
+            &format!(
+                r#"
+            SELECT t.root_post_id,
+                   (SELECT COUNT(*) FROM (
+                        SELECT 1 FROM {contents} r
+                        WHERE r.root_post_id = t.root_post_id AND r.content_type = 'reply'
+                        LIMIT $2
+                    ) n) as replies_count
+            FROM (
+                SELECT CASE WHEN content_type = 'reply' THEN root_post_id ELSE transaction_id END as root_post_id
+                FROM {contents}
+                WHERE transaction_id = $1
+                LIMIT 1
+            ) t
+            "#
+            ),
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .bind(limit as i64 + 1)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        let Some(thread) = thread else {
+            return Ok(Some(Vec::new()));
+        };
+        let Some(root_post_id) = thread.get::<Option<Vec<u8>>, _>("root_post_id") else {
+            return Ok(None);
+        };
+        if thread.get::<i64, _>("replies_count") > limit as i64 {
+            return Ok(None);
+        }
+
+        // The whole conversation is read once, the reply counts of its replies grouped from it
+        let vote_counts = self.vote_counts_join("nr.transaction_id", "$2");
+        let render_spans_column = self.render_spans_column("nr.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$2", "nr.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "get_thread_replies",
+            &format!(
+                r#"
+            WITH thread AS MATERIALIZED (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.referenced_content_id, c.base64_encoded_message,
+                       c.is_rate_limited
+                FROM {contents} c
+                WHERE c.root_post_id = $1 AND c.content_type = 'reply'
+                ORDER BY c.block_time DESC, c.id DESC
+                LIMIT $3
+            ),
+            reply_counts AS (
+                SELECT referenced_content_id, COUNT(*) as replies_count
+                FROM thread
+                GROUP BY referenced_content_id
+            )
+            SELECT
+                nr.id, nr.transaction_id, nr.block_time, nr.sender_pubkey,
+                nr.sender_signature, nr.referenced_content_id, nr.base64_encoded_message,
+
+                COALESCE(
+                    ARRAY(
+                        SELECT encode(m.mentioned_pubkey, 'hex')
+                        FROM k_mentions m
+                        WHERE m.content_id = nr.transaction_id AND m.content_type = 'reply'
+                        ORDER BY m.id
+                        LIMIT {MAX_LISTED_MENTIONS}
+                    ),
+                    '{{}}'::text[]
+                ) as mentioned_pubkeys,
+                (SELECT COUNT(*) FROM k_mentions m
+                 WHERE m.content_id = nr.transaction_id AND m.content_type = 'reply') as mentions_count,
+                {render_spans_column},
+
+                COALESCE(rc.replies_count, 0) as replies_count,
+                (SELECT COUNT(*) FROM k_contents qt
+                 WHERE qt.content_type = 'quote' AND qt.referenced_content_id = nr.transaction_id) as quotes_count,
+
+                COALESCE(v.up_votes_count, 0) as up_votes_count,
+                COALESCE(v.down_votes_count, 0) as down_votes_count,
+                COALESCE(v.user_upvoted, false) as is_upvoted,
+                COALESCE(v.user_downvoted, false) as is_downvoted,
+
+                COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                b.base64_encoded_profile_image as user_profile_image
+
+            FROM thread nr
+            LEFT JOIN reply_counts rc ON rc.referenced_content_id = nr.transaction_id
+            {blocks_join}
+            {vote_counts}
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = nr.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            WHERE {not_blocked}{rate_limited_filter}{visibility_filter}
+            ORDER BY nr.block_time DESC, nr.id DESC
+            "#,
+                rate_limited_filter = self.rate_limited_filter("nr"),
+                visibility_filter = self.visibility_filter("nr"),
+            ),
+        );
+
+        let rows = query
+            .query()
+            .bind(&root_post_id)
+            .bind(&requester_pubkey_bytes)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(Some(rows.iter().map(Self::nested_reply_record).collect()))
+    }
+
+    async fn get_replies_by_user(
+        &self,
+        user_public_key: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KReplyRecord>> {
+        let pool = self.read_pool(&options);
+        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1; // Get one extra to check if there are more
+
+        let mut params = QueryParams::new();
+        params.push(&user_pubkey_bytes); // $1
+
+        // Add cursor logic to the limited_replies CTE
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+        let requester_param = params.push(&requester_pubkey_bytes);
+
+        let order_clause = if options.sort_descending {
+            " ORDER BY c.block_time DESC, c.id DESC"
+        } else {
+            " ORDER BY c.block_time ASC, c.id ASC"
+        };
+
+        let final_order_clause = if options.sort_descending {
+            " ORDER BY rs.block_time DESC, rs.id DESC"
+        } else {
+            " ORDER BY rs.block_time ASC, rs.id ASC"
+        };
+
+        let vote_counts = self.vote_counts_join("lr.transaction_id", &requester_param);
+        let render_spans_column = self.render_spans_column("rs.transaction_id");
+        let (blocks_join, not_blocked) = self.blocked_authors_join(
+            &requester_pubkey_bytes,
+            &requester_param,
+            "c.sender_pubkey",
+            Some(&user_pubkey_bytes),
+        );
+        let query = NamedQuery::new(
+            "get_replies_by_user",
+            &format!(
+                r#"
+            WITH limited_replies AS (
+                -- Get limited replies for specific user first to reduce data volume
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.referenced_content_id, c.base64_encoded_message
+                FROM k_contents c
+                {blocks_join}
+                WHERE c.content_type = 'reply'
+                  AND c.sender_pubkey = $1
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            ),
+            reply_stats AS (
+                -- Pre-aggregate metadata only for limited replies
+                SELECT
+                    lr.id, lr.transaction_id, lr.block_time, lr.sender_pubkey,
+                    lr.sender_signature, lr.referenced_content_id, lr.base64_encoded_message,
+
+                    -- Replies count (nested replies to this reply)
+                    COALESCE(r.replies_count, 0) as replies_count,
+
+                    -- Quotes count
+                    COALESCE(q.quotes_count, 0) as quotes_count,
+
+                    -- Vote statistics
+                    COALESCE(v.up_votes_count, 0) as up_votes_count,
                     COALESCE(v.down_votes_count, 0) as down_votes_count,
                     COALESCE(v.user_upvoted, false) as is_upvoted,
                     COALESCE(v.user_downvoted, false) as is_downvoted
@@ -2527,18 +3998,7 @@ impl DatabaseInterface for PostgresDbManager {
                     GROUP BY referenced_content_id
                 ) q ON lr.transaction_id = q.referenced_content_id
 
-                -- Optimized vote aggregation with EXISTS filter and combined user vote
-                LEFT JOIN (
-                    SELECT
-                        post_id,
-                        COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                        COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                        bool_or(vote = 'upvote' AND sender_pubkey = ${requester_param}) as user_upvoted,
-                        bool_or(vote = 'downvote' AND sender_pubkey = ${requester_param}) as user_downvoted
-                    FROM k_votes v
-                    WHERE EXISTS (SELECT 1 FROM limited_replies lr WHERE lr.transaction_id = v.post_id)
-                    GROUP BY post_id
-                ) v ON lr.transaction_id = v.post_id
+                {vote_counts}
             )
             SELECT
                 rs.id, rs.transaction_id, rs.block_time, rs.sender_pubkey,
@@ -2550,9 +4010,14 @@ impl DatabaseInterface for PostgresDbManager {
                         SELECT encode(m.mentioned_pubkey, 'hex')
                         FROM k_mentions m
                         WHERE m.content_id = rs.transaction_id AND m.content_type = 'reply'
+                        ORDER BY m.id
+                        LIMIT {MAX_LISTED_MENTIONS}
                     ),
                     '{{}}'::text[]
                 ) as mentioned_pubkeys,
+                (SELECT COUNT(*) FROM k_mentions m
+                 WHERE m.content_id = rs.transaction_id AND m.content_type = 'reply') as mentions_count,
+                {render_spans_column},
 
                 rs.replies_count,
                 rs.quotes_count,
@@ -2575,35 +4040,19 @@ impl DatabaseInterface for PostgresDbManager {
             WHERE 1=1
             {final_order_clause}
             "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1,
-            requester_param = bind_count + 2
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
         );
 
         // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&user_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder
-            .bind(offset_limit)
-            .bind(&requester_pubkey_bytes);
+        let query_builder = params.bind(query.query());
 
         let rows = query_builder
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await
             .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
@@ -2631,6 +4080,8 @@ impl DatabaseInterface for PostgresDbManager {
                 post_id: Self::encode_bytes_to_hex(&referenced_content_id),
                 base64_encoded_message: row.get("base64_encoded_message"),
                 mentioned_pubkeys: mentioned_pubkeys_array,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
                 content_type: None,
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                 quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
@@ -2659,38 +4110,20 @@ impl DatabaseInterface for PostgresDbManager {
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
         let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1; // Get one extra to check if there are more
 
-        let mut bind_count = 1;
-        let mut cursor_conditions = String::new();
+        let mut params = QueryParams::new();
+        params.push(&user_pubkey_bytes); // $1
 
         // Add cursor logic to the all_posts CTE
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+        let requester_param = params.push(&requester_pubkey_bytes);
 
         let order_clause = if options.sort_descending {
             " ORDER BY c.block_time DESC, c.id DESC"
@@ -2704,158 +4137,154 @@ impl DatabaseInterface for PostgresDbManager {
             " ORDER BY ps.block_time ASC, ps.id ASC"
         };
 
-        let query = format!(
-            r#"
-            WITH all_posts AS (
-                -- Get limited posts for specific user first to reduce data volume
-                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
-                       c.sender_signature, c.base64_encoded_message, c.content_type,
-                       c.referenced_content_id
-                FROM k_contents c
-                WHERE c.content_type IN ('post', 'quote') AND c.sender_pubkey = $1{cursor_conditions}
-                {order_clause}
-                LIMIT ${limit_param}
-            ),
-            post_stats AS (
-                -- Pre-aggregate metadata only for limited posts
-                SELECT
-                    lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
-                    lp.sender_signature, lp.base64_encoded_message, lp.content_type,
-                    lp.referenced_content_id,
-
-                    -- Replies count (optimized with EXISTS)
-                    COALESCE(r.replies_count, 0) as replies_count,
-
-                    -- Quotes count (optimized with EXISTS)
-                    COALESCE(q.quotes_count, 0) as quotes_count,
-
-                    -- Vote statistics (optimized with EXISTS)
-                    COALESCE(v.up_votes_count, 0) as up_votes_count,
-                    COALESCE(v.down_votes_count, 0) as down_votes_count,
-                    COALESCE(v.user_upvoted, false) as is_upvoted,
-                    COALESCE(v.user_downvoted, false) as is_downvoted
-
-                FROM all_posts lp
-
-                -- Optimized replies aggregation with EXISTS filter
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as replies_count
-                    FROM k_contents r
-                    WHERE r.content_type = 'reply'
-                      AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = r.referenced_content_id)
-                    GROUP BY referenced_content_id
-                ) r ON lp.transaction_id = r.referenced_content_id
-
-                -- Optimized quotes aggregation with EXISTS filter
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as quotes_count
-                    FROM k_contents qt
-                    WHERE qt.content_type = 'quote'
-                      AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = qt.referenced_content_id)
-                    GROUP BY referenced_content_id
-                ) q ON lp.transaction_id = q.referenced_content_id
-
-                -- Optimized vote aggregation with EXISTS filter and combined user vote
-                LEFT JOIN (
-                    SELECT
-                        post_id,
-                        COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                        COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                        bool_or(vote = 'upvote' AND sender_pubkey = ${requester_param}) as user_upvoted,
-                        bool_or(vote = 'downvote' AND sender_pubkey = ${requester_param}) as user_downvoted
-                    FROM k_votes v
-                    WHERE EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = v.post_id)
-                    GROUP BY post_id
-                ) v ON lp.transaction_id = v.post_id
-            )
-            SELECT
-                ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
-                ps.sender_signature, ps.base64_encoded_message,
-
-                -- Get mentioned pubkeys efficiently with subquery
-                COALESCE(
-                    ARRAY(
-                        SELECT encode(m.mentioned_pubkey, 'hex')
-                        FROM k_mentions m
-                        WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')
-                    ),
-                    '{{}}'::text[]
-                ) as mentioned_pubkeys,
-
-                ps.replies_count,
-                ps.quotes_count,
-                ps.up_votes_count,
-                ps.down_votes_count,
-                ps.is_upvoted,
-                ps.is_downvoted,
-
-                -- User profile lookup with LATERAL join
-                COALESCE(b.base64_encoded_nickname, '') as user_nickname,
-                b.base64_encoded_profile_image as user_profile_image,
-
-                -- Quote reference data
-                encode(ps.referenced_content_id, 'hex') as referenced_content_id,
-                ref_c.base64_encoded_message as referenced_message,
-                encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
-                COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
-                ref_b.base64_encoded_profile_image as referenced_profile_image
-
-            FROM post_stats ps
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_nickname, base64_encoded_profile_image
-                FROM k_broadcasts b
-                WHERE b.sender_pubkey = ps.sender_pubkey
-                LIMIT 1
-            ) b ON true
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_message, sender_pubkey
-                FROM k_contents
-                WHERE transaction_id = ps.referenced_content_id
-                  AND ps.content_type IN ('reply', 'quote')
-                LIMIT 1
-            ) ref_c ON true
-            LEFT JOIN LATERAL (
-                SELECT base64_encoded_nickname, base64_encoded_profile_image
-                FROM k_broadcasts
-                WHERE sender_pubkey = ref_c.sender_pubkey
-                LIMIT 1
-            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
-            LEFT JOIN k_blocks kb ON kb.sender_pubkey = ${requester_param} AND kb.blocked_user_pubkey = ps.sender_pubkey
-            WHERE kb.blocked_user_pubkey IS NULL
-            {final_order_clause}
-            "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1,
-            requester_param = bind_count + 2
+        let vote_counts = self.vote_counts_join("lp.transaction_id", &requester_param);
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) = self.blocked_authors_join(
+            &requester_pubkey_bytes,
+            &requester_param,
+            "ps.sender_pubkey",
+            Some(&user_pubkey_bytes),
         );
+        // Read the cold tier only when the page reaches that far back
+        let mut rows = Vec::new();
+        for contents in self.feed_relations(&options) {
+            let query = NamedQuery::new(
+                "get_posts_by_user",
+                &format!(
+                    r#"
+                WITH all_posts AS (
+                    -- Get limited posts for specific user first to reduce data volume
+                    SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                           c.sender_signature, c.base64_encoded_message, c.content_type,
+                           c.referenced_content_id
+                    FROM {contents} c
+                    WHERE c.content_type IN ('post', 'quote') AND c.sender_pubkey = $1{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                    {order_clause}
+                    LIMIT {limit_param}
+                ),
+                post_stats AS (
+                    -- Pre-aggregate metadata only for limited posts
+                    SELECT
+                        lp.id, lp.transaction_id, lp.block_time, lp.sender_pubkey,
+                        lp.sender_signature, lp.base64_encoded_message, lp.content_type,
+                        lp.referenced_content_id,
+
+                        -- Replies count (optimized with EXISTS)
+                        COALESCE(r.replies_count, 0) as replies_count,
+
+                        -- Quotes count (optimized with EXISTS)
+                        COALESCE(q.quotes_count, 0) as quotes_count,
+
+                        -- Vote statistics (optimized with EXISTS)
+                        COALESCE(v.up_votes_count, 0) as up_votes_count,
+                        COALESCE(v.down_votes_count, 0) as down_votes_count,
+                        COALESCE(v.user_upvoted, false) as is_upvoted,
+                        COALESCE(v.user_downvoted, false) as is_downvoted
+
+                    FROM all_posts lp
+
+                    -- Optimized replies aggregation with EXISTS filter
+                    LEFT JOIN (
+                        SELECT referenced_content_id, COUNT(*) as replies_count
+                        FROM {contents} r
+                        WHERE r.content_type = 'reply'
+                          AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = r.referenced_content_id)
+                        GROUP BY referenced_content_id
+                    ) r ON lp.transaction_id = r.referenced_content_id
+
+                    -- Optimized quotes aggregation with EXISTS filter
+                    LEFT JOIN (
+                        SELECT referenced_content_id, COUNT(*) as quotes_count
+                        FROM {contents} qt
+                        WHERE qt.content_type = 'quote'
+                          AND EXISTS (SELECT 1 FROM all_posts lp WHERE lp.transaction_id = qt.referenced_content_id)
+                        GROUP BY referenced_content_id
+                    ) q ON lp.transaction_id = q.referenced_content_id
+
+                    {vote_counts}
+                )
+                SELECT
+                    ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
+                    ps.sender_signature, ps.base64_encoded_message,
+
+                    -- Get mentioned pubkeys efficiently with subquery
+                    COALESCE(
+                        ARRAY(
+                            SELECT encode(m.mentioned_pubkey, 'hex')
+                            FROM k_mentions m
+                            WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')
+                            ORDER BY m.id
+                            LIMIT {MAX_LISTED_MENTIONS}
+                        ),
+                        '{{}}'::text[]
+                    ) as mentioned_pubkeys,
+                    (SELECT COUNT(*) FROM k_mentions m
+                     WHERE m.content_id = ps.transaction_id AND m.content_type IN ('post', 'quote')) as mentions_count,
+                    {render_spans_column},
+
+                    ps.replies_count,
+                    ps.quotes_count,
+                    ps.up_votes_count,
+                    ps.down_votes_count,
+                    ps.is_upvoted,
+                    ps.is_downvoted,
+
+                    -- User profile lookup with LATERAL join
+                    COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                    b.base64_encoded_profile_image as user_profile_image,
 
-        // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&user_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
+                    -- Quote reference data
+                    encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                    ref_c.base64_encoded_message as referenced_message,
+                    encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                    COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                    ref_b.base64_encoded_profile_image as referenced_profile_image
 
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
+                FROM post_stats ps
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts b
+                    WHERE b.sender_pubkey = ps.sender_pubkey
+                    LIMIT 1
+                ) b ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_message, sender_pubkey
+                    FROM {contents}
+                    WHERE transaction_id = ps.referenced_content_id
+                      AND ps.content_type IN ('reply', 'quote')
+                    LIMIT 1
+                ) ref_c ON true
+                LEFT JOIN LATERAL (
+                    SELECT base64_encoded_nickname, base64_encoded_profile_image
+                    FROM k_broadcasts
+                    WHERE sender_pubkey = ref_c.sender_pubkey
+                    LIMIT 1
+                ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+                {blocks_join}
+                WHERE {not_blocked}
+                {final_order_clause}
+                "#,
+                    contents = contents,
+                    cursor_conditions = cursor_conditions,
+                    rate_limited_filter = self.rate_limited_filter("c"),
+                    visibility_filter = self.visibility_filter("c"),
+                    order_clause = order_clause,
+                    final_order_clause = final_order_clause
+                ),
+            );
+
+            // Build query with parameter binding
+            let query_builder = params.bind(query.query());
+
+            rows = query_builder
+                .fetch_all(pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            if !self.page_reaches_cold_tier(&rows, limit) {
+                break;
             }
         }
 
-        query_builder = query_builder
-            .bind(offset_limit)
-            .bind(&requester_pubkey_bytes);
-
-        let rows = query_builder
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
         let has_more = rows.len() > limit as usize;
         let actual_items = if has_more {
             rows.into_iter().take(limit as usize).collect::<Vec<_>>()
@@ -2878,6 +4307,8 @@ impl DatabaseInterface for PostgresDbManager {
                 sender_signature: Self::encode_bytes_to_hex(&sender_signature),
                 base64_encoded_message: row.get("base64_encoded_message"),
                 mentioned_pubkeys: mentioned_pubkeys_array,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
                 content_type: None,
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                 quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
@@ -2911,12 +4342,15 @@ impl DatabaseInterface for PostgresDbManager {
         after: Option<String>,
     ) -> DatabaseResult<u64> {
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let collapse_filter = self.notification_collapse_filter("km");
 
         let count_result = if let Some(cursor_str) = after {
             // If after cursor is provided, count notifications since that cursor (excluding blocked users)
             if let Ok((cursor_timestamp, cursor_id)) = Self::parse_compound_cursor(&cursor_str) {
-                sqlx::query_scalar::<_, i64>(
-                    r#"
+                NamedQuery::new(
+                    "get_notification_count",
+                    &format!(
+                        r#"
                     SELECT COUNT(*)
                     FROM (
                         SELECT km.block_time, km.id
@@ -2929,11 +4363,14 @@ impl DatabaseInterface for PostgresDbManager {
                               SELECT 1 FROM k_blocks kb
                               WHERE kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = km.sender_pubkey
                           )
+                          {collapse_filter}
                         ORDER BY block_time DESC, id DESC
                         LIMIT 31
                     ) recent_notifications
-                    "#,
+                    "#
+                    ),
                 )
+                .query_scalar::<i64>()
                 .bind(&requester_pubkey_bytes)
                 .bind(cursor_timestamp as i64)
                 .bind(cursor_id)
@@ -2946,8 +4383,10 @@ impl DatabaseInterface for PostgresDbManager {
             }
         } else {
             // If no cursor is provided, count all notifications (excluding blocked users)
-            sqlx::query_scalar::<_, i64>(
-                r#"
+            NamedQuery::new(
+                "get_notification_count",
+                &format!(
+                    r#"
                 SELECT COUNT(*)
                 FROM (
                     SELECT km.block_time, km.id
@@ -2959,11 +4398,14 @@ impl DatabaseInterface for PostgresDbManager {
                           SELECT 1 FROM k_blocks kb
                           WHERE kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = km.sender_pubkey
                       )
+                      {collapse_filter}
                     ORDER BY block_time DESC, id DESC
                     LIMIT 31
                 ) recent_notifications
-                "#,
+                "#
+                ),
             )
+            .query_scalar::<i64>()
             .bind(&requester_pubkey_bytes)
             .fetch_one(&self.pool)
             .await
@@ -2983,140 +4425,168 @@ impl DatabaseInterface for PostgresDbManager {
         requester_pubkey: &str,
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<NotificationContentRecord>> {
+        let pool = self.read_pool(&options);
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
         let limit = options.limit.unwrap_or(20) as i64;
         let offset_limit = limit + 1; // Get one extra to check if there are more
 
         // Build cursor conditions for filtering
-        let mut cursor_conditions = String::new();
-        let mut bind_count = 1;
-
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (km.block_time < ${} OR (km.block_time = ${} AND km.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (km.block_time > ${} OR (km.block_time = ${} AND km.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "km.block_time", "km.id", &options);
+        let limit_param = params.push(offset_limit);
 
         let final_order_clause = if options.sort_descending {
             "ORDER BY block_time DESC, notification_id DESC"
         } else {
             "ORDER BY block_time ASC, notification_id ASC"
         };
-        let final_limit = format!("LIMIT ${}", bind_count + 1);
-
-        // Optimized query: get all notifications from k_mentions table
-        let query = format!(
-            r#"
-            WITH filtered_notifications AS (
-                SELECT km.id as notification_id, km.content_id, km.content_type, km.block_time, km.sender_pubkey,
-                       kc.referenced_content_id,
-                       CASE WHEN km.content_type = 'quote' THEN 'quote' ELSE 'mention' END as notification_type
-                FROM k_mentions km
-                LEFT JOIN k_contents kc ON km.content_type = 'quote' AND km.content_id = kc.transaction_id
-                WHERE km.mentioned_pubkey = $1
-                  AND km.sender_pubkey IS NOT NULL
-                  AND km.sender_pubkey != $1
-                  AND NOT EXISTS (
-                      SELECT 1 FROM k_blocks kb
-                      WHERE kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = km.sender_pubkey
-                  )
-                {cursor_conditions}
-                {final_order_clause}
-                {final_limit}
-            ),
-            notifications_with_content AS (
-                -- Step 2: Get content details for all notifications
-                SELECT
-                    CASE fn.content_type
-                        WHEN 'post' THEN c.id
-                        WHEN 'reply' THEN c.id
-                        WHEN 'quote' THEN c.id
-                        WHEN 'vote' THEN v.id
-                    END as id,
-                    fn.content_id as transaction_id,
-                    fn.block_time,
-                    fn.sender_pubkey,
-                    CASE fn.content_type
-                        WHEN 'post' THEN c.base64_encoded_message
-                        WHEN 'reply' THEN c.base64_encoded_message
-                        WHEN 'quote' THEN c.base64_encoded_message
-                        WHEN 'vote' THEN ''
-                    END as base64_encoded_message,
+        let final_limit = format!("LIMIT {limit_param}");
+
+        let query = if self.enriched_mentions.load(Ordering::Relaxed) {
+            let collapse_filter = self.notification_collapse_filter("km");
+            let collapse_count = self.notification_collapse_count("fn", "$1");
+            // Notification data denormalized into k_mentions at index time (schema v23+)
+            NamedQuery::new(
+                "get_notifications",
+                &format!(
+                    r#"
+                WITH filtered_notifications AS (
+                    SELECT km.id as notification_id, km.content_id, km.content_type, km.block_time, km.sender_pubkey,
+                           km.parent_id, km.content_snippet, km.parent_snippet
+                    FROM k_mentions km
+                    WHERE km.mentioned_pubkey = $1
+                      AND km.sender_pubkey IS NOT NULL
+                      AND km.sender_pubkey != $1
+                      AND NOT EXISTS (
+                          SELECT 1 FROM k_blocks kb
+                          WHERE kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = km.sender_pubkey
+                      )
+                    {cursor_conditions}
+                    {collapse_filter}
+                    {final_order_clause}
+                    {final_limit}
+                )
+                SELECT
+                    fn.notification_id as id,
+                    fn.content_id as transaction_id,
+                    fn.block_time,
+                    fn.sender_pubkey,
+                    CASE WHEN fn.content_type = 'vote' THEN '' ELSE COALESCE(fn.content_snippet, '') END as base64_encoded_message,
                     fn.notification_id,
                     COALESCE(b.base64_encoded_nickname, '') as user_nickname,
                     b.base64_encoded_profile_image as user_profile_image,
                     fn.content_type,
-                    fn.notification_type,
-                    -- Vote-specific fields
-                    CASE WHEN fn.content_type = 'vote' THEN v.vote ELSE NULL END as vote_type,
-                    CASE WHEN fn.content_type = 'vote' THEN v.block_time ELSE NULL END as vote_block_time,
-                    CASE WHEN fn.content_type = 'vote' THEN encode(v.post_id, 'hex') ELSE NULL END as content_id,
-                    CASE WHEN fn.content_type = 'vote' THEN COALESCE(vc.base64_encoded_message, '') ELSE NULL END as voted_content,
+                    CASE WHEN fn.content_type = 'quote' THEN 'quote' ELSE 'mention' END as notification_type,
+                    -- Vote-specific fields (content_snippet holds the vote value)
+                    CASE WHEN fn.content_type = 'vote' THEN fn.content_snippet ELSE NULL END as vote_type,
+                    CASE WHEN fn.content_type = 'vote' THEN fn.block_time ELSE NULL END as vote_block_time,
+                    CASE WHEN fn.content_type = 'vote' THEN encode(fn.parent_id, 'hex') ELSE NULL END as content_id,
+                    CASE WHEN fn.content_type = 'vote' THEN COALESCE(fn.parent_snippet, '') ELSE NULL END as voted_content,
                     -- Quote-specific fields: the original content that was quoted
-                    encode(fn.referenced_content_id, 'hex') as quoted_content_id,
-                    CASE WHEN fn.notification_type = 'quote' THEN original.base64_encoded_message ELSE NULL END as quoted_content_message
+                    CASE WHEN fn.content_type = 'quote' THEN encode(fn.parent_id, 'hex') ELSE NULL END as quoted_content_id,
+                    CASE WHEN fn.content_type = 'quote' THEN fn.parent_snippet ELSE NULL END as quoted_content_message,
+                    {collapse_count}
                 FROM filtered_notifications fn
-                LEFT JOIN k_contents c ON fn.content_type IN ('post', 'reply', 'quote') AND fn.content_id = c.transaction_id AND c.content_type = fn.content_type
-                LEFT JOIN k_votes v ON fn.content_type = 'vote' AND fn.content_id = v.transaction_id
-                -- Get user profile for sender
                 LEFT JOIN LATERAL (
                     SELECT base64_encoded_nickname, base64_encoded_profile_image
                     FROM k_broadcasts b
                     WHERE b.sender_pubkey = fn.sender_pubkey
                     LIMIT 1
                 ) b ON true
-                -- For votes, get the content being voted on
-                LEFT JOIN k_contents vc ON fn.content_type = 'vote' AND v.post_id = vc.transaction_id
-                -- For quotes, get the original content that was quoted
-                LEFT JOIN k_contents original ON fn.notification_type = 'quote' AND fn.referenced_content_id = original.transaction_id
                 {final_order_clause}
+                "#
+                ),
             )
-            SELECT * FROM notifications_with_content
-            "#,
-            cursor_conditions = cursor_conditions,
-            final_order_clause = final_order_clause,
-            final_limit = final_limit
-        );
+        } else {
+            // Optimized query: get all notifications from k_mentions table
+            NamedQuery::new(
+                "get_notifications",
+                &format!(
+                    r#"
+                WITH filtered_notifications AS (
+                    SELECT km.id as notification_id, km.content_id, km.content_type, km.block_time, km.sender_pubkey,
+                           kc.referenced_content_id,
+                           CASE WHEN km.content_type = 'quote' THEN 'quote' ELSE 'mention' END as notification_type
+                    FROM k_mentions km
+                    LEFT JOIN k_contents kc ON km.content_type = 'quote' AND km.content_id = kc.transaction_id
+                    WHERE km.mentioned_pubkey = $1
+                      AND km.sender_pubkey IS NOT NULL
+                      AND km.sender_pubkey != $1
+                      AND NOT EXISTS (
+                          SELECT 1 FROM k_blocks kb
+                          WHERE kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = km.sender_pubkey
+                      )
+                    {cursor_conditions}
+                    {final_order_clause}
+                    {final_limit}
+                ),
+                notifications_with_content AS (
+                    -- Step 2: Get content details for all notifications
+                    SELECT
+                        CASE fn.content_type
+                            WHEN 'post' THEN c.id
+                            WHEN 'reply' THEN c.id
+                            WHEN 'quote' THEN c.id
+                            WHEN 'vote' THEN v.id
+                        END as id,
+                        fn.content_id as transaction_id,
+                        fn.block_time,
+                        fn.sender_pubkey,
+                        CASE fn.content_type
+                            WHEN 'post' THEN c.base64_encoded_message
+                            WHEN 'reply' THEN c.base64_encoded_message
+                            WHEN 'quote' THEN c.base64_encoded_message
+                            WHEN 'vote' THEN ''
+                        END as base64_encoded_message,
+                        fn.notification_id,
+                        COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                        b.base64_encoded_profile_image as user_profile_image,
+                        fn.content_type,
+                        fn.notification_type,
+                        -- Vote-specific fields
+                        CASE WHEN fn.content_type = 'vote' THEN v.vote ELSE NULL END as vote_type,
+                        CASE WHEN fn.content_type = 'vote' THEN v.block_time ELSE NULL END as vote_block_time,
+                        CASE WHEN fn.content_type = 'vote' THEN encode(v.post_id, 'hex') ELSE NULL END as content_id,
+                        CASE WHEN fn.content_type = 'vote' THEN COALESCE(vc.base64_encoded_message, '') ELSE NULL END as voted_content,
+                        -- Quote-specific fields: the original content that was quoted
+                        encode(fn.referenced_content_id, 'hex') as quoted_content_id,
+                        CASE WHEN fn.notification_type = 'quote' THEN original.base64_encoded_message ELSE NULL END as quoted_content_message,
+                        -- Notifications are only collapsed from schema v23 (k_mentions.parent_id)
+                        1::bigint as collapse_count
+                    FROM filtered_notifications fn
+                    LEFT JOIN k_contents c ON fn.content_type IN ('post', 'reply', 'quote') AND fn.content_id = c.transaction_id AND c.content_type = fn.content_type
+                    LEFT JOIN k_votes v ON fn.content_type = 'vote' AND fn.content_id = v.transaction_id
+                    -- Get user profile for sender
+                    LEFT JOIN LATERAL (
+                        SELECT base64_encoded_nickname, base64_encoded_profile_image
+                        FROM k_broadcasts b
+                        WHERE b.sender_pubkey = fn.sender_pubkey
+                        LIMIT 1
+                    ) b ON true
+                    -- For votes, get the content being voted on
+                    LEFT JOIN k_contents vc ON fn.content_type = 'vote' AND v.post_id = vc.transaction_id
+                    -- For quotes, get the original content that was quoted
+                    LEFT JOIN k_contents original ON fn.notification_type = 'quote' AND fn.referenced_content_id = original.transaction_id
+                    {final_order_clause}
+                )
+                SELECT * FROM notifications_with_content
+                "#,
+                    cursor_conditions = cursor_conditions,
+                    final_order_clause = final_order_clause,
+                    final_limit = final_limit
+                ),
+            )
+        };
 
         // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query).bind(&requester_pubkey_bytes);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder
-            .bind(offset_limit)
-            .bind(&requester_pubkey_bytes);
+        let query_builder = params.bind(query.query());
 
+        let mut tx = self.begin_budgeted_on(pool, "get_notifications").await?;
         let rows = query_builder
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *tx)
             .await
             .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
@@ -3134,6 +4604,7 @@ impl DatabaseInterface for PostgresDbManager {
             let content_type: String = row.get("content_type");
             let notification_id: i64 = row.get("notification_id");
             let block_time: i64 = row.get("block_time");
+            let collapsed_count = row.get::<i64, _>("collapse_count") as u64;
 
             if content_type == "post" {
                 let post_record = KPostRecord {
@@ -3144,6 +4615,8 @@ impl DatabaseInterface for PostgresDbManager {
                     sender_signature: String::new(),
                     base64_encoded_message: row.get("base64_encoded_message"),
                     mentioned_pubkeys: Vec::new(),
+                    mentions_count: None,
+                    render_spans: None,
                     content_type: None,
                     up_votes_count: None,
                     down_votes_count: None,
@@ -3161,9 +4634,12 @@ impl DatabaseInterface for PostgresDbManager {
                 };
 
                 notifications.push(NotificationContentRecord {
-                    content: ContentRecord::Post(post_record),
+                    subject: NotificationSubject::Content(Box::new(ContentRecord::Post(
+                        post_record,
+                    ))),
                     mention_id: notification_id,
                     mention_block_time: block_time as u64,
+                    collapsed_count,
                 });
             } else if content_type == "quote" {
                 // Handle quote notifications - someone quoted my content
@@ -3178,6 +4654,8 @@ impl DatabaseInterface for PostgresDbManager {
                     sender_signature: String::new(),
                     base64_encoded_message: row.get("base64_encoded_message"),
                     mentioned_pubkeys: Vec::new(),
+                    mentions_count: None,
+                    render_spans: None,
                     content_type: None,
                     up_votes_count: None,
                     down_votes_count: None,
@@ -3195,9 +4673,12 @@ impl DatabaseInterface for PostgresDbManager {
                 };
 
                 notifications.push(NotificationContentRecord {
-                    content: ContentRecord::Post(post_record),
+                    subject: NotificationSubject::Content(Box::new(ContentRecord::Post(
+                        post_record,
+                    ))),
                     mention_id: notification_id,
                     mention_block_time: block_time as u64,
+                    collapsed_count,
                 });
             } else if content_type == "reply" {
                 let reply_record = KReplyRecord {
@@ -3209,6 +4690,8 @@ impl DatabaseInterface for PostgresDbManager {
                     post_id: String::new(),
                     base64_encoded_message: row.get("base64_encoded_message"),
                     mentioned_pubkeys: Vec::new(),
+                    mentions_count: None,
+                    render_spans: None,
                     content_type: None,
                     replies_count: None,
                     quotes_count: None,
@@ -3221,9 +4704,12 @@ impl DatabaseInterface for PostgresDbManager {
                 };
 
                 notifications.push(NotificationContentRecord {
-                    content: ContentRecord::Reply(reply_record),
+                    subject: NotificationSubject::Content(Box::new(ContentRecord::Reply(
+                        reply_record,
+                    ))),
                     mention_id: notification_id,
                     mention_block_time: block_time as u64,
+                    collapsed_count,
                 });
             } else if content_type == "vote" {
                 let vote_record = KVoteRecord {
@@ -3245,9 +4731,27 @@ impl DatabaseInterface for PostgresDbManager {
                 };
 
                 notifications.push(NotificationContentRecord {
-                    content: ContentRecord::Vote(vote_record),
+                    subject: NotificationSubject::Content(Box::new(ContentRecord::Vote(
+                        vote_record,
+                    ))),
+                    mention_id: notification_id,
+                    mention_block_time: block_time as u64,
+                    collapsed_count,
+                });
+            } else if content_type == "follow" {
+                let follow_record = KFollowRecord {
+                    transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                    block_time: block_time as u64,
+                    sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                    user_nickname: Some(row.get("user_nickname")),
+                    user_profile_image: row.get("user_profile_image"),
+                };
+
+                notifications.push(NotificationContentRecord {
+                    subject: NotificationSubject::Follow(follow_record),
                     mention_id: notification_id,
                     mention_block_time: block_time as u64,
+                    collapsed_count,
                 });
             }
         }
@@ -3275,183 +4779,2062 @@ impl DatabaseInterface for PostgresDbManager {
             ));
         }
 
-        Ok(PaginatedResult {
-            items: notifications,
-            pagination,
+        Ok(PaginatedResult {
+            items: notifications,
+            pagination,
+        })
+    }
+
+    async fn get_network(&self) -> DatabaseResult<String> {
+        self.get_network_from_db()
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_block_hash(&self, transaction_id: &str) -> DatabaseResult<Option<String>> {
+        // The Kaspa indexer only fills blocks_transactions when not started with
+        // --disable=blocks_transactions_table
+        let available: bool = NamedQuery::new(
+            "get_block_hash",
+            "SELECT to_regclass('blocks_transactions') IS NOT NULL",
+        )
+        .query_scalar()
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        if !available {
+            return Ok(None);
+        }
+
+        let block_hash: Option<Vec<u8>> = NamedQuery::new(
+            "get_block_hash",
+            "SELECT block_hash FROM blocks_transactions WHERE transaction_id = $1 LIMIT 1",
+        )
+        .query_scalar()
+        .bind(Self::decode_hex_to_bytes(transaction_id)?)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(block_hash.map(hex::encode))
+    }
+
+    async fn get_schema_meta(&self) -> DatabaseResult<Option<SchemaMeta>> {
+        // k_meta is only present from schema v3 onwards
+        let table_exists = NamedQuery::new(
+            "get_schema_meta",
+            "SELECT EXISTS(SELECT 1 FROM information_schema.tables WHERE table_name = 'k_meta')",
+        )
+        .query()
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?
+        .get::<bool, _>(0);
+
+        if !table_exists {
+            return Ok(None);
+        }
+
+        // SELECT * so maintenance columns (schema v8+) are read only when present
+        let row = NamedQuery::new("get_schema_meta", "SELECT * FROM k_meta WHERE id = 1")
+            .query()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to fetch schema meta: {}", e))
+            })?;
+
+        let meta = row.as_ref().map(|row| SchemaMeta {
+            schema_version: row.get("schema_version"),
+            features: row.get("features"),
+            processor_version: row.get("processor_version"),
+            updated_at: row.get("updated_at"),
+            maintenance_mode: row.try_get("maintenance_mode").unwrap_or(false),
+            maintenance_retry_after: row.try_get("maintenance_retry_after").unwrap_or(0),
+            maintenance_reason: row.try_get("maintenance_reason").unwrap_or(None),
+        });
+        self.vote_totals.store(
+            meta.as_ref()
+                .is_some_and(|meta| meta.supports("vote_totals")),
+            Ordering::Relaxed,
+        );
+        self.enriched_mentions.store(
+            meta.as_ref()
+                .is_some_and(|meta| meta.supports("enriched_mentions")),
+            Ordering::Relaxed,
+        );
+        self.render_spans.store(
+            meta.as_ref()
+                .is_some_and(|meta| meta.supports("render_spans")),
+            Ordering::Relaxed,
+        );
+        self.visibility_watermark.store(
+            meta.as_ref()
+                .is_some_and(|meta| meta.supports("visibility_watermark")),
+            Ordering::Relaxed,
+        );
+        self.thread_roots.store(
+            meta.as_ref()
+                .is_some_and(|meta| meta.supports("thread_roots")),
+            Ordering::Relaxed,
+        );
+        // cold_tier_before is only present from schema v24 onwards (NULL until content is moved)
+        let cold_tier_before = row
+            .as_ref()
+            .and_then(|row| row.try_get::<Option<i64>, _>("cold_tier_before").ok())
+            .flatten();
+        self.cold_tier_before
+            .store(cold_tier_before.unwrap_or(0), Ordering::Relaxed);
+        Ok(meta)
+    }
+
+    async fn get_profile_image(
+        &self,
+        user_public_key: &str,
+    ) -> DatabaseResult<Option<ProfileImageRecord>> {
+        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_public_key)?;
+
+        let row = NamedQuery::new(
+            "get_profile_image",
+            r#"
+            SELECT base64_encoded_profile_image, profile_image_ref, profile_image_hash
+            FROM k_broadcasts
+            WHERE sender_pubkey = $1
+            LIMIT 1
+            "#,
+        )
+        .query()
+        .bind(&user_pubkey_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch profile image: {}", e)))?;
+
+        Ok(row.map(|row| ProfileImageRecord {
+            base64_encoded_profile_image: row.get("base64_encoded_profile_image"),
+            profile_image_ref: row.get("profile_image_ref"),
+            profile_image_hash: row.get("profile_image_hash"),
+        }))
+    }
+
+    async fn get_users_count(&self) -> DatabaseResult<u64> {
+        let row = NamedQuery::new(
+            "get_users_count",
+            r#"
+            SELECT COUNT(*) as count FROM k_broadcasts
+            "#,
+        )
+        .query()
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        let count: i64 = row.get("count");
+        Ok(count as u64)
+    }
+
+    async fn get_stats(&self) -> DatabaseResult<crate::database_trait::DatabaseStats> {
+        let mut tx = self.begin_budgeted("get_stats").await?;
+        let row = NamedQuery::new(
+            "get_stats",
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM k_broadcasts) as broadcasts_count,
+                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'post') as posts_count,
+                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'reply') as replies_count,
+                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'quote') as quotes_count,
+                (SELECT COUNT(*) FROM k_votes) as votes_count,
+                (SELECT COUNT(*) FROM k_follows) as follows_count,
+                (SELECT COUNT(*) FROM k_blocks) as blocks_count
+            "#,
+        )
+        .query()
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(crate::database_trait::DatabaseStats {
+            broadcasts_count: row.get("broadcasts_count"),
+            posts_count: row.get("posts_count"),
+            replies_count: row.get("replies_count"),
+            quotes_count: row.get("quotes_count"),
+            votes_count: row.get("votes_count"),
+            follows_count: row.get("follows_count"),
+            blocks_count: row.get("blocks_count"),
+        })
+    }
+
+    /// Get content (posts, replies, quotes) containing a specific hashtag
+    async fn get_hashtag_content(
+        &self,
+        hashtag: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1; // Get one extra to check if there are more
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+        params.push(hashtag); // $2
+
+        // Add cursor logic
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+
+        let order_clause = if options.sort_descending {
+            " ORDER BY c.block_time DESC, c.id DESC"
+        } else {
+            " ORDER BY c.block_time ASC, c.id ASC"
+        };
+
+        let final_order_clause = if options.sort_descending {
+            " ORDER BY ps.block_time DESC, ps.id DESC"
+        } else {
+            " ORDER BY ps.block_time ASC, ps.id ASC"
+        };
+
+        let vote_counts = self.vote_counts_join("hc.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "get_hashtag_content",
+            &format!(
+                r#"
+            WITH hashtag_content AS (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message, c.content_type,
+                       c.referenced_content_id
+                FROM k_contents c
+                INNER JOIN k_hashtags h ON h.content_id = c.transaction_id
+                {blocks_join}
+                WHERE h.hashtag = $2
+                  AND c.content_type IN ('post', 'reply', 'quote')
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            ), content_stats AS (
+                SELECT hc.id, hc.transaction_id, hc.block_time, hc.sender_pubkey,
+                       hc.sender_signature, hc.base64_encoded_message, hc.content_type,
+                       hc.referenced_content_id,
+                       COALESCE(r.replies_count, 0) as replies_count,
+                       COALESCE(q.quotes_count, 0) as quotes_count,
+                       COALESCE(v.up_votes_count, 0) as up_votes_count,
+                       COALESCE(v.down_votes_count, 0) as down_votes_count,
+                       COALESCE(v.user_upvoted, false) as is_upvoted,
+                       COALESCE(v.user_downvoted, false) as is_downvoted
+                FROM hashtag_content hc
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as replies_count
+                    FROM k_contents r
+                    WHERE r.content_type = 'reply'
+                      AND EXISTS (SELECT 1 FROM hashtag_content hc WHERE hc.transaction_id = r.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) r ON hc.transaction_id = r.referenced_content_id
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as quotes_count
+                    FROM k_contents qt
+                    WHERE qt.content_type = 'quote'
+                      AND EXISTS (SELECT 1 FROM hashtag_content hc WHERE hc.transaction_id = qt.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) q ON hc.transaction_id = q.referenced_content_id
+                {vote_counts}
+            )
+            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
+                   ps.sender_signature, ps.base64_encoded_message, ps.content_type,
+                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type) as mentions_count,
+                   {render_spans_column},
+                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
+                   ps.is_upvoted, ps.is_downvoted,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image,
+                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                   ref_c.base64_encoded_message as referenced_message,
+                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                   ref_b.base64_encoded_profile_image as referenced_profile_image
+            FROM content_stats ps
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = ps.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM k_contents
+                WHERE transaction_id = ps.referenced_content_id
+                  AND ps.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            WHERE 1=1
+            {final_order_clause}
+            "#,
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
+        );
+
+        // Build query with parameter binding
+        let query_builder = params.bind(query.query());
+
+        let mut tx = self.begin_budgeted_on(pool, "get_hashtag_content").await?;
+        let rows = query_builder.fetch_all(&mut *tx).await.map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch hashtag content: {}", e))
+        })?;
+
+        // Process results and build pagination
+        let mut items = Vec::new();
+        let mut has_more = false;
+
+        for (index, row) in rows.iter().enumerate() {
+            if index >= limit as usize {
+                has_more = true;
+                break;
+            }
+
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+            let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
+
+            let referenced_content_id: Option<String> = row.try_get("referenced_content_id").ok();
+            let referenced_message: Option<String> = row.try_get("referenced_message").ok();
+            let referenced_sender_pubkey: Option<String> =
+                row.try_get("referenced_sender_pubkey").ok();
+            let referenced_nickname: Option<String> = row.try_get("referenced_nickname").ok();
+            let referenced_profile_image: Option<String> =
+                row.try_get("referenced_profile_image").ok();
+
+            let record = KPostRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_message: row.get("base64_encoded_message"),
+                mentioned_pubkeys: mentioned_pubkeys_raw,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
+                content_type: row.try_get("content_type").ok(),
+                replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                is_upvoted: Some(row.get("is_upvoted")),
+                is_downvoted: Some(row.get("is_downvoted")),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+                referenced_content_id,
+                referenced_message,
+                referenced_sender_pubkey,
+                referenced_nickname,
+                referenced_profile_image,
+            };
+
+            items.push(record);
+        }
+
+        // Build pagination metadata
+        let pagination = if items.is_empty() {
+            PaginationMetadata {
+                has_more: false,
+                next_cursor: None,
+                prev_cursor: None,
+            }
+        } else {
+            let first_item = items.first().unwrap();
+            let last_item = items.last().unwrap();
+
+            let next_cursor = if has_more {
+                Some(Self::create_compound_cursor(
+                    last_item.block_time,
+                    last_item.id,
+                ))
+            } else {
+                None
+            };
+
+            let prev_cursor = Some(Self::create_compound_cursor(
+                first_item.block_time,
+                first_item.id,
+            ));
+
+            PaginationMetadata {
+                has_more,
+                next_cursor,
+                prev_cursor,
+            }
+        };
+
+        Ok(PaginatedResult { items, pagination })
+    }
+
+    /// Full-text search over the posts and replies of both tiers (search_vector, schema v31+)
+    /// `search_query` is parsed by websearch_to_tsquery: words, "quoted phrases", OR and -excluded
+    async fn search_posts(
+        &self,
+        search_query: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1; // Get one extra to check if there are more
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+        params.push(search_query); // $2
+
+        // Add cursor logic
+        let cursor_conditions =
+            Self::cursor_conditions(&mut params, "c.block_time", "c.id", &options);
+        let limit_param = params.push(offset_limit);
+
+        let order_clause = if options.sort_descending {
+            " ORDER BY c.block_time DESC, c.id DESC"
+        } else {
+            " ORDER BY c.block_time ASC, c.id ASC"
+        };
+
+        let final_order_clause = if options.sort_descending {
+            " ORDER BY ps.block_time DESC, ps.id DESC"
+        } else {
+            " ORDER BY ps.block_time ASC, ps.id ASC"
+        };
+
+        // Old content moved to the cold tier is searched too
+        let contents = if self.cold_tier_before.load(Ordering::Relaxed) == 0 {
+            "k_contents"
+        } else {
+            "k_contents_tiered"
+        };
+        let vote_counts = self.vote_counts_join("mc.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("ps.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "search_posts",
+            &format!(
+                r#"
+            WITH matching_content AS (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message, c.content_type,
+                       c.referenced_content_id
+                FROM {contents} c
+                {blocks_join}
+                WHERE c.search_vector @@ websearch_to_tsquery('simple', $2)
+                  AND c.content_type IN ('post', 'reply')
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}{cursor_conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            ), content_stats AS (
+                SELECT mc.id, mc.transaction_id, mc.block_time, mc.sender_pubkey,
+                       mc.sender_signature, mc.base64_encoded_message, mc.content_type,
+                       mc.referenced_content_id,
+                       COALESCE(r.replies_count, 0) as replies_count,
+                       COALESCE(q.quotes_count, 0) as quotes_count,
+                       COALESCE(v.up_votes_count, 0) as up_votes_count,
+                       COALESCE(v.down_votes_count, 0) as down_votes_count,
+                       COALESCE(v.user_upvoted, false) as is_upvoted,
+                       COALESCE(v.user_downvoted, false) as is_downvoted
+                FROM matching_content mc
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as replies_count
+                    FROM {contents} r
+                    WHERE r.content_type = 'reply'
+                      AND EXISTS (SELECT 1 FROM matching_content mc WHERE mc.transaction_id = r.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) r ON mc.transaction_id = r.referenced_content_id
+                LEFT JOIN (
+                    SELECT referenced_content_id, COUNT(*) as quotes_count
+                    FROM {contents} qt
+                    WHERE qt.content_type = 'quote'
+                      AND EXISTS (SELECT 1 FROM matching_content mc WHERE mc.transaction_id = qt.referenced_content_id)
+                    GROUP BY referenced_content_id
+                ) q ON mc.transaction_id = q.referenced_content_id
+                {vote_counts}
+            )
+            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
+                   ps.sender_signature, ps.base64_encoded_message, ps.content_type,
+                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type) as mentions_count,
+                   {render_spans_column},
+                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
+                   ps.is_upvoted, ps.is_downvoted,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image,
+                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                   ref_c.base64_encoded_message as referenced_message,
+                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                   ref_b.base64_encoded_profile_image as referenced_profile_image
+            FROM content_stats ps
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = ps.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM {contents}
+                WHERE transaction_id = ps.referenced_content_id
+                  AND ps.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            WHERE 1=1
+            {final_order_clause}
+            "#,
+                cursor_conditions = cursor_conditions,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
+        );
+
+        // Build query with parameter binding
+        let query_builder = params.bind(query.query());
+
+        let mut tx = self.begin_budgeted_on(pool, "search_posts").await?;
+        let rows = query_builder
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to search posts: {}", e)))?;
+
+        // Process results and build pagination
+        let mut items = Vec::new();
+        let mut has_more = false;
+
+        for (index, row) in rows.iter().enumerate() {
+            if index >= limit as usize {
+                has_more = true;
+                break;
+            }
+
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+            let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
+
+            let referenced_content_id: Option<String> = row.try_get("referenced_content_id").ok();
+            let referenced_message: Option<String> = row.try_get("referenced_message").ok();
+            let referenced_sender_pubkey: Option<String> =
+                row.try_get("referenced_sender_pubkey").ok();
+            let referenced_nickname: Option<String> = row.try_get("referenced_nickname").ok();
+            let referenced_profile_image: Option<String> =
+                row.try_get("referenced_profile_image").ok();
+
+            let record = KPostRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_message: row.get("base64_encoded_message"),
+                mentioned_pubkeys: mentioned_pubkeys_raw,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
+                content_type: row.try_get("content_type").ok(),
+                replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                is_upvoted: Some(row.get("is_upvoted")),
+                is_downvoted: Some(row.get("is_downvoted")),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+                referenced_content_id,
+                referenced_message,
+                referenced_sender_pubkey,
+                referenced_nickname,
+                referenced_profile_image,
+            };
+
+            items.push(record);
+        }
+
+        // Build pagination metadata
+        let pagination = if items.is_empty() {
+            PaginationMetadata {
+                has_more: false,
+                next_cursor: None,
+                prev_cursor: None,
+            }
+        } else {
+            let first_item = items.first().unwrap();
+            let last_item = items.last().unwrap();
+
+            let next_cursor = if has_more {
+                Some(Self::create_compound_cursor(
+                    last_item.block_time,
+                    last_item.id,
+                ))
+            } else {
+                None
+            };
+
+            let prev_cursor = Some(Self::create_compound_cursor(
+                first_item.block_time,
+                first_item.id,
+            ));
+
+            PaginationMetadata {
+                has_more,
+                next_cursor,
+                prev_cursor,
+            }
+        };
+
+        Ok(PaginatedResult { items, pagination })
+    }
+
+    /// Get trending hashtags within a time window
+    async fn get_trending_hashtags(
+        &self,
+        from_time: u64,
+        to_time: u64,
+        limit: u32,
+    ) -> DatabaseResult<Vec<(String, u64)>> {
+        let query = NamedQuery::new(
+            "get_trending_hashtags",
+            r#"
+            SELECT hashtag, COUNT(*) as usage_count
+            FROM k_hashtags
+            WHERE block_time >= $1 AND block_time <= $2
+            GROUP BY hashtag
+            ORDER BY usage_count DESC, hashtag ASC
+            LIMIT $3
+        "#,
+        );
+
+        let mut tx = self.begin_budgeted("get_trending_hashtags").await?;
+        let rows = query
+            .query()
+            .bind(from_time as i64)
+            .bind(to_time as i64)
+            .bind(limit as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to fetch trending hashtags: {}", e))
+            })?;
+
+        let trending_hashtags: Vec<(String, u64)> = rows
+            .iter()
+            .map(|row| {
+                let hashtag: String = row.get("hashtag");
+                let usage_count: i64 = row.get("usage_count");
+                (hashtag, usage_count as u64)
+            })
+            .collect();
+
+        Ok(trending_hashtags)
+    }
+
+    /// Get groups visible to the requester (open groups and closed groups the requester is a member of)
+    async fn get_groups(
+        &self,
+        requester_pubkey: &str,
+        member_pubkey: Option<&str>,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KGroupRecord>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let member_pubkey_bytes = member_pubkey.map(Self::decode_hex_to_bytes).transpose()?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1;
+
+        let mut query = NamedQuery::new(
+            "get_groups",
+            r#"
+            SELECT g.id, g.transaction_id, g.block_time, g.sender_pubkey, g.sender_signature,
+                   g.base64_encoded_name, g.base64_encoded_description, g.membership_policy,
+                   (SELECT COUNT(*) FROM k_group_members gm WHERE gm.group_id = g.transaction_id) as members_count,
+                   EXISTS(
+                       SELECT 1 FROM k_group_members rm
+                       WHERE rm.group_id = g.transaction_id AND rm.member_pubkey = $1
+                   ) as is_member
+            FROM k_groups g
+            WHERE (
+                g.membership_policy = 'open'
+                OR EXISTS(
+                    SELECT 1 FROM k_group_members rm
+                    WHERE rm.group_id = g.transaction_id AND rm.member_pubkey = $1
+                )
+            )
+            "#,
+        );
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+
+        if let Some(member_pubkey_bytes) = &member_pubkey_bytes {
+            let member_param = params.push(member_pubkey_bytes);
+            query.push_str(&format!(
+                " AND EXISTS(SELECT 1 FROM k_group_members fm WHERE fm.group_id = g.transaction_id AND fm.member_pubkey = {member_param})"
+            ));
+        }
+
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "g.block_time",
+            "g.id",
+            &options,
+        ));
+
+        if options.sort_descending {
+            query.push_str(" ORDER BY g.block_time DESC, g.id DESC");
+        } else {
+            query.push_str(" ORDER BY g.block_time ASC, g.id ASC");
+        }
+
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
+
+        let query_builder = params.bind(query.query());
+
+        let rows = query_builder
+            .fetch_all(pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch groups: {}", e)))?;
+
+        let mut groups: Vec<KGroupRecord> = rows.iter().map(Self::group_record_from_row).collect();
+
+        let has_more = groups.len() > limit as usize;
+        if has_more {
+            groups.pop();
+        }
+
+        let pagination = self.create_compound_pagination_metadata(&groups, limit as u32, has_more);
+
+        Ok(PaginatedResult {
+            items: groups,
+            pagination,
+        })
+    }
+
+    /// Get a group by ID with member count and requester membership
+    async fn get_group_by_id(
+        &self,
+        group_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<KGroupRecord>> {
+        let group_id_bytes = Self::decode_hex_to_bytes(group_id)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        let row = NamedQuery::new(
+            "get_group_by_id",
+            r#"
+            SELECT g.id, g.transaction_id, g.block_time, g.sender_pubkey, g.sender_signature,
+                   g.base64_encoded_name, g.base64_encoded_description, g.membership_policy,
+                   (SELECT COUNT(*) FROM k_group_members gm WHERE gm.group_id = g.transaction_id) as members_count,
+                   EXISTS(
+                       SELECT 1 FROM k_group_members rm
+                       WHERE rm.group_id = g.transaction_id AND rm.member_pubkey = $2
+                   ) as is_member
+            FROM k_groups g
+            WHERE g.transaction_id = $1
+            "#,
+        )
+        .query()
+        .bind(&group_id_bytes)
+        .bind(&requester_pubkey_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch group by ID: {}", e)))?;
+
+        Ok(row.as_ref().map(Self::group_record_from_row))
+    }
+
+    /// Get posts of a group (excludes blocked users)
+    async fn get_group_posts(
+        &self,
+        group_id: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KGroupPostRecord>> {
+        let pool = self.read_pool(&options);
+        let group_id_bytes = Self::decode_hex_to_bytes(group_id)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1;
+
+        let mut query = NamedQuery::new(
+            "get_group_posts",
+            r#"
+            SELECT gp.id, gp.transaction_id, gp.block_time, gp.group_id, gp.sender_pubkey,
+                   gp.sender_signature, gp.base64_encoded_message,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image
+            FROM k_group_posts gp
+            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = gp.sender_pubkey
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = gp.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            WHERE gp.group_id = $1
+              AND kb.blocked_user_pubkey IS NULL
+            "#,
+        );
+
+        let mut params = QueryParams::new();
+        params.push(&group_id_bytes); // $1
+        params.push(&requester_pubkey_bytes); // $2
+
+        query.push_str(&Self::cursor_conditions(
+            &mut params,
+            "gp.block_time",
+            "gp.id",
+            &options,
+        ));
+
+        if options.sort_descending {
+            query.push_str(" ORDER BY gp.block_time DESC, gp.id DESC");
+        } else {
+            query.push_str(" ORDER BY gp.block_time ASC, gp.id ASC");
+        }
+
+        let limit_param = params.push(offset_limit);
+        query.push_str(&format!(" LIMIT {limit_param}"));
+
+        let query_builder = params.bind(query.query());
+
+        let rows = query_builder.fetch_all(pool).await.map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch group posts: {}", e))
+        })?;
+
+        let mut posts = Vec::new();
+        for row in &rows {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let group_id: Vec<u8> = row.get("group_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+
+            posts.push(KGroupPostRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                group_id: Self::encode_bytes_to_hex(&group_id),
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_message: row.get("base64_encoded_message"),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+            });
+        }
+
+        let has_more = posts.len() > limit as usize;
+        if has_more {
+            posts.pop();
+        }
+
+        let pagination = self.create_compound_pagination_metadata(&posts, limit as u32, has_more);
+
+        Ok(PaginatedResult {
+            items: posts,
+            pagination,
+        })
+    }
+
+    /// Get upcoming or past events with RSVP counts and the requester RSVP (excludes blocked users)
+    async fn get_events(
+        &self,
+        requester_pubkey: &str,
+        organizer_pubkey: Option<&str>,
+        upcoming: bool,
+        now: u64,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KEventRecord>> {
+        let pool = self.read_pool(&options);
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+        let organizer_pubkey_bytes = organizer_pubkey
+            .map(Self::decode_hex_to_bytes)
+            .transpose()?;
+        let limit = options.limit.unwrap_or(20) as i64;
+        let offset_limit = limit + 1;
+
+        let mut params = QueryParams::new();
+        params.push(&requester_pubkey_bytes); // $1
+        params.push(now as i64); // $2
+        let mut conditions = String::new();
+
+        // Upcoming events include the ones currently in progress
+        if upcoming {
+            conditions.push_str(" AND e.end_time >= $2");
+        } else {
+            conditions.push_str(" AND e.end_time < $2");
+        }
+
+        if let Some(organizer_pubkey_bytes) = &organizer_pubkey_bytes {
+            let organizer_param = params.push(organizer_pubkey_bytes);
+            conditions.push_str(&format!(" AND e.sender_pubkey = {organizer_param}"));
+        }
+
+        conditions.push_str(&Self::cursor_conditions(
+            &mut params,
+            "e.start_time",
+            "e.id",
+            &options,
+        ));
+        let limit_param = params.push(offset_limit);
+
+        let (order_clause, final_order_clause) = if options.sort_descending {
+            (
+                " ORDER BY e.start_time DESC, e.id DESC",
+                " ORDER BY ev.start_time DESC, ev.id DESC",
+            )
+        } else {
+            (
+                " ORDER BY e.start_time ASC, e.id ASC",
+                " ORDER BY ev.start_time ASC, ev.id ASC",
+            )
+        };
+
+        let query = NamedQuery::new(
+            "get_events",
+            &format!(
+                r#"
+            WITH events AS (
+                SELECT e.id, e.transaction_id, e.block_time, e.sender_pubkey, e.sender_signature,
+                       e.base64_encoded_title, e.start_time, e.end_time, e.base64_encoded_location
+                FROM k_events e
+                LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = e.sender_pubkey
+                WHERE kb.blocked_user_pubkey IS NULL{conditions}
+                {order_clause}
+                LIMIT {limit_param}
+            )
+            SELECT ev.id, ev.transaction_id, ev.block_time, ev.sender_pubkey, ev.sender_signature,
+                   ev.base64_encoded_title, ev.start_time, ev.end_time, ev.base64_encoded_location,
+                   COALESCE(r.going_count, 0) as going_count,
+                   COALESCE(r.interested_count, 0) as interested_count,
+                   COALESCE(r.not_going_count, 0) as not_going_count,
+                   r.requester_rsvp,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image
+            FROM events ev
+            LEFT JOIN LATERAL (
+                SELECT COUNT(*) FILTER (WHERE rsvp_status = 'going') as going_count,
+                       COUNT(*) FILTER (WHERE rsvp_status = 'interested') as interested_count,
+                       COUNT(*) FILTER (WHERE rsvp_status = 'not_going') as not_going_count,
+                       MAX(rsvp_status) FILTER (WHERE sender_pubkey = $1) as requester_rsvp
+                FROM k_event_rsvps
+                WHERE event_id = ev.transaction_id
+            ) r ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = ev.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            {final_order_clause}
+            "#,
+                conditions = conditions,
+                order_clause = order_clause,
+                final_order_clause = final_order_clause
+            ),
+        );
+
+        let query_builder = params.bind(query.query());
+
+        let rows = query_builder
+            .fetch_all(pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch events: {}", e)))?;
+
+        let mut events = Vec::new();
+        for row in &rows {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+
+            events.push(KEventRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_title: row.get("base64_encoded_title"),
+                start_time: row.get::<i64, _>("start_time") as u64,
+                end_time: row.get::<i64, _>("end_time") as u64,
+                base64_encoded_location: row.get("base64_encoded_location"),
+                going_count: row.get::<i64, _>("going_count") as u64,
+                interested_count: row.get::<i64, _>("interested_count") as u64,
+                not_going_count: row.get::<i64, _>("not_going_count") as u64,
+                requester_rsvp: row.get("requester_rsvp"),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+            });
+        }
+
+        let has_more = events.len() > limit as usize;
+        if has_more {
+            events.pop();
+        }
+
+        let pagination = self.create_compound_pagination_metadata(&events, limit as u32, has_more);
+
+        Ok(PaginatedResult {
+            items: events,
+            pagination,
+        })
+    }
+
+    /// Get an article by manifest ID with received chunk count and requester block status
+    async fn get_article_by_id(
+        &self,
+        article_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<KArticleRecord>> {
+        let article_id_bytes = Self::decode_hex_to_bytes(article_id)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // Chunks are deleted once the article is finished, so completed articles count all chunks
+        let row = NamedQuery::new(
+            "get_article_by_id",
+            r#"
+            SELECT a.id, a.transaction_id, a.block_time, a.sender_pubkey, a.sender_signature,
+                   a.base64_encoded_title, a.chunk_count, a.content_hash, a.base64_encoded_content,
+                   a.status, a.completed_at,
+                   CASE WHEN a.status = 'complete' THEN a.chunk_count::BIGINT ELSE (
+                       SELECT COUNT(DISTINCT c.chunk_index) FROM k_article_chunks c
+                       WHERE c.manifest_id = a.transaction_id
+                         AND c.sender_pubkey = a.sender_pubkey
+                         AND c.chunk_index < a.chunk_count
+                   ) END as received_chunks,
+                   EXISTS(
+                       SELECT 1 FROM k_blocks kb
+                       WHERE kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = a.sender_pubkey
+                   ) as is_blocked,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image
+            FROM k_articles a
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = a.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            WHERE a.transaction_id = $1
+            "#,
+        )
+        .query()
+        .bind(&article_id_bytes)
+        .bind(&requester_pubkey_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch article by ID: {}", e)))?;
+
+        Ok(row.map(|row| {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+            let content_hash: Vec<u8> = row.get("content_hash");
+
+            KArticleRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_title: row.get("base64_encoded_title"),
+                chunk_count: row.get::<i32, _>("chunk_count") as u32,
+                received_chunks: row.get::<i64, _>("received_chunks") as u32,
+                content_hash: Self::encode_bytes_to_hex(&content_hash),
+                base64_encoded_content: row.get("base64_encoded_content"),
+                status: row.get("status"),
+                completed_at: row
+                    .get::<Option<i64>, _>("completed_at")
+                    .map(|time| time as u64),
+                is_blocked: row.get("is_blocked"),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+            }
+        }))
+    }
+
+    /// Get every pubkey mentioned by a post, reply or quote
+    async fn get_content_mentions(&self, content_id: &str) -> DatabaseResult<Option<Vec<String>>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+
+        let row = NamedQuery::new(
+            "get_content_mentions",
+            r#"
+            SELECT ARRAY(
+                       SELECT encode(m.mentioned_pubkey, 'hex')
+                       FROM k_mentions m
+                       WHERE m.content_id = c.transaction_id AND m.content_type = c.content_type
+                       ORDER BY m.id
+                   ) as mentioned_pubkeys
+            FROM k_contents c
+            WHERE c.transaction_id = $1
+              AND c.content_type IN ('post', 'reply', 'quote')
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch content mentions: {}", e))
+        })?;
+
+        Ok(row.map(|row| row.get("mentioned_pubkeys")))
+    }
+
+    /// Get the tip totals of a post, reply or quote
+    async fn get_content_tips(
+        &self,
+        content_id: &str,
+    ) -> DatabaseResult<Option<KContentTipsRecord>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+
+        let row = NamedQuery::new(
+            "get_content_tips",
+            r#"
+            SELECT c.transaction_id, c.sender_pubkey,
+                   COUNT(t.id) as tips_count,
+                   COUNT(DISTINCT t.sender_pubkey) as tippers_count,
+                   COALESCE(SUM(t.amount), 0)::BIGINT as total_amount
+            FROM k_contents c
+            LEFT JOIN k_tips t ON t.content_id = c.transaction_id
+            WHERE c.transaction_id = $1
+              AND c.content_type IN ('post', 'reply', 'quote')
+            GROUP BY c.transaction_id, c.sender_pubkey
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch content tips: {}", e)))?;
+
+        Ok(row.map(|row| {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+
+            KContentTipsRecord {
+                content_id: Self::encode_bytes_to_hex(&transaction_id),
+                recipient_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                tips_count: row.get::<i64, _>("tips_count") as u64,
+                tippers_count: row.get::<i64, _>("tippers_count") as u64,
+                total_amount: row.get::<i64, _>("total_amount") as u64,
+            }
+        }))
+    }
+
+    /// Get the contents that received the highest tip amount within a time window
+    async fn get_top_tipped_posts(
+        &self,
+        requester_pubkey: &str,
+        from_time: u64,
+        to_time: u64,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KTippedPostRecord>> {
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        let vote_counts = self.vote_counts_join("tt.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("tt.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        let query = NamedQuery::new(
+            "get_top_tipped_posts",
+            &format!(
+                r#"
+            WITH window_tips AS (
+                SELECT t.content_id, COUNT(*) as tips_count, SUM(t.amount)::BIGINT as tips_amount
+                FROM k_tips t
+                WHERE t.block_time >= $2 AND t.block_time <= $3
+                GROUP BY t.content_id
+            ), top_tipped AS (
+                SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
+                       c.sender_signature, c.base64_encoded_message, c.content_type,
+                       c.referenced_content_id, wt.tips_count, wt.tips_amount
+                FROM window_tips wt
+                INNER JOIN k_contents c ON c.transaction_id = wt.content_id
+                {blocks_join}
+                WHERE c.content_type IN ('post', 'reply', 'quote')
+                  AND {not_blocked}{rate_limited_filter}{visibility_filter}
+                ORDER BY wt.tips_amount DESC, c.id DESC
+                LIMIT $4
+            )
+            SELECT tt.id, tt.transaction_id, tt.block_time, tt.sender_pubkey,
+                   tt.sender_signature, tt.base64_encoded_message, tt.content_type,
+                   tt.tips_count, tt.tips_amount,
+                   COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
+                                  WHERE m.content_id = tt.transaction_id AND m.content_type = tt.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = tt.transaction_id AND m.content_type = tt.content_type) as mentions_count,
+                   {render_spans_column},
+                   (SELECT COUNT(*) FROM k_contents r
+                    WHERE r.referenced_content_id = tt.transaction_id AND r.content_type = 'reply') as replies_count,
+                   (SELECT COUNT(*) FROM k_contents q
+                    WHERE q.referenced_content_id = tt.transaction_id AND q.content_type = 'quote') as quotes_count,
+                   COALESCE(v.up_votes_count, 0) as up_votes_count,
+                   COALESCE(v.down_votes_count, 0) as down_votes_count,
+                   COALESCE(v.user_upvoted, false) as is_upvoted,
+                   COALESCE(v.user_downvoted, false) as is_downvoted,
+                   COALESCE(b.base64_encoded_nickname, '') as user_nickname,
+                   b.base64_encoded_profile_image as user_profile_image,
+                   encode(tt.referenced_content_id, 'hex') as referenced_content_id,
+                   ref_c.base64_encoded_message as referenced_message,
+                   encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                   COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
+                   ref_b.base64_encoded_profile_image as referenced_profile_image
+            FROM top_tipped tt
+            {vote_counts}
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts b
+                WHERE b.sender_pubkey = tt.sender_pubkey
+                LIMIT 1
+            ) b ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM k_contents
+                WHERE transaction_id = tt.referenced_content_id
+                  AND tt.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            ORDER BY tt.tips_amount DESC, tt.id DESC
+            "#,
+                rate_limited_filter = self.rate_limited_filter("c"),
+                visibility_filter = self.visibility_filter("c"),
+            ),
+        );
+
+        let mut tx = self.begin_budgeted("get_top_tipped_posts").await?;
+        let rows = query
+            .query()
+            .bind(&requester_pubkey_bytes)
+            .bind(from_time as i64)
+            .bind(to_time as i64)
+            .bind(limit as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to fetch top tipped posts: {}", e))
+            })?;
+
+        let mut items = Vec::new();
+        for row in &rows {
+            let transaction_id: Vec<u8> = row.get("transaction_id");
+            let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
+            let sender_signature: Vec<u8> = row.get("sender_signature");
+            let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
+
+            let post = KPostRecord {
+                id: row.get::<i64, _>("id"),
+                transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                block_time: row.get::<i64, _>("block_time") as u64,
+                sender_pubkey: Self::encode_bytes_to_hex(&sender_pubkey),
+                sender_signature: Self::encode_bytes_to_hex(&sender_signature),
+                base64_encoded_message: row.get("base64_encoded_message"),
+                mentioned_pubkeys: mentioned_pubkeys_raw,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
+                content_type: row.try_get("content_type").ok(),
+                replies_count: Some(row.get::<i64, _>("replies_count") as u64),
+                up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
+                down_votes_count: Some(row.get::<i64, _>("down_votes_count") as u64),
+                quotes_count: Some(row.get::<i64, _>("quotes_count") as u64),
+                is_upvoted: Some(row.get("is_upvoted")),
+                is_downvoted: Some(row.get("is_downvoted")),
+                user_nickname: Some(row.get("user_nickname")),
+                user_profile_image: row.get("user_profile_image"),
+                referenced_content_id: row.try_get("referenced_content_id").ok(),
+                referenced_message: row.try_get("referenced_message").ok(),
+                referenced_sender_pubkey: row.try_get("referenced_sender_pubkey").ok(),
+                referenced_nickname: row.try_get("referenced_nickname").ok(),
+                referenced_profile_image: row.try_get("referenced_profile_image").ok(),
+            };
+
+            items.push(KTippedPostRecord {
+                post,
+                tips_count: row.get::<i64, _>("tips_count") as u64,
+                tips_amount: row.get::<i64, _>("tips_amount") as u64,
+            });
+        }
+
+        Ok(items)
+    }
+
+    /// Record a view in the HyperLogLog registers of a post, reply or quote
+    async fn record_post_view(
+        &self,
+        content_id: &str,
+        register_index: usize,
+        rank: u8,
+    ) -> DatabaseResult<()> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+
+        // Registers only grow: repeated views of the same viewer do not write anything
+        NamedQuery::new(
+            "record_post_view",
+            r#"
+            INSERT INTO k_post_views (content_id, registers, updated_at)
+            SELECT $1, set_byte(decode(repeat('00', 1024), 'hex'), $2, $3), $4
+            WHERE EXISTS (
+                SELECT 1 FROM k_contents
+                WHERE transaction_id = $1 AND content_type IN ('post', 'reply', 'quote')
+            )
+            ON CONFLICT (content_id) DO UPDATE
+            SET registers = set_byte(k_post_views.registers, $2, $3),
+                updated_at = EXCLUDED.updated_at
+            WHERE get_byte(k_post_views.registers, $2) < $3
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .bind(register_index as i32)
+        .bind(rank as i32)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to record post view: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Get the HyperLogLog registers of a post, reply or quote
+    async fn get_post_view_registers(&self, content_id: &str) -> DatabaseResult<Option<Vec<u8>>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+
+        NamedQuery::new(
+            "get_post_view_registers",
+            "SELECT registers FROM k_post_views WHERE content_id = $1",
+        )
+        .query_scalar()
+        .bind(&content_id_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch post view registers: {}", e))
+        })
+    }
+
+    async fn get_vote_weights(
+        &self,
+        content_ids: &[String],
+    ) -> DatabaseResult<Vec<(String, u64, u64)>> {
+        if content_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let content_ids_bytes = content_ids
+            .iter()
+            .map(|id| Self::decode_hex_to_bytes(id))
+            .collect::<DatabaseResult<Vec<Vec<u8>>>>()?;
+
+        let rows = NamedQuery::new(
+            "get_vote_weights",
+            r#"
+            SELECT encode(post_id, 'hex') as post_id, up_votes_fee, down_votes_fee
+            FROM k_vote_totals
+            WHERE post_id = ANY($1)
+            "#,
+        )
+        .query()
+        .bind(&content_ids_bytes)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch vote weights: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get("post_id"),
+                    row.get::<i64, _>("up_votes_fee").max(0) as u64,
+                    row.get::<i64, _>("down_votes_fee").max(0) as u64,
+                )
+            })
+            .collect())
+    }
+
+    /// Get the operator feature flags
+    async fn get_feature_flags(&self) -> DatabaseResult<Vec<FeatureFlagRecord>> {
+        let rows = NamedQuery::new(
+            "get_feature_flags",
+            "SELECT name, enabled, rollout_percent FROM k_feature_flags",
+        )
+        .query()
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch feature flags: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FeatureFlagRecord {
+                name: row.get("name"),
+                enabled: row.get("enabled"),
+                rollout_percent: row.get("rollout_percent"),
+            })
+            .collect())
+    }
+
+    /// Record a pending erasure request
+    async fn create_erasure_request(
+        &self,
+        subject_pubkey: &str,
+        reason: Option<&str>,
+    ) -> DatabaseResult<KErasureRequestRecord> {
+        let subject_pubkey_bytes = Self::decode_hex_to_bytes(subject_pubkey)?;
+        let requested_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        let row = NamedQuery::new(
+            "create_erasure_request",
+            r#"
+            INSERT INTO k_erasure_requests (subject_pubkey, reason, requested_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, subject_pubkey, reason, status, requested_at, completed_at,
+                      removed_rows, tombstoned_transactions
+            "#,
+        )
+        .query()
+        .bind(&subject_pubkey_bytes)
+        .bind(reason)
+        .bind(requested_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to create erasure request: {}", e))
+        })?;
+
+        Ok(Self::erasure_request_from_row(&row))
+    }
+
+    /// Get an erasure request by id
+    async fn get_erasure_request(
+        &self,
+        request_id: i64,
+    ) -> DatabaseResult<Option<KErasureRequestRecord>> {
+        let row = NamedQuery::new(
+            "get_erasure_request",
+            r#"
+            SELECT id, subject_pubkey, reason, status, requested_at, completed_at,
+                   removed_rows, tombstoned_transactions
+            FROM k_erasure_requests
+            WHERE id = $1
+            "#,
+        )
+        .query()
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch erasure request: {}", e))
+        })?;
+
+        Ok(row.map(|row| Self::erasure_request_from_row(&row)))
+    }
+
+    /// Get messages posted verbatim by many different pubkeys within a time window
+    async fn get_duplicate_contents(
+        &self,
+        from_time: u64,
+        to_time: u64,
+        min_senders: u32,
+        max_senders: u32,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KDuplicateContentRecord>> {
+        let mut tx = self.begin_budgeted("get_duplicate_contents").await?;
+        let rows = NamedQuery::new(
+            "get_duplicate_contents",
+            r#"
+            WITH duplicates AS (
+                SELECT
+                    message_hash,
+                    COUNT(DISTINCT sender_pubkey) AS distinct_senders,
+                    COUNT(*) AS content_count,
+                    MIN(block_time) AS first_block_time,
+                    MAX(block_time) AS last_block_time,
+                    (ARRAY_AGG(content_id ORDER BY block_time, content_id))[1] AS sample_content_id,
+                    (ARRAY_AGG(DISTINCT sender_pubkey))[1:$4] AS sender_pubkeys
+                FROM k_content_hashes
+                WHERE block_time >= $1 AND block_time <= $2
+                GROUP BY message_hash
+                HAVING COUNT(DISTINCT sender_pubkey) >= $3
+            )
+            SELECT d.message_hash, d.distinct_senders, d.content_count, d.first_block_time,
+                   d.last_block_time, d.sample_content_id, d.sender_pubkeys,
+                   kc.base64_encoded_message AS sample_message
+            FROM duplicates d
+            JOIN k_contents kc ON kc.transaction_id = d.sample_content_id
+            ORDER BY d.distinct_senders DESC, d.last_block_time DESC, d.message_hash
+            LIMIT $5
+            "#,
+        )
+        .query()
+        .bind(from_time as i64)
+        .bind(to_time as i64)
+        .bind(min_senders as i64)
+        .bind(max_senders as i32)
+        .bind(limit as i64)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch duplicate contents: {}", e))
+        })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let message_hash: Vec<u8> = row.get("message_hash");
+                let sample_content_id: Vec<u8> = row.get("sample_content_id");
+                let sender_pubkeys: Vec<Vec<u8>> = row.get("sender_pubkeys");
+
+                KDuplicateContentRecord {
+                    message_hash: Self::encode_bytes_to_hex(&message_hash),
+                    sample_content_id: Self::encode_bytes_to_hex(&sample_content_id),
+                    sample_message: row.get("sample_message"),
+                    distinct_senders: row.get::<i64, _>("distinct_senders") as u64,
+                    content_count: row.get::<i64, _>("content_count") as u64,
+                    first_block_time: row.get::<i64, _>("first_block_time") as u64,
+                    last_block_time: row.get::<i64, _>("last_block_time") as u64,
+                    sender_pubkeys: sender_pubkeys
+                        .iter()
+                        .map(|pubkey| Self::encode_bytes_to_hex(pubkey))
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// Get per-table storage usage with the snapshot to compare it with
+    async fn get_table_storage(
+        &self,
+        compare_before: u64,
+    ) -> DatabaseResult<Vec<KTableStorageRecord>> {
+        let rows = NamedQuery::new(
+            "get_table_storage",
+            r#"
+            WITH usage AS (
+                SELECT relname::text AS table_name, n_live_tup AS row_count,
+                       pg_total_relation_size(relid) AS total_bytes
+                FROM pg_stat_user_tables
+                WHERE schemaname = current_schema() AND relname LIKE 'k\_%'
+            )
+            SELECT u.table_name, u.row_count, u.total_bytes,
+                   p.taken_at AS previous_taken_at,
+                   p.row_count AS previous_row_count,
+                   p.total_bytes AS previous_total_bytes
+            FROM usage u
+            LEFT JOIN LATERAL (
+                SELECT taken_at, row_count, total_bytes
+                FROM k_storage_snapshots s
+                WHERE s.table_name = u.table_name AND s.taken_at <= $1
+                ORDER BY s.taken_at DESC
+                LIMIT 1
+            ) p ON true
+            ORDER BY u.total_bytes DESC, u.table_name
+            "#,
+        )
+        .query()
+        .bind(compare_before as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch table storage: {}", e)))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| KTableStorageRecord {
+                table_name: row.get("table_name"),
+                row_count: row.get::<i64, _>("row_count").max(0) as u64,
+                total_bytes: row.get::<i64, _>("total_bytes") as u64,
+                previous_taken_at: row
+                    .get::<Option<i64>, _>("previous_taken_at")
+                    .map(|time| time as u64),
+                previous_row_count: row
+                    .get::<Option<i64>, _>("previous_row_count")
+                    .map(|count| count as u64),
+                previous_total_bytes: row
+                    .get::<Option<i64>, _>("previous_total_bytes")
+                    .map(|bytes| bytes as u64),
+            })
+            .collect())
+    }
+
+    async fn get_query_stats(&self, limit: u32) -> DatabaseResult<Option<Vec<KQueryStatsRecord>>> {
+        let installed: bool = NamedQuery::new(
+            "get_query_stats",
+            "SELECT to_regclass('pg_stat_statements') IS NOT NULL",
+        )
+        .query_scalar()
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        if !installed {
+            return Ok(None);
+        }
+
+        // Every query of a method starts with its /* k:<method> */ comment (NamedQuery); dynamic
+        // queries of one method are recorded as several statements and summed up
+        let result = NamedQuery::new(
+            "get_query_stats",
+            r#"
+            WITH statements AS (
+                SELECT substring(query FROM '/\* k:([a-z_:]+) \*/') AS method,
+                       calls, total_exec_time, max_exec_time, rows,
+                       shared_blks_hit, shared_blks_read
+                FROM pg_stat_statements
+                WHERE dbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+            )
+            SELECT method,
+                   SUM(calls)::BIGINT AS calls,
+                   SUM(total_exec_time) AS total_time_ms,
+                   MAX(max_exec_time) AS max_time_ms,
+                   SUM(rows)::BIGINT AS rows,
+                   SUM(shared_blks_hit)::BIGINT AS shared_blocks_hit,
+                   SUM(shared_blks_read)::BIGINT AS shared_blocks_read,
+                   (SELECT SUM(total_exec_time) FROM statements) AS database_time_ms
+            FROM statements
+            WHERE method IS NOT NULL
+            GROUP BY method
+            ORDER BY total_time_ms DESC
+            LIMIT $1
+            "#,
+        )
+        .query()
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await;
+
+        let rows = match result {
+            Ok(rows) => rows,
+            // Extension created but missing from shared_preload_libraries
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("55000") => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(DatabaseError::QueryError(format!(
+                    "Failed to fetch query stats: {}",
+                    e
+                )));
+            }
+        };
+
+        Ok(Some(
+            rows.iter()
+                .map(|row| KQueryStatsRecord {
+                    method: row.get("method"),
+                    calls: row.get::<i64, _>("calls") as u64,
+                    total_time_ms: row.get("total_time_ms"),
+                    max_time_ms: row.get("max_time_ms"),
+                    rows: row.get::<i64, _>("rows") as u64,
+                    shared_blocks_hit: row.get::<i64, _>("shared_blocks_hit") as u64,
+                    shared_blocks_read: row.get::<i64, _>("shared_blocks_read") as u64,
+                    database_time_ms: row.get("database_time_ms"),
+                })
+                .collect(),
+        ))
+    }
+
+    async fn get_archived_content_by_id(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<(KPostRecord, bool)>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+        let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
+
+        // Quoted content may itself be live or archived
+        let row = NamedQuery::new(
+            "get_archived_content_by_id",
+            r#"
+            SELECT
+                a.id,
+                a.transaction_id,
+                a.block_time,
+                a.sender_pubkey,
+                a.sender_signature,
+                a.base64_encoded_message,
+                COALESCE(
+                    ARRAY(
+                        SELECT m.mentioned_pubkey
+                        FROM k_mentions_archive m
+                        WHERE m.content_id = a.transaction_id AND m.content_type = a.content_type
+                    ),
+                    ARRAY[]::bytea[]
+                ) as mentioned_pubkeys,
+                user_profile.base64_encoded_nickname as user_nickname,
+                user_profile.base64_encoded_profile_image as user_profile_image,
+                encode(a.referenced_content_id, 'hex') as ref_content_id,
+                ref_c.base64_encoded_message as referenced_message,
+                encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
+                ref_b.base64_encoded_nickname as referenced_nickname,
+                ref_b.base64_encoded_profile_image as referenced_profile_image,
+                kb.blocked_user_pubkey IS NOT NULL as is_blocked
+            FROM k_contents_archive a
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = a.sender_pubkey
+                LIMIT 1
+            ) user_profile ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_message, sender_pubkey
+                FROM (
+                    SELECT base64_encoded_message, sender_pubkey
+                    FROM k_contents
+                    WHERE transaction_id = a.referenced_content_id
+                    UNION ALL
+                    SELECT base64_encoded_message, sender_pubkey
+                    FROM k_contents_archive
+                    WHERE transaction_id = a.referenced_content_id
+                ) referenced
+                WHERE a.content_type = 'quote'
+                LIMIT 1
+            ) ref_c ON true
+            LEFT JOIN LATERAL (
+                SELECT base64_encoded_nickname, base64_encoded_profile_image
+                FROM k_broadcasts
+                WHERE sender_pubkey = ref_c.sender_pubkey
+                LIMIT 1
+            ) ref_b ON ref_c.sender_pubkey IS NOT NULL
+            LEFT JOIN k_blocks kb ON kb.sender_pubkey = $2 AND kb.blocked_user_pubkey = a.sender_pubkey
+            WHERE a.transaction_id = $1
+            LIMIT 1
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .bind(&requester_pubkey_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch archived content: {}", e))
+        })?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let mentioned_pubkeys_bytes: Vec<Vec<u8>> = row.get("mentioned_pubkeys");
+        let post_record = KPostRecord {
+            id: row.get("id"),
+            transaction_id: Self::encode_bytes_to_hex(&row.get::<Vec<u8>, _>("transaction_id")),
+            block_time: row.get::<i64, _>("block_time") as u64,
+            sender_pubkey: Self::encode_bytes_to_hex(&row.get::<Vec<u8>, _>("sender_pubkey")),
+            sender_signature: Self::encode_bytes_to_hex(&row.get::<Vec<u8>, _>("sender_signature")),
+            base64_encoded_message: row.get("base64_encoded_message"),
+            mentioned_pubkeys: mentioned_pubkeys_bytes
+                .iter()
+                .map(|bytes| Self::encode_bytes_to_hex(bytes))
+                .collect(),
+            mentions_count: None,
+            render_spans: None,
+            content_type: None,
+            // Replies and votes of archived contents are purged with them
+            replies_count: None,
+            up_votes_count: None,
+            down_votes_count: None,
+            quotes_count: None,
+            is_upvoted: None,
+            is_downvoted: None,
+            user_nickname: row.get("user_nickname"),
+            user_profile_image: row.get("user_profile_image"),
+            referenced_content_id: row.get("ref_content_id"),
+            referenced_message: row.get("referenced_message"),
+            referenced_sender_pubkey: row.get("referenced_sender_pubkey"),
+            referenced_nickname: row.get("referenced_nickname"),
+            referenced_profile_image: row.get("referenced_profile_image"),
+        };
+
+        Ok(Some((post_record, row.get("is_blocked"))))
+    }
+
+    async fn get_profile_diff(
+        &self,
+        user_pubkey: &str,
+        from_time: u64,
+        to_time: u64,
+    ) -> DatabaseResult<KProfileDiffRecord> {
+        let user_pubkey_bytes = Self::decode_hex_to_bytes(user_pubkey)?;
+
+        let row = NamedQuery::new(
+            "get_profile_diff",
+            r#"
+            SELECT
+                f.transaction_id AS from_transaction_id,
+                f.block_time AS from_block_time,
+                f.base64_encoded_nickname AS from_nickname,
+                f.profile_image_hash AS from_image_hash,
+                f.base64_encoded_message AS from_message,
+                t.transaction_id AS to_transaction_id,
+                t.block_time AS to_block_time,
+                t.base64_encoded_nickname AS to_nickname,
+                t.profile_image_hash AS to_image_hash,
+                t.base64_encoded_message AS to_message,
+                (
+                    SELECT COUNT(*)
+                    FROM k_profile_history
+                    WHERE sender_pubkey = $1 AND block_time > $2 AND block_time <= $3
+                ) AS updates_count
+            FROM (SELECT 1) AS requested
+            LEFT JOIN LATERAL (
+                SELECT transaction_id, block_time, base64_encoded_nickname, profile_image_hash,
+                       base64_encoded_message
+                FROM k_profile_history
+                WHERE sender_pubkey = $1 AND block_time <= $2
+                ORDER BY block_time DESC, id DESC
+                LIMIT 1
+            ) f ON true
+            LEFT JOIN LATERAL (
+                SELECT transaction_id, block_time, base64_encoded_nickname, profile_image_hash,
+                       base64_encoded_message
+                FROM k_profile_history
+                WHERE sender_pubkey = $1 AND block_time <= $3
+                ORDER BY block_time DESC, id DESC
+                LIMIT 1
+            ) t ON true
+            "#,
+        )
+        .query()
+        .bind(&user_pubkey_bytes)
+        .bind(from_time as i64)
+        .bind(to_time as i64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch profile diff: {}", e)))?;
+
+        let snapshot = |prefix: &str| {
+            row.get::<Option<Vec<u8>>, _>(format!("{}_transaction_id", prefix).as_str())
+                .map(|transaction_id| KProfileSnapshotRecord {
+                    transaction_id: Self::encode_bytes_to_hex(&transaction_id),
+                    block_time: row.get::<i64, _>(format!("{}_block_time", prefix).as_str()) as u64,
+                    base64_encoded_nickname: row.get(format!("{}_nickname", prefix).as_str()),
+                    profile_image_hash: row
+                        .get::<Option<Vec<u8>>, _>(format!("{}_image_hash", prefix).as_str())
+                        .map(|hash| Self::encode_bytes_to_hex(&hash)),
+                    base64_encoded_message: row.get(format!("{}_message", prefix).as_str()),
+                })
+        };
+
+        Ok(KProfileDiffRecord {
+            from_profile: snapshot("from"),
+            to_profile: snapshot("to"),
+            updates_count: row.get::<i64, _>("updates_count") as u64,
         })
     }
+    /// Get impersonation flags by review status
+    async fn get_impersonation_flags(
+        &self,
+        status: &str,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KImpersonationFlagRecord>> {
+        let rows = NamedQuery::new(
+            "get_impersonation_flags",
+            r#"
+            SELECT id, suspect_pubkey, base64_encoded_suspect_nickname, target_pubkey,
+                   base64_encoded_target_nickname, target_followers, similarity, transaction_id,
+                   flagged_at, status, reviewed_at
+            FROM k_impersonation_flags
+            WHERE status = $1
+            ORDER BY flagged_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .query()
+        .bind(status)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch impersonation flags: {}", e))
+        })?;
 
-    async fn get_network(&self) -> DatabaseResult<String> {
-        self.get_network_from_db()
-            .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))
+        Ok(rows.iter().map(Self::impersonation_flag_from_row).collect())
     }
 
-    async fn get_users_count(&self) -> DatabaseResult<u64> {
-        let row = sqlx::query(
+    /// Record the review outcome of an impersonation flag
+    async fn review_impersonation_flag(
+        &self,
+        flag_id: i64,
+        status: &str,
+    ) -> DatabaseResult<Option<KImpersonationFlagRecord>> {
+        let reviewed_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        let row = NamedQuery::new(
+            "review_impersonation_flag",
             r#"
-            SELECT COUNT(*) as count FROM k_broadcasts
+            UPDATE k_impersonation_flags
+            SET status = $2, reviewed_at = $3
+            WHERE id = $1
+            RETURNING id, suspect_pubkey, base64_encoded_suspect_nickname, target_pubkey,
+                      base64_encoded_target_nickname, target_followers, similarity, transaction_id,
+                      flagged_at, status, reviewed_at
             "#,
         )
-        .fetch_one(&self.pool)
+        .query()
+        .bind(flag_id)
+        .bind(status)
+        .bind(reviewed_at)
+        .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to review impersonation flag: {}", e))
+        })?;
 
-        let count: i64 = row.get("count");
-        Ok(count as u64)
+        Ok(row.map(|row| Self::impersonation_flag_from_row(&row)))
     }
 
-    async fn get_stats(&self) -> DatabaseResult<crate::database_trait::DatabaseStats> {
-        let row = sqlx::query(
+    /// Get the users of a normalized nickname across all profile broadcasts
+    async fn get_nickname_history(
+        &self,
+        normalized_nickname: &str,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KNicknameUseRecord>> {
+        let rows = NamedQuery::new(
+            "get_nickname_history",
             r#"
-            SELECT
-                (SELECT COUNT(*) FROM k_broadcasts) as broadcasts_count,
-                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'post') as posts_count,
-                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'reply') as replies_count,
-                (SELECT COUNT(*) FROM k_contents WHERE content_type = 'quote') as quotes_count,
-                (SELECT COUNT(*) FROM k_votes) as votes_count,
-                (SELECT COUNT(*) FROM k_follows) as follows_count,
-                (SELECT COUNT(*) FROM k_blocks) as blocks_count
+            SELECT h.sender_pubkey,
+                   (ARRAY_AGG(h.base64_encoded_nickname ORDER BY h.block_time DESC))[1] AS base64_encoded_nickname,
+                   MIN(h.block_time) AS first_used,
+                   MAX(h.block_time) AS last_used,
+                   COUNT(*) AS broadcasts_count,
+                   (SELECT b.base64_encoded_nickname FROM k_broadcasts b
+                    WHERE b.sender_pubkey = h.sender_pubkey) AS base64_encoded_current_nickname
+            FROM k_profile_history h
+            WHERE h.normalized_nickname = $1
+            GROUP BY h.sender_pubkey
+            ORDER BY first_used ASC, h.sender_pubkey
+            LIMIT $2
             "#,
         )
-        .fetch_one(&self.pool)
+        .query()
+        .bind(normalized_nickname)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to fetch nickname history: {}", e)))?;
 
-        Ok(crate::database_trait::DatabaseStats {
-            broadcasts_count: row.get("broadcasts_count"),
-            posts_count: row.get("posts_count"),
-            replies_count: row.get("replies_count"),
-            quotes_count: row.get("quotes_count"),
-            votes_count: row.get("votes_count"),
-            follows_count: row.get("follows_count"),
-            blocks_count: row.get("blocks_count"),
-        })
+        Ok(rows
+            .iter()
+            .map(|row| KNicknameUseRecord {
+                sender_pubkey: Self::encode_bytes_to_hex(&row.get::<Vec<u8>, _>("sender_pubkey")),
+                base64_encoded_nickname: row.get("base64_encoded_nickname"),
+                first_used: row.get::<i64, _>("first_used") as u64,
+                last_used: row.get::<i64, _>("last_used") as u64,
+                broadcasts_count: row.get::<i64, _>("broadcasts_count") as u64,
+                base64_encoded_current_nickname: row.get("base64_encoded_current_nickname"),
+            })
+            .collect())
     }
 
-    /// Get content (posts, replies, quotes) containing a specific hashtag
-    async fn get_hashtag_content(
+    /// Get the featured contents with full post metadata, in the order chosen by the operator
+    async fn get_featured_posts(
         &self,
-        hashtag: &str,
         requester_pubkey: &str,
-        options: QueryOptions,
-    ) -> DatabaseResult<PaginatedResult<KPostRecord>> {
+        limit: u32,
+    ) -> DatabaseResult<Vec<KFeaturedPostRecord>> {
         let requester_pubkey_bytes = Self::decode_hex_to_bytes(requester_pubkey)?;
-        let limit = options.limit.unwrap_or(20) as i64;
-        let offset_limit = limit + 1; // Get one extra to check if there are more
-
-        let mut bind_count = 2; // $1 for requester_pubkey, $2 for hashtag
-        let mut cursor_conditions = String::new();
-
-        // Add cursor logic
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time < ${} OR (c.block_time = ${} AND c.id < ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                bind_count += 2;
-                cursor_conditions.push_str(&format!(
-                    " AND (c.block_time > ${} OR (c.block_time = ${} AND c.id > ${}))",
-                    bind_count - 1,
-                    bind_count - 1,
-                    bind_count
-                ));
-            }
-        }
-
-        let order_clause = if options.sort_descending {
-            " ORDER BY c.block_time DESC, c.id DESC"
-        } else {
-            " ORDER BY c.block_time ASC, c.id ASC"
-        };
-
-        let final_order_clause = if options.sort_descending {
-            " ORDER BY ps.block_time DESC, ps.id DESC"
-        } else {
-            " ORDER BY ps.block_time ASC, ps.id ASC"
-        };
 
-        let query = format!(
-            r#"
-            WITH hashtag_content AS (
+        let vote_counts = self.vote_counts_join("fc.transaction_id", "$1");
+        let render_spans_column = self.render_spans_column("fc.transaction_id");
+        let (blocks_join, not_blocked) =
+            self.blocked_authors_join(&requester_pubkey_bytes, "$1", "c.sender_pubkey", None);
+        // Chosen by the operator: listed regardless of the sender rate limit and the visibility
+        // watermark, only the requester's own blocks apply
+        let query = NamedQuery::new(
+            "get_featured_posts",
+            &format!(
+                r#"
+            WITH featured_contents AS (
                 SELECT c.id, c.transaction_id, c.block_time, c.sender_pubkey,
                        c.sender_signature, c.base64_encoded_message, c.content_type,
-                       c.referenced_content_id
-                FROM k_contents c
-                INNER JOIN k_hashtags h ON h.content_id = c.transaction_id
-                LEFT JOIN k_blocks kb ON kb.sender_pubkey = $1 AND kb.blocked_user_pubkey = c.sender_pubkey
-                WHERE h.hashtag = $2
-                  AND c.content_type IN ('post', 'reply', 'quote')
-                  AND kb.blocked_user_pubkey IS NULL{cursor_conditions}
-                {order_clause}
-                LIMIT ${limit_param}
-            ), content_stats AS (
-                SELECT hc.id, hc.transaction_id, hc.block_time, hc.sender_pubkey,
-                       hc.sender_signature, hc.base64_encoded_message, hc.content_type,
-                       hc.referenced_content_id,
-                       COALESCE(r.replies_count, 0) as replies_count,
-                       COALESCE(q.quotes_count, 0) as quotes_count,
-                       COALESCE(v.up_votes_count, 0) as up_votes_count,
-                       COALESCE(v.down_votes_count, 0) as down_votes_count,
-                       COALESCE(v.user_upvoted, false) as is_upvoted,
-                       COALESCE(v.user_downvoted, false) as is_downvoted
-                FROM hashtag_content hc
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as replies_count
-                    FROM k_contents r
-                    WHERE r.content_type = 'reply'
-                    GROUP BY referenced_content_id
-                ) r ON hc.transaction_id = r.referenced_content_id
-                LEFT JOIN (
-                    SELECT referenced_content_id, COUNT(*) as quotes_count
-                    FROM k_contents qt
-                    WHERE qt.content_type = 'quote'
-                    GROUP BY referenced_content_id
-                ) q ON hc.transaction_id = q.referenced_content_id
-                LEFT JOIN (
-                    SELECT post_id,
-                           COUNT(*) FILTER (WHERE vote = 'upvote') as up_votes_count,
-                           COUNT(*) FILTER (WHERE vote = 'downvote') as down_votes_count,
-                           bool_or(vote = 'upvote' AND sender_pubkey = $1) as user_upvoted,
-                           bool_or(vote = 'downvote' AND sender_pubkey = $1) as user_downvoted
-                    FROM k_votes v
-                    GROUP BY post_id
-                ) v ON hc.transaction_id = v.post_id
+                       c.referenced_content_id, f.position, f.note, f.featured_at
+                FROM k_featured f
+                INNER JOIN k_contents c ON c.transaction_id = f.content_id
+                {blocks_join}
+                WHERE c.content_type IN ('post', 'reply', 'quote')
+                  AND {not_blocked}
+                ORDER BY f.position ASC, f.featured_at DESC
+                LIMIT $2
             )
-            SELECT ps.id, ps.transaction_id, ps.block_time, ps.sender_pubkey,
-                   ps.sender_signature, ps.base64_encoded_message, ps.content_type,
+            SELECT fc.id, fc.transaction_id, fc.block_time, fc.sender_pubkey,
+                   fc.sender_signature, fc.base64_encoded_message, fc.content_type,
+                   fc.position, fc.note, fc.featured_at,
                    COALESCE(ARRAY(SELECT encode(m.mentioned_pubkey, 'hex') FROM k_mentions m
-                                  WHERE m.content_id = ps.transaction_id AND m.content_type = ps.content_type), '{{}}') as mentioned_pubkeys,
-                   ps.replies_count, ps.quotes_count, ps.up_votes_count, ps.down_votes_count,
-                   ps.is_upvoted, ps.is_downvoted,
+                                  WHERE m.content_id = fc.transaction_id AND m.content_type = fc.content_type
+                                  ORDER BY m.id LIMIT {MAX_LISTED_MENTIONS}), '{{}}') as mentioned_pubkeys,
+                   (SELECT COUNT(*) FROM k_mentions m
+                    WHERE m.content_id = fc.transaction_id AND m.content_type = fc.content_type) as mentions_count,
+                   {render_spans_column},
+                   (SELECT COUNT(*) FROM k_contents r
+                    WHERE r.referenced_content_id = fc.transaction_id AND r.content_type = 'reply') as replies_count,
+                   (SELECT COUNT(*) FROM k_contents q
+                    WHERE q.referenced_content_id = fc.transaction_id AND q.content_type = 'quote') as quotes_count,
+                   COALESCE(v.up_votes_count, 0) as up_votes_count,
+                   COALESCE(v.down_votes_count, 0) as down_votes_count,
+                   COALESCE(v.user_upvoted, false) as is_upvoted,
+                   COALESCE(v.user_downvoted, false) as is_downvoted,
                    COALESCE(b.base64_encoded_nickname, '') as user_nickname,
                    b.base64_encoded_profile_image as user_profile_image,
-                   encode(ps.referenced_content_id, 'hex') as referenced_content_id,
+                   encode(fc.referenced_content_id, 'hex') as referenced_content_id,
                    ref_c.base64_encoded_message as referenced_message,
                    encode(ref_c.sender_pubkey, 'hex') as referenced_sender_pubkey,
                    COALESCE(ref_b.base64_encoded_nickname, '') as referenced_nickname,
                    ref_b.base64_encoded_profile_image as referenced_profile_image
-            FROM content_stats ps
+            FROM featured_contents fc
+            {vote_counts}
             LEFT JOIN LATERAL (
                 SELECT base64_encoded_nickname, base64_encoded_profile_image
                 FROM k_broadcasts b
-                WHERE b.sender_pubkey = ps.sender_pubkey
+                WHERE b.sender_pubkey = fc.sender_pubkey
                 LIMIT 1
             ) b ON true
             LEFT JOIN LATERAL (
                 SELECT base64_encoded_message, sender_pubkey
                 FROM k_contents
-                WHERE transaction_id = ps.referenced_content_id
-                  AND ps.content_type = 'quote'
+                WHERE transaction_id = fc.referenced_content_id
+                  AND fc.content_type = 'quote'
                 LIMIT 1
             ) ref_c ON true
             LEFT JOIN LATERAL (
@@ -3460,63 +6843,30 @@ impl DatabaseInterface for PostgresDbManager {
                 WHERE sender_pubkey = ref_c.sender_pubkey
                 LIMIT 1
             ) ref_b ON ref_c.sender_pubkey IS NOT NULL
-            WHERE 1=1
-            {final_order_clause}
-            "#,
-            cursor_conditions = cursor_conditions,
-            order_clause = order_clause,
-            final_order_clause = final_order_clause,
-            limit_param = bind_count + 1
+            ORDER BY fc.position ASC, fc.featured_at DESC
+            "#
+            ),
         );
 
-        // Build query with parameter binding
-        let mut query_builder = sqlx::query(&query)
+        let mut tx = self.begin_budgeted("get_featured_posts").await?;
+        let rows = query
+            .query()
             .bind(&requester_pubkey_bytes)
-            .bind(hashtag);
-
-        // Add cursor parameters if present
-        if let Some(before_cursor) = &options.before {
-            if let Ok((before_timestamp, before_id)) = Self::parse_compound_cursor(before_cursor) {
-                query_builder = query_builder.bind(before_timestamp as i64).bind(before_id);
-            }
-        }
-
-        if let Some(after_cursor) = &options.after {
-            if let Ok((after_timestamp, after_id)) = Self::parse_compound_cursor(after_cursor) {
-                query_builder = query_builder.bind(after_timestamp as i64).bind(after_id);
-            }
-        }
-
-        query_builder = query_builder.bind(offset_limit);
-
-        let rows = query_builder.fetch_all(&self.pool).await.map_err(|e| {
-            DatabaseError::QueryError(format!("Failed to fetch hashtag content: {}", e))
-        })?;
+            .bind(limit as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                DatabaseError::QueryError(format!("Failed to fetch featured posts: {}", e))
+            })?;
 
-        // Process results and build pagination
         let mut items = Vec::new();
-        let mut has_more = false;
-
-        for (index, row) in rows.iter().enumerate() {
-            if index >= limit as usize {
-                has_more = true;
-                break;
-            }
-
+        for row in &rows {
             let transaction_id: Vec<u8> = row.get("transaction_id");
             let sender_pubkey: Vec<u8> = row.get("sender_pubkey");
             let sender_signature: Vec<u8> = row.get("sender_signature");
             let mentioned_pubkeys_raw: Vec<String> = row.get("mentioned_pubkeys");
 
-            let referenced_content_id: Option<String> = row.try_get("referenced_content_id").ok();
-            let referenced_message: Option<String> = row.try_get("referenced_message").ok();
-            let referenced_sender_pubkey: Option<String> =
-                row.try_get("referenced_sender_pubkey").ok();
-            let referenced_nickname: Option<String> = row.try_get("referenced_nickname").ok();
-            let referenced_profile_image: Option<String> =
-                row.try_get("referenced_profile_image").ok();
-
-            let record = KPostRecord {
+            let post = KPostRecord {
                 id: row.get::<i64, _>("id"),
                 transaction_id: Self::encode_bytes_to_hex(&transaction_id),
                 block_time: row.get::<i64, _>("block_time") as u64,
@@ -3524,6 +6874,8 @@ impl DatabaseInterface for PostgresDbManager {
                 sender_signature: Self::encode_bytes_to_hex(&sender_signature),
                 base64_encoded_message: row.get("base64_encoded_message"),
                 mentioned_pubkeys: mentioned_pubkeys_raw,
+                mentions_count: Some(row.get::<i64, _>("mentions_count") as u64),
+                render_spans: row.get("render_spans"),
                 content_type: row.try_get("content_type").ok(),
                 replies_count: Some(row.get::<i64, _>("replies_count") as u64),
                 up_votes_count: Some(row.get::<i64, _>("up_votes_count") as u64),
@@ -3533,86 +6885,144 @@ impl DatabaseInterface for PostgresDbManager {
                 is_downvoted: Some(row.get("is_downvoted")),
                 user_nickname: Some(row.get("user_nickname")),
                 user_profile_image: row.get("user_profile_image"),
-                referenced_content_id,
-                referenced_message,
-                referenced_sender_pubkey,
-                referenced_nickname,
-                referenced_profile_image,
+                referenced_content_id: row.try_get("referenced_content_id").ok(),
+                referenced_message: row.try_get("referenced_message").ok(),
+                referenced_sender_pubkey: row.try_get("referenced_sender_pubkey").ok(),
+                referenced_nickname: row.try_get("referenced_nickname").ok(),
+                referenced_profile_image: row.try_get("referenced_profile_image").ok(),
             };
 
-            items.push(record);
+            items.push(KFeaturedPostRecord {
+                featured: KFeaturedRecord {
+                    content_id: post.transaction_id.clone(),
+                    position: row.get("position"),
+                    note: row.get("note"),
+                    featured_at: row.get::<i64, _>("featured_at") as u64,
+                    available: true,
+                },
+                post,
+            });
         }
 
-        // Build pagination metadata
-        let pagination = if items.is_empty() {
-            PaginationMetadata {
-                has_more: false,
-                next_cursor: None,
-                prev_cursor: None,
-            }
-        } else {
-            let first_item = items.first().unwrap();
-            let last_item = items.last().unwrap();
+        Ok(items)
+    }
 
-            let next_cursor = if has_more {
-                Some(Self::create_compound_cursor(
-                    last_item.block_time,
-                    last_item.id,
-                ))
-            } else {
-                None
-            };
+    /// Get the featured entries for the operator, by position then most recently featured
+    async fn get_featured_entries(&self, limit: u32) -> DatabaseResult<Vec<KFeaturedRecord>> {
+        let rows = NamedQuery::new(
+            "get_featured_entries",
+            r#"
+            SELECT f.content_id, f.position, f.note, f.featured_at,
+                   EXISTS(SELECT 1 FROM k_contents c
+                          WHERE c.transaction_id = f.content_id
+                            AND c.content_type IN ('post', 'reply', 'quote')) AS available
+            FROM k_featured f
+            ORDER BY f.position ASC, f.featured_at DESC
+            LIMIT $1
+            "#,
+        )
+        .query()
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to fetch featured entries: {}", e))
+        })?;
 
-            let prev_cursor = Some(Self::create_compound_cursor(
-                first_item.block_time,
-                first_item.id,
-            ));
+        Ok(rows.iter().map(Self::featured_from_row).collect())
+    }
 
-            PaginationMetadata {
-                has_more,
-                next_cursor,
-                prev_cursor,
-            }
-        };
+    /// Insert or update a featured entry, for indexed posts, replies and quotes only
+    async fn set_featured(
+        &self,
+        content_id: &str,
+        position: Option<i32>,
+        note: Option<&str>,
+    ) -> DatabaseResult<Option<KFeaturedRecord>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
+        let featured_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
 
-        Ok(PaginatedResult { items, pagination })
+        let row = NamedQuery::new(
+            "set_featured",
+            r#"
+            INSERT INTO k_featured (content_id, position, note, featured_at)
+            SELECT c.transaction_id, COALESCE($2, 0), NULLIF($3, ''), $4
+            FROM k_contents c
+            WHERE c.transaction_id = $1 AND c.content_type IN ('post', 'reply', 'quote')
+            ON CONFLICT (content_id) DO UPDATE
+            SET position = COALESCE($2, k_featured.position),
+                note = CASE WHEN $3::TEXT IS NULL THEN k_featured.note ELSE NULLIF($3, '') END
+            RETURNING content_id, position, note, featured_at, TRUE AS available
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .bind(position)
+        .bind(note)
+        .bind(featured_at)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(format!("Failed to feature content: {}", e)))?;
+
+        Ok(row.as_ref().map(Self::featured_from_row))
     }
 
-    /// Get trending hashtags within a time window
-    async fn get_trending_hashtags(
-        &self,
-        from_time: u64,
-        to_time: u64,
-        limit: u32,
-    ) -> DatabaseResult<Vec<(String, u64)>> {
-        let query = r#"
-            SELECT hashtag, COUNT(*) as usage_count
-            FROM k_hashtags
-            WHERE block_time >= $1 AND block_time <= $2
-            GROUP BY hashtag
-            ORDER BY usage_count DESC, hashtag ASC
-            LIMIT $3
-        "#;
+    /// Remove a featured entry
+    async fn delete_featured(&self, content_id: &str) -> DatabaseResult<Option<KFeaturedRecord>> {
+        let content_id_bytes = Self::decode_hex_to_bytes(content_id)?;
 
-        let rows = sqlx::query(query)
-            .bind(from_time as i64)
-            .bind(to_time as i64)
-            .bind(limit as i64)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| {
-                DatabaseError::QueryError(format!("Failed to fetch trending hashtags: {}", e))
-            })?;
+        let row = NamedQuery::new(
+            "delete_featured",
+            r#"
+            DELETE FROM k_featured f
+            WHERE f.content_id = $1
+            RETURNING f.content_id, f.position, f.note, f.featured_at,
+                      EXISTS(SELECT 1 FROM k_contents c
+                             WHERE c.transaction_id = f.content_id
+                               AND c.content_type IN ('post', 'reply', 'quote')) AS available
+            "#,
+        )
+        .query()
+        .bind(&content_id_bytes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            DatabaseError::QueryError(format!("Failed to remove featured content: {}", e))
+        })?;
 
-        let trending_hashtags: Vec<(String, u64)> = rows
-            .iter()
-            .map(|row| {
-                let hashtag: String = row.get("hashtag");
-                let usage_count: i64 = row.get("usage_count");
-                (hashtag, usage_count as u64)
-            })
-            .collect();
+        Ok(row.as_ref().map(Self::featured_from_row))
+    }
+}
 
-        Ok(trending_hashtags)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_collapse_disabled() {
+        assert_eq!(notification_collapse_filter(0, "km"), "");
+        assert_eq!(
+            notification_collapse_count(0, "fn", "$1"),
+            "1::bigint as collapse_count"
+        );
+    }
+
+    #[test]
+    fn test_notification_collapse_window() {
+        let filter = notification_collapse_filter(3_600_000, "km");
+        assert!(filter.starts_with(" AND NOT EXISTS ("));
+        // Only notifications in the same window are collapsed, so a sender cannot hide older
+        // activity by repeating it
+        assert!(filter.contains("newer.block_time / 3600000 = km.block_time / 3600000"));
+        assert!(filter.contains("km.content_type IN ('vote', 'reply', 'quote')"));
+        assert!(filter.contains("newer.sender_pubkey = km.sender_pubkey"));
+
+        let count = notification_collapse_count(3_600_000, "fn", "$1");
+        assert!(count.contains("older.mentioned_pubkey = $1"));
+        assert!(count.contains("older.block_time / 3600000 = fn.block_time / 3600000"));
+        assert!(count.ends_with(" ELSE 1 END as collapse_count"));
     }
 }
@@ -1,6 +1,9 @@
 use crate::models::{
-    ContentRecord, KBroadcastRecord, KPostRecord, KReplyRecord, NotificationContentRecord,
-    PaginationMetadata,
+    ContentRecord, KArticleRecord, KBroadcastRecord, KContentTipsRecord, KDuplicateContentRecord,
+    KErasureRequestRecord, KEventRecord, KFeaturedPostRecord, KFeaturedRecord, KGroupPostRecord,
+    KGroupRecord, KImpersonationFlagRecord, KNicknameUseRecord, KPostRecord, KProfileDiffRecord,
+    KQueryStatsRecord, KReplyRecord, KTableStorageRecord, KTippedPostRecord, KUserStatsRecord,
+    NotificationContentRecord, PaginationMetadata,
 };
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -40,6 +43,16 @@ pub struct QueryOptions {
     pub sort_descending: bool,
 }
 
+/// Order of a ranked posts feed (get-posts-watching sort=top|hot); the engagement of a post
+/// is its upvotes minus downvotes plus its replies and quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostRanking {
+    /// Most engagement first
+    Top,
+    /// Most engagement decayed by age first: engagement / (age in hours + 2)^1.5
+    Hot,
+}
+
 impl Default for QueryOptions {
     fn default() -> Self {
         Self {
@@ -85,12 +98,32 @@ pub trait DatabaseInterface: Send + Sync {
         searched_user_nickname: Option<String>,
     ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>>;
 
+    // Users whose decoded nickname contains `query` (case-insensitive), nicknames starting with
+    // it first; a single page of at most `limit` users with block/follow status, no cursors
+    async fn search_broadcasts_by_nickname(
+        &self,
+        requester_pubkey: &str,
+        query: &str,
+        limit: u32,
+    ) -> DatabaseResult<PaginatedResult<(KBroadcastRecord, bool, bool)>>;
+
     async fn get_user_details(
         &self,
         user_public_key: &str,
         requester_pubkey: &str,
     ) -> DatabaseResult<Option<(KBroadcastRecord, bool, bool, i64, i64, i64)>>;
 
+    // Content, vote and activity totals of a user, computed from the indexed tables
+    async fn get_user_stats(&self, user_public_key: &str) -> DatabaseResult<KUserStatsRecord>;
+
+    // Latest broadcast of each of `user_public_keys` in their order, with its block status by
+    // the requester; users without broadcast get the empty record of get_user_details
+    async fn get_users_by_keys(
+        &self,
+        user_public_keys: &[String],
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Vec<(KBroadcastRecord, bool)>>;
+
     async fn get_blocked_users_by_requester(
         &self,
         requester_pubkey: &str,
@@ -124,6 +157,52 @@ pub trait DatabaseInterface: Send + Sync {
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
 
+    // Posts and quotes of a time window ranked by engagement (excludes blocked users)
+    async fn get_ranked_posts(
+        &self,
+        requester_pubkey: &str,
+        ranking: PostRanking,
+        from_time: u64,
+        to_time: u64,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KPostRecord>>;
+
+    // Page of get_all_posts with only the columns of k_contents (no counts, votes, profiles,
+    // mentions or quoted content), read without any join but the requester's blocks
+    async fn get_all_posts_light(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
+
+    // Counts, requester votes, profiles, mentions and quoted content of the posts, replies and
+    // quotes among `content_ids` (unknown ones are left out)
+    async fn get_posts_meta(
+        &self,
+        requester_pubkey: &str,
+        content_ids: &[String],
+    ) -> DatabaseResult<Vec<KPostRecord>>;
+
+    // Per-requester state of pages served from the response cache (--response-cache-ttl)
+
+    /// Hash of the set of users blocked by the requester (None = blocks nobody); requesters with
+    /// equal hashes get the same /get-users and /get-posts-watching pages apart from follows and votes
+    async fn get_block_set_hash(&self, requester_pubkey: &str) -> DatabaseResult<Option<String>>;
+
+    /// Users among `user_pubkeys` followed by the requester
+    async fn get_followed_among(
+        &self,
+        requester_pubkey: &str,
+        user_pubkeys: &[String],
+    ) -> DatabaseResult<Vec<String>>;
+
+    /// Votes of the requester on `post_ids` as (post id, "upvote" or "downvote")
+    async fn get_requester_votes(
+        &self,
+        requester_pubkey: &str,
+        post_ids: &[String],
+    ) -> DatabaseResult<Vec<(String, String)>>;
+
     // NEW: k_contents table - Get content (posts, replies, quotes) from followed users (excludes blocked users)
     async fn get_content_following(
         &self,
@@ -131,6 +210,13 @@ pub trait DatabaseInterface: Send + Sync {
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
 
+    // k_contents table - Get posts (no replies or quotes) from followed users (excludes blocked users)
+    async fn get_posts_following(
+        &self,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
+
     // NEW: k_contents table - Get contents mentioning a specific user using unified content table (excludes blocked users)
     async fn get_contents_mentioning_user(
         &self,
@@ -155,6 +241,25 @@ pub trait DatabaseInterface: Send + Sync {
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KReplyRecord>>;
 
+    // Get the first `per_parent_limit` replies of each parent content in one query (excludes blocked users)
+    async fn get_nested_replies(
+        &self,
+        parent_ids: &[String],
+        requester_pubkey: &str,
+        per_parent_limit: u32,
+    ) -> DatabaseResult<Vec<KReplyRecord>>;
+
+    // Get every reply of the conversation of a post, quote or reply in one query through
+    // k_contents.root_post_id (schema v33+), newest first (excludes blocked users). None when the
+    // schema has no thread roots, the thread root of a reply is not indexed yet or the
+    // conversation has more than `limit` replies
+    async fn get_thread_replies(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+        limit: u32,
+    ) -> DatabaseResult<Option<Vec<KReplyRecord>>>;
+
     // NEW: k_contents table - Get posts by user using unified content table (excludes blocked users)
     async fn get_posts_by_user(
         &self,
@@ -177,6 +282,25 @@ pub trait DatabaseInterface: Send + Sync {
         requester_pubkey: &str,
     ) -> DatabaseResult<Option<(ContentRecord, bool)>>;
 
+    // Posts, replies and quotes among `content_ids` in their order, with the same data and
+    // block status as get_content_by_id and their content type (unknown ones are left out)
+    async fn get_contents_by_ids(
+        &self,
+        content_ids: &[String],
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Vec<(ContentRecord, bool)>>;
+
+    // Archived post/quote (k_contents_archive) with its blocking status for the requester
+    async fn get_archived_content_by_id(
+        &self,
+        content_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<(KPostRecord, bool)>>;
+
+    // Every pubkey mentioned by a post, reply or quote, in mention order (None if the content
+    // is unknown); feed queries list only the first ones
+    async fn get_content_mentions(&self, content_id: &str) -> DatabaseResult<Option<Vec<String>>>;
+
     // Get count of notifications (mentions) for a user
     async fn get_notification_count(
         &self,
@@ -190,6 +314,22 @@ pub trait DatabaseInterface: Send + Sync {
     // Get network type from k_vars table
     async fn get_network(&self) -> DatabaseResult<String>;
 
+    // Get the hash of a block accepting a transaction, from the blocks_transactions table of
+    // the Kaspa indexer (None if the table is not populated or the transaction not found)
+    async fn get_block_hash(&self, transaction_id: &str) -> DatabaseResult<Option<String>>;
+
+    // Get schema version and supported features from k_meta table (None if not published)
+    async fn get_schema_meta(&self) -> DatabaseResult<Option<SchemaMeta>>;
+
+    // Get the operator feature flags from k_feature_flags (schema v14+)
+    async fn get_feature_flags(&self) -> DatabaseResult<Vec<FeatureFlagRecord>>;
+
+    // Get the stored profile image of a user (inline base64 or blob storage reference)
+    async fn get_profile_image(
+        &self,
+        user_public_key: &str,
+    ) -> DatabaseResult<Option<ProfileImageRecord>>;
+
     // Get database statistics
     async fn get_stats(&self) -> DatabaseResult<DatabaseStats>;
 
@@ -203,6 +343,205 @@ pub trait DatabaseInterface: Send + Sync {
         options: QueryOptions,
     ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
 
+    // Full-text search over posts and replies (websearch_to_tsquery syntax)
+    async fn search_posts(
+        &self,
+        search_query: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KPostRecord>>;
+
+    // Group operations
+
+    // Get groups visible to the requester: open groups and closed groups the requester is a member of
+    // With member_pubkey, only groups that user is a member of are returned
+    async fn get_groups(
+        &self,
+        requester_pubkey: &str,
+        member_pubkey: Option<&str>,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KGroupRecord>>;
+
+    // Get a group by ID with member count and requester membership
+    async fn get_group_by_id(
+        &self,
+        group_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<KGroupRecord>>;
+
+    // Get posts of a group (excludes blocked users)
+    async fn get_group_posts(
+        &self,
+        group_id: &str,
+        requester_pubkey: &str,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KGroupPostRecord>>;
+
+    // Event operations
+
+    // Get upcoming (end_time >= now) or past (end_time < now) events with RSVP counts,
+    // optionally from a single organizer (excludes blocked users)
+    async fn get_events(
+        &self,
+        requester_pubkey: &str,
+        organizer_pubkey: Option<&str>,
+        upcoming: bool,
+        now: u64,
+        options: QueryOptions,
+    ) -> DatabaseResult<PaginatedResult<KEventRecord>>;
+
+    // Article operations
+
+    // Get an article by manifest ID with the number of chunks received so far
+    // and whether the requester blocked the author
+    async fn get_article_by_id(
+        &self,
+        article_id: &str,
+        requester_pubkey: &str,
+    ) -> DatabaseResult<Option<KArticleRecord>>;
+
+    // Tip operations
+
+    // Get the tip totals of a post, reply or quote (None if the content is unknown)
+    async fn get_content_tips(
+        &self,
+        content_id: &str,
+    ) -> DatabaseResult<Option<KContentTipsRecord>>;
+
+    // Get the contents that received the highest tip amount within a time window
+    // (excludes blocked users)
+    async fn get_top_tipped_posts(
+        &self,
+        requester_pubkey: &str,
+        from_time: u64,
+        to_time: u64,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KTippedPostRecord>>;
+
+    // View tracking operations
+
+    // Record a view in the HyperLogLog registers of a post, reply or quote
+    // (raise register `register_index` to `rank`; views of unknown contents are ignored)
+    async fn record_post_view(
+        &self,
+        content_id: &str,
+        register_index: usize,
+        rank: u8,
+    ) -> DatabaseResult<()>;
+
+    // Get the HyperLogLog registers of a post, reply or quote (None if never viewed)
+    async fn get_post_view_registers(&self, content_id: &str) -> DatabaseResult<Option<Vec<u8>>>;
+
+    // Fee-weighted vote totals (content id, up votes fee, down votes fee) of the contents that
+    // have votes (schema v27+)
+    async fn get_vote_weights(
+        &self,
+        content_ids: &[String],
+    ) -> DatabaseResult<Vec<(String, u64, u64)>>;
+
+    // Erasure operations (schema v15+)
+
+    // Record a pending right-to-be-forgotten request for a pubkey
+    async fn create_erasure_request(
+        &self,
+        subject_pubkey: &str,
+        reason: Option<&str>,
+    ) -> DatabaseResult<KErasureRequestRecord>;
+
+    // Get an erasure request with its completion report
+    async fn get_erasure_request(
+        &self,
+        request_id: i64,
+    ) -> DatabaseResult<Option<KErasureRequestRecord>>;
+
+    // Content hash operations (schema v17+)
+
+    // Get messages posted by at least `min_senders` different pubkeys within a time window,
+    // most widespread first (at most `max_senders` sender pubkeys listed per message)
+    async fn get_duplicate_contents(
+        &self,
+        from_time: u64,
+        to_time: u64,
+        min_senders: u32,
+        max_senders: u32,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KDuplicateContentRecord>>;
+
+    // Storage operations (schema v18+)
+
+    // Get the current row count and on-disk size of every K table, largest first, with the
+    // latest k_storage_snapshots row taken at or before `compare_before`
+    async fn get_table_storage(
+        &self,
+        compare_before: u64,
+    ) -> DatabaseResult<Vec<KTableStorageRecord>>;
+
+    // Query statistics operations (pg_stat_statements)
+
+    // Get the pg_stat_statements totals of the queries of each DatabaseInterface method, most
+    // total execution time first (None when the extension is not installed or not loaded)
+    async fn get_query_stats(&self, limit: u32) -> DatabaseResult<Option<Vec<KQueryStatsRecord>>>;
+
+    // Profile history operations (schema v20+)
+
+    // Get the profile of a user as of `from_time` and `to_time` (latest broadcast at or before
+    // each) and the number of broadcasts in between
+    async fn get_profile_diff(
+        &self,
+        user_pubkey: &str,
+        from_time: u64,
+        to_time: u64,
+    ) -> DatabaseResult<KProfileDiffRecord>;
+
+    // Impersonation operations (schema v21+)
+
+    // Get impersonation flags with the given review status, most recently flagged first
+    async fn get_impersonation_flags(
+        &self,
+        status: &str,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KImpersonationFlagRecord>>;
+
+    // Set the review status of an impersonation flag (None if the flag does not exist)
+    async fn review_impersonation_flag(
+        &self,
+        flag_id: i64,
+        status: &str,
+    ) -> DatabaseResult<Option<KImpersonationFlagRecord>>;
+
+    // Get every user whose profile history has the normalized nickname (schema v29+),
+    // earliest user first
+    async fn get_nickname_history(
+        &self,
+        normalized_nickname: &str,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KNicknameUseRecord>>;
+
+    // Featured content operations (schema v30+)
+
+    // Get the featured contents with their posts, by position then most recently featured
+    // (skips removed contents and excludes blocked users)
+    async fn get_featured_posts(
+        &self,
+        requester_pubkey: &str,
+        limit: u32,
+    ) -> DatabaseResult<Vec<KFeaturedPostRecord>>;
+
+    // Get the featured entries, including the ones whose content was removed
+    async fn get_featured_entries(&self, limit: u32) -> DatabaseResult<Vec<KFeaturedRecord>>;
+
+    // Feature a content, or update its entry: a None position or note keeps the current value
+    // and an empty note clears it (None if the content is not an indexed post, reply or quote)
+    async fn set_featured(
+        &self,
+        content_id: &str,
+        position: Option<i32>,
+        note: Option<&str>,
+    ) -> DatabaseResult<Option<KFeaturedRecord>>;
+
+    // Remove a featured entry (None if the content was not featured)
+    async fn delete_featured(&self, content_id: &str) -> DatabaseResult<Option<KFeaturedRecord>>;
+
     // Get trending hashtags within a time window
     // Returns: Vec<(hashtag: String, usage_count: u64)>
     async fn get_trending_hashtags(
@@ -223,3 +562,38 @@ pub struct DatabaseStats {
     pub follows_count: i64,
     pub blocks_count: i64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaMeta {
+    pub schema_version: i32,
+    pub features: Vec<String>,
+    pub processor_version: String,
+    pub updated_at: i64,
+    // Read-only maintenance mode (schema v8+, false on older schemas)
+    pub maintenance_mode: bool,
+    pub maintenance_retry_after: i32,
+    pub maintenance_reason: Option<String>,
+}
+
+/// Feature flag row of k_feature_flags (schema v14+)
+#[derive(Debug, Clone)]
+pub struct FeatureFlagRecord {
+    pub name: String,
+    pub enabled: bool,
+    pub rollout_percent: i16,
+}
+
+/// Profile image location for a user broadcast
+/// Either the inline base64 image or a reference to the blob storage (schema v4+)
+#[derive(Debug, Clone)]
+pub struct ProfileImageRecord {
+    pub base64_encoded_profile_image: Option<String>,
+    pub profile_image_ref: Option<String>,
+    pub profile_image_hash: Option<Vec<u8>>,
+}
+
+impl SchemaMeta {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+}
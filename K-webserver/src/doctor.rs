@@ -0,0 +1,202 @@
+use crate::config::{AppConfig, ImageStorageConfig};
+use crate::database_trait::SchemaMeta;
+use crate::web_server::unavailable_endpoints;
+use k_common::doctor::{
+    Report, Status, check_database_disk, check_disk, check_extensions, check_notification_channel,
+    connect,
+};
+use sqlx::{PgPool, Row};
+
+/// Indexes behind the feed, replies, votes, mentions, blocks, follows and hashtag queries
+const READ_PATH_INDEXES: &[&str] = &[
+    "idx_k_contents_feed_optimized",
+    "idx_k_contents_sender_pubkey",
+    "idx_k_contents_replies",
+    "idx_k_votes_post_id_sender",
+    "idx_k_mentions_comprehensive",
+    "idx_k_blocks_sender_blocked_user_unique",
+    "idx_k_follows_sender_followed_user_unique",
+    "idx_k_hashtags_by_hashtag_time",
+];
+
+/// Check database (and read replica) connectivity, extensions, indexes, schema version, the
+/// notification channel and disk space, printing one line per check
+pub async fn run(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = Report::new();
+    println!("K-webserver v{} doctor", env!("CARGO_PKG_VERSION"));
+
+    let database = &config.database;
+    let Some(pool) = connect(
+        &mut report,
+        "Database",
+        &config.connection_string(),
+        &format!("{}:{}/{}", database.host, database.port, database.database),
+        1,
+    )
+    .await
+    else {
+        return report.finish();
+    };
+
+    if let (Some(replica), Some(connection_string)) =
+        (&database.replica, config.replica_connection_string())
+    {
+        let target = format!("{}:{}/{}", replica.host, replica.port, database.database);
+        if let Some(replica_pool) =
+            connect(&mut report, "Read replica", &connection_string, &target, 1).await
+        {
+            check_replica(&mut report, &replica_pool).await;
+            replica_pool.close().await;
+        }
+    }
+
+    check_extensions(&mut report, &pool).await;
+    check_schema(&mut report, &pool).await;
+    check_indexes(&mut report, &pool).await;
+    check_notification_channel(&mut report, &pool).await;
+    check_database_disk(&mut report, &pool, &database.host).await;
+    if let ImageStorageConfig::Filesystem { path } = &config.server.image_storage {
+        check_disk(&mut report, "Image storage disk", path);
+    }
+
+    pool.close().await;
+    report.finish()
+}
+
+/// The replica must be a standby: reads sent to a diverged copy would serve stale data forever
+async fn check_replica(report: &mut Report, pool: &PgPool) {
+    match sqlx::query_scalar::<_, bool>("SELECT pg_is_in_recovery()")
+        .fetch_one(pool)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => report.check(
+            Status::Warn,
+            "Read replica",
+            "not in recovery (a promoted or standalone server, not a streaming standby)",
+        ),
+        Err(e) => report.check(Status::Fail, "Read replica", e.to_string()),
+    }
+}
+
+/// Schema published by K-transaction-processor in k_meta, as read by get_schema_meta
+async fn check_schema(report: &mut Report, pool: &PgPool) {
+    let row = match sqlx::query("SELECT * FROM k_meta WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            report.check(
+                Status::Fail,
+                "Schema version",
+                "k_meta is empty (start K-transaction-processor)",
+            );
+            return;
+        }
+        Err(e) => {
+            report.check(
+                Status::Fail,
+                "Schema version",
+                format!("k_meta not readable (start K-transaction-processor): {}", e),
+            );
+            return;
+        }
+    };
+
+    let meta = SchemaMeta {
+        schema_version: row.get("schema_version"),
+        features: row.get("features"),
+        processor_version: row.get("processor_version"),
+        updated_at: row.get("updated_at"),
+        maintenance_mode: row.try_get("maintenance_mode").unwrap_or(false),
+        maintenance_retry_after: row.try_get("maintenance_retry_after").unwrap_or(0),
+        maintenance_reason: row.try_get("maintenance_reason").unwrap_or(None),
+    };
+    report.check(
+        Status::Ok,
+        "Schema version",
+        format!(
+            "v{} published by K-transaction-processor v{}",
+            meta.schema_version, meta.processor_version
+        ),
+    );
+
+    let unavailable = unavailable_endpoints(&meta);
+    if !unavailable.is_empty() {
+        report.check(
+            Status::Warn,
+            "Schema features",
+            format!(
+                "not supported by v{}, answered with 503: /{}",
+                meta.schema_version,
+                unavailable.join(", /")
+            ),
+        );
+    }
+    if meta.maintenance_mode {
+        report.check(
+            Status::Warn,
+            "Maintenance mode",
+            meta.maintenance_reason
+                .as_deref()
+                .unwrap_or("enabled, writes are paused"),
+        );
+    }
+}
+
+/// Read path indexes must exist and be valid (a failed CREATE INDEX CONCURRENTLY leaves an
+/// invalid index that is never used)
+async fn check_indexes(report: &mut Report, pool: &PgPool) {
+    let indexes = sqlx::query(
+        r#"
+        SELECT c.relname::text AS name, i.indisvalid AS valid
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        WHERE c.relname LIKE 'idx_k_%'
+        "#,
+    )
+    .fetch_all(pool)
+    .await;
+
+    match indexes {
+        Ok(rows) => {
+            let valid = |name: &str| {
+                rows.iter()
+                    .find(|row| row.get::<String, _>("name") == name)
+                    .map(|row| row.get::<bool, _>("valid"))
+            };
+            let missing: Vec<&str> = READ_PATH_INDEXES
+                .iter()
+                .copied()
+                .filter(|index| valid(index).is_none())
+                .collect();
+            let invalid: Vec<String> = rows
+                .iter()
+                .filter(|row| !row.get::<bool, _>("valid"))
+                .map(|row| row.get("name"))
+                .collect();
+
+            if !missing.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("missing: {}", missing.join(", ")),
+                );
+            } else if !invalid.is_empty() {
+                report.check(
+                    Status::Fail,
+                    "Indexes",
+                    format!("invalid (rebuild them): {}", invalid.join(", ")),
+                );
+            } else {
+                report.check(
+                    Status::Ok,
+                    "Indexes",
+                    format!("{} K indexes, read path indexes present", rows.len()),
+                );
+            }
+        }
+        Err(e) => report.check(Status::Fail, "Indexes", e.to_string()),
+    }
+}
@@ -0,0 +1,186 @@
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Approximate view counts in post details (on unless configured otherwise)
+pub const VIEW_COUNTS: &str = "view_counts";
+
+/// Prefix of flags gating a whole endpoint, e.g. "endpoint:get-top-tipped-posts"
+pub const ENDPOINT_FLAG_PREFIX: &str = "endpoint:";
+
+/// Rollout rule of a single flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagRule {
+    pub enabled: bool,
+    // Share of requesters (0-100) the flag is on for
+    pub rollout_percent: u8,
+}
+
+impl FlagRule {
+    fn is_on_for(&self, bucket: u8) -> bool {
+        self.enabled && bucket < self.rollout_percent
+    }
+}
+
+/// Stable bucket (0-99) of a requester for a flag
+/// Hashed per flag so the same requesters do not land in every experiment
+pub fn flag_bucket(flag: &str, subject: &str) -> u8 {
+    let digest = Sha256::digest(format!("{}:{}", flag, subject).as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) % 100) as u8
+}
+
+/// Feature flags for experimental API behavior
+/// Rules come from k_feature_flags (schema v14+) and --feature-flags, the command line wins
+pub struct FlagService {
+    overrides: HashMap<String, FlagRule>,
+    table_rules: RwLock<HashMap<String, FlagRule>>,
+    // Buckets requests without a requester in turn, so N% of them get the flag
+    request_counter: AtomicU64,
+}
+
+impl FlagService {
+    pub fn new(overrides: HashMap<String, FlagRule>) -> Self {
+        Self {
+            overrides,
+            table_rules: RwLock::new(HashMap::new()),
+            request_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the rules read from k_feature_flags
+    pub fn set_table_rules(&self, rules: HashMap<String, FlagRule>) {
+        *self.table_rules.write().unwrap() = rules;
+    }
+
+    fn rule(&self, flag: &str) -> Option<FlagRule> {
+        self.overrides
+            .get(flag)
+            .copied()
+            .or_else(|| self.table_rules.read().unwrap().get(flag).copied())
+    }
+
+    fn bucket(&self, flag: &str, subject: Option<&str>) -> u8 {
+        match subject {
+            Some(subject) => flag_bucket(flag, subject),
+            None => (self.request_counter.fetch_add(1, Ordering::Relaxed) % 100) as u8,
+        }
+    }
+
+    /// Whether a flag is on for the requester (None = flag not configured)
+    pub fn check(&self, flag: &str, subject: Option<&str>) -> Option<bool> {
+        self.rule(flag)
+            .map(|rule| rule.is_on_for(self.bucket(flag, subject)))
+    }
+
+    /// Whether a flag is on for the requester, unconfigured flags are off
+    pub fn is_enabled(&self, flag: &str, subject: Option<&str>) -> bool {
+        self.check(flag, subject).unwrap_or(false)
+    }
+
+    /// Whether a flag is configured and off for every requester (disabled or 0% rollout)
+    pub fn is_off_for_all(&self, flag: &str) -> bool {
+        self.rule(flag)
+            .is_some_and(|rule| !rule.enabled || rule.rollout_percent == 0)
+    }
+
+    /// Every configured flag evaluated for the requester
+    pub fn evaluate_all(&self, subject: Option<&str>) -> BTreeMap<String, bool> {
+        let mut names: BTreeSet<String> = self.overrides.keys().cloned().collect();
+        names.extend(self.table_rules.read().unwrap().keys().cloned());
+
+        names
+            .into_iter()
+            .map(|name| {
+                let enabled = self.is_enabled(&name, subject);
+                (name, enabled)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(enabled: bool, rollout_percent: u8) -> FlagRule {
+        FlagRule {
+            enabled,
+            rollout_percent,
+        }
+    }
+
+    #[test]
+    fn unconfigured_flags_are_off() {
+        let flags = FlagService::new(HashMap::new());
+        assert_eq!(flags.check("reactions", Some("02aa")), None);
+        assert!(!flags.is_enabled("reactions", Some("02aa")));
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_table_rules() {
+        let flags = FlagService::new(HashMap::from([("reactions".to_string(), rule(false, 100))]));
+        flags.set_table_rules(HashMap::from([
+            ("reactions".to_string(), rule(true, 100)),
+            ("hot_ranking".to_string(), rule(true, 100)),
+        ]));
+
+        assert!(!flags.is_enabled("reactions", Some("02aa")));
+        assert!(flags.is_enabled("hot_ranking", Some("02aa")));
+    }
+
+    #[test]
+    fn off_for_all_ignores_partial_rollouts() {
+        let flags = FlagService::new(HashMap::from([
+            ("disabled".to_string(), rule(false, 100)),
+            ("no_rollout".to_string(), rule(true, 0)),
+            ("partial".to_string(), rule(true, 10)),
+        ]));
+
+        assert!(flags.is_off_for_all("disabled"));
+        assert!(flags.is_off_for_all("no_rollout"));
+        assert!(!flags.is_off_for_all("partial"));
+        assert!(!flags.is_off_for_all("unconfigured"));
+    }
+
+    #[test]
+    fn requester_bucket_is_stable() {
+        let flags = FlagService::new(HashMap::from([("reactions".to_string(), rule(true, 50))]));
+        let first = flags.is_enabled("reactions", Some("02aa"));
+        for _ in 0..10 {
+            assert_eq!(flags.is_enabled("reactions", Some("02aa")), first);
+        }
+    }
+
+    #[test]
+    fn rollout_percent_applies_to_requests_without_requester() {
+        let flags = FlagService::new(HashMap::from([("reactions".to_string(), rule(true, 25))]));
+        let enabled = (0..100)
+            .filter(|_| flags.is_enabled("reactions", None))
+            .count();
+        assert_eq!(enabled, 25);
+    }
+
+    #[test]
+    fn rollout_percent_splits_requesters() {
+        let enabled = (0..10_000)
+            .filter(|i| flag_bucket("reactions", &i.to_string()) < 30)
+            .count();
+        assert!((2_700..3_300).contains(&enabled), "{}", enabled);
+    }
+
+    #[test]
+    fn evaluate_all_lists_every_flag() {
+        let flags = FlagService::new(HashMap::from([("reactions".to_string(), rule(true, 100))]));
+        flags.set_table_rules(HashMap::from([(
+            "hot_ranking".to_string(),
+            rule(false, 100),
+        )]));
+
+        let evaluated = flags.evaluate_all(Some("02aa"));
+        assert_eq!(evaluated.get("reactions"), Some(&true));
+        assert_eq!(evaluated.get("hot_ranking"), Some(&false));
+    }
+}
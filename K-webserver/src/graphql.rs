@@ -0,0 +1,771 @@
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Error, ErrorExtensions, Object,
+    Result, Schema, SchemaBuilder, SimpleObject,
+};
+use axum::{Json, http::StatusCode};
+use std::sync::Arc;
+use tracing::error as log_error;
+
+use crate::database_trait::{DatabaseError, PaginatedResult, QueryOptions};
+use crate::models::{
+    ApiError, ContentRecord, KPostRecord, KReplyRecord, KVoteRecord, PaginationMetadata, QuoteData,
+    RenderSpan, ServerPost, ServerPostFromRecord, ServerReply, ServerReplyFromRecord,
+    ServerUserPost, ServerUserPostFromRecord,
+};
+use crate::requester_privacy::ANONYMOUS_REQUESTER;
+use crate::web_server::{AppState, check_schema_feature, resolve_limit};
+
+/// Deepest selection accepted (a page of contents with their replies is 4 levels deep)
+const MAX_DEPTH: usize = 8;
+
+/// Highest query complexity accepted: every field costs 1, lists cost their limit times the
+/// cost of their items
+const MAX_COMPLEXITY: usize = 2000;
+
+/// Items a list without an explicit limit is assumed to hold when computing complexity
+const COMPLEXITY_PAGE_SIZE: usize = 20;
+
+/// Read-only GraphQL schema over the database interface (--enable-graphql): the contents,
+/// users and blocks of the REST endpoints, with the fields each client selects
+pub type GraphqlSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Schema served on /graphql; every request carries the Arc<AppState> of its community
+pub fn build_schema() -> GraphqlSchema {
+    schema_builder().finish()
+}
+
+fn schema_builder() -> SchemaBuilder<QueryRoot, EmptyMutation, EmptySubscription> {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+}
+
+/// Complexity of a page of `limit` items of `child_complexity` each
+fn page_complexity(limit: Option<u32>, child_complexity: usize) -> usize {
+    limit
+        .map_or(COMPLEXITY_PAGE_SIZE, |limit| limit as usize)
+        .saturating_mul(child_complexity)
+}
+
+/// Error carrying the code the REST endpoints answer with in its "code" extension
+fn coded_error(message: &str, code: &'static str) -> Error {
+    Error::new(message).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+/// Error of a check shared with the REST endpoints (limits, schema features)
+fn rest_error((_, Json(error)): (StatusCode, Json<ApiError>)) -> Error {
+    let code = error.code;
+    Error::new(error.error).extend_with(|_, extensions| extensions.set("code", code.as_str()))
+}
+
+fn database_error(err: DatabaseError) -> Error {
+    log_error!("Database error while resolving a GraphQL query: {}", err);
+    coded_error(
+        "Internal server error during database query",
+        "DATABASE_ERROR",
+    )
+}
+
+/// Compressed public key: 66 hex characters starting with 02 or 03
+fn check_pubkey(pubkey: &str, argument: &str) -> Result<()> {
+    if pubkey.len() != 66
+        || !pubkey.chars().all(|c| c.is_ascii_hexdigit())
+        || !(pubkey.starts_with("02") || pubkey.starts_with("03"))
+    {
+        return Err(coded_error(
+            &format!(
+                "Invalid {} format. Must be a compressed public key (66 hex characters starting with 02 or 03).",
+                argument
+            ),
+            "INVALID_USER_KEY",
+        ));
+    }
+    Ok(())
+}
+
+/// Requester the relations of other users' contents and profiles are read for: the anonymous
+/// requester with --requester-privacy (like requesterPubkey of the REST endpoints)
+fn relations_requester(state: &AppState, requester_pubkey: String) -> String {
+    if state.server_config.requester_privacy {
+        ANONYMOUS_REQUESTER.to_string()
+    } else {
+        requester_pubkey
+    }
+}
+
+/// Transaction ID: 64 hex characters
+fn check_content_id(id: &str, argument: &str) -> Result<()> {
+    if id.len() != 64 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(coded_error(
+            &format!("Invalid {} format. Must be 64 hex characters.", argument),
+            "INVALID_POST_ID",
+        ));
+    }
+    Ok(())
+}
+
+/// Newest-first page options, with the page sizes of the matching REST endpoint
+fn page_options(
+    state: &AppState,
+    endpoint: &str,
+    limit: Option<u32>,
+    before: Option<String>,
+    after: Option<String>,
+) -> Result<QueryOptions> {
+    let limit = resolve_limit(state, endpoint, limit).map_err(rest_error)?;
+    Ok(QueryOptions {
+        limit: Some(limit as u64),
+        before,
+        after,
+        sort_descending: true,
+    })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Posts of every user not blocked by the requester, newest first (get-posts-watching)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        requester_pubkey: String,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let options = page_options(state, "get-posts-watching", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_all_posts(&requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        Ok(ContentPage::from_posts(page, &requester_pubkey))
+    }
+
+    /// Post, reply, quote or vote by transaction ID, null when not indexed (get-post-details)
+    async fn content(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        requester_pubkey: String,
+    ) -> Result<Option<Content>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_content_id(&id, "id")?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let requester_pubkey = relations_requester(state, requester_pubkey);
+
+        let content = state
+            .db
+            .get_content_by_id(&id, &requester_pubkey)
+            .await
+            .map_err(database_error)?;
+        Ok(content.map(|(record, is_blocked)| match record {
+            ContentRecord::Post(post) => Content::from_post(&post, is_blocked, &requester_pubkey),
+            ContentRecord::Reply(reply) => {
+                Content::from_reply(&reply, is_blocked, &requester_pubkey)
+            }
+            ContentRecord::Vote(vote) => Content::from_vote(&vote, is_blocked, &requester_pubkey),
+        }))
+    }
+
+    /// Replies to a post or reply, newest first (get-replies)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn replies(
+        &self,
+        ctx: &Context<'_>,
+        post_id: String,
+        requester_pubkey: String,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        check_content_id(&post_id, "postId")?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let state = ctx.data::<Arc<AppState>>()?;
+        let requester_pubkey = relations_requester(state, requester_pubkey);
+        replies_page(ctx, &post_id, &requester_pubkey, limit, before, after).await
+    }
+
+    /// Contents and votes mentioning a user, newest first (get-mentions)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn mentions(
+        &self,
+        ctx: &Context<'_>,
+        user: String,
+        requester_pubkey: String,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_pubkey(&user, "user")?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let requester_pubkey = relations_requester(state, requester_pubkey);
+        let options = page_options(state, "get-mentions", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_contents_mentioning_user(&user, &requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        let items = page
+            .items
+            .iter()
+            .map(|record| match record {
+                ContentRecord::Post(post) => Content::from_post(post, false, &requester_pubkey),
+                ContentRecord::Reply(reply) => Content::from_reply(reply, false, &requester_pubkey),
+                ContentRecord::Vote(vote) => Content::from_vote(vote, false, &requester_pubkey),
+            })
+            .collect();
+        Ok(ContentPage {
+            items,
+            pagination: page.pagination.into(),
+        })
+    }
+
+    /// Profile of a user with the follow and block status of the requester (get-user-details)
+    async fn user(
+        &self,
+        ctx: &Context<'_>,
+        user: String,
+        requester_pubkey: String,
+    ) -> Result<User> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_pubkey(&user, "user")?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let requester_pubkey = relations_requester(state, requester_pubkey);
+
+        let details = state
+            .db
+            .get_user_details(&user, &requester_pubkey)
+            .await
+            .map_err(database_error)?;
+        let Some((record, blocked, followed, followers, following, blocked_count)) = details else {
+            return Ok(User::without_broadcast(
+                &user,
+                false,
+                false,
+                &requester_pubkey,
+            ));
+        };
+
+        // Users without a broadcast come as a record without an ID
+        let mut user = if record.id == 0 && record.transaction_id.is_empty() {
+            User::without_broadcast(&user, blocked, followed, &requester_pubkey)
+        } else {
+            User::new(
+                ServerUserPost::from_k_broadcast_record_with_block_and_follow_status(
+                    &record, blocked, followed,
+                ),
+                &requester_pubkey,
+            )
+        };
+        user.followers_count = Some(followers);
+        user.following_count = Some(following);
+        user.blocked_count = Some(blocked_count);
+        Ok(user)
+    }
+
+    /// Users with a broadcast, newest first (get-users)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        requester_pubkey: String,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<UserPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let requester_pubkey = relations_requester(state, requester_pubkey);
+        let options = page_options(state, "get-users", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_all_users(&requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        let items = page
+            .items
+            .iter()
+            .map(|(record, blocked, followed)| {
+                User::new(
+                    ServerUserPost::from_k_broadcast_record_with_block_and_follow_status(
+                        record, *blocked, *followed,
+                    ),
+                    &requester_pubkey,
+                )
+            })
+            .collect();
+        Ok(UserPage {
+            items,
+            pagination: page.pagination.into(),
+        })
+    }
+
+    /// Users blocked by the requester, most recently blocked first (get-blocked-users)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn blocked_users(
+        &self,
+        ctx: &Context<'_>,
+        requester_pubkey: String,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<UserPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        check_schema_feature(state, "blocks")
+            .await
+            .map_err(rest_error)?;
+        check_pubkey(&requester_pubkey, "requesterPubkey")?;
+        let options = page_options(state, "get-blocked-users", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_blocked_users_by_requester(&requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        let items = page
+            .items
+            .iter()
+            .map(|record| {
+                let mut user = ServerUserPost::from_k_broadcast_record(record);
+                // Blocked users are listed without their broadcast message
+                user.post_content = String::new();
+                user.blocked_user = Some(true);
+                User::new(user, &requester_pubkey)
+            })
+            .collect();
+        Ok(UserPage {
+            items,
+            pagination: page.pagination.into(),
+        })
+    }
+}
+
+/// Replies to `post_id` for the root replies field and Content.replies
+async fn replies_page(
+    ctx: &Context<'_>,
+    post_id: &str,
+    requester_pubkey: &str,
+    limit: Option<u32>,
+    before: Option<String>,
+    after: Option<String>,
+) -> Result<ContentPage> {
+    let state = ctx.data::<Arc<AppState>>()?;
+    let options = page_options(state, "get-replies", limit, before, after)?;
+
+    let page = state
+        .db
+        .get_replies_by_post_id(post_id, requester_pubkey, options)
+        .await
+        .map_err(database_error)?;
+    Ok(ContentPage::from_replies(page, requester_pubkey))
+}
+
+/// Post, reply, quote or vote, with the fields of the REST ServerPost
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Content {
+    id: String,
+    user_public_key: String,
+    /// Base64 encoded message (masked when the author is blocked by the requester)
+    post_content: String,
+    signature: String,
+    timestamp: u64,
+    /// "post", "reply", "quote" or "vote"
+    content_type: Option<String>,
+    /// Content replied to or voted on
+    parent_post_id: Option<String>,
+    replies_count: u64,
+    up_votes_count: u64,
+    down_votes_count: u64,
+    quotes_count: u64,
+    reposts_count: u64,
+    mentioned_pubkeys: Vec<String>,
+    /// Total number of mentions, only when mentionedPubkeys lists the first ones
+    mentions_count: Option<u64>,
+    render_spans: Option<Vec<Span>>,
+    is_upvoted: Option<bool>,
+    is_downvoted: Option<bool>,
+    /// "upvote" or "downvote" for votes
+    vote: Option<String>,
+    user_nickname: Option<String>,
+    user_profile_image: Option<String>,
+    blocked_user: Option<bool>,
+    is_quote: bool,
+    quote: Option<Quote>,
+    #[graphql(skip)]
+    requester_pubkey: String,
+}
+
+#[ComplexObject]
+impl Content {
+    /// Replies to this content, newest first
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn replies(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        replies_page(ctx, &self.id, &self.requester_pubkey, limit, before, after).await
+    }
+}
+
+impl Content {
+    fn new(post: ServerPost, requester_pubkey: &str) -> Self {
+        Self {
+            id: post.id,
+            user_public_key: post.user_public_key,
+            post_content: post.post_content,
+            signature: post.signature,
+            timestamp: post.timestamp,
+            content_type: post.content_type,
+            parent_post_id: post.parent_post_id,
+            replies_count: post.replies_count,
+            up_votes_count: post.up_votes_count,
+            down_votes_count: post.down_votes_count,
+            quotes_count: post.quotes_count,
+            reposts_count: post.reposts_count,
+            mentioned_pubkeys: post.mentioned_pubkeys,
+            mentions_count: post.mentions_count,
+            render_spans: post
+                .render_spans
+                .map(|spans| spans.into_iter().map(Span::from).collect()),
+            is_upvoted: post.is_upvoted,
+            is_downvoted: post.is_downvoted,
+            vote: None,
+            user_nickname: post.user_nickname,
+            user_profile_image: post.user_profile_image,
+            blocked_user: post.blocked_user,
+            is_quote: post.is_quote,
+            quote: post.quote.map(Quote::from),
+            requester_pubkey: requester_pubkey.to_string(),
+        }
+    }
+
+    fn from_post(record: &KPostRecord, is_blocked: bool, requester_pubkey: &str) -> Self {
+        Self::new(
+            ServerPost::from_enriched_k_post_record_with_block_status(record, is_blocked),
+            requester_pubkey,
+        )
+    }
+
+    fn from_reply(record: &KReplyRecord, is_blocked: bool, requester_pubkey: &str) -> Self {
+        Self::new(
+            ServerReply::from_enriched_k_reply_record_with_block_status(record, is_blocked),
+            requester_pubkey,
+        )
+    }
+
+    fn from_vote(record: &KVoteRecord, is_blocked: bool, requester_pubkey: &str) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            user_public_key: record.sender_pubkey.clone(),
+            // Votes don't have content
+            post_content: String::new(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            content_type: Some("vote".to_string()),
+            parent_post_id: Some(record.post_id.clone()),
+            replies_count: 0,
+            up_votes_count: 0,
+            down_votes_count: 0,
+            quotes_count: 0,
+            reposts_count: 0,
+            mentioned_pubkeys: Vec::new(),
+            mentions_count: None,
+            render_spans: None,
+            is_upvoted: None,
+            is_downvoted: None,
+            vote: Some(record.vote.clone()),
+            user_nickname: record.user_nickname.clone(),
+            user_profile_image: record.user_profile_image.clone(),
+            blocked_user: Some(is_blocked),
+            is_quote: false,
+            quote: None,
+            requester_pubkey: requester_pubkey.to_string(),
+        }
+    }
+}
+
+/// Mention, hashtag or URL of the decoded message (UTF-16 offsets, end exclusive)
+#[derive(SimpleObject)]
+#[graphql(name = "RenderSpan")]
+struct Span {
+    /// "mention", "hashtag" or "url"
+    #[graphql(name = "type")]
+    span_type: String,
+    start: u32,
+    end: u32,
+    value: String,
+}
+
+impl From<RenderSpan> for Span {
+    fn from(span: RenderSpan) -> Self {
+        Self {
+            span_type: span.span_type,
+            start: span.start,
+            end: span.end,
+            value: span.value,
+        }
+    }
+}
+
+/// Content quoted by a quote
+#[derive(SimpleObject)]
+struct Quote {
+    referenced_content_id: String,
+    referenced_message: String,
+    referenced_sender_pubkey: String,
+    referenced_nickname: Option<String>,
+    referenced_profile_image: Option<String>,
+}
+
+impl From<QuoteData> for Quote {
+    fn from(quote: QuoteData) -> Self {
+        Self {
+            referenced_content_id: quote.referenced_content_id,
+            referenced_message: quote.referenced_message,
+            referenced_sender_pubkey: quote.referenced_sender_pubkey,
+            referenced_nickname: quote.referenced_nickname,
+            referenced_profile_image: quote.referenced_profile_image,
+        }
+    }
+}
+
+/// User with the latest broadcast and the follow and block status of the requester
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct User {
+    user_public_key: String,
+    /// Transaction ID of the broadcast (empty without a broadcast)
+    id: String,
+    /// Base64 encoded broadcast message
+    post_content: String,
+    signature: String,
+    timestamp: u64,
+    user_nickname: Option<String>,
+    user_profile_image: Option<String>,
+    blocked_user: Option<bool>,
+    followed_user: Option<bool>,
+    /// Counts of the user profile (user field only)
+    followers_count: Option<i64>,
+    following_count: Option<i64>,
+    blocked_count: Option<i64>,
+    #[graphql(skip)]
+    requester_pubkey: String,
+}
+
+#[ComplexObject]
+impl User {
+    /// Posts of this user, newest first (get-posts)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let options = page_options(state, "get-posts", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_posts_by_user(&self.user_public_key, &self.requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        Ok(ContentPage::from_posts(page, &self.requester_pubkey))
+    }
+
+    /// Replies of this user, newest first (get-replies with user)
+    #[graphql(complexity = "page_complexity(limit, child_complexity)")]
+    async fn replies(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<u32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result<ContentPage> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let options = page_options(state, "get-replies", limit, before, after)?;
+
+        let page = state
+            .db
+            .get_replies_by_user(&self.user_public_key, &self.requester_pubkey, options)
+            .await
+            .map_err(database_error)?;
+        Ok(ContentPage::from_replies(page, &self.requester_pubkey))
+    }
+}
+
+impl User {
+    fn new(user: ServerUserPost, requester_pubkey: &str) -> Self {
+        Self {
+            user_public_key: user.user_public_key,
+            id: user.id,
+            post_content: user.post_content,
+            signature: user.signature,
+            timestamp: user.timestamp,
+            user_nickname: user.user_nickname,
+            user_profile_image: user.user_profile_image,
+            blocked_user: user.blocked_user,
+            followed_user: user.followed_user,
+            followers_count: user.followers_count,
+            following_count: user.following_count,
+            blocked_count: user.blocked_count,
+            requester_pubkey: requester_pubkey.to_string(),
+        }
+    }
+
+    fn without_broadcast(
+        user_public_key: &str,
+        blocked: bool,
+        followed: bool,
+        requester_pubkey: &str,
+    ) -> Self {
+        Self {
+            user_public_key: user_public_key.to_string(),
+            id: String::new(),
+            post_content: String::new(),
+            signature: String::new(),
+            timestamp: 0,
+            user_nickname: None,
+            user_profile_image: None,
+            blocked_user: Some(blocked),
+            followed_user: Some(followed),
+            followers_count: None,
+            following_count: None,
+            blocked_count: None,
+            requester_pubkey: requester_pubkey.to_string(),
+        }
+    }
+}
+
+/// Cursors of a page, as in the pagination of the REST endpoints
+#[derive(SimpleObject)]
+struct Pagination {
+    has_more: bool,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
+}
+
+impl From<PaginationMetadata> for Pagination {
+    fn from(pagination: PaginationMetadata) -> Self {
+        Self {
+            has_more: pagination.has_more,
+            next_cursor: pagination.next_cursor,
+            prev_cursor: pagination.prev_cursor,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ContentPage {
+    items: Vec<Content>,
+    pagination: Pagination,
+}
+
+impl ContentPage {
+    fn from_posts(page: PaginatedResult<KPostRecord>, requester_pubkey: &str) -> Self {
+        Self {
+            items: page
+                .items
+                .iter()
+                .map(|post| Content::from_post(post, false, requester_pubkey))
+                .collect(),
+            pagination: page.pagination.into(),
+        }
+    }
+
+    fn from_replies(page: PaginatedResult<KReplyRecord>, requester_pubkey: &str) -> Self {
+        Self {
+            items: page
+                .items
+                .iter()
+                .map(|reply| Content::from_reply(reply, false, requester_pubkey))
+                .collect(),
+            pagination: page.pagination.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct UserPage {
+    items: Vec<User>,
+    pagination: Pagination,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_messages(response: &async_graphql::Response) -> Vec<String> {
+        response
+            .errors
+            .iter()
+            .map(|error| error.message.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_depth_and_complexity_limits() {
+        let schema = schema_builder().finish();
+        let requester = format!("02{}", "a".repeat(64));
+
+        // Rejected before resolving: no application state is needed
+        let nested = format!(
+            "{{ posts(requesterPubkey: \"{}\", limit: 1) {{ items {{ replies(limit: 1) {{ items {{ replies(limit: 1) {{ items {{ replies(limit: 1) {{ items {{ id }} }} }} }} }} }} }} }} }}",
+            requester
+        );
+        let response = schema.execute(nested.as_str()).await;
+        assert_eq!(error_messages(&response), ["Query is nested too deep."]);
+
+        let wide = format!(
+            "{{ posts(requesterPubkey: \"{}\", limit: 100) {{ items {{ id replies(limit: 100) {{ items {{ id }} }} }} }} }}",
+            requester
+        );
+        let response = schema.execute(wide.as_str()).await;
+        assert_eq!(error_messages(&response), ["Query is too complex."]);
+
+        // Within the limits, resolving fails without application state
+        let simple = format!(
+            "{{ posts(requesterPubkey: \"{}\") {{ items {{ id }} }} }}",
+            requester
+        );
+        let response = schema.execute(simple.as_str()).await;
+        assert_eq!(response.errors.len(), 1);
+        assert_ne!(response.errors[0].message, "Query is too complex.");
+    }
+
+    #[test]
+    fn test_page_complexity() {
+        assert_eq!(page_complexity(None, 3), 3 * COMPLEXITY_PAGE_SIZE);
+        assert_eq!(page_complexity(Some(5), 3), 15);
+        assert_eq!(page_complexity(Some(u32::MAX), usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn test_argument_checks() {
+        assert!(check_pubkey(&format!("03{}", "F".repeat(64)), "user").is_ok());
+        assert!(check_pubkey(&format!("04{}", "a".repeat(64)), "user").is_err());
+        assert!(check_pubkey(&"02".repeat(32), "user").is_err());
+        assert!(check_content_id(&"0".repeat(64), "id").is_ok());
+        assert!(check_content_id(&"g".repeat(64), "id").is_err());
+
+        let error = check_content_id("abc", "postId").unwrap_err();
+        let code = error
+            .extensions
+            .and_then(|extensions| extensions.get("code").cloned());
+        assert_eq!(code, Some(async_graphql::Value::from("INVALID_POST_ID")));
+    }
+}
@@ -0,0 +1,601 @@
+use anyhow::{Context, Result, anyhow, bail};
+use axum::http::{HeaderMap, Method, header};
+use base64ct::{Base64, Encoding};
+use chrono::{DateTime, Utc};
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePublicKey, LineEnding};
+use rsa::sha2::Sha256;
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::ttl_cache::TtlCache;
+
+/// Signed requests dated further than this from now are rejected (replay window, allowing for
+/// clock drift between servers)
+const MAX_DATE_SKEW_SECS: i64 = 5 * 60;
+
+/// Headers every accepted signature must cover
+const REQUIRED_HEADERS: &[&str] = &["(request-target)", "host", "date"];
+
+/// Remote actor keys are reused for this long before being fetched again
+const KEY_CACHE_TTL: Duration = Duration::from_secs(3600);
+const KEY_CACHE_MAX_ENTRIES: usize = 10_000;
+
+/// A keyId is fetched at most once per interval, successfully or not: a rotated key is picked
+/// up then, and a keyId that fails to dereference is not fetched for every request signed with it
+const KEY_REFETCH_INTERVAL: Duration = Duration::from_secs(300);
+
+const KEY_FETCH_MAX_REDIRECTS: usize = 3;
+
+const KEY_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+// Actor documents are small; anything bigger is not a key
+const KEY_FETCH_MAX_BYTES: usize = 256 * 1024;
+
+/// Parameters of a Signature header (draft-cavage-http-signatures)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureParams {
+    pub key_id: String,
+    pub algorithm: Option<String>,
+    pub headers: Vec<String>,
+    pub signature: String,
+}
+
+/// Parse `keyId="...",algorithm="...",headers="...",signature="..."`
+pub fn parse_signature_header(value: &str) -> Option<SignatureParams> {
+    let mut params = HashMap::new();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let (name, after_name) = rest.split_once('=')?;
+        let (param_value, after_value) = match after_name.strip_prefix('"') {
+            Some(quoted) => {
+                let (param_value, after_quote) = quoted.split_once('"')?;
+                (param_value, after_quote)
+            }
+            None => after_name
+                .split_once(',')
+                .map_or((after_name, ""), |(param_value, after)| {
+                    (param_value, after)
+                }),
+        };
+        params.insert(name.trim().to_ascii_lowercase(), param_value.to_string());
+        rest = after_value.trim_start_matches([',', ' ']);
+    }
+
+    Some(SignatureParams {
+        key_id: params.remove("keyid")?,
+        algorithm: params.remove("algorithm"),
+        // The draft defaults to the Date header alone
+        headers: params
+            .remove("headers")
+            .unwrap_or_else(|| "date".to_string())
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect(),
+        signature: params.remove("signature")?,
+    })
+}
+
+/// Signing string over the listed headers; None when a listed header is missing
+pub fn signing_string(
+    method: &Method,
+    path_and_query: &str,
+    headers: &HeaderMap,
+    signed_headers: &[String],
+) -> Option<String> {
+    let lines: Option<Vec<String>> = signed_headers
+        .iter()
+        .map(|name| {
+            if name == "(request-target)" {
+                return Some(format!(
+                    "(request-target): {} {}",
+                    method.as_str().to_ascii_lowercase(),
+                    path_and_query
+                ));
+            }
+            let values: Vec<&str> = headers
+                .get_all(name.as_str())
+                .iter()
+                .map(|value| value.to_str().map(str::trim))
+                .collect::<Result<_, _>>()
+                .ok()?;
+            (!values.is_empty()).then(|| format!("{}: {}", name, values.join(", ")))
+        })
+        .collect();
+
+    lines.map(|lines| lines.join("\n"))
+}
+
+/// Whether an HTTP date is close enough to now to accept a signature over it
+pub fn date_within_skew(date: &str, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc2822(date)
+        .map(|date| (now - date.with_timezone(&Utc)).num_seconds().abs() <= MAX_DATE_SKEW_SECS)
+        .unwrap_or(false)
+}
+
+fn http_date(now: DateTime<Utc>) -> String {
+    now.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether a key fetch may connect to `ip`: keyIds are chosen by whoever signs the request, so
+/// loopback, private, link-local and other non-public addresses are never fetched
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // This network (0.0.0.0/8), shared address space (100.64.0.0/10), IETF protocol
+        // assignments (192.0.0.0/24), benchmarking (198.18.0.0/15) and reserved (240.0.0.0/4)
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let [first, second, ..] = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Documentation (2001:db8::/32)
+        || (first == 0x2001 && second == 0x0db8))
+}
+
+/// Check a key URL (keyId or redirect) before it is fetched: https only, and hosts given as an
+/// IP address must be public (names are checked when they are resolved, see PublicResolver)
+fn check_key_url(url: &reqwest::Url) -> Result<()> {
+    if url.scheme() != "https" {
+        bail!("keyId must be an https URL");
+    }
+    let host = url.host_str().ok_or_else(|| anyhow!("keyId has no host"))?;
+    // IPv6 hosts are bracketed
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) if !is_public_address(ip) => bail!("keyId host {} is not a public address", ip),
+        _ => Ok(()),
+    }
+}
+
+/// DNS resolver of the key fetches, refusing names with any non-public address. Checking the
+/// addresses actually connected to also covers redirects and names re-pointed between lookups
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip())) {
+                return Err(
+                    format!("{} resolves to non-public address {}", host, addr.ip()).into(),
+                );
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// RSA key shared by the ActivityPub actors, signing the fetches made by the webserver
+pub struct InstanceKey {
+    signing_key: SigningKey<Sha256>,
+    public_key_pem: String,
+}
+
+impl InstanceKey {
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self> {
+        let private_key =
+            RsaPrivateKey::from_pkcs8_pem(pem).context("Invalid PKCS#8 PEM RSA private key")?;
+        let public_key_pem = private_key
+            .to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .context("Failed to encode RSA public key")?;
+
+        Ok(Self {
+            signing_key: SigningKey::new(private_key),
+            public_key_pem,
+        })
+    }
+
+    pub fn public_key_pem(&self) -> &str {
+        &self.public_key_pem
+    }
+
+    /// Base64 RSA-SHA256 signature of a signing string
+    pub fn sign(&self, signing_string: &str) -> String {
+        Base64::encode_string(&self.signing_key.sign(signing_string.as_bytes()).to_bytes())
+    }
+}
+
+/// Verifies HTTP signatures of incoming fetches against the keys of remote actors
+pub struct HttpSignatures {
+    instance_key: InstanceKey,
+    // keyId of the instance actor, signing the key fetches
+    instance_key_id: String,
+    client: reqwest::Client,
+    key_cache: TtlCache<String, RsaPublicKey>,
+    // Outcome of the keyId fetches of the last KEY_REFETCH_INTERVAL (the error when it failed)
+    recent_fetches: TtlCache<String, Result<(), String>>,
+}
+
+impl HttpSignatures {
+    pub fn new(instance_key: InstanceKey, instance_key_id: String) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(KEY_FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() > KEY_FETCH_MAX_REDIRECTS {
+                    attempt.error("Too many redirects")
+                } else if let Err(e) = check_key_url(attempt.url()) {
+                    attempt.error(e)
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .dns_resolver(Arc::new(PublicResolver))
+            // A proxy would resolve the names itself, past PublicResolver
+            .no_proxy()
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()?;
+
+        Ok(Self {
+            instance_key,
+            instance_key_id,
+            client,
+            key_cache: TtlCache::new(KEY_CACHE_TTL, KEY_CACHE_MAX_ENTRIES),
+            recent_fetches: TtlCache::new(KEY_REFETCH_INTERVAL, KEY_CACHE_MAX_ENTRIES),
+        })
+    }
+
+    /// Drop expired remote keys and fetch outcomes; returns the number of keys left
+    pub fn prune_key_cache(&self, now: Instant) -> usize {
+        self.recent_fetches.prune(now);
+        self.key_cache.prune(now)
+    }
+
+    pub fn public_key_pem(&self) -> &str {
+        self.instance_key.public_key_pem()
+    }
+
+    /// Verify the Signature header of a request, returning the keyId that signed it
+    pub async fn verify(
+        &self,
+        method: &Method,
+        path_and_query: &str,
+        headers: &HeaderMap,
+    ) -> Result<String> {
+        let params = headers
+            .get("signature")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_signature_header)
+            .ok_or_else(|| anyhow!("Missing or malformed Signature header"))?;
+
+        if let Some(algorithm) = &params.algorithm {
+            // hs2019 leaves the algorithm to the key, which is always RSA in the fediverse
+            if algorithm != "rsa-sha256" && algorithm != "hs2019" {
+                bail!("Unsupported signature algorithm {}", algorithm);
+            }
+        }
+        if let Some(missing) = REQUIRED_HEADERS
+            .iter()
+            .find(|required| !params.headers.iter().any(|name| name == *required))
+        {
+            bail!("Signature must cover {}", missing);
+        }
+
+        let date = headers
+            .get(header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if !date_within_skew(date, Utc::now()) {
+            bail!("Date header missing or out of range");
+        }
+
+        let signing_string = signing_string(method, path_and_query, headers, &params.headers)
+            .ok_or_else(|| anyhow!("Signed header missing from request"))?;
+        let signature_bytes =
+            Base64::decode_vec(&params.signature).map_err(|_| anyhow!("Invalid signature"))?;
+        let signature = Signature::try_from(signature_bytes.as_slice())?;
+
+        let (public_key, cached) = self.public_key(&params.key_id).await?;
+        if verify_with(&public_key, &signing_string, &signature) {
+            return Ok(params.key_id);
+        }
+
+        // The remote actor may have rotated its key since it was cached, unless it was fetched
+        // again lately
+        if cached
+            && self
+                .recent_fetches
+                .get(&params.key_id, Instant::now())
+                .is_none()
+        {
+            let public_key = self.fetch_public_key(&params.key_id).await?;
+            if verify_with(&public_key, &signing_string, &signature) {
+                return Ok(params.key_id);
+            }
+        }
+
+        bail!("Signature verification failed")
+    }
+
+    /// Public key of a keyId with whether it came from the cache
+    async fn public_key(&self, key_id: &str) -> Result<(RsaPublicKey, bool)> {
+        let now = Instant::now();
+        if let Some(key) = self.key_cache.get(key_id, now) {
+            return Ok((key, true));
+        }
+        if let Some(Err(e)) = self.recent_fetches.get(key_id, now) {
+            bail!("Key fetch failed lately: {}", e);
+        }
+
+        Ok((self.fetch_public_key(key_id).await?, false))
+    }
+
+    /// Dereference a keyId with a signed fetch, caching its key and the outcome of the fetch
+    async fn fetch_public_key(&self, key_id: &str) -> Result<RsaPublicKey> {
+        let result = self.fetch_key_document(key_id).await;
+        let now = Instant::now();
+        match &result {
+            Ok(public_key) => {
+                self.key_cache
+                    .insert(key_id.to_string(), public_key.clone(), now);
+                self.recent_fetches.insert(key_id.to_string(), Ok(()), now);
+            }
+            Err(e) => {
+                self.recent_fetches
+                    .insert(key_id.to_string(), Err(e.to_string()), now);
+            }
+        }
+        result
+    }
+
+    /// Key of a keyId, from its actor or key document
+    async fn fetch_key_document(&self, key_id: &str) -> Result<RsaPublicKey> {
+        let mut url = reqwest::Url::parse(key_id).context("keyId is not a URL")?;
+        check_key_url(&url)?;
+        url.set_fragment(None);
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!("keyId has no host"),
+        };
+        let path_and_query = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let date = http_date(Utc::now());
+
+        // Servers enforcing authorized fetch only serve actor documents to signed requests
+        let signing_string = format!(
+            "(request-target): get {}\nhost: {}\ndate: {}",
+            path_and_query, host, date
+        );
+        let signature = format!(
+            "keyId=\"{}\",algorithm=\"rsa-sha256\",headers=\"(request-target) host date\",signature=\"{}\"",
+            self.instance_key_id,
+            self.instance_key.sign(&signing_string)
+        );
+
+        let mut response = self
+            .client
+            .get(url)
+            .header(
+                header::ACCEPT,
+                "application/activity+json, application/ld+json",
+            )
+            .header(header::DATE, date)
+            .header("signature", signature)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > KEY_FETCH_MAX_BYTES {
+                bail!("Key document too large");
+            }
+        }
+
+        let document: serde_json::Value = serde_json::from_slice(&body)?;
+        let pem = public_key_pem(&document, key_id)
+            .ok_or_else(|| anyhow!("No public key {} in fetched document", key_id))?;
+        let public_key = RsaPublicKey::from_public_key_pem(pem)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+            .context("Invalid remote public key")?;
+
+        Ok(public_key)
+    }
+}
+
+fn verify_with(public_key: &RsaPublicKey, signing_string: &str, signature: &Signature) -> bool {
+    VerifyingKey::<Sha256>::new(public_key.clone())
+        .verify(signing_string.as_bytes(), signature)
+        .is_ok()
+}
+
+/// PEM of a key in an actor document (publicKey object or array) or in a bare key document
+pub fn public_key_pem<'a>(document: &'a serde_json::Value, key_id: &str) -> Option<&'a str> {
+    let keys: Vec<&serde_json::Value> = match &document["publicKey"] {
+        serde_json::Value::Array(keys) => keys.iter().collect(),
+        serde_json::Value::Object(_) => vec![&document["publicKey"]],
+        _ => vec![document],
+    };
+
+    let only = match keys.as_slice() {
+        [only] => Some(*only),
+        _ => None,
+    };
+    keys.iter()
+        .copied()
+        .find(|key| key["id"].as_str() == Some(key_id))
+        .or(only)
+        .and_then(|key| key["publicKeyPem"].as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_signature_header() {
+        let params = parse_signature_header(
+            r#"keyId="https://a.example/users/x#main-key",algorithm="rsa-sha256",headers="(request-target) Host date",signature="c2ln""#,
+        )
+        .unwrap();
+        assert_eq!(params.key_id, "https://a.example/users/x#main-key");
+        assert_eq!(params.algorithm.as_deref(), Some("rsa-sha256"));
+        assert_eq!(params.headers, vec!["(request-target)", "host", "date"]);
+        assert_eq!(params.signature, "c2ln");
+
+        let defaults = parse_signature_header(r#"keyId="k", signature="c2ln""#).unwrap();
+        assert_eq!(defaults.headers, vec!["date"]);
+        assert_eq!(defaults.algorithm, None);
+
+        assert!(parse_signature_header(r#"keyId="k""#).is_none());
+        assert!(parse_signature_header(r#"keyId="k,signature="c2ln"#).is_none());
+    }
+
+    #[test]
+    fn test_signing_string() {
+        let mut headers = HeaderMap::new();
+        headers.insert("host", HeaderValue::from_static("k.example.org"));
+        headers.insert(
+            "date",
+            HeaderValue::from_static("Fri, 07 Jun 2024 20:51:35 GMT"),
+        );
+        let signed: Vec<String> = ["(request-target)", "host", "date"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        assert_eq!(
+            signing_string(&Method::GET, "/ap/users/02ab?page=true", &headers, &signed).unwrap(),
+            "(request-target): get /ap/users/02ab?page=true\nhost: k.example.org\ndate: Fri, 07 Jun 2024 20:51:35 GMT"
+        );
+
+        let with_digest = vec!["digest".to_string()];
+        assert!(signing_string(&Method::GET, "/", &headers, &with_digest).is_none());
+    }
+
+    #[test]
+    fn test_date_within_skew() {
+        let now = DateTime::parse_from_rfc2822("Fri, 07 Jun 2024 20:51:35 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(date_within_skew(&http_date(now), now));
+        assert!(date_within_skew("Fri, 07 Jun 2024 20:47:35 GMT", now));
+        assert!(date_within_skew("Fri, 07 Jun 2024 20:55:00 GMT", now));
+        assert!(!date_within_skew("Fri, 07 Jun 2024 20:40:00 GMT", now));
+        assert!(!date_within_skew("Fri, 07 Jun 2024 09:00:00 GMT", now));
+        assert!(!date_within_skew("not a date", now));
+    }
+
+    #[test]
+    fn test_is_public_address() {
+        for public in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_address(public.parse().unwrap()), "{}", public);
+        }
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.10",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(
+                !is_public_address(internal.parse().unwrap()),
+                "{}",
+                internal
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_key_url() {
+        let url = |url: &str| reqwest::Url::parse(url).unwrap();
+        assert!(check_key_url(&url("https://a.example/users/x#main-key")).is_ok());
+        assert!(check_key_url(&url("https://93.184.216.34/key")).is_ok());
+        assert!(check_key_url(&url("http://a.example/users/x")).is_err());
+        assert!(check_key_url(&url("https://127.0.0.1/key")).is_err());
+        assert!(check_key_url(&url("https://[::1]:8443/key")).is_err());
+        assert!(check_key_url(&url("https://169.254.169.254/latest/meta-data")).is_err());
+    }
+
+    #[test]
+    fn test_public_key_pem() {
+        let actor = json!({
+            "id": "https://a.example/users/x",
+            "publicKey": {"id": "https://a.example/users/x#main-key", "publicKeyPem": "PEM"}
+        });
+        assert_eq!(
+            public_key_pem(&actor, "https://a.example/users/x#main-key"),
+            Some("PEM")
+        );
+
+        let multiple = json!({
+            "publicKey": [
+                {"id": "https://a.example/users/x#old", "publicKeyPem": "OLD"},
+                {"id": "https://a.example/users/x#new", "publicKeyPem": "NEW"}
+            ]
+        });
+        assert_eq!(
+            public_key_pem(&multiple, "https://a.example/users/x#new"),
+            Some("NEW")
+        );
+        assert_eq!(
+            public_key_pem(&multiple, "https://a.example/users/x#gone"),
+            None
+        );
+
+        let key_document = json!({"id": "https://a.example/key", "publicKeyPem": "KEY"});
+        assert_eq!(
+            public_key_pem(&key_document, "https://a.example/key"),
+            Some("KEY")
+        );
+    }
+}
@@ -0,0 +1,750 @@
+mod activitypub;
+mod api_handlers;
+mod api_version;
+mod atom_feed;
+mod block_filter;
+mod config;
+mod count_buckets;
+mod database_postgres_impl;
+mod database_trait;
+mod doctor;
+mod feature_flags;
+mod graphql;
+mod http_signatures;
+mod live_feed;
+mod load_shedder;
+mod models;
+mod named_query;
+mod nickname_normalizer;
+mod openapi;
+mod payload_simulator;
+mod post_preview;
+mod query_builder;
+mod replica_lag;
+mod request_analytics;
+mod request_id;
+mod requester_privacy;
+mod response_cache;
+mod shadow_query;
+mod slow_requests;
+mod tenants;
+mod ttl_cache;
+mod verify_indexes;
+mod view_counter;
+mod web_server;
+
+use clap::{Parser, Subcommand};
+use config::AppConfig;
+use database_postgres_impl::PostgresDbManager;
+use k_common::{failover, pool_monitor};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use web_server::WebServer;
+
+pub use k_client::models::{PayloadSimulationResponse, SimulatedTableWrites};
+pub use payload_simulator::{PayloadSimulator, Simulation};
+pub use slow_requests::statement_capture_layer;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "K-indexer PostgreSQL webserver", long_about = None)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        short = 'H',
+        long,
+        help = "Database host, or comma-separated hosts (host[:port]) tried in order and failed over to"
+    )]
+    db_host: String,
+
+    #[arg(short = 'P', long, default_value = "5432", help = "Database port")]
+    db_port: u16,
+
+    #[arg(
+        long,
+        default_value = "any",
+        help = "Host of --db-host connections go to: 'any' (the first reachable) or 'read-write' (the first accepting writes, the primary)"
+    )]
+    db_target_session_attrs: String,
+
+    #[arg(short = 'd', long, help = "Database name")]
+    db_name: String,
+
+    #[arg(short = 'u', long, help = "Database username")]
+    db_user: String,
+
+    #[arg(short = 'p', long, help = "Database password")]
+    db_password: String,
+
+    #[arg(
+        short = 'm',
+        long,
+        help = "Maximum database connections (defaults to worker_threads * 3)"
+    )]
+    db_max_connections: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Give up if the database is not reachable within this many seconds (default: wait indefinitely)"
+    )]
+    wait_for_db: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Wait for K-transaction-processor to publish the schema version (k_meta) before serving"
+    )]
+    wait_for_schema: bool,
+
+    #[arg(
+        long,
+        help = "Cancel database statements running longer than this many milliseconds (default: request timeout, 0 = no timeout)"
+    )]
+    statement_timeout_ms: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Tag every database connection checkout with the request ID (application_name and k.request_id), at the cost of one statement per checkout"
+    )]
+    db_request_id: bool,
+
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Also run the candidate rewrites of the feed queries for this percentage of their calls and log how their rows and durations compare (0-100, 0 = off)"
+    )]
+    shadow_query_percent: u8,
+
+    #[arg(
+        long,
+        help = "Read replica host serving paginated reads (same database name and credentials as the primary)"
+    )]
+    replica_db_host: Option<String>,
+
+    #[arg(long, help = "Read replica port (default: --db-port)")]
+    replica_db_port: Option<u16>,
+
+    #[arg(
+        long,
+        default_value = "5000",
+        help = "Send paginated reads to the primary while the read replica lags more than this many milliseconds"
+    )]
+    replica_max_lag_ms: u64,
+
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Reload an in-memory bloom filter of k_blocks every this many milliseconds and leave the block join out of the feeds of requesters blocking nobody; new blocks apply to such feeds once reloaded (0 = off)"
+    )]
+    block_filter_refresh_ms: u64,
+
+    #[arg(short = 'w', long, help = "Number of worker threads for Tokio runtime")]
+    worker_threads: Option<usize>,
+
+    #[arg(
+        short = 't',
+        long,
+        default_value = "30",
+        help = "Request timeout in seconds"
+    )]
+    request_timeout: u64,
+
+    #[arg(
+        short = 'r',
+        long,
+        default_value = "100",
+        help = "Rate limit: requests per minute per IP"
+    )]
+    rate_limit: u32,
+
+    #[arg(
+        long,
+        default_value = "100000",
+        help = "Client addresses tracked by the rate limiter at most; the least recently seen are forgotten first"
+    )]
+    rate_limit_max_clients: usize,
+
+    #[arg(
+        short = 'b',
+        long,
+        default_value = "127.0.0.1:8080",
+        help = "Server bind address"
+    )]
+    bind_address: String,
+
+    #[arg(
+        long,
+        default_value = "60",
+        help = "Interval in seconds between schema version/feature checks (k_meta)"
+    )]
+    schema_refresh_interval: u64,
+
+    #[arg(
+        long,
+        default_value = "database",
+        help = "Profile image storage used by the processor: database, filesystem or s3"
+    )]
+    image_storage: String,
+
+    #[arg(
+        long,
+        help = "Directory of stored profile images (required with --image-storage=filesystem)"
+    )]
+    image_storage_path: Option<String>,
+
+    #[arg(
+        long,
+        help = "Public base URL of the image bucket/CDN (required with --image-storage=s3)"
+    )]
+    image_base_url: Option<String>,
+
+    #[arg(
+        long,
+        help = "Hide content flagged as rate-limited by K-transaction-processor (schema v7+)"
+    )]
+    hide_rate_limited: bool,
+
+    #[arg(
+        long,
+        default_value = "3600",
+        help = "Collapse repeated vote, reply and quote notifications of one sender on the same content within windows of this many seconds (schema v23+, 0 = disabled)"
+    )]
+    notification_collapse_window: u64,
+
+    #[arg(
+        long,
+        default_value = "20",
+        help = "Page size used when a paginated request has no limit parameter"
+    )]
+    default_page_size: u32,
+
+    #[arg(
+        long,
+        default_value = "100",
+        help = "Maximum accepted limit parameter of paginated requests"
+    )]
+    max_page_size: u32,
+
+    #[arg(
+        long,
+        default_value = "get-notifications=10:50,get-users=50:200",
+        help = "Per-endpoint page sizes as endpoint=default:max, comma separated"
+    )]
+    page_size_overrides: String,
+
+    #[arg(
+        long,
+        default_value = "15",
+        help = "Interval in seconds between connection pool samples (Prometheus gauges)"
+    )]
+    pool_monitor_interval: u64,
+
+    #[arg(
+        long,
+        default_value = "500",
+        help = "Warn when acquiring a DB connection takes longer than this many milliseconds"
+    )]
+    pool_acquire_warn_ms: u64,
+
+    #[arg(
+        long,
+        help = "Enable /track-view and approximate view counts in post details (schema v13+)"
+    )]
+    enable_view_tracking: bool,
+
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Round public vote and view counts down to a multiple of this size and withhold vote weights, so single votes and views cannot be told apart on low-traffic nodes (0 = exact counts)"
+    )]
+    count_bucket_size: u64,
+
+    #[arg(
+        long,
+        help = "Read the contents and profiles of other users for an anonymous requester and answer null isUpvoted, isDownvoted, blockedUser and followedUser, so the node cannot be used to probe the relations of other users"
+    )]
+    requester_privacy: bool,
+
+    #[arg(
+        long,
+        help = "Keep hourly counts of the requests per public endpoint and per content ID for this many hours, reported by /admin/request-analytics (no address or requester is recorded; requires --admin-token)"
+    )]
+    request_analytics_hours: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Log the statements of requests taking this many milliseconds or more: SQL, row counts and timings, without query string or bind values"
+    )]
+    slow_request_trace_ms: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Enable the /ws live feed of newly indexed posts, replies, quotes and votes"
+    )]
+    enable_live_feed: bool,
+
+    #[arg(
+        long,
+        default_value = "1000",
+        help = "WebSocket connections served at once by the live feed; further ones answer 503"
+    )]
+    live_feed_max_clients: usize,
+
+    #[arg(
+        long,
+        help = "Enable the read-only /graphql endpoint over posts, replies, votes, users, mentions and blocks"
+    )]
+    enable_graphql: bool,
+
+    #[arg(
+        long,
+        default_value = "",
+        help = "Feature flags as flag=on|off|percent, comma separated (override k_feature_flags, schema v14+)"
+    )]
+    feature_flags: String,
+
+    #[arg(
+        long,
+        help = "Bearer token required by the /admin endpoints (admin endpoints are disabled without it)"
+    )]
+    admin_token: Option<String>,
+
+    #[arg(
+        long,
+        help = "Public base URL of the webserver (e.g. https://k.example.org); exposes K users as read-only ActivityPub actors under /ap"
+    )]
+    activitypub_base_url: Option<String>,
+
+    #[arg(
+        long,
+        help = "PKCS#8 PEM RSA private key of the ActivityPub actors (required with --activitypub-base-url)"
+    )]
+    activitypub_key_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Serve ActivityPub actors, outboxes and notes to HTTP-signed fetches only (authorized fetch)"
+    )]
+    activitypub_require_signatures: bool,
+
+    #[arg(
+        long,
+        help = "K client URL linked from the Atom feeds (enables /feeds/user/{pubkey}.xml and /feeds/watching.xml)"
+    )]
+    feed_client_url: Option<String>,
+
+    #[arg(
+        long,
+        default_value = "/post/{id}",
+        help = "Client path of a post linked from feed entries ({id} = post id)"
+    )]
+    feed_post_path: String,
+
+    #[arg(
+        long,
+        default_value = "/user/{pubkey}",
+        help = "Client path of a user linked from feeds ({pubkey} = user public key)"
+    )]
+    feed_user_path: String,
+
+    #[arg(
+        long,
+        help = "Public base URL of the webserver; enables /p/{txid} link preview pages and /sitemap.xml"
+    )]
+    preview_base_url: Option<String>,
+
+    #[arg(
+        long,
+        help = "Kaspa block explorer linked from post details, preview pages and /.well-known/k-indexer: a base URL, or network=URL entries comma separated (e.g. mainnet=https://explorer.kaspa.org,testnet-10=https://explorer-tn10.kaspa.org)"
+    )]
+    explorer_url: Option<String>,
+
+    #[arg(
+        long,
+        default_value = "/txs/{id}",
+        help = "Explorer path of a transaction ({id} = transaction id, with --explorer-url)"
+    )]
+    explorer_transaction_path: String,
+
+    #[arg(
+        long,
+        default_value = "/blocks/{hash}",
+        help = "Explorer path of a block ({hash} = block hash, with --explorer-url)"
+    )]
+    explorer_block_path: String,
+
+    #[arg(
+        long,
+        help = "Reject expensive endpoints with 503 + Retry-After while the database is under pressure"
+    )]
+    load_shedding: bool,
+
+    #[arg(
+        long,
+        default_value = "1000",
+        help = "Shed load when acquiring a DB connection takes this many milliseconds (with --load-shedding)"
+    )]
+    shed_acquire_ms: u64,
+
+    #[arg(
+        long,
+        default_value = "256",
+        help = "Shed load when more requests than this are in flight (with --load-shedding)"
+    )]
+    shed_max_in_flight: usize,
+
+    #[arg(
+        long,
+        default_value = "10",
+        help = "Minimum seconds load shedding lasts once started, sent as Retry-After (with --load-shedding)"
+    )]
+    shed_hold_secs: u64,
+
+    #[arg(
+        long,
+        default_value = "get-mentions,get-notifications,get-contents-following,get-posts-following,search-users",
+        help = "Endpoints rejected while shedding load, comma separated (with --load-shedding)"
+    )]
+    shed_endpoints: String,
+
+    #[arg(
+        long,
+        default_value = "",
+        help = "Endpoints answered with 501 on this node, comma separated (e.g. get-mentions,search-users); listed in /.well-known/k-indexer"
+    )]
+    disabled_endpoints: String,
+
+    #[arg(
+        long,
+        default_value = "",
+        help = "K communities indexed by the processor's --tenant, served under /t/{id} and on their hostname: id or id=hostname entries, comma separated"
+    )]
+    tenants: String,
+
+    #[arg(
+        long,
+        help = "Serve first pages of /get-users and /get-posts-watching from an in-process cache for this many seconds"
+    )]
+    response_cache_ttl: Option<u64>,
+
+    #[arg(
+        long,
+        default_value = "30",
+        help = "Seconds an expired cached page is still served while it is refreshed in the background (with --response-cache-ttl)"
+    )]
+    response_cache_stale: u64,
+
+    #[arg(
+        long,
+        default_value = "5",
+        help = "Seconds between refreshes of the global feed and trending hashtags first pages, 0 disables (with --response-cache-ttl)"
+    )]
+    response_cache_prewarm_interval: u64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check database connectivity, extensions, indexes, schema version, notification channel
+    /// and disk space, print a report and exit (non-zero exit code if a check fails)
+    Doctor,
+    /// EXPLAIN the queries of the DatabaseInterface methods behind the public endpoints against
+    /// the database, print a report and exit (non-zero exit code if a query reads a large table
+    /// with a sequential scan)
+    VerifyIndexes {
+        #[arg(
+            long,
+            default_value = "10000",
+            help = "Estimated rows from which a sequentially scanned table fails the check"
+        )]
+        min_rows: u64,
+    },
+}
+
+/// Serve the API or run the subcommand of `args` on a runtime of --worker-threads threads
+/// (tracing is initialized by the caller: K-webserver or the k-indexer CLI)
+pub fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    run_with_simulator(args, None)
+}
+
+/// `run`, serving /admin/simulate-payload with `simulator` (k-indexer only: the payloads are
+/// simulated by K-transaction-processor)
+pub fn run_with_simulator(
+    args: Args,
+    simulator: Option<Arc<dyn PayloadSimulator>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Determine worker thread count
+    let worker_threads = args.worker_threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+    });
+
+    // Load configuration from CLI arguments only, reporting every invalid option at once
+    let config = match AppConfig::from_args(&args, worker_threads) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+
+    // Build custom Tokio runtime
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(worker_threads)
+        .enable_all()
+        .build()?;
+
+    // Run the async main function
+    runtime.block_on(async_main(args, config, worker_threads, simulator))
+}
+
+async fn async_main(
+    args: Args,
+    config: AppConfig,
+    worker_threads: usize,
+    simulator: Option<Arc<dyn PayloadSimulator>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!(
+        "Starting K-indexer PostgreSQL webserver v{}",
+        env!("CARGO_PKG_VERSION")
+    );
+    info!("Using {} worker threads", worker_threads);
+    info!("Request timeout: {}s", args.request_timeout);
+    info!("Rate limit: {} requests/minute per IP", args.rate_limit);
+
+    match args.command {
+        Some(Command::Doctor) => return doctor::run(&config).await,
+        Some(Command::VerifyIndexes { min_rows }) => {
+            return verify_indexes::run(&config, min_rows).await;
+        }
+        None => {}
+    }
+
+    let connection_string = config.connection_string();
+    let hosts: Vec<String> = config
+        .database
+        .hosts
+        .iter()
+        .map(|host| host.to_string())
+        .collect();
+    info!("Connecting to database at {}", hosts.join(","));
+
+    // Create database connection
+    info!(
+        "Creating database connection pool with {} max connections",
+        config.database.max_connections
+    );
+    let db_manager = match PostgresDbManager::new(
+        &connection_string,
+        &config.database.hosts,
+        config.database.target_session_attrs,
+        config.database.max_connections as u32,
+        config.database.wait_for_db_secs,
+        config.database.statement_timeout_ms,
+        config.database.tag_request_id,
+    )
+    .await
+    {
+        Ok(manager) => {
+            info!("Successfully connected to PostgreSQL database");
+            info!("Database pool connection test successful");
+            manager
+                .with_hide_rate_limited(config.server.hide_rate_limited)
+                .with_notification_collapse_window(config.server.notification_collapse_window_secs)
+                .with_shadow_queries(config.database.shadow_query_percent)
+        }
+        Err(e) => {
+            error!("Failed to connect to PostgreSQL database: {}", e);
+            error!("Make sure PostgreSQL is running and the database/user exists");
+            error!(
+                "Connection string (without password): postgresql://{}@{}:{}/{}",
+                config.database.username,
+                config.database.host,
+                config.database.port,
+                config.database.database
+            );
+            return Err(e.into());
+        }
+    };
+
+    // Send paginated reads to the read replica while its lag allows (--replica-db-host)
+    let db_manager = match (&config.database.replica, config.replica_connection_string()) {
+        (Some(replica), Some(replica_connection_string)) => {
+            info!(
+                "Connecting to read replica at {}:{} (max lag {}ms)",
+                replica.host, replica.port, replica.max_lag_ms
+            );
+            let replica_manager = PostgresDbManager::new(
+                &replica_connection_string,
+                &[],
+                failover::TargetSessionAttrs::Any,
+                config.database.max_connections as u32,
+                config.database.wait_for_db_secs,
+                config.database.statement_timeout_ms,
+                config.database.tag_request_id,
+            )
+            .await
+            .inspect_err(|e| error!("Failed to connect to the read replica: {}", e))?;
+
+            let lag = Arc::new(replica_lag::ReplicaLag::new(replica.max_lag_ms));
+            tokio::spawn(replica_lag::start_replica_lag_monitor(
+                db_manager.pool.clone(),
+                replica_manager.pool.clone(),
+                lag.clone(),
+            ));
+            db_manager.with_replica(replica_manager.pool, lag)
+        }
+        _ => db_manager,
+    };
+    let db_manager = with_block_filter(db_manager, config.database.block_filter_refresh_ms);
+
+    // Start connection pool monitor (gauges exported on /metrics + slow acquire warnings)
+    tokio::spawn(pool_monitor::start_pool_monitor(
+        db_manager.pool.clone(),
+        config.server.pool_monitor_interval_secs,
+        config.server.pool_acquire_warn_ms,
+    ));
+
+    // Create web server
+    let pool = db_manager.pool.clone();
+    let mut failover_pools = vec![pool.clone()];
+    let db_interface: Arc<dyn database_trait::DatabaseInterface> = Arc::new(db_manager);
+    if config.database.wait_for_schema {
+        wait_for_schema_meta(db_interface.as_ref()).await;
+    }
+    let mut web_server = WebServer::new(db_interface, config.server.clone(), simulator).await;
+
+    // Forward the processor's live events to /ws clients (--enable-live-feed)
+    if let Some(live_feed) = &web_server.app_state.live_feed {
+        tokio::spawn(live_feed::start_live_feed_listener(
+            pool.clone(),
+            live_feed::LIVE_EVENTS_CHANNEL.to_string(),
+            live_feed.clone(),
+        ));
+    }
+
+    // Start load shedding probes (--load-shedding)
+    if let Some(load_shedder) = &web_server.app_state.load_shedder {
+        tokio::spawn(load_shedder::start_load_shedder(pool, load_shedder.clone()));
+    }
+
+    // Serve the communities of --tenants from their schema, on the primary only
+    for tenant in &config.server.tenants {
+        info!(
+            "Serving tenant {} from schema {} under {}",
+            tenant.id,
+            tenant.schema(),
+            tenant.path_prefix()
+        );
+        let tenant_manager = PostgresDbManager::new(
+            &config.tenant_connection_string(tenant),
+            &config.database.hosts,
+            config.database.target_session_attrs,
+            config.database.max_connections as u32,
+            config.database.wait_for_db_secs,
+            config.database.statement_timeout_ms,
+            config.database.tag_request_id,
+        )
+        .await
+        .inspect_err(|e| {
+            error!(
+                "Failed to connect to the schema of tenant {}: {}",
+                tenant.id, e
+            )
+        })?
+        .with_hide_rate_limited(config.server.hide_rate_limited)
+        .with_notification_collapse_window(config.server.notification_collapse_window_secs)
+        .with_shadow_queries(config.database.shadow_query_percent);
+        let tenant_manager =
+            with_block_filter(tenant_manager, config.database.block_filter_refresh_ms);
+
+        let tenant_pool = tenant_manager.pool.clone();
+        failover_pools.push(tenant_pool.clone());
+        let tenant_db: Arc<dyn database_trait::DatabaseInterface> = Arc::new(tenant_manager);
+        if config.database.wait_for_schema {
+            wait_for_schema_meta(tenant_db.as_ref()).await;
+        }
+        // Simulations only use the network's own schema
+        let tenant_server = WebServer::new(tenant_db, config.server.clone(), None).await;
+        if let Some(live_feed) = &tenant_server.app_state.live_feed {
+            tokio::spawn(live_feed::start_live_feed_listener(
+                tenant_pool.clone(),
+                tenant.live_events_channel(),
+                live_feed.clone(),
+            ));
+        }
+        if let Some(load_shedder) = &tenant_server.app_state.load_shedder {
+            tokio::spawn(load_shedder::start_load_shedder(
+                tenant_pool,
+                load_shedder.clone(),
+            ));
+        }
+        web_server = web_server.with_tenant(tenant.clone(), tenant_server);
+    }
+
+    // Follow the primary across failovers among the hosts of --db-host
+    if config.database.hosts.len() > 1 {
+        tokio::spawn(failover::start_failover_watcher(
+            failover_pools,
+            config.database.hosts.clone(),
+            config.database.target_session_attrs,
+        ));
+    }
+
+    info!("Starting web server on {}", config.server.bind_address);
+
+    // Start the server
+    if let Err(e) = web_server.serve(&config.server.bind_address).await {
+        error!("Web server error: {}", e);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Spare the feeds of `manager` the k_blocks join of requesters blocking nobody, with a block
+/// filter reloaded from its k_blocks every `refresh_ms` milliseconds (0 = off)
+fn with_block_filter(manager: PostgresDbManager, refresh_ms: u64) -> PostgresDbManager {
+    if refresh_ms == 0 {
+        return manager;
+    }
+    let filter = Arc::new(block_filter::BlockFilter::new(
+        std::time::Duration::from_millis(refresh_ms),
+    ));
+    tokio::spawn(block_filter::start_block_filter_refresh(
+        manager.pool.clone(),
+        filter.clone(),
+    ));
+    manager.with_block_filter(filter)
+}
+
+/// Block until K-transaction-processor has published k_meta (schema v3+)
+async fn wait_for_schema_meta(db: &dyn database_trait::DatabaseInterface) {
+    let started = std::time::Instant::now();
+
+    loop {
+        match db.get_schema_meta().await {
+            Ok(Some(meta)) => {
+                info!(
+                    "Schema v{} published by K-transaction-processor v{}",
+                    meta.schema_version, meta.processor_version
+                );
+                return;
+            }
+            Ok(None) => warn!(
+                "Waiting for K-transaction-processor to publish the schema version ({}s elapsed)",
+                started.elapsed().as_secs()
+            ),
+            Err(e) => warn!(
+                "Waiting for schema version ({}s elapsed): {}",
+                started.elapsed().as_secs(),
+                e
+            ),
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(
+            database_postgres_impl::STARTUP_RETRY_SECS,
+        ))
+        .await;
+    }
+}
@@ -0,0 +1,204 @@
+use crate::models::LiveEvent;
+use axum_prometheus::metrics::gauge;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Channel K-transaction-processor notifies when a batch saving posts, replies, quotes,
+/// votes or notifications commits
+pub const LIVE_EVENTS_CHANNEL: &str = "k_live_events";
+
+/// Delay before listening again after the LISTEN connection failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Events buffered for a slow client; a client falling further behind is disconnected
+const EVENT_BUFFER: usize = 1024;
+
+/// What a /ws or /notifications/stream connection receives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveSubscription {
+    // New posts and quotes, as listed by get-posts-watching
+    Watching,
+    // Posts, replies, quotes and votes sent by a user
+    User(String),
+    // Replies and votes in the thread of a post
+    Thread(String),
+    // New notifications of a user, as counted by get-notifications-count
+    Notifications(String),
+}
+
+impl LiveSubscription {
+    pub fn matches(&self, event: &LiveEvent) -> bool {
+        match self {
+            LiveSubscription::Watching => matches!(event.event_type.as_str(), "post" | "quote"),
+            LiveSubscription::User(pubkey) => {
+                event.event_type != "notification" && event.sender_pubkey == *pubkey
+            }
+            LiveSubscription::Thread(post_id) => {
+                event.id != *post_id && event.thread_id.as_deref() == Some(post_id.as_str())
+            }
+            LiveSubscription::Notifications(pubkey) => {
+                event.event_type == "notification"
+                    && event.mentioned_pubkey.as_deref() == Some(pubkey.as_str())
+            }
+        }
+    }
+}
+
+/// Fan-out of the live events channel to the /ws and /notifications/stream connections
+/// (--enable-live-feed)
+pub struct LiveFeed {
+    sender: broadcast::Sender<Arc<LiveEvent>>,
+    clients: AtomicUsize,
+    max_clients: usize,
+    // Drop events of rate limited senders (--hide-rate-limited)
+    hide_rate_limited: bool,
+}
+
+impl LiveFeed {
+    pub fn new(max_clients: usize, hide_rate_limited: bool) -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUFFER).0,
+            clients: AtomicUsize::new(0),
+            max_clients,
+            hide_rate_limited,
+        }
+    }
+
+    /// Register a connection (None when --live-feed-max-clients are already connected)
+    pub fn subscribe(self: &Arc<Self>) -> Option<LiveClient> {
+        let reserved = self
+            .clients
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |clients| {
+                (clients < self.max_clients).then_some(clients + 1)
+            });
+        let clients = reserved.ok()? + 1;
+        gauge!("k_live_feed_clients").set(clients as f64);
+
+        Some(LiveClient {
+            feed: self.clone(),
+            events: self.sender.subscribe(),
+        })
+    }
+
+    /// Forward a notification payload to the connected clients
+    fn publish(&self, payload: &str) {
+        match serde_json::from_str::<LiveEvent>(payload) {
+            Ok(event) if event.rate_limited && self.hide_rate_limited => {}
+            // No receiver is not an error: nobody is connected
+            Ok(event) => drop(self.sender.send(Arc::new(event))),
+            Err(e) => warn!("Ignoring malformed live event '{}': {}", payload, e),
+        }
+    }
+}
+
+/// A connected /ws or /notifications/stream client, unregistered when dropped
+pub struct LiveClient {
+    feed: Arc<LiveFeed>,
+    pub events: broadcast::Receiver<Arc<LiveEvent>>,
+}
+
+impl Drop for LiveClient {
+    fn drop(&mut self) {
+        let clients = self.feed.clients.fetch_sub(1, Ordering::AcqRel) - 1;
+        gauge!("k_live_feed_clients").set(clients as f64);
+    }
+}
+
+/// LISTEN on `channel` (LIVE_EVENTS_CHANNEL, or the one of a tenant) and publish every
+/// notification, reconnecting after errors; events committed while disconnected are not replayed
+pub async fn start_live_feed_listener(pool: PgPool, channel: String, feed: Arc<LiveFeed>) {
+    loop {
+        if let Err(e) = listen(&pool, &channel, &feed).await {
+            warn!(
+                "Live feed listener error: {} - reconnecting in {}s",
+                e,
+                RECONNECT_DELAY.as_secs()
+            );
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn listen(pool: &PgPool, channel: &str, feed: &LiveFeed) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(channel).await?;
+    info!("Live feed listening on channel {}", channel);
+
+    loop {
+        let notification = listener.recv().await?;
+        feed.publish(notification.payload());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, id: &str, thread_id: &str) -> LiveEvent {
+        LiveEvent {
+            event_type: event_type.to_string(),
+            id: id.to_string(),
+            sender_pubkey: "02aa".to_string(),
+            parent_id: None,
+            thread_id: Some(thread_id.to_string()),
+            vote: None,
+            mentioned_pubkey: None,
+            content_type: None,
+            block_time: 0,
+            rate_limited: false,
+        }
+    }
+
+    #[test]
+    fn test_subscription_matches() {
+        let post = event("post", "p1", "p1");
+        let reply = event("reply", "r1", "p1");
+        let vote = event("vote", "v1", "p2");
+
+        assert!(LiveSubscription::Watching.matches(&post));
+        assert!(!LiveSubscription::Watching.matches(&reply));
+
+        let user = LiveSubscription::User("02aa".to_string());
+        assert!(user.matches(&post) && user.matches(&vote));
+        assert!(!LiveSubscription::User("02bb".to_string()).matches(&post));
+
+        // The post opening a thread is not part of its live thread
+        let thread = LiveSubscription::Thread("p1".to_string());
+        assert!(thread.matches(&reply));
+        assert!(!thread.matches(&post));
+        assert!(!thread.matches(&vote));
+
+        // Notifications only reach the notified user's stream
+        let notification = LiveEvent {
+            mentioned_pubkey: Some("02bb".to_string()),
+            content_type: Some("reply".to_string()),
+            thread_id: None,
+            ..event("notification", "r1", "p1")
+        };
+        let notifications = LiveSubscription::Notifications("02bb".to_string());
+        assert!(notifications.matches(&notification));
+        assert!(!notifications.matches(&reply));
+        assert!(!LiveSubscription::Notifications("02aa".to_string()).matches(&notification));
+        assert!(!user.matches(&notification));
+        assert!(!LiveSubscription::Watching.matches(&notification));
+    }
+
+    #[test]
+    fn test_client_bound_and_rate_limited_events() {
+        let feed = Arc::new(LiveFeed::new(1, true));
+        let mut client = feed.subscribe().unwrap();
+        assert!(feed.subscribe().is_none());
+
+        feed.publish(r#"{"type":"post","id":"p1","senderPubkey":"02aa","threadId":"p1","blockTime":1,"rateLimited":true}"#);
+        feed.publish(r#"{"type":"post","id":"p2","senderPubkey":"02aa","threadId":"p2","blockTime":2,"rateLimited":false}"#);
+        assert_eq!(client.events.try_recv().unwrap().id, "p2");
+
+        drop(client);
+        assert!(feed.subscribe().is_some());
+    }
+}
@@ -0,0 +1,196 @@
+use axum_prometheus::metrics::{counter, gauge};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::LoadSheddingConfig;
+
+/// Interval between acquire probes of the load shedder (shorter than the pool monitor's, so
+/// shedding starts within a second of the pool saturating)
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Rejects expensive endpoints while the database is under pressure, so cheap ones stay fast
+pub struct LoadShedder {
+    config: LoadSheddingConfig,
+    // Requests currently being served (queue depth in front of the pool)
+    in_flight: AtomicUsize,
+    // Shedding continues until this instant (None = never started)
+    shedding_until: Mutex<Option<Instant>>,
+}
+
+/// Counts a request as in flight until dropped
+pub struct InFlightGuard<'a> {
+    shedder: &'a LoadShedder,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.shedder.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadSheddingConfig) -> Self {
+        Self {
+            config,
+            in_flight: AtomicUsize::new(0),
+            shedding_until: Mutex::new(None),
+        }
+    }
+
+    /// Whether requests to `endpoint` (path without leading slash or /v2) can be shed
+    pub fn is_expensive(&self, endpoint: &str) -> bool {
+        self.config.endpoints.iter().any(|name| name == endpoint)
+    }
+
+    pub fn start_request(&self) -> InFlightGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard { shedder: self }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Start (or extend) shedding when acquiring a pooled connection took too long
+    pub fn record_acquire_wait(&self, wait: Duration, now: Instant) {
+        if wait >= Duration::from_millis(self.config.acquire_threshold_ms) {
+            self.shed(now, &format!("pool acquire took {}ms", wait.as_millis()));
+        }
+    }
+
+    /// Seconds a client should wait before retrying an expensive endpoint (None = serve it)
+    pub fn retry_after(&self, now: Instant) -> Option<u64> {
+        let in_flight = self.in_flight();
+        if in_flight > self.config.max_in_flight {
+            self.shed(now, &format!("{} requests in flight", in_flight));
+        }
+
+        let until = (*self.shedding_until.lock().unwrap())?;
+        let remaining = until.checked_duration_since(now)?;
+        if remaining.is_zero() {
+            return None;
+        }
+        // Round up, so clients never retry before shedding ends
+        Some(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+    }
+
+    pub fn is_shedding(&self, now: Instant) -> bool {
+        self.shedding_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| until > now)
+    }
+
+    fn shed(&self, now: Instant, reason: &str) {
+        let mut shedding_until = self.shedding_until.lock().unwrap();
+        if !shedding_until.is_some_and(|until| until > now) {
+            warn!(
+                "Load shedding started ({}): rejecting {} for at least {}s",
+                reason,
+                self.config.endpoints.join(", "),
+                self.config.hold_secs
+            );
+            counter!("k_load_shedding_activations_total").increment(1);
+        }
+        *shedding_until = Some(now + Duration::from_secs(self.config.hold_secs));
+    }
+}
+
+/// Probe pool acquire latency every second and publish the shedding state as Prometheus gauges
+pub async fn start_load_shedder(pool: PgPool, shedder: Arc<LoadShedder>) {
+    let threshold = Duration::from_millis(shedder.config.acquire_threshold_ms);
+    let mut interval = tokio::time::interval(PROBE_INTERVAL);
+    let mut was_shedding = false;
+
+    loop {
+        interval.tick().await;
+
+        // Bounded probe: a wait past the threshold is enough to start shedding
+        let start = Instant::now();
+        let acquired = tokio::time::timeout(threshold, pool.acquire()).await;
+        let wait = match acquired {
+            Ok(Ok(_conn)) => start.elapsed(),
+            // Timed out or failed: the pool is saturated
+            Ok(Err(_)) | Err(_) => start.elapsed().max(threshold),
+        };
+        let now = Instant::now();
+        shedder.record_acquire_wait(wait, now);
+
+        let shedding = shedder.is_shedding(now);
+        if was_shedding && !shedding {
+            info!("Load shedding stopped");
+        }
+        was_shedding = shedding;
+        gauge!("k_load_shedding_active").set(if shedding { 1.0 } else { 0.0 });
+        gauge!("k_http_requests_in_flight").set(shedder.in_flight() as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shedder() -> LoadShedder {
+        LoadShedder::new(LoadSheddingConfig {
+            acquire_threshold_ms: 1000,
+            max_in_flight: 2,
+            hold_secs: 10,
+            endpoints: vec!["get-mentions".to_string(), "get-notifications".to_string()],
+        })
+    }
+
+    #[test]
+    fn test_fast_acquire_does_not_shed() {
+        let shedder = shedder();
+        let now = Instant::now();
+        shedder.record_acquire_wait(Duration::from_millis(999), now);
+        assert_eq!(shedder.retry_after(now), None);
+        assert!(!shedder.is_shedding(now));
+    }
+
+    #[test]
+    fn test_slow_acquire_sheds_for_hold_period() {
+        let shedder = shedder();
+        let now = Instant::now();
+        shedder.record_acquire_wait(Duration::from_millis(1000), now);
+        assert_eq!(shedder.retry_after(now), Some(10));
+        assert_eq!(
+            shedder.retry_after(now + Duration::from_millis(2500)),
+            Some(8)
+        );
+        assert_eq!(shedder.retry_after(now + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn test_in_flight_requests_over_limit_shed() {
+        let shedder = shedder();
+        let now = Instant::now();
+        let first = shedder.start_request();
+        let _second = shedder.start_request();
+        assert_eq!(shedder.retry_after(now), None);
+
+        let third = shedder.start_request();
+        assert_eq!(shedder.in_flight(), 3);
+        assert_eq!(shedder.retry_after(now), Some(10));
+
+        drop(third);
+        drop(first);
+        assert_eq!(shedder.in_flight(), 1);
+        // Shedding holds after the queue drains
+        assert!(shedder.is_shedding(now + Duration::from_secs(9)));
+        assert_eq!(shedder.retry_after(now + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn test_is_expensive() {
+        let shedder = shedder();
+        assert!(shedder.is_expensive("get-mentions"));
+        assert!(shedder.is_expensive("get-notifications"));
+        assert!(!shedder.is_expensive("get-notifications-count"));
+        assert!(!shedder.is_expensive("health"));
+    }
+}
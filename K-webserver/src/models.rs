@@ -1,33 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-// K Protocol Data Models
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct KTransaction {
-    pub transaction_id: String,
-    pub block_time: u64,
-    pub payload: String,
-    pub action_type: KActionType,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum KActionType {
-    Broadcast(KBroadcast),
-    Post(KPost),
-    Reply(KReply),
-    Vote(KVote),
-    Unknown(String),
-}
+// API response models live in k-client, shared with API clients
+pub use k_client::models::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct KBroadcast {
-    pub sender_pubkey: String,
-    pub sender_signature: String,
-    #[serde(default)]
-    pub base64_encoded_nickname: String,
-    pub base64_encoded_profile_image: Option<String>,
-    pub base64_encoded_message: String,
-}
+// K Protocol Data Models
 
 // Database model for K protocol broadcasts with additional metadata
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -43,14 +19,6 @@ pub struct KBroadcastRecord {
     pub base64_encoded_message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct KPost {
-    pub sender_pubkey: String,
-    pub sender_signature: String,
-    pub base64_encoded_message: String,
-    pub mentioned_pubkeys: Vec<String>,
-}
-
 // Database model for K protocol posts with additional metadata
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KPostRecord {
@@ -61,6 +29,10 @@ pub struct KPostRecord {
     pub sender_signature: String,
     pub base64_encoded_message: String,
     pub mentioned_pubkeys: Vec<String>,
+    // Number of mentions when only the first ones are listed in mentioned_pubkeys
+    pub mentions_count: Option<u64>,
+    // JSON array of mention, hashtag and URL spans of the decoded message (k_render_spans)
+    pub render_spans: Option<String>,
     pub content_type: Option<String>,
     // Optional enriched metadata fields for optimized queries
     pub replies_count: Option<u64>,
@@ -79,15 +51,6 @@ pub struct KPostRecord {
     pub referenced_profile_image: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct KReply {
-    pub sender_pubkey: String,
-    pub sender_signature: String,
-    pub post_id: String,
-    pub base64_encoded_message: String,
-    pub mentioned_pubkeys: Vec<String>,
-}
-
 // Database model for K protocol replies with additional metadata
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KReplyRecord {
@@ -99,6 +62,10 @@ pub struct KReplyRecord {
     pub post_id: String,
     pub base64_encoded_message: String,
     pub mentioned_pubkeys: Vec<String>,
+    // Number of mentions when only the first ones are listed in mentioned_pubkeys
+    pub mentions_count: Option<u64>,
+    // JSON array of mention, hashtag and URL spans of the decoded message (k_render_spans)
+    pub render_spans: Option<String>,
     pub content_type: Option<String>,
     // Optional enriched metadata fields for optimized queries
     pub replies_count: Option<u64>,
@@ -111,14 +78,6 @@ pub struct KReplyRecord {
     pub user_profile_image: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct KVote {
-    pub sender_pubkey: String,
-    pub sender_signature: String,
-    pub post_id: String,
-    pub vote: String,
-}
-
 // Database model for K protocol votes with additional metadata
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KVoteRecord {
@@ -136,6 +95,242 @@ pub struct KVoteRecord {
     pub user_profile_image: Option<String>,
 }
 
+// Database model for a follow notified to the followed user, with the follower's profile
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KFollowRecord {
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub sender_pubkey: String,
+    pub user_nickname: Option<String>,
+    pub user_profile_image: Option<String>,
+}
+
+// Database model for K protocol groups with member count and requester membership
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KGroupRecord {
+    pub id: i64,
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_name: String,
+    pub base64_encoded_description: String,
+    pub membership_policy: String,
+    pub members_count: u64,
+    pub is_member: bool,
+}
+
+// Database model for group-scoped posts with author metadata
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KGroupPostRecord {
+    pub id: i64,
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub group_id: String,
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_message: String,
+    pub user_nickname: Option<String>,
+    pub user_profile_image: Option<String>,
+}
+
+// Database model for calendar events with RSVP counts and requester RSVP
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KEventRecord {
+    pub id: i64,
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_title: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub base64_encoded_location: String,
+    pub going_count: u64,
+    pub interested_count: u64,
+    pub not_going_count: u64,
+    pub requester_rsvp: Option<String>,
+    pub user_nickname: Option<String>,
+    pub user_profile_image: Option<String>,
+}
+
+// Database model for long-form articles with reassembly status
+// base64_encoded_content is only set once the article is complete
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KArticleRecord {
+    pub id: i64,
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub sender_pubkey: String,
+    pub sender_signature: String,
+    pub base64_encoded_title: String,
+    pub chunk_count: u32,
+    pub received_chunks: u32,
+    pub content_hash: String,
+    pub base64_encoded_content: Option<String>,
+    pub status: String,
+    pub completed_at: Option<u64>,
+    pub is_blocked: bool,
+    pub user_nickname: Option<String>,
+    pub user_profile_image: Option<String>,
+}
+
+// Database model for the tip totals of a post, reply or quote (amounts in sompi)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KContentTipsRecord {
+    pub content_id: String,
+    pub recipient_pubkey: String,
+    pub tips_count: u64,
+    pub tippers_count: u64,
+    pub total_amount: u64,
+}
+
+// Database model for the activity totals of a user (block times in ms, None without activity)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KUserStatsRecord {
+    pub user_pubkey: String,
+    pub posts_count: u64,
+    pub replies_count: u64,
+    pub quotes_count: u64,
+    pub votes_given_count: u64,
+    pub up_votes_received: u64,
+    pub down_votes_received: u64,
+    pub first_seen: Option<u64>,
+    pub last_active: Option<u64>,
+}
+
+// Database model for a post with the tips it received within a time window
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KTippedPostRecord {
+    pub post: KPostRecord,
+    pub tips_count: u64,
+    pub tips_amount: u64,
+}
+
+// Database model for right-to-be-forgotten erasure requests (carried out by K-content-remover)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KErasureRequestRecord {
+    pub id: i64,
+    pub subject_pubkey: String,
+    pub reason: Option<String>,
+    pub status: String,
+    pub requested_at: u64,
+    pub completed_at: Option<u64>,
+    pub removed_rows: Option<u64>,
+    pub tombstoned_transactions: Option<u64>,
+}
+
+// Database model for a message posted verbatim (after normalization) by several pubkeys
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KDuplicateContentRecord {
+    pub message_hash: String,
+    // Earliest content with this message in the window
+    pub sample_content_id: String,
+    pub sample_message: String,
+    pub distinct_senders: u64,
+    pub content_count: u64,
+    pub first_block_time: u64,
+    pub last_block_time: u64,
+    pub sender_pubkeys: Vec<String>,
+}
+
+// Database model for the current storage usage of a K table and its usage a week earlier
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KTableStorageRecord {
+    pub table_name: String,
+    // Estimated (pg_stat_user_tables), exact counts are too slow on large tables
+    pub row_count: u64,
+    pub total_bytes: u64,
+    // Latest k_storage_snapshots row at least a week old (None before the first week)
+    pub previous_taken_at: Option<u64>,
+    pub previous_row_count: Option<u64>,
+    pub previous_total_bytes: Option<u64>,
+}
+
+// Database model for the pg_stat_statements totals of the queries of one DatabaseInterface
+// method, named by their /* k:<method> */ comment
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KQueryStatsRecord {
+    pub method: String,
+    pub calls: u64,
+    pub total_time_ms: f64,
+    pub max_time_ms: f64,
+    pub rows: u64,
+    pub shared_blocks_hit: u64,
+    pub shared_blocks_read: u64,
+    // Execution time of every statement recorded in the database, tagged or not
+    pub database_time_ms: f64,
+}
+
+// Database model for one profile broadcast kept in k_profile_history
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KProfileSnapshotRecord {
+    pub transaction_id: String,
+    pub block_time: u64,
+    pub base64_encoded_nickname: String,
+    // SHA-256 of the decoded image (None without a profile image)
+    pub profile_image_hash: Option<String>,
+    pub base64_encoded_message: String,
+}
+
+// Database model for the profile of a user at two points in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KProfileDiffRecord {
+    // Latest broadcast at or before each time (None if the user had no profile yet)
+    pub from_profile: Option<KProfileSnapshotRecord>,
+    pub to_profile: Option<KProfileSnapshotRecord>,
+    // Broadcasts after `from` up to `to`
+    pub updates_count: u64,
+}
+
+// Database model for a profile flagged as likely impersonator of a high-follower user
+// (written by the K-transaction-processor impersonation detector)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KImpersonationFlagRecord {
+    pub id: i64,
+    pub suspect_pubkey: String,
+    pub base64_encoded_suspect_nickname: String,
+    pub target_pubkey: String,
+    pub base64_encoded_target_nickname: String,
+    pub target_followers: u64,
+    pub similarity: f64,
+    // Profile broadcast that triggered the flag
+    pub transaction_id: String,
+    pub flagged_at: u64,
+    pub status: String,
+    pub reviewed_at: Option<u64>,
+}
+
+// Database model for one user of a nickname across the profile history (schema v29+)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KNicknameUseRecord {
+    pub sender_pubkey: String,
+    pub base64_encoded_nickname: String,
+    pub first_used: u64,
+    pub last_used: u64,
+    pub broadcasts_count: u64,
+    // Nickname of the latest broadcast in k_broadcasts
+    pub base64_encoded_current_nickname: Option<String>,
+}
+
+// Database model for an operator-curated featured content (schema v30+)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KFeaturedRecord {
+    pub content_id: String,
+    pub position: i32,
+    pub note: Option<String>,
+    pub featured_at: u64,
+    // Whether the content is still in k_contents (post, reply or quote)
+    pub available: bool,
+}
+
+// Database model for a featured content with its post
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KFeaturedPostRecord {
+    pub featured: KFeaturedRecord,
+    pub post: KPostRecord,
+}
+
 // Merged content record for unified content retrieval
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ContentRecord {
@@ -144,149 +339,113 @@ pub enum ContentRecord {
     Vote(KVoteRecord),
 }
 
-// Content record with mention metadata for notifications
+// What a notification is about: content mentioning the user or voting on their content,
+// or a new follower
+#[derive(Debug, Clone)]
+pub enum NotificationSubject {
+    Content(Box<ContentRecord>),
+    Follow(KFollowRecord),
+}
+
+// Notification subject with mention metadata for notifications
 #[derive(Debug, Clone)]
 pub struct NotificationContentRecord {
-    pub content: ContentRecord,
+    pub subject: NotificationSubject,
     pub mention_id: i64,
     pub mention_block_time: u64,
+    // Number of notifications this one stands for (1 unless repeated ones were collapsed)
+    pub collapsed_count: u64,
 }
 
-// Referenced content data for quotes (only the original content being quoted)
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct QuoteData {
-    #[serde(rename = "referencedContentId")]
-    pub referenced_content_id: String,
-    #[serde(rename = "referencedMessage")]
-    pub referenced_message: String,
-    #[serde(rename = "referencedSenderPubkey")]
-    pub referenced_sender_pubkey: String,
-    #[serde(rename = "referencedNickname", skip_serializing_if = "Option::is_none")]
-    pub referenced_nickname: Option<String>,
-    #[serde(
-        rename = "referencedProfileImage",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub referenced_profile_image: Option<String>,
+/// Conversion from the legacy per-endpoint paginated responses into the /v2 envelope
+pub trait IntoPaginatedEnvelope {
+    type Item: Serialize + Send + 'static;
+
+    fn into_envelope(self) -> PaginatedEnvelope<Self::Item>;
 }
 
-// API Response models
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ServerPost {
-    pub id: String,
-    #[serde(rename = "userPublicKey")]
-    pub user_public_key: String,
-    #[serde(rename = "postContent")]
-    pub post_content: String,
-    pub signature: String,
-    pub timestamp: u64,
-    #[serde(rename = "repliesCount")]
-    pub replies_count: u64,
-    #[serde(rename = "upVotesCount")]
-    pub up_votes_count: u64,
-    #[serde(rename = "downVotesCount")]
-    pub down_votes_count: u64,
-    #[serde(rename = "quotesCount")]
-    pub quotes_count: u64,
-    #[serde(rename = "repostsCount")]
-    pub reposts_count: u64,
-    #[serde(rename = "parentPostId")]
-    pub parent_post_id: Option<String>,
-    #[serde(rename = "mentionedPubkeys")]
-    pub mentioned_pubkeys: Vec<String>,
-    #[serde(rename = "isUpvoted", skip_serializing_if = "Option::is_none")]
-    pub is_upvoted: Option<bool>,
-    #[serde(rename = "isDownvoted", skip_serializing_if = "Option::is_none")]
-    pub is_downvoted: Option<bool>,
-    #[serde(rename = "userNickname", skip_serializing_if = "Option::is_none")]
-    pub user_nickname: Option<String>,
-    #[serde(rename = "userProfileImage", skip_serializing_if = "Option::is_none")]
-    pub user_profile_image: Option<String>,
-    #[serde(rename = "blockedUser", skip_serializing_if = "Option::is_none")]
-    pub blocked_user: Option<bool>,
-    #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
-    pub content_type: Option<String>,
-    #[serde(rename = "isQuote")]
-    pub is_quote: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub quote: Option<QuoteData>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PostsResponse {
-    pub posts: Vec<ServerPost>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PaginationMetadata {
-    #[serde(rename = "hasMore")]
-    pub has_more: bool,
-    #[serde(rename = "nextCursor")]
-    pub next_cursor: Option<String>,
-    #[serde(rename = "prevCursor")]
-    pub prev_cursor: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedPostsResponse {
-    pub posts: Vec<ServerPost>,
-    pub pagination: PaginationMetadata,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedNotificationsResponse {
-    pub notifications: Vec<NotificationPost>,
-    pub pagination: PaginationMetadata,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TrendingHashtag {
-    pub hashtag: String,
-    #[serde(rename = "usageCount")]
-    pub usage_count: u64,
-    pub rank: u32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TrendingHashtagsResponse {
-    #[serde(rename = "timeWindow")]
-    pub time_window: String,
-    #[serde(rename = "fromTime")]
-    pub from_time: u64,
-    #[serde(rename = "toTime")]
-    pub to_time: u64,
-    pub hashtags: Vec<TrendingHashtag>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ServerUserPost {
-    pub id: String,
-    #[serde(rename = "userPublicKey")]
-    pub user_public_key: String,
-    #[serde(rename = "postContent")]
-    pub post_content: String,
-    pub signature: String,
-    pub timestamp: u64,
-    #[serde(rename = "userNickname", skip_serializing_if = "Option::is_none")]
-    pub user_nickname: Option<String>,
-    #[serde(rename = "userProfileImage", skip_serializing_if = "Option::is_none")]
-    pub user_profile_image: Option<String>,
-    #[serde(rename = "blockedUser", skip_serializing_if = "Option::is_none")]
-    pub blocked_user: Option<bool>,
-    #[serde(rename = "followedUser", skip_serializing_if = "Option::is_none")]
-    pub followed_user: Option<bool>,
-    #[serde(rename = "followersCount", skip_serializing_if = "Option::is_none")]
-    pub followers_count: Option<i64>,
-    #[serde(rename = "followingCount", skip_serializing_if = "Option::is_none")]
-    pub following_count: Option<i64>,
-    #[serde(rename = "blockedCount", skip_serializing_if = "Option::is_none")]
-    pub blocked_count: Option<i64>,
-    #[serde(rename = "contentsCount", skip_serializing_if = "Option::is_none")]
-    pub contents_count: Option<i64>,
-}
-
-impl ServerUserPost {
-    pub fn from_k_broadcast_record(record: &KBroadcastRecord) -> Self {
+impl IntoPaginatedEnvelope for PaginatedPostsResponse {
+    type Item = ServerPost;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerPost> {
+        PaginatedEnvelope::new("post", self.posts, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedPostsLightResponse {
+    type Item = ServerPostLight;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerPostLight> {
+        PaginatedEnvelope::new("post_light", self.posts, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedRepliesResponse {
+    type Item = ServerReply;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerReply> {
+        PaginatedEnvelope::new("reply", self.replies, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedUsersResponse {
+    type Item = ServerUserPost;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerUserPost> {
+        PaginatedEnvelope::new("user", self.posts, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedNotificationsResponse {
+    type Item = NotificationPost;
+
+    fn into_envelope(self) -> PaginatedEnvelope<NotificationPost> {
+        PaginatedEnvelope::new("notification", self.notifications, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedGroupsResponse {
+    type Item = ServerGroup;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerGroup> {
+        PaginatedEnvelope::new("group", self.groups, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedGroupPostsResponse {
+    type Item = ServerGroupPost;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerGroupPost> {
+        PaginatedEnvelope::new("group_post", self.posts, self.pagination)
+    }
+}
+
+impl IntoPaginatedEnvelope for PaginatedEventsResponse {
+    type Item = ServerEvent;
+
+    fn into_envelope(self) -> PaginatedEnvelope<ServerEvent> {
+        PaginatedEnvelope::new("event", self.events, self.pagination)
+    }
+}
+
+/// Construction of ServerUserPost from database records
+pub trait ServerUserPostFromRecord {
+    fn from_k_broadcast_record(record: &KBroadcastRecord) -> Self;
+
+    fn from_k_broadcast_record_with_block_status(
+        record: &KBroadcastRecord,
+        is_blocked: bool,
+    ) -> Self;
+
+    fn from_k_broadcast_record_with_block_and_follow_status(
+        record: &KBroadcastRecord,
+        is_blocked: bool,
+        is_followed: bool,
+    ) -> Self;
+}
+
+impl ServerUserPostFromRecord for ServerUserPost {
+    fn from_k_broadcast_record(record: &KBroadcastRecord) -> Self {
         Self {
             id: record.transaction_id.clone(),
             user_public_key: record.sender_pubkey.clone(),
@@ -304,7 +463,7 @@ impl ServerUserPost {
         }
     }
 
-    pub fn from_k_broadcast_record_with_block_status(
+    fn from_k_broadcast_record_with_block_status(
         record: &KBroadcastRecord,
         is_blocked: bool,
     ) -> Self {
@@ -333,7 +492,7 @@ impl ServerUserPost {
         }
     }
 
-    pub fn from_k_broadcast_record_with_block_and_follow_status(
+    fn from_k_broadcast_record_with_block_and_follow_status(
         record: &KBroadcastRecord,
         is_blocked: bool,
         is_followed: bool,
@@ -364,31 +523,30 @@ impl ServerUserPost {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UsersResponse {
-    pub posts: Vec<ServerUserPost>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedUsersResponse {
-    pub posts: Vec<ServerUserPost>,
-    pub pagination: PaginationMetadata,
+/// Total mention count, reported only when mentioned_pubkeys does not hold every mention
+fn listed_mentions_count(mentions_count: Option<u64>, listed: &[String]) -> Option<u64> {
+    mentions_count.filter(|&count| count > listed.len() as u64)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PostDetailsResponse {
-    pub post: ServerPost,
+/// Render spans of a content, dropped for blocked users whose message is masked
+fn visible_render_spans(render_spans: Option<&str>, is_blocked: bool) -> Option<Vec<RenderSpan>> {
+    if is_blocked {
+        return None;
+    }
+    render_spans.and_then(|spans| serde_json::from_str(spans).ok())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ApiError {
-    pub error: String,
-    pub code: String,
+/// Construction of ServerPost from database records
+pub trait ServerPostFromRecord {
+    fn from_enriched_k_post_record_with_block_status(
+        record: &KPostRecord,
+        is_blocked: bool,
+    ) -> Self;
 }
 
-impl ServerPost {
+impl ServerPostFromRecord for ServerPost {
     // New method to construct from enriched KPostRecord with blocking status
-    pub fn from_enriched_k_post_record_with_block_status(
+    fn from_enriched_k_post_record_with_block_status(
         record: &KPostRecord,
         is_blocked: bool,
     ) -> Self {
@@ -432,6 +590,8 @@ impl ServerPost {
             reposts_count: 0,
             parent_post_id: None,
             mentioned_pubkeys: record.mentioned_pubkeys.clone(),
+            mentions_count: listed_mentions_count(record.mentions_count, &record.mentioned_pubkeys),
+            render_spans: visible_render_spans(record.render_spans.as_deref(), is_blocked),
             is_upvoted: record.is_upvoted,
             is_downvoted: record.is_downvoted,
             user_nickname: record.user_nickname.clone(),
@@ -440,34 +600,108 @@ impl ServerPost {
             content_type: record.content_type.clone(),
             is_quote,
             quote,
+            nested_replies: None,
+            view_count: None,
+            up_votes_weight: None,
+            down_votes_weight: None,
+            explorer_links: None,
         }
     }
 }
 
-pub type ServerReply = ServerPost;
+/// Construction of ServerPostLight from database records
+pub trait ServerPostLightFromRecord {
+    fn from_k_post_record(record: &KPostRecord) -> Self;
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct NotificationPost {
-    pub id: String,
-    pub user_public_key: String,
-    pub post_content: String,
-    pub timestamp: u64,
-    pub user_nickname: Option<String>,
-    pub user_profile_image: Option<String>,
-    pub content_type: String, // "post", "reply", or "vote" from k_mentions table
-    pub cursor: String,       // Compound cursor combining block_time and k_mentions.id
-    // Vote-specific fields
-    pub vote_type: Option<String>,       // "upvote" or "downvote"
-    pub mention_block_time: Option<u64>, // block_time from k_mentions table
-    pub content_id: Option<String>,      // The ID of the content being voted on
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub post_id: Option<String>, // The post ID that the vote refers to
-    pub voted_content: Option<String>,   // Content of the post/reply being voted on
-}
-
-impl NotificationPost {
-    pub fn from_k_post_record_with_mention_cursor(
+impl ServerPostLightFromRecord for ServerPostLight {
+    fn from_k_post_record(record: &KPostRecord) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            user_public_key: record.sender_pubkey.clone(),
+            post_content: record.base64_encoded_message.clone(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            is_quote: record.referenced_content_id.is_some(),
+            referenced_content_id: record.referenced_content_id.clone(),
+        }
+    }
+}
+
+/// Construction of ServerPostMeta from database records
+pub trait ServerPostMetaFromRecord {
+    fn from_enriched_k_post_record(record: &KPostRecord) -> Self;
+}
+
+impl ServerPostMetaFromRecord for ServerPostMeta {
+    fn from_enriched_k_post_record(record: &KPostRecord) -> Self {
+        // Only quotes carry the quoted content, as in ServerPost
+        let quote = match (
+            record.content_type.as_deref(),
+            record.referenced_content_id.as_ref(),
+            record.referenced_message.as_ref(),
+            record.referenced_sender_pubkey.as_ref(),
+        ) {
+            (Some("quote"), Some(ref_id), Some(ref_msg), Some(ref_pubkey)) => Some(QuoteData {
+                referenced_content_id: ref_id.clone(),
+                referenced_message: ref_msg.clone(),
+                referenced_sender_pubkey: ref_pubkey.clone(),
+                referenced_nickname: record.referenced_nickname.clone(),
+                referenced_profile_image: record.referenced_profile_image.clone(),
+            }),
+            _ => None,
+        };
+
+        Self {
+            id: record.transaction_id.clone(),
+            replies_count: record.replies_count.unwrap_or(0),
+            up_votes_count: record.up_votes_count.unwrap_or(0),
+            down_votes_count: record.down_votes_count.unwrap_or(0),
+            quotes_count: record.quotes_count.unwrap_or(0),
+            mentioned_pubkeys: record.mentioned_pubkeys.clone(),
+            mentions_count: listed_mentions_count(record.mentions_count, &record.mentioned_pubkeys),
+            render_spans: visible_render_spans(record.render_spans.as_deref(), false),
+            is_upvoted: record.is_upvoted,
+            is_downvoted: record.is_downvoted,
+            user_nickname: record.user_nickname.clone(),
+            user_profile_image: record.user_profile_image.clone(),
+            quote,
+        }
+    }
+}
+
+/// Construction of NotificationPost from database records
+pub trait NotificationPostFromRecord {
+    fn from_k_post_record_with_mention_cursor(
+        record: &KPostRecord,
+        mention_id: i64,
+        mention_block_time: u64,
+    ) -> Self;
+
+    fn from_k_reply_record_with_mention_cursor(
+        record: &KReplyRecord,
+        mention_id: i64,
+        mention_block_time: u64,
+    ) -> Self;
+
+    fn from_k_vote_record_with_mention_cursor(
+        vote_record: &KVoteRecord,
+        mention_id: i64,
+        mention_block_time: u64,
+        voted_content: String,
+        user_nickname: Option<String>,
+        user_profile_image: Option<String>,
+    ) -> Self;
+
+    fn from_k_follow_record_with_mention_cursor(
+        follow_record: &KFollowRecord,
+        mention_id: i64,
+        mention_block_time: u64,
+    ) -> Self;
+}
+
+impl NotificationPostFromRecord for NotificationPost {
+    fn from_k_post_record_with_mention_cursor(
         record: &KPostRecord,
         mention_id: i64,
         mention_block_time: u64,
@@ -493,10 +727,11 @@ impl NotificationPost {
             content_id: None,
             post_id: None,
             voted_content: None,
+            collapse_count: None,
         }
     }
 
-    pub fn from_k_reply_record_with_mention_cursor(
+    fn from_k_reply_record_with_mention_cursor(
         record: &KReplyRecord,
         mention_id: i64,
         mention_block_time: u64,
@@ -515,10 +750,11 @@ impl NotificationPost {
             content_id: None,
             post_id: None,
             voted_content: None,
+            collapse_count: None,
         }
     }
 
-    pub fn from_k_vote_record_with_mention_cursor(
+    fn from_k_vote_record_with_mention_cursor(
         vote_record: &KVoteRecord,
         mention_id: i64,
         mention_block_time: u64,
@@ -540,24 +776,45 @@ impl NotificationPost {
             content_id: Some(vote_record.post_id.clone()),
             post_id: None,
             voted_content: Some(voted_content),
+            collapse_count: None,
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RepliesResponse {
-    pub replies: Vec<ServerReply>,
+    fn from_k_follow_record_with_mention_cursor(
+        follow_record: &KFollowRecord,
+        mention_id: i64,
+        mention_block_time: u64,
+    ) -> Self {
+        Self {
+            id: follow_record.transaction_id.clone(),
+            user_public_key: follow_record.sender_pubkey.clone(),
+            post_content: String::new(), // Follows don't have content
+            timestamp: mention_block_time,
+            user_nickname: follow_record.user_nickname.clone(),
+            user_profile_image: follow_record.user_profile_image.clone(),
+            content_type: "follow".to_string(),
+            cursor: format!("{}_{}", mention_block_time, mention_id),
+            vote_type: None,
+            mention_block_time: None,
+            content_id: None,
+            post_id: None,
+            voted_content: None,
+            collapse_count: None,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedRepliesResponse {
-    pub replies: Vec<ServerReply>,
-    pub pagination: PaginationMetadata,
+/// Construction of ServerReply from database records
+pub trait ServerReplyFromRecord {
+    fn from_enriched_k_reply_record_with_block_status(
+        record: &KReplyRecord,
+        is_blocked: bool,
+    ) -> Self;
 }
 
-impl ServerReply {
+impl ServerReplyFromRecord for ServerReply {
     // New method to construct from enriched KReplyRecord with blocking status
-    pub fn from_enriched_k_reply_record_with_block_status(
+    fn from_enriched_k_reply_record_with_block_status(
         record: &KReplyRecord,
         is_blocked: bool,
     ) -> Self {
@@ -582,6 +839,8 @@ impl ServerReply {
             reposts_count: 0,
             parent_post_id: Some(record.post_id.clone()),
             mentioned_pubkeys: record.mentioned_pubkeys.clone(),
+            mentions_count: listed_mentions_count(record.mentions_count, &record.mentioned_pubkeys),
+            render_spans: visible_render_spans(record.render_spans.as_deref(), is_blocked),
             is_upvoted: record.is_upvoted,
             is_downvoted: record.is_downvoted,
             user_nickname: record.user_nickname.clone(),
@@ -590,6 +849,503 @@ impl ServerReply {
             content_type: record.content_type.clone(),
             is_quote: false,
             quote: None,
+            nested_replies: None,
+            view_count: None,
+            up_votes_weight: None,
+            down_votes_weight: None,
+            explorer_links: None,
+        }
+    }
+}
+
+/// Construction of ServerGroup from database records
+pub trait ServerGroupFromRecord {
+    fn from_k_group_record(record: &KGroupRecord) -> Self;
+}
+
+impl ServerGroupFromRecord for ServerGroup {
+    fn from_k_group_record(record: &KGroupRecord) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            owner_public_key: record.sender_pubkey.clone(),
+            group_name: record.base64_encoded_name.clone(),
+            group_description: record.base64_encoded_description.clone(),
+            membership_policy: record.membership_policy.clone(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            members_count: record.members_count,
+            is_member: record.is_member,
+        }
+    }
+}
+
+/// Construction of ServerGroupPost from database records
+pub trait ServerGroupPostFromRecord {
+    fn from_k_group_post_record(record: &KGroupPostRecord) -> Self;
+}
+
+impl ServerGroupPostFromRecord for ServerGroupPost {
+    fn from_k_group_post_record(record: &KGroupPostRecord) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            group_id: record.group_id.clone(),
+            user_public_key: record.sender_pubkey.clone(),
+            post_content: record.base64_encoded_message.clone(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            user_nickname: record.user_nickname.clone(),
+            user_profile_image: record.user_profile_image.clone(),
+        }
+    }
+}
+
+/// Construction of ServerEvent from database records
+pub trait ServerEventFromRecord {
+    fn from_k_event_record(record: &KEventRecord) -> Self;
+}
+
+impl ServerEventFromRecord for ServerEvent {
+    fn from_k_event_record(record: &KEventRecord) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            organizer_public_key: record.sender_pubkey.clone(),
+            title: record.base64_encoded_title.clone(),
+            start_time: record.start_time,
+            end_time: record.end_time,
+            location: record.base64_encoded_location.clone(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            going_count: record.going_count,
+            interested_count: record.interested_count,
+            not_going_count: record.not_going_count,
+            rsvp_status: record.requester_rsvp.clone(),
+            organizer_nickname: record.user_nickname.clone(),
+            organizer_profile_image: record.user_profile_image.clone(),
         }
     }
 }
+
+/// Construction of ServerArticle from database records
+pub trait ServerArticleFromRecord {
+    fn from_k_article_record(record: &KArticleRecord) -> Self;
+}
+
+impl ServerArticleFromRecord for ServerArticle {
+    fn from_k_article_record(record: &KArticleRecord) -> Self {
+        let is_complete = record.status == "complete";
+        Self {
+            id: record.transaction_id.clone(),
+            author_public_key: record.sender_pubkey.clone(),
+            title: record.base64_encoded_title.clone(),
+            chunk_count: record.chunk_count,
+            received_chunks: record.received_chunks,
+            status: record.status.clone(),
+            is_complete,
+            content: if is_complete && !record.is_blocked {
+                record.base64_encoded_content.clone()
+            } else {
+                None
+            },
+            content_hash: record.content_hash.clone(),
+            signature: record.sender_signature.clone(),
+            timestamp: record.block_time,
+            completed_at: record.completed_at,
+            blocked_user: Some(record.is_blocked),
+            author_nickname: record.user_nickname.clone(),
+            author_profile_image: record.user_profile_image.clone(),
+        }
+    }
+}
+
+/// Construction of PostTipsResponse from database records
+pub trait PostTipsResponseFromRecord {
+    fn from_k_content_tips_record(record: &KContentTipsRecord) -> Self;
+}
+
+impl PostTipsResponseFromRecord for PostTipsResponse {
+    fn from_k_content_tips_record(record: &KContentTipsRecord) -> Self {
+        Self {
+            post_id: record.content_id.clone(),
+            recipient_public_key: record.recipient_pubkey.clone(),
+            tips_count: record.tips_count,
+            tippers_count: record.tippers_count,
+            total_amount: record.total_amount,
+        }
+    }
+}
+
+/// Construction of UserStatsResponse from database records
+pub trait UserStatsResponseFromRecord {
+    fn from_k_user_stats_record(record: &KUserStatsRecord) -> Self;
+}
+
+impl UserStatsResponseFromRecord for UserStatsResponse {
+    fn from_k_user_stats_record(record: &KUserStatsRecord) -> Self {
+        Self {
+            user_public_key: record.user_pubkey.clone(),
+            posts_count: record.posts_count,
+            replies_count: record.replies_count,
+            quotes_count: record.quotes_count,
+            votes_given_count: record.votes_given_count,
+            up_votes_count: record.up_votes_received,
+            down_votes_count: record.down_votes_received,
+            first_seen: record.first_seen,
+            last_active: record.last_active,
+        }
+    }
+}
+
+/// Construction of DuplicateContent from database records
+pub trait DuplicateContentFromRecord {
+    fn from_k_duplicate_content_record(record: &KDuplicateContentRecord) -> Self;
+}
+
+impl DuplicateContentFromRecord for DuplicateContent {
+    fn from_k_duplicate_content_record(record: &KDuplicateContentRecord) -> Self {
+        Self {
+            message_hash: record.message_hash.clone(),
+            sample_content_id: record.sample_content_id.clone(),
+            sample_message: record.sample_message.clone(),
+            distinct_senders: record.distinct_senders,
+            content_count: record.content_count,
+            first_block_time: record.first_block_time,
+            last_block_time: record.last_block_time,
+            sender_pubkeys: record.sender_pubkeys.clone(),
+        }
+    }
+}
+
+/// Construction of TableStorageStats from database records
+pub trait TableStorageStatsFromRecord {
+    fn from_k_table_storage_record(record: &KTableStorageRecord) -> Self;
+}
+
+impl TableStorageStatsFromRecord for TableStorageStats {
+    fn from_k_table_storage_record(record: &KTableStorageRecord) -> Self {
+        let size_growth = record
+            .previous_total_bytes
+            .map(|previous| record.total_bytes as i64 - previous as i64);
+
+        Self {
+            table: record.table_name.clone(),
+            row_count: record.row_count,
+            total_bytes: record.total_bytes,
+            compared_to: record.previous_taken_at,
+            row_growth: record
+                .previous_row_count
+                .map(|previous| record.row_count as i64 - previous as i64),
+            size_growth,
+            size_growth_percent: match (size_growth, record.previous_total_bytes) {
+                (Some(growth), Some(previous)) if previous > 0 => {
+                    Some((growth as f64 * 1000.0 / previous as f64).round() / 10.0)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Construction of QueryStats from database records
+pub trait QueryStatsFromRecord {
+    fn from_k_query_stats_record(record: &KQueryStatsRecord) -> Self;
+}
+
+impl QueryStatsFromRecord for QueryStats {
+    fn from_k_query_stats_record(record: &KQueryStatsRecord) -> Self {
+        let blocks = record.shared_blocks_hit + record.shared_blocks_read;
+
+        Self {
+            method: record.method.clone(),
+            calls: record.calls,
+            total_time_ms: round_ms(record.total_time_ms),
+            mean_time_ms: round_ms(record.total_time_ms / record.calls.max(1) as f64),
+            max_time_ms: round_ms(record.max_time_ms),
+            rows: record.rows,
+            cache_hit_percent: (blocks > 0)
+                .then(|| (record.shared_blocks_hit as f64 * 1000.0 / blocks as f64).round() / 10.0),
+            time_share_percent: if record.database_time_ms > 0.0 {
+                (record.total_time_ms * 1000.0 / record.database_time_ms).round() / 10.0
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+fn round_ms(ms: f64) -> f64 {
+    (ms * 100.0).round() / 100.0
+}
+
+/// Construction of ProfileSnapshot from database records
+pub trait ProfileSnapshotFromRecord {
+    fn from_k_profile_snapshot_record(record: &KProfileSnapshotRecord) -> Self;
+}
+
+impl ProfileSnapshotFromRecord for ProfileSnapshot {
+    fn from_k_profile_snapshot_record(record: &KProfileSnapshotRecord) -> Self {
+        Self {
+            id: record.transaction_id.clone(),
+            timestamp: record.block_time,
+            user_nickname: record.base64_encoded_nickname.clone(),
+            profile_image_hash: record.profile_image_hash.clone(),
+            post_content: record.base64_encoded_message.clone(),
+        }
+    }
+}
+
+/// Construction of ProfileDiffResponse from database records
+pub trait ProfileDiffResponseFromRecord {
+    fn from_k_profile_diff_record(
+        user_public_key: &str,
+        from: u64,
+        to: u64,
+        record: &KProfileDiffRecord,
+    ) -> Self;
+}
+
+impl ProfileDiffResponseFromRecord for ProfileDiffResponse {
+    fn from_k_profile_diff_record(
+        user_public_key: &str,
+        from: u64,
+        to: u64,
+        record: &KProfileDiffRecord,
+    ) -> Self {
+        let from_profile = record.from_profile.as_ref();
+        let to_profile = record.to_profile.as_ref();
+
+        // A missing profile compares like empty fields: only fields set on one side differ
+        fn nickname(profile: Option<&KProfileSnapshotRecord>) -> Option<&str> {
+            profile
+                .map(|p| p.base64_encoded_nickname.as_str())
+                .filter(|nickname| !nickname.is_empty())
+        }
+        fn image(profile: Option<&KProfileSnapshotRecord>) -> Option<&str> {
+            profile.and_then(|p| p.profile_image_hash.as_deref())
+        }
+        fn bio(profile: Option<&KProfileSnapshotRecord>) -> Option<&str> {
+            profile
+                .map(|p| p.base64_encoded_message.as_str())
+                .filter(|bio| !bio.is_empty())
+        }
+
+        let mut changed_fields = Vec::new();
+        if nickname(from_profile) != nickname(to_profile) {
+            changed_fields.push("nickname".to_string());
+        }
+        if image(from_profile) != image(to_profile) {
+            changed_fields.push("profileImage".to_string());
+        }
+        if bio(from_profile) != bio(to_profile) {
+            changed_fields.push("bio".to_string());
+        }
+
+        Self {
+            user_public_key: user_public_key.to_string(),
+            from,
+            to,
+            from_profile: from_profile.map(ProfileSnapshot::from_k_profile_snapshot_record),
+            to_profile: to_profile.map(ProfileSnapshot::from_k_profile_snapshot_record),
+            changed_fields,
+            profile_updates: record.updates_count,
+        }
+    }
+}
+
+/// Construction of ErasureRequestResponse from database records
+pub trait ErasureRequestResponseFromRecord {
+    fn from_k_erasure_request_record(record: &KErasureRequestRecord) -> Self;
+}
+
+impl ErasureRequestResponseFromRecord for ErasureRequestResponse {
+    fn from_k_erasure_request_record(record: &KErasureRequestRecord) -> Self {
+        Self {
+            id: record.id,
+            subject_public_key: record.subject_pubkey.clone(),
+            reason: record.reason.clone(),
+            status: record.status.clone(),
+            requested_at: record.requested_at,
+            completed_at: record.completed_at,
+            removed_rows: record.removed_rows,
+            tombstoned_transactions: record.tombstoned_transactions,
+        }
+    }
+}
+
+/// Construction of ImpersonationFlag from database records
+pub trait ImpersonationFlagFromRecord {
+    fn from_k_impersonation_flag_record(record: &KImpersonationFlagRecord) -> Self;
+}
+
+impl ImpersonationFlagFromRecord for ImpersonationFlag {
+    fn from_k_impersonation_flag_record(record: &KImpersonationFlagRecord) -> Self {
+        Self {
+            id: record.id,
+            suspect_public_key: record.suspect_pubkey.clone(),
+            suspect_nickname: record.base64_encoded_suspect_nickname.clone(),
+            target_public_key: record.target_pubkey.clone(),
+            target_nickname: record.base64_encoded_target_nickname.clone(),
+            target_followers: record.target_followers,
+            similarity: record.similarity,
+            transaction_id: record.transaction_id.clone(),
+            flagged_at: record.flagged_at,
+            status: record.status.clone(),
+            reviewed_at: record.reviewed_at,
+        }
+    }
+}
+
+/// Construction of FeaturedEntry from database records
+pub trait FeaturedEntryFromRecord {
+    fn from_k_featured_record(record: &KFeaturedRecord) -> Self;
+}
+
+impl FeaturedEntryFromRecord for FeaturedEntry {
+    fn from_k_featured_record(record: &KFeaturedRecord) -> Self {
+        Self {
+            content_id: record.content_id.clone(),
+            position: record.position,
+            note: record.note.clone(),
+            featured_at: record.featured_at,
+            available: record.available,
+        }
+    }
+}
+
+/// Construction of NicknameUse from database records
+pub trait NicknameUseFromRecord {
+    fn from_k_nickname_use_record(record: &KNicknameUseRecord) -> Self;
+}
+
+impl NicknameUseFromRecord for NicknameUse {
+    fn from_k_nickname_use_record(record: &KNicknameUseRecord) -> Self {
+        Self {
+            user_public_key: record.sender_pubkey.clone(),
+            nickname: record.base64_encoded_nickname.clone(),
+            first_used: record.first_used,
+            last_used: record.last_used,
+            broadcasts_count: record.broadcasts_count,
+            current_nickname: record.base64_encoded_current_nickname.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_user_stats_response_shape() {
+        let record = KUserStatsRecord {
+            user_pubkey: "02aa".to_string(),
+            posts_count: 3,
+            replies_count: 2,
+            quotes_count: 1,
+            votes_given_count: 7,
+            up_votes_received: 5,
+            down_votes_received: 4,
+            first_seen: Some(1_000),
+            last_active: Some(2_000),
+        };
+        assert_eq!(
+            serde_json::to_value(UserStatsResponse::from_k_user_stats_record(&record)).unwrap(),
+            json!({
+                "userPublicKey": "02aa",
+                "postsCount": 3,
+                "repliesCount": 2,
+                "quotesCount": 1,
+                "votesGivenCount": 7,
+                "upVotesCount": 5,
+                "downVotesCount": 4,
+                "firstSeen": 1_000,
+                "lastActive": 2_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_user_stats_response_without_activity() {
+        let record = KUserStatsRecord {
+            user_pubkey: "02aa".to_string(),
+            posts_count: 0,
+            replies_count: 0,
+            quotes_count: 0,
+            votes_given_count: 0,
+            up_votes_received: 0,
+            down_votes_received: 0,
+            first_seen: None,
+            last_active: None,
+        };
+        let value =
+            serde_json::to_value(UserStatsResponse::from_k_user_stats_record(&record)).unwrap();
+        assert_eq!(value["postsCount"], 0);
+        assert!(value.get("firstSeen").is_none());
+        assert!(value.get("lastActive").is_none());
+    }
+
+    #[test]
+    fn test_follow_notification() {
+        let record = KFollowRecord {
+            transaction_id: "ff".to_string(),
+            block_time: 1_000,
+            sender_pubkey: "02aa".to_string(),
+            user_nickname: Some("bmljaw==".to_string()),
+            user_profile_image: None,
+        };
+        let notification =
+            NotificationPost::from_k_follow_record_with_mention_cursor(&record, 42, 1_500);
+        assert_eq!(notification.content_type, "follow");
+        assert_eq!(notification.id, "ff");
+        assert_eq!(notification.user_public_key, "02aa");
+        assert_eq!(notification.post_content, "");
+        assert_eq!(notification.timestamp, 1_500);
+        assert_eq!(notification.cursor, "1500_42");
+        assert_eq!(notification.user_nickname.as_deref(), Some("bmljaw=="));
+        assert!(notification.content_id.is_none());
+        assert!(notification.vote_type.is_none());
+    }
+
+    #[test]
+    fn test_paginated_envelope_shape() {
+        let pagination = || PaginationMetadata {
+            has_more: true,
+            next_cursor: Some("1000_1".to_string()),
+            prev_cursor: None,
+        };
+        let envelope = PaginatedRepliesResponse {
+            replies: Vec::new(),
+            pagination: pagination(),
+        }
+        .into_envelope();
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            json!({
+                "type": "reply",
+                "items": [],
+                "pagination": {"hasMore": true, "nextCursor": "1000_1", "prevCursor": null},
+            })
+        );
+
+        let item_types = [
+            PaginatedPostsResponse {
+                posts: Vec::new(),
+                pagination: pagination(),
+            }
+            .into_envelope()
+            .item_type,
+            PaginatedUsersResponse {
+                posts: Vec::new(),
+                pagination: pagination(),
+            }
+            .into_envelope()
+            .item_type,
+            PaginatedNotificationsResponse {
+                notifications: Vec::new(),
+                pagination: pagination(),
+            }
+            .into_envelope()
+            .item_type,
+        ];
+        assert_eq!(item_types, ["post", "user", "notification"]);
+    }
+}
@@ -0,0 +1,53 @@
+use sqlx::FromRow;
+use sqlx::postgres::{PgArguments, PgRow, Postgres};
+use sqlx::query::{Query, QueryAs, QueryScalar};
+
+/// SQL text of a query run by a `DatabaseInterface` method, prefixed with a `/* k:<method> */`
+/// comment that Postgres keeps in pg_stat_activity, pg_stat_statements and the server logs, so
+/// database load can be attributed to the method (and so to the endpoints calling it)
+pub struct NamedQuery {
+    sql: String,
+}
+
+impl NamedQuery {
+    pub fn new(method: &str, sql: &str) -> Self {
+        Self {
+            sql: format!("/* k:{} */ {}", method, sql),
+        }
+    }
+
+    /// Append a dynamically built part (cursor conditions, ORDER BY, LIMIT)
+    pub fn push_str(&mut self, sql: &str) {
+        self.sql.push_str(sql);
+    }
+
+    pub fn query(&self) -> Query<'_, Postgres, PgArguments> {
+        sqlx::query(&self.sql)
+    }
+
+    pub fn query_as<T>(&self) -> QueryAs<'_, Postgres, T, PgArguments>
+    where
+        T: for<'r> FromRow<'r, PgRow>,
+    {
+        sqlx::query_as(&self.sql)
+    }
+
+    pub fn query_scalar<T>(&self) -> QueryScalar<'_, Postgres, T, PgArguments>
+    where
+        (T,): for<'r> FromRow<'r, PgRow>,
+    {
+        sqlx::query_scalar(&self.sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_comment_prefix() {
+        let mut query = NamedQuery::new("get_all_users", "SELECT 1");
+        query.push_str(" LIMIT $1");
+        assert_eq!(query.sql, "/* k:get_all_users */ SELECT 1 LIMIT $1");
+    }
+}
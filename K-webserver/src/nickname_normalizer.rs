@@ -0,0 +1,90 @@
+// Nickname normalization of K-transaction-processor (nickname_similarity), which stores the
+// normalized nickname of every profile broadcast in k_profile_history.normalized_nickname.
+// Searches must normalize the queried nickname the same way: keep both in sync
+
+/// Invisible characters inserted to make copies of a nickname look different
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Map look-alike characters (Cyrillic, Greek, fullwidth, digits and symbols) to the Latin
+/// letter they imitate; expects lowercase input
+fn fold_homoglyph(c: char) -> char {
+    match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' => 'e',
+        'һ' => 'h',
+        'і' | 'ї' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'н' => 'h',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'ԝ' => 'w',
+        'х' => 'x',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'η' => 'n',
+        'ι' => 'i',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        // Digits and symbols used as letters
+        '0' => 'o',
+        '1' | '!' | '|' => 'l',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        '8' => 'b',
+        // Fullwidth Latin letters
+        'ａ'..='ｚ' => char::from_u32(c as u32 - 'ａ' as u32 + 'a' as u32).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Normalize a nickname so look-alike variants compare equal: lowercase, zero-width characters
+/// removed, homoglyphs folded to Latin letters, separators dropped, "rn" read as "m" and "vv" as "w"
+pub fn normalize_nickname(nickname: &str) -> String {
+    nickname
+        .chars()
+        .filter(|c| !is_zero_width(*c))
+        .collect::<String>()
+        .to_lowercase()
+        .chars()
+        .map(fold_homoglyph)
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_nickname() {
+        assert_eq!(normalize_nickname("The Sheep_Cat."), "thesheepcat");
+        // Cyrillic "е" and "а", digit zero for "o"
+        assert_eq!(normalize_nickname("Shееp Cаt"), "sheepcat");
+        assert_eq!(normalize_nickname("K0SPA"), "kospa");
+        assert_eq!(normalize_nickname("Sheep\u{200B}Cat"), "sheepcat");
+        assert_eq!(normalize_nickname("Sarn"), "sam");
+    }
+}
@@ -0,0 +1,603 @@
+use k_client::requests::*;
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, SchemaGenerator};
+use serde_json::{Map, Value, json};
+
+use crate::api_version;
+use crate::models::*;
+
+/// OpenAPI document of the webserver (GET /openapi.json): every route with the query struct
+/// its handler deserializes and the model it serializes, so SDKs can be generated from it.
+/// Routes registered only with an option are included, with the option in their description.
+/// Public endpoints are listed under /v1, their unversioned paths as deprecated aliases
+pub fn document() -> Value {
+    let mut api = OpenApi::new();
+
+    // System
+    api.get::<(), Value>("/health", "Health of the webserver and its database");
+    api.get::<(), Value>(
+        "/.well-known/k-indexer",
+        "Discovery document: protocol, schema features and endpoints served",
+    );
+    api.get::<(), Value>("/stats", "Indexing statistics");
+    api.get::<(), Value>("/openapi.json", "This OpenAPI document");
+    api.raw::<()>(
+        "get",
+        "/metrics",
+        "Prometheus metrics",
+        "200",
+        "text/plain",
+        None,
+    );
+
+    // Public endpoints, versioned (see api_version)
+    api.versioned = true;
+
+    // Posts and replies
+    api.paginated::<GetPostsQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-posts",
+        "Posts of a user",
+    );
+    api.get::<GetPostDetailsQuery, PostDetailsResponse>(
+        "/get-post-details",
+        "Post, reply, quote or vote by transaction ID",
+    );
+    api.get::<GetPostMentionsQuery, PostMentionsResponse>(
+        "/get-post-mentions",
+        "Users mentioned by a content",
+    );
+    api.paginated::<GetPostsWatchingQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-posts-watching",
+        "Posts of every user not blocked by the requester",
+    );
+    api.paginated::<GetPostsLightQuery, PaginatedPostsLightResponse, ServerPostLight>(
+        "/get-posts-light",
+        "Posts of /get-posts-watching without counts, votes and profile data",
+    );
+    api.get::<GetPostsMetaQuery, PostsMetaResponse>(
+        "/get-posts-meta",
+        "Counts, votes, profile data and quotes of up to 100 contents by transaction ID",
+    );
+    api.get::<GetPostsByIdsQuery, PostsByIdsResponse>(
+        "/get-posts-by-ids",
+        "Posts, replies and quotes of up to 100 contents by transaction ID",
+    );
+    api.paginated::<GetContentsFollowingQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-contents-following",
+        "Contents of the users followed by the requester",
+    );
+    api.paginated::<GetPostsFollowingQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-posts-following",
+        "Posts of the users followed by the requester",
+    );
+    api.paginated::<GetRepliesQuery, PaginatedRepliesResponse, ServerPost>(
+        "/get-replies",
+        "Replies to a content, or replies of a user",
+    );
+    api.get::<GetThreadQuery, ThreadResponse>(
+        "/get-thread",
+        "Content with its reply tree, down to maxDepth levels",
+    );
+    api.paginated::<GetMentionsQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-mentions",
+        "Contents mentioning a user",
+    );
+    api.paginated::<GetHashtagContentQuery, PaginatedPostsResponse, ServerPost>(
+        "/get-hashtag-content",
+        "Contents with a hashtag",
+    );
+    api.get::<GetTrendingHashtagsQuery, TrendingHashtagsResponse>(
+        "/get-trending-hashtags",
+        "Most used hashtags of a time window",
+    );
+    api.paginated::<SearchPostsQuery, PaginatedPostsResponse, ServerPost>(
+        "/search-posts",
+        "Full-text search of contents",
+    );
+    api.get::<GetPostTipsQuery, PostTipsResponse>("/get-post-tips", "Tips sent to a content");
+    api.get::<GetTopTippedPostsQuery, TopTippedPostsResponse>(
+        "/get-top-tipped-posts",
+        "Contents with the most tips",
+    );
+    api.get::<GetFeaturedQuery, FeaturedPostsResponse>(
+        "/get-featured",
+        "Contents featured by the operator",
+    );
+
+    // Users
+    api.paginated::<GetUsersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-users",
+        "Users with a broadcast",
+    );
+    api.paginated::<GetMostActiveUsersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-most-active-users",
+        "Users with the most contents in a time window",
+    );
+    api.get::<GetUsersCountQuery, CountResponse>("/get-users-count", "Number of users");
+    api.paginated::<SearchUsersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/search-users",
+        "Users by public key or nickname",
+    );
+    api.get::<GetUserDetailsQuery, ServerUserPost>(
+        "/get-user-details",
+        "User details with the follow and block status of the requester",
+    );
+    api.get::<GetUserStatsQuery, UserStatsResponse>(
+        "/get-user-stats",
+        "Content, vote and activity totals of a user",
+    );
+    api.get::<GetUsersByKeysQuery, UsersResponse>(
+        "/get-users-by-keys",
+        "Latest profiles of up to 100 users by public key, with the block status of the requester",
+    );
+    api.get::<GetProfileQuery, ProfileResponse>(
+        "/get-profile",
+        "User details with the first page of their posts",
+    );
+    api.raw::<GetProfileImageQuery>(
+        "get",
+        "/get-profile-image",
+        "Profile image of a user",
+        "200",
+        "image/*",
+        None,
+    );
+    api.get::<GetProfileDiffQuery, ProfileDiffResponse>(
+        "/get-profile-diff",
+        "Changes between two broadcasts of a user",
+    );
+    api.paginated::<GetFollowedUsersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-followed-users",
+        "Users followed by the requester",
+    );
+    api.paginated::<GetUsersFollowingQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-users-following",
+        "Users followed by a user",
+    );
+    api.paginated::<GetUsersFollowersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-users-followers",
+        "Followers of a user",
+    );
+    api.paginated::<GetBlockedUsersQuery, PaginatedUsersResponse, ServerUserPost>(
+        "/get-blocked-users",
+        "Users blocked by the requester",
+    );
+
+    // Notifications
+    api.get::<GetNotificationsCountQuery, CountResponse>(
+        "/get-notifications-count",
+        "Number of notifications of the requester",
+    );
+    api.paginated::<GetNotificationsQuery, PaginatedNotificationsResponse, NotificationPost>(
+        "/get-notifications",
+        "Notifications of the requester",
+    );
+
+    // Groups, events and articles
+    api.paginated::<GetGroupsQuery, PaginatedGroupsResponse, ServerGroup>("/get-groups", "Groups");
+    api.paginated::<GetGroupPostsQuery, PaginatedGroupPostsResponse, ServerGroupPost>(
+        "/get-group-posts",
+        "Posts of a group",
+    );
+    api.paginated::<GetEventsQuery, PaginatedEventsResponse, ServerEvent>("/get-events", "Events");
+    api.get::<GetArticleQuery, ArticleDetailsResponse>(
+        "/get-article",
+        "Article with its reassembled chunks",
+    );
+    api.get::<GetFeatureFlagsQuery, FeatureFlagsResponse>(
+        "/get-feature-flags",
+        "Feature flags of the requester",
+    );
+
+    // Optional endpoints
+    api.raw::<TrackViewQuery>(
+        "post",
+        "/track-view",
+        "Count a view of a content (--enable-view-tracking)",
+        "204",
+        "",
+        None,
+    );
+    api.raw::<LiveFeedQuery>(
+        "get",
+        "/ws",
+        "WebSocket live feed of new contents and votes (--enable-live-feed)",
+        "101",
+        "",
+        None,
+    );
+    api.raw::<NotificationStreamQuery>(
+        "get",
+        "/notifications/stream",
+        "Server-Sent Events of new notifications (--enable-live-feed)",
+        "200",
+        "text/event-stream",
+        None,
+    );
+    api.raw::<()>(
+        "get",
+        "/graphql",
+        "GraphQL schema in SDL (--enable-graphql)",
+        "200",
+        "text/plain",
+        None,
+    );
+    api.raw::<()>(
+        "post",
+        "/graphql",
+        "GraphQL query (--enable-graphql)",
+        "200",
+        "application/json",
+        Some(json!({ "type": "object" })),
+    );
+
+    api.versioned = false;
+
+    // Operator endpoints (--admin-token)
+    api.admin::<GetErasureRequestQuery, ErasureRequestResponse>(
+        "get",
+        "/admin/erasure-requests",
+        "Erasure request of a user",
+    );
+    api.admin::<CreateErasureRequestQuery, ErasureRequestResponse>(
+        "post",
+        "/admin/erasure-requests",
+        "Request the erasure of a user's contents",
+    );
+    api.admin::<GetDuplicateContentsQuery, DuplicateContentsResponse>(
+        "get",
+        "/admin/duplicate-contents",
+        "Messages posted by several users",
+    );
+    api.admin::<(), StorageStatsResponse>(
+        "get",
+        "/admin/storage-stats",
+        "Size and growth of the tables",
+    );
+    api.admin::<GetSlowQueriesQuery, SlowQueriesResponse>(
+        "get",
+        "/admin/slow-queries",
+        "Slowest queries of the webserver",
+    );
+    api.admin::<GetRequestAnalyticsQuery, RequestAnalyticsResponse>(
+        "get",
+        "/admin/request-analytics",
+        "Most requested endpoints and contents per hour (--request-analytics-hours)",
+    );
+    api.admin::<GetImpersonationFlagsQuery, ImpersonationFlagsResponse>(
+        "get",
+        "/admin/impersonation-flags",
+        "Nicknames flagged as possible impersonations",
+    );
+    api.admin::<ReviewImpersonationFlagQuery, ImpersonationFlag>(
+        "post",
+        "/admin/impersonation-flags",
+        "Review an impersonation flag",
+    );
+    api.admin::<GetNicknameHistoryQuery, NicknameHistoryResponse>(
+        "get",
+        "/admin/nickname-history",
+        "Nicknames used by a user",
+    );
+    api.admin::<GetFeaturedEntriesQuery, FeaturedEntriesResponse>(
+        "get",
+        "/admin/featured",
+        "Featured contents with their operator notes",
+    );
+    api.admin::<SetFeaturedQuery, FeaturedEntry>("post", "/admin/featured", "Feature a content");
+    api.admin::<DeleteFeaturedQuery, FeaturedEntry>(
+        "delete",
+        "/admin/featured",
+        "Stop featuring a content",
+    );
+    api.admin::<SimulatePayloadQuery, PayloadSimulationResponse>(
+        "post",
+        "/admin/simulate-payload",
+        "Rows a K payload would write, nothing persisted (k-indexer --simulator)",
+    );
+
+    api.finish()
+}
+
+/// Paths and shared response schemas of the document being built
+struct OpenApi {
+    // Response models, emitted once under components/schemas
+    models: SchemaGenerator,
+    paths: Map<String, Value>,
+    // Paths added are public endpoints, served under /v1 and at their deprecated unversioned path
+    versioned: bool,
+}
+
+impl OpenApi {
+    fn new() -> Self {
+        Self {
+            models: settings().for_serialize().into_generator(),
+            paths: Map::new(),
+            versioned: false,
+        }
+    }
+
+    /// Public GET endpoint answering `R`
+    fn get<Q: JsonSchema, R: JsonSchema>(&mut self, path: &str, summary: &str) {
+        let response = self.models.subschema_for::<R>().to_value();
+        self.add::<Q>("get", path, summary, json_response(response), false);
+    }
+
+    /// Paginated endpoint answering `R`, also served under /v2 with the envelope of `I` items
+    fn paginated<Q: JsonSchema, R: JsonSchema, I: JsonSchema>(
+        &mut self,
+        path: &str,
+        summary: &str,
+    ) {
+        self.get::<Q, R>(path, summary);
+        self.get::<Q, PaginatedEnvelope<I>>(
+            &format!("/v2{}", path),
+            &format!("{} (unified pagination envelope)", summary),
+        );
+    }
+
+    /// Operator endpoint answering `R`, authenticated with the --admin-token bearer token
+    fn admin<Q: JsonSchema, R: JsonSchema>(&mut self, method: &str, path: &str, summary: &str) {
+        let response = self.models.subschema_for::<R>().to_value();
+        self.add::<Q>(method, path, summary, json_response(response), true);
+    }
+
+    /// Endpoint answering `status` with a body of `media_type` other than a response model
+    /// (no body when empty)
+    fn raw<Q: JsonSchema>(
+        &mut self,
+        method: &str,
+        path: &str,
+        summary: &str,
+        status: &str,
+        media_type: &str,
+        schema: Option<Value>,
+    ) {
+        let mut response = json!({ "description": summary });
+        if !media_type.is_empty() {
+            response["content"] = json!({ media_type: { "schema": schema.unwrap_or(json!({})) } });
+        }
+        self.add::<Q>(method, path, summary, (status, response), false);
+    }
+
+    fn add<Q: JsonSchema>(
+        &mut self,
+        method: &str,
+        path: &str,
+        summary: &str,
+        (status, response): (&str, Value),
+        admin: bool,
+    ) {
+        let mut operation = json!({
+            "operationId": operation_id(method, path),
+            "summary": summary,
+            "tags": [tag(path)],
+            "parameters": query_parameters::<Q>(),
+            "responses": {
+                status: response,
+                "default": {
+                    "description": "Error with its code (e.g. MISSING_PARAMETER, INVALID_USER_KEY, RATE_LIMIT_EXCEEDED)",
+                    "content": {
+                        "application/json": {
+                            "schema": self.models.subschema_for::<ApiError>().to_value()
+                        }
+                    }
+                }
+            }
+        });
+        if admin {
+            operation["security"] = json!([{ "adminToken": [] }]);
+        }
+
+        if self.versioned && !path.starts_with("/v2/") {
+            let v1_path = format!("/v1{}", path);
+            let mut alias = operation.clone();
+            alias["deprecated"] = json!(true);
+            alias["tags"] = json!(["legacy"]);
+            alias["description"] = json!(format!(
+                "Deprecated alias of {}, or of the version requested with the K-API-Version header",
+                v1_path
+            ));
+            operation["operationId"] = json!(operation_id(method, &v1_path));
+            operation["tags"] = json!([tag(&v1_path)]);
+            self.insert(method, &v1_path, operation);
+            self.insert(method, path, alias);
+        } else {
+            self.insert(method, path, operation);
+        }
+    }
+
+    fn insert(&mut self, method: &str, path: &str, operation: Value) {
+        let item = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| json!({}));
+        item[method] = operation;
+    }
+
+    fn finish(mut self) -> Value {
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "K-indexer webserver API",
+                "version": env!("CARGO_PKG_VERSION"),
+                "description": "Indexed K protocol contents, users and notifications. Query parameters are all optional in the schema: handlers answer MISSING_PARAMETER for the ones an endpoint requires."
+            },
+            "paths": self.paths,
+            "components": {
+                "schemas": self.models.take_definitions(true),
+                "securitySchemes": {
+                    "adminToken": { "type": "http", "scheme": "bearer" }
+                }
+            }
+        })
+    }
+}
+
+/// JSON Schema 2020-12 (the dialect of OpenAPI 3.1) with shared schemas in components
+fn settings() -> SchemaSettings {
+    let mut settings = SchemaSettings::draft2020_12();
+    settings.definitions_path = "/components/schemas".into();
+    settings
+}
+
+fn json_response(schema: Value) -> (&'static str, Value) {
+    (
+        "200",
+        json!({
+            "description": "Success",
+            "content": { "application/json": { "schema": schema } }
+        }),
+    )
+}
+
+/// Query string parameters: the properties of the query struct, as the handler deserializes it
+fn query_parameters<Q: JsonSchema>() -> Vec<Value> {
+    let schema = settings()
+        .for_deserialize()
+        .into_generator()
+        .into_root_schema_for::<Q>()
+        .to_value();
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut property = property.clone();
+            let description = property
+                .as_object_mut()
+                .and_then(|property| property.remove("description"));
+            // Unset parameters are left out of the query string, none is ever null
+            if let Some(types) = property.get_mut("type").and_then(Value::as_array_mut) {
+                types.retain(|schema_type| schema_type != "null");
+                if let [schema_type] = types.as_slice() {
+                    property["type"] = schema_type.clone();
+                }
+            }
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "required": required.contains(&name.as_str()),
+                "schema": property
+            });
+            if let Some(description) = description {
+                parameter["description"] = description;
+            }
+            parameter
+        })
+        .collect()
+}
+
+/// `getPosts` for GET /get-posts, `postAdminFeatured` for POST /admin/featured
+fn operation_id(method: &str, path: &str) -> String {
+    let mut id = if method == "get" {
+        String::new()
+    } else {
+        method.to_string()
+    };
+    for word in path
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            if id.is_empty() {
+                id.push(first);
+            } else {
+                id.push(first.to_ascii_uppercase());
+            }
+            id.extend(chars);
+        }
+    }
+    id
+}
+
+/// Group of an operation: its API version, admin, or system for the other root paths
+fn tag(path: &str) -> &'static str {
+    if let Some(version) = api_version::version_of(path) {
+        version
+    } else if path.starts_with("/admin/") {
+        "admin"
+    } else {
+        "system"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_id() {
+        assert_eq!(operation_id("get", "/get-posts"), "getPosts");
+        assert_eq!(operation_id("get", "/v1/get-posts"), "v1GetPosts");
+        assert_eq!(operation_id("get", "/v2/get-posts"), "v2GetPosts");
+        assert_eq!(operation_id("post", "/admin/featured"), "postAdminFeatured");
+        assert_eq!(
+            operation_id("get", "/.well-known/k-indexer"),
+            "wellKnownKIndexer"
+        );
+    }
+
+    #[test]
+    fn test_document() {
+        let document = document();
+
+        // Operation IDs are unique
+        let mut ids: Vec<&str> = document["paths"]
+            .as_object()
+            .unwrap()
+            .values()
+            .flat_map(|item| item.as_object().unwrap().values())
+            .map(|operation| operation["operationId"].as_str().unwrap())
+            .collect();
+        let operations = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), operations);
+
+        // Query parameters come from the query struct, under their serialized names (sorted)
+        let parameters = &document["paths"]["/v1/get-posts"]["get"]["parameters"];
+        let names: Vec<&str> = parameters
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["after", "before", "limit", "requesterPubkey", "user"]
+        );
+        assert_eq!(parameters[2]["schema"]["type"], "integer");
+
+        // Unversioned paths of public endpoints are deprecated aliases, system ones are not
+        let alias = &document["paths"]["/get-posts"]["get"];
+        assert_eq!(alias["deprecated"], true);
+        assert_eq!(alias["parameters"], *parameters);
+        assert!(document["paths"]["/health"]["get"]["deprecated"].is_null());
+        assert!(document["paths"]["/v1/health"].is_null());
+
+        // Every referenced response model is defined in the components
+        let schemas = &document["components"]["schemas"];
+        let response = &document["paths"]["/v2/get-posts"]["get"]["responses"]["200"];
+        let reference = response["content"]["application/json"]["schema"]["$ref"]
+            .as_str()
+            .unwrap();
+        assert_eq!(reference, "#/components/schemas/ServerPostEnvelope");
+        for name in ["ServerPostEnvelope", "ServerPost", "ApiError"] {
+            assert!(schemas.get(name).is_some(), "{} is not defined", name);
+        }
+        assert!(
+            document["paths"]["/admin/featured"]["delete"]["security"]
+                .as_array()
+                .is_some()
+        );
+    }
+}
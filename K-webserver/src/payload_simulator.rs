@@ -0,0 +1,25 @@
+use crate::models::PayloadSimulationResponse;
+use async_trait::async_trait;
+
+/// Outcome of a simulated payload
+pub enum Simulation {
+    /// Accepted by the pipeline, with the rows it would write
+    Indexed(PayloadSimulationResponse),
+    /// The payload does not parse
+    Invalid(String),
+    /// Parsed, but validation would skip it or nothing would be written
+    Rejected(String),
+}
+
+/// Runs payloads through the parser and validation of K-transaction-processor without
+/// persisting them. The webserver cannot link the processor: k-indexer, which links both,
+/// provides it to serve /admin/simulate-payload
+#[async_trait]
+pub trait PayloadSimulator: Send + Sync {
+    async fn simulate(
+        &self,
+        payload: &str,
+        transaction_id: Option<String>,
+        block_time: Option<i64>,
+    ) -> anyhow::Result<Simulation>;
+}
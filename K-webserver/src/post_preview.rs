@@ -0,0 +1,346 @@
+use crate::activitypub::{escape_html, html_text, rfc3339};
+use crate::models::ExplorerLinks;
+use serde_json::{Value, json};
+
+pub const HTML: &str = "text/html; charset=utf-8";
+pub const SITEMAP_XML: &str = "application/xml; charset=utf-8";
+
+/// Descriptions of link previews are cut to this many characters
+const SNIPPET_MAX_CHARS: usize = 200;
+
+/// Width of embedded posts, unless the consumer asks for less (maxwidth)
+pub const EMBED_WIDTH: u32 = 550;
+
+/// Seconds oEmbed consumers may cache an embed
+const EMBED_CACHE_AGE: u32 = 300;
+
+/// A post or reply rendered as a link preview page (text already decoded)
+pub struct PostPreview<'a> {
+    // Absolute URL of the preview page itself
+    pub url: &'a str,
+    pub author_name: &'a str,
+    pub text: &'a str,
+    pub block_time: u64,
+    pub image_url: Option<&'a str>,
+    // Page of the post in a K client, if one is configured
+    pub client_link: Option<&'a str>,
+    // Block explorer pages of the transaction, if an explorer is configured (--explorer-url)
+    pub explorer_links: Option<&'a ExplorerLinks>,
+    pub is_reply: bool,
+    // oEmbed endpoint of the webserver, advertised for embed discovery
+    pub oembed_endpoint: &'a str,
+}
+
+/// Minimal HTML page carrying OpenGraph and Twitter card tags, so chat apps unfurl the link
+pub fn render_page(preview: &PostPreview) -> String {
+    let title = title(preview);
+    let description = snippet(preview.text);
+    let published = rfc3339(preview.block_time);
+
+    let mut meta = vec![
+        ("name", "description", description.as_str()),
+        ("property", "og:type", "article"),
+        ("property", "og:site_name", "K"),
+        ("property", "og:title", title.as_str()),
+        ("property", "og:description", description.as_str()),
+        ("property", "og:url", preview.url),
+        ("property", "article:published_time", published.as_str()),
+        ("name", "twitter:card", "summary"),
+        ("name", "twitter:title", title.as_str()),
+        ("name", "twitter:description", description.as_str()),
+    ];
+    if let Some(image_url) = preview.image_url {
+        meta.push(("property", "og:image", image_url));
+        meta.push(("name", "twitter:image", image_url));
+    }
+
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    html.push_str(&format!(
+        "<link rel=\"canonical\" href=\"{}\">\n",
+        escape_html(preview.url)
+    ));
+    html.push_str(&format!(
+        "<link rel=\"alternate\" type=\"application/json+oembed\" href=\"{}\" title=\"{}\">\n",
+        escape_html(&format!(
+            "{}?url={}",
+            preview.oembed_endpoint,
+            percent_encode(preview.url)
+        )),
+        escape_html(&title)
+    ));
+    for (attribute, name, content) in meta {
+        html.push_str(&format!(
+            "<meta {}=\"{}\" content=\"{}\">\n",
+            attribute,
+            name,
+            escape_html(content)
+        ));
+    }
+    html.push_str("</head>\n<body>\n<article>\n");
+    html.push_str(&format!(
+        "<p><strong>{}</strong> &middot; <time datetime=\"{}\">{}</time></p>\n",
+        escape_html(preview.author_name),
+        published,
+        published
+    ));
+    html.push_str(&html_text(preview.text));
+    html.push('\n');
+    if let Some(client_link) = preview.client_link {
+        html.push_str(&format!(
+            "<p><a href=\"{}\">Open in K</a></p>\n",
+            escape_html(client_link)
+        ));
+    }
+    if let Some(explorer_links) = preview.explorer_links {
+        html.push_str(&format!(
+            "<p><a href=\"{}\">View transaction on explorer</a>",
+            escape_html(&explorer_links.transaction)
+        ));
+        if let Some(block) = &explorer_links.block {
+            html.push_str(&format!(
+                " &middot; <a href=\"{}\">View block</a>",
+                escape_html(block)
+            ));
+        }
+        html.push_str("</p>\n");
+    }
+    html.push_str("</article>\n</body>\n</html>\n");
+    html
+}
+
+/// oEmbed 1.0 "rich" response embedding the post as a blockquote linking to its preview page
+pub fn oembed(preview: &PostPreview, provider_url: &str, author_url: &str, width: u32) -> Value {
+    let html = format!(
+        "<blockquote class=\"k-post\" cite=\"{url}\">{text}<p>&mdash; {author} <a href=\"{url}\">{date}</a></p></blockquote>",
+        url = escape_html(preview.url),
+        text = html_text(preview.text),
+        author = escape_html(preview.author_name),
+        date = rfc3339(preview.block_time)
+    );
+
+    json!({
+        "version": "1.0",
+        "type": "rich",
+        "provider_name": "K",
+        "provider_url": provider_url,
+        "title": title(preview),
+        "author_name": preview.author_name,
+        "author_url": author_url,
+        "html": html,
+        "width": width,
+        // Height depends on the text and the embedding page styles
+        "height": null,
+        "cache_age": EMBED_CACHE_AGE
+    })
+}
+
+/// Post id of a URL following one of the templates (`{id}` = 64 hex characters)
+pub fn post_id_from_url<'a>(url: &'a str, templates: &[String]) -> Option<&'a str> {
+    templates.iter().find_map(|template| {
+        let (prefix, suffix) = template.split_once("{id}")?;
+        // Fragments and tracking parameters may be appended to links without their own
+        let mut url = url;
+        for separator in ['#', '?'] {
+            if !template.contains(separator) {
+                url = url.split(separator).next().unwrap_or(url);
+            }
+        }
+        let id = url.strip_prefix(prefix)?.strip_suffix(suffix)?;
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then_some(id)
+    })
+}
+
+fn title(preview: &PostPreview) -> String {
+    if preview.is_reply {
+        format!("{} replied on K", preview.author_name)
+    } else {
+        format!("{} on K", preview.author_name)
+    }
+}
+
+/// Percent-encode everything but unreserved characters (RFC 3986), for query values
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Sitemap (sitemaps.org 0.9) of preview page URLs with their block time
+pub fn render_sitemap(urls: &[(String, u64)]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for (url, block_time) in urls {
+        xml.push_str(&format!(
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+            escape_html(url),
+            rfc3339(*block_time)
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// Text on a single line, whitespace collapsed and cut to SNIPPET_MAX_CHARS
+fn snippet(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() > SNIPPET_MAX_CHARS {
+        let cut: String = collapsed.chars().take(SNIPPET_MAX_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        collapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview<'a>(text: &'a str, image_url: Option<&'a str>) -> PostPreview<'a> {
+        preview_with_explorer(text, image_url, None)
+    }
+
+    fn preview_with_explorer<'a>(
+        text: &'a str,
+        image_url: Option<&'a str>,
+        explorer_links: Option<&'a ExplorerLinks>,
+    ) -> PostPreview<'a> {
+        PostPreview {
+            url: "https://k.example.org/p/ab",
+            author_name: "Alice <3",
+            text,
+            block_time: 0,
+            image_url,
+            client_link: Some("https://client.example/post/ab?x=1&y=2"),
+            explorer_links,
+            is_reply: false,
+            oembed_endpoint: "https://k.example.org/oembed",
+        }
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("  one\n\ntwo\tthree "), "one two three");
+        let long = "word ".repeat(100);
+        let cut = snippet(&long);
+        assert!(cut.chars().count() <= SNIPPET_MAX_CHARS);
+        assert!(cut.ends_with('…'));
+    }
+
+    #[test]
+    fn test_render_page_escapes_meta_tags() {
+        let html = render_page(&preview("hello \"world\"\nbye", None));
+        assert!(html.contains("<title>Alice &lt;3 on K</title>"));
+        assert!(html.contains(
+            "<meta property=\"og:description\" content=\"hello &quot;world&quot; bye\">"
+        ));
+        assert!(html.contains("<p>hello &quot;world&quot;<br>bye</p>"));
+        assert!(html.contains("href=\"https://client.example/post/ab?x=1&amp;y=2\""));
+        assert!(!html.contains("og:image"));
+
+        let html = render_page(&preview("hi", Some("https://k.example.org/img")));
+        assert!(
+            html.contains("<meta property=\"og:image\" content=\"https://k.example.org/img\">")
+        );
+        assert!(
+            html.contains("<meta name=\"twitter:image\" content=\"https://k.example.org/img\">")
+        );
+    }
+
+    #[test]
+    fn test_render_page_explorer_links() {
+        assert!(!render_page(&preview("hi", None)).contains("explorer"));
+
+        let mut links = ExplorerLinks {
+            transaction: "https://explorer.example/txs/ab".to_string(),
+            block: None,
+        };
+        let html = render_page(&preview_with_explorer("hi", None, Some(&links)));
+        assert!(html.contains(
+            "<p><a href=\"https://explorer.example/txs/ab\">View transaction on explorer</a></p>"
+        ));
+        assert!(!html.contains("View block"));
+
+        links.block = Some("https://explorer.example/blocks/cd".to_string());
+        let html = render_page(&preview_with_explorer("hi", None, Some(&links)));
+        assert!(html.contains(
+            " &middot; <a href=\"https://explorer.example/blocks/cd\">View block</a></p>"
+        ));
+    }
+
+    #[test]
+    fn test_render_page_advertises_oembed() {
+        let html = render_page(&preview("hi", None));
+        assert!(html.contains(
+            "<link rel=\"alternate\" type=\"application/json+oembed\" href=\"https://k.example.org/oembed?url=https%3A%2F%2Fk.example.org%2Fp%2Fab\""
+        ));
+    }
+
+    #[test]
+    fn test_oembed() {
+        let response = oembed(
+            &preview("a & b", None),
+            "https://k.example.org",
+            "https://k.example.org/user",
+            400,
+        );
+        assert_eq!(response["type"], json!("rich"));
+        assert_eq!(response["width"], json!(400));
+        assert_eq!(response["title"], json!("Alice <3 on K"));
+        let html = response["html"].as_str().unwrap();
+        assert!(html.starts_with(
+            "<blockquote class=\"k-post\" cite=\"https://k.example.org/p/ab\"><p>a &amp; b</p>"
+        ));
+        assert!(html.contains(
+            "&mdash; Alice &lt;3 <a href=\"https://k.example.org/p/ab\">1970-01-01T00:00:00Z</a>"
+        ));
+    }
+
+    #[test]
+    fn test_post_id_from_url() {
+        let id = "7e2b9c4d1a8f3e6b0c5d2a9f4e1b8c3d6a0f5e2b9c4d1a8f3e6b0c5d2a9f4e1b";
+        let templates = vec![
+            "https://k.example.org/p/{id}".to_string(),
+            "https://client.example/#/post/{id}/view".to_string(),
+        ];
+        assert_eq!(
+            post_id_from_url(
+                &format!("https://k.example.org/p/{}?utm=x#top", id),
+                &templates
+            ),
+            Some(id)
+        );
+        assert_eq!(
+            post_id_from_url(
+                &format!("https://client.example/#/post/{}/view", id),
+                &templates
+            ),
+            Some(id)
+        );
+        assert_eq!(
+            post_id_from_url("https://k.example.org/p/abcd", &templates),
+            None
+        );
+        assert_eq!(
+            post_id_from_url(&format!("https://other.example/p/{}", id), &templates),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_sitemap() {
+        let xml = render_sitemap(&[("https://k.example.org/p/ab".to_string(), 86_400_000)]);
+        assert!(xml.contains(
+            "<url><loc>https://k.example.org/p/ab</loc><lastmod>1970-01-02T00:00:00Z</lastmod></url>"
+        ));
+        assert!(xml.ends_with("</urlset>\n"));
+    }
+}
@@ -0,0 +1,143 @@
+use sqlx::postgres::{PgArguments, Postgres};
+use sqlx::query::Query;
+
+/// Value bound to a parameter of a query assembled at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    BigInt(i64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> Self {
+        SqlValue::BigInt(value)
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
+
+impl From<&[u8]> for SqlValue {
+    fn from(value: &[u8]) -> Self {
+        SqlValue::Bytes(value.to_vec())
+    }
+}
+
+impl From<&Vec<u8>> for SqlValue {
+    fn from(value: &Vec<u8>) -> Self {
+        SqlValue::Bytes(value.clone())
+    }
+}
+
+impl From<Vec<u8>> for SqlValue {
+    fn from(value: Vec<u8>) -> Self {
+        SqlValue::Bytes(value)
+    }
+}
+
+/// Parameters of a query whose SQL is assembled at runtime (optional filters, cursors, LIMIT)
+/// Every value is added together with the placeholder it binds, so placeholders always follow
+/// the bind order; the placeholder syntax and `bind` are the only Postgres-specific parts
+#[derive(Debug, Clone, Default)]
+pub struct QueryParams {
+    values: Vec<SqlValue>,
+}
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a value, returning the placeholder to use for it in the SQL
+    pub fn push(&mut self, value: impl Into<SqlValue>) -> String {
+        self.values.push(value.into());
+        format!("${}", self.values.len())
+    }
+
+    /// Keyset pagination conditions (each starting with AND) on a (sort value, id) pair: rows
+    /// before the `before` cursor and after the `after` cursor, empty without cursors
+    pub fn keyset_conditions(
+        &mut self,
+        sort_column: &str,
+        id_column: &str,
+        before: Option<(u64, i64)>,
+        after: Option<(u64, i64)>,
+    ) -> String {
+        let mut conditions = String::new();
+        for (cursor, op) in [(before, "<"), (after, ">")] {
+            if let Some((sort_value, id)) = cursor {
+                let sort_param = self.push(sort_value as i64);
+                let id_param = self.push(id);
+                conditions.push_str(&format!(
+                    " AND ({sort_column} {op} {sort_param} OR ({sort_column} = {sort_param} AND {id_column} {op} {id_param}))"
+                ));
+            }
+        }
+        conditions
+    }
+
+    /// Bind all values in order
+    pub fn bind<'q>(
+        &'q self,
+        mut query: Query<'q, Postgres, PgArguments>,
+    ) -> Query<'q, Postgres, PgArguments> {
+        for value in &self.values {
+            query = match value {
+                SqlValue::BigInt(value) => query.bind(*value),
+                SqlValue::Text(value) => query.bind(value.as_str()),
+                SqlValue::Bytes(value) => query.bind(value.as_slice()),
+            };
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_follow_push_order() {
+        let mut params = QueryParams::new();
+        assert_eq!(params.push(vec![2u8, 3]), "$1");
+        assert_eq!(params.push("kaspa"), "$2");
+        assert_eq!(params.push(21i64), "$3");
+        assert_eq!(
+            params.values,
+            vec![
+                SqlValue::Bytes(vec![2, 3]),
+                SqlValue::Text("kaspa".to_string()),
+                SqlValue::BigInt(21)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyset_conditions() {
+        let mut params = QueryParams::new();
+        params.push(vec![2u8]);
+        assert_eq!(
+            params.keyset_conditions("c.block_time", "c.id", Some((1000, 7)), Some((500, 3))),
+            " AND (c.block_time < $2 OR (c.block_time = $2 AND c.id < $3)) \
+             AND (c.block_time > $4 OR (c.block_time = $4 AND c.id > $5))"
+        );
+        assert_eq!(params.push(11i64), "$6");
+
+        let mut params = QueryParams::new();
+        assert_eq!(
+            params.keyset_conditions("c.block_time", "c.id", None, None),
+            ""
+        );
+        assert_eq!(params.push(11i64), "$1");
+    }
+}
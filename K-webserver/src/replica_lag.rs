@@ -0,0 +1,246 @@
+use axum_prometheus::metrics::gauge;
+use sqlx::PgPool;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Interval between WAL position samples of the primary and the read replica
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Primary WAL samples kept while the replica has not replayed them (one per probe)
+const MAX_SAMPLES: usize = 3600;
+
+/// Replay position of a read replica, as the primary wall clock time it has caught up to
+pub struct ReplicaLag {
+    // Reads go to the primary while the replica lags more than this
+    max_lag_ms: i64,
+    // (primary WAL LSN, wall clock ms) samples not yet replayed by the replica, oldest first
+    samples: Mutex<VecDeque<(i64, i64)>>,
+    // Everything the primary committed before this time (ms) is visible on the replica (0 = unknown)
+    applied_at_ms: AtomicI64,
+    // Block time (ms) up to which the replica lists indexed content (0 = unknown): the processed
+    // watermark it has replayed, capped by its k_meta.visible_before. Cursors hold block times,
+    // which are older than the wall clock time their content was committed at
+    indexed_before_ms: AtomicI64,
+}
+
+impl ReplicaLag {
+    pub fn new(max_lag_ms: u64) -> Self {
+        Self {
+            max_lag_ms: max_lag_ms as i64,
+            samples: Mutex::new(VecDeque::new()),
+            applied_at_ms: AtomicI64::new(0),
+            indexed_before_ms: AtomicI64::new(0),
+        }
+    }
+
+    /// Record the primary WAL position (pg_current_wal_lsn) at `now_ms`
+    pub fn record_primary(&self, lsn: i64, now_ms: i64) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((lsn, now_ms));
+    }
+
+    /// Record the replica replay position (pg_last_wal_replay_lsn): it has caught up to the
+    /// newest primary sample at or below that position
+    pub fn record_replica(&self, replay_lsn: i64) {
+        let mut samples = self.samples.lock().unwrap();
+        let mut applied = None;
+        while let Some(&(lsn, at_ms)) = samples.front() {
+            if lsn > replay_lsn {
+                break;
+            }
+            applied = Some(at_ms);
+            samples.pop_front();
+        }
+        if let Some(at_ms) = applied {
+            self.applied_at_ms.fetch_max(at_ms, Ordering::Relaxed);
+        }
+    }
+
+    /// Record the block time up to which the replica lists indexed content (None = unknown,
+    /// e.g. nothing processed yet)
+    pub fn record_replica_indexed(&self, indexed_before_ms: Option<i64>) {
+        self.indexed_before_ms
+            .store(indexed_before_ms.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Replication lag in milliseconds at `now_ms` (None = replica position unknown)
+    pub fn lag_ms(&self, now_ms: i64) -> Option<i64> {
+        match self.applied_at_ms.load(Ordering::Relaxed) {
+            0 => None,
+            applied_at_ms => Some((now_ms - applied_at_ms).max(0)),
+        }
+    }
+
+    /// Whether the replica can serve a read whose `after` cursor is at block time `after_ms`:
+    /// its lag is within the limit and it lists the indexed content up to the cursor, so content
+    /// the client has already seen (e.g. their own new post) cannot disappear
+    pub fn serves(&self, after_ms: Option<i64>, now_ms: i64) -> bool {
+        let Some(lag_ms) = self.lag_ms(now_ms) else {
+            return false;
+        };
+        let indexed_before_ms = self.indexed_before_ms.load(Ordering::Relaxed);
+        lag_ms <= self.max_lag_ms
+            && after_ms
+                .is_none_or(|after_ms| indexed_before_ms > 0 && after_ms <= indexed_before_ms)
+    }
+}
+
+pub fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Sample the WAL positions of the primary and the replica every second and publish the lag
+/// as a Prometheus gauge
+pub async fn start_replica_lag_monitor(primary: PgPool, replica: PgPool, lag: Arc<ReplicaLag>) {
+    let mut interval = tokio::time::interval(PROBE_INTERVAL);
+    let mut was_lagging = false;
+
+    loop {
+        interval.tick().await;
+
+        // Primary first: the replica position read right after covers this sample when caught up
+        let sampled_at = now_ms();
+        match sqlx::query_scalar::<_, i64>("SELECT (pg_current_wal_lsn() - '0/0')::bigint")
+            .fetch_one(&primary)
+            .await
+        {
+            Ok(lsn) => lag.record_primary(lsn, sampled_at),
+            Err(e) => {
+                warn!("Failed to read the primary WAL position: {}", e);
+                continue;
+            }
+        }
+
+        // A replica promoted to primary (or the primary itself) has no replay position
+        match sqlx::query_scalar::<_, i64>(
+            "SELECT (COALESCE(pg_last_wal_replay_lsn(), pg_current_wal_lsn()) - '0/0')::bigint",
+        )
+        .fetch_one(&replica)
+        .await
+        {
+            Ok(replay_lsn) => lag.record_replica(replay_lsn),
+            Err(e) => warn!("Failed to read the read replica replay position: {}", e),
+        }
+
+        // visible_before is read through to_jsonb: the column only exists from schema v28
+        match sqlx::query_scalar::<_, Option<i64>>(
+            r#"
+            SELECT LEAST(watermark, COALESCE(visible_before, watermark))
+            FROM (
+                SELECT
+                    (SELECT value::BIGINT FROM k_vars WHERE key = 'processed_block_time') AS watermark,
+                    (SELECT (to_jsonb(m) ->> 'visible_before')::BIGINT FROM k_meta m WHERE id = 1) AS visible_before
+            ) replica
+            "#,
+        )
+        .fetch_one(&replica)
+        .await
+        {
+            Ok(indexed_before) => lag.record_replica_indexed(indexed_before),
+            Err(e) => {
+                lag.record_replica_indexed(None);
+                warn!("Failed to read the read replica processed watermark: {}", e);
+            }
+        }
+
+        let now = now_ms();
+        let lagging = !lag.serves(None, now);
+        if lagging != was_lagging {
+            match lag.lag_ms(now) {
+                Some(lag_ms) if lagging => warn!(
+                    "Read replica lags {}ms behind, paginated reads go to the primary",
+                    lag_ms
+                ),
+                None => warn!("Read replica position unknown, paginated reads go to the primary"),
+                _ => info!("Read replica caught up, paginated reads go to the replica again"),
+            }
+        }
+        was_lagging = lagging;
+        if let Some(lag_ms) = lag.lag_ms(now) {
+            gauge!("k_replica_lag_ms").set(lag_ms as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_position_is_not_served() {
+        let lag = ReplicaLag::new(5000);
+        assert_eq!(lag.lag_ms(1_000), None);
+        assert!(!lag.serves(None, 1_000));
+
+        // Replica behind the only sample
+        lag.record_primary(100, 1_000);
+        lag.record_replica(99);
+        assert!(!lag.serves(None, 1_000));
+    }
+
+    #[test]
+    fn test_caught_up_replica_serves() {
+        let lag = ReplicaLag::new(5000);
+        lag.record_primary(100, 1_000);
+        lag.record_replica(100);
+        assert_eq!(lag.lag_ms(1_200), Some(200));
+        assert!(lag.serves(None, 1_200));
+        // Cursors need the indexed position of the replica
+        assert!(!lag.serves(Some(900), 1_200));
+        lag.record_replica_indexed(Some(900));
+        assert!(lag.serves(Some(900), 1_200));
+        // Cursor newer than what the replica has indexed
+        assert!(!lag.serves(Some(901), 1_200));
+    }
+
+    #[test]
+    fn test_cursor_routed_by_indexed_block_time() {
+        let lag = ReplicaLag::new(5000);
+        lag.record_primary(100, 10_000);
+        lag.record_replica(100);
+        // The replica has replayed the primary up to 10s, but content of block time 8s was
+        // committed later (indexing and confirmation depth delays) and is not there yet
+        lag.record_replica_indexed(Some(6_000));
+        assert!(!lag.serves(Some(8_000), 10_100));
+        assert!(lag.serves(Some(6_000), 10_100));
+
+        lag.record_replica_indexed(None);
+        assert!(!lag.serves(Some(6_000), 10_100));
+        assert!(lag.serves(None, 10_100));
+    }
+
+    #[test]
+    fn test_applied_position_follows_replay() {
+        let lag = ReplicaLag::new(5000);
+        lag.record_primary(100, 1_000);
+        lag.record_primary(200, 2_000);
+        lag.record_primary(300, 3_000);
+
+        lag.record_replica(250);
+        assert_eq!(lag.lag_ms(3_000), Some(1_000));
+        lag.record_replica(300);
+        assert_eq!(lag.lag_ms(3_000), Some(0));
+        // Replay position never goes back
+        lag.record_replica(100);
+        assert_eq!(lag.lag_ms(3_000), Some(0));
+    }
+
+    #[test]
+    fn test_lag_over_limit_is_not_served() {
+        let lag = ReplicaLag::new(5000);
+        lag.record_primary(100, 1_000);
+        lag.record_replica(100);
+        assert!(lag.serves(None, 6_000));
+        assert!(!lag.serves(None, 6_001));
+    }
+}
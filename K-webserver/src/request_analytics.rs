@@ -0,0 +1,203 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::models::{ContentRequests, EndpointRequests, RequestAnalyticsHour};
+
+const HOUR_SECS: u64 = 3600;
+
+/// Distinct contents counted per hour, so a crawler walking every post cannot grow the counts
+/// without bound; requests for further contents are only counted as untracked
+const MAX_CONTENTS_PER_HOUR: usize = 5_000;
+
+/// Query parameters naming the content a request reads (get-post-details?id=, get-replies?post=)
+const CONTENT_ID_PARAMS: [&str; 2] = ["id", "post"];
+
+/// Requests of one hour
+struct HourCounts {
+    start: u64,
+    requests: u64,
+    endpoints: HashMap<&'static str, u64>,
+    contents: HashMap<String, u64>,
+    untracked_contents: u64,
+}
+
+impl HourCounts {
+    fn new(start: u64) -> Self {
+        Self {
+            start,
+            requests: 0,
+            endpoints: HashMap::new(),
+            contents: HashMap::new(),
+            untracked_contents: 0,
+        }
+    }
+}
+
+/// Hourly counts of the requests per public endpoint and per content ID
+/// (--request-analytics-hours); neither the client address, the requester nor any other
+/// parameter is recorded
+pub struct RequestAnalytics {
+    retention_hours: u64,
+    // Hours with requests, oldest first
+    hours: Mutex<VecDeque<HourCounts>>,
+}
+
+impl RequestAnalytics {
+    pub fn new(retention_hours: u64) -> Self {
+        Self {
+            retention_hours,
+            hours: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn retention_hours(&self) -> u64 {
+        self.retention_hours
+    }
+
+    /// Count a request to `endpoint` at `now` (Unix seconds), for `content_id` if it reads one
+    pub fn record(&self, endpoint: &'static str, content_id: Option<String>, now: u64) {
+        let start = now - now % HOUR_SECS;
+        let mut hours = self.hours.lock().unwrap();
+        if hours.back().is_none_or(|hour| hour.start < start) {
+            hours.push_back(HourCounts::new(start));
+            let oldest_kept = start.saturating_sub((self.retention_hours - 1) * HOUR_SECS);
+            while hours.front().is_some_and(|hour| hour.start < oldest_kept) {
+                hours.pop_front();
+            }
+        }
+        // A clock moved backwards counts into the newest hour
+        let Some(hour) = hours.back_mut() else {
+            return;
+        };
+
+        hour.requests += 1;
+        *hour.endpoints.entry(endpoint).or_default() += 1;
+        if let Some(content_id) = content_id {
+            if let Some(count) = hour.contents.get_mut(&content_id) {
+                *count += 1;
+            } else if hour.contents.len() < MAX_CONTENTS_PER_HOUR {
+                hour.contents.insert(content_id, 1);
+            } else {
+                hour.untracked_contents += 1;
+            }
+        }
+    }
+
+    /// Hours with requests among the last `hours` before `now`, newest first, with their
+    /// `top` most requested endpoints and contents
+    pub fn report(&self, hours: u64, top: usize, now: u64) -> Vec<RequestAnalyticsHour> {
+        let current = now - now % HOUR_SECS;
+        let oldest = current.saturating_sub(hours.saturating_sub(1) * HOUR_SECS);
+        let counts = self.hours.lock().unwrap();
+
+        counts
+            .iter()
+            .rev()
+            .filter(|hour| hour.start >= oldest)
+            .map(|hour| RequestAnalyticsHour {
+                hour_start: hour.start,
+                requests: hour.requests,
+                endpoints: most_requested(&hour.endpoints, top)
+                    .into_iter()
+                    .map(|(endpoint, requests)| EndpointRequests {
+                        endpoint: endpoint.to_string(),
+                        requests,
+                    })
+                    .collect(),
+                contents: most_requested(&hour.contents, top)
+                    .into_iter()
+                    .map(|(id, requests)| ContentRequests {
+                        id: id.clone(),
+                        requests,
+                    })
+                    .collect(),
+                untracked_content_requests: hour.untracked_contents,
+            })
+            .collect()
+    }
+}
+
+/// Content a request reads: its id or post parameter when it is a transaction ID (lowercase)
+pub fn content_id_of(query: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        let is_content_id = CONTENT_ID_PARAMS.contains(&key)
+            && value.len() == 64
+            && value.bytes().all(|byte| byte.is_ascii_hexdigit());
+        is_content_id.then(|| value.to_ascii_lowercase())
+    })
+}
+
+/// The `top` keys with the most requests, ties in key order
+fn most_requested<K: Ord>(counts: &HashMap<K, u64>, top: usize) -> Vec<(&K, u64)> {
+    let mut counts: Vec<(&K, u64)> = counts.iter().map(|(key, &count)| (key, count)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    counts.truncate(top);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POST: &str = "a7b8c9d0e1f2a3b4c5d6e7f8a9b0c1d2e3f4a5b6c7d8e9f0a1b2c3d4e5f6a7b8";
+
+    #[test]
+    fn test_content_id_of() {
+        assert_eq!(
+            content_id_of(&format!("id={}&requesterPubkey=02ab", POST.to_uppercase())),
+            Some(POST.to_string())
+        );
+        assert_eq!(
+            content_id_of(&format!("requesterPubkey=02ab&post={}", POST)),
+            Some(POST.to_string())
+        );
+        assert_eq!(content_id_of("id=12"), None);
+        assert_eq!(content_id_of(&format!("user={}", POST)), None);
+    }
+
+    #[test]
+    fn test_report_counts_hours() {
+        let analytics = RequestAnalytics::new(2);
+        let hour = 1_700_000_000 - 1_700_000_000 % HOUR_SECS;
+        analytics.record("get-posts", None, hour + 5);
+        analytics.record("get-post-details", Some(POST.to_string()), hour + HOUR_SECS);
+        analytics.record("get-replies", Some(POST.to_string()), hour + HOUR_SECS + 10);
+        analytics.record("get-posts", None, hour + HOUR_SECS + 20);
+        analytics.record("get-posts", None, hour + HOUR_SECS + 30);
+
+        let report = analytics.report(24, 2, hour + HOUR_SECS + 40);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].hour_start, hour + HOUR_SECS);
+        assert_eq!(report[0].requests, 4);
+        let endpoints: Vec<(&str, u64)> = report[0]
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.endpoint.as_str(), endpoint.requests))
+            .collect();
+        assert_eq!(endpoints, [("get-posts", 2), ("get-post-details", 1)]);
+        assert_eq!(report[0].contents[0].id, POST);
+        assert_eq!(report[0].contents[0].requests, 2);
+        assert_eq!(report[1].requests, 1);
+
+        // Only the last hour asked, then the first hour dropped once out of retention
+        assert_eq!(analytics.report(1, 2, hour + HOUR_SECS + 40).len(), 1);
+        analytics.record("get-posts", None, hour + 2 * HOUR_SECS);
+        let report = analytics.report(24, 2, hour + 2 * HOUR_SECS);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[1].hour_start, hour + HOUR_SECS);
+    }
+
+    #[test]
+    fn test_contents_per_hour_are_bounded() {
+        let analytics = RequestAnalytics::new(1);
+        for i in 0..MAX_CONTENTS_PER_HOUR + 3 {
+            analytics.record("get-post-details", Some(format!("{:064x}", i)), 0);
+        }
+        analytics.record("get-post-details", Some(format!("{:064x}", 0)), 0);
+
+        let report = analytics.report(1, 1, 0);
+        assert_eq!(report[0].untracked_content_requests, 3);
+        assert_eq!(report[0].contents[0].requests, 2);
+    }
+}
@@ -0,0 +1,92 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{Instrument, info_span};
+
+/// Correlation ID of a request, taken from the client or generated, echoed in the response
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest accepted client ID, keeping `k-webserver:req:<id>` within the 63 bytes of a
+/// Postgres application_name
+const MAX_REQUEST_ID_LEN: usize = 40;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request served by the current task (None in background tasks)
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// application_name of the database sessions working for request `id`
+pub fn application_name(id: &str) -> String {
+    format!("k-webserver:req:{}", id)
+}
+
+/// Client IDs are logged and sent to the database, so only short plain tokens are kept
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+fn generate() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    // Every RandomState is seeded differently, so this is random rather than sequential
+    let id = RandomState::new().hash_one(NEXT.fetch_add(1, Ordering::Relaxed));
+    format!("{:016x}", id)
+}
+
+/// Serve the request under its ID: the `X-Request-Id` header of the client when valid, a new
+/// one otherwise. Log lines of the request carry it, and so do its database sessions
+pub async fn propagate_request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(generate);
+
+    let span = info_span!("request", request_id = %id);
+    let mut response = REQUEST_ID
+        .scope(id.clone(), next.run(request).instrument(span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_request_ids() {
+        assert!(is_valid("4bf92f3577b34da6a3ce929d0e0e4736"));
+        assert!(is_valid("req-42_a.b"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("two words"));
+        assert!(!is_valid("x'; DROP TABLE k_contents; --"));
+        assert!(!is_valid(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+        assert!(application_name(&"a".repeat(MAX_REQUEST_ID_LEN)).len() <= 63);
+    }
+
+    #[test]
+    fn test_generated_request_ids() {
+        let (first, second) = (generate(), generate());
+        assert!(is_valid(&first));
+        assert_eq!(first.len(), 16);
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,123 @@
+use serde_json::Value;
+
+/// Requester the relations of contents and profiles are read for with --requester-privacy:
+/// a compressed key with x = 0, which is not a point of secp256k1, so it never blocks,
+/// follows or votes
+pub const ANONYMOUS_REQUESTER: &str =
+    "020000000000000000000000000000000000000000000000000000000000000000";
+
+/// Fields describing the relation of the requester to a content or user, withheld (null)
+const REQUESTER_FIELDS: [&str; 4] = ["isUpvoted", "isDownvoted", "blockedUser", "followedUser"];
+
+/// Endpoints reading the requester's own feeds, lists, notifications and group memberships,
+/// which keep the requester of the request
+pub const REQUESTER_SCOPED_ENDPOINTS: [&str; 13] = [
+    "get-posts-watching",
+    "get-posts-light",
+    "get-contents-following",
+    "get-posts-following",
+    "get-followed-users",
+    "get-blocked-users",
+    "get-notifications-count",
+    "get-notifications",
+    "get-groups",
+    "get-group-posts",
+    "get-feature-flags",
+    "ws",
+    "notifications/stream",
+];
+
+/// Query string with requesterPubkey replaced by ANONYMOUS_REQUESTER (None without one)
+/// Keys are decoded as the Query extractor decodes them, so a percent-encoded key name
+/// (requester%50ubkey) is replaced too
+pub fn anonymize_requester(query: &str) -> Option<String> {
+    let mut pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).ok()?;
+    let mut found = false;
+    for (key, value) in pairs.iter_mut() {
+        if key == "requesterPubkey" {
+            found = true;
+            *value = ANONYMOUS_REQUESTER.to_string();
+        }
+    }
+    if !found {
+        return None;
+    }
+    serde_urlencoded::to_string(&pairs).ok()
+}
+
+/// Set the requester fields of every object of a JSON response to null, nested replies,
+/// quotes and envelopes included
+pub fn withhold_requester_fields(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, field) in object.iter_mut() {
+                if REQUESTER_FIELDS.contains(&key.as_str()) {
+                    *field = Value::Null;
+                } else {
+                    withhold_requester_fields(field);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                withhold_requester_fields(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_anonymize_requester() {
+        assert_eq!(anonymize_requester("user=02ab&limit=10"), None);
+        assert_eq!(
+            anonymize_requester("user=02ab&requesterPubkey=03cd&limit=10"),
+            Some(format!(
+                "user=02ab&requesterPubkey={}&limit=10",
+                ANONYMOUS_REQUESTER
+            ))
+        );
+        assert_eq!(
+            anonymize_requester("requester%50ubkey=03cd&user=02ab"),
+            Some(format!("requesterPubkey={}&user=02ab", ANONYMOUS_REQUESTER))
+        );
+    }
+
+    #[test]
+    fn test_withhold_requester_fields() {
+        let mut response = json!({
+            "posts": [{
+                "id": "a",
+                "isUpvoted": true,
+                "isDownvoted": false,
+                "blockedUser": false,
+                "quote": { "referencedMessage": "b", "blockedUser": true },
+                "nestedReplies": [{ "id": "c", "isUpvoted": false }]
+            }],
+            "user": { "followedUser": true, "followersCount": 3 },
+            "pagination": { "hasMore": false }
+        });
+        withhold_requester_fields(&mut response);
+
+        assert_eq!(
+            response,
+            json!({
+                "posts": [{
+                    "id": "a",
+                    "isUpvoted": null,
+                    "isDownvoted": null,
+                    "blockedUser": null,
+                    "quote": { "referencedMessage": "b", "blockedUser": null },
+                    "nestedReplies": [{ "id": "c", "isUpvoted": null }]
+                }],
+                "user": { "followedUser": null, "followersCount": 3 },
+                "pagination": { "hasMore": false }
+            })
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use axum_prometheus::metrics::counter;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::config::ResponseCacheConfig;
+use crate::ttl_cache::TtlCache;
+
+/// Entries kept at most; on overflow expired entries are dropped first, then the least
+/// recently used ones
+const MAX_ENTRIES: usize = 4096;
+
+/// Short-lived cache of hot first pages, shared by all requesters that get the same response
+/// Stale entries are served while a single background refresh replaces them, so a popular page
+/// costs one query per TTL instead of one per request
+pub struct ResponseCache {
+    ttl: Duration,
+    // Entries expire once stale for too long, and are then not served at all
+    entries: TtlCache<String, CacheEntry>,
+}
+
+struct CacheEntry {
+    json: Arc<str>,
+    fetched_at: Instant,
+    // A background refresh of this entry is running
+    refreshing: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Fresh(Arc<str>),
+    // `refresh` is set for the one caller that has to start the background refresh
+    Stale { json: Arc<str>, refresh: bool },
+    Miss,
+}
+
+impl ResponseCache {
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        let ttl = Duration::from_secs(config.ttl_secs);
+        let max_age = ttl + Duration::from_secs(config.stale_secs);
+        Self {
+            ttl,
+            entries: TtlCache::new(max_age, MAX_ENTRIES),
+        }
+    }
+
+    pub fn lookup(&self, key: &str, now: Instant) -> Lookup {
+        self.entries
+            .with(key, now, |entry| {
+                if now.saturating_duration_since(entry.fetched_at) < self.ttl {
+                    Lookup::Fresh(entry.json.clone())
+                } else {
+                    let refresh = !std::mem::replace(&mut entry.refreshing, true);
+                    Lookup::Stale {
+                        json: entry.json.clone(),
+                        refresh,
+                    }
+                }
+            })
+            .unwrap_or(Lookup::Miss)
+    }
+
+    pub fn store(&self, key: String, json: Arc<str>, now: Instant) {
+        let entry = CacheEntry {
+            json,
+            fetched_at: now,
+            refreshing: false,
+        };
+        self.entries.insert(key, entry, now);
+    }
+
+    /// Drop entries too old to be served; returns the number of entries left
+    pub fn prune(&self, now: Instant) -> usize {
+        self.entries.prune(now)
+    }
+
+    /// Replace `key` with a freshly fetched response, ahead of any request for it
+    pub async fn prewarm<Fut>(&self, endpoint: &'static str, key: String, fetch: Fut)
+    where
+        Fut: Future<Output = Result<String, String>>,
+    {
+        match fetch.await {
+            Ok(json) => {
+                self.store(key, json.into(), Instant::now());
+                counter!("k_response_cache_prewarms_total", "endpoint" => endpoint).increment(1);
+            }
+            Err(e) => warn!("Failed to prewarm cached {} page: {}", endpoint, e),
+        }
+    }
+
+    /// Let the next stale hit retry a failed background refresh
+    fn refresh_failed(&self, key: &str) {
+        self.entries
+            .with(key, Instant::now(), |entry| entry.refreshing = false);
+    }
+
+    /// Serve `key` from the cache; `fetch` builds the response on a miss and refreshes stale
+    /// entries in the background (its errors are returned on a miss, logged on a refresh)
+    pub async fn get_or_fetch<F, Fut>(
+        self: &Arc<Self>,
+        endpoint: &'static str,
+        key: String,
+        fetch: F,
+    ) -> Result<Arc<str>, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        match self.lookup(&key, Instant::now()) {
+            Lookup::Fresh(json) => {
+                counter!("k_response_cache_requests_total", "endpoint" => endpoint, "result" => "hit")
+                    .increment(1);
+                Ok(json)
+            }
+            Lookup::Stale { json, refresh } => {
+                counter!("k_response_cache_requests_total", "endpoint" => endpoint, "result" => "stale")
+                    .increment(1);
+                if refresh {
+                    let cache = self.clone();
+                    let refreshed = fetch();
+                    tokio::spawn(async move {
+                        match refreshed.await {
+                            Ok(json) => cache.store(key, json.into(), Instant::now()),
+                            Err(e) => {
+                                warn!("Failed to refresh cached {} page: {}", endpoint, e);
+                                cache.refresh_failed(&key);
+                            }
+                        }
+                    });
+                }
+                Ok(json)
+            }
+            Lookup::Miss => {
+                counter!("k_response_cache_requests_total", "endpoint" => endpoint, "result" => "miss")
+                    .increment(1);
+                let json: Arc<str> = fetch().await?.into();
+                self.store(key, json.clone(), Instant::now());
+                Ok(json)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> ResponseCache {
+        ResponseCache::new(ResponseCacheConfig {
+            ttl_secs: 2,
+            stale_secs: 30,
+            prewarm_interval_secs: 0,
+        })
+    }
+
+    #[test]
+    fn test_fresh_then_stale_then_miss() {
+        let cache = cache();
+        let now = Instant::now();
+        assert_eq!(cache.lookup("page", now), Lookup::Miss);
+
+        cache.store("page".to_string(), "{}".into(), now);
+        assert_eq!(
+            cache.lookup("page", now + Duration::from_secs(1)),
+            Lookup::Fresh("{}".into())
+        );
+        assert_eq!(
+            cache.lookup("page", now + Duration::from_secs(2)),
+            Lookup::Stale {
+                json: "{}".into(),
+                refresh: true
+            }
+        );
+        assert_eq!(
+            cache.lookup("page", now + Duration::from_secs(32)),
+            Lookup::Miss
+        );
+    }
+
+    #[test]
+    fn test_single_refresh_per_stale_entry() {
+        let cache = cache();
+        let now = Instant::now();
+        cache.store("page".to_string(), "old".into(), now);
+
+        let later = now + Duration::from_secs(5);
+        assert!(matches!(
+            cache.lookup("page", later),
+            Lookup::Stale { refresh: true, .. }
+        ));
+        assert!(matches!(
+            cache.lookup("page", later),
+            Lookup::Stale { refresh: false, .. }
+        ));
+
+        // A failed refresh is retried by the next request
+        cache.refresh_failed("page");
+        assert!(matches!(
+            cache.lookup("page", later),
+            Lookup::Stale { refresh: true, .. }
+        ));
+
+        cache.store("page".to_string(), "new".into(), later);
+        assert_eq!(cache.lookup("page", later), Lookup::Fresh("new".into()));
+    }
+
+    #[test]
+    fn test_overflow_drops_expired_entries_first() {
+        let cache = cache();
+        let now = Instant::now();
+        cache.store("expired".to_string(), "{}".into(), now);
+        let later = now + Duration::from_secs(60);
+        for i in 1..MAX_ENTRIES {
+            cache.store(format!("page-{}", i), "{}".into(), later);
+        }
+
+        cache.store("new".to_string(), "{}".into(), later);
+        assert_eq!(cache.lookup("expired", later), Lookup::Miss);
+        assert_eq!(cache.lookup("page-1", later), Lookup::Fresh("{}".into()));
+        assert_eq!(cache.lookup("new", later), Lookup::Fresh("{}".into()));
+    }
+}
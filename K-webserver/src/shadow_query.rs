@@ -0,0 +1,180 @@
+use axum_prometheus::metrics::counter;
+use sqlx::postgres::PgRow;
+use sqlx::{Column, PgPool, Row, ValueRef};
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::named_query::NamedQuery;
+use crate::query_builder::QueryParams;
+use crate::request_id;
+
+/// Candidate rewrites of the costliest queries, run next to the current query for a sample of
+/// the calls (--shadow-query-percent) so their rows and durations can be compared before the
+/// rewrite replaces the current query. Candidates run in the background: the response always
+/// comes from the current query and is not delayed by the candidate
+pub struct ShadowQueries {
+    percent: u8,
+}
+
+impl ShadowQueries {
+    pub fn new(percent: u8) -> Self {
+        Self { percent }
+    }
+
+    /// Whether this call also runs the candidate rewrite
+    pub fn sample(&self) -> bool {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        // Every RandomState is seeded differently, so the draw is random
+        self.percent > 0
+            && RandomState::new().hash_one(NEXT.fetch_add(1, Ordering::Relaxed)) % 100
+                < self.percent as u64
+    }
+
+    /// Run `candidate` with `params` on `pool` and compare it with the `rows` the current query
+    /// of `method` returned after `elapsed`: outcomes and durations are counted in
+    /// k_shadow_queries_total and k_shadow_query_ms_total, mismatches and failures logged
+    pub fn spawn(
+        &self,
+        method: &'static str,
+        pool: &PgPool,
+        candidate: NamedQuery,
+        params: QueryParams,
+        rows: &[PgRow],
+        elapsed: Duration,
+    ) {
+        let current = ResultFingerprint::of(rows);
+        let pool = pool.clone();
+        // Background tasks do not inherit the request, its ID is passed along for the logs
+        let request_id = request_id::current().unwrap_or_default();
+
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let result = params.bind(candidate.query()).fetch_all(&pool).await;
+            let candidate_elapsed = started.elapsed();
+
+            let outcome = match result {
+                Ok(candidate_rows) => {
+                    let fingerprint = ResultFingerprint::of(&candidate_rows);
+                    if fingerprint == current {
+                        info!(
+                            "Shadow query {} matched ({} rows): {}ms current, {}ms candidate",
+                            method,
+                            current.rows,
+                            elapsed.as_millis(),
+                            candidate_elapsed.as_millis()
+                        );
+                        "match"
+                    } else {
+                        warn!(
+                            "Shadow query {} mismatch (request {}): {} rows current, {} rows candidate{} ({}ms current, {}ms candidate)",
+                            method,
+                            request_id,
+                            current.rows,
+                            fingerprint.rows,
+                            if fingerprint.rows == current.rows {
+                                " with different values or order"
+                            } else {
+                                ""
+                            },
+                            elapsed.as_millis(),
+                            candidate_elapsed.as_millis()
+                        );
+                        "mismatch"
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Shadow query {} failed (request {}): {}",
+                        method, request_id, e
+                    );
+                    "error"
+                }
+            };
+
+            counter!("k_shadow_queries_total", "method" => method, "outcome" => outcome)
+                .increment(1);
+            counter!("k_shadow_query_ms_total", "method" => method, "query" => "current")
+                .increment(elapsed.as_millis() as u64);
+            counter!("k_shadow_query_ms_total", "method" => method, "query" => "candidate")
+                .increment(candidate_elapsed.as_millis() as u64);
+        });
+    }
+}
+
+/// Rows of a query result, in order, reduced to their count and a hash of their column names
+/// and raw values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ResultFingerprint {
+    rows: usize,
+    hash: u64,
+}
+
+impl ResultFingerprint {
+    fn of(rows: &[PgRow]) -> Self {
+        Self::from_rows(rows.iter().map(|row| {
+            row.columns()
+                .iter()
+                .map(|column| {
+                    let value = row
+                        .try_get_raw(column.ordinal())
+                        .ok()
+                        .filter(|value| !value.is_null())
+                        .and_then(|value| value.as_bytes().ok());
+                    (column.name(), value)
+                })
+                .collect::<Vec<_>>()
+        }))
+    }
+
+    fn from_rows<'a>(rows: impl Iterator<Item = Vec<(&'a str, Option<&'a [u8]>)>>) -> Self {
+        // Fixed keys: fingerprints of both queries must hash alike
+        let mut hasher = DefaultHasher::new();
+        let mut count = 0;
+        for row in rows {
+            row.hash(&mut hasher);
+            count += 1;
+        }
+        Self {
+            rows: count,
+            hash: hasher.finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_bounds() {
+        assert!((0..1000).all(|_| !ShadowQueries::new(0).sample()));
+        assert!((0..1000).all(|_| ShadowQueries::new(100).sample()));
+    }
+
+    // Column names and raw values of a row
+    type TestRow = &'static [(&'static str, Option<&'static [u8]>)];
+
+    fn fingerprint(rows: &[TestRow]) -> ResultFingerprint {
+        ResultFingerprint::from_rows(rows.iter().map(|row| row.to_vec()))
+    }
+
+    #[test]
+    fn test_fingerprint_compares_values_and_order() {
+        let a: TestRow = &[("id", Some(b"1")), ("nickname", None)];
+        let b: TestRow = &[("id", Some(b"2")), ("nickname", Some(b""))];
+
+        assert_eq!(fingerprint(&[a, b]), fingerprint(&[a, b]));
+        assert_eq!(fingerprint(&[a, b]).rows, 2);
+        assert_ne!(fingerprint(&[a, b]), fingerprint(&[b, a]));
+        assert_ne!(fingerprint(&[a]), fingerprint(&[a, b]));
+
+        // NULL and empty values differ, and so do column names
+        let empty: TestRow = &[("id", Some(b"1")), ("nickname", Some(b""))];
+        let renamed: TestRow = &[("id", Some(b"1")), ("nick", None)];
+        assert_ne!(fingerprint(&[a]), fingerprint(&[empty]));
+        assert_ne!(fingerprint(&[a]), fingerprint(&[renamed]));
+    }
+}
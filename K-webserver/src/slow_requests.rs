@@ -0,0 +1,258 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::metadata::LevelFilter;
+use tracing::subscriber::Interest;
+use tracing::{Event, Subscriber, warn};
+use tracing_subscriber::filter::dynamic_filter_fn;
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target of the event sqlx emits (at DEBUG) when a statement completes
+const STATEMENT_TARGET: &str = "sqlx::query";
+
+/// Statements kept per request, later ones are only counted
+const MAX_TRACED_STATEMENTS: usize = 200;
+
+/// A statement run while serving a traced request
+struct TracedStatement {
+    // Start, from the start of the request
+    offset: Duration,
+    elapsed: Duration,
+    sql: String,
+    rows_returned: u64,
+    rows_affected: u64,
+}
+
+struct RequestTrace {
+    started: Instant,
+    statements: Vec<TracedStatement>,
+    // Statements past MAX_TRACED_STATEMENTS
+    untraced: usize,
+}
+
+tokio::task_local! {
+    static TRACE: Arc<Mutex<RequestTrace>>;
+}
+
+/// Whether the current task serves a traced request (statements are only captured then)
+fn is_tracing() -> bool {
+    TRACE.try_with(|_| ()).is_ok()
+}
+
+/// Fields of a sqlx statement event
+#[derive(Default)]
+struct StatementVisitor {
+    summary: String,
+    statement: String,
+    rows_returned: u64,
+    rows_affected: u64,
+    elapsed_secs: f64,
+}
+
+impl Visit for StatementVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = value.to_string(),
+            "db.statement" => self.statement = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "rows_returned" => self.rows_returned = value,
+            "rows_affected" => self.rows_affected = value,
+            _ => {}
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Records the statements of traced requests into their trace
+struct StatementCapture;
+
+impl<S: Subscriber> Layer<S> for StatementCapture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = StatementVisitor::default();
+        event.record(&mut visitor);
+        let elapsed = Duration::from_secs_f64(visitor.elapsed_secs.max(0.0));
+        // sqlx only sends the statement when it is longer than its summary (first 4 words)
+        let sql = if visitor.statement.trim().is_empty() {
+            visitor.summary
+        } else {
+            visitor.statement
+        };
+
+        let _ = TRACE.try_with(|trace| {
+            let mut trace = trace.lock().unwrap();
+            if trace.statements.len() >= MAX_TRACED_STATEMENTS {
+                trace.untraced += 1;
+                return;
+            }
+            let offset = trace.started.elapsed().saturating_sub(elapsed);
+            trace.statements.push(TracedStatement {
+                offset,
+                elapsed,
+                sql: sql.split_whitespace().collect::<Vec<_>>().join(" "),
+                rows_returned: visitor.rows_returned,
+                rows_affected: visitor.rows_affected,
+            });
+        });
+    }
+}
+
+/// Layer capturing the statements of the requests traced by trace_slow_requests. Its filter
+/// only enables the sqlx statement events inside them, so it costs nothing without
+/// --slow-request-trace-ms
+pub fn statement_capture_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let filter =
+        dynamic_filter_fn(|metadata, _ctx| metadata.target() == STATEMENT_TARGET && is_tracing())
+            .with_callsite_filter(|metadata| {
+                if metadata.target() == STATEMENT_TARGET {
+                    Interest::sometimes()
+                } else {
+                    Interest::never()
+                }
+            })
+            .with_max_level_hint(LevelFilter::DEBUG);
+    StatementCapture.with_filter(filter)
+}
+
+/// Number of bind parameters ($1, $2, ...) of `sql`, whose values are never captured
+fn bind_count(sql: &str) -> usize {
+    sql.split('$')
+        .skip(1)
+        .filter_map(|rest| {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            rest[..digits].parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl RequestTrace {
+    /// Log lines of a request that took `elapsed`: its timing split between the database and
+    /// the rest, then every statement with its timing, row counts and SQL
+    fn dump(&self, method: &Method, path: &str, status: u16, elapsed: Duration) -> String {
+        let database: Duration = self.statements.iter().map(|s| s.elapsed).sum();
+        let mut dump = format!(
+            "Slow request {} {} ({}): {:.1} ms, {} statements taking {:.1} ms, {:.1} ms outside the database",
+            method,
+            path,
+            status,
+            ms(elapsed),
+            self.statements.len() + self.untraced,
+            ms(database),
+            ms(elapsed.saturating_sub(database)),
+        );
+        for (i, statement) in self.statements.iter().enumerate() {
+            let _ = write!(
+                dump,
+                "\n  #{} at +{:.1} ms: {:.1} ms, {} rows returned, {} rows affected, {} binds (redacted): {}",
+                i + 1,
+                ms(statement.offset),
+                ms(statement.elapsed),
+                statement.rows_returned,
+                statement.rows_affected,
+                bind_count(&statement.sql),
+                statement.sql,
+            );
+        }
+        if self.untraced > 0 {
+            let _ = write!(dump, "\n  ... {} more statements not kept", self.untraced);
+        }
+        dump
+    }
+}
+
+/// --slow-request-trace-ms: record the statements run while serving the request and log them
+/// when it takes `threshold` or longer. The query string is left out, as are bind values,
+/// since both carry requester keys
+pub async fn trace_slow_requests(
+    State(threshold): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let trace = Arc::new(Mutex::new(RequestTrace {
+        started: Instant::now(),
+        statements: Vec::new(),
+        untraced: 0,
+    }));
+
+    let response = TRACE.scope(trace.clone(), next.run(request)).await;
+
+    let trace = trace.lock().unwrap();
+    let elapsed = trace.started.elapsed();
+    if elapsed >= threshold {
+        warn!(
+            "{}",
+            trace.dump(&method, &path, response.status().as_u16(), elapsed)
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_count() {
+        assert_eq!(bind_count("SELECT 1"), 0);
+        assert_eq!(
+            bind_count("SELECT * FROM k_contents WHERE id = ANY($1) LIMIT $12 OFFSET $2"),
+            12
+        );
+        assert_eq!(bind_count("SELECT '$' || $3"), 3);
+    }
+
+    #[test]
+    fn test_dump_lists_statements() {
+        let trace = RequestTrace {
+            started: Instant::now(),
+            statements: vec![TracedStatement {
+                offset: Duration::from_millis(2),
+                elapsed: Duration::from_millis(1200),
+                sql: "/* k:get_all_posts */ SELECT id FROM k_contents LIMIT $2".to_string(),
+                rows_returned: 20,
+                rows_affected: 0,
+            }],
+            untraced: 3,
+        };
+        let dump = trace.dump(
+            &Method::GET,
+            "/v1/get-posts-watching",
+            200,
+            Duration::from_millis(1500),
+        );
+        assert_eq!(
+            dump,
+            "Slow request GET /v1/get-posts-watching (200): 1500.0 ms, 4 statements taking 1200.0 ms, 300.0 ms outside the database\n  \
+             #1 at +2.0 ms: 1200.0 ms, 20 rows returned, 0 rows affected, 2 binds (redacted): /* k:get_all_posts */ SELECT id FROM k_contents LIMIT $2\n  \
+             ... 3 more statements not kept"
+        );
+    }
+}
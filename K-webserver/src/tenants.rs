@@ -0,0 +1,163 @@
+use crate::live_feed::LIVE_EVENTS_CHANNEL;
+
+/// Path prefix selecting a tenant, followed by its id: /t/{id}/get-posts
+pub const TENANT_PATH_PREFIX: &str = "/t/";
+
+/// A K community indexed by K-transaction-processor into the schema tenant_<id> (--tenants)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantConfig {
+    pub id: String,
+    // Requests with this Host header are served from the tenant (None = path prefix only)
+    pub host: Option<String>,
+}
+
+impl TenantConfig {
+    /// PostgreSQL schema holding the K tables of the tenant
+    pub fn schema(&self) -> String {
+        format!("tenant_{}", self.id)
+    }
+
+    /// Channel of the live events of the tenant
+    pub fn live_events_channel(&self) -> String {
+        format!("{}_{}", LIVE_EVENTS_CHANNEL, self.id)
+    }
+
+    pub fn path_prefix(&self) -> String {
+        format!("{}{}", TENANT_PATH_PREFIX, self.id)
+    }
+}
+
+/// Parse --tenants: `id` or `id=hostname` entries, comma separated
+pub fn parse_tenants(value: &str) -> Result<Vec<TenantConfig>, String> {
+    let mut tenants: Vec<TenantConfig> = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (id, host) = match entry.split_once('=') {
+            Some((id, host)) => (id.trim(), Some(host.trim().to_ascii_lowercase())),
+            None => (entry, None),
+        };
+        // Same ids as accepted by the processor's --tenant
+        if id.is_empty()
+            || id.len() > 32
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!(
+                "Invalid tenant id '{}': 1 to 32 lowercase letters, digits or '_'",
+                id
+            ));
+        }
+        if host.as_deref() == Some("") {
+            return Err(format!("Tenant '{}' has an empty hostname", id));
+        }
+        if tenants
+            .iter()
+            .any(|other| other.id == id || (host.is_some() && other.host == host))
+        {
+            return Err(format!("Tenant '{}' or its hostname is defined twice", id));
+        }
+        tenants.push(TenantConfig {
+            id: id.to_string(),
+            host,
+        });
+    }
+    Ok(tenants)
+}
+
+/// Tenant serving a request (index in `tenants`) and the path and query to route within it:
+/// the tenant of the Host header, else the one of a /t/{id} path prefix, which is stripped.
+/// None = the network's own community
+pub fn select_tenant(
+    tenants: &[TenantConfig],
+    host: Option<&str>,
+    path_and_query: &str,
+) -> Option<(usize, String)> {
+    // Host header without port
+    let host = host.map(|host| host.rsplit_once(':').map_or(host, |(name, _)| name));
+    if let Some(host) = host
+        && let Some(index) = tenants.iter().position(|tenant| {
+            tenant
+                .host
+                .as_deref()
+                .is_some_and(|tenant_host| tenant_host.eq_ignore_ascii_case(host))
+        })
+    {
+        return Some((index, path_and_query.to_string()));
+    }
+
+    let rest = path_and_query.strip_prefix(TENANT_PATH_PREFIX)?;
+    let id_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let index = tenants
+        .iter()
+        .position(|tenant| tenant.id == rest[..id_end])?;
+    let path_and_query = match &rest[id_end..] {
+        rest if rest.starts_with('/') => rest.to_string(),
+        rest => format!("/{}", rest),
+    };
+    Some((index, path_and_query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tenants() {
+        let tenants = parse_tenants("guild=Guild.Example.org, club").unwrap();
+        assert_eq!(
+            tenants,
+            vec![
+                TenantConfig {
+                    id: "guild".to_string(),
+                    host: Some("guild.example.org".to_string()),
+                },
+                TenantConfig {
+                    id: "club".to_string(),
+                    host: None,
+                },
+            ]
+        );
+        assert_eq!(tenants[0].schema(), "tenant_guild");
+        assert_eq!(tenants[0].live_events_channel(), "k_live_events_guild");
+        assert!(parse_tenants("").unwrap().is_empty());
+
+        assert!(parse_tenants("Guild").is_err());
+        assert!(parse_tenants("guild=").is_err());
+        assert!(parse_tenants("guild,guild").is_err());
+        assert!(parse_tenants("guild=a.org,club=a.org").is_err());
+    }
+
+    #[test]
+    fn test_select_tenant() {
+        let tenants = parse_tenants("guild=guild.example.org,club").unwrap();
+
+        assert_eq!(
+            select_tenant(
+                &tenants,
+                Some("GUILD.example.org:443"),
+                "/get-posts?limit=5"
+            ),
+            Some((0, "/get-posts?limit=5".to_string()))
+        );
+        assert_eq!(
+            select_tenant(&tenants, Some("k.example.org"), "/t/club/get-posts?limit=5"),
+            Some((1, "/get-posts?limit=5".to_string()))
+        );
+        assert_eq!(
+            select_tenant(&tenants, None, "/t/club?x=1"),
+            Some((1, "/?x=1".to_string()))
+        );
+        assert_eq!(
+            select_tenant(&tenants, None, "/t/club"),
+            Some((1, "/".to_string()))
+        );
+
+        // Unknown tenants and look-alike paths stay with the network's own community
+        assert_eq!(select_tenant(&tenants, None, "/t/other/get-posts"), None);
+        assert_eq!(select_tenant(&tenants, None, "/t/clubs/get-posts"), None);
+        assert_eq!(
+            select_tenant(&tenants, Some("k.example.org"), "/get-posts"),
+            None
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Share of the entries evicted at once (1 / divisor) when a full cache has no expired entry,
+/// so a burst of new keys does not scan the whole map on every insert
+const LRU_EVICTION_DIVISOR: usize = 10;
+
+/// Bounded in-process map whose entries expire `ttl` after they were last written
+/// A full cache drops its expired entries first, then the least recently used ones; `prune`
+/// drops expired entries ahead of time so keys seen once do not linger until the next overflow
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<K, Slot<V>>>,
+}
+
+struct Slot<V> {
+    value: V,
+    written_at: Instant,
+    used_at: Instant,
+}
+
+impl<K: Eq + Hash + Clone, V> TtlCache<K, V> {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries: max_entries.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_live(&self, slot: &Slot<V>, now: Instant) -> bool {
+        now.saturating_duration_since(slot.written_at) < self.ttl
+    }
+
+    /// Run `f` on the live entry of `key` (None if missing or expired), marking it as used
+    pub fn with<Q, R>(&self, key: &Q, now: Instant, f: impl FnOnce(&mut V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut entries = self.entries.lock().unwrap();
+        let slot = entries
+            .get_mut(key)
+            .filter(|slot| self.is_live(slot, now))?;
+        slot.used_at = now;
+        Some(f(&mut slot.value))
+    }
+
+    /// Clone of the live value of `key`
+    pub fn get<Q>(&self, key: &Q, now: Instant) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        self.with(key, now, |value| value.clone())
+    }
+
+    /// Insert or replace the entry of `key`
+    pub fn insert(&self, key: K, value: V, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        self.make_room(&mut entries, &key, now);
+        entries.insert(
+            key,
+            Slot {
+                value,
+                written_at: now,
+                used_at: now,
+            },
+        );
+    }
+
+    /// Run `f` on the entry of `key`, created with `init` when missing or expired, and restart
+    /// its TTL
+    pub fn upsert<R>(
+        &self,
+        key: K,
+        now: Instant,
+        init: impl FnOnce() -> V,
+        f: impl FnOnce(&mut V) -> R,
+    ) -> R {
+        let mut entries = self.entries.lock().unwrap();
+        if entries
+            .get(&key)
+            .is_some_and(|slot| !self.is_live(slot, now))
+        {
+            entries.remove(&key);
+        }
+        self.make_room(&mut entries, &key, now);
+
+        let slot = entries.entry(key).or_insert_with(|| Slot {
+            value: init(),
+            written_at: now,
+            used_at: now,
+        });
+        slot.written_at = now;
+        slot.used_at = now;
+        f(&mut slot.value)
+    }
+
+    /// Drop the expired entries; returns the number of entries left
+    pub fn prune(&self, now: Instant) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, slot| self.is_live(slot, now));
+        entries.len()
+    }
+
+    /// Free a slot for a new `key` in a full cache: expired entries first, then the least
+    /// recently used tenth of the entries
+    fn make_room(&self, entries: &mut HashMap<K, Slot<V>>, key: &K, now: Instant) {
+        if entries.len() < self.max_entries || entries.contains_key(key) {
+            return;
+        }
+
+        entries.retain(|_, slot| self.is_live(slot, now));
+        if entries.len() < self.max_entries {
+            return;
+        }
+
+        let evicted = (entries.len() + 1 - self.max_entries
+            + self.max_entries / LRU_EVICTION_DIVISOR)
+            .min(entries.len());
+        let mut by_use: Vec<(Instant, K)> = entries
+            .iter()
+            .map(|(key, slot)| (slot.used_at, key.clone()))
+            .collect();
+        by_use.select_nth_unstable_by_key(evicted - 1, |(used_at, _)| *used_at);
+        for (_, key) in &by_use[..evicted] {
+            entries.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_entries: usize) -> TtlCache<String, u32> {
+        TtlCache::new(Duration::from_secs(60), max_entries)
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let cache = cache(10);
+        let now = Instant::now();
+        cache.insert("a".to_string(), 1, now);
+
+        assert_eq!(cache.get("a", now + Duration::from_secs(59)), Some(1));
+        assert_eq!(cache.get("a", now + Duration::from_secs(60)), None);
+        assert_eq!(cache.prune(now + Duration::from_secs(59)), 1);
+        assert_eq!(cache.prune(now + Duration::from_secs(60)), 0);
+    }
+
+    #[test]
+    fn test_upsert_restarts_ttl() {
+        let cache = cache(10);
+        let now = Instant::now();
+        let count = |at: Instant| {
+            cache.upsert(
+                "a".to_string(),
+                at,
+                || 0,
+                |count| {
+                    *count += 1;
+                    *count
+                },
+            )
+        };
+
+        assert_eq!(count(now), 1);
+        assert_eq!(count(now + Duration::from_secs(50)), 2);
+        assert_eq!(count(now + Duration::from_secs(100)), 3);
+        // Expired entries start over
+        assert_eq!(count(now + Duration::from_secs(200)), 1);
+    }
+
+    #[test]
+    fn test_full_cache_drops_expired_entries_first() {
+        let cache = cache(3);
+        let now = Instant::now();
+        cache.insert("expired".to_string(), 0, now);
+        let later = now + Duration::from_secs(90);
+        cache.insert("b".to_string(), 1, later - Duration::from_secs(10));
+        cache.insert("c".to_string(), 2, later);
+
+        cache.insert("d".to_string(), 3, later);
+        assert_eq!(cache.get("b", later), Some(1));
+        assert_eq!(cache.prune(later), 3);
+    }
+
+    #[test]
+    fn test_full_cache_evicts_least_recently_used() {
+        let cache = cache(3);
+        let now = Instant::now();
+        for (index, key) in ["a", "b", "c"].iter().enumerate() {
+            cache.insert(key.to_string(), 0, now + Duration::from_secs(index as u64));
+        }
+        // Reading "a" makes "b" the least recently used entry
+        let later = now + Duration::from_secs(10);
+        assert_eq!(cache.get("a", later), Some(0));
+
+        cache.insert("d".to_string(), 0, later);
+        assert_eq!(cache.get("b", later), None);
+        assert_eq!(cache.prune(later), 3);
+    }
+}
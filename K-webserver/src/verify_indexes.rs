@@ -0,0 +1,434 @@
+use crate::config::AppConfig;
+use crate::database_postgres_impl::PostgresDbManager;
+use crate::database_trait::{DatabaseInterface, DatabaseResult, PostRanking, QueryOptions};
+use k_common::doctor::{Report, Status};
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+
+/// DatabaseInterface methods behind the public endpoints, each called with a first page and,
+/// when paginated, with a `before` cursor (writes and admin reports are not checked)
+const CANONICAL_METHODS: &[&str] = &[
+    "get_all_posts",
+    "get_ranked_posts",
+    "get_all_posts_light",
+    "get_posts_meta",
+    "get_content_following",
+    "get_posts_following",
+    "get_posts_by_user",
+    "get_replies_by_post_id",
+    "get_replies_by_user",
+    "get_nested_replies",
+    "get_thread_replies",
+    "get_contents_mentioning_user",
+    "get_notifications",
+    "get_notification_count",
+    "get_content_by_id",
+    "get_contents_by_ids",
+    "get_content_mentions",
+    "get_hashtag_content",
+    "search_posts",
+    "get_trending_hashtags",
+    "get_top_tipped_posts",
+    "get_featured_posts",
+    "get_all_users",
+    "get_most_active_users",
+    "search_users",
+    "get_user_details",
+    "get_user_stats",
+    "get_users_by_keys",
+    "get_profile_image",
+    "get_blocked_users_by_requester",
+    "get_followed_users_by_requester",
+    "get_users_following",
+    "get_users_followers",
+    "get_groups",
+    "get_group_posts",
+    "get_events",
+];
+
+/// Window of the time-ranged methods, ending at the newest content
+const WINDOW_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// Values the methods are called with: the newest content and its author when the database
+/// has contents. Plans are generic, so the values only decide which queries a method runs
+struct Samples {
+    pubkey: String,
+    content_id: String,
+    block_time: u64,
+    cursor: String,
+}
+
+impl Samples {
+    async fn read(pool: &PgPool) -> Self {
+        let newest = sqlx::query(
+            "SELECT id, transaction_id, sender_pubkey, block_time FROM k_contents ORDER BY block_time DESC, id DESC LIMIT 1",
+        )
+        .fetch_optional(pool)
+        .await;
+
+        match newest {
+            Ok(Some(row)) => {
+                let block_time = row.get::<i64, _>("block_time") as u64;
+                Self {
+                    pubkey: hex::encode(row.get::<Vec<u8>, _>("sender_pubkey")),
+                    content_id: hex::encode(row.get::<Vec<u8>, _>("transaction_id")),
+                    block_time,
+                    cursor: format!("{}_{}", block_time, row.get::<i64, _>("id")),
+                }
+            }
+            _ => Self {
+                pubkey: format!("02{}", "11".repeat(32)),
+                content_id: "22".repeat(32),
+                block_time: 0,
+                cursor: format!("{}_{}", i64::MAX, i64::MAX),
+            },
+        }
+    }
+}
+
+/// Call `method` with the sample values, as a first page and from the sample cursor
+async fn call(db: &PostgresDbManager, method: &str, samples: &Samples) -> DatabaseResult<()> {
+    let s = samples;
+    let ids = std::slice::from_ref(&s.content_id);
+    let (from, to) = (s.block_time.saturating_sub(WINDOW_MILLIS), s.block_time);
+
+    for before in [None, Some(s.cursor.clone())] {
+        let page = QueryOptions {
+            limit: Some(20),
+            before,
+            after: None,
+            sort_descending: true,
+        };
+        let paginated = page.before.is_some();
+        match method {
+            "get_all_posts" => {
+                db.get_all_posts(&s.pubkey, page).await?;
+            }
+            "get_all_posts_light" => {
+                db.get_all_posts_light(&s.pubkey, page).await?;
+            }
+            "get_content_following" => {
+                db.get_content_following(&s.pubkey, page).await?;
+            }
+            "get_posts_following" => {
+                db.get_posts_following(&s.pubkey, page).await?;
+            }
+            "get_posts_by_user" => {
+                db.get_posts_by_user(&s.pubkey, &s.pubkey, page).await?;
+            }
+            "get_replies_by_post_id" => {
+                db.get_replies_by_post_id(&s.content_id, &s.pubkey, page)
+                    .await?;
+            }
+            "get_replies_by_user" => {
+                db.get_replies_by_user(&s.pubkey, &s.pubkey, page).await?;
+            }
+            "get_contents_mentioning_user" => {
+                db.get_contents_mentioning_user(&s.pubkey, &s.pubkey, page)
+                    .await?;
+            }
+            "get_notifications" => {
+                db.get_notifications(&s.pubkey, page).await?;
+            }
+            "get_hashtag_content" => {
+                db.get_hashtag_content("kaspa", &s.pubkey, page).await?;
+            }
+            "search_posts" => {
+                db.search_posts("kaspa", &s.pubkey, page).await?;
+            }
+            "get_all_users" => {
+                db.get_all_users(&s.pubkey, page).await?;
+            }
+            "get_most_active_users" => {
+                db.get_most_active_users(&s.pubkey, page, from, to).await?;
+            }
+            "search_users" => {
+                db.search_users(&s.pubkey, page, None, Some("kaspa".to_string()))
+                    .await?;
+            }
+            "get_blocked_users_by_requester" => {
+                db.get_blocked_users_by_requester(&s.pubkey, page).await?;
+            }
+            "get_followed_users_by_requester" => {
+                db.get_followed_users_by_requester(&s.pubkey, page).await?;
+            }
+            "get_users_following" => {
+                db.get_users_following(&s.pubkey, &s.pubkey, page).await?;
+            }
+            "get_users_followers" => {
+                db.get_users_followers(&s.pubkey, &s.pubkey, page).await?;
+            }
+            "get_groups" => {
+                db.get_groups(&s.pubkey, None, page).await?;
+            }
+            "get_group_posts" => {
+                db.get_group_posts(&s.content_id, &s.pubkey, page).await?;
+            }
+            "get_events" => {
+                db.get_events(&s.pubkey, None, true, to, page).await?;
+            }
+            // Not paginated: called once
+            _ if paginated => {}
+            "get_ranked_posts" => {
+                db.get_ranked_posts(&s.pubkey, PostRanking::Hot, from, to, 20)
+                    .await?;
+            }
+            "get_posts_meta" => {
+                db.get_posts_meta(&s.pubkey, ids).await?;
+            }
+            "get_nested_replies" => {
+                db.get_nested_replies(ids, &s.pubkey, 3).await?;
+            }
+            "get_thread_replies" => {
+                db.get_thread_replies(&s.content_id, &s.pubkey, 2000)
+                    .await?;
+            }
+            "get_notification_count" => {
+                db.get_notification_count(&s.pubkey, Some(s.cursor.clone()))
+                    .await?;
+            }
+            "get_content_by_id" => {
+                db.get_content_by_id(&s.content_id, &s.pubkey).await?;
+            }
+            "get_contents_by_ids" => {
+                db.get_contents_by_ids(ids, &s.pubkey).await?;
+            }
+            "get_content_mentions" => {
+                db.get_content_mentions(&s.content_id).await?;
+            }
+            "get_trending_hashtags" => {
+                db.get_trending_hashtags(from, to, 20).await?;
+            }
+            "get_top_tipped_posts" => {
+                db.get_top_tipped_posts(&s.pubkey, from, to, 20).await?;
+            }
+            "get_featured_posts" => {
+                db.get_featured_posts(&s.pubkey, 20).await?;
+            }
+            "get_user_details" => {
+                db.get_user_details(&s.pubkey, &s.pubkey).await?;
+            }
+            "get_user_stats" => {
+                db.get_user_stats(&s.pubkey).await?;
+            }
+            "get_users_by_keys" => {
+                db.get_users_by_keys(std::slice::from_ref(&s.pubkey), &s.pubkey)
+                    .await?;
+            }
+            "get_profile_image" => {
+                db.get_profile_image(&s.pubkey).await?;
+            }
+            _ => unreachable!("{} is not a canonical method", method),
+        }
+    }
+    Ok(())
+}
+
+/// Run every canonical DatabaseInterface method against the database and EXPLAIN the queries
+/// it ran with a generic plan (as used for any parameter values), failing when a query reads
+/// a table of at least `min_rows` rows with a sequential scan, i.e. an index it relies on is
+/// missing or no longer matches the query
+pub async fn run(config: &AppConfig, min_rows: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut report = Report::new();
+    println!("K-webserver v{} verify-indexes", env!("CARGO_PKG_VERSION"));
+
+    let database = &config.database;
+    // A single connection: the statements of every method are prepared in its session, where
+    // their text and parameter types are read back
+    let db = match PostgresDbManager::new(
+        &config.connection_string(),
+        &database.hosts,
+        database.target_session_attrs,
+        1,
+        Some(0),
+        database.statement_timeout_ms,
+        false,
+    )
+    .await
+    {
+        Ok(db) => db,
+        Err(e) => {
+            report.check(
+                Status::Fail,
+                "Database",
+                format!(
+                    "{}:{}/{} not reachable: {}",
+                    database.host, database.port, database.database, e
+                ),
+            );
+            return report.finish();
+        }
+    };
+
+    // Queries are built for the features of the published schema
+    match db.get_schema_meta().await {
+        Ok(Some(meta)) => report.check(
+            Status::Ok,
+            "Schema version",
+            format!("v{}", meta.schema_version),
+        ),
+        Ok(None) => report.check(
+            Status::Warn,
+            "Schema version",
+            "k_meta is empty, queries are checked for the oldest schema",
+        ),
+        Err(e) => report.check(Status::Fail, "Schema version", e.to_string()),
+    }
+
+    let samples = Samples::read(&db.pool).await;
+    // Statements of the schema read above are not those of a canonical method
+    let mut explained = HashSet::new();
+    if let Err(e) = explain_new_statements(&db.pool, &mut explained).await {
+        report.check(Status::Fail, "EXPLAIN", e.to_string());
+        return report.finish();
+    }
+    for method in CANONICAL_METHODS {
+        if let Err(e) = call(&db, method, &samples).await {
+            report.check(Status::Warn, method, format!("not checked: {}", e));
+            continue;
+        }
+
+        match explain_new_statements(&db.pool, &mut explained).await {
+            Ok(scans) => {
+                let mut large: Vec<String> = Vec::new();
+                for scan in scans.iter().filter(|scan| scan.rows >= min_rows) {
+                    let scan = format!("{} ({} rows) in k:{}", scan.table, scan.rows, scan.query);
+                    if !large.contains(&scan) {
+                        large.push(scan);
+                    }
+                }
+                if large.is_empty() {
+                    report.check(Status::Ok, method, "no sequential scan of a large table");
+                } else {
+                    report.check(
+                        Status::Fail,
+                        method,
+                        format!("sequential scan of {}", large.join(", ")),
+                    );
+                }
+            }
+            Err(e) => report.check(Status::Fail, method, e.to_string()),
+        }
+    }
+
+    db.pool.close().await;
+    report.finish()
+}
+
+/// Sequential scan in the plan of a query
+struct SeqScan {
+    /// Method named by the `/* k:<method> */` comment of the query
+    query: String,
+    table: String,
+    /// Estimated rows of the table
+    rows: u64,
+}
+
+/// Sequential scans in the generic plans of the K statements prepared in the session of the
+/// pool's connection and not yet in `explained`
+async fn explain_new_statements(
+    pool: &PgPool,
+    explained: &mut HashSet<String>,
+) -> Result<Vec<SeqScan>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let statements = sqlx::query(
+        r#"
+        SELECT name, statement, cardinality(parameter_types) AS parameters
+        FROM pg_prepared_statements
+        WHERE statement LIKE '/* k:%'
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    sqlx::query("SET plan_cache_mode = force_generic_plan")
+        .execute(&mut *conn)
+        .await?;
+    let mut scans = Vec::new();
+    for statement in statements {
+        let text: String = statement.get("statement");
+        if !explained.insert(text.clone()) {
+            continue;
+        }
+        let name: String = statement.get("name");
+        let parameters = statement.get::<Option<i32>, _>("parameters").unwrap_or(0) as usize;
+
+        let arguments = vec!["NULL"; parameters].join(", ");
+        let explain = if parameters == 0 {
+            format!("EXPLAIN EXECUTE {}", name)
+        } else {
+            format!("EXPLAIN EXECUTE {}({})", name, arguments)
+        };
+        let plan: Vec<String> = sqlx::query_scalar(&explain).fetch_all(&mut *conn).await?;
+
+        for table in seq_scanned_tables(&plan) {
+            let rows: i64 = sqlx::query_scalar(
+                r#"
+                SELECT GREATEST(c.reltuples::bigint, COALESCE(pg_stat_get_live_tuples(c.oid), 0))
+                FROM pg_class c
+                WHERE c.oid = to_regclass($1)
+                "#,
+            )
+            .bind(table)
+            .fetch_optional(&mut *conn)
+            .await?
+            .unwrap_or(0);
+            scans.push(SeqScan {
+                query: query_name(&text).to_string(),
+                table: table.to_string(),
+                rows: rows.max(0) as u64,
+            });
+        }
+    }
+    sqlx::query("RESET plan_cache_mode")
+        .execute(&mut *conn)
+        .await?;
+    Ok(scans)
+}
+
+/// Tables read by `Seq Scan` (or `Parallel Seq Scan`) nodes of a text EXPLAIN output
+fn seq_scanned_tables(plan: &[String]) -> Vec<&str> {
+    plan.iter()
+        .filter_map(|line| {
+            let rest = &line[line.find("Seq Scan on ")? + "Seq Scan on ".len()..];
+            rest.split_whitespace().next()
+        })
+        .collect()
+}
+
+/// Method of a `/* k:<method> */` query
+fn query_name(statement: &str) -> &str {
+    statement
+        .strip_prefix("/* k:")
+        .and_then(|rest| rest.split(" */").next())
+        .unwrap_or(statement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seq_scanned_tables() {
+        let plan: Vec<String> = [
+            "Limit  (cost=0.42..12.31 rows=20 width=310)",
+            "  ->  Nested Loop Anti Join  (cost=0.42..1843.10 rows=3100 width=310)",
+            "        ->  Index Scan using idx_k_contents_feed_optimized on k_contents c  (cost=0.42..900.00 rows=3200 width=310)",
+            "        ->  Seq Scan on k_blocks b  (cost=0.00..1.10 rows=1 width=33)",
+            "              ->  Parallel Seq Scan on k_votes  (cost=0.00..8000.00 rows=400000 width=40)",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        assert_eq!(seq_scanned_tables(&plan), vec!["k_blocks", "k_votes"]);
+    }
+
+    #[test]
+    fn test_query_name() {
+        assert_eq!(
+            query_name("/* k:get_all_posts */ SELECT 1"),
+            "get_all_posts"
+        );
+        assert_eq!(query_name("SELECT 1"), "SELECT 1");
+    }
+}
@@ -0,0 +1,93 @@
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+
+/// HyperLogLog precision: 2^10 one-byte registers per post (~3.25% standard error)
+pub const HLL_PRECISION: u32 = 10;
+pub const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+/// Stable 64-bit hash of a viewer IP address (the IP itself is never stored)
+pub fn viewer_hash(ip: IpAddr) -> u64 {
+    let digest = Sha256::digest(ip.to_string().as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Register index and rank (position of the first set bit) to record for a viewer hash
+/// The same viewer always maps to the same register and rank, so repeated views do not count
+pub fn register_update(hash: u64) -> (usize, u8) {
+    let index = (hash >> (64 - HLL_PRECISION)) as usize;
+    // Sentinel bit bounds the rank to 64 - HLL_PRECISION + 1
+    let remaining = (hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1));
+    let rank = remaining.leading_zeros() as u8 + 1;
+    (index, rank)
+}
+
+/// Approximate number of distinct viewers recorded in the registers
+pub fn estimate(registers: &[u8]) -> u64 {
+    if registers.len() != HLL_REGISTERS {
+        return 0;
+    }
+
+    let m = HLL_REGISTERS as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum: f64 = registers
+        .iter()
+        .map(|&register| 2f64.powi(-(register as i32)))
+        .sum();
+    let raw = alpha * m * m / sum;
+
+    // Small range correction (linear counting) while registers are still empty
+    let zeros = registers.iter().filter(|&&register| register == 0).count();
+    let estimate = if raw <= 2.5 * m && zeros > 0 {
+        m * (m / zeros as f64).ln()
+    } else {
+        raw
+    };
+
+    estimate.round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn record(registers: &mut [u8], ip: IpAddr) {
+        let (index, rank) = register_update(viewer_hash(ip));
+        registers[index] = registers[index].max(rank);
+    }
+
+    #[test]
+    fn test_register_update_bounds() {
+        assert_eq!(register_update(0), (0, 55));
+        assert_eq!(register_update(u64::MAX), (HLL_REGISTERS - 1, 1));
+        assert_eq!(register_update(1 << 53), (0, 1));
+    }
+
+    #[test]
+    fn test_empty_registers_estimate_zero() {
+        assert_eq!(estimate(&[0u8; HLL_REGISTERS]), 0);
+        assert_eq!(estimate(&[]), 0);
+    }
+
+    #[test]
+    fn test_repeated_views_count_once() {
+        let mut registers = vec![0u8; HLL_REGISTERS];
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+        for _ in 0..100 {
+            record(&mut registers, ip);
+        }
+        assert_eq!(estimate(&registers), 1);
+    }
+
+    #[test]
+    fn test_estimate_is_approximate() {
+        let mut registers = vec![0u8; HLL_REGISTERS];
+        for i in 0..10_000u32 {
+            record(&mut registers, IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + i)));
+        }
+        let estimate = estimate(&registers) as f64;
+        assert!((estimate - 10_000.0).abs() / 10_000.0 < 0.1, "{}", estimate);
+    }
+}
@@ -1,28 +1,95 @@
 use axum::{
     Router,
-    extract::{ConnectInfo, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    extract::{
+        ConnectInfo, OriginalUri, Path as AxumPath, Query, Request, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Json, Redirect, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{MethodRouter, any, get, post},
 };
-use axum_prometheus::PrometheusMetricLayer;
-use serde::Deserialize;
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
-use tokio::{net::TcpListener, sync::RwLock, time::Instant};
+use axum_prometheus::{
+    PrometheusMetricLayer,
+    metrics::{counter, gauge},
+};
+use base64ct::{Base64, Encoding};
+use futures_util::{Stream, stream};
+use k_client::requests::{
+    CreateErasureRequestQuery, DeleteFeaturedQuery, GetArticleQuery, GetBlockedUsersQuery,
+    GetContentsFollowingQuery, GetDuplicateContentsQuery, GetErasureRequestQuery, GetEventsQuery,
+    GetFeatureFlagsQuery, GetFeaturedEntriesQuery, GetFeaturedQuery, GetFollowedUsersQuery,
+    GetGroupPostsQuery, GetGroupsQuery, GetHashtagContentQuery, GetImpersonationFlagsQuery,
+    GetMentionsQuery, GetMostActiveUsersQuery, GetNicknameHistoryQuery, GetNotificationsCountQuery,
+    GetNotificationsQuery, GetPostDetailsQuery, GetPostMentionsQuery, GetPostTipsQuery,
+    GetPostsByIdsQuery, GetPostsFollowingQuery, GetPostsLightQuery, GetPostsMetaQuery,
+    GetPostsQuery, GetPostsWatchingQuery, GetProfileDiffQuery, GetProfileImageQuery,
+    GetProfileQuery, GetRepliesQuery, GetRequestAnalyticsQuery, GetSlowQueriesQuery,
+    GetThreadQuery, GetTopTippedPostsQuery, GetTrendingHashtagsQuery, GetUserDetailsQuery,
+    GetUserStatsQuery, GetUsersByKeysQuery, GetUsersCountQuery, GetUsersFollowersQuery,
+    GetUsersFollowingQuery, GetUsersQuery, LiveFeedQuery, NotificationStreamQuery,
+    ReviewImpersonationFlagQuery, SearchPostsQuery, SearchUsersQuery, SetFeaturedQuery,
+    SimulatePayloadQuery, TrackViewQuery,
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    net::TcpListener,
+    sync::{RwLock, broadcast},
+};
+use tower::ServiceExt;
 use tower_http::{
     cors::{Any, CorsLayer},
     limit::RequestBodyLimitLayer,
     timeout::TimeoutLayer,
 };
-use tracing::{error as log_error, info as log_info};
+use tracing::{error as log_error, info as log_info, warn as log_warn};
 
-use crate::api_handlers::ApiHandlers;
-use crate::config::ServerConfig;
-use crate::database_trait::DatabaseInterface;
+use crate::activitypub;
+use crate::api_handlers::{ApiHandlers, IncludeOptions};
+use crate::api_version::{self, API_VERSION_HEADER};
+use crate::atom_feed;
+use crate::config::{FeedConfig, ImageStorageConfig, PageSizeLimits, ServerConfig};
+use crate::count_buckets::bucket_public_counts;
+use crate::database_trait::{DatabaseInterface, PostRanking, QueryOptions, SchemaMeta};
+use crate::feature_flags::{ENDPOINT_FLAG_PREFIX, FlagRule, FlagService};
+use crate::graphql::{self, GraphqlSchema};
+use crate::http_signatures::{HttpSignatures, InstanceKey};
+use crate::live_feed::{LiveClient, LiveFeed, LiveSubscription};
+use crate::load_shedder::LoadShedder;
 use crate::models::{
-    ApiError, PaginatedNotificationsResponse, PaginatedPostsResponse, PaginatedRepliesResponse,
-    PaginatedUsersResponse, PostDetailsResponse, ServerUserPost, TrendingHashtagsResponse,
+    ApiError, ArticleDetailsResponse, ContentRecord, DuplicateContentsResponse,
+    ErasureRequestResponse, ExplorerLinks, FeatureFlagsResponse, FeaturedEntriesResponse,
+    FeaturedEntry, FeaturedPostsResponse, ImpersonationFlag, ImpersonationFlagsResponse,
+    IntoPaginatedEnvelope, KPostRecord, NicknameHistoryResponse, PaginatedEventsResponse,
+    PaginatedGroupPostsResponse, PaginatedGroupsResponse, PaginatedNotificationsResponse,
+    PaginatedPostsLightResponse, PaginatedPostsResponse, PaginatedRepliesResponse,
+    PaginatedUsersResponse, PayloadSimulationResponse, PostDetailsResponse, PostMentionsResponse,
+    PostTipsResponse, PostsByIdsResponse, PostsMetaResponse, ProfileDiffResponse, ProfileResponse,
+    RequestAnalyticsResponse, ServerUserPost, SlowQueriesResponse, StorageStatsResponse,
+    ThreadResponse, TopTippedPostsResponse, TrendingHashtagsResponse, UserStatsResponse,
+    UsersResponse,
 };
+use crate::openapi;
+use crate::payload_simulator::{PayloadSimulator, Simulation};
+use crate::post_preview;
+use crate::request_analytics::{RequestAnalytics, content_id_of};
+use crate::request_id::propagate_request_id;
+use crate::requester_privacy::{
+    REQUESTER_SCOPED_ENDPOINTS, anonymize_requester, withhold_requester_fields,
+};
+use crate::response_cache::ResponseCache;
+use crate::slow_requests::trace_slow_requests;
+use crate::tenants::{TenantConfig, select_tenant};
+use crate::ttl_cache::TtlCache;
 
 #[derive(Debug, Clone)]
 pub(crate) struct RateLimitEntry {
@@ -30,232 +97,560 @@ pub(crate) struct RateLimitEntry {
     window_start: Instant,
 }
 
-type RateLimitMap = Arc<RwLock<HashMap<SocketAddr, RateLimitEntry>>>;
-
-pub struct AppState {
-    pub api_handlers: ApiHandlers,
-    pub rate_limit_map: RateLimitMap,
-    pub server_config: ServerConfig,
-    pub db: Arc<dyn DatabaseInterface>,
-}
+// Keyed by IP: every connection of a client comes from a new port
+type RateLimitMap = Arc<TtlCache<IpAddr, RateLimitEntry>>;
 
-pub struct WebServer {
-    pub app_state: Arc<AppState>,
-}
+/// Window of the per-IP rate limit (--rate-limit requests per window)
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
 
-#[derive(Debug, Deserialize)]
-struct GetPostsQuery {
-    user: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+/// Interval between two prunings of the expired rate limit and cache entries
+const CACHE_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
 
-#[derive(Debug, Deserialize)]
-struct GetRepliesQuery {
-    post: Option<String>,
-    user: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>, // Changed to String to support compound cursors
-    after: Option<String>,  // Changed to String to support compound cursors
-}
+/// Interval of the pings keeping idle /ws connections open through proxies
+const LIVE_FEED_PING_INTERVAL: Duration = Duration::from_secs(30);
 
-#[derive(Debug, Deserialize)]
-struct GetPostsWatchingQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+/// Maximum number of nested replies embedded per item with includeReplies
+const MAX_INCLUDE_REPLIES: u32 = 10;
 
-#[derive(Debug, Deserialize)]
-struct GetContentsFollowingQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+/// Levels of replies of a /get-thread response without maxDepth
+const DEFAULT_THREAD_DEPTH: u32 = 3;
 
-#[derive(Debug, Deserialize)]
-struct GetUsersQuery {
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-}
+/// Maximum length of a /search-posts query, in characters
+const MAX_SEARCH_QUERY_CHARS: usize = 200;
 
-#[derive(Debug, Deserialize)]
-struct GetMostActiveUsersQuery {
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    #[serde(rename = "timeWindow")]
-    time_window: Option<String>,
-}
+/// Maximum length of a /search-users nickname query, in characters
+const MAX_NICKNAME_QUERY_CHARS: usize = 50;
 
-#[derive(Debug, Deserialize)]
-struct SearchUsersQuery {
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    #[serde(rename = "searchedUserPubkey")]
-    searched_user_pubkey: Option<String>,
-    #[serde(rename = "searchedUserNickname")]
-    searched_user_nickname: Option<String>,
-}
+/// K protocol versions indexed by K-transaction-processor ("k:1:" payload prefix)
+const PROTOCOL_VERSIONS: &[u32] = &[1];
 
-#[derive(Debug, Deserialize)]
-struct GetMentionsQuery {
-    user: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+/// K protocol actions with the schema feature their index tables come with (None = any schema)
+const PROTOCOL_ACTIONS: &[(&str, Option<&str>)] = &[
+    ("broadcast", None),
+    ("post", None),
+    ("reply", None),
+    ("quote", None),
+    ("vote", None),
+    ("block", Some("blocks")),
+    ("follow", Some("follows")),
+    ("group", Some("groups")),
+    ("membership", Some("groups")),
+    ("grouppost", Some("groups")),
+    ("event", Some("events")),
+    ("rsvp", Some("events")),
+    ("article", Some("articles")),
+    ("chunk", Some("articles")),
+    ("tip", Some("tips")),
+];
 
-#[derive(Debug, Deserialize)]
-struct GetNotificationsQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
+/// A public endpoint advertised by /.well-known/k-indexer
+struct PublicEndpoint {
+    path: &'static str,
+    method: &'static str,
+    // Schema feature checked by the handler (None = any schema)
+    feature: Option<&'static str>,
+    // Also served under /v2 with the unified pagination envelope
+    v2: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct GetHashtagContentQuery {
-    hashtag: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
+const fn endpoint(path: &'static str, feature: Option<&'static str>, v2: bool) -> PublicEndpoint {
+    PublicEndpoint {
+        path,
+        method: "GET",
+        feature,
+        v2,
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct GetTrendingHashtagsQuery {
-    #[serde(rename = "timeWindow")]
-    time_window: Option<String>,
-    limit: Option<u32>,
-}
+/// Public endpoints, kept in sync with create_v1_router and create_v2_router
+const PUBLIC_ENDPOINTS: &[PublicEndpoint] = &[
+    endpoint("get-posts", None, true),
+    endpoint("get-post-details", None, false),
+    endpoint("get-post-mentions", None, false),
+    endpoint("get-posts-watching", None, true),
+    endpoint("get-posts-light", None, true),
+    endpoint("get-posts-meta", None, false),
+    endpoint("get-posts-by-ids", None, false),
+    endpoint("get-contents-following", Some("follows"), true),
+    endpoint("get-posts-following", Some("follows"), true),
+    endpoint("get-replies", None, true),
+    endpoint("get-thread", None, false),
+    endpoint("get-mentions", None, true),
+    endpoint("get-users", None, true),
+    endpoint("get-most-active-users", None, true),
+    endpoint("get-users-count", None, false),
+    endpoint("search-users", None, true),
+    endpoint("get-user-details", None, false),
+    endpoint("get-user-stats", None, false),
+    endpoint("get-users-by-keys", None, false),
+    endpoint("get-profile", None, false),
+    endpoint("get-profile-image", None, false),
+    endpoint("get-profile-diff", Some("profile_history"), false),
+    endpoint("get-followed-users", Some("follows"), true),
+    endpoint("get-users-following", Some("follows"), true),
+    endpoint("get-users-followers", Some("follows"), true),
+    endpoint("get-blocked-users", Some("blocks"), true),
+    endpoint("get-notifications-count", None, false),
+    endpoint("get-notifications", None, true),
+    endpoint("get-hashtag-content", Some("hashtags"), true),
+    endpoint("get-trending-hashtags", Some("hashtags"), false),
+    endpoint("search-posts", Some("full_text_search"), true),
+    endpoint("get-groups", Some("groups"), true),
+    endpoint("get-group-posts", Some("groups"), true),
+    endpoint("get-events", Some("events"), true),
+    endpoint("get-article", Some("articles"), false),
+    endpoint("get-post-tips", Some("tips"), false),
+    endpoint("get-top-tipped-posts", Some("tips"), false),
+    endpoint("get-featured", Some("featured"), false),
+    endpoint("get-feature-flags", None, false),
+    // Only registered with --enable-view-tracking
+    PublicEndpoint {
+        path: "track-view",
+        method: "POST",
+        feature: Some("views"),
+        v2: false,
+    },
+    // Only registered with --enable-live-feed
+    endpoint("ws", Some("live_events"), false),
+    endpoint("notifications/stream", Some("live_events"), false),
+    // Only registered with --enable-graphql
+    PublicEndpoint {
+        path: "graphql",
+        method: "POST",
+        feature: None,
+        v2: false,
+    },
+];
 
-#[derive(Debug, Deserialize)]
-struct GetPostDetailsQuery {
-    id: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
+pub struct AppState {
+    pub api_handlers: ApiHandlers,
+    pub rate_limit_map: RateLimitMap,
+    pub server_config: ServerConfig,
+    pub db: Arc<dyn DatabaseInterface>,
+    // Schema version/features published by K-transaction-processor (None if not available)
+    pub schema_meta: RwLock<Option<SchemaMeta>>,
+    // Experimental behavior toggles (--feature-flags and k_feature_flags)
+    pub feature_flags: Arc<FlagService>,
+    // Key and signature checks of the ActivityPub actors (None without --activitypub-base-url)
+    pub http_signatures: Option<HttpSignatures>,
+    // Rejects expensive endpoints under DB pressure (None without --load-shedding)
+    pub load_shedder: Option<Arc<LoadShedder>>,
+    // Shared first pages of hot feeds (None without --response-cache-ttl)
+    pub response_cache: Option<Arc<ResponseCache>>,
+    // Live events pushed to /ws clients (None without --enable-live-feed)
+    pub live_feed: Option<Arc<LiveFeed>>,
+    // Read-only GraphQL schema served on /graphql (None without --enable-graphql)
+    pub graphql: Option<GraphqlSchema>,
+    // Hourly request counts of /admin/request-analytics (None without --request-analytics-hours)
+    pub request_analytics: Option<Arc<RequestAnalytics>>,
+    // Payload simulations of /admin/simulate-payload (None outside k-indexer --simulator)
+    pub payload_simulator: Option<Arc<dyn PayloadSimulator>>,
 }
 
-#[derive(Debug, Deserialize)]
-struct GetUserDetailsQuery {
-    user: Option<String>,
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
+pub struct WebServer {
+    pub app_state: Arc<AppState>,
+    // Servers of the communities of --tenants, with their own database schema and state
+    tenants: Vec<(TenantConfig, WebServer)>,
 }
 
-#[derive(Debug, Deserialize)]
-struct GetBlockedUsersQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+impl WebServer {
+    pub async fn new(
+        db: Arc<dyn DatabaseInterface>,
+        server_config: ServerConfig,
+        payload_simulator: Option<Arc<dyn PayloadSimulator>>,
+    ) -> Self {
+        let feature_flags = Arc::new(FlagService::new(server_config.feature_flags.clone()));
+        for disabled in &server_config.disabled_endpoints {
+            if !PUBLIC_ENDPOINTS
+                .iter()
+                .any(|endpoint| endpoint.path == disabled)
+            {
+                log_warn!(
+                    "--disabled-endpoints: /{} is not a public endpoint",
+                    disabled
+                );
+            }
+        }
+        let api_handlers = ApiHandlers::new(db.clone(), feature_flags.clone());
+        // Entries are forgotten once their window is over
+        let rate_limit_map = Arc::new(TtlCache::new(
+            RATE_LIMIT_WINDOW,
+            server_config.rate_limit_max_clients,
+        ));
 
-#[derive(Debug, Deserialize)]
-struct GetFollowedUsersQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+        // Read schema version/features once at startup
+        let schema_meta = match db.get_schema_meta().await {
+            Ok(Some(meta)) => {
+                log_info!(
+                    "Database schema v{} (processor v{}), features: {:?}",
+                    meta.schema_version,
+                    meta.processor_version,
+                    meta.features
+                );
+                Some(meta)
+            }
+            Ok(None) => {
+                log_warn!("Schema meta (k_meta) not found - all endpoints enabled");
+                None
+            }
+            Err(e) => {
+                log_warn!("Failed to read schema meta: {} - all endpoints enabled", e);
+                None
+            }
+        };
 
-#[derive(Debug, Deserialize)]
-struct GetUsersFollowingQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    #[serde(rename = "userPubkey")]
-    user_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+        // ActivityPub actors share one RSA key, which also signs the key fetches of signature checks
+        let http_signatures = server_config.activitypub.as_ref().map(|config| {
+            let instance_key = InstanceKey::from_pkcs8_pem(&config.private_key_pem)
+                .unwrap_or_else(|e| panic!("Invalid --activitypub-key-file: {:#}", e));
+            let instance_key_id =
+                activitypub::key_id(&activitypub::instance_actor_id(&config.base_url));
+            HttpSignatures::new(instance_key, instance_key_id)
+                .unwrap_or_else(|e| panic!("Failed to create the ActivityPub HTTP client: {}", e))
+        });
 
-#[derive(Debug, Deserialize)]
-struct GetUsersFollowersQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    #[serde(rename = "userPubkey")]
-    user_pubkey: Option<String>,
-    limit: Option<u32>,
-    before: Option<String>,
-    after: Option<String>,
-}
+        let load_shedder = server_config.load_shedding.clone().map(|config| {
+            log_info!(
+                "Load shedding enabled for {} (acquire >= {}ms or > {} requests in flight)",
+                config.endpoints.join(", "),
+                config.acquire_threshold_ms,
+                config.max_in_flight
+            );
+            Arc::new(LoadShedder::new(config))
+        });
 
-#[derive(Debug, Deserialize)]
-struct GetNotificationsCountQuery {
-    #[serde(rename = "requesterPubkey")]
-    requester_pubkey: Option<String>,
-    after: Option<String>,
-}
+        let response_cache = server_config.response_cache.clone().map(|config| {
+            log_info!(
+                "Response cache enabled for first pages ({}s TTL, served stale for {}s more)",
+                config.ttl_secs,
+                config.stale_secs
+            );
+            Arc::new(ResponseCache::new(config))
+        });
 
-#[derive(Debug, Deserialize)]
-struct GetUsersCountQuery {}
+        let live_feed = server_config.live_feed.as_ref().map(|config| {
+            log_info!(
+                "Live feed enabled on /ws (at most {} connections)",
+                config.max_clients
+            );
+            Arc::new(LiveFeed::new(
+                config.max_clients,
+                server_config.hide_rate_limited,
+            ))
+        });
 
-impl WebServer {
-    pub async fn new(db: Arc<dyn DatabaseInterface>, server_config: ServerConfig) -> Self {
-        let api_handlers = ApiHandlers::new(db.clone());
-        let rate_limit_map = Arc::new(RwLock::new(HashMap::new()));
+        let graphql = server_config.graphql.then(|| {
+            log_info!("GraphQL endpoint enabled on /graphql");
+            graphql::build_schema()
+        });
+        let request_analytics = server_config.request_analytics_hours.map(|hours| {
+            log_info!("Request analytics enabled ({} hours kept)", hours);
+            Arc::new(RequestAnalytics::new(hours))
+        });
 
         let app_state = Arc::new(AppState {
             api_handlers,
             rate_limit_map,
             server_config,
             db,
+            schema_meta: RwLock::new(schema_meta),
+            feature_flags,
+            http_signatures,
+            load_shedder,
+            response_cache,
+            live_feed,
+            graphql,
+            request_analytics,
+            payload_simulator,
+        });
+
+        refresh_feature_flags(&app_state).await;
+
+        Self {
+            app_state,
+            tenants: Vec::new(),
+        }
+    }
+
+    /// Serve `tenant` from `server` (built on a database interface of the tenant schema) under
+    /// /t/{id} and on the hostname of the tenant
+    pub fn with_tenant(mut self, tenant: TenantConfig, server: WebServer) -> Self {
+        self.tenants.push((tenant, server));
+        self
+    }
+
+    /// Periodically re-read k_meta so schema upgrades are picked up without a restart
+    fn spawn_schema_meta_refresh(&self) {
+        let app_state = self.app_state.clone();
+        let interval = Duration::from_secs(app_state.server_config.schema_refresh_interval.max(1));
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                match app_state.db.get_schema_meta().await {
+                    Ok(meta) => {
+                        let mut current = app_state.schema_meta.write().await;
+                        let old_version = current.as_ref().map(|m| m.schema_version);
+                        let new_version = meta.as_ref().map(|m| m.schema_version);
+                        if old_version != new_version {
+                            log_info!(
+                                "Database schema version changed: {:?} -> {:?}",
+                                old_version,
+                                new_version
+                            );
+                        }
+                        *current = meta;
+                    }
+                    Err(e) => {
+                        log_warn!("Failed to refresh schema meta: {}", e);
+                    }
+                }
+
+                refresh_feature_flags(&app_state).await;
+            }
+        });
+    }
+
+    /// Drop expired rate limit entries, cached pages and remote keys every CACHE_PRUNE_INTERVAL
+    /// and export the number of entries left, so the maps stay small between overflows
+    fn spawn_cache_pruning(&self) {
+        let app_state = self.app_state.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CACHE_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = Instant::now();
+
+                let rate_limit_entries = app_state.rate_limit_map.prune(now);
+                gauge!("k_cache_entries", "cache" => "rate_limit").set(rate_limit_entries as f64);
+                if let Some(cache) = &app_state.response_cache {
+                    gauge!("k_cache_entries", "cache" => "response").set(cache.prune(now) as f64);
+                }
+                if let Some(http_signatures) = &app_state.http_signatures {
+                    let keys = http_signatures.prune_key_cache(now);
+                    gauge!("k_cache_entries", "cache" => "remote_keys").set(keys as f64);
+                }
+            }
         });
+    }
+
+    /// Refresh the default first pages of /get-posts-watching (requesters blocking nobody) and
+    /// /get-trending-hashtags every --response-cache-prewarm-interval seconds, starting right
+    /// away, so the most requested pages are served from the cache even just after a restart
+    fn spawn_response_cache_prewarm(&self) {
+        let app_state = self.app_state.clone();
+        let (Some(cache), Some(config)) = (
+            app_state.response_cache.clone(),
+            app_state.server_config.response_cache.as_ref(),
+        ) else {
+            return;
+        };
+        if config.prewarm_interval_secs == 0 {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(config.prewarm_interval_secs));
+        let page_sizes = &app_state.server_config.page_sizes;
+        let posts_limit = page_sizes.limits_for("get-posts-watching").default;
+        let hashtags_limit = page_sizes.limits_for("get-trending-hashtags").default;
+
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
 
-        Self { app_state }
+                cache
+                    .prewarm(
+                        "get-posts-watching",
+                        posts_watching_cache_key(posts_limit, NO_BLOCKS_KEY),
+                        shared_posts_watching_page(
+                            app_state.clone(),
+                            posts_limit,
+                            PREWARM_REQUESTER.to_string(),
+                        ),
+                    )
+                    .await;
+
+                if check_schema_feature(&app_state, "hashtags").await.is_ok() {
+                    cache
+                        .prewarm(
+                            "get-trending-hashtags",
+                            trending_hashtags_cache_key(PREWARM_TRENDING_WINDOW, hashtags_limit),
+                            trending_hashtags_page(
+                                app_state.clone(),
+                                PREWARM_TRENDING_WINDOW.to_string(),
+                                hashtags_limit,
+                            ),
+                        )
+                        .await;
+                }
+            }
+        });
     }
 
     pub fn create_router(&self) -> Router {
         let timeout_duration = Duration::from_secs(self.app_state.server_config.request_timeout);
         let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
 
-        Router::new()
-            .route("/", get(handle_root))
-            .route("/health", get(handle_health))
-            .route("/stats", get(handle_stats))
+        let router = if self.tenants.is_empty() {
+            self.create_community_router()
+        } else {
+            let tenants = self
+                .tenants
+                .iter()
+                .map(|(tenant, server)| (tenant.clone(), server.create_community_router()))
+                .collect();
+            dispatch_tenants(self.create_community_router(), tenants)
+        };
+
+        // Same document for every tenant, serialized once
+        let openapi_json = Arc::new(openapi::document().to_string());
+
+        let mut router = router
             .route(
                 "/metrics",
                 get(move || async move { metric_handle.render() }),
             )
+            .route(
+                "/openapi.json",
+                get(move || async move {
+                    (
+                        [(header::CONTENT_TYPE, "application/json")],
+                        openapi_json.to_string(),
+                    )
+                }),
+            )
+            .layer(prometheus_layer)
+            .layer(TimeoutLayer::new(timeout_duration))
+            .layer(RequestBodyLimitLayer::new(1024 * 1024)) // 1MB limit
+            .layer(
+                CorsLayer::new()
+                    .allow_origin(Any)
+                    .allow_methods(Any)
+                    .allow_headers(Any),
+            );
+
+        // Outside the timeout, so timed out requests are logged with the statements they ran,
+        // and inside propagate_request_id, so under the request ID
+        if let Some(threshold_ms) = self.app_state.server_config.slow_request_trace_ms {
+            router = router.layer(middleware::from_fn_with_state(
+                Duration::from_millis(threshold_ms),
+                trace_slow_requests,
+            ));
+        }
+
+        router.layer(middleware::from_fn(propagate_request_id))
+    }
+
+    /// Endpoints of the community of this server, behind the guards reading its state
+    fn create_community_router(&self) -> Router {
+        let mut router = Router::new()
+            .route("/", get(handle_root))
+            .route("/health", get(handle_health))
+            .route("/.well-known/k-indexer", get(handle_well_known))
+            .route("/stats", get(handle_stats));
+
+        // Operator endpoints (--admin-token)
+        if self.app_state.server_config.admin_token.is_some() {
+            router = router.nest("/admin", create_admin_router(self.app_state.clone()));
+        }
+
+        // Atom feeds (--feed-client-url)
+        if self.app_state.server_config.feeds.is_some() {
+            router = router
+                .route("/feeds/user/:file", get(handle_user_feed))
+                .route("/feeds/watching.xml", get(handle_watching_feed));
+        }
+
+        // Link preview pages, oEmbed and sitemap (--preview-base-url)
+        if self.app_state.server_config.preview_base_url.is_some() {
+            router = router
+                .route("/p/:txid", get(handle_post_preview))
+                .route("/oembed", get(handle_oembed))
+                .route("/sitemap.xml", get(handle_sitemap));
+        }
+
+        // Read-only ActivityPub actors (--activitypub-base-url)
+        if let Some(activitypub) = &self.app_state.server_config.activitypub {
+            router = router
+                .route("/.well-known/webfinger", get(handle_webfinger))
+                .nest(
+                    "/ap",
+                    create_activitypub_router(
+                        self.app_state.clone(),
+                        activitypub.require_signatures,
+                    ),
+                );
+        }
+
+        // Public endpoints under their version, and at their unversioned paths (deprecated)
+        let versioned = Router::new()
+            .nest("/v1", api_version_routes(self.create_v1_router(), "v1"))
+            .nest("/v2", api_version_routes(create_v2_router(), "v2"));
+        router
+            .merge(versioned.clone())
+            .merge(legacy_alias_routes(
+                versioned.with_state(self.app_state.clone()),
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                count_bucketing_layer,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                requester_privacy_layer,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                feature_flag_guard,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                disabled_endpoint_guard,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                maintenance_guard,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                load_shedding_guard,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.app_state.clone(),
+                request_analytics_layer,
+            ))
+            .with_state(self.app_state.clone())
+    }
+
+    /// /v1 routes: every public endpoint (PUBLIC_ENDPOINTS), with the optional ones enabled
+    fn create_v1_router(&self) -> Router<Arc<AppState>> {
+        let mut router = Router::new()
             .route("/get-posts", get(handle_get_posts))
             .route("/get-post-details", get(handle_get_post_details))
+            .route("/get-post-mentions", get(handle_get_post_mentions))
             .route("/get-posts-watching", get(handle_get_posts_watching))
+            .route("/get-posts-light", get(handle_get_posts_light))
+            .route("/get-posts-meta", get(handle_get_posts_meta))
+            .route("/get-posts-by-ids", get(handle_get_posts_by_ids))
             .route(
                 "/get-contents-following",
                 get(handle_get_contents_following),
             )
+            .route("/get-posts-following", get(handle_get_posts_following))
             .route("/get-replies", get(handle_get_replies))
+            .route("/get-thread", get(handle_get_thread))
             .route("/get-mentions", get(handle_get_mentions))
             .route("/get-users", get(handle_get_users))
             .route("/get-most-active-users", get(handle_get_most_active_users))
             .route("/get-users-count", get(handle_get_users_count))
             .route("/search-users", get(handle_search_users))
             .route("/get-user-details", get(handle_get_user_details))
+            .route("/get-user-stats", get(handle_get_user_stats))
+            .route("/get-users-by-keys", get(handle_get_users_by_keys))
+            .route("/get-profile", get(handle_get_profile))
+            .route("/get-profile-image", get(handle_get_profile_image))
+            .route("/get-profile-diff", get(handle_get_profile_diff))
             .route("/get-followed-users", get(handle_get_followed_users))
             .route("/get-users-following", get(handle_get_users_following))
             .route("/get-users-followers", get(handle_get_users_followers))
@@ -267,22 +662,52 @@ impl WebServer {
             .route("/get-notifications", get(handle_get_notifications))
             .route("/get-hashtag-content", get(handle_get_hashtag_content))
             .route("/get-trending-hashtags", get(handle_get_trending_hashtags))
-            .layer(prometheus_layer)
-            .layer(TimeoutLayer::new(timeout_duration))
-            .layer(RequestBodyLimitLayer::new(1024 * 1024)) // 1MB limit
-            .layer(
-                CorsLayer::new()
-                    .allow_origin(Any)
-                    .allow_methods(Any)
-                    .allow_headers(Any),
-            )
-            .with_state(self.app_state.clone())
+            .route("/search-posts", get(handle_search_posts))
+            .route("/get-groups", get(handle_get_groups))
+            .route("/get-group-posts", get(handle_get_group_posts))
+            .route("/get-events", get(handle_get_events))
+            .route("/get-article", get(handle_get_article))
+            .route("/get-post-tips", get(handle_get_post_tips))
+            .route("/get-top-tipped-posts", get(handle_get_top_tipped_posts))
+            .route("/get-featured", get(handle_get_featured))
+            .route("/get-feature-flags", get(handle_get_feature_flags));
+
+        // Optional view tracking (--enable-view-tracking)
+        if self.app_state.server_config.view_tracking {
+            router = router.route("/track-view", post(handle_track_view));
+        }
+
+        // WebSocket live feed and notification streams (--enable-live-feed)
+        if self.app_state.live_feed.is_some() {
+            router = router
+                .route("/ws", get(handle_live_feed))
+                .route("/notifications/stream", get(handle_notification_stream));
+        }
+
+        // Read-only GraphQL queries, schema in SDL on GET (--enable-graphql)
+        if self.app_state.graphql.is_some() {
+            router = router.route("/graphql", get(handle_graphql_sdl).post(handle_graphql));
+        }
+
+        router
+    }
+
+    /// Background refreshes of this server and of its tenants
+    fn spawn_background_tasks(&self) {
+        self.spawn_schema_meta_refresh();
+        self.spawn_response_cache_prewarm();
+        self.spawn_cache_pruning();
+        for (_, server) in &self.tenants {
+            server.spawn_background_tasks();
+        }
     }
 
     pub async fn serve(&self, bind_address: &str) -> Result<(), Box<dyn std::error::Error>> {
         let router = self.create_router();
         let listener = TcpListener::bind(bind_address).await?;
 
+        self.spawn_background_tasks();
+
         log_info!("Web server starting on {}", bind_address);
         axum::serve(
             listener,
@@ -294,28 +719,146 @@ impl WebServer {
     }
 }
 
+/// Route each request to the community router of its tenant (see select_tenant), with the
+/// /t/{id} prefix stripped; other requests go to the network's own community
+fn dispatch_tenants(default: Router, tenants: Vec<(TenantConfig, Router)>) -> Router {
+    let (configs, routers): (Vec<TenantConfig>, Vec<Router>) = tenants.into_iter().unzip();
+    let configs = Arc::new(configs);
+
+    Router::new().fallback(move |mut request: Request| {
+        let configs = configs.clone();
+        let default = default.clone();
+        let routers = routers.clone();
+        async move {
+            let host = request
+                .headers()
+                .get(header::HOST)
+                .and_then(|host| host.to_str().ok())
+                .or_else(|| request.uri().host());
+            let path_and_query = request
+                .uri()
+                .path_and_query()
+                .map_or("/", |path_and_query| path_and_query.as_str());
+
+            let router = match select_tenant(&configs, host, path_and_query) {
+                Some((index, path_and_query)) => {
+                    match path_and_query.parse() {
+                        Ok(uri) => *request.uri_mut() = uri,
+                        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+                    }
+                    routers[index].clone()
+                }
+                None => default,
+            };
+            router.oneshot(request).await.into_response()
+        }
+    })
+}
+
+/// Routes of an API version, answering with the version in K-API-Version
+fn api_version_routes(
+    router: Router<Arc<AppState>>,
+    version: &'static str,
+) -> Router<Arc<AppState>> {
+    router.layer(middleware::map_response(
+        move |mut response: Response| async move {
+            response
+                .headers_mut()
+                .insert(API_VERSION_HEADER, HeaderValue::from_static(version));
+            response
+        },
+    ))
+}
+
+/// Unversioned paths of the public endpoints, deprecated aliases of their versioned paths
+/// served by `versioned` (/v1 and /v2 routes)
+fn legacy_alias_routes(versioned: Router) -> Router<Arc<AppState>> {
+    PUBLIC_ENDPOINTS
+        .iter()
+        .fold(Router::new(), |router, endpoint| {
+            let versioned = versioned.clone();
+            router.route(
+                &format!("/{}", endpoint.path),
+                any(move |request: Request| {
+                    handle_legacy_alias(versioned.clone(), endpoint, request)
+                }),
+            )
+        })
+}
+
+/// The K-API-Version header picks the version answering an unversioned path (v1 by default,
+/// or the latest version up to the requested one that changed the endpoint). The response
+/// is marked deprecated with a link to the versioned path
+async fn handle_legacy_alias(
+    versioned: Router,
+    endpoint: &'static PublicEndpoint,
+    mut request: Request,
+) -> Response {
+    let requested = match request.headers().get(API_VERSION_HEADER) {
+        None => api_version::DEFAULT_API_VERSION,
+        Some(value) => match value.to_str().ok().and_then(api_version::parse_version) {
+            Some(version) => version,
+            None => {
+                let error = ApiError {
+                    error: format!(
+                        "Unsupported API version (supported: {})",
+                        api_version::API_VERSIONS.join(", ")
+                    ),
+                    code: "UNSUPPORTED_API_VERSION".to_string(),
+                };
+                return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+            }
+        },
+    };
+    let version = api_version::negotiate(requested, |version| version == "v2" && endpoint.v2);
+
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("/{}/{}?{}", version, endpoint.path, query),
+        None => format!("/{}/{}", version, endpoint.path),
+    };
+    match path_and_query.parse() {
+        Ok(uri) => *request.uri_mut() = uri,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    }
+
+    let mut response = versioned.oneshot(request).await.into_response();
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!(
+        "<{}>; rel=\"successor-version\"",
+        api_version::successor_reference(version, endpoint.path)
+    )) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
 // Rate limiting middleware
 async fn check_rate_limit(
     state: &AppState,
     client_addr: SocketAddr,
 ) -> Result<(), (StatusCode, Json<ApiError>)> {
     let now = Instant::now();
-    let mut rate_limits = state.rate_limit_map.write().await;
-
-    let entry = rate_limits.entry(client_addr).or_insert(RateLimitEntry {
-        count: 0,
-        window_start: now,
-    });
-
-    // Reset window if 1 minute has passed
-    if now.duration_since(entry.window_start) >= Duration::from_secs(60) {
-        entry.count = 0;
-        entry.window_start = now;
-    }
+    let count = state.rate_limit_map.upsert(
+        client_addr.ip(),
+        now,
+        || RateLimitEntry {
+            count: 0,
+            window_start: now,
+        },
+        |entry| {
+            // Reset window if 1 minute has passed
+            if now.duration_since(entry.window_start) >= RATE_LIMIT_WINDOW {
+                entry.count = 0;
+                entry.window_start = now;
+            }
 
-    entry.count += 1;
+            entry.count += 1;
+            entry.count
+        },
+    );
 
-    if entry.count > state.server_config.rate_limit {
+    if count > state.server_config.rate_limit {
         let error = ApiError {
             error: "Rate limit exceeded. Too many requests per minute.".to_string(),
             code: "RATE_LIMIT_EXCEEDED".to_string(),
@@ -326,8 +869,536 @@ async fn check_rate_limit(
     Ok(())
 }
 
-// API Handler Functions
-
+// Schema feature gate: reject endpoints whose tables are not supported by the active schema
+pub(crate) async fn check_schema_feature(
+    state: &AppState,
+    feature: &str,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    let schema_meta = state.schema_meta.read().await;
+
+    match schema_meta.as_ref() {
+        Some(meta) if !meta.supports(feature) => {
+            let error = ApiError {
+                error: format!(
+                    "Endpoint not available: database schema v{} does not support '{}'",
+                    meta.schema_version, feature
+                ),
+                code: "FEATURE_UNAVAILABLE".to_string(),
+            };
+            Err((StatusCode::SERVICE_UNAVAILABLE, Json(error)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether a request only reads, and is served during read-only maintenance mode
+fn is_read_request(method: &Method, path: &str) -> bool {
+    // GraphQL has no mutations: its POSTed queries are reads too
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || api_version::endpoint_of(path) == "graphql"
+}
+
+/// Read-only maintenance mode (k_meta.maintenance_mode): reads keep being served,
+/// anything else is rejected with 503 and Retry-After until maintenance ends
+async fn maintenance_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_read_request(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let maintenance = state
+        .schema_meta
+        .read()
+        .await
+        .as_ref()
+        .filter(|meta| meta.maintenance_mode)
+        .map(|meta| {
+            (
+                meta.maintenance_retry_after.max(0),
+                meta.maintenance_reason.clone(),
+            )
+        });
+
+    match maintenance {
+        Some((retry_after, reason)) => {
+            let error = ApiError {
+                error: reason
+                    .unwrap_or_else(|| "Service is in read-only maintenance mode".to_string()),
+                code: "MAINTENANCE_MODE".to_string(),
+            };
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(error),
+            )
+                .into_response()
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Reload the operator feature flags when the schema has k_feature_flags (schema v14+)
+async fn refresh_feature_flags(state: &AppState) {
+    let supported = state
+        .schema_meta
+        .read()
+        .await
+        .as_ref()
+        .is_some_and(|meta| meta.supports("feature_flags"));
+    if !supported {
+        return;
+    }
+
+    match state.db.get_feature_flags().await {
+        Ok(records) => state.feature_flags.set_table_rules(
+            records
+                .into_iter()
+                .map(|record| {
+                    let rule = FlagRule {
+                        enabled: record.enabled,
+                        rollout_percent: record.rollout_percent.clamp(0, 100) as u8,
+                    };
+                    (record.name, rule)
+                })
+                .collect(),
+        ),
+        Err(e) => {
+            log_warn!("Failed to refresh feature flags: {}", e);
+        }
+    }
+}
+
+/// Endpoints disabled by the operator (--disabled-endpoints) answer 501 with a link to the
+/// discovery document listing what this node serves
+async fn disabled_endpoint_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let endpoint = api_version::endpoint_of(request.uri().path());
+
+    if state
+        .server_config
+        .disabled_endpoints
+        .iter()
+        .any(|disabled| disabled == endpoint)
+    {
+        let error = ApiError {
+            error: format!(
+                "Endpoint disabled on this node: /{} (available endpoints are listed in /.well-known/k-indexer)",
+                endpoint
+            ),
+            code: "ENDPOINT_DISABLED".to_string(),
+        };
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            [(
+                header::LINK,
+                "</.well-known/k-indexer>; rel=\"service-desc\"",
+            )],
+            Json(error),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Experimental endpoints: a configured "endpoint:<name>" flag that is off for the
+/// requester (requesterPubkey, or this request without one) hides the endpoint with 404
+async fn feature_flag_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let endpoint = api_version::endpoint_of(request.uri().path());
+    let flag = format!("{}{}", ENDPOINT_FLAG_PREFIX, endpoint);
+    let requester_pubkey = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("requesterPubkey="))
+    });
+
+    if state.feature_flags.check(&flag, requester_pubkey) == Some(false) {
+        let error = ApiError {
+            error: format!("Endpoint not available: /{}", endpoint),
+            code: "FEATURE_DISABLED".to_string(),
+        };
+        return (StatusCode::NOT_FOUND, Json(error)).into_response();
+    }
+
+    next.run(request).await
+}
+
+// Load shedding: count in-flight requests, reject expensive endpoints while the DB is under pressure
+async fn load_shedding_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(load_shedder) = &state.load_shedder else {
+        return next.run(request).await;
+    };
+    let _in_flight = load_shedder.start_request();
+
+    let endpoint = api_version::endpoint_of(request.uri().path());
+    if load_shedder.is_expensive(endpoint)
+        && let Some(retry_after) = load_shedder.retry_after(Instant::now())
+    {
+        counter!("k_load_shed_requests_total", "endpoint" => endpoint.to_string()).increment(1);
+        let error = ApiError {
+            error: format!(
+                "Server under heavy load: /{} is temporarily unavailable",
+                endpoint
+            ),
+            code: "OVERLOADED".to_string(),
+        };
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(error),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Round the public vote and view counts of JSON responses down to --count-bucket-size, in
+/// one place for every endpoint, v2 envelopes and cached pages included
+async fn count_bucketing_layer(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let bucket_size = state.server_config.count_bucket_size;
+    let response = next.run(request).await;
+    if bucket_size <= 1 {
+        return response;
+    }
+
+    rewrite_json_response(response, |document| {
+        bucket_public_counts(document, bucket_size)
+    })
+    .await
+}
+
+/// --requester-privacy: read the contents and profiles of other users for the anonymous
+/// requester, so blocked authors are not told apart by their withheld content, and answer
+/// null requester fields; requester-scoped endpoints keep their requester
+async fn requester_privacy_layer(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !state.server_config.requester_privacy {
+        return next.run(request).await;
+    }
+
+    let endpoint = api_version::endpoint_of(request.uri().path());
+    if !REQUESTER_SCOPED_ENDPOINTS.contains(&endpoint) {
+        let anonymized = request.uri().query().and_then(anonymize_requester);
+        if let Some(query) = anonymized {
+            match format!("{}?{}", request.uri().path(), query).parse() {
+                Ok(uri) => *request.uri_mut() = uri,
+                Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+            }
+        }
+    }
+
+    let response = next.run(request).await;
+    rewrite_json_response(response, withhold_requester_fields).await
+}
+
+/// Apply `rewrite` to the document of a JSON response; other responses are returned as is
+async fn rewrite_json_response(
+    response: Response,
+    rewrite: impl FnOnce(&mut serde_json::Value),
+) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            log_error!("Failed to read JSON response body: {}", err);
+            return internal_error_response().into_response();
+        }
+    };
+    let Ok(mut document) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, axum::body::Body::from(bytes));
+    };
+    rewrite(&mut document);
+    // The body length changes with the rewritten fields
+    parts.headers.remove(header::CONTENT_LENGTH);
+    (parts, Json(document)).into_response()
+}
+
+/// --request-analytics-hours: count every request to a public endpoint, served or rejected,
+/// with the content it reads
+async fn request_analytics_layer(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(analytics) = &state.request_analytics {
+        let endpoint = api_version::endpoint_of(request.uri().path());
+        if let Some(endpoint) = PUBLIC_ENDPOINTS
+            .iter()
+            .find(|public| public.path == endpoint)
+        {
+            let content_id = request.uri().query().and_then(content_id_of);
+            analytics.record(endpoint.path, content_id, unix_now());
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Whether `headers` carry `Authorization: Bearer <expected>` (never without an expected token)
+fn admin_token_matches(expected: Option<&str>, headers: &HeaderMap) -> bool {
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (expected, provided) {
+        (Some(expected), Some(provided)) => {
+            // Compare every byte so the response time does not reveal the token
+            expected.len() == provided.len()
+                && expected
+                    .bytes()
+                    .zip(provided.bytes())
+                    .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        }
+        _ => false,
+    }
+}
+
+/// Check `Authorization: Bearer <--admin-token>` (always fails without a configured token)
+fn check_admin_token(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    if !admin_token_matches(state.server_config.admin_token.as_deref(), headers) {
+        let error = ApiError {
+            error: "Missing or invalid admin token".to_string(),
+            code: "UNAUTHORIZED".to_string(),
+        };
+        return Err((StatusCode::UNAUTHORIZED, Json(error)));
+    }
+
+    Ok(())
+}
+
+/// Admin endpoints: reject requests without `Authorization: Bearer <--admin-token>`
+async fn admin_auth_guard(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Err(rejection) = check_admin_token(&state, request.headers()) {
+        return rejection.into_response();
+    }
+
+    next.run(request).await
+}
+
+// API Handler Functions
+
+type HandlerResult<T> = Result<Json<T>, (StatusCode, Json<ApiError>)>;
+
+/// /admin routes: operator endpoints, all behind the admin token
+fn create_admin_router(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let mut router = Router::new()
+        .route(
+            "/erasure-requests",
+            get(handle_get_erasure_request).post(handle_create_erasure_request),
+        )
+        .route("/duplicate-contents", get(handle_get_duplicate_contents))
+        .route("/storage-stats", get(handle_get_storage_stats))
+        .route("/slow-queries", get(handle_get_slow_queries))
+        .route(
+            "/impersonation-flags",
+            get(handle_get_impersonation_flags).post(handle_review_impersonation_flag),
+        )
+        .route("/nickname-history", get(handle_get_nickname_history))
+        .route(
+            "/featured",
+            get(handle_get_featured_entries)
+                .post(handle_set_featured)
+                .delete(handle_delete_featured),
+        );
+
+    // Hourly request counts (--request-analytics-hours)
+    if app_state.request_analytics.is_some() {
+        router = router.route("/request-analytics", get(handle_get_request_analytics));
+    }
+    // Payload simulations (k-indexer --simulator)
+    if app_state.payload_simulator.is_some() {
+        router = router.route("/simulate-payload", post(handle_simulate_payload));
+    }
+
+    router.route_layer(middleware::from_fn_with_state(app_state, admin_auth_guard))
+}
+
+/// /v2 routes: same queries and parameters as v1, every list wrapped in the unified envelope
+fn create_v2_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/get-posts", paginated_v2(handle_get_posts))
+        .route(
+            "/get-posts-watching",
+            paginated_v2(handle_get_posts_watching),
+        )
+        .route("/get-posts-light", paginated_v2(handle_get_posts_light))
+        .route(
+            "/get-contents-following",
+            paginated_v2(handle_get_contents_following),
+        )
+        .route(
+            "/get-posts-following",
+            paginated_v2(handle_get_posts_following),
+        )
+        .route("/get-replies", paginated_v2(handle_get_replies))
+        .route("/get-mentions", paginated_v2(handle_get_mentions))
+        .route("/get-users", paginated_v2(handle_get_users))
+        .route(
+            "/get-most-active-users",
+            paginated_v2(handle_get_most_active_users),
+        )
+        .route("/search-users", paginated_v2(handle_search_users))
+        .route(
+            "/get-followed-users",
+            paginated_v2(handle_get_followed_users),
+        )
+        .route(
+            "/get-users-following",
+            paginated_v2(handle_get_users_following),
+        )
+        .route(
+            "/get-users-followers",
+            paginated_v2(handle_get_users_followers),
+        )
+        .route("/get-blocked-users", paginated_v2(handle_get_blocked_users))
+        .route("/get-notifications", paginated_v2(handle_get_notifications))
+        .route(
+            "/get-hashtag-content",
+            paginated_v2(handle_get_hashtag_content),
+        )
+        .route("/search-posts", paginated_v2(handle_search_posts))
+        .route("/get-groups", paginated_v2(handle_get_groups))
+        .route("/get-group-posts", paginated_v2(handle_get_group_posts))
+        .route("/get-events", paginated_v2(handle_get_events))
+}
+
+/// Wrap a v1 paginated handler so its response is converted into the /v2 envelope
+fn paginated_v2<Q, R, F, Fut>(handler: F) -> MethodRouter<Arc<AppState>>
+where
+    Q: DeserializeOwned + Send + 'static,
+    R: IntoPaginatedEnvelope,
+    F: Fn(ConnectInfo<SocketAddr>, State<Arc<AppState>>, Query<Q>) -> Fut
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    Fut: Future<Output = HandlerResult<R>> + Send + 'static,
+{
+    get(
+        move |addr: ConnectInfo<SocketAddr>, state: State<Arc<AppState>>, query: Query<Q>| async move {
+            handler(addr, state, query)
+                .await
+                .map(|Json(response)| Json(response.into_envelope()))
+        },
+    )
+}
+
+/// Apply the configured page sizes of an endpoint to the optional limit parameter
+pub(crate) fn resolve_limit(
+    app_state: &AppState,
+    endpoint: &str,
+    limit: Option<u32>,
+) -> Result<u32, (StatusCode, Json<ApiError>)> {
+    apply_page_sizes(
+        app_state.server_config.page_sizes.limits_for(endpoint),
+        limit,
+    )
+}
+
+/// The limit parameter (the default page size when absent), rejected outside 1..=max
+fn apply_page_sizes(
+    page_sizes: PageSizeLimits,
+    limit: Option<u32>,
+) -> Result<u32, (StatusCode, Json<ApiError>)> {
+    let limit = limit.unwrap_or(page_sizes.default);
+    if limit < 1 || limit > page_sizes.max {
+        let error = ApiError {
+            error: format!("Limit parameter must be between 1 and {}", page_sizes.max),
+            code: "INVALID_LIMIT".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+    Ok(limit)
+}
+
+/// Validate the optional includeReplies parameter (nested replies embedded per item, 0 = none)
+fn validate_include_replies(
+    include_replies: Option<u32>,
+) -> Result<u32, (StatusCode, Json<ApiError>)> {
+    let include_replies = include_replies.unwrap_or(0);
+    if include_replies > MAX_INCLUDE_REPLIES {
+        let error = ApiError {
+            error: format!(
+                "includeReplies parameter must be between 0 and {}",
+                MAX_INCLUDE_REPLIES
+            ),
+            code: "INVALID_INCLUDE_REPLIES".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+    Ok(include_replies)
+}
+
+/// Validate the optional sort parameter of get-posts-watching (None = newest first)
+fn parse_post_ranking(
+    sort: Option<&str>,
+) -> Result<Option<PostRanking>, (StatusCode, Json<ApiError>)> {
+    match sort {
+        None | Some("latest") => Ok(None),
+        Some("top") => Ok(Some(PostRanking::Top)),
+        Some("hot") => Ok(Some(PostRanking::Hot)),
+        Some(_) => {
+            let error = ApiError {
+                error: "Invalid sort parameter. Must be one of: latest, top, hot".to_string(),
+                code: "INVALID_PARAMETER".to_string(),
+            };
+            Err((StatusCode::BAD_REQUEST, Json(error)))
+        }
+    }
+}
+
+/// Whether fee-weighted vote totals are returned: requested with includeVoteWeights and
+/// recorded by the schema (vote_fees, v27+)
+async fn include_vote_weights(app_state: &AppState, requested: Option<bool>) -> bool {
+    requested.unwrap_or(false)
+        && app_state
+            .schema_meta
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|meta| meta.supports("vote_fees"))
+}
+
 async fn handle_root() -> &'static str {
     "K-indexer API Server - Posts API v1.0"
 }
@@ -346,14 +1417,167 @@ async fn handle_health(
         .await
         .unwrap_or_else(|_| "unknown".to_string());
 
+    let schema_meta = app_state.schema_meta.read().await;
+
     Ok(Json(serde_json::json!({
         "status": "healthy",
         "service": env!("CARGO_PKG_NAME"),
         "version": env!("CARGO_PKG_VERSION"),
-        "network": network
+        "network": network,
+        "schemaVersion": schema_meta.as_ref().map(|m| m.schema_version),
+        "features": schema_meta.as_ref().map(|m| m.features.clone()),
+        "maintenance": schema_meta.as_ref().is_some_and(|m| m.maintenance_mode)
     })))
 }
 
+/// Public endpoints answered with 503 because the schema described by `meta` lacks their feature
+pub(crate) fn unavailable_endpoints(meta: &SchemaMeta) -> Vec<&'static str> {
+    PUBLIC_ENDPOINTS
+        .iter()
+        .filter(|endpoint| {
+            endpoint
+                .feature
+                .is_some_and(|feature| !meta.supports(feature))
+        })
+        .map(|endpoint| endpoint.path)
+        .collect()
+}
+
+/// Capabilities of this deployment for clients and peer indexers: protocol versions and
+/// actions, schema features, network, API versions and the public endpoints currently served
+async fn handle_well_known(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    let network = app_state
+        .db
+        .get_network()
+        .await
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let schema_meta = app_state.schema_meta.read().await;
+    // Without schema meta every feature is assumed, as in check_schema_feature
+    let supports = |feature: Option<&str>| match (feature, schema_meta.as_ref()) {
+        (Some(feature), Some(meta)) => meta.supports(feature),
+        _ => true,
+    };
+
+    let actions: Vec<&str> = PROTOCOL_ACTIONS
+        .iter()
+        .filter(|(_, feature)| supports(*feature))
+        .map(|(action, _)| *action)
+        .collect();
+
+    let endpoints: Vec<serde_json::Value> = PUBLIC_ENDPOINTS
+        .iter()
+        .filter(|endpoint| supports(endpoint.feature))
+        .filter(|endpoint| endpoint.path != "track-view" || app_state.server_config.view_tracking)
+        .filter(|endpoint| {
+            !matches!(endpoint.path, "ws" | "notifications/stream") || app_state.live_feed.is_some()
+        })
+        .filter(|endpoint| endpoint.path != "graphql" || app_state.graphql.is_some())
+        .filter(|endpoint| {
+            !app_state
+                .server_config
+                .disabled_endpoints
+                .iter()
+                .any(|disabled| disabled == endpoint.path)
+        })
+        .filter(|endpoint| {
+            !app_state
+                .feature_flags
+                .is_off_for_all(&format!("{}{}", ENDPOINT_FLAG_PREFIX, endpoint.path))
+        })
+        .map(|endpoint| {
+            serde_json::json!({
+                "path": format!("/{}", endpoint.path),
+                "method": endpoint.method,
+                "v2": endpoint.v2
+            })
+        })
+        .collect();
+
+    // Link templates of the explorer of this network, as used for explorerLinks
+    let explorer = app_state
+        .server_config
+        .explorer
+        .as_ref()
+        .and_then(|explorer| {
+            explorer.base_url(&network).map(|base_url| {
+                serde_json::json!({
+                    "transaction": format!("{}{}", base_url, explorer.transaction_path),
+                    "block": format!("{}{}", base_url, explorer.block_path)
+                })
+            })
+        });
+
+    // Communities of --tenants, each serving the endpoints below under its path prefix
+    let tenants: Vec<serde_json::Value> = app_state
+        .server_config
+        .tenants
+        .iter()
+        .map(|tenant| {
+            serde_json::json!({
+                "id": tenant.id,
+                "host": tenant.host,
+                "pathPrefix": tenant.path_prefix()
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "service": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "network": network,
+        "protocol": {
+            "versions": PROTOCOL_VERSIONS,
+            "actions": actions
+        },
+        "apiVersions": api_version::API_VERSIONS,
+        "apiVersionHeader": API_VERSION_HEADER.as_str(),
+        "schemaVersion": schema_meta.as_ref().map(|m| m.schema_version),
+        "processorVersion": schema_meta.as_ref().map(|m| m.processor_version.clone()),
+        "features": schema_meta.as_ref().map(|m| m.features.clone()),
+        "maintenance": schema_meta.as_ref().is_some_and(|m| m.maintenance_mode),
+        "viewTracking": app_state.server_config.view_tracking,
+        "countBucketSize": app_state.server_config.count_bucket_size,
+        "requesterPrivacy": app_state.server_config.requester_privacy,
+        "liveFeed": app_state.live_feed.is_some(),
+        "graphql": app_state.graphql.is_some(),
+        "activityPub": app_state.server_config.activitypub.is_some(),
+        "feeds": app_state.server_config.feeds.is_some(),
+        "previews": app_state.server_config.preview_base_url.is_some(),
+        "endpoints": endpoints,
+        "disabledEndpoints": app_state.server_config.disabled_endpoints,
+        "tenants": tenants,
+        "explorer": explorer
+    })))
+}
+
+/// Block explorer pages of a transaction on the network of the database (None without an
+/// explorer for that network); the block link needs the blocks_transactions table
+async fn explorer_links(app_state: &AppState, transaction_id: &str) -> Option<ExplorerLinks> {
+    let explorer = app_state.server_config.explorer.as_ref()?;
+    let network = app_state.db.get_network().await.ok()?;
+    let transaction = explorer.transaction_link(&network, transaction_id)?;
+    let block = match app_state.db.get_block_hash(transaction_id).await {
+        Ok(block_hash) => block_hash.and_then(|hash| explorer.block_link(&network, &hash)),
+        Err(e) => {
+            log_warn!(
+                "Failed to get block of transaction {}: {}",
+                transaction_id,
+                e
+            );
+            None
+        }
+    };
+
+    Some(ExplorerLinks { transaction, block })
+}
+
 async fn handle_stats(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
@@ -413,26 +1637,8 @@ async fn handle_get_posts(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-posts", params.limit)?;
 
     // Use the API handler to get paginated posts for the user with voting status
     match app_state
@@ -487,10 +1693,18 @@ async fn handle_get_posts(
 async fn handle_get_post_details(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(params): Query<GetPostDetailsQuery>,
 ) -> Result<Json<PostDetailsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
+
+    // Archived contents are only visible to the operator
+    let include_archived = params.include_archived.unwrap_or(false);
+    if include_archived {
+        check_admin_token(&app_state, &headers)?;
+        check_schema_feature(&app_state, "archive").await?;
+    }
     // Check if id parameter is provided
     let post_id = match params.id {
         Some(id) => id,
@@ -515,16 +1729,40 @@ async fn handle_get_post_details(
         }
     };
 
+    let include = IncludeOptions {
+        replies: validate_include_replies(params.include_replies)?,
+        vote_weights: include_vote_weights(&app_state, params.include_vote_weights).await,
+    };
+
+    // View counts need view tracking enabled and a schema with k_post_views
+    let include_view_count = app_state.server_config.view_tracking
+        && app_state
+            .schema_meta
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|meta| meta.supports("views"));
+
     // Use the API handler to get post details with voting information and blocking status
     match app_state
         .api_handlers
-        .get_post_details(&post_id, &requester_pubkey)
+        .get_post_details(
+            &post_id,
+            &requester_pubkey,
+            include,
+            include_view_count,
+            include_archived,
+        )
         .await
     {
         Ok(response_json) => {
             // Parse the JSON response back to PostDetailsResponse
             match serde_json::from_str::<PostDetailsResponse>(&response_json) {
-                Ok(post_details_response) => Ok(Json(post_details_response)),
+                Ok(mut post_details_response) => {
+                    post_details_response.post.explorer_links =
+                        explorer_links(&app_state, &post_details_response.post.id).await;
+                    Ok(Json(post_details_response))
+                }
                 Err(err) => {
                     log_error!("Failed to parse post details response: {}", err);
                     let error = ApiError {
@@ -558,19 +1796,19 @@ async fn handle_get_post_details(
     }
 }
 
-async fn handle_get_mentions(
+async fn handle_get_thread(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetMentionsQuery>,
-) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetThreadQuery>,
+) -> Result<Json<ThreadResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-    // Check if user parameter is provided
-    let user_public_key = match params.user {
-        Some(user) => user,
+    // Check if id parameter is provided
+    let content_id = match params.id {
+        Some(id) => id,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: user".to_string(),
+                error: "Missing required parameter: id".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
@@ -589,61 +1827,40 @@ async fn handle_get_mentions(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Replies per parent, at every level
+    let limit = resolve_limit(&app_state, "get-thread", params.limit)?;
 
-    // Use the API handler to get paginated mentions for the user with voting status
     match app_state
         .api_handlers
-        .get_mentions_paginated(
-            &user_public_key,
+        .get_thread(
+            &content_id,
             &requester_pubkey,
+            params.max_depth.unwrap_or(DEFAULT_THREAD_DEPTH),
             limit,
             params.before,
             params.after,
         )
         .await
     {
-        Ok(response_json) => {
-            // Parse the JSON response back to PaginatedPostsResponse
-            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
-                Ok(mentions_response) => Ok(Json(mentions_response)),
-                Err(err) => {
-                    log_error!("Failed to parse paginated mentions response: {}", err);
-                    let error = ApiError {
-                        error: "Internal server error".to_string(),
-                        code: "INTERNAL_ERROR".to_string(),
-                    };
-                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                }
-            }
-        }
-        Err(error_json) => {
-            // Parse the error response
-            match serde_json::from_str::<ApiError>(&error_json) {
+        Ok(response_json) => match serde_json::from_str::<ThreadResponse>(&response_json) {
+            Ok(thread_response) => Ok(Json(thread_response)),
+            Err(err) => {
+                log_error!("Failed to parse thread response: {}", err);
+                let error = ApiError {
+                    error: "Internal server error".to_string(),
+                    code: "INTERNAL_ERROR".to_string(),
+                };
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+            }
+        },
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_POST_ID" | "INVALID_USER_KEY"
+                        | "INVALID_PARAMETER" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -660,13 +1877,24 @@ async fn handle_get_mentions(
     }
 }
 
-async fn handle_get_notifications(
+async fn handle_get_mentions(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetNotificationsQuery>,
-) -> Result<Json<PaginatedNotificationsResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetMentionsQuery>,
+) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
+    // Check if user parameter is provided
+    let user_public_key = match params.user {
+        Some(user) => user,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: user".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
 
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
@@ -680,27 +1908,82 @@ async fn handle_get_notifications(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-mentions", params.limit)?;
+
+    // Use the API handler to get paginated mentions for the user with voting status
+    match app_state
+        .api_handlers
+        .get_mentions_paginated(
+            &user_public_key,
+            &requester_pubkey,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedPostsResponse
+            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
+                Ok(mentions_response) => Ok(Json(mentions_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated mentions response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
             }
-            limit
         }
+    }
+}
+
+async fn handle_get_notifications(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetNotificationsQuery>,
+) -> Result<Json<PaginatedNotificationsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-notifications", params.limit)?;
+
     // Use the API handler to get paginated notifications for the user
     match app_state
         .api_handlers
@@ -752,6 +2035,7 @@ async fn handle_get_hashtag_content(
 ) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "hashtags").await?;
 
     // Check if hashtag parameter is provided
     let hashtag = match params.hashtag {
@@ -782,17 +2066,8 @@ async fn handle_get_hashtag_content(
         return Err((StatusCode::BAD_REQUEST, Json(error)));
     }
 
-    // Limit defaults to 20 if not provided
-    let limit = params.limit.unwrap_or(20);
-
-    // Validate limit parameter
-    if limit < 1 || limit > 100 {
-        let error = ApiError {
-            error: "Limit parameter must be between 1 and 100".to_string(),
-            code: "INVALID_LIMIT".to_string(),
-        };
-        return Err((StatusCode::BAD_REQUEST, Json(error)));
-    }
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-hashtag-content", params.limit)?;
 
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
@@ -860,34 +2135,121 @@ async fn handle_get_hashtag_content(
     }
 }
 
-async fn handle_get_users(
+async fn handle_search_posts(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetUsersQuery>,
-) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<SearchPostsQuery>,
+) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
+    check_schema_feature(&app_state, "full_text_search").await?;
+
+    // Check if query parameter is provided
+    let search_query = match params.query {
+        Some(query) => query.trim().to_string(),
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: query".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
+    };
+
+    // Validate query length
+    if search_query.is_empty() {
+        let error = ApiError {
+            error: "Query parameter cannot be empty".to_string(),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    if search_query.chars().count() > MAX_SEARCH_QUERY_CHARS {
+        let error = ApiError {
+            error: format!(
+                "Query parameter cannot exceed {} characters",
+                MAX_SEARCH_QUERY_CHARS
+            ),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "search-posts", params.limit)?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
+    // Use the API handler to search posts and replies
+    match app_state
+        .api_handlers
+        .search_posts_paginated(
+            &search_query,
+            &requester_pubkey,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedPostsResponse
+            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
+                Ok(posts_response) => Ok(Json(posts_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated post search response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_users(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetUsersQuery>,
+) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-users", params.limit)?;
+
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
         Some(pubkey) => pubkey,
@@ -900,6 +2262,14 @@ async fn handle_get_users(
         }
     };
 
+    if params.before.is_none()
+        && params.after.is_none()
+        && let Some(users_response) =
+            cached_users_first_page(&app_state, limit, &requester_pubkey).await
+    {
+        return Ok(Json(users_response));
+    }
+
     // Use the API handler to get paginated user introduction posts with block status
     match app_state
         .api_handlers
@@ -945,6 +2315,209 @@ async fn handle_get_users(
     }
 }
 
+/// Block set part of the response cache keys of requesters blocking nobody
+const NO_BLOCKS_KEY: &str = "none";
+
+/// Requester of prewarmed pages: 66 hex characters like a real key but not a curve point, so it
+/// blocks nobody (follows and votes are looked up per request anyway)
+const PREWARM_REQUESTER: &str =
+    "020000000000000000000000000000000000000000000000000000000000000000";
+
+/// Time window of prewarmed trending hashtags, the default of /get-trending-hashtags
+const PREWARM_TRENDING_WINDOW: &str = "24h";
+
+/// Cache key part shared by requesters blocking the same users (None = serve uncached)
+async fn block_set_key(app_state: &AppState, requester_pubkey: &str) -> Option<String> {
+    if !activitypub::is_user_pubkey(requester_pubkey) {
+        // Invalid keys get their error from the uncached path
+        return None;
+    }
+    match app_state.db.get_block_set_hash(requester_pubkey).await {
+        Ok(hash) => Some(hash.unwrap_or_else(|| NO_BLOCKS_KEY.to_string())),
+        Err(e) => {
+            log_warn!("Failed to read block set, serving uncached: {}", e);
+            None
+        }
+    }
+}
+
+/// First page of /get-users as cached: the same for every requester with the given block set
+async fn shared_users_page(
+    app_state: Arc<AppState>,
+    limit: u32,
+    requester_pubkey: String,
+) -> Result<String, String> {
+    let json = app_state
+        .api_handlers
+        .get_users_paginated(limit, &requester_pubkey, None, None)
+        .await?;
+    let mut shared: PaginatedUsersResponse =
+        serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    for user in &mut shared.posts {
+        user.followed_user = Some(false);
+    }
+    serde_json::to_string(&shared).map_err(|e| e.to_string())
+}
+
+/// First page of /get-posts-watching as cached: the same for every requester with the given
+/// block set
+async fn shared_posts_watching_page(
+    app_state: Arc<AppState>,
+    limit: u32,
+    requester_pubkey: String,
+) -> Result<String, String> {
+    let json = app_state
+        .api_handlers
+        .get_posts_watching_paginated(&requester_pubkey, limit, None, None)
+        .await?;
+    let mut shared: PaginatedPostsResponse =
+        serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    for post in &mut shared.posts {
+        post.is_upvoted = Some(false);
+        post.is_downvoted = Some(false);
+    }
+    serde_json::to_string(&shared).map_err(|e| e.to_string())
+}
+
+async fn trending_hashtags_page(
+    app_state: Arc<AppState>,
+    time_window: String,
+    limit: u32,
+) -> Result<String, String> {
+    app_state
+        .api_handlers
+        .get_trending_hashtags(&time_window, limit)
+        .await
+}
+
+/// First page of /get-users from the response cache (None = serve it uncached)
+/// The cached page does not depend on the requester, who followed the listed users is
+/// looked up per request
+async fn cached_users_first_page(
+    app_state: &Arc<AppState>,
+    limit: u32,
+    requester_pubkey: &str,
+) -> Option<PaginatedUsersResponse> {
+    let cache = app_state.response_cache.as_ref()?;
+    let key = users_cache_key(limit, &block_set_key(app_state, requester_pubkey).await?);
+
+    let fetch_state = app_state.clone();
+    let fetch_requester = requester_pubkey.to_string();
+    let json = cache
+        .get_or_fetch("get-users", key, move || {
+            shared_users_page(fetch_state, limit, fetch_requester)
+        })
+        .await
+        .ok()?;
+
+    let mut users_response: PaginatedUsersResponse = serde_json::from_str(&json).ok()?;
+    let pubkeys: Vec<String> = users_response
+        .posts
+        .iter()
+        .map(|user| user.user_public_key.clone())
+        .collect();
+    let followed = match app_state
+        .db
+        .get_followed_among(requester_pubkey, &pubkeys)
+        .await
+    {
+        Ok(followed) => followed,
+        Err(e) => {
+            log_warn!("Failed to read followed users, serving uncached: {}", e);
+            return None;
+        }
+    };
+    for user in &mut users_response.posts {
+        user.followed_user = Some(followed.contains(&user.user_public_key));
+    }
+    Some(users_response)
+}
+
+/// First page of /get-posts-watching from the response cache (None = serve it uncached)
+/// Posts of blocked users are excluded through the block set in the key, the requester's own
+/// votes are looked up per request
+async fn cached_posts_watching_first_page(
+    app_state: &Arc<AppState>,
+    limit: u32,
+    requester_pubkey: &str,
+) -> Option<PaginatedPostsResponse> {
+    let cache = app_state.response_cache.as_ref()?;
+    let key = posts_watching_cache_key(limit, &block_set_key(app_state, requester_pubkey).await?);
+
+    let fetch_state = app_state.clone();
+    let fetch_requester = requester_pubkey.to_string();
+    let json = cache
+        .get_or_fetch("get-posts-watching", key, move || {
+            shared_posts_watching_page(fetch_state, limit, fetch_requester)
+        })
+        .await
+        .ok()?;
+
+    let mut posts_response: PaginatedPostsResponse = serde_json::from_str(&json).ok()?;
+    let post_ids: Vec<String> = posts_response
+        .posts
+        .iter()
+        .map(|post| post.id.clone())
+        .collect();
+    let votes = match app_state
+        .db
+        .get_requester_votes(requester_pubkey, &post_ids)
+        .await
+    {
+        Ok(votes) => votes,
+        Err(e) => {
+            log_warn!("Failed to read requester votes, serving uncached: {}", e);
+            return None;
+        }
+    };
+    for (post_id, vote) in votes {
+        if let Some(post) = posts_response
+            .posts
+            .iter_mut()
+            .find(|post| post.id == post_id)
+        {
+            match vote.as_str() {
+                "upvote" => post.is_upvoted = Some(true),
+                "downvote" => post.is_downvoted = Some(true),
+                _ => {}
+            }
+        }
+    }
+    Some(posts_response)
+}
+
+/// Trending hashtags from the response cache (None = serve them uncached)
+async fn cached_trending_hashtags(
+    app_state: &Arc<AppState>,
+    time_window: &str,
+    limit: u32,
+) -> Option<TrendingHashtagsResponse> {
+    let cache = app_state.response_cache.as_ref()?;
+    let key = trending_hashtags_cache_key(time_window, limit);
+
+    let fetch_state = app_state.clone();
+    let fetch_window = time_window.to_string();
+    let json = cache
+        .get_or_fetch("get-trending-hashtags", key, move || {
+            trending_hashtags_page(fetch_state, fetch_window, limit)
+        })
+        .await
+        .ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn users_cache_key(limit: u32, block_set: &str) -> String {
+    format!("get-users:{}:{}", limit, block_set)
+}
+
+fn posts_watching_cache_key(limit: u32, block_set: &str) -> String {
+    format!("get-posts-watching:{}:{}", limit, block_set)
+}
+
+fn trending_hashtags_cache_key(time_window: &str, limit: u32) -> String {
+    format!("get-trending-hashtags:{}:{}", time_window, limit)
+}
+
 async fn handle_get_most_active_users(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
@@ -977,26 +2550,8 @@ async fn handle_get_most_active_users(
         return Err((StatusCode::BAD_REQUEST, Json(error)));
     }
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-most-active-users", params.limit)?;
 
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
@@ -1063,52 +2618,65 @@ async fn handle_search_users(
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "search-users", params.limit)?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Check if requesterPubkey parameter is provided
-    let requester_pubkey = match params.requester_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
+    // Nickname lookup (mention autocomplete): one ranked page, without the other filters
+    let search_result = if let Some(query) = params.query {
+        let query = query.trim();
+        if query.is_empty() || query.chars().count() > MAX_NICKNAME_QUERY_CHARS {
             let error = ApiError {
-                error: "Missing required parameter: requesterPubkey".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
+                error: format!(
+                    "Query parameter must be 1 to {} characters",
+                    MAX_NICKNAME_QUERY_CHARS
+                ),
+                code: "INVALID_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
+        if params.searched_user_pubkey.is_some()
+            || params.searched_user_nickname.is_some()
+            || params.before.is_some()
+            || params.after.is_some()
+        {
+            let error = ApiError {
+                error: "Query parameter cannot be combined with searchedUserPubkey, searchedUserNickname, before or after".to_string(),
+                code: "INVALID_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+
+        app_state
+            .api_handlers
+            .search_users_by_nickname(limit, &requester_pubkey, query)
+            .await
+    } else {
+        app_state
+            .api_handlers
+            .search_users_paginated(
+                limit,
+                &requester_pubkey,
+                params.before,
+                params.after,
+                params.searched_user_pubkey,
+                params.searched_user_nickname,
+            )
+            .await
     };
 
-    // Use the API handler to search users
-    match app_state
-        .api_handlers
-        .search_users_paginated(
-            limit,
-            &requester_pubkey,
-            params.before,
-            params.after,
-            params.searched_user_pubkey,
-            params.searched_user_nickname,
-        )
-        .await
-    {
+    match search_result {
         Ok(response_json) => {
             // Parse the JSON response back to PaginatedUsersResponse
             match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
@@ -1131,9 +2699,8 @@ async fn handle_search_users(
                         "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
                             StatusCode::INTERNAL_SERVER_ERROR
                         }
-                        "MISSING_PARAMETER" | "INVALID_LIMIT" | "INVALID_USER_KEY" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_LIMIT"
+                        | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1169,33 +2736,61 @@ async fn handle_get_posts_watching(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
+    // Newest first by default, else ranked by engagement within a time window
+    let ranking = parse_post_ranking(params.sort.as_deref())?;
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-posts-watching", params.limit)?;
+
+    let result = match ranking {
+        Some(ranking) => {
+            // A ranked feed is a single page
+            if params.before.is_some() || params.after.is_some() {
+                let error = ApiError {
+                    error:
+                        "The before and after cursors are not supported with sort=top or sort=hot"
+                            .to_string(),
+                    code: "INVALID_PARAMETER".to_string(),
+                };
+                return Err((StatusCode::BAD_REQUEST, Json(error)));
+            }
+
+            let time_window = params.time_window.unwrap_or_else(|| "24h".to_string());
+            let valid_windows = ["1h", "6h", "24h", "7d", "30d"];
+            if !valid_windows.contains(&time_window.as_str()) {
                 let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
+                    error: format!(
+                        "Invalid timeWindow parameter. Must be one of: {}",
+                        valid_windows.join(", ")
+                    ),
+                    code: "INVALID_PARAMETER".to_string(),
                 };
                 return Err((StatusCode::BAD_REQUEST, Json(error)));
             }
-            limit
+
+            app_state
+                .api_handlers
+                .get_posts_watching_ranked(&requester_pubkey, ranking, &time_window, limit)
+                .await
         }
         None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
+            if params.before.is_none()
+                && params.after.is_none()
+                && let Some(posts_response) =
+                    cached_posts_watching_first_page(&app_state, limit, &requester_pubkey).await
+            {
+                return Ok(Json(posts_response));
+            }
+
+            // Use the API handler to get paginated posts for watching with voting status
+            app_state
+                .api_handlers
+                .get_posts_watching_paginated(&requester_pubkey, limit, params.before, params.after)
+                .await
         }
     };
 
-    // Use the API handler to get paginated posts for watching with voting status
-    match app_state
-        .api_handlers
-        .get_posts_watching_paginated(&requester_pubkey, limit, params.before, params.after)
-        .await
-    {
+    match result {
         Ok(response_json) => {
             // Parse the JSON response back to PaginatedPostsResponse
             match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
@@ -1218,9 +2813,8 @@ async fn handle_get_posts_watching(
                         "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
                             StatusCode::INTERNAL_SERVER_ERROR
                         }
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1237,11 +2831,11 @@ async fn handle_get_posts_watching(
     }
 }
 
-async fn handle_get_contents_following(
+async fn handle_get_posts_light(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetContentsFollowingQuery>,
-) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetPostsLightQuery>,
+) -> Result<Json<PaginatedPostsLightResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
     // Check if requesterPubkey parameter is provided
@@ -1256,39 +2850,20 @@ async fn handle_get_contents_following(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-posts-light", params.limit)?;
 
-    // Use the API handler to get paginated content from followed users
+    // Same posts as /get-posts-watching, without counts and profile data
     match app_state
         .api_handlers
-        .get_content_following_paginated(&requester_pubkey, limit, params.before, params.after)
+        .get_posts_light_paginated(&requester_pubkey, limit, params.before, params.after)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to PaginatedPostsResponse
-            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
+            match serde_json::from_str::<PaginatedPostsLightResponse>(&response_json) {
                 Ok(posts_response) => Ok(Json(posts_response)),
                 Err(err) => {
-                    log_error!("Failed to parse paginated content response: {}", err);
+                    log_error!("Failed to parse light posts response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -1305,9 +2880,8 @@ async fn handle_get_contents_following(
                         "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
                             StatusCode::INTERNAL_SERVER_ERROR
                         }
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1324,218 +2898,126 @@ async fn handle_get_contents_following(
     }
 }
 
-async fn handle_get_replies(
+async fn handle_get_posts_meta(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetRepliesQuery>,
-) -> Result<Json<PaginatedRepliesResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetPostsMetaQuery>,
+) -> Result<Json<PostsMetaResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-    // Check if requesterPubkey parameter is provided
-    let requester_pubkey = match params.requester_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
+    let (requester_pubkey, ids) = match (params.requester_pubkey, params.ids) {
+        (Some(pubkey), Some(ids)) => (pubkey, ids),
+        (None, _) => {
             let error = ApiError {
                 error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
-    };
-
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
+        (_, None) => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: ids".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Check if exactly one of post or user parameter is provided
-    match (params.post.as_ref(), params.user.as_ref()) {
-        (Some(post_id), None) => {
-            // Post replies mode: get replies to a specific post
-            match app_state
-                .api_handlers
-                .get_replies_paginated(
-                    post_id,
-                    &requester_pubkey,
-                    limit,
-                    params.before,
-                    params.after,
-                )
-                .await
-            {
-                Ok(response_json) => {
-                    match serde_json::from_str::<PaginatedRepliesResponse>(&response_json) {
-                        Ok(replies_response) => Ok(Json(replies_response)),
-                        Err(err) => {
-                            log_error!("Failed to parse paginated replies response: {}", err);
-                            let error = ApiError {
-                                error: "Internal server error".to_string(),
-                                code: "INTERNAL_ERROR".to_string(),
-                            };
-                            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                        }
-                    }
-                }
-                Err(error_json) => match serde_json::from_str::<ApiError>(&error_json) {
-                    Ok(api_error) => {
-                        let status_code = match api_error.code.as_str() {
-                            "MISSING_PARAMETER" | "INVALID_POST_ID" | "INVALID_USER_KEY"
-                            | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
-                            _ => StatusCode::INTERNAL_SERVER_ERROR,
-                        };
-                        Err((status_code, Json(api_error)))
-                    }
-                    Err(_) => {
-                        let error = ApiError {
-                            error: "Internal server error".to_string(),
-                            code: "INTERNAL_ERROR".to_string(),
-                        };
-                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                    }
-                },
+    // Counts, votes, profile data and quotes of posts loaded with /get-posts-light
+    match app_state
+        .api_handlers
+        .get_posts_meta(&requester_pubkey, &ids)
+        .await
+    {
+        Ok(response_json) => match serde_json::from_str::<PostsMetaResponse>(&response_json) {
+            Ok(meta_response) => Ok(Json(meta_response)),
+            Err(err) => {
+                log_error!("Failed to parse posts meta response: {}", err);
+                let error = ApiError {
+                    error: "Internal server error".to_string(),
+                    code: "INTERNAL_ERROR".to_string(),
+                };
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
             }
-        }
-        (None, Some(user_public_key)) => {
-            // User replies mode: get all replies made by a specific user
-            match app_state
-                .api_handlers
-                .get_user_replies_paginated(
-                    user_public_key,
-                    &requester_pubkey,
-                    limit,
-                    params.before,
-                    params.after,
-                )
-                .await
-            {
-                Ok(response_json) => {
-                    match serde_json::from_str::<PaginatedRepliesResponse>(&response_json) {
-                        Ok(replies_response) => Ok(Json(replies_response)),
-                        Err(err) => {
-                            log_error!("Failed to parse paginated user replies response: {}", err);
-                            let error = ApiError {
-                                error: "Internal server error".to_string(),
-                                code: "INTERNAL_ERROR".to_string(),
-                            };
-                            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+        },
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
+                            StatusCode::INTERNAL_SERVER_ERROR
                         }
-                    }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
                 }
-                Err(error_json) => match serde_json::from_str::<ApiError>(&error_json) {
-                    Ok(api_error) => {
-                        let status_code = match api_error.code.as_str() {
-                            "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                                StatusCode::BAD_REQUEST
-                            }
-                            _ => StatusCode::INTERNAL_SERVER_ERROR,
-                        };
-                        Err((status_code, Json(api_error)))
-                    }
-                    Err(_) => {
-                        let error = ApiError {
-                            error: "Internal server error".to_string(),
-                            code: "INTERNAL_ERROR".to_string(),
-                        };
-                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                    }
-                },
             }
         }
-        (Some(_), Some(_)) => {
-            // Both parameters provided - not allowed
-            let error = ApiError {
-                error: "Cannot provide both 'post' and 'user' parameters. Use 'post' for post replies or 'user' for user replies.".to_string(),
-                code: "INVALID_PARAMETERS".to_string(),
-            };
-            Err((StatusCode::BAD_REQUEST, Json(error)))
-        }
-        (None, None) => {
-            // Neither parameter provided - not allowed
-            let error = ApiError {
-                error: "Missing required parameter: either 'post' or 'user' must be provided"
-                    .to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            Err((StatusCode::BAD_REQUEST, Json(error)))
-        }
     }
 }
 
-async fn handle_get_user_details(
+async fn handle_get_posts_by_ids(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetUserDetailsQuery>,
-) -> Result<Json<ServerUserPost>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetPostsByIdsQuery>,
+) -> Result<Json<PostsByIdsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-
-    // Check if user parameter is provided
-    let user_public_key = match params.user {
-        Some(user) => user,
-        None => {
+    let (requester_pubkey, ids) = match (params.requester_pubkey, params.ids) {
+        (Some(pubkey), Some(ids)) => (pubkey, ids),
+        (None, _) => {
             let error = ApiError {
-                error: "Missing required parameter: user".to_string(),
+                error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
-    };
-
-    // Check if requesterPubkey parameter is provided
-    let requester_pubkey = match params.requester_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
+        (_, None) => {
             let error = ApiError {
-                error: "Missing required parameter: requesterPubkey".to_string(),
+                error: "Missing required parameter: ids".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Use the API handler to get user details
+    // Full contents of the listed IDs, e.g. to refresh posts cached by the client
     match app_state
         .api_handlers
-        .get_user_details(&user_public_key, &requester_pubkey)
+        .get_posts_by_ids(&requester_pubkey, &ids)
         .await
     {
-        Ok(response_json) => {
-            // Parse the JSON response back to ServerUserPost
-            match serde_json::from_str::<ServerUserPost>(&response_json) {
-                Ok(user_details_response) => Ok(Json(user_details_response)),
-                Err(err) => {
-                    log_error!("Failed to parse user details response: {}", err);
-                    let error = ApiError {
-                        error: "Internal server error".to_string(),
-                        code: "INTERNAL_ERROR".to_string(),
-                    };
-                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                }
+        Ok(response_json) => match serde_json::from_str::<PostsByIdsResponse>(&response_json) {
+            Ok(posts_response) => Ok(Json(posts_response)),
+            Err(err) => {
+                log_error!("Failed to parse posts by ids response: {}", err);
+                let error = ApiError {
+                    error: "Internal server error".to_string(),
+                    code: "INTERNAL_ERROR".to_string(),
+                };
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
             }
-        }
+        },
         Err(error_json) => {
             // Parse the error response
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
-                        "USER_NOT_FOUND" => StatusCode::NOT_FOUND,
+                        "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1552,14 +3034,14 @@ async fn handle_get_user_details(
     }
 }
 
-async fn handle_get_blocked_users(
+async fn handle_get_contents_following(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetBlockedUsersQuery>,
-) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetContentsFollowingQuery>,
+) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-
+    check_schema_feature(&app_state, "follows").await?;
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
         Some(pubkey) => pubkey,
@@ -1572,39 +3054,21 @@ async fn handle_get_blocked_users(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-contents-following", params.limit)?;
 
-    // Use the API handler to get paginated blocked users
+    // Use the API handler to get paginated content from followed users
     match app_state
         .api_handlers
-        .get_blocked_users_paginated(&requester_pubkey, limit, params.before, params.after)
+        .get_content_following_paginated(&requester_pubkey, limit, params.before, params.after)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to PaginatedUsersResponse
-            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
-                Ok(users_response) => Ok(Json(users_response)),
+            // Parse the JSON response back to PaginatedPostsResponse
+            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
+                Ok(posts_response) => Ok(Json(posts_response)),
                 Err(err) => {
-                    log_error!("Failed to parse paginated blocked users response: {}", err);
+                    log_error!("Failed to parse paginated content response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -1618,6 +3082,9 @@ async fn handle_get_blocked_users(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
+                        "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        }
                         "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
                             StatusCode::BAD_REQUEST
                         }
@@ -1637,14 +3104,14 @@ async fn handle_get_blocked_users(
     }
 }
 
-async fn handle_get_followed_users(
+async fn handle_get_posts_following(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetFollowedUsersQuery>,
-) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetPostsFollowingQuery>,
+) -> Result<Json<PaginatedPostsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-
+    check_schema_feature(&app_state, "follows").await?;
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
         Some(pubkey) => pubkey,
@@ -1657,39 +3124,21 @@ async fn handle_get_followed_users(
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-posts-following", params.limit)?;
 
-    // Use the API handler to get paginated followed users
+    // Use the API handler to get paginated posts from followed users
     match app_state
         .api_handlers
-        .get_followed_users_paginated(&requester_pubkey, limit, params.before, params.after)
+        .get_posts_following_paginated(&requester_pubkey, limit, params.before, params.after)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to PaginatedUsersResponse
-            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
-                Ok(users_response) => Ok(Json(users_response)),
+            // Parse the JSON response back to PaginatedPostsResponse
+            match serde_json::from_str::<PaginatedPostsResponse>(&response_json) {
+                Ok(posts_response) => Ok(Json(posts_response)),
                 Err(err) => {
-                    log_error!("Failed to parse paginated followed users response: {}", err);
+                    log_error!("Failed to parse paginated content response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -1703,6 +3152,9 @@ async fn handle_get_followed_users(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
+                        "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        }
                         "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
                             StatusCode::BAD_REQUEST
                         }
@@ -1722,14 +3174,13 @@ async fn handle_get_followed_users(
     }
 }
 
-async fn handle_get_users_following(
+async fn handle_get_replies(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetUsersFollowingQuery>,
-) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetRepliesQuery>,
+) -> Result<Json<PaginatedRepliesResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-
     // Check if requesterPubkey parameter is provided
     let requester_pubkey = match params.requester_pubkey {
         Some(pubkey) => pubkey,
@@ -1742,60 +3193,173 @@ async fn handle_get_users_following(
         }
     };
 
-    // Check if userPubkey parameter is provided
-    let user_pubkey = match params.user_pubkey {
-        Some(pubkey) => pubkey,
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-replies", params.limit)?;
+
+    let include = IncludeOptions {
+        replies: validate_include_replies(params.include_replies)?,
+        vote_weights: include_vote_weights(&app_state, params.include_vote_weights).await,
+    };
+
+    // Check if exactly one of post or user parameter is provided
+    match (params.post.as_ref(), params.user.as_ref()) {
+        (Some(post_id), None) => {
+            // Post replies mode: get replies to a specific post
+            match app_state
+                .api_handlers
+                .get_replies_paginated(
+                    post_id,
+                    &requester_pubkey,
+                    limit,
+                    params.before,
+                    params.after,
+                    include,
+                )
+                .await
+            {
+                Ok(response_json) => {
+                    match serde_json::from_str::<PaginatedRepliesResponse>(&response_json) {
+                        Ok(replies_response) => Ok(Json(replies_response)),
+                        Err(err) => {
+                            log_error!("Failed to parse paginated replies response: {}", err);
+                            let error = ApiError {
+                                error: "Internal server error".to_string(),
+                                code: "INTERNAL_ERROR".to_string(),
+                            };
+                            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                        }
+                    }
+                }
+                Err(error_json) => match serde_json::from_str::<ApiError>(&error_json) {
+                    Ok(api_error) => {
+                        let status_code = match api_error.code.as_str() {
+                            "MISSING_PARAMETER" | "INVALID_POST_ID" | "INVALID_USER_KEY"
+                            | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                            _ => StatusCode::INTERNAL_SERVER_ERROR,
+                        };
+                        Err((status_code, Json(api_error)))
+                    }
+                    Err(_) => {
+                        let error = ApiError {
+                            error: "Internal server error".to_string(),
+                            code: "INTERNAL_ERROR".to_string(),
+                        };
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                    }
+                },
+            }
+        }
+        (None, Some(user_public_key)) => {
+            // User replies mode: get all replies made by a specific user
+            match app_state
+                .api_handlers
+                .get_user_replies_paginated(
+                    user_public_key,
+                    &requester_pubkey,
+                    limit,
+                    params.before,
+                    params.after,
+                    include,
+                )
+                .await
+            {
+                Ok(response_json) => {
+                    match serde_json::from_str::<PaginatedRepliesResponse>(&response_json) {
+                        Ok(replies_response) => Ok(Json(replies_response)),
+                        Err(err) => {
+                            log_error!("Failed to parse paginated user replies response: {}", err);
+                            let error = ApiError {
+                                error: "Internal server error".to_string(),
+                                code: "INTERNAL_ERROR".to_string(),
+                            };
+                            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                        }
+                    }
+                }
+                Err(error_json) => match serde_json::from_str::<ApiError>(&error_json) {
+                    Ok(api_error) => {
+                        let status_code = match api_error.code.as_str() {
+                            "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                                StatusCode::BAD_REQUEST
+                            }
+                            _ => StatusCode::INTERNAL_SERVER_ERROR,
+                        };
+                        Err((status_code, Json(api_error)))
+                    }
+                    Err(_) => {
+                        let error = ApiError {
+                            error: "Internal server error".to_string(),
+                            code: "INTERNAL_ERROR".to_string(),
+                        };
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                    }
+                },
+            }
+        }
+        (Some(_), Some(_)) => {
+            // Both parameters provided - not allowed
+            let error = ApiError {
+                error: "Cannot provide both 'post' and 'user' parameters. Use 'post' for post replies or 'user' for user replies.".to_string(),
+                code: "INVALID_PARAMETERS".to_string(),
+            };
+            Err((StatusCode::BAD_REQUEST, Json(error)))
+        }
+        (None, None) => {
+            // Neither parameter provided - not allowed
+            let error = ApiError {
+                error: "Missing required parameter: either 'post' or 'user' must be provided"
+                    .to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            Err((StatusCode::BAD_REQUEST, Json(error)))
+        }
+    }
+}
+
+async fn handle_get_user_details(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetUserDetailsQuery>,
+) -> Result<Json<ServerUserPost>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Check if user parameter is provided
+    let user_public_key = match params.user {
+        Some(user) => user,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: userPubkey".to_string(),
+                error: "Missing required parameter: user".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Use the API handler to get paginated users following
+    // Use the API handler to get user details
     match app_state
         .api_handlers
-        .get_users_following_paginated(
-            &requester_pubkey,
-            &user_pubkey,
-            limit,
-            params.before,
-            params.after,
-        )
+        .get_user_details(&user_public_key, &requester_pubkey)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to PaginatedUsersResponse
-            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
-                Ok(users_response) => Ok(Json(users_response)),
+            // Parse the JSON response back to ServerUserPost
+            match serde_json::from_str::<ServerUserPost>(&response_json) {
+                Ok(user_details_response) => Ok(Json(user_details_response)),
                 Err(err) => {
-                    log_error!(
-                        "Failed to parse paginated users following response: {}",
-                        err
-                    );
+                    log_error!("Failed to parse user details response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -1809,9 +3373,8 @@ async fn handle_get_users_following(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        "USER_NOT_FOUND" => StatusCode::NOT_FOUND,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1828,80 +3391,38 @@ async fn handle_get_users_following(
     }
 }
 
-async fn handle_get_users_followers(
+async fn handle_get_user_stats(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetUsersFollowersQuery>,
-) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetUserStatsQuery>,
+) -> Result<Json<UserStatsResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
 
-    // Check if requesterPubkey parameter is provided
-    let requester_pubkey = match params.requester_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: requesterPubkey".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
-
-    // Check if userPubkey parameter is provided
-    let user_pubkey = match params.user_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
-            let error = ApiError {
-                error: "Missing required parameter: userPubkey".to_string(),
-                code: "MISSING_PARAMETER".to_string(),
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(error)));
-        }
-    };
-
-    // Validate required limit parameter
-    let limit = match params.limit {
-        Some(limit) => {
-            if limit < 1 || limit > 100 {
-                let error = ApiError {
-                    error: "Limit parameter must be between 1 and 100".to_string(),
-                    code: "INVALID_LIMIT".to_string(),
-                };
-                return Err((StatusCode::BAD_REQUEST, Json(error)));
-            }
-            limit
-        }
+    // Check if user parameter is provided
+    let user_public_key = match params.user {
+        Some(user) => user,
         None => {
             let error = ApiError {
-                error: "Missing required parameter: limit".to_string(),
+                error: "Missing required parameter: user".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
     };
 
-    // Use the API handler to get paginated users followers
+    // Use the API handler to get the totals of the user
     match app_state
         .api_handlers
-        .get_users_followers_paginated(
-            &requester_pubkey,
-            &user_pubkey,
-            limit,
-            params.before,
-            params.after,
-        )
+        .get_user_stats(&user_public_key)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to PaginatedUsersResponse
-            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
-                Ok(users_response) => Ok(Json(users_response)),
+            // Parse the JSON response back to UserStatsResponse
+            match serde_json::from_str::<UserStatsResponse>(&response_json) {
+                Ok(stats_response) => Ok(Json(stats_response)),
                 Err(err) => {
-                    log_error!(
-                        "Failed to parse paginated users followers response: {}",
-                        err
-                    );
+                    log_error!("Failed to parse user stats response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -1915,9 +3436,7 @@ async fn handle_get_users_followers(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
-                            StatusCode::BAD_REQUEST
-                        }
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1934,52 +3453,59 @@ async fn handle_get_users_followers(
     }
 }
 
-async fn handle_get_notifications_count(
+async fn handle_get_users_by_keys(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetNotificationsCountQuery>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetUsersByKeysQuery>,
+) -> Result<Json<UsersResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
-
-    // Check if requesterPubkey parameter is provided
-    let requester_pubkey = match params.requester_pubkey {
-        Some(pubkey) => pubkey,
-        None => {
+    let (requester_pubkey, pubkeys) = match (params.requester_pubkey, params.pubkeys) {
+        (Some(requester_pubkey), Some(pubkeys)) => (requester_pubkey, pubkeys),
+        (None, _) => {
             let error = ApiError {
                 error: "Missing required parameter: requesterPubkey".to_string(),
                 code: "MISSING_PARAMETER".to_string(),
             };
             return Err((StatusCode::BAD_REQUEST, Json(error)));
         }
+        (_, None) => {
+            let error = ApiError {
+                error: "Missing required parameter: pubkeys".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
     };
 
-    // Use the API handler to get notification count
+    // Profiles of the authors of a timeline in one round trip
     match app_state
         .api_handlers
-        .get_notification_count(&requester_pubkey, params.after)
+        .get_users_by_keys(&requester_pubkey, &pubkeys)
         .await
     {
-        Ok(response_json) => {
-            // Parse the JSON response back to a generic JSON value
-            match serde_json::from_str::<serde_json::Value>(&response_json) {
-                Ok(response) => Ok(Json(response)),
-                Err(err) => {
-                    log_error!("Failed to parse notification count response: {}", err);
-                    let error = ApiError {
-                        error: "Internal server error".to_string(),
-                        code: "INTERNAL_ERROR".to_string(),
-                    };
-                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
-                }
+        Ok(response_json) => match serde_json::from_str::<UsersResponse>(&response_json) {
+            Ok(users_response) => Ok(Json(users_response)),
+            Err(err) => {
+                log_error!("Failed to parse users by keys response: {}", err);
+                let error = ApiError {
+                    error: "Internal server error".to_string(),
+                    code: "INTERNAL_ERROR".to_string(),
+                };
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
             }
-        }
+        },
         Err(error_json) => {
             // Parse the error response
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        "DATABASE_ERROR" | "SERIALIZATION_ERROR" => {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        }
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY" => {
+                            StatusCode::BAD_REQUEST
+                        }
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -1996,22 +3522,53 @@ async fn handle_get_notifications_count(
     }
 }
 
-async fn handle_get_users_count(
+async fn handle_get_profile(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(_params): Query<GetUsersCountQuery>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetProfileQuery>,
+) -> Result<Json<ProfileResponse>, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
 
-    // Use the API handler to get users count
-    match app_state.api_handlers.get_users_count().await {
+    // Check if user parameter is provided
+    let user_public_key = match params.user {
+        Some(user) => user,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: user".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // The posts page follows the page sizes of /get-posts
+    let limit = resolve_limit(&app_state, "get-posts", params.limit)?;
+
+    // Use the API handler to get the user details and first page of posts together
+    match app_state
+        .api_handlers
+        .get_profile(&user_public_key, &requester_pubkey, limit)
+        .await
+    {
         Ok(response_json) => {
-            // Parse the JSON response back to a generic JSON value
-            match serde_json::from_str::<serde_json::Value>(&response_json) {
-                Ok(response) => Ok(Json(response)),
+            // Parse the JSON response back to ProfileResponse
+            match serde_json::from_str::<ProfileResponse>(&response_json) {
+                Ok(profile_response) => Ok(Json(profile_response)),
                 Err(err) => {
-                    log_error!("Failed to parse users count response: {}", err);
+                    log_error!("Failed to parse profile response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -2025,6 +3582,10 @@ async fn handle_get_users_count(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        "USER_NOT_FOUND" => StatusCode::NOT_FOUND,
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -2041,54 +3602,182 @@ async fn handle_get_users_count(
     }
 }
 
-async fn handle_get_trending_hashtags(
+async fn handle_get_profile_image(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     State(app_state): State<Arc<AppState>>,
-    Query(params): Query<GetTrendingHashtagsQuery>,
-) -> Result<Json<TrendingHashtagsResponse>, (StatusCode, Json<ApiError>)> {
+    Query(params): Query<GetProfileImageQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
     // Check rate limit first
     check_rate_limit(&app_state, addr).await?;
 
-    // Parse and validate time_window parameter (default: "24h")
-    let time_window = params.time_window.unwrap_or_else(|| "24h".to_string());
+    // Check if user parameter is provided
+    let user_public_key = match params.user {
+        Some(user) => user,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: user".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
 
-    // Validate time_window values
-    let valid_windows = ["1h", "6h", "24h", "7d", "30d"];
-    if !valid_windows.contains(&time_window.as_str()) {
+    // Validate user public key format (66 hex characters, compressed key prefix 02/03)
+    if user_public_key.len() != 66
+        || !user_public_key.chars().all(|c| c.is_ascii_hexdigit())
+        || !(user_public_key.starts_with("02") || user_public_key.starts_with("03"))
+    {
         let error = ApiError {
-            error: format!(
-                "Invalid timeWindow parameter. Must be one of: {}",
-                valid_windows.join(", ")
-            ),
-            code: "INVALID_PARAMETER".to_string(),
+            error:
+                "Invalid user public key format. Must be 66 hex characters starting with 02 or 03."
+                    .to_string(),
+            code: "INVALID_USER_KEY".to_string(),
         };
         return Err((StatusCode::BAD_REQUEST, Json(error)));
     }
 
-    // Limit defaults to 20 if not provided
-    let limit = params.limit.unwrap_or(20);
-
-    // Validate limit parameter
-    if limit < 1 || limit > 100 {
+    let not_found = || {
         let error = ApiError {
-            error: "Limit parameter must be between 1 and 100".to_string(),
-            code: "INVALID_LIMIT".to_string(),
+            error: "Profile image not found".to_string(),
+            code: "IMAGE_NOT_FOUND".to_string(),
+        };
+        (StatusCode::NOT_FOUND, Json(error))
+    };
+    let internal_error = || {
+        let error = ApiError {
+            error: "Internal server error".to_string(),
+            code: "INTERNAL_ERROR".to_string(),
+        };
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error))
+    };
+
+    let record = match app_state.db.get_profile_image(&user_public_key).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(not_found()),
+        Err(e) => {
+            log_error!("Failed to get profile image: {}", e);
+            return Err(internal_error());
+        }
+    };
+
+    let etag = record
+        .profile_image_hash
+        .as_ref()
+        .map(|hash| format!("\"{}\"", hex::encode(hash)));
+
+    // Image kept in blob storage: proxy from the shared directory or redirect to the bucket URL
+    if let Some(image_ref) = record.profile_image_ref {
+        // References are written by the processor, but never follow path traversal
+        if image_ref
+            .split('/')
+            .any(|part| part == ".." || part.is_empty())
+        {
+            log_warn!("Rejected invalid profile image reference: {}", image_ref);
+            return Err(not_found());
+        }
+
+        return match &app_state.server_config.image_storage {
+            ImageStorageConfig::S3 { base_url } => {
+                Ok(Redirect::temporary(&format!("{}/{}", base_url, image_ref)).into_response())
+            }
+            ImageStorageConfig::Filesystem { path } => {
+                match tokio::fs::read(std::path::Path::new(path).join(&image_ref)).await {
+                    Ok(bytes) => Ok(profile_image_response(bytes, etag)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(not_found()),
+                    Err(e) => {
+                        log_error!("Failed to read profile image {}: {}", image_ref, e);
+                        Err(internal_error())
+                    }
+                }
+            }
+            ImageStorageConfig::Database => {
+                log_warn!(
+                    "Profile image {} is stored externally but --image-storage is 'database'",
+                    image_ref
+                );
+                Err(not_found())
+            }
         };
-        return Err((StatusCode::BAD_REQUEST, Json(error)));
     }
 
-    // Use the API handler to get trending hashtags
+    // Image stored inline as base64 in k_broadcasts
+    match record.base64_encoded_profile_image {
+        Some(encoded) if !encoded.is_empty() => match Base64::decode_vec(&encoded) {
+            Ok(bytes) => Ok(profile_image_response(bytes, etag)),
+            Err(_) => Err(not_found()),
+        },
+        _ => Err(not_found()),
+    }
+}
+
+fn profile_image_response(bytes: Vec<u8>, etag: Option<String>) -> Response {
+    let mut response = (
+        [
+            (header::CONTENT_TYPE, image_content_type(&bytes)),
+            (header::CACHE_CONTROL, "public, max-age=300"),
+        ],
+        bytes,
+    )
+        .into_response();
+
+    if let Some(etag) = etag.and_then(|etag| etag.parse().ok()) {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+
+    response
+}
+
+// Detect the image format from its magic bytes (profile images carry no MIME type)
+fn image_content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+async fn handle_get_blocked_users(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetBlockedUsersQuery>,
+) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "blocks").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-blocked-users", params.limit)?;
+
+    // Use the API handler to get paginated blocked users
     match app_state
         .api_handlers
-        .get_trending_hashtags(&time_window, limit)
+        .get_blocked_users_paginated(&requester_pubkey, limit, params.before, params.after)
         .await
     {
         Ok(response_json) => {
-            // Parse the JSON response back to TrendingHashtagsResponse
-            match serde_json::from_str::<TrendingHashtagsResponse>(&response_json) {
-                Ok(response) => Ok(Json(response)),
+            // Parse the JSON response back to PaginatedUsersResponse
+            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
+                Ok(users_response) => Ok(Json(users_response)),
                 Err(err) => {
-                    log_error!("Failed to parse trending hashtags response: {}", err);
+                    log_error!("Failed to parse paginated blocked users response: {}", err);
                     let error = ApiError {
                         error: "Internal server error".to_string(),
                         code: "INTERNAL_ERROR".to_string(),
@@ -2102,7 +3791,9 @@ async fn handle_get_trending_hashtags(
             match serde_json::from_str::<ApiError>(&error_json) {
                 Ok(api_error) => {
                     let status_code = match api_error.code.as_str() {
-                        "INVALID_PARAMETER" | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
                         _ => StatusCode::INTERNAL_SERVER_ERROR,
                     };
                     Err((status_code, Json(api_error)))
@@ -2118,3 +3809,3092 @@ async fn handle_get_trending_hashtags(
         }
     }
 }
+
+async fn handle_get_followed_users(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetFollowedUsersQuery>,
+) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "follows").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-followed-users", params.limit)?;
+
+    // Use the API handler to get paginated followed users
+    match app_state
+        .api_handlers
+        .get_followed_users_paginated(&requester_pubkey, limit, params.before, params.after)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedUsersResponse
+            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
+                Ok(users_response) => Ok(Json(users_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated followed users response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_users_following(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetUsersFollowingQuery>,
+) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "follows").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if userPubkey parameter is provided
+    let user_pubkey = match params.user_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: userPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-users-following", params.limit)?;
+
+    // Use the API handler to get paginated users following
+    match app_state
+        .api_handlers
+        .get_users_following_paginated(
+            &requester_pubkey,
+            &user_pubkey,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedUsersResponse
+            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
+                Ok(users_response) => Ok(Json(users_response)),
+                Err(err) => {
+                    log_error!(
+                        "Failed to parse paginated users following response: {}",
+                        err
+                    );
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_users_followers(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetUsersFollowersQuery>,
+) -> Result<Json<PaginatedUsersResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "follows").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if userPubkey parameter is provided
+    let user_pubkey = match params.user_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: userPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-users-followers", params.limit)?;
+
+    // Use the API handler to get paginated users followers
+    match app_state
+        .api_handlers
+        .get_users_followers_paginated(
+            &requester_pubkey,
+            &user_pubkey,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedUsersResponse
+            match serde_json::from_str::<PaginatedUsersResponse>(&response_json) {
+                Ok(users_response) => Ok(Json(users_response)),
+                Err(err) => {
+                    log_error!(
+                        "Failed to parse paginated users followers response: {}",
+                        err
+                    );
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_notifications_count(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetNotificationsCountQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to get notification count
+    match app_state
+        .api_handlers
+        .get_notification_count(&requester_pubkey, params.after)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to a generic JSON value
+            match serde_json::from_str::<serde_json::Value>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse notification count response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_users_count(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(_params): Query<GetUsersCountQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Use the API handler to get users count
+    match app_state.api_handlers.get_users_count().await {
+        Ok(response_json) => {
+            // Parse the JSON response back to a generic JSON value
+            match serde_json::from_str::<serde_json::Value>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse users count response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_trending_hashtags(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetTrendingHashtagsQuery>,
+) -> Result<Json<TrendingHashtagsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "hashtags").await?;
+
+    // Parse and validate time_window parameter (default: "24h")
+    let time_window = params.time_window.unwrap_or_else(|| "24h".to_string());
+
+    // Validate time_window values
+    let valid_windows = ["1h", "6h", "24h", "7d", "30d"];
+    if !valid_windows.contains(&time_window.as_str()) {
+        let error = ApiError {
+            error: format!(
+                "Invalid timeWindow parameter. Must be one of: {}",
+                valid_windows.join(", ")
+            ),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-trending-hashtags", params.limit)?;
+
+    if let Some(response) = cached_trending_hashtags(&app_state, &time_window, limit).await {
+        return Ok(Json(response));
+    }
+
+    // Use the API handler to get trending hashtags
+    match app_state
+        .api_handlers
+        .get_trending_hashtags(&time_window, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to TrendingHashtagsResponse
+            match serde_json::from_str::<TrendingHashtagsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse trending hashtags response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_PARAMETER" | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_groups(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetGroupsQuery>,
+) -> Result<Json<PaginatedGroupsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "groups").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-groups", params.limit)?;
+
+    // Use the API handler to get paginated groups visible to the requester
+    match app_state
+        .api_handlers
+        .get_groups_paginated(
+            &requester_pubkey,
+            params.member.as_deref(),
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedGroupsResponse
+            match serde_json::from_str::<PaginatedGroupsResponse>(&response_json) {
+                Ok(groups_response) => Ok(Json(groups_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated groups response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" | "INVALID_LIMIT" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_group_posts(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetGroupPostsQuery>,
+) -> Result<Json<PaginatedGroupPostsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "groups").await?;
+
+    // Check if groupId parameter is provided
+    let group_id = match params.group_id {
+        Some(group_id) => group_id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: groupId".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-group-posts", params.limit)?;
+
+    // Use the API handler to get paginated group posts (membership checked for closed groups)
+    match app_state
+        .api_handlers
+        .get_group_posts_paginated(
+            &group_id,
+            &requester_pubkey,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedGroupPostsResponse
+            match serde_json::from_str::<PaginatedGroupPostsResponse>(&response_json) {
+                Ok(posts_response) => Ok(Json(posts_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated group posts response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_GROUP_ID" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        "NOT_GROUP_MEMBER" => StatusCode::FORBIDDEN,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_events(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetEventsQuery>,
+) -> Result<Json<PaginatedEventsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "events").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Upcoming events by default
+    let upcoming = match params.when.as_deref().unwrap_or("upcoming") {
+        "upcoming" => true,
+        "past" => false,
+        other => {
+            let error = ApiError {
+                error: format!(
+                    "Invalid when parameter '{}'. Valid values: upcoming, past",
+                    other
+                ),
+                code: "INVALID_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-events", params.limit)?;
+
+    // Use the API handler to get paginated events with RSVP counts
+    match app_state
+        .api_handlers
+        .get_events_paginated(
+            &requester_pubkey,
+            params.organizer.as_deref(),
+            upcoming,
+            limit,
+            params.before,
+            params.after,
+        )
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to PaginatedEventsResponse
+            match serde_json::from_str::<PaginatedEventsResponse>(&response_json) {
+                Ok(events_response) => Ok(Json(events_response)),
+                Err(err) => {
+                    log_error!("Failed to parse paginated events response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_article(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetArticleQuery>,
+) -> Result<Json<ArticleDetailsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "articles").await?;
+
+    // Check if id parameter is provided
+    let article_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to get the article with its completeness status
+    match app_state
+        .api_handlers
+        .get_article(&article_id, &requester_pubkey)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ArticleDetailsResponse
+            match serde_json::from_str::<ArticleDetailsResponse>(&response_json) {
+                Ok(article_response) => Ok(Json(article_response)),
+                Err(err) => {
+                    log_error!("Failed to parse article response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_ARTICLE_ID" | "INVALID_USER_KEY" => {
+                            StatusCode::BAD_REQUEST
+                        }
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_track_view(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<TrackViewQuery>,
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "views").await?;
+
+    // Check if id parameter is provided
+    let post_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Viewers are deduplicated by IP address
+    match app_state.api_handlers.track_view(&post_id, addr.ip()).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_live_feed(
+    ws: WebSocketUpgrade,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<LiveFeedQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "live_events").await?;
+
+    let subscription = match (params.user, params.post) {
+        (Some(_), Some(_)) => {
+            let error = ApiError {
+                error: "Subscribe to either user or post, not both".to_string(),
+                code: "INVALID_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+        (Some(user), None) => {
+            if user.len() != 66 || !user.chars().all(|c| c.is_ascii_hexdigit()) {
+                let error = ApiError {
+                    error: "Invalid user public key format. Must be 66 hex characters.".to_string(),
+                    code: "INVALID_USER_KEY".to_string(),
+                };
+                return Err((StatusCode::BAD_REQUEST, Json(error)));
+            }
+            LiveSubscription::User(user.to_lowercase())
+        }
+        (None, Some(post)) => {
+            if post.len() != 64 || !post.chars().all(|c| c.is_ascii_hexdigit()) {
+                let error = ApiError {
+                    error: "Invalid post ID format. Must be 64 hex characters.".to_string(),
+                    code: "INVALID_POST_ID".to_string(),
+                };
+                return Err((StatusCode::BAD_REQUEST, Json(error)));
+            }
+            LiveSubscription::Thread(post.to_lowercase())
+        }
+        (None, None) => LiveSubscription::Watching,
+    };
+
+    // Only routed with a live feed
+    let Some(live_feed) = &app_state.live_feed else {
+        return Err(internal_error_response());
+    };
+    let Some(client) = live_feed.subscribe() else {
+        let error = ApiError {
+            error: "Live feed is full, try again later".to_string(),
+            code: "LIVE_FEED_FULL".to_string(),
+        };
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error)));
+    };
+
+    Ok(ws.on_upgrade(move |socket| stream_live_events(socket, client, subscription)))
+}
+
+/// Push the events matching the subscription until the client leaves or falls too far
+/// behind (it then reconnects and catches up with the paginated endpoints)
+async fn stream_live_events(
+    mut socket: WebSocket,
+    mut client: LiveClient,
+    subscription: LiveSubscription,
+) {
+    let mut ping = tokio::time::interval(LIVE_FEED_PING_INTERVAL);
+    loop {
+        tokio::select! {
+            event = client.events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log_warn!("Closing a live feed connection {} events behind", missed);
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if !subscription.matches(&event) {
+                    continue;
+                }
+                let Ok(text) = serde_json::to_string(event.as_ref()) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                // Client messages are ignored, pongs are answered by axum
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn handle_notification_stream(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<NotificationStreamQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "live_events").await?;
+
+    let Some(requester_pubkey) = params.requester_pubkey else {
+        let error = ApiError {
+            error: "Missing required parameter: requesterPubkey".to_string(),
+            code: "MISSING_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    };
+    if requester_pubkey.len() != 66 || !requester_pubkey.chars().all(|c| c.is_ascii_hexdigit()) {
+        let error = ApiError {
+            error: "Invalid requester public key format. Must be 66 hex characters.".to_string(),
+            code: "INVALID_USER_KEY".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    // Only routed with a live feed
+    let Some(live_feed) = &app_state.live_feed else {
+        return Err(internal_error_response());
+    };
+    let Some(client) = live_feed.subscribe() else {
+        let error = ApiError {
+            error: "Live feed is full, try again later".to_string(),
+            code: "LIVE_FEED_FULL".to_string(),
+        };
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(error)));
+    };
+
+    let subscription = LiveSubscription::Notifications(requester_pubkey.to_lowercase());
+    Ok(Sse::new(notification_events(client, subscription))
+        .keep_alive(KeepAlive::new().interval(LIVE_FEED_PING_INTERVAL))
+        .into_response())
+}
+
+/// POST /graphql: a query of the read-only GraphQL schema, answered with its data and errors
+/// (argument errors carry the code of the matching REST error in their "code" extension)
+async fn handle_graphql(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Only routed with a schema
+    let Some(schema) = &app_state.graphql else {
+        return Err(internal_error_response());
+    };
+    Ok(Json(schema.execute(request.data(app_state.clone())).await))
+}
+
+/// GET /graphql: the schema in SDL, for client code generation
+async fn handle_graphql_sdl(State(app_state): State<Arc<AppState>>) -> Response {
+    match &app_state.graphql {
+        Some(schema) => schema.sdl().into_response(),
+        None => internal_error_response().into_response(),
+    }
+}
+
+/// Server-Sent "notification" events matching the subscription, until the client leaves or
+/// falls too far behind (it then reconnects and catches up with get-notifications)
+fn notification_events(
+    client: LiveClient,
+    subscription: LiveSubscription,
+) -> impl Stream<Item = Result<Event, axum::Error>> {
+    stream::unfold(
+        (client, subscription),
+        |(mut client, subscription)| async move {
+            loop {
+                match client.events.recv().await {
+                    Ok(event) if subscription.matches(&event) => {
+                        let event = Event::default()
+                            .event("notification")
+                            .json_data(event.as_ref());
+                        return Some((event, (client, subscription)));
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log_warn!("Closing a notification stream {} events behind", missed);
+                        return None;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    )
+}
+
+async fn handle_get_post_mentions(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetPostMentionsQuery>,
+) -> Result<Json<PostMentionsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Check if id parameter is provided
+    let post_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to get the full list of mentioned pubkeys
+    match app_state.api_handlers.get_post_mentions(&post_id).await {
+        Ok(response_json) => {
+            // Parse the JSON response back to PostMentionsResponse
+            match serde_json::from_str::<PostMentionsResponse>(&response_json) {
+                Ok(mentions_response) => Ok(Json(mentions_response)),
+                Err(err) => {
+                    log_error!("Failed to parse post mentions response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_post_tips(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetPostTipsQuery>,
+) -> Result<Json<PostTipsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "tips").await?;
+
+    // Check if id parameter is provided
+    let post_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to get the tip totals of the content
+    match app_state.api_handlers.get_post_tips(&post_id).await {
+        Ok(response_json) => {
+            // Parse the JSON response back to PostTipsResponse
+            match serde_json::from_str::<PostTipsResponse>(&response_json) {
+                Ok(tips_response) => Ok(Json(tips_response)),
+                Err(err) => {
+                    log_error!("Failed to parse post tips response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_top_tipped_posts(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetTopTippedPostsQuery>,
+) -> Result<Json<TopTippedPostsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "tips").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Parse and validate time_window parameter (default: "24h")
+    let time_window = params.time_window.unwrap_or_else(|| "24h".to_string());
+
+    // Validate time_window values
+    let valid_windows = ["1h", "6h", "24h", "7d", "30d"];
+    if !valid_windows.contains(&time_window.as_str()) {
+        let error = ApiError {
+            error: format!(
+                "Invalid timeWindow parameter. Must be one of: {}",
+                valid_windows.join(", ")
+            ),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-top-tipped-posts", params.limit)?;
+
+    // Use the API handler to get the top tipped posts
+    match app_state
+        .api_handlers
+        .get_top_tipped_posts(&requester_pubkey, &time_window, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to TopTippedPostsResponse
+            match serde_json::from_str::<TopTippedPostsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse top tipped posts response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_PARAMETER" | "INVALID_USER_KEY"
+                        | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_feature_flags(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetFeatureFlagsQuery>,
+) -> Result<Json<FeatureFlagsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Use the API handler to evaluate the flags for the requester
+    match app_state
+        .api_handlers
+        .get_feature_flags(params.requester_pubkey.as_deref())
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to FeatureFlagsResponse
+            match serde_json::from_str::<FeatureFlagsResponse>(&response_json) {
+                Ok(flags_response) => Ok(Json(flags_response)),
+                Err(err) => {
+                    log_error!("Failed to parse feature flags response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_create_erasure_request(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<CreateErasureRequestQuery>,
+) -> Result<(StatusCode, Json<ErasureRequestResponse>), (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "erasure").await?;
+
+    // Check if pubkey parameter is provided
+    let pubkey = match params.pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: pubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to record the erasure request
+    match app_state
+        .api_handlers
+        .create_erasure_request(&pubkey, params.reason.as_deref())
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ErasureRequestResponse
+            match serde_json::from_str::<ErasureRequestResponse>(&response_json) {
+                Ok(erasure_response) => {
+                    log_info!(
+                        "Erasure request {} recorded for {}",
+                        erasure_response.id,
+                        erasure_response.subject_public_key
+                    );
+                    Ok((StatusCode::CREATED, Json(erasure_response)))
+                }
+                Err(err) => {
+                    log_error!("Failed to parse erasure request response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_erasure_request(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetErasureRequestQuery>,
+) -> Result<Json<ErasureRequestResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "erasure").await?;
+
+    // Check if id parameter is provided
+    let request_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to get the erasure request status
+    match app_state
+        .api_handlers
+        .get_erasure_request(&request_id)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ErasureRequestResponse
+            match serde_json::from_str::<ErasureRequestResponse>(&response_json) {
+                Ok(erasure_response) => Ok(Json(erasure_response)),
+                Err(err) => {
+                    log_error!("Failed to parse erasure request response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "MISSING_PARAMETER" | "INVALID_REQUEST_ID" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_duplicate_contents(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetDuplicateContentsQuery>,
+) -> Result<Json<DuplicateContentsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "content_hashes").await?;
+
+    // Parse and validate time_window parameter (default: "24h")
+    let time_window = params.time_window.unwrap_or_else(|| "24h".to_string());
+
+    // Validate time_window values
+    let valid_windows = ["1h", "6h", "24h", "7d", "30d"];
+    if !valid_windows.contains(&time_window.as_str()) {
+        let error = ApiError {
+            error: format!(
+                "Invalid timeWindow parameter. Must be one of: {}",
+                valid_windows.join(", ")
+            ),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    // Default: a message from 5 different pubkeys
+    let min_senders = params.min_senders.unwrap_or(5);
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "duplicate-contents", params.limit)?;
+
+    // Use the API handler to get the duplicated messages
+    match app_state
+        .api_handlers
+        .get_duplicate_contents(&time_window, min_senders, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to DuplicateContentsResponse
+            match serde_json::from_str::<DuplicateContentsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse duplicate contents response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_PARAMETER" | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_storage_stats(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<StorageStatsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "storage_stats").await?;
+
+    // Use the API handler to measure the K tables
+    match app_state.api_handlers.get_storage_stats().await {
+        Ok(response_json) => {
+            // Parse the JSON response back to StorageStatsResponse
+            match serde_json::from_str::<StorageStatsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse storage stats response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response (database and serialization errors only)
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(api_error))),
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_slow_queries(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetSlowQueriesQuery>,
+) -> Result<Json<SlowQueriesResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "slow-queries", params.limit)?;
+
+    // Use the API handler to read pg_stat_statements
+    match app_state.api_handlers.get_slow_queries(limit).await {
+        Ok(response_json) => {
+            // Parse the JSON response back to SlowQueriesResponse
+            match serde_json::from_str::<SlowQueriesResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse slow queries response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "FEATURE_UNAVAILABLE" => StatusCode::SERVICE_UNAVAILABLE,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or(0)
+}
+
+async fn handle_get_request_analytics(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetRequestAnalyticsQuery>,
+) -> Result<Json<RequestAnalyticsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Only routed with --request-analytics-hours
+    let Some(analytics) = &app_state.request_analytics else {
+        return Err(internal_error_response());
+    };
+
+    // Default: every hour kept
+    let hours = params
+        .hours
+        .map_or(analytics.retention_hours(), u64::from)
+        .min(analytics.retention_hours());
+    if hours == 0 {
+        let error = ApiError {
+            error: "Invalid hours parameter. Must be at least 1".to_string(),
+            code: "INVALID_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+    // Default and maximum number of endpoints and contents per hour are configured per endpoint
+    let limit = resolve_limit(&app_state, "request-analytics", params.limit)?;
+
+    let now = unix_now();
+    Ok(Json(RequestAnalyticsResponse {
+        generated_at: now * 1000,
+        retention_hours: analytics.retention_hours(),
+        hours: analytics.report(hours, limit as usize, now),
+    }))
+}
+
+async fn handle_get_impersonation_flags(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetImpersonationFlagsQuery>,
+) -> Result<Json<ImpersonationFlagsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "impersonation_flags").await?;
+
+    // Default: flags waiting for review
+    let status = params.status.unwrap_or_else(|| "pending".to_string());
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "impersonation-flags", params.limit)?;
+
+    // Use the API handler to list the flags
+    match app_state
+        .api_handlers
+        .get_impersonation_flags(&status, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ImpersonationFlagsResponse
+            match serde_json::from_str::<ImpersonationFlagsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse impersonation flags response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_PARAMETER" | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_nickname_history(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetNicknameHistoryQuery>,
+) -> Result<Json<NicknameHistoryResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "nickname_history").await?;
+
+    // Check if nickname parameter is provided
+    let nickname = match params.nickname {
+        Some(nickname) => nickname,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: nickname".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "nickname-history", params.limit)?;
+
+    // Use the API handler to search the profile history
+    match app_state
+        .api_handlers
+        .get_nickname_history(&nickname, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to NicknameHistoryResponse
+            match serde_json::from_str::<NicknameHistoryResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse nickname history response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_PARAMETER" | "INVALID_LIMIT" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_review_impersonation_flag(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ReviewImpersonationFlagQuery>,
+) -> Result<Json<ImpersonationFlag>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "impersonation_flags").await?;
+
+    // Check if id parameter is provided
+    let flag_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if status parameter is provided
+    let status = match params.status {
+        Some(status) => status,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: status".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to record the review
+    match app_state
+        .api_handlers
+        .review_impersonation_flag(&flag_id, &status)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ImpersonationFlag
+            match serde_json::from_str::<ImpersonationFlag>(&response_json) {
+                Ok(flag) => {
+                    log_info!("Impersonation flag {} marked {}", flag.id, flag.status);
+                    Ok(Json(flag))
+                }
+                Err(err) => {
+                    log_error!("Failed to parse impersonation flag response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_REQUEST_ID" | "INVALID_PARAMETER" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_featured(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetFeaturedQuery>,
+) -> Result<Json<FeaturedPostsResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "featured").await?;
+
+    // Check if requesterPubkey parameter is provided
+    let requester_pubkey = match params.requester_pubkey {
+        Some(pubkey) => pubkey,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: requesterPubkey".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "get-featured", params.limit)?;
+
+    // Use the API handler to get the featured posts
+    match app_state
+        .api_handlers
+        .get_featured(&requester_pubkey, limit)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to FeaturedPostsResponse
+            match serde_json::from_str::<FeaturedPostsResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse featured posts response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_USER_KEY" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_featured_entries(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetFeaturedEntriesQuery>,
+) -> Result<Json<FeaturedEntriesResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "featured").await?;
+
+    // Default and maximum page size are configured per endpoint
+    let limit = resolve_limit(&app_state, "featured", params.limit)?;
+
+    // Use the API handler to list the featured entries
+    match app_state.api_handlers.get_featured_entries(limit).await {
+        Ok(response_json) => {
+            // Parse the JSON response back to FeaturedEntriesResponse
+            match serde_json::from_str::<FeaturedEntriesResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse featured entries response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response (database and serialization errors only)
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(api_error))),
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_set_featured(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<SetFeaturedQuery>,
+) -> Result<Json<FeaturedEntry>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "featured").await?;
+
+    // Check if id parameter is provided
+    let content_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to feature the content
+    match app_state
+        .api_handlers
+        .set_featured(&content_id, params.position, params.note.as_deref())
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to FeaturedEntry
+            match serde_json::from_str::<FeaturedEntry>(&response_json) {
+                Ok(entry) => {
+                    log_info!(
+                        "Content {} featured at position {}",
+                        entry.content_id,
+                        entry.position
+                    );
+                    Ok(Json(entry))
+                }
+                Err(err) => {
+                    log_error!("Failed to parse featured entry response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_POST_ID" | "INVALID_PARAMETER" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+/// Run a payload through the processor's parser and validation without persisting it. A payload
+/// that would not be indexed is reported as an error: 400 when it does not parse, 422 when
+/// validation skips it
+async fn handle_simulate_payload(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<SimulatePayloadQuery>,
+) -> Result<Json<PayloadSimulationResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+
+    // Only routed with k-indexer --simulator
+    let Some(simulator) = &app_state.payload_simulator else {
+        return Err(internal_error_response());
+    };
+
+    let Some(payload) = params.payload else {
+        let error = ApiError {
+            error: "Missing required parameter: payload".to_string(),
+            code: "MISSING_PARAMETER".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    };
+
+    match simulator
+        .simulate(&payload, params.transaction_id, params.block_time)
+        .await
+    {
+        Ok(Simulation::Indexed(simulation)) => Ok(Json(simulation)),
+        Ok(Simulation::Invalid(reason)) => {
+            let error = ApiError {
+                error: reason,
+                code: "INVALID_PAYLOAD".to_string(),
+            };
+            Err((StatusCode::BAD_REQUEST, Json(error)))
+        }
+        Ok(Simulation::Rejected(reason)) => {
+            let error = ApiError {
+                error: reason,
+                code: "PAYLOAD_REJECTED".to_string(),
+            };
+            Err((StatusCode::UNPROCESSABLE_ENTITY, Json(error)))
+        }
+        Err(err) => {
+            log_error!("Failed to simulate payload: {:#}", err);
+            Err(internal_error_response())
+        }
+    }
+}
+
+async fn handle_delete_featured(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<DeleteFeaturedQuery>,
+) -> Result<Json<FeaturedEntry>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "featured").await?;
+
+    // Check if id parameter is provided
+    let content_id = match params.id {
+        Some(id) => id,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: id".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Use the API handler to remove the featured entry
+    match app_state.api_handlers.delete_featured(&content_id).await {
+        Ok(response_json) => {
+            // Parse the JSON response back to FeaturedEntry
+            match serde_json::from_str::<FeaturedEntry>(&response_json) {
+                Ok(entry) => {
+                    log_info!("Content {} no longer featured", entry.content_id);
+                    Ok(Json(entry))
+                }
+                Err(err) => {
+                    log_error!("Failed to parse featured entry response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_POST_ID" => StatusCode::BAD_REQUEST,
+                        "NOT_FOUND" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+async fn handle_get_profile_diff(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<GetProfileDiffQuery>,
+) -> Result<Json<ProfileDiffResponse>, (StatusCode, Json<ApiError>)> {
+    // Check rate limit first
+    check_rate_limit(&app_state, addr).await?;
+    check_schema_feature(&app_state, "profile_history").await?;
+
+    // Check if user parameter is provided
+    let user_pubkey = match params.user {
+        Some(user) => user,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: user".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    // Check if from parameter is provided
+    let from = match params.from {
+        Some(from) => from,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: from".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    let to = params.to.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_millis() as u64)
+            .unwrap_or(0)
+    });
+
+    // Use the API handler to compare the profile at both times
+    match app_state
+        .api_handlers
+        .get_profile_diff(&user_pubkey, from, to)
+        .await
+    {
+        Ok(response_json) => {
+            // Parse the JSON response back to ProfileDiffResponse
+            match serde_json::from_str::<ProfileDiffResponse>(&response_json) {
+                Ok(response) => Ok(Json(response)),
+                Err(err) => {
+                    log_error!("Failed to parse profile diff response: {}", err);
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+        Err(error_json) => {
+            // Parse the error response
+            match serde_json::from_str::<ApiError>(&error_json) {
+                Ok(api_error) => {
+                    let status_code = match api_error.code.as_str() {
+                        "INVALID_USER_KEY" | "INVALID_PARAMETER" => StatusCode::BAD_REQUEST,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+                    Err((status_code, Json(api_error)))
+                }
+                Err(_) => {
+                    let error = ApiError {
+                        error: "Internal server error".to_string(),
+                        code: "INTERNAL_ERROR".to_string(),
+                    };
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)))
+                }
+            }
+        }
+    }
+}
+
+/// Requester of reads made on behalf of nobody (ActivityPub, feeds, previews):
+/// no personal block lists apply
+const ANONYMOUS_REQUESTER: &str = "";
+
+/// Whether a profile has an image, from its inline image column: blob storage clears the
+/// inline image, so only the database mode can tell for sure that there is none
+fn profile_image_available(app_state: &AppState, inline_image: Option<&str>) -> bool {
+    inline_image.is_some_and(|image| !image.is_empty())
+        || !matches!(
+            app_state.server_config.image_storage,
+            ImageStorageConfig::Database
+        )
+}
+
+/// 500 response of handlers querying the database directly
+fn internal_error_response() -> (StatusCode, Json<ApiError>) {
+    let error = ApiError {
+        error: "Internal server error".to_string(),
+        code: "INTERNAL_ERROR".to_string(),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error))
+}
+
+// ActivityPub (--activitypub-base-url): K users as read-only actors
+
+#[derive(Debug, Deserialize)]
+struct WebfingerQuery {
+    resource: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivityPubOutboxQuery {
+    page: Option<String>,
+    before: Option<String>,
+}
+
+fn activity_json(content_type: &'static str, document: serde_json::Value) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], document.to_string()).into_response()
+}
+
+fn activitypub_not_found(what: &str) -> (StatusCode, Json<ApiError>) {
+    let error = ApiError {
+        error: format!("{} not found", what),
+        code: "NOT_FOUND".to_string(),
+    };
+    (StatusCode::NOT_FOUND, Json(error))
+}
+
+/// Base URL and key of the ActivityPub actors (the /ap routes are only registered with both)
+fn activitypub_context(app_state: &AppState) -> (&str, &HttpSignatures) {
+    let base_url = app_state
+        .server_config
+        .activitypub
+        .as_ref()
+        .map(|config| config.base_url.as_str())
+        .unwrap_or_default();
+    let signatures = app_state
+        .http_signatures
+        .as_ref()
+        .expect("ActivityPub routes registered without an instance key");
+    (base_url, signatures)
+}
+
+fn validate_activitypub_user(pubkey: &str) -> Result<String, (StatusCode, Json<ApiError>)> {
+    if !activitypub::is_user_pubkey(pubkey) {
+        let error = ApiError {
+            error:
+                "Invalid user public key format. Must be 66 hex characters starting with 02 or 03."
+                    .to_string(),
+            code: "INVALID_USER_KEY".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+    Ok(pubkey.to_ascii_lowercase())
+}
+
+/// /ap routes; actors, outboxes and notes require HTTP signatures with
+/// --activitypub-require-signatures, the instance actor never does (it signs our own key fetches)
+fn create_activitypub_router(
+    app_state: Arc<AppState>,
+    require_signatures: bool,
+) -> Router<Arc<AppState>> {
+    let mut router = Router::new()
+        .route("/users/:pubkey", get(handle_activitypub_user))
+        .route("/users/:pubkey/outbox", get(handle_activitypub_outbox))
+        .route(
+            "/users/:pubkey/posts/:content_id",
+            get(handle_activitypub_note),
+        );
+
+    if require_signatures {
+        router = router.route_layer(middleware::from_fn_with_state(
+            app_state,
+            activitypub_signature_guard,
+        ));
+    }
+
+    router
+        .route("/users/:pubkey/inbox", post(handle_activitypub_inbox))
+        .route("/actor", get(handle_activitypub_instance_actor))
+        .route("/actor/inbox", post(handle_activitypub_inbox))
+}
+
+/// Authorized fetch: reject requests without a valid HTTP signature of a remote actor
+async fn activitypub_signature_guard(
+    State(app_state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (_, signatures) = activitypub_context(&app_state);
+
+    // Routes are nested under /ap: the signature covers the path as sent by the client
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|original| original.0.clone())
+        .unwrap_or_else(|| request.uri().clone());
+    let path_and_query = uri
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or("/");
+
+    if let Err(e) = signatures
+        .verify(request.method(), path_and_query, request.headers())
+        .await
+    {
+        let error = ApiError {
+            error: format!("HTTP signature required: {}", e),
+            code: "INVALID_SIGNATURE".to_string(),
+        };
+        return (StatusCode::UNAUTHORIZED, Json(error)).into_response();
+    }
+
+    next.run(request).await
+}
+
+async fn handle_webfinger(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<WebfingerQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let (base_url, _) = activitypub_context(&app_state);
+    let resource = match params.resource {
+        Some(resource) => resource,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: resource".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    let pubkey = activitypub::webfinger_pubkey(base_url, &resource)
+        .ok_or_else(|| activitypub_not_found("Resource"))?;
+
+    Ok(activity_json(
+        activitypub::JRD_JSON,
+        activitypub::webfinger(base_url, &pubkey),
+    ))
+}
+
+async fn handle_activitypub_instance_actor(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let (base_url, signatures) = activitypub_context(&app_state);
+    Ok(activity_json(
+        activitypub::ACTIVITY_JSON,
+        activitypub::instance_actor(base_url, signatures.public_key_pem()),
+    ))
+}
+
+async fn handle_activitypub_user(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    AxumPath(pubkey): AxumPath<String>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let (base_url, signatures) = activitypub_context(&app_state);
+    let pubkey = validate_activitypub_user(&pubkey)?;
+
+    let details = app_state
+        .db
+        .get_user_details(&pubkey, ANONYMOUS_REQUESTER)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get ActivityPub actor: {}", e);
+            internal_error_response()
+        })?;
+
+    let actor = match details {
+        Some((broadcast, ..)) => {
+            let has_profile_image = profile_image_available(
+                &app_state,
+                broadcast.base64_encoded_profile_image.as_deref(),
+            );
+            activitypub::person(
+                base_url,
+                &activitypub::ActorProfile {
+                    pubkey: &pubkey,
+                    base64_nickname: &broadcast.base64_encoded_nickname,
+                    base64_message: &broadcast.base64_encoded_message,
+                    block_time: broadcast.block_time,
+                    has_profile_image,
+                },
+                signatures.public_key_pem(),
+            )
+        }
+        None => {
+            // Users who posted without ever broadcasting a profile still get a bare actor
+            let posts = app_state
+                .db
+                .get_posts_by_user(
+                    &pubkey,
+                    ANONYMOUS_REQUESTER,
+                    QueryOptions {
+                        limit: Some(1),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(|e| {
+                    log_error!("Failed to get ActivityPub actor posts: {}", e);
+                    internal_error_response()
+                })?;
+            let first_post = posts
+                .items
+                .first()
+                .ok_or_else(|| activitypub_not_found("User"))?;
+            activitypub::person(
+                base_url,
+                &activitypub::ActorProfile {
+                    pubkey: &pubkey,
+                    base64_nickname: "",
+                    base64_message: "",
+                    block_time: first_post.block_time,
+                    has_profile_image: false,
+                },
+                signatures.public_key_pem(),
+            )
+        }
+    };
+
+    Ok(activity_json(activitypub::ACTIVITY_JSON, actor))
+}
+
+async fn handle_activitypub_outbox(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    AxumPath(pubkey): AxumPath<String>,
+    Query(params): Query<ActivityPubOutboxQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let (base_url, _) = activitypub_context(&app_state);
+    let pubkey = validate_activitypub_user(&pubkey)?;
+
+    // The collection itself only links its first page
+    if params.page.is_none() {
+        return Ok(activity_json(
+            activitypub::ACTIVITY_JSON,
+            activitypub::outbox(base_url, &pubkey),
+        ));
+    }
+
+    let limit = resolve_limit(&app_state, "ap-outbox", None)?;
+    let posts = app_state
+        .db
+        .get_posts_by_user(
+            &pubkey,
+            ANONYMOUS_REQUESTER,
+            QueryOptions {
+                limit: Some(limit as u64),
+                before: params.before.clone(),
+                after: None,
+                sort_descending: true,
+            },
+        )
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get ActivityPub outbox: {}", e);
+            internal_error_response()
+        })?;
+
+    let items = posts
+        .items
+        .iter()
+        .map(|post| activitypub::create_activity(base_url, &note_source(post)))
+        .collect();
+    let next_cursor = posts
+        .pagination
+        .next_cursor
+        .as_deref()
+        .filter(|_| posts.pagination.has_more);
+
+    Ok(activity_json(
+        activitypub::ACTIVITY_JSON,
+        activitypub::outbox_page(
+            base_url,
+            &pubkey,
+            params.before.as_deref(),
+            items,
+            next_cursor,
+        ),
+    ))
+}
+
+async fn handle_activitypub_note(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    AxumPath((pubkey, content_id)): AxumPath<(String, String)>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let (base_url, _) = activitypub_context(&app_state);
+    let pubkey = validate_activitypub_user(&pubkey)?;
+    if content_id.len() != 64 || !content_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        let error = ApiError {
+            error: "Invalid post ID format. Must be 64 hex characters.".to_string(),
+            code: "INVALID_POST_ID".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+
+    let content = app_state
+        .db
+        .get_content_by_id(&content_id.to_ascii_lowercase(), ANONYMOUS_REQUESTER)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get ActivityPub note: {}", e);
+            internal_error_response()
+        })?;
+
+    // Only posts and quotes are published; replies and votes stay on K
+    match content {
+        Some((ContentRecord::Post(post), _)) if post.sender_pubkey == pubkey => Ok(activity_json(
+            activitypub::ACTIVITY_JSON,
+            activitypub::note_document(base_url, &note_source(&post)),
+        )),
+        _ => Err(activitypub_not_found("Post")),
+    }
+}
+
+/// Actors are read-only: deliveries (Follow included) are refused rather than queued
+async fn handle_activitypub_inbox() -> (StatusCode, Json<ApiError>) {
+    let error = ApiError {
+        error: "ActivityPub actors of this indexer are read-only and accept no activities"
+            .to_string(),
+        code: "READ_ONLY".to_string(),
+    };
+    (StatusCode::METHOD_NOT_ALLOWED, Json(error))
+}
+
+fn note_source(post: &KPostRecord) -> activitypub::NoteSource<'_> {
+    activitypub::NoteSource {
+        content_id: &post.transaction_id,
+        sender_pubkey: &post.sender_pubkey,
+        base64_message: &post.base64_encoded_message,
+        block_time: post.block_time,
+        mentioned_pubkeys: &post.mentioned_pubkeys,
+        quoted: post
+            .referenced_content_id
+            .as_deref()
+            .zip(post.referenced_sender_pubkey.as_deref()),
+    }
+}
+
+// Atom feeds (--feed-client-url)
+
+#[derive(Debug, Deserialize)]
+struct FeedQuery {
+    #[serde(rename = "requesterPubkey")]
+    requester_pubkey: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Feed settings (the /feeds routes are only registered with --feed-client-url)
+fn feed_config(app_state: &AppState) -> &FeedConfig {
+    app_state
+        .server_config
+        .feeds
+        .as_ref()
+        .expect("Feed routes registered without --feed-client-url")
+}
+
+/// Optional requesterPubkey of a feed: the requester's blocks apply, anonymous otherwise
+fn feed_requester(
+    requester_pubkey: Option<String>,
+) -> Result<String, (StatusCode, Json<ApiError>)> {
+    match requester_pubkey {
+        Some(pubkey) if !activitypub::is_user_pubkey(&pubkey) => {
+            let error = ApiError {
+                error: "Invalid requester public key format. Must be 66 hex characters starting with 02 or 03.".to_string(),
+                code: "INVALID_USER_KEY".to_string(),
+            };
+            Err((StatusCode::BAD_REQUEST, Json(error)))
+        }
+        Some(pubkey) => Ok(pubkey.to_ascii_lowercase()),
+        None => Ok(ANONYMOUS_REQUESTER.to_string()),
+    }
+}
+
+fn feed_entries(config: &FeedConfig, posts: &[KPostRecord]) -> Vec<atom_feed::FeedEntry> {
+    posts
+        .iter()
+        .map(|post| {
+            let nickname = post
+                .user_nickname
+                .as_deref()
+                .map(activitypub::decode_text)
+                .filter(|nickname| !nickname.trim().is_empty());
+            atom_feed::FeedEntry {
+                link: config.post_link(&post.transaction_id),
+                author_name: nickname.unwrap_or_else(|| post.sender_pubkey.clone()),
+                author_link: config.user_link(&post.sender_pubkey),
+                text: activitypub::decode_text(&post.base64_encoded_message),
+                block_time: post.block_time,
+            }
+        })
+        .collect()
+}
+
+fn feed_response(xml: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, atom_feed::ATOM_XML),
+            (header::CACHE_CONTROL, "public, max-age=60"),
+        ],
+        xml,
+    )
+        .into_response()
+}
+
+async fn handle_user_feed(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    AxumPath(file): AxumPath<String>,
+    Query(params): Query<FeedQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let config = feed_config(&app_state);
+    let pubkey = match file.strip_suffix(".xml") {
+        Some(pubkey) if activitypub::is_user_pubkey(pubkey) => pubkey.to_ascii_lowercase(),
+        _ => {
+            let error = ApiError {
+                error: "Invalid user feed. Must be /feeds/user/<66 hex characters public key>.xml"
+                    .to_string(),
+                code: "INVALID_USER_KEY".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+    let requester_pubkey = feed_requester(params.requester_pubkey)?;
+    let limit = resolve_limit(&app_state, "feeds", params.limit)?;
+
+    // Same query as get-posts, newest first
+    let posts = app_state
+        .db
+        .get_posts_by_user(
+            &pubkey,
+            &requester_pubkey,
+            QueryOptions {
+                limit: Some(limit as u64),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get user feed posts: {}", e);
+            internal_error_response()
+        })?;
+
+    let nickname = posts
+        .items
+        .iter()
+        .find_map(|post| post.user_nickname.as_deref())
+        .map(activitypub::decode_text)
+        .filter(|nickname| !nickname.trim().is_empty())
+        .unwrap_or_else(|| pubkey.clone());
+    let link = config.user_link(&pubkey);
+    let self_path = format!("/feeds/user/{}.xml", pubkey);
+    let title = format!("{} on K", nickname);
+
+    Ok(feed_response(atom_feed::render(
+        &atom_feed::Feed {
+            link: &link,
+            self_path: &self_path,
+            title: &title,
+        },
+        &feed_entries(config, &posts.items),
+    )))
+}
+
+async fn handle_watching_feed(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<FeedQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let config = feed_config(&app_state);
+    let requester_pubkey = feed_requester(params.requester_pubkey)?;
+    let limit = resolve_limit(&app_state, "feeds", params.limit)?;
+
+    // Same query as get-posts-watching, newest first
+    let posts = app_state
+        .db
+        .get_all_posts(
+            &requester_pubkey,
+            QueryOptions {
+                limit: Some(limit as u64),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get watching feed posts: {}", e);
+            internal_error_response()
+        })?;
+
+    Ok(feed_response(atom_feed::render(
+        &atom_feed::Feed {
+            link: &config.client_url,
+            self_path: "/feeds/watching.xml",
+            title: "Latest posts on K",
+        },
+        &feed_entries(config, &posts.items),
+    )))
+}
+
+// Link previews and oEmbed (--preview-base-url)
+
+/// Recent posts listed in /sitemap.xml
+const SITEMAP_MAX_URLS: u64 = 1000;
+
+/// Public URL of the webserver (the preview routes are only registered with --preview-base-url)
+fn preview_base_url(app_state: &AppState) -> &str {
+    app_state
+        .server_config
+        .preview_base_url
+        .as_deref()
+        .expect("Preview routes registered without --preview-base-url")
+}
+
+/// A post, quote or reply shown by the preview page and oEmbed
+struct SharedPost {
+    txid: String,
+    sender_pubkey: String,
+    author_name: String,
+    text: String,
+    block_time: u64,
+    image_url: Option<String>,
+    is_reply: bool,
+}
+
+impl SharedPost {
+    fn preview<'a>(
+        &'a self,
+        url: &'a str,
+        client_link: Option<&'a str>,
+        explorer_links: Option<&'a ExplorerLinks>,
+        oembed_endpoint: &'a str,
+    ) -> post_preview::PostPreview<'a> {
+        post_preview::PostPreview {
+            url,
+            author_name: &self.author_name,
+            text: &self.text,
+            block_time: self.block_time,
+            image_url: self.image_url.as_deref(),
+            client_link,
+            explorer_links,
+            is_reply: self.is_reply,
+            oembed_endpoint,
+        }
+    }
+}
+
+/// Load a post, quote or reply through the get-post-details query
+async fn load_shared_post(
+    app_state: &AppState,
+    txid: &str,
+) -> Result<SharedPost, (StatusCode, Json<ApiError>)> {
+    if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+        let error = ApiError {
+            error: "Invalid post ID format. Must be 64 hex characters.".to_string(),
+            code: "INVALID_POST_ID".to_string(),
+        };
+        return Err((StatusCode::BAD_REQUEST, Json(error)));
+    }
+    let txid = txid.to_ascii_lowercase();
+
+    let content = app_state
+        .db
+        .get_content_by_id(&txid, ANONYMOUS_REQUESTER)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get shared post: {}", e);
+            internal_error_response()
+        })?;
+
+    // (sender, message, block time, nickname, inline image, reply)
+    let (sender_pubkey, message, block_time, nickname, inline_image, is_reply) = match content {
+        Some((ContentRecord::Post(post), _)) => (
+            post.sender_pubkey,
+            post.base64_encoded_message,
+            post.block_time,
+            post.user_nickname,
+            post.user_profile_image,
+            false,
+        ),
+        Some((ContentRecord::Reply(reply), _)) => (
+            reply.sender_pubkey,
+            reply.base64_encoded_message,
+            reply.block_time,
+            reply.user_nickname,
+            reply.user_profile_image,
+            true,
+        ),
+        _ => {
+            let error = ApiError {
+                error: "Post not found".to_string(),
+                code: "NOT_FOUND".to_string(),
+            };
+            return Err((StatusCode::NOT_FOUND, Json(error)));
+        }
+    };
+
+    let author_name = nickname
+        .as_deref()
+        .map(activitypub::decode_text)
+        .filter(|nickname| !nickname.trim().is_empty())
+        .unwrap_or_else(|| sender_pubkey.clone());
+    // Users without a profile broadcast have no image at all
+    let image_url = (nickname.is_some()
+        && profile_image_available(app_state, inline_image.as_deref()))
+    .then(|| {
+        format!(
+            "{}/get-profile-image?user={}",
+            preview_base_url(app_state),
+            sender_pubkey
+        )
+    });
+
+    Ok(SharedPost {
+        txid,
+        sender_pubkey,
+        author_name,
+        text: activitypub::decode_text(&message),
+        block_time,
+        image_url,
+        is_reply,
+    })
+}
+
+async fn handle_post_preview(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    AxumPath(txid): AxumPath<String>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    let post = load_shared_post(&app_state, &txid).await?;
+    let base_url = preview_base_url(&app_state);
+    let url = format!("{}/p/{}", base_url, post.txid);
+    let oembed_endpoint = format!("{}/oembed", base_url);
+    let client_link = app_state
+        .server_config
+        .feeds
+        .as_ref()
+        .map(|feeds| feeds.post_link(&post.txid));
+    let explorer_links = explorer_links(&app_state, &post.txid).await;
+
+    let html = post_preview::render_page(&post.preview(
+        &url,
+        client_link.as_deref(),
+        explorer_links.as_ref(),
+        &oembed_endpoint,
+    ));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, post_preview::HTML),
+            (header::CACHE_CONTROL, "public, max-age=300"),
+        ],
+        html,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct OembedQuery {
+    url: Option<String>,
+    format: Option<String>,
+    maxwidth: Option<u32>,
+}
+
+/// oEmbed provider for preview page URLs and client post links (--feed-client-url)
+async fn handle_oembed(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<OembedQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    // The spec answers unsupported formats with 501
+    if params
+        .format
+        .as_deref()
+        .is_some_and(|format| format != "json")
+    {
+        let error = ApiError {
+            error: "Only the json format is supported".to_string(),
+            code: "UNSUPPORTED_FORMAT".to_string(),
+        };
+        return Err((StatusCode::NOT_IMPLEMENTED, Json(error)));
+    }
+
+    let url = match params.url {
+        Some(url) => url,
+        None => {
+            let error = ApiError {
+                error: "Missing required parameter: url".to_string(),
+                code: "MISSING_PARAMETER".to_string(),
+            };
+            return Err((StatusCode::BAD_REQUEST, Json(error)));
+        }
+    };
+
+    let base_url = preview_base_url(&app_state);
+    let feeds = app_state.server_config.feeds.as_ref();
+    let mut templates = vec![format!("{}/p/{{id}}", base_url)];
+    if let Some(feeds) = feeds {
+        templates.push(feeds.post_link("{id}"));
+    }
+    let txid = post_preview::post_id_from_url(&url, &templates).ok_or_else(|| {
+        let error = ApiError {
+            error: "Not a K post URL".to_string(),
+            code: "NOT_FOUND".to_string(),
+        };
+        (StatusCode::NOT_FOUND, Json(error))
+    })?;
+
+    let post = load_shared_post(&app_state, txid).await?;
+    let page_url = format!("{}/p/{}", base_url, post.txid);
+    let oembed_endpoint = format!("{}/oembed", base_url);
+    let author_url = match feeds {
+        Some(feeds) => feeds.user_link(&post.sender_pubkey),
+        None => base_url.to_string(),
+    };
+    let width = params
+        .maxwidth
+        .map_or(post_preview::EMBED_WIDTH, |maxwidth| {
+            maxwidth.min(post_preview::EMBED_WIDTH)
+        });
+
+    let response = post_preview::oembed(
+        &post.preview(&page_url, None, None, &oembed_endpoint),
+        base_url,
+        &author_url,
+        width,
+    );
+
+    Ok((
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(response),
+    )
+        .into_response())
+}
+
+async fn handle_sitemap(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    check_rate_limit(&app_state, addr).await?;
+
+    // Same query as get-posts-watching, newest first
+    let posts = app_state
+        .db
+        .get_all_posts(
+            ANONYMOUS_REQUESTER,
+            QueryOptions {
+                limit: Some(SITEMAP_MAX_URLS),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get sitemap posts: {}", e);
+            internal_error_response()
+        })?;
+
+    let base_url = preview_base_url(&app_state);
+    let urls: Vec<(String, u64)> = posts
+        .items
+        .iter()
+        .map(|post| {
+            (
+                format!("{}/p/{}", base_url, post.transaction_id),
+                post.block_time,
+            )
+        })
+        .collect();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, post_preview::SITEMAP_XML),
+            (header::CACHE_CONTROL, "public, max-age=300"),
+        ],
+        post_preview::render_sitemap(&urls),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_include_replies() {
+        assert_eq!(validate_include_replies(None).unwrap(), 0);
+        assert_eq!(validate_include_replies(Some(0)).unwrap(), 0);
+        assert_eq!(
+            validate_include_replies(Some(MAX_INCLUDE_REPLIES)).unwrap(),
+            MAX_INCLUDE_REPLIES
+        );
+
+        let (status, Json(error)) =
+            validate_include_replies(Some(MAX_INCLUDE_REPLIES + 1)).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, "INVALID_INCLUDE_REPLIES");
+    }
+
+    #[test]
+    fn test_parse_post_ranking() {
+        assert_eq!(parse_post_ranking(None).unwrap(), None);
+        assert_eq!(parse_post_ranking(Some("latest")).unwrap(), None);
+        assert_eq!(
+            parse_post_ranking(Some("top")).unwrap(),
+            Some(PostRanking::Top)
+        );
+        assert_eq!(
+            parse_post_ranking(Some("hot")).unwrap(),
+            Some(PostRanking::Hot)
+        );
+
+        for sort in ["", "TOP", "trending"] {
+            let (status, Json(error)) = parse_post_ranking(Some(sort)).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(error.code, "INVALID_PARAMETER");
+        }
+    }
+
+    #[test]
+    fn test_is_read_request() {
+        assert!(is_read_request(&Method::GET, "/v1/get-posts"));
+        assert!(is_read_request(&Method::HEAD, "/health"));
+        assert!(is_read_request(&Method::OPTIONS, "/v1/track-view"));
+        assert!(is_read_request(&Method::POST, "/graphql"));
+        assert!(is_read_request(&Method::POST, "/v1/graphql"));
+
+        assert!(!is_read_request(&Method::POST, "/v1/track-view"));
+        assert!(!is_read_request(&Method::POST, "/admin/featured"));
+        assert!(!is_read_request(&Method::DELETE, "/admin/featured"));
+    }
+
+    #[test]
+    fn test_admin_token_matches() {
+        let headers = |authorization: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, authorization.parse().unwrap());
+            headers
+        };
+
+        assert!(admin_token_matches(
+            Some("secret"),
+            &headers("Bearer secret")
+        ));
+
+        assert!(!admin_token_matches(Some("secret"), &HeaderMap::new()));
+        assert!(!admin_token_matches(
+            Some("secret"),
+            &headers("Bearer secreT")
+        ));
+        assert!(!admin_token_matches(
+            Some("secret"),
+            &headers("Bearer secret2")
+        ));
+        assert!(!admin_token_matches(
+            Some("secret"),
+            &headers("Basic secret")
+        ));
+        assert!(!admin_token_matches(Some("secret"), &headers("secret")));
+        // Without a configured token the admin endpoints are closed
+        assert!(!admin_token_matches(None, &headers("Bearer secret")));
+        assert!(!admin_token_matches(None, &headers("Bearer ")));
+    }
+
+    #[test]
+    fn test_apply_page_sizes() {
+        let page_sizes = PageSizeLimits {
+            default: 20,
+            max: 50,
+        };
+        assert_eq!(apply_page_sizes(page_sizes, None).unwrap(), 20);
+        assert_eq!(apply_page_sizes(page_sizes, Some(1)).unwrap(), 1);
+        assert_eq!(apply_page_sizes(page_sizes, Some(50)).unwrap(), 50);
+
+        for limit in [0, 51] {
+            let (status, Json(error)) = apply_page_sizes(page_sizes, Some(limit)).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(error.code, "INVALID_LIMIT");
+            assert_eq!(error.error, "Limit parameter must be between 1 and 50");
+        }
+    }
+}